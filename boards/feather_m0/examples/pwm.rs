@@ -17,7 +17,7 @@ use hal::clock::GenericClockController;
 use hal::delay::Delay;
 use hal::ehal::{delay::DelayNs, pwm::SetDutyCycle};
 use hal::fugit::RateExtU32;
-use hal::pwm::Pwm3;
+use hal::pwm::{Pwm3, TC3Pinout};
 use pac::{CorePeripherals, Peripherals};
 
 #[entry]
@@ -34,13 +34,14 @@ fn main() -> ! {
     let mut delay = Delay::new(core.SYST, &mut clocks);
     let pins = bsp::Pins::new(peripherals.port);
 
-    let _d5: bsp::D5Pwm = pin_alias!(pins.d5_pwm).into();
+    let d5: bsp::D5Pwm = pin_alias!(pins.d5_pwm).into();
 
     let gclk0 = clocks.gclk0();
     let mut pwm3 = Pwm3::new(
         &clocks.tcc2_tc3(&gclk0).unwrap(),
         1.kHz(),
         peripherals.tc3,
+        TC3Pinout::Pa15(d5),
         &mut peripherals.pm,
     );
     let max_duty = pwm3.max_duty_cycle();
@@ -13,10 +13,11 @@ use bsp::hal;
 use bsp::entry;
 use hal::clock::GenericClockController;
 use hal::delay::Delay;
-use hal::gpio::*;
+use hal::ehal::pwm::SetDutyCycle;
 use hal::pac::{CorePeripherals, Peripherals};
 use hal::prelude::*;
-use hal::pwm::{Channel, Pwm0};
+use hal::pwm::tcc::Config;
+use hal::pwm::TCC0Pinout;
 
 #[entry]
 fn main() -> ! {
@@ -32,25 +33,29 @@ fn main() -> ! {
     let mut delay = Delay::new(core.SYST, &mut clocks);
     let pins = bsp::Pins::new(peripherals.port);
 
-    let _d1: Pin<_, AlternateF> = pins.d1.into_mode();
-    let _d14: Pin<_, AlternateF> = pins.d14.into_mode();
+    // WO[1] on d1 and WO[0] on d14
+    let pwm_pins = (TCC0Pinout::Pa5(pins.d1), TCC0Pinout::Pa4(pins.d14));
 
     let gclk0 = clocks.gclk0();
-    let mut pwm0 = Pwm0::new(
+    let mut tcc0 = Config::new(
         &clocks.tcc0(&gclk0).unwrap(),
-        1.kHz(),
         peripherals.tcc0,
+        pwm_pins,
         &mut peripherals.pm,
-    );
-    let max_duty = pwm0.get_max_duty();
+    )
+    .frequency(1.kHz())
+    .enable();
+    let max_duty = tcc0.channel(0).max_duty_cycle();
 
     loop {
-        pwm0.set_duty(Channel::_0, max_duty / 2);
-        pwm0.set_duty(Channel::_1, max_duty / 8);
+        // The embedded-hal spec requires that set_duty_cycle returns a Result.
+        // In our case, the function is infaillible so we can safely ignore the result.
+        let _ = tcc0.channel(0).set_duty_cycle(max_duty / 2);
+        let _ = tcc0.channel(1).set_duty_cycle(max_duty / 8);
         delay.delay_ms(1000u16);
 
-        pwm0.set_duty(Channel::_0, max_duty / 8);
-        pwm0.set_duty(Channel::_1, max_duty / 2);
+        let _ = tcc0.channel(0).set_duty_cycle(max_duty / 8);
+        let _ = tcc0.channel(1).set_duty_cycle(max_duty / 2);
         delay.delay_ms(1000u16);
     }
 }
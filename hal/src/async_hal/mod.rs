@@ -26,6 +26,8 @@
 //! * [`DMAC`](crate::dmac)
 //! * [`EIC`](crate::eic) (external GPIO interrupts)
 //! * [`Timers`](crate::timer)
+//! * [`USB`](crate::usb) (device, through `embassy-usb-driver`, with the `usb`
+//!   feature)
 //!
 //!  **Note**: The asynchronous APIs for the individual peripherals are provided
 //! in their respective modules. This module only deals with the generalities of
@@ -60,6 +62,7 @@
 //!
 //! * `SERCOMx: [SERCOMx_0, SERCOMx_1, SERCOMx_2, SERCOMx_OTHER]`
 //! * `DMAC: [DMAC_0, DMAC_1, DMAC_2, DMAC_OTHER]`
+//! * `USB: [USB_OTHER, USB_SOF_HSOF, USB_TRCPT0, USB_TRCPT1]`
 //!
 //! ## Complete example using the `feather_m0` BSP and the [Embassy executor](https://crates.io/crates/embassy-executor)
 //! ```no_run
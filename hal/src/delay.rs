@@ -1,6 +1,5 @@
 //! Delays
 
-use atsamd_hal_macros::hal_cfg;
 use cortex_m::peripheral::syst::SystClkSource;
 use cortex_m::peripheral::SYST;
 
@@ -9,11 +8,8 @@ use crate::ehal::delay::DelayNs;
 use crate::ehal_02;
 use crate::time::Hertz;
 
-#[hal_cfg("rtc-d5x")]
-use crate::typelevel::Increment;
-
-#[hal_cfg("rtc-d5x")]
 use crate::clock::v2::{gclk::Gclk0Id, Source};
+use crate::typelevel::Increment;
 
 /// System timer (SysTick) as a delay provider
 pub struct Delay {
@@ -32,7 +28,6 @@ impl Delay {
         }
     }
 
-    #[hal_cfg("rtc-d5x")]
     /// Configures the system timer (SysTick) as a delay provide, compatible
     /// with the V2 clocking API
     pub fn new_with_source<S>(mut syst: SYST, gclk0: S) -> (Self, S::Inc)
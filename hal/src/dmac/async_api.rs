@@ -4,7 +4,7 @@ use atsamd_hal_macros::hal_cfg;
 
 use crate::{
     async_hal::interrupts::{Handler, DMAC},
-    dmac::{waker::WAKERS, TriggerSource},
+    dmac::{sram, waker::WAKERS, TriggerSource},
     util::BitIter,
 };
 
@@ -32,23 +32,27 @@ impl Handler<DMAC> for InterruptHandler {
             for pend_channel in pending_interrupts {
                 unsafe { dmac.chid().modify(|_, w| w.id().bits(pend_channel as u8)) };
 
-                let wake = if dmac.chintflag().read().tcmpl().bit_is_set() {
+                let (wake, stop) = if dmac.chintflag().read().tcmpl().bit_is_set() {
                     // Transfer complete. Don't clear the flag, but
                     // disable the interrupt. Flag will be cleared when polled
                     dmac.chintenclr().modify(|_, w| w.tcmpl().set_bit());
-                    true
+                    // Circular transfers interrupting after every block keep running
+                    (true, !sram::interrupts_every_block(pend_channel as usize))
                 } else if dmac.chintflag().read().terr().bit_is_set() {
                     // Transfer error
                     dmac.chintenclr().modify(|_, w| w.terr().set_bit());
-                    true
+                    (true, true)
                 } else {
-                    false
+                    (false, false)
                 };
 
-                if wake {
+                if stop {
                     dmac.chctrla().modify(|_, w| w.enable().clear_bit());
                     dmac.chctrlb()
                         .modify(|_, w| w.trigsrc().variant(TriggerSource::Disable));
+                }
+
+                if wake {
                     WAKERS[pend_channel as usize].wake();
                 }
             }
@@ -68,7 +72,7 @@ impl Handler<DMAC> for InterruptHandler {
 
         let pending_channels = BitIter(dmac.intstatus().read().bits());
         for channel in pending_channels.map(|c| c as usize) {
-            let wake = if dmac
+            let (wake, stop) = if dmac
                 .channel(channel)
                 .chintflag()
                 .read()
@@ -80,22 +84,26 @@ impl Handler<DMAC> for InterruptHandler {
                 dmac.channel(channel)
                     .chintenclr()
                     .modify(|_, w| w.tcmpl().set_bit());
-                true
+                // Circular transfers interrupting after every block keep running
+                (true, !sram::interrupts_every_block(channel))
             } else if dmac.channel(channel).chintflag().read().terr().bit_is_set() {
                 // Transfer error
                 dmac.channel(channel)
                     .chintenclr()
                     .modify(|_, w| w.terr().set_bit());
-                true
+                (true, true)
             } else {
-                false
+                (false, false)
             };
 
-            if wake {
+            if stop {
                 dmac.channel(channel).chctrla().modify(|_, w| {
                     w.enable().clear_bit();
                     w.trigsrc().variant(TriggerSource::Disable)
                 });
+            }
+
+            if wake {
                 WAKERS[channel].wake();
             }
         }
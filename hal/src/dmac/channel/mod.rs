@@ -19,6 +19,17 @@
 //! to wait for multiple Beats before sending a Burst. See SAMD5x/E5x datasheet
 //! section 22.6.2.8 for more information.
 //!
+//! # Events
+//!
+//! A channel can be connected to the event system. The action taken on an
+//! incoming event is set by [`Channel::event_input`], and output events are
+//! enabled by [`Channel::event_output`]. For instance, a transfer started with
+//! [`TriggerSource::Disable`] on a channel whose event input action is
+//! [`EventInputAction::Trig`] moves one beat, burst, block or transaction
+//! (depending on its [`TriggerAction`]) on each event. When the event is a
+//! TC overflow, samples are streamed to or from an ADC or DAC at a precise
+//! sample rate.
+//!
 //! # Channel status
 //!
 //! Channels can be in any of three statuses: [`Uninitialized`], [`Ready`], and
@@ -39,7 +50,7 @@ use core::sync::atomic;
 use atsamd_hal_macros::{hal_cfg, hal_macro_helper};
 
 use super::{
-    dma_controller::{ChId, PriorityLevel, TriggerAction, TriggerSource},
+    dma_controller::{ChId, EventInputAction, PriorityLevel, TriggerAction, TriggerSource},
     sram::{self, DmacDescriptor},
     transfer::{BufferPair, Transfer},
     Beat, Buffer, Error,
@@ -51,7 +62,7 @@ mod reg;
 use reg::RegisterBlock;
 
 #[hal_cfg("dmac-d5x")]
-use super::dma_controller::{BurstLength, FifoThreshold};
+use super::dma_controller::{BurstLength, EventOutputMode, FifoThreshold};
 
 //==============================================================================
 // Channel Status
@@ -261,6 +272,28 @@ impl<Id: ChId, S: Status> Channel<Id, S> {
         // Reset the channel to its startup state and wait for reset to complete
         self.regs.chctrla.modify(|_, w| w.swrst().set_bit());
         while self.regs.chctrla.read().swrst().bit_is_set() {}
+
+        // The event output selection lives in the descriptor, which is not
+        // affected by the reset
+        self.descriptor_mut()
+            .btctrl
+            .set_evosel(EventOutput::Disable as u8);
+    }
+
+    /// Returns whether a transfer using `trig_src` must be started by a
+    /// software trigger, which is the case unless the channel is triggered
+    /// by its event input.
+    #[inline]
+    #[hal_macro_helper]
+    fn needs_software_trigger(&mut self, trig_src: TriggerSource) -> bool {
+        #[hal_cfg(any("dmac-d11", "dmac-d21"))]
+        let evctrl = self.regs.chctrlb.read();
+
+        #[hal_cfg("dmac-d5x")]
+        let evctrl = self.regs.chevctrl.read();
+
+        let event_triggered = evctrl.evie().bit_is_set() && evctrl.evact().is_trig();
+        trig_src == TriggerSource::Disable && !event_triggered
     }
 
     #[inline]
@@ -337,6 +370,7 @@ impl<Id: ChId, S: Status> Channel<Id, S> {
         circular: bool,
     ) {
         let descriptor = self.descriptor_mut();
+        let evosel = descriptor.btctrl.evosel();
 
         // Enable support for circular transfers. If circular_xfer is true,
         // we set the address of the "next" block descriptor to actually
@@ -352,6 +386,8 @@ impl<Id: ChId, S: Status> Channel<Id, S> {
         };
 
         write_descriptor(descriptor, source, destination, descaddr);
+        // Keep the event output selected by `event_output`
+        descriptor.btctrl.set_evosel(evosel);
     }
 
     /// Add a linked descriptor after the first descriptor in the transfer.
@@ -375,6 +411,20 @@ impl<Id: ChId, S: Status> Channel<Id, S> {
     pub(super) unsafe fn link_next(&mut self, next: *mut DmacDescriptor) {
         self.descriptor_mut().descaddr = next;
     }
+
+    /// Copy `first` into the channel's descriptor, from which the DMAC
+    /// fetches the first block of a linked transfer.
+    ///
+    /// # Safety
+    ///
+    /// * This method may only be called on a channel which is not actively
+    ///   being used for transferring data.
+    ///
+    /// * `first`, along with the chain of descriptors it links to, must be
+    ///   valid and remain in scope for the entirety of the transfer.
+    pub(super) unsafe fn set_first_descriptor(&mut self, first: &DmacDescriptor) {
+        *self.descriptor_mut() = *first;
+    }
 }
 
 impl<Id, R> Channel<Id, R>
@@ -411,6 +461,60 @@ where
             .modify(|_, w| w.burstlen().variant(burst_length));
     }
 
+    /// Set the action taken by the channel when it receives an event from
+    /// the event system. [`EventInputAction::Noact`] disables the event
+    /// input.
+    ///
+    /// With [`EventInputAction::Trig`], each event triggers the channel
+    /// according to the [`TriggerAction`] of the transfer, which should then
+    /// use [`TriggerSource::Disable`]. No software trigger is issued in that
+    /// case, so that the transfer is paced by the events alone.
+    #[inline]
+    #[hal_macro_helper]
+    pub fn event_input(&mut self, action: EventInputAction) {
+        let enabled = action != EventInputAction::Noact;
+
+        #[hal_cfg(any("dmac-d11", "dmac-d21"))]
+        self.regs.chctrlb.modify(|_, w| {
+            w.evact().variant(action);
+            w.evie().bit(enabled)
+        });
+
+        #[hal_cfg("dmac-d5x")]
+        self.regs.chevctrl.modify(|_, w| {
+            w.evact().variant(action);
+            w.evie().bit(enabled)
+        });
+    }
+
+    /// Emit events to the event system on the strobe selected by `output`.
+    ///
+    /// This applies to the transfers started with a single descriptor. The
+    /// blocks of a linked transfer select their own strobe through
+    /// [`DescriptorList::set_event_output`](super::DescriptorList::set_event_output).
+    #[inline]
+    #[hal_macro_helper]
+    pub fn event_output(&mut self, output: EventOutput) {
+        let enabled = output != EventOutput::Disable;
+
+        #[hal_cfg(any("dmac-d11", "dmac-d21"))]
+        self.regs.chctrlb.modify(|_, w| w.evoe().bit(enabled));
+
+        #[hal_cfg("dmac-d5x")]
+        self.regs.chevctrl.modify(|_, w| w.evoe().bit(enabled));
+
+        self.descriptor_mut().btctrl.set_evosel(output as u8);
+    }
+
+    /// Select whether the event output follows the strobe selected by
+    /// [`event_output`](Self::event_output), or is emitted on every trigger
+    /// action.
+    #[hal_cfg("dmac-d5x")]
+    #[inline]
+    pub fn event_output_mode(&mut self, mode: EventOutputMode) {
+        self.regs.chevctrl.modify(|_, w| w.evomode().variant(mode));
+    }
+
     /// Start the transfer.
     ///
     /// # Safety
@@ -430,7 +534,7 @@ where
         self._enable_private();
 
         // If trigger source is DISABLE, manually trigger transfer
-        if trig_src == TriggerSource::Disable {
+        if self.needs_software_trigger(trig_src) {
             self._trigger_private();
         }
     }
@@ -552,7 +656,7 @@ impl<Id: ChId> Channel<Id, Ready> {
         self.configure_trigger(trig_src, trig_act);
         self._enable_private();
 
-        if trig_src == TriggerSource::Disable {
+        if self.needs_software_trigger(trig_src) {
             self._trigger_private();
         }
     }
@@ -758,7 +862,8 @@ mod transfer_future {
 
             self.chan._enable_private();
 
-            if !self.triggered && self.trig_src == TriggerSource::Disable {
+            let trig_src = self.trig_src;
+            if !self.triggered && self.chan.needs_software_trigger(trig_src) {
                 self.triggered = true;
                 self.chan._trigger_private();
             }
@@ -783,6 +888,19 @@ mod transfer_future {
     }
 }
 
+/// Strobe on which a channel emits its output events
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum EventOutput {
+    /// No event is emitted
+    Disable = 0,
+    /// An event is emitted at the end of each block
+    Block = 1,
+    /// An event is emitted after each beat (SAMD11/SAMD21) or burst
+    /// (SAMD51/SAMx5x)
+    Beat = 3,
+}
+
 /// Interrupt sources available to a DMA channel
 #[bitfield]
 #[repr(u8)]
@@ -39,7 +39,7 @@ use channel_regs::{
 use channel_regs::{Chctrla, Chctrlb, Chintenclr, Chintenset, Chintflag, Chstatus};
 
 #[hal_cfg("dmac-d5x")]
-use pac::dmac::channel::{chprilvl::ChprilvlSpec, Chprilvl};
+use pac::dmac::channel::{chevctrl::ChevctrlSpec, chprilvl::ChprilvlSpec, Chevctrl, Chprilvl};
 
 //==============================================================================
 // RegisterBlock
@@ -267,6 +267,8 @@ reg_proxy!(chintflag, register, rw);
 reg_proxy!(chstatus, register, r);
 #[hal_cfg("dmac-d5x")]
 reg_proxy!(chprilvl, register, rw);
+#[hal_cfg("dmac-d5x")]
+reg_proxy!(chevctrl, register, rw);
 
 reg_proxy!(intstatus, bit, r);
 reg_proxy!(busych, bit, r);
@@ -294,6 +296,8 @@ pub(super) struct RegisterBlock<Id: ChId> {
     pub swtrigctrl: SwtrigctrlProxy<Id, Swtrigctrl>,
     #[hal_cfg("dmac-d5x")]
     pub chprilvl: ChprilvlProxy<Id, Chprilvl>,
+    #[hal_cfg("dmac-d5x")]
+    pub chevctrl: ChevctrlProxy<Id, Chevctrl>,
 }
 
 impl<Id: ChId> RegisterBlock<Id> {
@@ -312,6 +316,8 @@ impl<Id: ChId> RegisterBlock<Id> {
             swtrigctrl: SwtrigctrlProxy::new(),
             #[hal_cfg("dmac-d5x")]
             chprilvl: ChprilvlProxy::new(),
+            #[hal_cfg("dmac-d5x")]
+            chevctrl: ChevctrlProxy::new(),
         }
     }
 }
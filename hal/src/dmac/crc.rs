@@ -0,0 +1,231 @@
+//! # DMAC CRC engine
+//!
+//! The DMAC contains a CRC engine, which computes either CRC-16 or CRC-32
+//! checksums:
+//!
+//! * [`CrcPolynomial::Crc16`] is the CRC-16/CCITT checksum (polynomial
+//!   `0x1021`, initial value `0xFFFF`).
+//! * [`CrcPolynomial::Crc32`] is the IEEE 802.3 CRC-32 checksum, as used by
+//!   Ethernet or zlib.
+//!
+//! The engine is accessed through the [`DmacCrc`] handle returned by
+//! [`DmaController::crc`](super::DmaController::crc). It either computes the
+//! checksum of data written by the CPU, all at once with
+//! [`compute`](DmacCrc::compute) or piece by piece with a [`CrcStream`], or
+//! the checksum of every beat transferred by a DMA channel, with a
+//! [`ChannelCrc`].
+//!
+//! ```no_run
+//! use atsamd_hal::dmac::{CrcPolynomial, Transfer, TriggerAction};
+//!
+//! let mut crc = dmac.crc();
+//! let checksum = crc.compute(CrcPolynomial::Crc32, b"123456789");
+//!
+//! // Compute the checksum of a packet while it is sent
+//! let xfer = Transfer::new(chan0, packet, uart_data, false).unwrap();
+//! let channel_crc = crc.attach_transfer(CrcPolynomial::Crc16, &xfer);
+//! let (chan0, packet, uart_data) = xfer.begin(trigger_source, TriggerAction::Burst).wait();
+//! let checksum = channel_crc.checksum().unwrap();
+//! ```
+
+use atsamd_hal_macros::hal_macro_helper;
+
+use super::{
+    channel::{AnyChannel, ChannelId},
+    dma_controller::ChId,
+    transfer::{AnyBufferPair, Transfer},
+};
+use crate::pac::Dmac;
+
+pub use crate::pac::dmac::crcctrl::Crcpolyselect as CrcPolynomial;
+
+/// `CRCSRC` value of the I/O interface
+const CRCSRC_IO: u8 = 0x01;
+/// `CRCSRC` value of the first DMA channel
+const CRCSRC_CHANNEL: u8 = 0x20;
+
+/// Handle to the CRC engine of the DMAC
+pub struct DmacCrc<'a> {
+    dmac: &'a Dmac,
+}
+
+impl<'a> DmacCrc<'a> {
+    #[inline]
+    pub(super) fn new(dmac: &'a Dmac) -> Self {
+        Self { dmac }
+    }
+
+    /// Compute the checksum of `data`
+    #[inline]
+    pub fn compute(&mut self, polynomial: CrcPolynomial, data: &[u8]) -> u32 {
+        let mut stream = self.stream(polynomial);
+        stream.update(data);
+        stream.finish()
+    }
+
+    /// Start computing a checksum over data provided in several pieces
+    #[inline]
+    pub fn stream(&mut self, polynomial: CrcPolynomial) -> CrcStream<'_> {
+        start(self.dmac, polynomial, CRCSRC_IO);
+        CrcStream {
+            dmac: self.dmac,
+            polynomial,
+        }
+    }
+
+    /// Compute the checksum of every beat transferred by a DMA channel
+    ///
+    /// The checksum covers the beats transferred from now on, so the CRC
+    /// engine should be attached before the transfer begins.
+    #[inline]
+    pub fn attach<Ch: AnyChannel>(
+        &mut self,
+        polynomial: CrcPolynomial,
+        _channel: &Ch,
+    ) -> ChannelCrc<'_> {
+        self.attach_id::<ChannelId<Ch>>(polynomial)
+    }
+
+    /// Compute the checksum of every beat transferred by a [`Transfer`]
+    ///
+    /// The checksum covers the beats transferred from now on, so the CRC
+    /// engine should be attached before the transfer begins.
+    #[inline]
+    pub fn attach_transfer<C, B>(
+        &mut self,
+        polynomial: CrcPolynomial,
+        _transfer: &Transfer<C, B>,
+    ) -> ChannelCrc<'_>
+    where
+        C: AnyChannel,
+        B: AnyBufferPair,
+    {
+        self.attach_id::<ChannelId<C>>(polynomial)
+    }
+
+    #[inline]
+    fn attach_id<Id: ChId>(&mut self, polynomial: CrcPolynomial) -> ChannelCrc<'_> {
+        start(self.dmac, polynomial, CRCSRC_CHANNEL + Id::U8);
+        ChannelCrc {
+            dmac: self.dmac,
+            polynomial,
+        }
+    }
+}
+
+/// Checksum being computed over data written by the CPU
+///
+/// The CRC engine is released when the stream is dropped.
+pub struct CrcStream<'a> {
+    dmac: &'a Dmac,
+    polynomial: CrcPolynomial,
+}
+
+impl CrcStream<'_> {
+    /// Add `data` to the checksum
+    #[inline]
+    pub fn update(&mut self, data: &[u8]) {
+        for &byte in data {
+            // SAFETY: Any value may be written to CRCDATAIN
+            self.dmac
+                .crcdatain()
+                .write(|w| unsafe { w.bits(byte as u32) });
+            // The engine takes one cycle to process a byte
+            cortex_m::asm::nop();
+        }
+    }
+
+    /// Return the checksum of all the data added to the stream
+    #[inline]
+    pub fn finish(self) -> u32 {
+        let checksum = stop(self.dmac, self.polynomial);
+        core::mem::forget(self);
+        checksum
+    }
+}
+
+impl Drop for CrcStream<'_> {
+    fn drop(&mut self) {
+        stop(self.dmac, self.polynomial);
+    }
+}
+
+/// Checksum being computed over the beats transferred by a DMA channel
+///
+/// The CRC engine is detached from the channel when this struct is dropped.
+pub struct ChannelCrc<'a> {
+    dmac: &'a Dmac,
+    polynomial: CrcPolynomial,
+}
+
+impl ChannelCrc<'_> {
+    /// Return the checksum of the beats transferred so far, or `None` while
+    /// the channel is enabled
+    #[inline]
+    pub fn checksum(&self) -> Option<u32> {
+        if self.dmac.crcstatus().read().crcbusy().bit_is_set() {
+            None
+        } else {
+            Some(read_checksum(self.dmac, self.polynomial))
+        }
+    }
+
+    /// Detach the CRC engine from the channel, and return the final checksum
+    #[inline]
+    pub fn detach(self) -> u32 {
+        let checksum = stop(self.dmac, self.polynomial);
+        core::mem::forget(self);
+        checksum
+    }
+}
+
+impl Drop for ChannelCrc<'_> {
+    fn drop(&mut self) {
+        stop(self.dmac, self.polynomial);
+    }
+}
+
+/// Seed the checksum and start the CRC engine, reading from the `CRCSRC`
+/// source
+#[hal_macro_helper]
+fn start(dmac: &Dmac, polynomial: CrcPolynomial, source: u8) {
+    // Start from a disabled engine, as the checksum can only be written then
+    stop(dmac, polynomial);
+
+    let seed = match polynomial {
+        CrcPolynomial::Crc16 => 0xFFFF,
+        CrcPolynomial::Crc32 => 0xFFFF_FFFF,
+    };
+    // SAFETY: Any value may be written to CRCCHKSUM
+    dmac.crcchksum().write(|w| unsafe { w.bits(seed) });
+    dmac.crcctrl().write(|w| {
+        w.crcbeatsize().byte();
+        w.crcpoly().variant(polynomial);
+        // SAFETY: `source` is either the I/O interface or a DMA channel
+        unsafe { w.crcsrc().bits(source) }
+    });
+
+    #[hal_cfg(any("dmac-d11", "dmac-d21"))]
+    dmac.ctrl().modify(|_, w| w.crcenable().set_bit());
+}
+
+/// Stop the CRC engine, and return the checksum it computed
+#[hal_macro_helper]
+fn stop(dmac: &Dmac, polynomial: CrcPolynomial) -> u32 {
+    dmac.crcstatus().write(|w| w.crcbusy().set_bit());
+    dmac.crcctrl().reset();
+
+    #[hal_cfg(any("dmac-d11", "dmac-d21"))]
+    dmac.ctrl().modify(|_, w| w.crcenable().clear_bit());
+
+    read_checksum(dmac, polynomial)
+}
+
+#[inline]
+fn read_checksum(dmac: &Dmac, polynomial: CrcPolynomial) -> u32 {
+    let checksum = dmac.crcchksum().read().bits();
+    match polynomial {
+        CrcPolynomial::Crc16 => checksum & 0xFFFF,
+        CrcPolynomial::Crc32 => checksum,
+    }
+}
@@ -28,7 +28,8 @@ use seq_macro::seq;
 
 #[hal_cfg(any("dmac-d11", "dmac-d21"))]
 pub use crate::pac::dmac::chctrlb::{
-    Lvlselect as PriorityLevel, Trigactselect as TriggerAction, Trigsrcselect as TriggerSource,
+    Evactselect as EventInputAction, Lvlselect as PriorityLevel, Trigactselect as TriggerAction,
+    Trigsrcselect as TriggerSource,
 };
 
 #[hal_cfg("dmac-d5x")]
@@ -37,11 +38,13 @@ pub use crate::pac::dmac::channel::{
         Burstlenselect as BurstLength, Thresholdselect as FifoThreshold,
         Trigactselect as TriggerAction, Trigsrcselect as TriggerSource,
     },
+    chevctrl::{Evactselect as EventInputAction, Evomodeselect as EventOutputMode},
     chprilvl::Prilvlselect as PriorityLevel,
 };
 
 use super::{
     channel::{Channel, Uninitialized},
+    crc::DmacCrc,
     sram,
 };
 use crate::{
@@ -279,6 +282,12 @@ impl<T> DmaController<T> {
         }
     }
 
+    /// Borrow the CRC engine of the DMAC
+    #[inline]
+    pub fn crc(&mut self) -> DmacCrc<'_> {
+        DmacCrc::new(&self.dmac)
+    }
+
     /// Use the [`DmaController`] in async mode. You are required to provide the
     /// struct created by the
     /// [`bind_interrupts`](crate::bind_interrupts) macro to prove
@@ -0,0 +1,794 @@
+//! # Linked-list (multi-descriptor) transfers
+//!
+//! A DMA transfer is made of one or more blocks, each described by a
+//! [`DmacDescriptor`]. Every descriptor holds the address of the next one,
+//! which the DMAC fetches as soon as the current block is complete. This
+//! module builds such descriptor chains from user-owned storage, and runs them
+//! on a DMA channel.
+//!
+//! # Descriptor chains
+//!
+//! A [`DescriptorList`] links together blocks made of any source and
+//! destination [`Buffer`]s, as long as they share the same [`Beat`] size. This
+//! allows scatter-gather transfers, for example sending a header, a payload
+//! slice and a checksum to a peripheral in a single transfer. The list may be
+//! [circular](DescriptorList::set_circular), in which case the DMAC loops
+//! back to the first block after the last one, until the transfer is stopped.
+//!
+//! A [`PingPong`] chain splits a memory buffer into two halves, which the DMAC
+//! transfers alternately to or from a peripheral. While one half is in flight,
+//! the CPU may process or refill the half the DMAC is done with.
+//!
+//! The first block of a chain is copied into the channel's own descriptor, so
+//! a chain of `N` blocks needs storage for `N` descriptors.
+//!
+//! # Block notifications
+//!
+//! The channel raises its transfer complete flag at the end of each block.
+//! Running transfers report the index of every completed block in order,
+//! either through [`LinkedTransfer::block_complete`] and
+//! [`LinkedTransfer::wait_block`], or by `await`ing
+//! [`AsyncLinkedTransfer::next_block`]. Once a block of a [`PingPong`] chain
+//! completes, its half is available through `completed_half`.
+//!
+//! The next block must be waited upon before the DMAC completes the one after
+//! it, otherwise a notification is lost. This also means that the CPU must
+//! be done with a [`PingPong`] half before the other one completes, as the
+//! DMAC then starts overwriting (or sending) it.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use atsamd_hal::dmac::{DescriptorList, DmacDescriptor, LinkedTransfer};
+//! use cortex_m::singleton;
+//!
+//! let storage = singleton!(: [DmacDescriptor; 3] = [DmacDescriptor::default(); 3]).unwrap();
+//! let header = singleton!(: [u8; 4] = [0; 4]).unwrap();
+//! let payload = singleton!(: [u8; 64] = [0; 64]).unwrap();
+//! let checksum = singleton!(: u8 = 0).unwrap();
+//!
+//! // `data_reg` is a copyable `Buffer` pointing to the data register of a
+//! // peripheral
+//! let mut list = DescriptorList::new(storage);
+//! list.push(header, data_reg)?;
+//! list.push(payload, data_reg)?;
+//! list.push(checksum, data_reg)?;
+//!
+//! let mut xfer = LinkedTransfer::new(chan0, list)?.begin(trigger_source, TriggerAction::Burst);
+//! while let Ok(block) = xfer.wait_block() {
+//!     // Block `block` has been sent
+//! }
+//! let (chan0, list) = xfer.wait();
+//! ```
+
+use core::marker::PhantomData;
+use core::sync::atomic;
+
+use super::{
+    channel::{
+        write_descriptor, AnyChannel, Busy, Channel, ChannelId, EventOutput, InterruptFlags, Ready,
+    },
+    dma_controller::{ChId, TriggerAction, TriggerSource},
+    sram::{self, DmacDescriptor},
+    Beat, Buffer, Error, Result,
+};
+
+#[cfg(feature = "async")]
+use super::channel::ReadyFuture;
+
+//==============================================================================
+// DescriptorChain
+//==============================================================================
+
+mod private {
+    use super::DmacDescriptor;
+
+    pub trait Sealed {
+        /// Descriptors of the chain, already linked together
+        fn descriptors(&mut self) -> &mut [DmacDescriptor];
+
+        /// Whether the last block links back to the first one
+        fn circular(&self) -> bool;
+    }
+}
+
+/// Chain of linked descriptors, which can be run by a DMA channel
+///
+/// Implemented by [`DescriptorList`] and [`PingPong`].
+pub trait DescriptorChain: private::Sealed {
+    /// DMAC beat size of the chain
+    type Beat: Beat;
+}
+
+//==============================================================================
+// DescriptorList
+//==============================================================================
+
+/// List of linked blocks, built in user-provided descriptor storage
+///
+/// The list borrows the buffers of its blocks, and its storage, for `'a`.
+pub struct DescriptorList<'a, B: Beat> {
+    descriptors: &'a mut [DmacDescriptor],
+    len: usize,
+    circular: bool,
+    _buffers: PhantomData<&'a mut B>,
+}
+
+impl<'a, B: Beat> DescriptorList<'a, B> {
+    /// Create an empty list, which can hold up to `storage.len()` blocks
+    #[inline]
+    pub fn new(storage: &'a mut [DmacDescriptor]) -> Self {
+        Self {
+            descriptors: storage,
+            len: 0,
+            circular: false,
+            _buffers: PhantomData,
+        }
+    }
+
+    /// Append a block, transferring `source` to `destination`, to the list
+    ///
+    /// Returns the index of the block in the list.
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::LengthMismatch`] if both buffers have a length > 1 and are
+    ///   not of equal length.
+    /// * [`Error::InvalidState`] if the storage of the list is full.
+    pub fn push<S, D>(&mut self, mut source: S, mut destination: D) -> Result<usize>
+    where
+        S: Buffer<Beat = B> + 'a,
+        D: Buffer<Beat = B> + 'a,
+    {
+        // SAFETY: The buffers are borrowed for as long as the list exists
+        unsafe { self.push_unchecked(&mut source, &mut destination) }
+    }
+
+    /// Append a block to the list, without tying the lifetime of the buffers
+    /// to the list.
+    ///
+    /// # Safety
+    ///
+    /// The memory described by the buffers must remain valid for as long as
+    /// the list exists.
+    unsafe fn push_unchecked<S, D>(&mut self, source: &mut S, destination: &mut D) -> Result<usize>
+    where
+        S: Buffer<Beat = B>,
+        D: Buffer<Beat = B>,
+    {
+        check_buffer_pair(source, destination)?;
+        if self.len == self.descriptors.len() {
+            return Err(Error::InvalidState);
+        }
+
+        let index = self.len;
+        write_descriptor(
+            &mut self.descriptors[index],
+            source,
+            destination,
+            core::ptr::null_mut(),
+        );
+        self.descriptors[index]
+            .btctrl
+            .set_blockact(sram::BLOCKACT_INT);
+
+        if index > 0 {
+            let next: *mut DmacDescriptor = &mut self.descriptors[index];
+            self.descriptors[index - 1].set_next_descriptor(next);
+        }
+        self.len += 1;
+        self.link_last();
+        Ok(index)
+    }
+
+    /// Make the DMAC loop back to the first block after the last one
+    #[inline]
+    pub fn set_circular(&mut self, circular: bool) {
+        self.circular = circular;
+        self.link_last();
+    }
+
+    /// Return whether the list is circular
+    #[inline]
+    pub fn is_circular(&self) -> bool {
+        self.circular
+    }
+
+    /// Select the strobe on which the channel emits output events while it
+    /// transfers block `index`
+    ///
+    /// The event output itself must be enabled with
+    /// [`Channel::event_output`].
+    #[inline]
+    pub fn set_event_output(&mut self, index: usize, output: EventOutput) -> Result<()> {
+        if index >= self.len {
+            return Err(Error::InvalidState);
+        }
+        self.descriptors[index].btctrl.set_evosel(output as u8);
+        Ok(())
+    }
+
+    /// Number of blocks in the list
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return whether the list holds no block
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Maximum number of blocks the list can hold
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.descriptors.len()
+    }
+
+    /// Terminate the chain after its last block, or link it back to the
+    /// first one if the list is circular.
+    fn link_last(&mut self) {
+        if self.len == 0 {
+            return;
+        }
+
+        let next: *mut DmacDescriptor = if self.circular {
+            &mut self.descriptors[0]
+        } else {
+            core::ptr::null_mut()
+        };
+        self.descriptors[self.len - 1].set_next_descriptor(next);
+    }
+}
+
+impl<B: Beat> private::Sealed for DescriptorList<'_, B> {
+    #[inline]
+    fn descriptors(&mut self) -> &mut [DmacDescriptor] {
+        &mut self.descriptors[..self.len]
+    }
+
+    #[inline]
+    fn circular(&self) -> bool {
+        self.circular
+    }
+}
+
+impl<B: Beat> DescriptorChain for DescriptorList<'_, B> {
+    type Beat = B;
+}
+
+//==============================================================================
+// PingPong
+//==============================================================================
+
+/// Circular chain of two blocks, alternately transferring each half of a
+/// memory buffer
+pub struct PingPong<'a, B: Beat> {
+    list: DescriptorList<'a, B>,
+    buffer: *mut B,
+    half_len: usize,
+}
+
+impl<'a, B: Beat> PingPong<'a, B> {
+    /// Create a chain receiving data from `source` into alternating halves of
+    /// `buffer`
+    ///
+    /// `source` is typically the data register of a peripheral.
+    ///
+    /// Returns [`Error::LengthMismatch`] if the length of `buffer` is not a
+    /// non-zero even number, or if the length of `source` is neither 1 nor
+    /// half the length of `buffer`.
+    pub fn receive<S>(
+        storage: &'a mut [DmacDescriptor; 2],
+        mut source: S,
+        buffer: &'a mut [B],
+    ) -> Result<Self>
+    where
+        S: Buffer<Beat = B> + 'a,
+    {
+        let ptr = buffer.as_mut_ptr();
+        let (mut first, mut second) = split_halves(buffer)?;
+        let half_len = first.len();
+
+        let mut list = DescriptorList::new(storage);
+        list.set_circular(true);
+        // SAFETY: `source` and `buffer` are borrowed for as long as the chain
+        // exists
+        unsafe {
+            list.push_unchecked(&mut source, &mut first)?;
+            list.push_unchecked(&mut source, &mut second)?;
+        }
+        Ok(Self {
+            list,
+            buffer: ptr,
+            half_len,
+        })
+    }
+
+    /// Create a chain sending alternating halves of `buffer` to
+    /// `destination`
+    ///
+    /// `destination` is typically the data register of a peripheral.
+    ///
+    /// Returns [`Error::LengthMismatch`] if the length of `buffer` is not a
+    /// non-zero even number, or if the length of `destination` is neither 1
+    /// nor half the length of `buffer`.
+    pub fn transmit<D>(
+        storage: &'a mut [DmacDescriptor; 2],
+        buffer: &'a mut [B],
+        mut destination: D,
+    ) -> Result<Self>
+    where
+        D: Buffer<Beat = B> + 'a,
+    {
+        let ptr = buffer.as_mut_ptr();
+        let (mut first, mut second) = split_halves(buffer)?;
+        let half_len = first.len();
+
+        let mut list = DescriptorList::new(storage);
+        list.set_circular(true);
+        // SAFETY: `destination` and `buffer` are borrowed for as long as the
+        // chain exists
+        unsafe {
+            list.push_unchecked(&mut first, &mut destination)?;
+            list.push_unchecked(&mut second, &mut destination)?;
+        }
+        Ok(Self {
+            list,
+            buffer: ptr,
+            half_len,
+        })
+    }
+
+    /// Length of each half, in beats
+    #[inline]
+    pub fn half_len(&self) -> usize {
+        self.half_len
+    }
+
+    /// Borrow both halves, for example to fill them before a transmission
+    /// starts
+    #[inline]
+    pub fn halves_mut(&mut self) -> (&mut [B], &mut [B]) {
+        // SAFETY: The chain is not running, as it would otherwise be borrowed
+        // by a transfer.
+        unsafe { (self.half_mut(0), self.half_mut(1)) }
+    }
+
+    /// Borrow the half transferred by block `index` of a running chain,
+    /// unless the DMAC is transferring it
+    ///
+    /// `channel_id` is the channel running the chain, which must have
+    /// completed at least one block. The returned reference must borrow the
+    /// transfer.
+    fn idle_half<'b>(&mut self, channel_id: usize, index: usize) -> Option<&'b mut [B]> {
+        // The write-back descriptor links to the descriptor following the
+        // block in flight. The first block runs from a copy of the first
+        // descriptor, which links to the second one.
+        // SAFETY: The channel has started the transfer
+        let next = unsafe { sram::writeback_next_descriptor(channel_id) };
+        let descriptors = &self.list.descriptors;
+        let in_flight = if core::ptr::eq(next, &descriptors[1]) {
+            0
+        } else if core::ptr::eq(next, &descriptors[0]) {
+            1
+        } else {
+            return None;
+        };
+
+        // SAFETY: The DMAC is transferring the other half
+        (in_flight != index).then(|| unsafe { self.half_mut(index) })
+    }
+
+    /// Borrow the half transferred by block `index`
+    ///
+    /// # Safety
+    ///
+    /// The DMAC must not be accessing this half, and the returned references
+    /// must not overlap.
+    #[inline]
+    unsafe fn half_mut<'b>(&mut self, index: usize) -> &'b mut [B] {
+        let start = self.buffer.add(index * self.half_len);
+        core::slice::from_raw_parts_mut(start, self.half_len)
+    }
+}
+
+impl<B: Beat> private::Sealed for PingPong<'_, B> {
+    #[inline]
+    fn descriptors(&mut self) -> &mut [DmacDescriptor] {
+        self.list.descriptors()
+    }
+
+    #[inline]
+    fn circular(&self) -> bool {
+        true
+    }
+}
+
+impl<B: Beat> DescriptorChain for PingPong<'_, B> {
+    type Beat = B;
+}
+
+/// Split `buffer` into two halves of equal, non-zero length
+fn split_halves<B>(buffer: &mut [B]) -> Result<(&mut [B], &mut [B])> {
+    if buffer.is_empty() || buffer.len() % 2 != 0 {
+        return Err(Error::LengthMismatch);
+    }
+    let half = buffer.len() / 2;
+    Ok(buffer.split_at_mut(half))
+}
+
+/// Check that the lengths of `source` and `destination` are compatible
+#[inline]
+fn check_buffer_pair<S, D>(source: &S, destination: &D) -> Result<()>
+where
+    S: Buffer,
+    D: Buffer<Beat = S::Beat>,
+{
+    let src_len = source.buffer_len();
+    let dst_len = destination.buffer_len();
+
+    if src_len > 1 && dst_len > 1 && src_len != dst_len {
+        Err(Error::LengthMismatch)
+    } else {
+        Ok(())
+    }
+}
+
+//==============================================================================
+// Block tracking
+//==============================================================================
+
+/// Tracks the blocks of a running chain
+struct Blocks {
+    /// Index of the next block to complete
+    next: usize,
+    len: usize,
+    circular: bool,
+    /// Index of the last completed block
+    completed: Option<usize>,
+    failed: bool,
+}
+
+impl Blocks {
+    fn new(len: usize, circular: bool) -> Self {
+        Self {
+            next: 0,
+            len,
+            circular,
+            completed: None,
+            failed: false,
+        }
+    }
+
+    /// Check whether the next block of the chain running on `chan` has
+    /// completed, and return its index.
+    fn poll<Id: ChId, S: super::channel::Status>(
+        &mut self,
+        chan: &mut Channel<Id, S>,
+    ) -> nb::Result<usize, Error> {
+        if self.failed {
+            return Err(nb::Error::Other(Error::TransferError));
+        }
+        if !self.circular && self.next == self.len {
+            return Err(nb::Error::Other(Error::InvalidState));
+        }
+
+        // Publish the CPU's accesses to the completed block before the DMAC
+        // may reach it again
+        atomic::fence(atomic::Ordering::Release); // ▲
+
+        let flags = InterruptFlags::new().with_tcmpl(true).with_terr(true);
+        let set = chan.check_and_clear_interrupts(flags);
+        if set.terr() {
+            self.failed = true;
+            self.completed = None;
+            return Err(nb::Error::Other(Error::TransferError));
+        }
+
+        // A one-shot chain which has stopped completed all of its blocks, even
+        // if their notifications were not observed
+        if set.tcmpl() || (!self.circular && chan.xfer_complete()) {
+            atomic::fence(atomic::Ordering::Acquire); // ▼
+            let index = self.next;
+            self.next += 1;
+            if self.circular && self.next == self.len {
+                self.next = 0;
+            }
+            self.completed = Some(index);
+            Ok(index)
+        } else {
+            Err(nb::Error::WouldBlock)
+        }
+    }
+}
+
+/// Copy the first descriptor of `chain` into the channel, and clear its
+/// stale interrupt flags
+///
+/// # Safety
+///
+/// The channel must not be running, and `chain` must remain valid until the
+/// transfer is completed or stopped.
+unsafe fn prepare<Id: ChId, S: super::channel::Status, L: DescriptorChain>(
+    chan: &mut Channel<Id, S>,
+    chain: &mut L,
+) -> Result<Blocks> {
+    let circular = chain.circular();
+    let descriptors = chain.descriptors();
+    let first = descriptors.first().ok_or(Error::InvalidState)?;
+    chan.set_first_descriptor(first);
+
+    chan.check_and_clear_interrupts(InterruptFlags::new().with_tcmpl(true).with_terr(true));
+    Ok(Blocks::new(descriptors.len(), circular))
+}
+
+//==============================================================================
+// LinkedTransfer
+//==============================================================================
+
+/// Blocking transfer of a [`DescriptorChain`], owning the channel and the
+/// chain until the transfer is done.
+pub struct LinkedTransfer<Chan, L>
+where
+    Chan: AnyChannel,
+    L: DescriptorChain,
+{
+    chan: Chan,
+    chain: L,
+    blocks: Blocks,
+}
+
+impl<C, L> LinkedTransfer<C, L>
+where
+    C: AnyChannel<Status = Ready>,
+    L: DescriptorChain + 'static,
+{
+    /// Construct a new `LinkedTransfer`. To guarantee memory safety, the chain
+    /// and the buffers it describes are required to be `'static`.
+    ///
+    /// Returns [`Error::InvalidState`] if the chain is empty.
+    #[allow(clippy::new_ret_no_self)]
+    #[inline]
+    pub fn new(mut chan: C, mut chain: L) -> Result<LinkedTransfer<C, L>> {
+        // SAFETY: The channel is not running, and the chain is 'static
+        let blocks = unsafe { prepare(chan.as_mut(), &mut chain)? };
+        Ok(LinkedTransfer {
+            chan,
+            chain,
+            blocks,
+        })
+    }
+
+    /// Begin the transfer in blocking mode. If [`TriggerSource::Disable`] is
+    /// used, a software trigger will be issued to the DMA channel to launch
+    /// the transfer.
+    #[inline]
+    pub fn begin(
+        self,
+        trig_src: TriggerSource,
+        trig_act: TriggerAction,
+    ) -> LinkedTransfer<Channel<ChannelId<C>, Busy>, L> {
+        let chan = self.chan.into().start(trig_src, trig_act);
+        LinkedTransfer {
+            chan,
+            chain: self.chain,
+            blocks: self.blocks,
+        }
+    }
+
+    /// Free the [`LinkedTransfer`] and return the resources it holds.
+    #[inline]
+    pub fn free(self) -> (Channel<ChannelId<C>, Ready>, L) {
+        (self.chan.into(), self.chain)
+    }
+}
+
+impl<C, L> LinkedTransfer<C, L>
+where
+    C: AnyChannel<Status = Busy>,
+    L: DescriptorChain,
+{
+    /// Issue a software trigger request to the corresponding channel.
+    #[inline]
+    pub fn software_trigger(&mut self) {
+        self.chan.as_mut().software_trigger();
+    }
+
+    /// Check whether the next block of the chain has completed, and return
+    /// its index.
+    ///
+    /// This clears the transfer complete flag of the channel, so it may be
+    /// called from the DMAC interrupt handler.
+    ///
+    /// # Errors
+    ///
+    /// * [`Error::TransferError`] if the channel reported an error, in which
+    ///   case the transfer has stopped.
+    /// * [`Error::InvalidState`] if all the blocks of a one-shot chain have
+    ///   already been reported.
+    #[inline]
+    pub fn block_complete(&mut self) -> nb::Result<usize, Error> {
+        self.blocks.poll(self.chan.as_mut())
+    }
+
+    /// Wait for the next block of the chain to complete, and return its
+    /// index. See [`block_complete`](Self::block_complete) for the errors.
+    ///
+    /// # Blocking: This method may block
+    #[inline]
+    pub fn wait_block(&mut self) -> Result<usize> {
+        nb::block!(self.block_complete())
+    }
+
+    /// Check if the transfer has completed. A circular chain only completes
+    /// if it is stopped by an error.
+    #[inline]
+    pub fn complete(&mut self) -> bool {
+        self.chan.as_mut().xfer_complete()
+    }
+
+    /// Wait for the transfer to complete and release all owned resources.
+    ///
+    /// # Blocking: This method may block
+    #[inline]
+    pub fn wait(mut self) -> (Channel<ChannelId<C>, Ready>, L) {
+        while !self.complete() {}
+        self.stop()
+    }
+
+    /// Non-blocking; Immediately stop the transfer and release all owned
+    /// resources
+    #[inline]
+    pub fn stop(self) -> (Channel<ChannelId<C>, Ready>, L) {
+        // `free()` stops the transfer, waits for the burst to finish, and emits a
+        // compiler fence.
+        (self.chan.into().free(), self.chain)
+    }
+}
+
+impl<C, B> LinkedTransfer<C, PingPong<'static, B>>
+where
+    C: AnyChannel<Status = Busy>,
+    B: Beat,
+{
+    /// Borrow the half of the buffer transferred by the last completed block,
+    /// which the DMAC does not access until the other half completes.
+    ///
+    /// Returns `None` until the first block completes, and if the DMAC is
+    /// already transferring that half again, which happens when block
+    /// notifications were missed.
+    #[inline]
+    pub fn completed_half(&mut self) -> Option<&mut [B]> {
+        let index = self.blocks.completed?;
+        self.chain.idle_half(ChannelId::<C>::USIZE, index)
+    }
+}
+
+//==============================================================================
+// AsyncLinkedTransfer
+//==============================================================================
+
+/// Running `async` transfer of a [`DescriptorChain`], created by
+/// [`Channel::transfer_linked`]
+///
+/// The transfer is stopped when this struct is dropped.
+#[cfg(feature = "async")]
+pub struct AsyncLinkedTransfer<'a, Id: ChId, L: DescriptorChain> {
+    chan: &'a mut Channel<Id, ReadyFuture>,
+    chain: &'a mut L,
+    blocks: Blocks,
+}
+
+#[cfg(feature = "async")]
+impl<Id: ChId> Channel<Id, ReadyFuture> {
+    /// Begin the transfer of a [`DescriptorChain`] using `async` operation.
+    ///
+    /// If [`TriggerSource::Disable`] is used, a software trigger will be
+    /// issued to the DMA channel to launch the transfer. Completed blocks are
+    /// then reported by [`AsyncLinkedTransfer::next_block`].
+    ///
+    /// Returns [`Error::InvalidState`] if the chain is empty.
+    ///
+    /// # Safety
+    ///
+    /// The chain does not need to be `'static`, and the transfer starts
+    /// before this method returns. The returned [`AsyncLinkedTransfer`] stops
+    /// the transfer when dropped, and must therefore never be forgotten
+    /// through [`forget`](core::mem::forget) or by wrapping it with a
+    /// [`ManuallyDrop`](core::mem::ManuallyDrop). Otherwise, the DMAC keeps
+    /// accessing the memory of the chain after it is freed.
+    pub unsafe fn transfer_linked<'a, L: DescriptorChain>(
+        &'a mut self,
+        chain: &'a mut L,
+        trig_src: TriggerSource,
+        trig_act: TriggerAction,
+    ) -> Result<AsyncLinkedTransfer<'a, Id, L>> {
+        // SAFETY: The channel is not running, and the caller guarantees that the
+        // returned struct, which stops the transfer, is dropped before the chain.
+        let blocks = prepare(self, chain)?;
+        self.disable_interrupts(InterruptFlags::new().with_tcmpl(true).with_terr(true));
+        self._start_private(trig_src, trig_act);
+
+        Ok(AsyncLinkedTransfer {
+            chan: self,
+            chain,
+            blocks,
+        })
+    }
+}
+
+#[cfg(feature = "async")]
+impl<Id: ChId, L: DescriptorChain> AsyncLinkedTransfer<'_, Id, L> {
+    /// Wait for the next block of the chain to complete, and return its
+    /// index.
+    ///
+    /// Returns `None` once all the blocks of a one-shot chain have completed,
+    /// or [`Error::TransferError`] if the channel reported an error, in which
+    /// case the transfer has stopped.
+    pub async fn next_block(&mut self) -> Result<Option<usize>> {
+        use super::waker::WAKERS;
+        use core::task::Poll;
+
+        let flags = InterruptFlags::new().with_tcmpl(true).with_terr(true);
+        core::future::poll_fn(|cx| {
+            let ready = |result: nb::Result<usize, Error>| match result {
+                Ok(index) => Some(Ok(Some(index))),
+                Err(nb::Error::Other(Error::InvalidState)) => Some(Ok(None)),
+                Err(nb::Error::Other(e)) => Some(Err(e)),
+                Err(nb::Error::WouldBlock) => None,
+            };
+
+            if let Some(result) = ready(self.blocks.poll(self.chan)) {
+                return Poll::Ready(result);
+            }
+
+            WAKERS[Id::USIZE].register(cx.waker());
+            self.chan.enable_interrupts(flags);
+
+            if let Some(result) = ready(self.blocks.poll(self.chan)) {
+                self.chan.disable_interrupts(flags);
+                return Poll::Ready(result);
+            }
+
+            Poll::Pending
+        })
+        .await
+    }
+
+    /// Check if the transfer has completed. A circular chain only completes
+    /// if it is stopped by an error.
+    #[inline]
+    pub fn complete(&mut self) -> bool {
+        self.chan.xfer_complete()
+    }
+
+    /// Stop the transfer, whether or not it has completed.
+    #[inline]
+    pub fn stop(self) {
+        // Dropping stops the transfer
+    }
+}
+
+#[cfg(feature = "async")]
+impl<Id: ChId, B: Beat> AsyncLinkedTransfer<'_, Id, PingPong<'_, B>> {
+    /// Borrow the half of the buffer transferred by the last completed block,
+    /// which the DMAC does not access until the other half completes.
+    ///
+    /// Returns `None` until the first block completes, and if the DMAC is
+    /// already transferring that half again, which happens when block
+    /// notifications were missed.
+    #[inline]
+    pub fn completed_half(&mut self) -> Option<&mut [B]> {
+        let index = self.blocks.completed?;
+        self.chain.idle_half(Id::USIZE, index)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<Id: ChId, L: DescriptorChain> Drop for AsyncLinkedTransfer<'_, Id, L> {
+    fn drop(&mut self) {
+        self.chan.stop();
+    }
+}
@@ -8,9 +8,11 @@
 //! functions, including memory-to-memory,
 //! memory-to-peripheral, peripheral-to-memory,
 //! and peripheral-to-peripheral transfers.
-//! One-shot and circular transfers are supported. More complex
-//! transfer configurations, including multi-buffer
-//! (linked-list descriptor) transfers, are not currently supported.
+//! One-shot and circular transfers are supported, as well as multi-buffer
+//! (linked-list descriptor) transfers through the [`linked_list`] module.
+//!
+//! The DMAC also contains a CRC engine, which is accessed through the [`crc`]
+//! module.
 //!
 //! Transfers are supported for `i8`, `u8`, `i16`, `u16`, `i32`, `u32` and `f32`
 //! beat sizes.
@@ -252,7 +254,10 @@
 use atsamd_hal_macros::hal_cfg;
 
 pub use channel::*;
+pub use crc::*;
 pub use dma_controller::*;
+pub use linked_list::*;
+pub use sram::DmacDescriptor;
 pub use transfer::*;
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
@@ -292,6 +297,20 @@ impl From<Error> for crate::sercom::uart::Error {
     }
 }
 
+#[hal_cfg("i2s")]
+impl From<Error> for crate::i2s::Error {
+    fn from(value: Error) -> Self {
+        crate::i2s::Error::Dma(value)
+    }
+}
+
+#[hal_cfg("pcc")]
+impl From<Error> for crate::pcc::Error {
+    fn from(value: Error) -> Self {
+        crate::pcc::Error::Dma(value)
+    }
+}
+
 /// Result for DMAC operations
 pub type Result<T> = core::result::Result<T, Error>;
 
@@ -425,6 +444,10 @@ pub(crate) mod sram {
         }
     }
 
+    /// `BLOCKACT` value which raises the transfer complete interrupt at the
+    /// end of the block, and keeps the channel running
+    pub(super) const BLOCKACT_INT: u8 = 0x1;
+
     /// Descriptor representing a SRAM register. Datasheet section 19.8.2
     #[derive(Clone, Copy)]
     #[repr(C, align(16))]
@@ -464,9 +487,9 @@ pub(crate) mod sram {
     ///
     /// # Safety
     ///
-    /// This variable should never be accessed. The only thing we need
-    /// to know about it is its starting address, given by
-    /// [`writeback_addr`].
+    /// This variable should never be accessed, besides the volatile reads of
+    /// [`writeback_next_descriptor`]. Otherwise, the only thing we need to
+    /// know about it is its starting address, given by [`writeback_addr`].
     static WRITEBACK: [DescriptorCell; NUM_CHANNELS] =
         [const { DescriptorCell::default() }; NUM_CHANNELS];
 
@@ -508,10 +531,38 @@ pub(crate) mod sram {
     pub(super) unsafe fn get_descriptor(channel_id: usize) -> *mut DmacDescriptor {
         DESCRIPTOR_SECTION[channel_id].get()
     }
+
+    /// Returns whether the first descriptor of the specified channel raises
+    /// the transfer complete interrupt at the end of every block, in which
+    /// case the channel keeps running after the interrupt.
+    ///
+    /// # Safety
+    ///
+    /// Same as [`get_descriptor`]. The descriptor is only read.
+    #[inline]
+    pub(super) unsafe fn interrupts_every_block(channel_id: usize) -> bool {
+        let btctrl = core::ptr::addr_of!((*get_descriptor(channel_id)).btctrl).read_volatile();
+        btctrl.blockact() == BLOCKACT_INT
+    }
+
+    /// Returns the address of the descriptor following the block which the
+    /// specified channel is transferring, as written back by the DMAC.
+    ///
+    /// # Safety
+    ///
+    /// `channel_id` must be lower than [`NUM_CHANNELS`]. The write-back
+    /// descriptor is only valid once the channel has started its transfer.
+    #[inline]
+    pub(super) unsafe fn writeback_next_descriptor(channel_id: usize) -> *const DmacDescriptor {
+        let writeback = WRITEBACK[channel_id].get();
+        core::ptr::addr_of!((*writeback).descaddr).read_volatile()
+    }
 }
 
 pub mod channel;
+pub mod crc;
 pub mod dma_controller;
+pub mod linked_list;
 pub mod transfer;
 
 #[cfg(feature = "async")]
@@ -0,0 +1,948 @@
+//! # Analog Comparator
+//!
+//! The Analog Comparator (AC) compares the voltages of two inputs, and drives
+//! its output high when the positive input is above the negative input. Each
+//! chip has two comparators, which can also be paired in window mode.
+//!
+//! ## Steps to create an [`AnalogComparator`]
+//!
+//! 1. Create an [`Ac`] by calling [`Ac::new`]. This initializes the AC
+//!    peripheral and enables its clocks.
+//!
+//! 1. Split the [`Ac`] into its individual [`Comparators`] by calling
+//!    [`Ac::split`].
+//!
+//! 1. Select the inputs of a [`Comparator`] and turn it into an
+//!    [`AnalogComparator`] by calling [`Comparator::configure`].
+//!
+//! The positive input is one of the AIN pins, configured in
+//! [`AlternateB`](crate::gpio::AlternateB) mode. The negative input is either
+//! an AIN pin or one of the internal inputs: [`Ground`], [`Bandgap`],
+//! [`DacOutput`] or [`VddScaler`]. On SAMx5x, the [`VddScaler`] can also be
+//! used as the positive input.
+//!
+//! ```no_run
+//! use atsamd_hal::ac::{Ac, Config, VddScaler};
+//! use atsamd_hal::gpio::AlternateB;
+//!
+//! let mut ac = Ac::new(&mut peripherals.pm, peripherals.ac, ac_dig_clock, ac_ana_clock);
+//! let comparators = ac.split();
+//!
+//! // Compare PA04 to VDD / 2
+//! let ain0: Pin<PA04, AlternateB> = pins.pa04.into();
+//! let mut comp = comparators.0.configure(ain0, VddScaler::new(31), Config::default());
+//!
+//! let above = comp.state();
+//! ```
+//!
+//! ## Window mode
+//!
+//! Both comparators can be combined into a [`Window`], which detects whether
+//! a signal connected to both positive inputs lies inside or outside the
+//! window delimited by the two negative inputs.
+//!
+//! ## Events
+//!
+//! Each comparator can emit an event on every change of its output, and
+//! start a single-shot comparison on an incoming event. These events can be
+//! routed to and from other peripherals through the [`evsys`](crate::evsys).
+//!
+//! ## `async` operation <span class="stab portability" title="Available on crate feature `async` only"><code>async</code></span>
+//!
+//! Bind the `AC` interrupt source to the [`InterruptHandler`] (refer to the
+//! module-level [`async_hal`](crate::async_hal) documentation for more
+//! information), then turn the [`Ac`] into an async-enabled [`Ac`] by calling
+//! [`Ac::into_future`], before splitting it. The resulting
+//! [`AnalogComparator`]s provide an async
+//! [`wait_for_edge`](AnalogComparator::wait_for_edge) method.
+
+use core::marker::PhantomData;
+
+use atsamd_hal_macros::{hal_cfg, hal_macro_helper};
+
+use crate::gpio::{AlternateB, Pin};
+use crate::pac;
+use crate::typelevel::{NoneT, Sealed};
+
+#[hal_cfg(any("ac-d11", "ac-d21"))]
+use crate::clock::{AcAnaClock, AcDigClock};
+
+#[hal_cfg("ac-d5x")]
+use crate::clock::AcClock;
+
+#[hal_cfg("pa04")]
+use crate::gpio::PA04;
+#[hal_cfg("pa05")]
+use crate::gpio::PA05;
+#[hal_cfg("pa06")]
+use crate::gpio::PA06;
+#[hal_cfg("pa07")]
+use crate::gpio::PA07;
+
+/// Filter applied to the comparator output
+pub use pac::ac::compctrl::Flenselect as Filter;
+/// Condition triggering the comparator interrupt
+pub use pac::ac::compctrl::Intselselect as InterruptSelection;
+/// Comparator output routed to the CMP pin
+pub use pac::ac::compctrl::Outselect as Output;
+pub use pac::ac::compctrl::{Muxnegselect, Muxposselect};
+/// Position of the window input relative to the window
+pub use pac::ac::statusa::Wstate0select as WindowState;
+/// Condition triggering the window interrupt
+pub use pac::ac::winctrl::Wintsel0select as WindowInterrupt;
+
+/// Comparator speed
+#[hal_cfg(any("ac-d11", "ac-d21"))]
+pub use pac::ac::compctrl::Speedselect as Speed;
+
+/// Hysteresis level
+#[hal_cfg("ac-d5x")]
+pub use pac::ac::compctrl::Hystselect as Hysteresis;
+
+#[inline]
+fn ac() -> &'static pac::ac::RegisterBlock {
+    // SAFETY: Each `Comparator` only touches the register fields belonging to
+    // its own comparator. Shared registers are only modified through
+    // read-modify-write sequences of fields belonging to a single comparator.
+    unsafe { &*pac::Ac::ptr() }
+}
+
+/// Wait for register synchronization
+#[hal_cfg(any("ac-d11", "ac-d21"))]
+#[inline]
+fn sync() {
+    while ac().statusb().read().syncbusy().bit_is_set() {
+        core::hint::spin_loop();
+    }
+}
+
+/// Wait for register synchronization
+#[hal_cfg("ac-d5x")]
+#[inline]
+fn sync() {
+    while ac().syncbusy().read().bits() != 0 {
+        core::hint::spin_loop();
+    }
+}
+
+/// Run `f` while the AC is disabled, if the registers it writes are
+/// enable-protected on this chip.
+#[hal_cfg(any("ac-d11", "ac-d21"))]
+#[inline]
+fn with_protected<R>(f: impl FnOnce() -> R) -> R {
+    f()
+}
+
+/// Run `f` while the AC is disabled, if the registers it writes are
+/// enable-protected on this chip.
+#[hal_cfg("ac-d5x")]
+#[inline]
+fn with_protected<R>(f: impl FnOnce() -> R) -> R {
+    ac().ctrla().modify(|_, w| w.enable().clear_bit());
+    sync();
+    let r = f();
+    ac().ctrla().modify(|_, w| w.enable().set_bit());
+    sync();
+    r
+}
+
+//==============================================================================
+// Comparator IDs
+//==============================================================================
+
+/// Trait representing a comparator ID
+pub trait CompId: Sealed {
+    const ID: usize;
+}
+
+/// ID of comparator 0
+pub enum Comp0 {}
+
+impl Sealed for Comp0 {}
+
+impl CompId for Comp0 {
+    const ID: usize = 0;
+}
+
+/// ID of comparator 1
+pub enum Comp1 {}
+
+impl Sealed for Comp1 {}
+
+impl CompId for Comp1 {
+    const ID: usize = 1;
+}
+
+//==============================================================================
+// Inputs
+//==============================================================================
+
+/// Input which can be connected to the positive input of a comparator
+pub trait PositiveInput: Sealed {
+    /// Value of the `COMPCTRL.MUXPOS` field selecting this input
+    const MUX: Muxposselect;
+
+    /// Value to write to the `SCALER` register, if this input uses the VDD
+    /// scaler
+    #[inline]
+    fn scaler(&self) -> Option<u8> {
+        None
+    }
+}
+
+/// Input which can be connected to the negative input of a comparator
+pub trait NegativeInput: Sealed {
+    /// Value of the `COMPCTRL.MUXNEG` field selecting this input
+    const MUX: Muxnegselect;
+
+    /// Value to write to the `SCALER` register, if this input uses the VDD
+    /// scaler
+    #[inline]
+    fn scaler(&self) -> Option<u8> {
+        None
+    }
+}
+
+macro_rules! ain_pins {
+    ($($(#[$cfg:meta])* $Id:ident: $Mux:ident),+ $(,)?) => {
+        $(
+            $(#[$cfg])*
+            impl PositiveInput for Pin<$Id, AlternateB> {
+                const MUX: Muxposselect = Muxposselect::$Mux;
+            }
+
+            $(#[$cfg])*
+            impl NegativeInput for Pin<$Id, AlternateB> {
+                const MUX: Muxnegselect = Muxnegselect::$Mux;
+            }
+        )+
+    };
+}
+
+ain_pins!(
+    #[hal_cfg("pa04")]
+    PA04: Pin0,
+    #[hal_cfg("pa05")]
+    PA05: Pin1,
+    #[hal_cfg("pa06")]
+    PA06: Pin2,
+    #[hal_cfg("pa07")]
+    PA07: Pin3,
+);
+
+/// Internal ground, used as a negative input
+pub struct Ground;
+
+impl Sealed for Ground {}
+
+impl NegativeInput for Ground {
+    const MUX: Muxnegselect = Muxnegselect::Gnd;
+}
+
+/// Internal bandgap voltage reference, used as a negative input
+pub struct Bandgap;
+
+impl Sealed for Bandgap {}
+
+impl NegativeInput for Bandgap {
+    const MUX: Muxnegselect = Muxnegselect::Bandgap;
+}
+
+/// DAC output, used as a negative input
+///
+/// On SAMx5x, this is the output of DAC0.
+pub struct DacOutput;
+
+impl Sealed for DacOutput {}
+
+impl NegativeInput for DacOutput {
+    const MUX: Muxnegselect = Muxnegselect::Dac;
+}
+
+/// VDD voltage scaler
+///
+/// The scaler outputs `VDD * (value + 1) / 64`. Each comparator has its own
+/// scaler.
+pub struct VddScaler {
+    value: u8,
+}
+
+impl VddScaler {
+    /// Create a new [`VddScaler`]. Only the lower 6 bits of `value` are
+    /// used.
+    #[inline]
+    pub fn new(value: u8) -> Self {
+        Self {
+            value: value & 0x3F,
+        }
+    }
+}
+
+impl Sealed for VddScaler {}
+
+impl NegativeInput for VddScaler {
+    const MUX: Muxnegselect = Muxnegselect::Vscale;
+
+    #[inline]
+    fn scaler(&self) -> Option<u8> {
+        Some(self.value)
+    }
+}
+
+#[hal_cfg("ac-d5x")]
+impl PositiveInput for VddScaler {
+    const MUX: Muxposselect = Muxposselect::Vscale;
+
+    #[inline]
+    fn scaler(&self) -> Option<u8> {
+        Some(self.value)
+    }
+}
+
+//==============================================================================
+// Config
+//==============================================================================
+
+/// Comparator configuration
+#[hal_macro_helper]
+#[derive(Clone, Copy)]
+pub struct Config {
+    /// Only perform a comparison when requested, instead of comparing
+    /// continuously.
+    pub single_shot: bool,
+    /// Enable hysteresis.
+    #[hal_cfg(any("ac-d11", "ac-d21"))]
+    pub hysteresis: bool,
+    /// Hysteresis level, or `None` to disable hysteresis.
+    #[hal_cfg("ac-d5x")]
+    pub hysteresis: Option<Hysteresis>,
+    /// Comparator speed. A higher speed increases the power consumption.
+    #[hal_cfg(any("ac-d11", "ac-d21"))]
+    pub speed: Speed,
+    /// Keep the comparator running in standby sleep mode.
+    #[hal_cfg("ac-d5x")]
+    pub run_in_standby: bool,
+    /// Output filter.
+    pub filter: Filter,
+    /// Drive the comparator output to its CMP pin. The pin must be
+    /// configured separately.
+    pub output: Output,
+    /// Condition triggering the comparator interrupt.
+    pub interrupt: InterruptSelection,
+    /// Swap the positive and negative inputs.
+    pub swap: bool,
+}
+
+impl Default for Config {
+    #[hal_macro_helper]
+    fn default() -> Self {
+        Self {
+            single_shot: false,
+            #[hal_cfg(any("ac-d11", "ac-d21"))]
+            hysteresis: false,
+            #[hal_cfg("ac-d5x")]
+            hysteresis: None,
+            #[hal_cfg(any("ac-d11", "ac-d21"))]
+            speed: Speed::High,
+            #[hal_cfg("ac-d5x")]
+            run_in_standby: false,
+            filter: Filter::Off,
+            output: Output::Off,
+            interrupt: InterruptSelection::Toggle,
+            swap: false,
+        }
+    }
+}
+
+//==============================================================================
+// Ac
+//==============================================================================
+
+/// Marker type for an [`Ac`] capable of doing async operations.
+#[cfg(feature = "async")]
+pub enum AcFuture {}
+
+/// Analog Comparator peripheral
+///
+/// Use [`split`](Self::split) to obtain the individual [`Comparators`].
+pub struct Ac<I = NoneT> {
+    ac: pac::Ac,
+    _irqs: PhantomData<I>,
+}
+
+/// Struct holding a handle to each comparator
+pub struct Comparators<I = NoneT>(pub Comparator<Comp0, I>, pub Comparator<Comp1, I>);
+
+impl Ac {
+    /// Create a new [`Ac`], reset and enable the peripheral.
+    #[hal_cfg(any("ac-d11", "ac-d21"))]
+    pub fn new(
+        pm: &mut pac::Pm,
+        ac: pac::Ac,
+        _dig_clock: AcDigClock,
+        _ana_clock: AcAnaClock,
+    ) -> Self {
+        pm.apbcmask().modify(|_, w| w.ac_().set_bit());
+        Self::init(ac)
+    }
+
+    /// Create a new [`Ac`], reset and enable the peripheral.
+    #[hal_cfg("ac-d5x")]
+    pub fn new(mclk: &mut pac::Mclk, ac: pac::Ac, _clock: AcClock) -> Self {
+        mclk.apbcmask().modify(|_, w| w.ac_().set_bit());
+        let ac = Self::init(ac);
+        with_protected(|| {
+            ac.ac
+                .calib()
+                .write(|w| unsafe { w.bias0().bits(crate::calibration::ac_bias0_cal()) });
+        });
+        ac
+    }
+
+    #[inline]
+    fn init(ac: pac::Ac) -> Self {
+        let mut ac = Self {
+            ac,
+            _irqs: PhantomData,
+        };
+        ac.swreset();
+        ac.ac.ctrla().write(|w| w.enable().set_bit());
+        sync();
+        ac
+    }
+}
+
+impl<I> Ac<I> {
+    /// Split the [`Ac`] into individual [`Comparators`].
+    #[inline]
+    pub fn split(&mut self) -> Comparators<I> {
+        Comparators(Comparator::new(), Comparator::new())
+    }
+
+    /// Keep the AC running in standby sleep mode.
+    #[hal_cfg(any("ac-d11", "ac-d21"))]
+    #[inline]
+    pub fn run_in_standby(&mut self, run_in_standby: bool) {
+        self.ac
+            .ctrla()
+            .modify(|_, w| w.runstdby().bit(run_in_standby));
+        sync();
+    }
+
+    /// Reset the AC and return the register block.
+    ///
+    /// **Note**: The [`Comparators`] struct is consumed by this method. This
+    /// means that any [`AnalogComparator`] must be
+    /// [`free`](AnalogComparator::free)d, and the resulting [`Comparator`]
+    /// moved back into the [`Comparators`] struct.
+    #[inline]
+    pub fn free(mut self, _comparators: Comparators<I>) -> pac::Ac {
+        self.swreset();
+        self.ac
+    }
+
+    #[inline]
+    fn swreset(&mut self) {
+        self.ac.ctrla().write(|w| w.swrst().set_bit());
+        sync();
+    }
+}
+
+//==============================================================================
+// Comparator
+//==============================================================================
+
+/// Unconfigured comparator
+///
+/// Use [`configure`](Self::configure) to select its inputs and turn it into
+/// an [`AnalogComparator`].
+pub struct Comparator<Id: CompId, I = NoneT> {
+    _id: PhantomData<Id>,
+    _irqs: PhantomData<I>,
+}
+
+impl<Id: CompId, I> Comparator<Id, I> {
+    #[inline]
+    fn new() -> Self {
+        Self {
+            _id: PhantomData,
+            _irqs: PhantomData,
+        }
+    }
+
+    /// Connect the inputs of the comparator, configure and enable it.
+    #[hal_macro_helper]
+    pub fn configure<P, N>(self, pos: P, neg: N, config: Config) -> AnalogComparator<Id, P, N, I>
+    where
+        P: PositiveInput,
+        N: NegativeInput,
+    {
+        if let Some(value) = pos.scaler().or(neg.scaler()) {
+            ac().scaler(Id::ID)
+                .write(|w| unsafe { w.value().bits(value) });
+        }
+
+        let compctrl = ac().compctrl(Id::ID);
+        compctrl.write(|w| {
+            w.single().bit(config.single_shot);
+            w.intsel().variant(config.interrupt);
+            w.muxpos().variant(P::MUX);
+            w.muxneg().variant(N::MUX);
+            w.swap().bit(config.swap);
+            w.flen().variant(config.filter);
+            w.out().variant(config.output);
+
+            #[hal_cfg(any("ac-d11", "ac-d21"))]
+            {
+                w.speed().variant(config.speed);
+                w.hyst().bit(config.hysteresis);
+            }
+
+            #[hal_cfg("ac-d5x")]
+            {
+                w.speed().high();
+                w.runstdby().bit(config.run_in_standby);
+                if let Some(hyst) = config.hysteresis {
+                    w.hysten().set_bit();
+                    w.hyst().variant(hyst);
+                }
+            }
+
+            w
+        });
+        sync();
+
+        let mut comp = AnalogComparator {
+            comp: self,
+            pos,
+            neg,
+        };
+        comp.enable();
+        comp
+    }
+}
+
+//==============================================================================
+// AnalogComparator
+//==============================================================================
+
+/// Configured comparator, comparing the positive input `P` to the negative
+/// input `N`
+pub struct AnalogComparator<Id: CompId, P, N, I = NoneT> {
+    comp: Comparator<Id, I>,
+    pos: P,
+    neg: N,
+}
+
+impl<Id, P, N, I> AnalogComparator<Id, P, N, I>
+where
+    Id: CompId,
+    P: PositiveInput,
+    N: NegativeInput,
+{
+    /// Return the comparator output, which is `true` when the positive input
+    /// is above the negative input.
+    ///
+    /// In single-shot mode, a new comparison is started and waited upon.
+    #[inline]
+    pub fn state(&mut self) -> bool {
+        if self.is_single_shot() {
+            self.start();
+        }
+        while !self.is_ready() {
+            core::hint::spin_loop();
+        }
+        self.read_state()
+    }
+
+    /// Start a single-shot comparison.
+    #[inline]
+    pub fn start(&mut self) {
+        ac().ctrlb().write(|w| match Id::ID {
+            0 => w.start0().set_bit(),
+            _ => w.start1().set_bit(),
+        });
+    }
+
+    /// Return `true` once the comparator output is valid.
+    #[inline]
+    pub fn is_ready(&self) -> bool {
+        let statusb = ac().statusb().read();
+        match Id::ID {
+            0 => statusb.ready0().bit_is_set(),
+            _ => statusb.ready1().bit_is_set(),
+        }
+    }
+
+    /// Change the condition triggering the comparator interrupt.
+    ///
+    /// The comparator is restarted, and its output is invalid until
+    /// [`is_ready`](Self::is_ready) returns `true`.
+    #[inline]
+    pub fn set_interrupt_selection(&mut self, interrupt: InterruptSelection) {
+        self.disable();
+        ac().compctrl(Id::ID)
+            .modify(|_, w| w.intsel().variant(interrupt));
+        sync();
+        self.enable();
+    }
+
+    /// Enable the comparator interrupt.
+    #[inline]
+    pub fn enable_interrupt(&mut self) {
+        ac().intenset().write(|w| match Id::ID {
+            0 => w.comp0().set_bit(),
+            _ => w.comp1().set_bit(),
+        });
+    }
+
+    /// Disable the comparator interrupt.
+    #[inline]
+    pub fn disable_interrupt(&mut self) {
+        ac().intenclr().write(|w| match Id::ID {
+            0 => w.comp0().set_bit(),
+            _ => w.comp1().set_bit(),
+        });
+    }
+
+    /// Return `true` if the comparator interrupt flag is set.
+    #[inline]
+    pub fn is_interrupt(&self) -> bool {
+        let intflag = ac().intflag().read();
+        match Id::ID {
+            0 => intflag.comp0().bit_is_set(),
+            _ => intflag.comp1().bit_is_set(),
+        }
+    }
+
+    /// Clear the comparator interrupt flag.
+    #[inline]
+    pub fn clear_interrupt(&mut self) {
+        ac().intflag().write(|w| match Id::ID {
+            0 => w.comp0().set_bit(),
+            _ => w.comp1().set_bit(),
+        });
+    }
+
+    /// Emit an event every time the comparator output changes, as selected
+    /// by [`Config::interrupt`].
+    ///
+    /// On SAMx5x, the AC is briefly disabled while the event configuration
+    /// is changed.
+    #[inline]
+    pub fn event_output(&mut self, enabled: bool) {
+        with_protected(|| {
+            ac().evctrl().modify(|_, w| match Id::ID {
+                0 => w.compeo0().bit(enabled),
+                _ => w.compeo1().bit(enabled),
+            })
+        });
+    }
+
+    /// Start a single-shot comparison on every incoming event.
+    ///
+    /// On SAMx5x, the AC is briefly disabled while the event configuration
+    /// is changed.
+    #[inline]
+    pub fn event_input(&mut self, enabled: bool) {
+        with_protected(|| {
+            ac().evctrl().modify(|_, w| match Id::ID {
+                0 => w.compei0().bit(enabled),
+                _ => w.compei1().bit(enabled),
+            })
+        });
+    }
+
+    /// Invert the incoming event.
+    ///
+    /// The AC is briefly disabled while the event configuration is changed.
+    #[hal_cfg("ac-d5x")]
+    #[inline]
+    pub fn invert_event_input(&mut self, inverted: bool) {
+        with_protected(|| {
+            ac().evctrl().modify(|_, w| match Id::ID {
+                0 => w.invei0().bit(inverted),
+                _ => w.invei1().bit(inverted),
+            })
+        });
+    }
+
+    /// Disable the comparator, and return the [`Comparator`] and its inputs.
+    #[inline]
+    pub fn free(mut self) -> (Comparator<Id, I>, P, N) {
+        self.disable();
+        (self.comp, self.pos, self.neg)
+    }
+
+    #[inline]
+    fn is_single_shot(&self) -> bool {
+        ac().compctrl(Id::ID).read().single().bit_is_set()
+    }
+
+    #[inline]
+    fn read_state(&self) -> bool {
+        let statusa = ac().statusa().read();
+        match Id::ID {
+            0 => statusa.state0().bit_is_set(),
+            _ => statusa.state1().bit_is_set(),
+        }
+    }
+
+    #[inline]
+    fn enable(&mut self) {
+        ac().compctrl(Id::ID).modify(|_, w| w.enable().set_bit());
+        sync();
+    }
+
+    #[inline]
+    fn disable(&mut self) {
+        ac().compctrl(Id::ID).modify(|_, w| w.enable().clear_bit());
+        sync();
+    }
+}
+
+//==============================================================================
+// Window
+//==============================================================================
+
+/// Reserved window state value read by [`Window::state`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct InvalidWindowState(pub u8);
+
+/// Both comparators operating in window mode
+///
+/// The positive inputs of both comparators should be connected to the same
+/// signal. The window is delimited by the negative inputs of the two
+/// comparators.
+pub struct Window<P0, N0, P1, N1, I = NoneT> {
+    comp0: AnalogComparator<Comp0, P0, N0, I>,
+    comp1: AnalogComparator<Comp1, P1, N1, I>,
+}
+
+impl<P0, N0, P1, N1, I> Window<P0, N0, P1, N1, I>
+where
+    P0: PositiveInput,
+    N0: NegativeInput,
+    P1: PositiveInput,
+    N1: NegativeInput,
+{
+    /// Combine both comparators in window mode.
+    #[inline]
+    pub fn new(
+        comp0: AnalogComparator<Comp0, P0, N0, I>,
+        comp1: AnalogComparator<Comp1, P1, N1, I>,
+        interrupt: WindowInterrupt,
+    ) -> Self {
+        // WINCTRL is enable-protected on SAMx5x, where the comparators are
+        // already enabled
+        with_protected(|| {
+            ac().winctrl().write(|w| {
+                w.wen0().set_bit();
+                w.wintsel0().variant(interrupt)
+            });
+            sync();
+        });
+        Self { comp0, comp1 }
+    }
+
+    /// Return the position of the input signal relative to the window.
+    ///
+    /// Returns [`InvalidWindowState`] if the hardware reports the reserved
+    /// state value.
+    #[inline]
+    pub fn state(&self) -> Result<WindowState, InvalidWindowState> {
+        let wstate = ac().statusa().read().wstate0();
+        wstate.variant().ok_or(InvalidWindowState(wstate.bits()))
+    }
+
+    /// Enable the window interrupt.
+    #[inline]
+    pub fn enable_interrupt(&mut self) {
+        ac().intenset().write(|w| w.win0().set_bit());
+    }
+
+    /// Disable the window interrupt.
+    #[inline]
+    pub fn disable_interrupt(&mut self) {
+        ac().intenclr().write(|w| w.win0().set_bit());
+    }
+
+    /// Return `true` if the window interrupt flag is set.
+    #[inline]
+    pub fn is_interrupt(&self) -> bool {
+        ac().intflag().read().win0().bit_is_set()
+    }
+
+    /// Clear the window interrupt flag.
+    #[inline]
+    pub fn clear_interrupt(&mut self) {
+        ac().intflag().write(|w| w.win0().set_bit());
+    }
+
+    /// Emit an event every time the window interrupt condition is met.
+    ///
+    /// On SAMx5x, the AC is briefly disabled while the event configuration
+    /// is changed.
+    #[inline]
+    pub fn event_output(&mut self, enabled: bool) {
+        with_protected(|| ac().evctrl().modify(|_, w| w.wineo0().bit(enabled)));
+    }
+
+    /// Leave window mode, and return both comparators.
+    #[inline]
+    #[allow(clippy::type_complexity)]
+    pub fn free(
+        self,
+    ) -> (
+        AnalogComparator<Comp0, P0, N0, I>,
+        AnalogComparator<Comp1, P1, N1, I>,
+    ) {
+        ac().winctrl().write(|w| w.wen0().clear_bit());
+        sync();
+        (self.comp0, self.comp1)
+    }
+}
+
+//==============================================================================
+// async
+//==============================================================================
+
+#[cfg(feature = "async")]
+mod async_api {
+    use core::future::poll_fn;
+    use core::task::Poll;
+
+    use embassy_sync::waitqueue::AtomicWaker;
+
+    use super::*;
+    use crate::async_hal::interrupts::{Binding, Handler, Interrupt, AC as AcInterrupt};
+
+    /// Index of the window waker in [`WAKERS`]
+    const WINDOW: usize = 2;
+
+    /// INTFLAG masks of the comparator 0, comparator 1 and window interrupts,
+    /// indexed like [`WAKERS`]
+    const MASKS: [u8; 3] = [1 << 0, 1 << 1, 1 << 4];
+
+    #[allow(clippy::declare_interior_mutable_const)]
+    const NEW_WAKER: AtomicWaker = AtomicWaker::new();
+    static WAKERS: [AtomicWaker; 3] = [NEW_WAKER; 3];
+
+    /// Interrupt handler for the AC peripheral.
+    pub struct InterruptHandler {
+        _private: (),
+    }
+
+    impl Sealed for InterruptHandler {}
+
+    impl Handler<AcInterrupt> for InterruptHandler {
+        unsafe fn on_interrupt() {
+            let ac = ac();
+            let pending = ac.intflag().read().bits() & ac.intenset().read().bits();
+            for (waker, mask) in WAKERS.iter().zip(MASKS) {
+                if pending & mask != 0 {
+                    // Disable the interrupt but don't clear; will be cleared
+                    // when future is next polled.
+                    ac.intenclr().write(|w| w.bits(mask));
+                    waker.wake();
+                }
+            }
+        }
+    }
+
+    impl Ac {
+        /// Turn an [`Ac`] into an async-enabled [`Ac`]. The correct interrupt
+        /// source is needed.
+        pub fn into_future<I>(self, _irq: I) -> Ac<AcFuture>
+        where
+            I: Binding<AcInterrupt, InterruptHandler>,
+        {
+            AcInterrupt::unpend();
+            unsafe { AcInterrupt::enable() };
+
+            Ac {
+                ac: self.ac,
+                _irqs: PhantomData,
+            }
+        }
+    }
+
+    impl<Id, P, N> AnalogComparator<Id, P, N, AcFuture>
+    where
+        Id: CompId,
+        P: PositiveInput,
+        N: NegativeInput,
+    {
+        /// Wait until the comparator output matches the `edge` condition.
+        ///
+        /// If the comparator is not currently configured for `edge`, it is
+        /// restarted with the new interrupt selection.
+        pub async fn wait_for_edge(&mut self, edge: InterruptSelection) {
+            let current = ac().compctrl(Id::ID).read().intsel().variant();
+            if current != edge {
+                self.set_interrupt_selection(edge);
+                while !self.is_ready() {
+                    core::hint::spin_loop();
+                }
+            }
+            self.clear_interrupt();
+
+            poll_fn(|cx| {
+                if self.is_interrupt() {
+                    self.clear_interrupt();
+                    self.disable_interrupt();
+                    return Poll::Ready(());
+                }
+
+                WAKERS[Id::ID].register(cx.waker());
+                self.enable_interrupt();
+
+                if self.is_interrupt() {
+                    self.clear_interrupt();
+                    self.disable_interrupt();
+                    return Poll::Ready(());
+                }
+
+                Poll::Pending
+            })
+            .await;
+        }
+    }
+
+    impl<P0, N0, P1, N1> Window<P0, N0, P1, N1, AcFuture>
+    where
+        P0: PositiveInput,
+        N0: NegativeInput,
+        P1: PositiveInput,
+        N1: NegativeInput,
+    {
+        /// Wait until the window interrupt condition is met.
+        pub async fn wait(&mut self) {
+            self.clear_interrupt();
+
+            poll_fn(|cx| {
+                if self.is_interrupt() {
+                    self.clear_interrupt();
+                    self.disable_interrupt();
+                    return Poll::Ready(());
+                }
+
+                WAKERS[WINDOW].register(cx.waker());
+                self.enable_interrupt();
+
+                if self.is_interrupt() {
+                    self.clear_interrupt();
+                    self.disable_interrupt();
+                    return Poll::Ready(());
+                }
+
+                Poll::Pending
+            })
+            .await;
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+pub use async_api::InterruptHandler;
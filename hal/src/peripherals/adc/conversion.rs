@@ -0,0 +1,219 @@
+//! Conversion of internal ADC channel results to physical values
+//!
+//! Nothing in here touches the hardware, so that the arithmetic can be tested
+//! on the host.
+
+use atsamd_hal_macros::hal_cfg;
+
+#[hal_cfg("adc-d5x")]
+use crate::nvm::TemperaturesCalibrationArea;
+
+/// Full scale value of a 12-bit conversion
+const FULL_SCALE: f32 = 4095.0;
+
+/// Convert a 12-bit `result`, measured against a `reference` voltage from an
+/// input scaled down by `scale`, back to volts.
+pub(super) fn scaled_voltage(result: u16, reference: f32, scale: f32) -> f32 {
+    result as f32 / FULL_SCALE * reference * scale
+}
+
+/// Temperature sensor calibration values for the SAMD11 and SAMD21
+///
+/// These are stored in the temperature log row of the NVM software
+/// calibration area.
+#[hal_cfg(any("adc-d11", "adc-d21"))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) struct TemperatureLog {
+    /// Room temperature, in °C
+    room_temp: f32,
+    /// Hot temperature, in °C
+    hot_temp: f32,
+    /// Actual voltage of the internal 1V reference at room temperature
+    room_int1v: f32,
+    /// Actual voltage of the internal 1V reference at hot temperature
+    hot_int1v: f32,
+    /// 12-bit ADC result at room temperature
+    room_adc: f32,
+    /// 12-bit ADC result at hot temperature
+    hot_adc: f32,
+}
+
+#[hal_cfg(any("adc-d11", "adc-d21"))]
+impl TemperatureLog {
+    /// Decode the 64-bit temperature log row.
+    pub(super) fn from_raw(raw: u64) -> Self {
+        let field = |shift: u32, width: u32| ((raw >> shift) & ((1 << width) - 1)) as u32;
+        // The reference drift is a two's complement value, in mV.
+        let int1v = |drift: u32| 1.0 - (drift as u8 as i8) as f32 / 1000.0;
+
+        Self {
+            room_temp: field(0, 8) as f32 + field(8, 4) as f32 / 10.0,
+            hot_temp: field(12, 8) as f32 + field(20, 4) as f32 / 10.0,
+            room_int1v: int1v(field(24, 8)),
+            hot_int1v: int1v(field(32, 8)),
+            room_adc: field(40, 12) as f32,
+            hot_adc: field(52, 12) as f32,
+        }
+    }
+
+    /// Convert a 12-bit temperature sensor result, measured against the
+    /// internal 1V reference, to °C.
+    ///
+    /// A first, coarse temperature is used to estimate the actual voltage of
+    /// the internal reference, which is then used to compute the final
+    /// temperature. See application note AT11481.
+    pub(super) fn temperature(&self, result: u16) -> f32 {
+        let room_voltage = self.room_adc * self.room_int1v / FULL_SCALE;
+        let hot_voltage = self.hot_adc * self.hot_int1v / FULL_SCALE;
+        let interpolate = |voltage: f32| {
+            self.room_temp
+                + (self.hot_temp - self.room_temp) * (voltage - room_voltage)
+                    / (hot_voltage - room_voltage)
+        };
+
+        let coarse = interpolate(scaled_voltage(result, 1.0, 1.0));
+
+        let int1v = self.room_int1v
+            + (self.hot_int1v - self.room_int1v) * (coarse - self.room_temp)
+                / (self.hot_temp - self.room_temp);
+        interpolate(scaled_voltage(result, int1v, 1.0))
+    }
+}
+
+/// Temperature sensor calibration values for the SAMx5x
+#[hal_cfg("adc-d5x")]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) struct TemperatureCalibration {
+    /// Low temperature, in °C
+    tl: f32,
+    /// High temperature, in °C
+    th: f32,
+    /// PTAT result at low temperature
+    vpl: f32,
+    /// PTAT result at high temperature
+    vph: f32,
+    /// CTAT result at low temperature
+    vcl: f32,
+    /// CTAT result at high temperature
+    vch: f32,
+}
+
+#[hal_cfg("adc-d5x")]
+impl TemperatureCalibration {
+    /// Extract the calibration values from the NVM temperature calibration
+    /// area.
+    pub(super) fn from_area(area: &TemperaturesCalibrationArea) -> Self {
+        Self {
+            tl: area.tli() as f32 + area.tld() as f32 / 10.0,
+            th: area.thi() as f32 + area.thd() as f32 / 10.0,
+            vpl: area.vpl() as f32,
+            vph: area.vph() as f32,
+            vcl: area.vcl() as f32,
+            vch: area.vch() as f32,
+        }
+    }
+
+    /// Compute the temperature in °C from a pair of PTAT and CTAT results.
+    ///
+    /// This is the formula given in the "Device Temperature Measurement"
+    /// section of the ADC chapter of the datasheet. Using both sensors
+    /// cancels out the dependency on the reference voltage.
+    pub(super) fn temperature(&self, ptat: u16, ctat: u16) -> f32 {
+        let Self {
+            tl,
+            th,
+            vpl,
+            vph,
+            vcl,
+            vch,
+        } = *self;
+        let tp = ptat as f32;
+        let tc = ctat as f32;
+
+        (tl * vph * tc - vpl * th * tc - tl * vch * tp + th * vcl * tp)
+            / (vcl * tp - vch * tp - vpl * tc + vph * tc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!(
+            (actual - expected).abs() < 0.01,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn scaled_voltage_full_scale() {
+        assert_close(scaled_voltage(4095, 1.0, 4.0), 4.0);
+        assert_close(scaled_voltage(0, 1.0, 4.0), 0.0);
+        assert_close(scaled_voltage(2048, 1.0, 1.0), 0.5);
+    }
+
+    #[hal_cfg(any("adc-d11", "adc-d21"))]
+    #[test]
+    fn temperature_log_decoding() {
+        // 25.3°C, 85.1°C, +5mV / -3mV reference drift, 2500 and 2900
+        let raw = 25 | 3 << 8 | 85 << 12 | 1 << 20 | 5 << 24 | 0xfd << 32 | 2500 << 40 | 2900 << 52;
+        let log = TemperatureLog::from_raw(raw);
+
+        assert_close(log.room_temp, 25.3);
+        assert_close(log.hot_temp, 85.1);
+        assert_close(log.room_int1v, 0.995);
+        assert_close(log.hot_int1v, 1.003);
+        assert_close(log.room_adc, 2500.0);
+        assert_close(log.hot_adc, 2900.0);
+    }
+
+    #[hal_cfg(any("adc-d11", "adc-d21"))]
+    #[test]
+    fn temperature_log_interpolation() {
+        let ideal = TemperatureLog {
+            room_temp: 25.0,
+            hot_temp: 85.0,
+            room_int1v: 1.0,
+            hot_int1v: 1.0,
+            room_adc: 2000.0,
+            hot_adc: 2400.0,
+        };
+        assert_close(ideal.temperature(2000), 25.0);
+        assert_close(ideal.temperature(2200), 55.0);
+        assert_close(ideal.temperature(2400), 85.0);
+
+        let drifting = TemperatureLog {
+            room_temp: 25.3,
+            hot_temp: 85.1,
+            room_int1v: 0.995,
+            hot_int1v: 1.003,
+            room_adc: 2500.0,
+            hot_adc: 2900.0,
+        };
+        assert_close(drifting.temperature(2700), 55.10);
+    }
+
+    #[hal_cfg("adc-d5x")]
+    #[test]
+    fn ptat_ctat_compensation() {
+        let calibration = TemperatureCalibration {
+            tl: 25.0,
+            th: 85.0,
+            vpl: 2000.0,
+            vph: 2400.0,
+            vcl: 3600.0,
+            vch: 3000.0,
+        };
+        assert_close(calibration.temperature(2000, 3600), 25.0);
+        assert_close(calibration.temperature(2200, 3300), 55.0);
+        assert_close(calibration.temperature(2400, 3000), 85.0);
+
+        let offset = TemperatureCalibration {
+            tl: 25.5,
+            th: 85.5,
+            ..calibration
+        };
+        assert_close(offset.temperature(2200, 3300), 55.5);
+    }
+}
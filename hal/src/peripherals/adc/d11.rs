@@ -1,11 +1,59 @@
 //! Analogue-to-Digital Conversion
+//!
+//! ## Differential conversions
+//!
+//! [`Adc::read_differential`] samples the difference between a positive input
+//! and a [`NegativeChannel`]. Only the pins mapped to `AIN0` through `AIN7` can
+//! be used as the negative input. Differential results are signed.
+//!
+//! ## Averaging, oversampling and correction
+//!
+//! [`Adc::averaging`] and [`Adc::oversampling`] use the hardware accumulator
+//! to average several samples, or to increase the resolution up to 16 bits.
+//! [`Adc::correction`] applies gain and offset correction to every result.
+//!
+//! ## Window monitor
+//!
+//! [`Adc::window_monitor`] compares every result against a lower and an upper
+//! threshold. Combined with free-running conversions started by
+//! [`Adc::start_monitoring`] and the window interrupt, a voltage can be
+//! watched without polling.
+//!
+//! ## Internal channels
+//!
+//! [`Adc::read_temperature`] measures the on-die temperature sensor and
+//! applies the factory calibration from the NVM temperature log row.
+//! [`Adc::read_vddio`] measures the I/O supply voltage, and
+//! [`Adc::read_bandgap`] samples the internal bandgap reference.
+//!
+//! ## DMA
+//!
+//! With the `dma` feature enabled, [`Adc::read_buffer`] fills a slice with
+//! consecutive conversions of a single pin. The ADC runs in free-running
+//! mode, and each result ready (RESRDY) DMA request moves one sample into the
+//! buffer.
+//!
+//! ## `async` operation <span class="stab portability" title="Available on crate feature `async` only"><code>async</code></span>
+//!
+//! Bind the `ADC` interrupt source to the [`InterruptHandler`] (refer to the
+//! module-level [`async_hal`](crate::async_hal) documentation for more
+//! information), then turn the [`Adc`] into an [`AdcFuture`] by calling
+//! [`Adc::into_future`].
 use atsamd_hal_macros::hal_cfg;
 
+use crate::calibration;
 use crate::clock::GenericClockController;
 use crate::ehal_02::adc::{Channel, OneShot};
 use crate::gpio::*;
 use crate::pac::{self, adc, Pm};
 
+use adc::inputctrl::Muxposselect;
+
+mod conversion;
+
+#[cfg(feature = "dma")]
+use crate::dmac::{self, AnyChannel, Buffer, Ready, TriggerAction, TriggerSource};
+
 /// Samples per reading
 pub use adc::avgctrl::Samplenumselect as SampleRate;
 /// Clock frequency relative to the system clock
@@ -19,6 +67,63 @@ pub use adc::ctrlb::Resselselect as Resolution;
 pub use adc::inputctrl::Gainselect as Gain;
 /// Reference voltage (or its source)
 pub use adc::refctrl::Refselselect as Reference;
+/// Window monitor mode
+///
+/// * `Mode1`: RESULT > WINLT
+/// * `Mode2`: RESULT < WINUT
+/// * `Mode3`: WINLT < RESULT < WINUT
+/// * `Mode4`: !(WINLT < RESULT < WINUT)
+pub use adc::winctrl::Winmodeselect as WindowMode;
+
+/// Increased resolution obtained by oversampling and decimation
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Oversampling {
+    /// 13-bit results, from 4 samples
+    Bits13,
+    /// 14-bit results, from 16 samples
+    Bits14,
+    /// 15-bit results, from 64 samples
+    Bits15,
+    /// 16-bit results, from 256 samples
+    Bits16,
+}
+
+impl Oversampling {
+    /// Number of samples to accumulate, and the matching ADJRES value
+    fn settings(self) -> (SampleRate, u8) {
+        // Table 32-3 (32.6.7) specifies the samplenum and adjres values for
+        // each oversampled resolution.
+        match self {
+            Oversampling::Bits13 => (SampleRate::_4, 1),
+            Oversampling::Bits14 => (SampleRate::_16, 2),
+            Oversampling::Bits15 => (SampleRate::_64, 1),
+            Oversampling::Bits16 => (SampleRate::_256, 0),
+        }
+    }
+}
+
+/// Gain and offset correction
+///
+/// The corrected result is `(RESULT - offset) * gain`, where `gain` is a
+/// 12-bit fixed point value with 11 fractional bits (`0x800` is a gain of 1).
+/// `offset` is a 12-bit two's complement value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Correction {
+    pub gain: u16,
+    pub offset: i16,
+}
+
+impl Default for Correction {
+    fn default() -> Self {
+        Self {
+            gain: 0x800,
+            offset: 0,
+        }
+    }
+}
+
+/// Pins which can be used as the negative input of a differential conversion
+pub trait NegativeChannel<ADC>: Channel<ADC, ID = u8> {}
 
 /// `Adc` encapsulates the device ADC
 pub struct Adc<ADC> {
@@ -84,6 +189,148 @@ impl Adc<pac::Adc> {
         while self.adc.status().read().syncbusy().bit_is_set() {}
     }
 
+    /// Average `samples` conversions into each result.
+    ///
+    /// Averaging requires 16-bit accumulation, so the resolution is changed
+    /// accordingly. Use [`Adc::samples`] with a single sample to disable
+    /// averaging, then set the desired [`Resolution`].
+    pub fn averaging(&mut self, samples: SampleRate) {
+        self.resolution(Resolution::_16bit);
+        self.samples(samples);
+    }
+
+    /// Accumulate and decimate several conversions to increase the result
+    /// resolution.
+    pub fn oversampling(&mut self, oversampling: Oversampling) {
+        let (samples, adjres) = oversampling.settings();
+        self.resolution(Resolution::_16bit);
+        self.adc.avgctrl().modify(|_, w| {
+            w.samplenum().variant(samples);
+            unsafe { w.adjres().bits(adjres) }
+        });
+        while self.adc.status().read().syncbusy().bit_is_set() {}
+    }
+
+    /// Enable or disable gain and offset correction.
+    pub fn correction(&mut self, correction: Option<Correction>) {
+        if let Some(correction) = correction {
+            self.adc
+                .gaincorr()
+                .write(|w| unsafe { w.gaincorr().bits(correction.gain & 0xfff) });
+            while self.adc.status().read().syncbusy().bit_is_set() {}
+            self.adc
+                .offsetcorr()
+                .write(|w| unsafe { w.offsetcorr().bits(correction.offset as u16 & 0xfff) });
+            while self.adc.status().read().syncbusy().bit_is_set() {}
+        }
+        self.adc
+            .ctrlb()
+            .modify(|_, w| w.corren().bit(correction.is_some()));
+        while self.adc.status().read().syncbusy().bit_is_set() {}
+    }
+
+    /// Configure the window monitor.
+    ///
+    /// Results are compared against the `lower` and `upper` thresholds after
+    /// averaging and correction. In differential mode, the thresholds are
+    /// interpreted as two's complement values.
+    pub fn window_monitor(&mut self, mode: WindowMode, lower: u16, upper: u16) {
+        self.adc.winlt().write(|w| unsafe { w.winlt().bits(lower) });
+        while self.adc.status().read().syncbusy().bit_is_set() {}
+        self.adc.winut().write(|w| unsafe { w.winut().bits(upper) });
+        while self.adc.status().read().syncbusy().bit_is_set() {}
+        self.adc.winctrl().write(|w| w.winmode().variant(mode));
+        while self.adc.status().read().syncbusy().bit_is_set() {}
+    }
+
+    /// Enable the window monitor interrupt.
+    pub fn enable_window_interrupt(&mut self) {
+        self.adc.intenset().write(|w| w.winmon().set_bit());
+    }
+
+    /// Disable the window monitor interrupt.
+    pub fn disable_window_interrupt(&mut self) {
+        self.adc.intenclr().write(|w| w.winmon().set_bit());
+    }
+
+    /// Check whether the window monitor condition has been met.
+    pub fn is_window_interrupt(&self) -> bool {
+        self.adc.intflag().read().winmon().bit_is_set()
+    }
+
+    /// Clear the window monitor interrupt flag.
+    pub fn clear_window_interrupt(&mut self) {
+        self.adc.intflag().write(|w| w.winmon().set_bit());
+    }
+
+    /// Start free-running conversions of `pin`, so that the window monitor
+    /// continuously checks its voltage.
+    pub fn start_monitoring<PIN>(&mut self, pin: &mut PIN)
+    where
+        PIN: Channel<pac::Adc, ID = u8>,
+    {
+        self.mux(pin);
+        self.enable_freerunning();
+        self.power_up();
+        self.adc.swtrig().modify(|_, w| w.start().set_bit());
+    }
+
+    /// Stop the free-running conversions started by
+    /// [`start_monitoring`](Adc::start_monitoring).
+    pub fn stop_monitoring(&mut self) {
+        self.power_down();
+        self.disable_freerunning();
+    }
+
+    /// Sample the difference between `pos` and `neg`.
+    pub fn read_differential<P, N>(&mut self, _pos: &mut P, _neg: &mut N) -> i16
+    where
+        P: Channel<pac::Adc, ID = u8>,
+        N: NegativeChannel<pac::Adc>,
+    {
+        self.set_inputs(P::channel(), Some(N::channel()));
+        self.power_up();
+        let result = self.convert();
+        self.power_down();
+        result as i16
+    }
+
+    /// Measure the die temperature, in °C.
+    ///
+    /// The temperature sensor is enabled in `SYSCTRL`, and the conversion is
+    /// made against the internal 1V reference. The previous ADC
+    /// configuration is restored afterwards.
+    pub fn read_temperature(&mut self) -> f32 {
+        sysctrl().vref().modify(|_, w| w.tsen().set_bit());
+        let result = self.convert_internal(Muxposselect::Temp);
+
+        conversion::TemperatureLog::from_raw(calibration::temperature_log_row()).temperature(result)
+    }
+
+    /// Measure the I/O supply voltage, in volts.
+    ///
+    /// The conversion is made against the internal 1V reference, from the
+    /// 1/4 scaled VDDIO input. The previous ADC configuration is restored
+    /// afterwards.
+    pub fn read_vddio(&mut self) -> f32 {
+        let result = self.convert_internal(Muxposselect::Scalediovcc);
+        conversion::scaled_voltage(result, 1.0, 4.0)
+    }
+
+    /// Sample the internal bandgap reference, using the current ADC
+    /// configuration.
+    ///
+    /// As the bandgap voltage is known, this can be used to compute the
+    /// actual voltage of an external or supply-derived [`Reference`].
+    pub fn read_bandgap(&mut self) -> u16 {
+        sysctrl().vref().modify(|_, w| w.bgouten().set_bit());
+        self.set_inputs(Muxposselect::Bandgap.into(), None);
+        self.power_up();
+        let result = self.convert();
+        self.power_down();
+        result
+    }
+
     /// Set the gain factor
     pub fn gain(&mut self, gain: Gain) {
         self.adc.inputctrl().modify(|_, w| w.gain().variant(gain));
@@ -142,6 +389,168 @@ impl Adc<pac::Adc> {
 
         self.adc.result().read().result().bits()
     }
+
+    /// Sets the mux to a particular pin, for single-ended conversions.
+    fn mux<PIN: Channel<pac::Adc, ID = u8>>(&mut self, _pin: &mut PIN) {
+        self.set_inputs(PIN::channel(), None);
+    }
+
+    /// Select the positive input, and either a negative input for
+    /// differential conversions or ground for single-ended conversions.
+    fn set_inputs(&mut self, positive: u8, negative: Option<u8>) {
+        while self.adc.status().read().syncbusy().bit_is_set() {}
+        self.adc.inputctrl().modify(|_, w| {
+            unsafe { w.muxpos().bits(positive) };
+            match negative {
+                Some(negative) => unsafe { w.muxneg().bits(negative) },
+                None => w.muxneg().gnd(),
+            }
+        });
+        while self.adc.status().read().syncbusy().bit_is_set() {}
+
+        self.adc
+            .ctrlb()
+            .modify(|_, w| w.diffmode().bit(negative.is_some()));
+        while self.adc.status().read().syncbusy().bit_is_set() {}
+    }
+
+    /// Convert an internal input with the internal 1V reference, unity gain,
+    /// 12-bit resolution and no averaging or correction, then restore the
+    /// previous configuration.
+    fn convert_internal(&mut self, input: Muxposselect) -> u16 {
+        let refctrl = self.adc.refctrl().read().bits();
+        let avgctrl = self.adc.avgctrl().read().bits();
+        let ctrlb = self.adc.ctrlb().read().bits();
+        let inputctrl = self.adc.inputctrl().read().bits();
+
+        self.adc.refctrl().write(|w| w.refsel().int1v());
+        while self.adc.status().read().syncbusy().bit_is_set() {}
+        self.adc.avgctrl().reset();
+        while self.adc.status().read().syncbusy().bit_is_set() {}
+        self.adc.ctrlb().modify(|_, w| {
+            w.ressel()._12bit();
+            w.corren().clear_bit();
+            w.leftadj().clear_bit();
+            w.freerun().clear_bit()
+        });
+        while self.adc.status().read().syncbusy().bit_is_set() {}
+        self.adc.inputctrl().modify(|_, w| w.gain()._1x());
+        self.set_inputs(input.into(), None);
+
+        self.power_up();
+        let result = self.convert();
+        self.power_down();
+
+        self.adc.refctrl().write(|w| unsafe { w.bits(refctrl) });
+        while self.adc.status().read().syncbusy().bit_is_set() {}
+        self.adc.avgctrl().write(|w| unsafe { w.bits(avgctrl) });
+        while self.adc.status().read().syncbusy().bit_is_set() {}
+        self.adc.ctrlb().write(|w| unsafe { w.bits(ctrlb) });
+        while self.adc.status().read().syncbusy().bit_is_set() {}
+        self.adc.inputctrl().write(|w| unsafe { w.bits(inputctrl) });
+        while self.adc.status().read().syncbusy().bit_is_set() {}
+
+        result
+    }
+
+    fn enable_freerunning(&mut self) {
+        self.adc.ctrlb().modify(|_, w| w.freerun().set_bit());
+        while self.adc.status().read().syncbusy().bit_is_set() {}
+    }
+
+    fn disable_freerunning(&mut self) {
+        self.adc.ctrlb().modify(|_, w| w.freerun().clear_bit());
+        while self.adc.status().read().syncbusy().bit_is_set() {}
+    }
+}
+
+#[cfg(feature = "dma")]
+impl Adc<pac::Adc> {
+    /// Fill `buffer` with consecutive conversions of `pin`, using a DMA
+    /// channel to move each result to memory.
+    ///
+    /// The ADC runs in free-running mode for the duration of the transfer,
+    /// so the sample rate is set by the ADC clock, prescaler and sampling
+    /// time. This method blocks until the buffer has been filled.
+    pub fn read_buffer<Ch, PIN>(
+        &mut self,
+        pin: &mut PIN,
+        mut buffer: &mut [u16],
+        channel: &mut Ch,
+    ) -> Result<(), dmac::Error>
+    where
+        Ch: AnyChannel<Status = Ready>,
+        PIN: Channel<pac::Adc, ID = u8>,
+    {
+        if buffer.is_empty() {
+            return Ok(());
+        }
+
+        let mut result = ResultPtr(self.adc.result().as_ptr());
+        let channel = channel.as_mut();
+
+        self.mux(pin);
+        self.power_up();
+        // The first conversion after the reference is changed must not be used.
+        let _ = self.convert();
+        self.enable_freerunning();
+
+        // SAFETY: We make sure that the DMA transfer is complete or stopped
+        // before returning, so `buffer` outlives the transfer.
+        unsafe {
+            channel.transfer(
+                &mut result,
+                &mut buffer,
+                TriggerSource::AdcResrdy,
+                TriggerAction::Beat,
+                None,
+            )?;
+        }
+        self.adc.swtrig().modify(|_, w| w.start().set_bit());
+
+        while !channel.xfer_complete() {
+            core::hint::spin_loop();
+        }
+
+        // Defensively disable the channel
+        channel.stop();
+
+        self.disable_freerunning();
+        self.power_down();
+        channel.xfer_success()
+    }
+}
+
+/// Access the `SYSCTRL` peripheral, which owns the temperature sensor and
+/// bandgap output enables.
+fn sysctrl() -> &'static pac::sysctrl::RegisterBlock {
+    // SAFETY: Only the VREF register is modified through this reference. It
+    // isn't used by the clocking API, which owns the `Sysctrl` peripheral.
+    unsafe { &*pac::Sysctrl::ptr() }
+}
+
+/// Pointer to the ADC result register, for use as a DMA source.
+#[cfg(feature = "dma")]
+struct ResultPtr(*mut u16);
+
+#[cfg(feature = "dma")]
+unsafe impl Buffer for ResultPtr {
+    type Beat = u16;
+
+    #[inline]
+    fn dma_ptr(&mut self) -> *mut Self::Beat {
+        self.0
+    }
+
+    #[inline]
+    fn incrementing(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn buffer_len(&self) -> usize {
+        1
+    }
 }
 
 impl<WORD, PIN> OneShot<pac::Adc, WORD, PIN> for Adc<pac::Adc>
@@ -151,13 +560,8 @@ where
 {
     type Error = ();
 
-    fn read(&mut self, _pin: &mut PIN) -> nb::Result<WORD, Self::Error> {
-        let chan = PIN::channel();
-        while self.adc.status().read().syncbusy().bit_is_set() {}
-
-        self.adc
-            .inputctrl()
-            .modify(|_, w| unsafe { w.muxpos().bits(chan) });
+    fn read(&mut self, pin: &mut PIN) -> nb::Result<WORD, Self::Error> {
+        self.mux(pin);
         self.power_up();
         let result = self.convert();
         self.power_down();
@@ -166,6 +570,246 @@ where
     }
 }
 
+//==============================================================================
+// async
+//==============================================================================
+
+#[cfg(feature = "async")]
+mod async_api {
+    use core::future::poll_fn;
+    use core::task::Poll;
+
+    use embassy_sync::waitqueue::AtomicWaker;
+
+    use super::*;
+    use crate::async_hal::interrupts::{Binding, Handler, Interrupt, ADC as AdcInterrupt};
+    use crate::typelevel::Sealed;
+
+    #[cfg(feature = "dma")]
+    use core::{future::Future, pin::pin};
+
+    #[cfg(feature = "dma")]
+    use crate::dmac::ReadyFuture;
+
+    static WAKER: AtomicWaker = AtomicWaker::new();
+
+    /// Keeps the ADC converting in free-running mode, and stops it when
+    /// dropped, so that a cancelled [`AdcFuture::read_buffer`] doesn't leave
+    /// the ADC running.
+    #[cfg(feature = "dma")]
+    struct FreeRunning<'a>(&'a mut Adc<pac::Adc>);
+
+    #[cfg(feature = "dma")]
+    impl<'a> FreeRunning<'a> {
+        fn start(adc: &'a mut Adc<pac::Adc>) -> Self {
+            adc.enable_freerunning();
+            adc.adc.swtrig().modify(|_, w| w.start().set_bit());
+            Self(adc)
+        }
+    }
+
+    #[cfg(feature = "dma")]
+    impl Drop for FreeRunning<'_> {
+        fn drop(&mut self) {
+            self.0.disable_freerunning();
+            self.0.power_down();
+        }
+    }
+
+    /// Interrupt handler for the ADC peripheral.
+    pub struct InterruptHandler {
+        _private: (),
+    }
+
+    impl Sealed for InterruptHandler {}
+
+    impl Handler<AdcInterrupt> for InterruptHandler {
+        unsafe fn on_interrupt() {
+            // SAFETY: We only touch the interrupt enable register, which the
+            // `AdcFuture` expects to be cleared from this handler.
+            let adc = unsafe { &*pac::Adc::ptr() };
+            let pending = adc.intflag().read().bits() & adc.intenset().read().bits();
+            if pending != 0 {
+                // Disable the interrupts but don't clear the flags; they will
+                // be cleared when the future is next polled.
+                adc.intenclr().write(|w| unsafe { w.bits(pending) });
+                WAKER.wake();
+            }
+        }
+    }
+
+    impl Adc<pac::Adc> {
+        /// Turn an [`Adc`] into an [`AdcFuture`]. The correct interrupt
+        /// source is needed.
+        pub fn into_future<I>(self, _irq: I) -> AdcFuture
+        where
+            I: Binding<AdcInterrupt, InterruptHandler>,
+        {
+            AdcInterrupt::unpend();
+            unsafe { AdcInterrupt::enable() };
+
+            AdcFuture { adc: self }
+        }
+    }
+
+    /// `async` version of an [`Adc`].
+    ///
+    /// Create this struct by calling [`Adc::into_future`].
+    pub struct AdcFuture {
+        adc: Adc<pac::Adc>,
+    }
+
+    impl AdcFuture {
+        /// Sample `pin`, waiting asynchronously for the conversion to
+        /// complete.
+        pub async fn read<PIN>(&mut self, pin: &mut PIN) -> u16
+        where
+            PIN: Channel<pac::Adc, ID = u8>,
+        {
+            self.adc.mux(pin);
+            self.adc.power_up();
+
+            // The first conversion after the reference is changed must not be
+            // used.
+            let _ = self.convert().await;
+            let result = self.convert().await;
+
+            self.adc.power_down();
+            result
+        }
+
+        /// Sample the difference between `pos` and `neg`, waiting
+        /// asynchronously for the conversion to complete.
+        pub async fn read_differential<P, N>(&mut self, _pos: &mut P, _neg: &mut N) -> i16
+        where
+            P: Channel<pac::Adc, ID = u8>,
+            N: NegativeChannel<pac::Adc>,
+        {
+            self.adc.set_inputs(P::channel(), Some(N::channel()));
+            self.adc.power_up();
+
+            // The first conversion after the reference is changed must not be
+            // used.
+            let _ = self.convert().await;
+            let result = self.convert().await;
+
+            self.adc.power_down();
+            result as i16
+        }
+
+        /// Convert `pin` continuously until the window monitor condition
+        /// configured with [`Adc::window_monitor`] is met, then return the
+        /// result which triggered it.
+        pub async fn wait_for_window<PIN>(&mut self, pin: &mut PIN) -> u16
+        where
+            PIN: Channel<pac::Adc, ID = u8>,
+        {
+            self.adc.clear_window_interrupt();
+            self.adc.start_monitoring(pin);
+
+            poll_fn(|cx| {
+                if self.adc.is_window_interrupt() {
+                    return Poll::Ready(());
+                }
+
+                WAKER.register(cx.waker());
+                self.adc.enable_window_interrupt();
+
+                if self.adc.is_window_interrupt() {
+                    self.adc.disable_window_interrupt();
+                    return Poll::Ready(());
+                }
+
+                Poll::Pending
+            })
+            .await;
+
+            let result = self.adc.adc.result().read().result().bits();
+            self.adc.stop_monitoring();
+            self.adc.clear_window_interrupt();
+            result
+        }
+
+        /// Fill `buffer` with consecutive conversions of `pin`, using a DMA
+        /// channel to move each result to memory.
+        ///
+        /// See [`Adc::read_buffer`] for more information.
+        #[cfg(feature = "dma")]
+        pub async fn read_buffer<Ch, PIN>(
+            &mut self,
+            pin: &mut PIN,
+            buffer: &mut [u16],
+            channel: &mut Ch,
+        ) -> Result<(), dmac::Error>
+        where
+            Ch: AnyChannel<Status = ReadyFuture>,
+            PIN: Channel<pac::Adc, ID = u8>,
+        {
+            if buffer.is_empty() {
+                return Ok(());
+            }
+
+            self.adc.mux(pin);
+            self.adc.power_up();
+            // The first conversion after the reference is changed must not be
+            // used.
+            let _ = self.convert().await;
+
+            let mut transfer = pin!(channel.as_mut().transfer_future(
+                ResultPtr(self.adc.adc.result().as_ptr()),
+                buffer,
+                TriggerSource::AdcResrdy,
+                TriggerAction::Beat,
+            ));
+
+            // Arm the DMA channel before starting the conversions
+            if let Poll::Ready(result) = poll_fn(|cx| Poll::Ready(transfer.as_mut().poll(cx))).await
+            {
+                self.adc.power_down();
+                return result;
+            }
+
+            let _running = FreeRunning::start(&mut self.adc);
+            transfer.await
+        }
+
+        /// Return the underlying [`Adc`].
+        pub fn free(self) -> Adc<pac::Adc> {
+            self.adc
+        }
+
+        async fn convert(&mut self) -> u16 {
+            let adc = &self.adc.adc;
+            adc.intflag().write(|w| w.resrdy().set_bit());
+            adc.swtrig().modify(|_, w| w.start().set_bit());
+
+            poll_fn(|cx| {
+                if adc.intflag().read().resrdy().bit_is_set() {
+                    return Poll::Ready(());
+                }
+
+                WAKER.register(cx.waker());
+                adc.intenset().write(|w| w.resrdy().set_bit());
+
+                if adc.intflag().read().resrdy().bit_is_set() {
+                    adc.intenclr().write(|w| w.resrdy().set_bit());
+                    return Poll::Ready(());
+                }
+
+                Poll::Pending
+            })
+            .await;
+
+            while adc.status().read().syncbusy().bit_is_set() {}
+            // Reading the result clears the RESRDY flag
+            adc.result().read().result().bits()
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+pub use async_api::{AdcFuture, InterruptHandler};
+
 macro_rules! adc_pins {
     (
         $(
@@ -184,6 +828,21 @@ macro_rules! adc_pins {
     }
 }
 
+macro_rules! negative_pins {
+    (
+        $(
+            $( #[$cfg:meta] )?
+            $PinId:ident
+        ),+
+        $(,)?
+    ) => {
+        $(
+            $( #[$cfg] )?
+            impl NegativeChannel<$crate::pac::Adc> for Pin<$PinId, AlternateB> {}
+        )+
+    }
+}
+
 #[hal_cfg("adc-d11")]
 adc_pins! {
     #[hal_cfg("pa02")]
@@ -251,3 +910,43 @@ adc_pins! {
     #[hal_cfg("pa11")]
     PA11: 19,
 }
+
+#[hal_cfg("adc-d11")]
+negative_pins! {
+    #[hal_cfg("pa02")]
+    PA02,
+    #[hal_cfg("pa03")]
+    PA03,
+    #[hal_cfg("pa04")]
+    PA04,
+    #[hal_cfg("pa05")]
+    PA05,
+    #[hal_cfg("pa06")]
+    PA06,
+    #[hal_cfg("pa07")]
+    PA07,
+    #[hal_cfg("pa14")]
+    PA14,
+    #[hal_cfg("pa15")]
+    PA15,
+}
+
+#[hal_cfg("adc-d21")]
+negative_pins! {
+    #[hal_cfg("pa02")]
+    PA02,
+    #[hal_cfg("pa03")]
+    PA03,
+    #[hal_cfg("pb08")]
+    PB08,
+    #[hal_cfg("pb09")]
+    PB09,
+    #[hal_cfg("pa04")]
+    PA04,
+    #[hal_cfg("pa05")]
+    PA05,
+    #[hal_cfg("pa06")]
+    PA06,
+    #[hal_cfg("pa07")]
+    PA07,
+}
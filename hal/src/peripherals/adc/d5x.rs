@@ -1,4 +1,45 @@
 //! Analogue-to-Digital Conversion
+//!
+//! ## Differential conversions
+//!
+//! `read_differential` samples the difference between a positive input and a
+//! [`NegativeChannel`]. Only the pins mapped to `AIN0` through `AIN7` of an ADC
+//! can be used as its negative input. Differential results are signed.
+//!
+//! ## Averaging, oversampling and correction
+//!
+//! `averaging` and `oversampling` use the hardware accumulator to average
+//! several samples, or to increase the resolution up to 16 bits. `correction`
+//! applies gain and offset correction to every result.
+//!
+//! ## Window monitor
+//!
+//! `window_monitor` compares every result against a lower and an upper
+//! threshold. Combined with free-running conversions started by
+//! `start_monitoring` and the window interrupt, a voltage can be watched
+//! without polling.
+//!
+//! ## Internal channels
+//!
+//! `read_temperature` measures the on-die PTAT and CTAT temperature sensors,
+//! and combines them using the factory calibration from the NVM temperature
+//! calibration area. `read_vddio` measures the I/O supply voltage, and
+//! `read_bandgap` samples the internal bandgap reference.
+//!
+//! ## DMA
+//!
+//! With the `dma` feature enabled, [`Adc::read_buffer`] fills a slice with
+//! consecutive conversions of a single pin. The ADC runs in free-running
+//! mode, and each result ready (RESRDY) DMA request moves one sample into the
+//! buffer.
+//!
+//! ## `async` operation <span class="stab portability" title="Available on crate feature `async` only"><code>async</code></span>
+//!
+//! Bind the `ADC0` or `ADC1` interrupt sources to the matching
+//! [`InterruptHandler`] (refer to the module-level
+//! [`async_hal`](crate::async_hal) documentation for more information), then
+//! turn the [`Adc`] into an [`AdcFuture`] by calling `into_future`. Each ADC
+//! has its own interrupt sources and waker, so both can be used concurrently.
 use atsamd_hal_macros::hal_cfg;
 
 use crate::clock::GenericClockController;
@@ -7,9 +48,17 @@ use crate::gpio::*;
 use crate::ehal_02::adc::{Channel, OneShot};
 use crate::pac::gclk::genctrl::Srcselect::Dfll;
 use crate::pac::gclk::pchctrl::Genselect;
-use crate::pac::{adc0, Adc0, Adc1, Mclk};
+use crate::pac::{self, adc0, Adc0, Adc1, Mclk};
 
 use crate::calibration;
+use crate::nvm::read_temperatures_calibration_area;
+
+use adc0::inputctrl::Muxposselect;
+
+mod conversion;
+
+#[cfg(feature = "dma")]
+use crate::dmac::{self, AnyChannel, Buffer, Ready, TriggerAction, TriggerSource};
 
 /// Samples per reading
 pub use adc0::avgctrl::Samplenumselect as SampleRate;
@@ -17,9 +66,66 @@ pub use adc0::avgctrl::Samplenumselect as SampleRate;
 pub use adc0::ctrla::Prescalerselect as Prescaler;
 /// Reading resolution in bits
 pub use adc0::ctrlb::Resselselect as Resolution;
+/// Window monitor mode
+///
+/// * `Mode1`: RESULT > WINLT
+/// * `Mode2`: RESULT < WINUT
+/// * `Mode3`: WINLT < RESULT < WINUT
+/// * `Mode4`: !(WINLT < RESULT < WINUT)
+pub use adc0::ctrlb::Winmodeselect as WindowMode;
 /// Reference voltage (or its source)
 pub use adc0::refctrl::Refselselect as Reference;
 
+/// Increased resolution obtained by oversampling and decimation
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Oversampling {
+    /// 13-bit results, from 4 samples
+    Bits13,
+    /// 14-bit results, from 16 samples
+    Bits14,
+    /// 15-bit results, from 64 samples
+    Bits15,
+    /// 16-bit results, from 256 samples
+    Bits16,
+}
+
+impl Oversampling {
+    /// Number of samples to accumulate, and the matching ADJRES value
+    fn settings(self) -> (SampleRate, u8) {
+        // Table 45-3 (45.6.2.10) specifies the samplenum and adjres values
+        // for each oversampled resolution.
+        match self {
+            Oversampling::Bits13 => (SampleRate::_4, 1),
+            Oversampling::Bits14 => (SampleRate::_16, 2),
+            Oversampling::Bits15 => (SampleRate::_64, 1),
+            Oversampling::Bits16 => (SampleRate::_256, 0),
+        }
+    }
+}
+
+/// Gain and offset correction
+///
+/// The corrected result is `(RESULT - offset) * gain`, where `gain` is a
+/// 12-bit fixed point value with 11 fractional bits (`0x800` is a gain of 1).
+/// `offset` is a 12-bit two's complement value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Correction {
+    pub gain: u16,
+    pub offset: i16,
+}
+
+impl Default for Correction {
+    fn default() -> Self {
+        Self {
+            gain: 0x800,
+            offset: 0,
+        }
+    }
+}
+
+/// Pins which can be used as the negative input of a differential conversion
+pub trait NegativeChannel<ADC>: Channel<ADC, ID = u8> {}
+
 /// An ADC where results are accessible via interrupt servicing.
 pub struct InterruptAdc<ADC, C>
 where
@@ -45,8 +151,39 @@ pub trait ConversionMode<ADC> {
 pub struct SingleConversion;
 pub struct FreeRunning;
 
+/// Access the `SUPC` peripheral, which owns the temperature sensors enable.
+fn supc() -> &'static pac::supc::RegisterBlock {
+    // SAFETY: Only the VREF register is modified through this reference, and
+    // no other part of the HAL uses it.
+    unsafe { &*pac::Supc::ptr() }
+}
+
+/// Pointer to an ADC result register, for use as a DMA source.
+#[cfg(feature = "dma")]
+struct ResultPtr(*mut u16);
+
+#[cfg(feature = "dma")]
+unsafe impl Buffer for ResultPtr {
+    type Beat = u16;
+
+    #[inline]
+    fn dma_ptr(&mut self) -> *mut Self::Beat {
+        self.0
+    }
+
+    #[inline]
+    fn incrementing(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn buffer_len(&self) -> usize {
+        1
+    }
+}
+
 macro_rules! adc_hal {
-    ($($ADC:ident: ($init:ident, $mclk:ident, $apmask:ident, $compcal:ident, $refcal:ident, $r2rcal:ident),)+) => {
+    ($($ADC:ident: ($init:ident, $mclk:ident, $apmask:ident, $compcal:ident, $refcal:ident, $r2rcal:ident, $trigger:ident),)+) => {
         $(
 impl Adc<$ADC> {
     pub fn $init(adc: $ADC, mclk: &mut Mclk, clocks: &mut GenericClockController, gclk:Genselect) -> Self {
@@ -120,6 +257,187 @@ impl Adc<$ADC> {
         while self.adc.syncbusy().read().ctrlb().bit_is_set() {}
     }
 
+    /// Average `samples` conversions into each result.
+    ///
+    /// Averaging requires 16-bit accumulation, so the resolution is changed
+    /// accordingly. Use `samples` with a single sample to disable averaging,
+    /// then set the desired [`Resolution`].
+    pub fn averaging(&mut self, samples: SampleRate) {
+        self.resolution(Resolution::_16bit);
+        self.samples(samples);
+    }
+
+    /// Accumulate and decimate several conversions to increase the result
+    /// resolution.
+    pub fn oversampling(&mut self, oversampling: Oversampling) {
+        let (samples, adjres) = oversampling.settings();
+        self.resolution(Resolution::_16bit);
+        self.adc.avgctrl().modify(|_, w| {
+            w.samplenum().variant(samples);
+            unsafe { w.adjres().bits(adjres) }
+        });
+        while self.adc.syncbusy().read().avgctrl().bit_is_set() {}
+    }
+
+    /// Enable or disable gain and offset correction.
+    pub fn correction(&mut self, correction: Option<Correction>) {
+        if let Some(correction) = correction {
+            self.adc
+                .gaincorr()
+                .write(|w| unsafe { w.gaincorr().bits(correction.gain & 0xfff) });
+            while self.adc.syncbusy().read().gaincorr().bit_is_set() {}
+            self.adc
+                .offsetcorr()
+                .write(|w| unsafe { w.offsetcorr().bits(correction.offset as u16 & 0xfff) });
+            while self.adc.syncbusy().read().offsetcorr().bit_is_set() {}
+        }
+        self.adc
+            .ctrlb()
+            .modify(|_, w| w.corren().bit(correction.is_some()));
+        while self.adc.syncbusy().read().ctrlb().bit_is_set() {}
+    }
+
+    /// Configure the window monitor.
+    ///
+    /// Results are compared against the `lower` and `upper` thresholds after
+    /// averaging and correction. In differential mode, the thresholds are
+    /// interpreted as two's complement values.
+    pub fn window_monitor(&mut self, mode: WindowMode, lower: u16, upper: u16) {
+        self.adc.winlt().write(|w| unsafe { w.winlt().bits(lower) });
+        while self.adc.syncbusy().read().winlt().bit_is_set() {}
+        self.adc.winut().write(|w| unsafe { w.winut().bits(upper) });
+        while self.adc.syncbusy().read().winut().bit_is_set() {}
+        self.adc.ctrlb().modify(|_, w| w.winmode().variant(mode));
+        while self.adc.syncbusy().read().ctrlb().bit_is_set() {}
+    }
+
+    /// Enable the window monitor interrupt.
+    pub fn enable_window_interrupt(&mut self) {
+        self.adc.intenset().write(|w| w.winmon().set_bit());
+    }
+
+    /// Disable the window monitor interrupt.
+    pub fn disable_window_interrupt(&mut self) {
+        self.adc.intenclr().write(|w| w.winmon().set_bit());
+    }
+
+    /// Check whether the window monitor condition has been met.
+    pub fn is_window_interrupt(&self) -> bool {
+        self.adc.intflag().read().winmon().bit_is_set()
+    }
+
+    /// Clear the window monitor interrupt flag.
+    pub fn clear_window_interrupt(&mut self) {
+        self.adc.intflag().write(|w| w.winmon().set_bit());
+    }
+
+    /// Start free-running conversions of `pin`, so that the window monitor
+    /// continuously checks its voltage.
+    pub fn start_monitoring<PIN: Channel<$ADC, ID=u8>>(&mut self, pin: &mut PIN) {
+        self.mux(pin);
+        self.enable_freerunning();
+        self.power_up();
+        self.start_conversion();
+    }
+
+    /// Stop the free-running conversions started by `start_monitoring`.
+    pub fn stop_monitoring(&mut self) {
+        self.power_down();
+        self.disable_freerunning();
+    }
+
+    /// Sample the difference between `pos` and `neg`.
+    pub fn read_differential<P, N>(&mut self, _pos: &mut P, _neg: &mut N) -> i16
+    where
+        P: Channel<$ADC, ID=u8>,
+        N: NegativeChannel<$ADC>,
+    {
+        self.set_inputs(P::channel(), Some(N::channel()));
+        self.power_up();
+        let result = self.synchronous_convert();
+        self.power_down();
+        result as i16
+    }
+
+    /// Measure the die temperature, in °C.
+    ///
+    /// The temperature sensors are enabled in `SUPC`, and both the PTAT and
+    /// CTAT sensors are converted against the internal reference. The
+    /// previous ADC configuration is restored afterwards.
+    pub fn read_temperature(&mut self) -> f32 {
+        supc().vref().modify(|_, w| {
+            w.tsen().set_bit();
+            w.ondemand().set_bit()
+        });
+        let ptat = self.convert_internal(Muxposselect::Ptat);
+        let ctat = self.convert_internal(Muxposselect::Ctat);
+
+        conversion::TemperatureCalibration::from_area(&read_temperatures_calibration_area())
+            .temperature(ptat, ctat)
+    }
+
+    /// Measure the I/O supply voltage, in volts.
+    ///
+    /// The conversion is made against the internal reference, from the 1/4
+    /// scaled VDDIO input. The internal reference is assumed to be set to its
+    /// default 1.0V level. The previous ADC configuration is restored
+    /// afterwards.
+    pub fn read_vddio(&mut self) -> f32 {
+        let result = self.convert_internal(Muxposselect::Scalediovcc);
+        conversion::scaled_voltage(result, 1.0, 4.0)
+    }
+
+    /// Sample the internal bandgap reference, using the current ADC
+    /// configuration.
+    ///
+    /// As the bandgap voltage is known, this can be used to compute the
+    /// actual voltage of an external or supply-derived [`Reference`].
+    pub fn read_bandgap(&mut self) -> u16 {
+        self.set_inputs(Muxposselect::Bandgap.into(), None);
+        self.power_up();
+        let result = self.synchronous_convert();
+        self.power_down();
+        result
+    }
+
+    /// Convert an internal input with the internal reference, 12-bit
+    /// resolution and no averaging or correction, then restore the previous
+    /// configuration.
+    fn convert_internal(&mut self, input: Muxposselect) -> u16 {
+        let refctrl = self.adc.refctrl().read().bits();
+        let avgctrl = self.adc.avgctrl().read().bits();
+        let ctrlb = self.adc.ctrlb().read().bits();
+        let inputctrl = self.adc.inputctrl().read().bits();
+
+        self.adc.refctrl().write(|w| w.refsel().intref());
+        while self.adc.syncbusy().read().refctrl().bit_is_set() {}
+        self.adc.avgctrl().reset();
+        while self.adc.syncbusy().read().avgctrl().bit_is_set() {}
+        self.adc.ctrlb().modify(|_, w| {
+            w.ressel()._12bit();
+            w.corren().clear_bit();
+            w.leftadj().clear_bit();
+            w.freerun().clear_bit()
+        });
+        while self.adc.syncbusy().read().ctrlb().bit_is_set() {}
+        self.set_inputs(input.into(), None);
+
+        self.power_up();
+        let result = self.synchronous_convert();
+        self.power_down();
+
+        self.adc.refctrl().write(|w| unsafe { w.bits(refctrl) });
+        while self.adc.syncbusy().read().refctrl().bit_is_set() {}
+        self.adc.avgctrl().write(|w| unsafe { w.bits(avgctrl) });
+        while self.adc.syncbusy().read().avgctrl().bit_is_set() {}
+        self.adc.ctrlb().write(|w| unsafe { w.bits(ctrlb) });
+        while self.adc.syncbusy().read().ctrlb().bit_is_set() {}
+        self.adc.inputctrl().write(|w| unsafe { w.bits(inputctrl) });
+        while self.adc.syncbusy().read().inputctrl().bit_is_set() {}
+
+        result
+    }
+
     fn power_up(&mut self) {
         while self.adc.syncbusy().read().enable().bit_is_set() {}
         self.adc.ctrla().modify(|_, w| w.enable().set_bit());
@@ -146,7 +464,7 @@ impl Adc<$ADC> {
     }
 
     fn disable_freerunning(&mut self) {
-        self.adc.ctrlb().modify(|_, w| w.freerun().set_bit());
+        self.adc.ctrlb().modify(|_, w| w.freerun().clear_bit());
         while self.adc.syncbusy().read().ctrlb().bit_is_set() {}
     }
 
@@ -181,9 +499,85 @@ impl Adc<$ADC> {
     /// Sets the mux to a particular pin. The pin mux is enabled-protected,
     /// so must be called while the peripheral is disabled.
     fn mux<PIN: Channel<$ADC, ID=u8>>(&mut self, _pin: &mut PIN) {
-        let chan = PIN::channel();
+        self.set_inputs(PIN::channel(), None);
+    }
+
+    /// Select the positive input, and either a negative input for
+    /// differential conversions or ground for single-ended conversions.
+    fn set_inputs(&mut self, positive: u8, negative: Option<u8>) {
         while self.adc.syncbusy().read().inputctrl().bit_is_set() {}
-        self.adc.inputctrl().modify(|_, w| unsafe{ w.muxpos().bits(chan) });
+        self.adc.inputctrl().modify(|_, w| {
+            unsafe { w.muxpos().bits(positive) };
+            w.diffmode().bit(negative.is_some());
+            match negative {
+                Some(negative) => unsafe { w.muxneg().bits(negative) },
+                None => w.muxneg().gnd(),
+            }
+        });
+        while self.adc.syncbusy().read().inputctrl().bit_is_set() {}
+    }
+}
+
+#[cfg(feature = "dma")]
+impl Adc<$ADC> {
+    /// Fill `buffer` with consecutive conversions of `pin`, using a DMA
+    /// channel to move each result to memory.
+    ///
+    /// The ADC runs in free-running mode for the duration of the transfer,
+    /// so the sample rate is set by the ADC clock, prescaler and sampling
+    /// time. This method blocks until the buffer has been filled.
+    pub fn read_buffer<Ch, PIN>(
+        &mut self,
+        pin: &mut PIN,
+        mut buffer: &mut [u16],
+        channel: &mut Ch,
+    ) -> Result<(), dmac::Error>
+    where
+        Ch: AnyChannel<Status = Ready>,
+        PIN: Channel<$ADC, ID = u8>,
+    {
+        if buffer.is_empty() {
+            return Ok(());
+        }
+
+        let mut result = ResultPtr(self.adc.result().as_ptr());
+        let channel = channel.as_mut();
+
+        self.mux(pin);
+        self.power_up();
+        // The first conversion after the reference is changed must not be used.
+        self.adc.intflag().write(|w| w.resrdy().set_bit());
+        let _ = self.synchronous_convert();
+
+        // SAFETY: We make sure that the DMA transfer is complete or stopped
+        // before returning, so `buffer` outlives the transfer.
+        let armed = unsafe {
+            channel.transfer(
+                &mut result,
+                &mut buffer,
+                TriggerSource::$trigger,
+                TriggerAction::Burst,
+                None,
+            )
+        };
+        if let Err(e) = armed {
+            self.power_down();
+            return Err(e);
+        }
+
+        self.enable_freerunning();
+        self.start_conversion();
+
+        while !channel.xfer_complete() {
+            core::hint::spin_loop();
+        }
+
+        // Defensively disable the channel
+        channel.stop();
+
+        self.power_down();
+        self.disable_freerunning();
+        channel.xfer_success()
     }
 }
 
@@ -268,10 +662,277 @@ where
 }
 
 adc_hal! {
-    Adc0: (adc0, apbdmask, adc0_, adc0_biascomp_scale_cal, adc0_biasref_scale_cal, adc0_biasr2r_scale_cal),
-    Adc1: (adc1, apbdmask, adc1_, adc1_biascomp_scale_cal, adc1_biasref_scale_cal, adc1_biasr2r_scale_cal),
+    Adc0: (adc0, apbdmask, adc0_, adc0_biascomp_scale_cal, adc0_biasref_scale_cal, adc0_biasr2r_scale_cal, Adc0Resrdy),
+    Adc1: (adc1, apbdmask, adc1_, adc1_biascomp_scale_cal, adc1_biasref_scale_cal, adc1_biasr2r_scale_cal, Adc1Resrdy),
+}
+
+//==============================================================================
+// async
+//==============================================================================
+
+#[cfg(feature = "async")]
+mod async_api {
+    use core::future::poll_fn;
+    use core::marker::PhantomData;
+    use core::task::Poll;
+
+    use embassy_sync::waitqueue::AtomicWaker;
+
+    use super::*;
+    use crate::async_hal::interrupts::{Binding, Handler, InterruptSource, ADC0, ADC1};
+    use crate::typelevel::Sealed;
+
+    #[cfg(feature = "dma")]
+    use core::{future::Future, pin::pin};
+
+    #[cfg(feature = "dma")]
+    use crate::dmac::ReadyFuture;
+
+    /// One waker per ADC instance
+    #[allow(clippy::declare_interior_mutable_const)]
+    const NEW_WAKER: AtomicWaker = AtomicWaker::new();
+    static WAKERS: [AtomicWaker; 2] = [NEW_WAKER; 2];
+
+    /// Interrupt handler for an ADC peripheral.
+    pub struct InterruptHandler<ADC> {
+        _private: (),
+        _adc: PhantomData<ADC>,
+    }
+
+    impl<ADC> Sealed for InterruptHandler<ADC> {}
+
+    /// `async` version of an [`Adc`].
+    ///
+    /// Create this struct by calling `into_future` on an [`Adc`].
+    pub struct AdcFuture<ADC> {
+        adc: Adc<ADC>,
+    }
+
+    impl<ADC> AdcFuture<ADC> {
+        /// Return the underlying [`Adc`].
+        pub fn free(self) -> Adc<ADC> {
+            self.adc
+        }
+    }
+
+    /// Free-running conversions of an ADC
+    #[cfg(feature = "dma")]
+    trait FreeRun {
+        fn start_free_running(&mut self);
+        fn stop_free_running(&mut self);
+    }
+
+    /// Keeps an ADC converting in free-running mode, and stops it when
+    /// dropped, so that a cancelled `read_buffer` doesn't leave the ADC
+    /// running.
+    #[cfg(feature = "dma")]
+    struct FreeRunning<'a, A: FreeRun>(&'a mut A);
+
+    #[cfg(feature = "dma")]
+    impl<'a, A: FreeRun> FreeRunning<'a, A> {
+        fn start(adc: &'a mut A) -> Self {
+            adc.start_free_running();
+            Self(adc)
+        }
+    }
+
+    #[cfg(feature = "dma")]
+    impl<A: FreeRun> Drop for FreeRunning<'_, A> {
+        fn drop(&mut self) {
+            self.0.stop_free_running();
+        }
+    }
+
+    macro_rules! adc_async {
+        ($($ADC:ident: ($Interrupt:ident, $trigger:ident, $index:literal),)+) => {
+            $(
+    impl Handler<$Interrupt> for InterruptHandler<$ADC> {
+        unsafe fn on_interrupt() {
+            // SAFETY: We only touch the interrupt enable register, which the
+            // `AdcFuture` expects to be cleared from this handler.
+            let adc = unsafe { &*$ADC::ptr() };
+            let pending = adc.intflag().read().bits() & adc.intenset().read().bits();
+            if pending != 0 {
+                // Disable the interrupts but don't clear the flags; they will be
+                // cleared when the future is next polled.
+                adc.intenclr().write(|w| unsafe { w.bits(pending) });
+                WAKERS[$index].wake();
+            }
+        }
+    }
+
+    impl Adc<$ADC> {
+        /// Turn an [`Adc`] into an [`AdcFuture`]. The correct interrupt sources
+        /// are needed.
+        pub fn into_future<I>(self, _irqs: I) -> AdcFuture<$ADC>
+        where
+            I: Binding<$Interrupt, InterruptHandler<$ADC>>,
+        {
+            $Interrupt::unpend();
+            unsafe { $Interrupt::enable() };
+
+            AdcFuture { adc: self }
+        }
+    }
+
+    #[cfg(feature = "dma")]
+    impl FreeRun for Adc<$ADC> {
+        fn start_free_running(&mut self) {
+            self.enable_freerunning();
+            self.power_up();
+            self.start_conversion();
+        }
+
+        fn stop_free_running(&mut self) {
+            self.power_down();
+            self.disable_freerunning();
+        }
+    }
+
+    impl AdcFuture<$ADC> {
+        /// Sample `pin`, waiting asynchronously for the conversion to complete.
+        pub async fn read<PIN: Channel<$ADC, ID=u8>>(&mut self, pin: &mut PIN) -> u16 {
+            self.adc.mux(pin);
+            self.adc.power_up();
+            // The first conversion after the reference is changed must not be
+            // used.
+            let _ = self.convert().await;
+            let result = self.convert().await;
+            self.adc.power_down();
+            result
+        }
+
+        /// Sample the difference between `pos` and `neg`, waiting asynchronously
+        /// for the conversion to complete.
+        pub async fn read_differential<P, N>(&mut self, _pos: &mut P, _neg: &mut N) -> i16
+        where
+            P: Channel<$ADC, ID=u8>,
+            N: NegativeChannel<$ADC>,
+        {
+            self.adc.set_inputs(P::channel(), Some(N::channel()));
+            self.adc.power_up();
+            // The first conversion after the reference is changed must not be
+            // used.
+            let _ = self.convert().await;
+            let result = self.convert().await;
+            self.adc.power_down();
+            result as i16
+        }
+
+        /// Convert `pin` continuously until the window monitor condition
+        /// configured with `window_monitor` is met, then return the result which
+        /// triggered it.
+        pub async fn wait_for_window<PIN: Channel<$ADC, ID=u8>>(&mut self, pin: &mut PIN) -> u16 {
+            self.adc.clear_window_interrupt();
+            self.adc.start_monitoring(pin);
+
+            poll_fn(|cx| {
+                if self.adc.is_window_interrupt() {
+                    return Poll::Ready(());
+                }
+
+                WAKERS[$index].register(cx.waker());
+                self.adc.enable_window_interrupt();
+
+                if self.adc.is_window_interrupt() {
+                    self.adc.disable_window_interrupt();
+                    return Poll::Ready(());
+                }
+
+                Poll::Pending
+            })
+            .await;
+
+            let result = self.adc.adc.result().read().result().bits();
+            self.adc.stop_monitoring();
+            self.adc.clear_window_interrupt();
+            result
+        }
+
+        /// Fill `buffer` with consecutive conversions of `pin`, using a DMA
+        /// channel to move each result to memory.
+        ///
+        /// See [`Adc::read_buffer`] for more information.
+        #[cfg(feature = "dma")]
+        pub async fn read_buffer<Ch, PIN>(
+            &mut self,
+            pin: &mut PIN,
+            buffer: &mut [u16],
+            channel: &mut Ch,
+        ) -> Result<(), dmac::Error>
+        where
+            Ch: AnyChannel<Status = ReadyFuture>,
+            PIN: Channel<$ADC, ID = u8>,
+        {
+            if buffer.is_empty() {
+                return Ok(());
+            }
+
+            self.adc.mux(pin);
+            self.adc.power_up();
+            // The first conversion after the reference is changed must not be
+            // used.
+            let _ = self.convert().await;
+
+            let mut transfer = pin!(channel.as_mut().transfer_future(
+                ResultPtr(self.adc.adc.result().as_ptr()),
+                buffer,
+                TriggerSource::$trigger,
+                TriggerAction::Burst,
+            ));
+
+            // Arm the DMA channel before starting the conversions
+            if let Poll::Ready(result) = poll_fn(|cx| Poll::Ready(transfer.as_mut().poll(cx))).await {
+                self.adc.power_down();
+                return result;
+            }
+
+            let _running = FreeRunning::start(&mut self.adc);
+            transfer.await
+        }
+
+        async fn convert(&mut self) -> u16 {
+            self.adc.adc.intflag().write(|w| w.resrdy().set_bit());
+            self.adc.start_conversion();
+            self.wait_result_ready().await;
+
+            // Reading the result clears the RESRDY flag
+            self.adc.adc.result().read().result().bits()
+        }
+
+        async fn wait_result_ready(&mut self) {
+            let adc = &self.adc.adc;
+            poll_fn(|cx| {
+                if adc.intflag().read().resrdy().bit_is_set() {
+                    return Poll::Ready(());
+                }
+
+                WAKERS[$index].register(cx.waker());
+                adc.intenset().write(|w| w.resrdy().set_bit());
+
+                if adc.intflag().read().resrdy().bit_is_set() {
+                    adc.intenclr().write(|w| w.resrdy().set_bit());
+                    return Poll::Ready(());
+                }
+
+                Poll::Pending
+            })
+            .await;
+        }
+    }
+            )+
+        };
+    }
+
+    adc_async! {
+        Adc0: (ADC0, Adc0Resrdy, 0),
+        Adc1: (ADC1, Adc1Resrdy, 1),
+    }
 }
 
+#[cfg(feature = "async")]
+pub use async_api::{AdcFuture, InterruptHandler};
+
 macro_rules! adc_pins {
     (
         $(
@@ -290,6 +951,21 @@ macro_rules! adc_pins {
     }
 }
 
+macro_rules! negative_pins {
+    (
+        $(
+            $( #[$cfg:meta] )?
+            $PinId:ident: $ADC:ident
+        ),+
+        $(,)?
+    ) => {
+        $(
+            $( #[$cfg] )?
+            impl NegativeChannel<$ADC> for Pin<$PinId, AlternateB> {}
+        )+
+    }
+}
+
 adc_pins! {
     #[hal_cfg("pa02")]
     PA02: (Adc0, 0),
@@ -357,3 +1033,39 @@ adc_pins! {
     #[hal_cfg("pd01")]
     PD01: (Adc1, 15),
 }
+
+negative_pins! {
+    #[hal_cfg("pa02")]
+    PA02: Adc0,
+    #[hal_cfg("pa03")]
+    PA03: Adc0,
+    #[hal_cfg("pb08")]
+    PB08: Adc0,
+    #[hal_cfg("pb09")]
+    PB09: Adc0,
+    #[hal_cfg("pa04")]
+    PA04: Adc0,
+    #[hal_cfg("pa05")]
+    PA05: Adc0,
+    #[hal_cfg("pa06")]
+    PA06: Adc0,
+    #[hal_cfg("pa07")]
+    PA07: Adc0,
+
+    #[hal_cfg("pb08")]
+    PB08: Adc1,
+    #[hal_cfg("pb09")]
+    PB09: Adc1,
+    #[hal_cfg("pa08")]
+    PA08: Adc1,
+    #[hal_cfg("pa09")]
+    PA09: Adc1,
+    #[hal_cfg("pc02")]
+    PC02: Adc1,
+    #[hal_cfg("pc03")]
+    PC03: Adc1,
+    #[hal_cfg("pb04")]
+    PB04: Adc1,
+    #[hal_cfg("pb05")]
+    PB05: Adc1,
+}
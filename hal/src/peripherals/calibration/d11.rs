@@ -64,3 +64,8 @@ pub fn usb_trim_cal() -> u8 {
 pub fn usb_trim_cal() -> u8 {
     cal_with_errata(4, 23, 7, 7, 3) as u8
 }
+
+/// Temperature log row, used to calibrate the temperature sensor
+pub fn temperature_log_row() -> u64 {
+    ((cal(0x14, 0, u32::MAX) as u64) << 32) | cal(0x10, 0, u32::MAX) as u64
+}
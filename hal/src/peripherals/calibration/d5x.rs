@@ -30,6 +30,11 @@ pub fn usb_trim_cal() -> u8 {
     cal(4, 10, 0b111) as u8
 }
 
+/// AC BIAS0 calibration value. Should be written to AC CALIB register.
+pub fn ac_bias0_cal() -> u8 {
+    cal(0, 0, 0b11) as u8
+}
+
 /// ADC0 BIASCOMP calibration value. Should be written to ADC0 CALIB register.
 pub fn adc0_biascomp_scale_cal() -> u8 {
     cal(0, 2, 0b111) as u8
@@ -10,6 +10,7 @@ use atsamd_hal_macros::{hal_cfg, hal_macro_helper};
 
 use fugit::RateExtU32;
 
+use crate::clock::v2::pclk::{ids, Pclk, PclkSourceId};
 use crate::pac::gclk::clkctrl::Genselect::*;
 use crate::pac::gclk::clkctrl::Idselect::*;
 use crate::pac::gclk::genctrl::Srcselect::*;
@@ -465,6 +466,96 @@ clock_generator!(
     (i2s1, I2S1Clock, I2s1),
 );
 
+/// V2 to V1 compatibility layer that allows to convert V2 [`Pclk`] constructs
+/// into corresponding V1 `*Clock` types. Thus, user can manage V1 clocking
+/// compatible peripherals while using V2 clocking API
+macro_rules! pclk_compat {
+    (
+        $(
+            $(#[$attr:meta])*
+            ($Type:ident, $PclkId:ident),
+        )+
+    ) => {
+        $(
+            $(#[$attr])*
+            impl<I: PclkSourceId> core::convert::From<Pclk<ids::$PclkId, I>> for $Type {
+                fn from(pclk: Pclk<ids::$PclkId, I>) -> Self {
+                    $Type { freq: pclk.freq() }
+                }
+            }
+        )+
+    };
+}
+
+// samd11
+#[hal_cfg("clock-d11")]
+#[hal_macro_helper]
+pclk_compat!(
+    (Tcc0Clock, Tcc0),
+    (Tc1Tc2Clock, Tc1Tc2),
+    (Sercom0CoreClock, Sercom0),
+    (Sercom1CoreClock, Sercom1),
+    #[hal_cfg("sercom2")]
+    (Sercom2CoreClock, Sercom2),
+    (RtcClock, Rtc),
+    (AdcClock, Adc),
+    (WdtClock, Wdt),
+    (EicClock, Eic),
+    (UsbClock, Usb),
+    (Evsys0Clock, EvSys0),
+    (Evsys1Clock, EvSys1),
+    (Evsys2Clock, EvSys2),
+    (Evsys3Clock, EvSys3),
+    (Evsys4Clock, EvSys4),
+    (Evsys5Clock, EvSys5),
+    (AcAnaClock, AcAna),
+    (AcDigClock, AcDig),
+    (DacClock, Dac),
+);
+// samd21
+#[hal_cfg("clock-d21")]
+#[hal_macro_helper]
+pclk_compat!(
+    (Tcc0Tcc1Clock, Tcc0Tcc1),
+    (Tcc2Tc3Clock, Tcc2Tc3),
+    (Tc4Tc5Clock, Tc4Tc5),
+    #[hal_cfg(all("tc6", "tc7"))]
+    (Tc6Tc7Clock, Tc6Tc7),
+    (Sercom0CoreClock, Sercom0),
+    (Sercom1CoreClock, Sercom1),
+    (Sercom2CoreClock, Sercom2),
+    (Sercom3CoreClock, Sercom3),
+    #[hal_cfg("sercom4")]
+    (Sercom4CoreClock, Sercom4),
+    #[hal_cfg("sercom5")]
+    (Sercom5CoreClock, Sercom5),
+    #[hal_cfg("usb")]
+    (UsbClock, Usb),
+    (RtcClock, Rtc),
+    (AdcClock, Adc),
+    (WdtClock, Wdt),
+    (EicClock, Eic),
+    (Evsys0Clock, EvSys0),
+    (Evsys1Clock, EvSys1),
+    (Evsys2Clock, EvSys2),
+    (Evsys3Clock, EvSys3),
+    (Evsys4Clock, EvSys4),
+    (Evsys5Clock, EvSys5),
+    (Evsys6Clock, EvSys6),
+    (Evsys7Clock, EvSys7),
+    (Evsys8Clock, EvSys8),
+    (Evsys9Clock, EvSys9),
+    (Evsys10Clock, EvSys10),
+    (Evsys11Clock, EvSys11),
+    (AcAnaClock, AcAna),
+    (AcDigClock, AcDig),
+    (DacClock, Dac),
+    #[hal_cfg("i2s")]
+    (I2S0Clock, I2S0),
+    #[hal_cfg("i2s")]
+    (I2S1Clock, I2S1),
+);
+
 /// The frequency of the 48Mhz source.
 pub const OSC48M_FREQ: Hertz = Hertz::Hz(48_000_000);
 /// The frequency of the 8 Mhz source.
@@ -488,7 +579,7 @@ fn enable_gclk_apb(pm: &mut Pm) {
 
 /// Turn on the internal 32hkz oscillator
 pub fn enable_internal_32kosc(sysctrl: &mut Sysctrl) {
-    let calibration = super::calibration::osc32k_cal();
+    let calibration = crate::calibration::osc32k_cal();
     sysctrl.osc32k().write(|w| {
         unsafe {
             w.ondemand().clear_bit();
@@ -562,7 +653,7 @@ fn configure_and_enable_dfll48m(sysctrl: &mut Sysctrl, use_external_crystal: boo
         });
     } else {
         // Apply calibration
-        let coarse = super::calibration::dfll48m_coarse_cal();
+        let coarse = crate::calibration::dfll48m_coarse_cal();
         let fine = 0x1ff;
 
         sysctrl.dfllval().write(|w| unsafe {
@@ -0,0 +1,350 @@
+//! # Version 2 of the `clock` module
+//!
+//! ## Overview
+//!
+//! This module provides a simple, ergonomic, and most of all **safe** API to
+//! create and manage the clock tree in ATSAMD11 and ATSAMD21 devices. It uses
+//! [type-level programming techniques](crate::typelevel) to prevent users from
+//! creating invalid or unsound clocking configurations.
+//!
+//! The API follows the same design as the SAMx5x `clock::v2` module. The
+//! concepts are summarized below, and the same vocabulary is used throughout
+//! the sub-modules.
+//!
+//! ## Clock safety
+//!
+//! A clock tree represents dependencies among clocks, where producer clocks
+//! feed consumer clocks. A producer clock may not be disabled or reconfigured
+//! while it still has consumers, and a consumer clock may not be enabled
+//! before its producer.
+//!
+//! Every clock in this module is in one of three states:
+//! - Disabled clocks are represented by `Token` types, e.g. [`XoscToken`]. The
+//!   tokens of all clocks that are disabled at power-on reset are found in the
+//!   [`Tokens`] struct.
+//! - Configured but disabled clocks are represented by the clock type itself,
+//!   e.g. [`Xosc`], which uses the builder pattern to store the desired
+//!   configuration.
+//! - Enabled clocks are wrapped by [`Enabled<T, N>`], e.g. [`EnabledXosc`].
+//!
+//! Bus clocks ([`AhbClk`]s and [`ApbClk`]s) and peripheral channel clocks
+//! ([`Pclk`]s) are never consumed by other clocks, so they are simply created
+//! from, and converted back into, their respective `Token` types.
+//!
+//! ## Tracking N at compile-time
+//!
+//! Most producer clocks can feed any number of consumers. The `N` parameter
+//! of [`Enabled<T, N>`] is a type-level, [`Unsigned`] integer that counts
+//! those consumers. Creating a consumer [`Increment`]s the count of its
+//! producer, and freeing the consumer [`Decrement`]s it. Methods to disable
+//! or reconfigure a producer are only implemented for `Enabled<T, U0>`.
+//!
+//! Only `Enabled` producer clocks implement the [`Source`] trait, which lets
+//! consumer clocks access the producer frequency and [`Increment`] its count.
+//!
+//! ### `Id` types
+//!
+//! Consumer clocks must track their [`Source`] at the type level, but they do
+//! not need to know how the source is configured. For instance, an [`Xosc`]
+//! can be in either [`ClockMode`] or [`CrystalMode`], but a [`Gclk`] fed by it
+//! does not care which. For this reason, the associated [`Id`] type of
+//! `Source` is an uninhabited type representing the *identity* of a clock,
+//! rather than the clock itself. For the `Xosc`, this is [`XoscId`], so that
+//! the complete type of such a `Gclk` is `Gclk<G, XoscId>`.
+//!
+//! ## Notes on memory safety
+//!
+//! ### Register interfaces
+//!
+//! The PAC clocking structs ([`SYSCTRL`], [`GCLK`] and [`PM`]) each control a
+//! large set of registers. `Token` types break them up into mutually exclusive
+//! pieces, one per clock, and they are only created in exchange for the PAC
+//! structs. As a result, `Token`s can write their registers without further
+//! synchronization.
+//!
+//! On the SAMD11 and SAMD21, the `GENCTRL`, `GENDIV` and `CLKCTRL` registers of
+//! the `GCLK` peripheral are indirectly addressed through an `ID` field. These
+//! registers are never read or modified in place. Instead, each [`GclkToken`]
+//! and [`PclkToken`] always performs complete, single writes that include its
+//! own `ID`, which keeps the tokens independent of each other.
+//!
+//! ### Bus clocks
+//!
+//! Bus clocks are fundamentally different, because they do not use mutually
+//! exclusive registers. For example, `ApbClk<Sercom0>` and `ApbClk<Sercom1>`
+//! share the `APBCMASK` register, and a read/modify/write of that register
+//! could be preempted. To prevent data races, enabling or disabling bus clocks
+//! requires exclusive access to the [`Ahb`] or [`Apb`] types, which represent
+//! the corresponding buses.
+//!
+//! ## Getting started
+//!
+//! To set up a clock tree, trade the [PAC](crate::pac)-level clocking structs
+//! for their HAL equivalents with [`clock_system_at_reset`]. Because
+//! bootloaders commonly change the clocks, this function returns the OSC8M and
+//! the `GCLK` peripheral to their reset state first.
+//!
+//! ```no_run
+//! use atsamd_hal::clock::v2::clock_system_at_reset;
+//! use atsamd_hal::pac::Peripherals;
+//! let mut pac = Peripherals::take().unwrap();
+//! let (buses, clocks, tokens) = clock_system_at_reset(
+//!     pac.sysctrl,
+//!     pac.gclk,
+//!     pac.pm,
+//!     &mut pac.nvmctrl,
+//! );
+//! ```
+//!
+//! The function returns three different objects:
+//! - The [`Buses`] contain the [`Ahb`] and [`Apb`] objects.
+//! - The [`Clocks`] contain the clocks running at power-on reset. These are
+//!   all of the [`AhbClks`], some of the [`ApbClks`], the [`Osc8m`] with a
+//!   prescaler of 8, [`Gclk0`] running at 1 MHz from the `Osc8m`, and the
+//!   always-enabled [`OscUlp32k`]. Because `Gclk0` drives the main clock, it
+//!   is represented as `Enabled<Gclk0<Osc8mId>, U1>`, and it has special
+//!   methods, such as [`swap_sources`], to change its source while running.
+//! - The [`Tokens`] contain the `Token`s for every clock that is disabled at
+//!   power-on reset.
+//!
+//! ## Example clock tree
+//!
+//! Suppose an 8 MHz crystal is attached to the XOSC pins. We would like to run
+//! the main clock at 48 MHz from the DPLL and provide an 8 MHz clock to
+//! SERCOM0.
+//!
+//! ```text
+//! XOSC (8 MHz)
+//! ├── DPLL (48 MHz)
+//! │   └── GCLK0 (48 MHz)
+//! │       └── Main clock (48 MHz)
+//! └── GCLK1 (8 MHz)
+//!     └── SERCOM0 peripheral clock
+//! ```
+//!
+//! ```no_run
+//! use atsamd_hal::{
+//!     clock::v2::{
+//!         clock_system_at_reset,
+//!         dpll::Dpll,
+//!         gclk::Gclk,
+//!         pclk::Pclk,
+//!         xosc::Xosc,
+//!     },
+//!     fugit::RateExtU32,
+//!     gpio::Pins,
+//!     pac::Peripherals,
+//! };
+//! let mut pac = Peripherals::take().unwrap();
+//! let (mut buses, clocks, tokens) = clock_system_at_reset(
+//!     pac.sysctrl,
+//!     pac.gclk,
+//!     pac.pm,
+//!     &mut pac.nvmctrl,
+//! );
+//! let pins = Pins::new(pac.port);
+//! let xosc = Xosc::from_crystal(tokens.xosc, pins.pa14, pins.pa15, 8.MHz()).enable();
+//! while !xosc.is_ready() {}
+//! // The XOSC is divided by 8 for a 1 MHz DPLL input
+//! let (dpll, xosc) = Dpll::from_xosc(tokens.dpll, xosc);
+//! let dpll = dpll.prediv(8).loop_div(48, 0).enable();
+//! while !dpll.is_ready() {}
+//! let (gclk0, osc8m, dpll) = clocks.gclk0.swap_sources(clocks.osc8m, dpll);
+//! let (gclk1, xosc) = Gclk::from_source(tokens.gclks.gclk1, xosc);
+//! let gclk1 = gclk1.enable();
+//! let (pclk_sercom0, gclk1) = Pclk::enable(tokens.pclks.sercom0, gclk1);
+//! let apb_sercom0 = buses.apb.enable(tokens.apbs.sercom0);
+//! ```
+//!
+//! The [`Pclk`] and [`ApbClk`] can then be handed to the SERCOM driver.
+//!
+//! [`SYSCTRL`]: crate::pac::Sysctrl
+//! [`GCLK`]: crate::pac::Gclk
+//! [`PM`]: crate::pac::Pm
+//! [`Ahb`]: ahb::Ahb
+//! [`AhbClk`]: ahb::AhbClk
+//! [`AhbClks`]: ahb::AhbClks
+//! [`Apb`]: apb::Apb
+//! [`ApbClk`]: apb::ApbClk
+//! [`ApbClks`]: apb::ApbClks
+//! [`Gclk`]: gclk::Gclk
+//! [`Gclk0`]: gclk::Gclk0
+//! [`GclkToken`]: gclk::GclkToken
+//! [`swap_sources`]: gclk::EnabledGclk0::swap_sources
+//! [`Osc8m`]: osc8m::Osc8m
+//! [`OscUlp32k`]: osculp32k::OscUlp32k
+//! [`Pclk`]: pclk::Pclk
+//! [`PclkToken`]: pclk::PclkToken
+//! [`Xosc`]: xosc::Xosc
+//! [`XoscId`]: xosc::XoscId
+//! [`XoscToken`]: xosc::XoscToken
+//! [`EnabledXosc`]: xosc::EnabledXosc
+//! [`CrystalMode`]: xosc::CrystalMode
+//! [`ClockMode`]: xosc::ClockMode
+//! [`Increment`]: crate::typelevel::Increment
+//! [`Decrement`]: crate::typelevel::Decrement
+//! [`Id`]: Source::Id
+//! [`Unsigned`]: typenum::Unsigned
+
+#![allow(clippy::manual_range_contains)]
+
+use typenum::U0;
+
+use crate::time::Hertz;
+use crate::typelevel::{PrivateDecrement, PrivateIncrement, Sealed};
+
+pub mod ahb;
+pub mod apb;
+pub mod dfll;
+pub mod dpll;
+pub mod gclk;
+pub mod osc32k;
+pub mod osc8m;
+pub mod osculp32k;
+pub mod pclk;
+pub mod types;
+pub mod xosc;
+pub mod xosc32k;
+
+mod reset;
+pub use reset::*;
+
+// `Token` types and memory safety
+//
+// Each of the PAC [`Peripherals`] is a zero-sized, singleton struct that
+// mediates access to the MMIO hardware registers. It is not possible to create
+// two instances of any peripheral without causing a run-time panic. These
+// structs implement [`Deref`] by conjuring a pointer to the corresponding
+// register block, and each register within the block is represented by a
+// `vcell::VolatileCell`. Because each register is wrapped in a `VolatileCell`,
+// it is safe to both read and write them through shared references. However,
+// because a read/modify/write operation is not atomic, the [`Peripherals`]
+// structs do not implement [`Sync`].
+//
+// This is a reasonable approach for the PAC, since it is generated from an
+// SVD file. However, it is not the ideal structure for our HAL API. In
+// particular, each [`Peripherals`] struct represents an entire peripheral,
+// rather than a particular functional unit. In the HAL, we want our API to
+// focus on functional units, so we need to define our own abstraction for
+// registers, which will involve `unsafe` code.
+//
+// In the `clock` module, we represent each functional unit with a
+// corresponding `Token` type. Just like the [`Peripherals`], each `Token` type
+// is meant to be a singleton. However, unlike the PAC, we do not have to
+// allow users to create `Token`s directly. Instead, we can have users exchange
+// [`Peripherals`] for the `Token`s. Because each PAC struct is a singleton, we
+// can guarantee each `Token` will be a singleton as well. With this approach,
+// we don't need to implement our own run-time panicking; we simply extend the
+// existing guarantees of the PAC.
+//
+// To implement a memory safe API, we must ensure that all `Token` types access
+// mutually exclusive sets of registers. In that way, we guarantee no two
+// `Token` types can access the same register. Moreover, in contrast to the PAC
+// [`Peripherals`], we can make our `Token`s [`Sync`] if we remove all interior
+// mutability and guarantee that writing or modifying a register requires
+// ownership or an `&mut` reference.
+//
+// Thus, our `Token`-based API should be memory safe if we always obey the
+// following requirements:
+//   - It should be `unsafe` to create a `Token` type unless it is created in
+//     exchange for the corresponding PAC peripheral struct.
+//   - Each `Token` type should have access to a mutually exclusive set of
+//     registers relative to the other `Token`s.
+//   - Writing or modifying a register should always require ownership of, or an
+//     `&mut` reference to, the corresponding `Token`.
+//   - When conjuring references to PAC registers or register blocks, we should
+//     *only* use shared, `&` references. There is no need to use exclusive,
+//     `&mut` references, because each register is wrapped in a `VolatileCell`.
+//     Moreover, using `&mut` references could cause UB, if we accidentally
+//     create two simultaneous references to the same register block from
+//     different `Tokens`.
+//
+// [`Peripherals`]: crate::pac::Peripherals
+// [`Deref`]: core::ops::Deref
+
+/// Marks [`Enabled`] 1:N producer clocks that can act as a clock source
+///
+/// Implementers of this type act as producer clocks and feed consumer clocks in
+/// the clock tree. All implementors are [`Enabled`], 1:N clocks. The `Id`
+/// associated type maps to the corresponding [`Id` type](self#id-types) of the
+/// implementer.
+///
+/// See the documentation on [`Source` clocks](self#tracking-n-at-compile-time)
+/// for more details.
+pub trait Source: Sealed {
+    /// Corresponding `Id` type for the implementer
+    ///
+    /// A given implementer of [`Source`] might have type parameters
+    /// representing its configuration. For instance, [`EnabledXosc<M>`] has a
+    /// type parameter to track its [`Mode`]. However, a consumer clock
+    /// typically does not care about such configuration. It only needs to
+    /// know *which* upstream clock is its [`Source`].
+    ///
+    /// `Id` types exist to fill this role. They represent the *identity* of a
+    /// given clock, regardless of any configuration. This is like the
+    /// distinction between a passport and a person. A passport identifies a
+    /// person, regardless of changes to their clothes or hair.
+    ///
+    /// Thus, [`EnabledXosc<M>`] implements [`Source`] with `Id = `[`XoscId`],
+    /// regardless of `M`.
+    ///
+    /// See the documentation on [`Id` types](self#id-types) for more details.
+    ///
+    /// [`EnabledXosc<M>`]: xosc::EnabledXosc
+    /// [`Mode`]: xosc::Mode
+    /// [`XoscId`]: xosc::XoscId
+    type Id;
+
+    /// Return the frequency of the clock source
+    fn freq(&self) -> Hertz;
+}
+
+/// An enabled, 1:N clock with a compile-time counter for N
+///
+/// This struct is a wrapper around other clock types from this module. It
+/// represents a clock, `T`, that has been enabled, and it maintains a
+/// compile-time counter, `N`, of its consumer clocks in the clock tree.
+///
+/// Compile-time counting allows the API to restrict when clocks may be modified
+/// or disabled. For example, `Enabled` clocks can only be disabled when their
+/// counter is [`U0`].
+///
+/// The type-level counter is implemented using [`Unsigned`] integers from
+/// the [`typenum`] crate, and it is modified using the [`Increment`] and
+/// [`Decrement`] traits.
+///
+/// See the [`Enabled` wrapper documentation](self#tracking-n-at-compile-time) for more
+/// details.
+///
+/// [`EnabledGclk0`]: gclk::EnabledGclk0
+/// [`Increment`]: crate::typelevel::Increment
+/// [`Decrement`]: crate::typelevel::Decrement
+/// [`Unsigned`]: typenum::Unsigned
+pub struct Enabled<T, N = U0>(pub(crate) T, N);
+
+impl<T, N> Sealed for Enabled<T, N> {}
+
+impl<T, N: Default> Enabled<T, N> {
+    #[inline]
+    pub(crate) fn new(t: T) -> Self {
+        Enabled(t, N::default())
+    }
+}
+
+impl<T, N: PrivateIncrement> PrivateIncrement for Enabled<T, N> {
+    type Inc = Enabled<T, N::Inc>;
+
+    #[inline]
+    fn inc(self) -> Self::Inc {
+        Enabled(self.0, self.1.inc())
+    }
+}
+
+impl<T, N: PrivateDecrement> PrivateDecrement for Enabled<T, N> {
+    type Dec = Enabled<T, N::Dec>;
+
+    #[inline]
+    fn dec(self) -> Self::Dec {
+        Enabled(self.0, self.1.dec())
+    }
+}
@@ -0,0 +1,653 @@
+//! # Advanced peripheral bus clocks
+//!
+//! ## Overview
+//!
+//! APB clocks facilitate communication between the processor core and
+//! peripherals on the APB bus. To communicate with a peripheral, the
+//! corresponding APB clock must be enabled, which is done by setting a bit in
+//! one of the three `APBXMASK` registers.
+//!
+//! In this module, *enabled* APB clocks are represented by the [`ApbClk<A>`]
+//! struct, where the type parameter `A` is a type that implements [`ApbId`] and
+//! corresponds to one of the bits in an `APBXMASK` register.
+//!
+//! While most other clocks in the `clock` module are configured through
+//! mutually exclusive registers, the [`ApbClk`]s share the three `APBXMASK`
+//! registers. This presents a challenge for memory safety. Specifically, if we
+//! allowed unrestricted access to the corresponding `APBXMASK` register through
+//! each `ApbClk`, we could create data races.
+//!
+//! To solve this problem, we restrict access to the `APBXMASK` registers using
+//! the [`Apb`] type. `Apb` was created to act as a gateway to the `APBXMASK`
+//! registers, allowing us to use `&mut Apb` as compile-time proof of exclusive
+//! access to them.
+//!
+//! ## Example
+//!
+//! Enabling and disabling the [`ApbClk`]s proceeds according to the principles
+//! outlined in the [`clock` module documentation]. It is best shown with an
+//! example.
+//!
+//! Let's start by using [`clock_system_at_reset`] to access the HAL clocking
+//! structs.
+//!
+//! ```no_run
+//! use atsamd_hal::{
+//!     clock::v2::{
+//!         clock_system_at_reset,
+//!     },
+//!     pac::Peripherals,
+//! };
+//! let mut pac = Peripherals::take().unwrap();
+//! let (mut buses, clocks, tokens) = clock_system_at_reset(
+//!     pac.sysctrl,
+//!     pac.gclk,
+//!     pac.pm,
+//!     &mut pac.nvmctrl,
+//! );
+//! ```
+//!
+//! Some APB clocks are enabled at power-on reset. We can find these in the
+//! [`Clocks`] struct.
+//!
+//! ```no_run
+//! # use atsamd_hal::{
+//! #     clock::v2::{
+//! #         clock_system_at_reset,
+//! #     },
+//! #     pac::Peripherals,
+//! # };
+//! # let mut pac = Peripherals::take().unwrap();
+//! # let (mut buses, clocks, tokens) = clock_system_at_reset(
+//! #     pac.sysctrl,
+//! #     pac.gclk,
+//! #     pac.pm,
+//! #     &mut pac.nvmctrl,
+//! # );
+//! let apb_port = clocks.apbs.port;
+//! ```
+//!
+//! Other APB clocks are disabled at power-on reset. To enable these, we must
+//! have access to the [`Apb`] bus type, which is found in the [`Buses`] struct.
+//! As described above, [`Apb`] mediates access to the shared `APBXMASK`
+//! registers. We call [`Apb::enable`] to convert an [`ApbToken`] into the
+//! corresponding [`ApbClk`]. The existence of each `ApbClk` type represents
+//! proof that the corresponding APB clock has been enabled.
+//!
+//! ```no_run
+//! # use atsamd_hal::{
+//! #     clock::v2::{
+//! #         clock_system_at_reset,
+//! #     },
+//! #     pac::Peripherals,
+//! # };
+//! # let mut pac = Peripherals::take().unwrap();
+//! # let (mut buses, clocks, tokens) = clock_system_at_reset(
+//! #     pac.sysctrl,
+//! #     pac.gclk,
+//! #     pac.pm,
+//! #     &mut pac.nvmctrl,
+//! # );
+//! # let apb_port = clocks.apbs.port;
+//! let apb_sercom0 = buses.apb.enable(tokens.apbs.sercom0);
+//! ```
+//!
+//! The complete example is shown below.
+//!
+//! ```no_run
+//! use atsamd_hal::{
+//!     clock::v2::{
+//!         clock_system_at_reset,
+//!     },
+//!     pac::Peripherals,
+//! };
+//! let mut pac = Peripherals::take().unwrap();
+//! let (mut buses, clocks, tokens) = clock_system_at_reset(
+//!     pac.sysctrl,
+//!     pac.gclk,
+//!     pac.pm,
+//!     &mut pac.nvmctrl,
+//! );
+//! let apb_port = clocks.apbs.port;
+//! let apb_sercom0 = buses.apb.enable(tokens.apbs.sercom0);
+//! ```
+//!
+//! [`clock` module documentation]: super
+//! [`clock_system_at_reset`]: super::clock_system_at_reset
+//! [`Clocks`]: super::Clocks
+//! [`Buses`]: super::Buses
+
+use atsamd_hal_macros::{hal_cfg, hal_macro_helper};
+use core::marker::PhantomData;
+
+use bitflags;
+use paste::paste;
+
+use crate::pac::{self, pm};
+
+use crate::typelevel::Sealed;
+
+use super::types::*;
+
+//==============================================================================
+// Registers
+//==============================================================================
+
+/// APB clock controller
+///
+/// As described in the [module-level documentation](self), this struct mediates
+/// access to the shared `APBXMASK` registers. Users can convert a disabled
+/// [`ApbToken<A>`] into an enabled [`ApbClk<A>`] using [`Apb::enable`], and
+/// vice versa with [`Apb::disable`].
+pub struct Apb(());
+
+impl Apb {
+    /// Create a new instance of [`Apb`]
+    ///
+    /// # Safety
+    ///
+    /// Because the `Apb` mediates access to the `APBMASK` registers, it must be
+    /// a singleton. There must never be two simulatenous instances of it at a
+    /// time. See the notes on `Token` types and memory safety in the root of
+    /// the `clock` module for more details.
+    #[inline]
+    pub(super) unsafe fn new() -> Self {
+        Self(())
+    }
+
+    #[inline]
+    fn pm(&self) -> &pm::RegisterBlock {
+        // Safety: The `Apb` type has exclusive access to the `APBXMASK`
+        // registers, and it uses a shared reference to the register block. See
+        // the notes on `Token` types and memory safety in the root of the
+        // `clock` module for more details.
+        unsafe { &*pac::Pm::PTR }
+    }
+
+    #[inline]
+    fn apbamask(&mut self) -> &pm::Apbamask {
+        self.pm().apbamask()
+    }
+
+    #[inline]
+    fn apbbmask(&mut self) -> &pm::Apbbmask {
+        self.pm().apbbmask()
+    }
+
+    #[inline]
+    fn apbcmask(&mut self) -> &pm::Apbcmask {
+        self.pm().apbcmask()
+    }
+
+    #[inline]
+    fn enable_mask(&mut self, mask: ApbMask) {
+        // Safety: The mask bits are derived from a `bitflags` struct, so they
+        // are guaranteed to be valid.
+        unsafe {
+            match mask {
+                ApbMask::A(mask) => {
+                    self.apbamask()
+                        .modify(|r, w| w.bits(r.bits() | mask.bits()));
+                }
+                ApbMask::B(mask) => {
+                    self.apbbmask()
+                        .modify(|r, w| w.bits(r.bits() | mask.bits()));
+                }
+                ApbMask::C(mask) => {
+                    self.apbcmask()
+                        .modify(|r, w| w.bits(r.bits() | mask.bits()));
+                }
+            }
+        }
+    }
+
+    #[inline]
+    fn disable_mask(&mut self, mask: ApbMask) {
+        // Safety: The mask bits are derived from a `bitflags` struct, so they
+        // are guaranteed to be valid.
+        unsafe {
+            match mask {
+                ApbMask::A(mask) => {
+                    self.apbamask()
+                        .modify(|r, w| w.bits(r.bits() & !mask.bits()));
+                }
+                ApbMask::B(mask) => {
+                    self.apbbmask()
+                        .modify(|r, w| w.bits(r.bits() & !mask.bits()));
+                }
+                ApbMask::C(mask) => {
+                    self.apbcmask()
+                        .modify(|r, w| w.bits(r.bits() & !mask.bits()));
+                }
+            }
+        }
+    }
+
+    /// Enable the corresponding APB clock
+    ///
+    /// Consume an [`ApbToken`], enable the corresponding APB clock and return
+    /// an [`ApbClk`]. The `ApbClk` represents proof that the corresponding APB
+    /// clock has been enabled.
+    #[inline]
+    pub fn enable<A: ApbId>(&mut self, token: ApbToken<A>) -> ApbClk<A> {
+        self.enable_mask(A::DYN.into());
+        ApbClk::new(token)
+    }
+
+    /// Disable the corresponding APB clock
+    ///
+    /// Consume the [`ApbClk`], disable the corresponding APB clock and return
+    /// the [`ApbToken`].
+    #[inline]
+    pub fn disable<A: ApbId>(&mut self, clock: ApbClk<A>) -> ApbToken<A> {
+        self.disable_mask(A::DYN.into());
+        clock.free()
+    }
+}
+
+//==============================================================================
+// DynApbId & ApbMask
+//==============================================================================
+
+/// A mask corresponding to one of the APB bridge registers
+///
+/// Each variant is a [`bitflags`] struct with a binary representation exactly
+/// matching the corresponding APB `MASK` register.
+enum ApbMask {
+    A(ApbAMask),
+    B(ApbBMask),
+    C(ApbCMask),
+}
+
+macro_rules! define_apb_types {
+    (
+        $(
+            $Reg:ident {
+                $(
+                    $( #[$( $cfg:tt )+] )?
+                    $Type:ident = $BIT:literal,
+                )+
+            }
+        )+
+    ) => {
+        /// Value-level enum identifying a single APB clock
+        ///
+        /// Each variant of this enum corresponds to a specific bit in one of
+        /// the three `APBXMASK` registers and identifies one of many possible
+        /// APB clocks, which can vary by chip.
+        ///
+        /// `DynApbId` is the value-level equivalent of [`ApbId`].
+        #[repr(u8)]
+        pub enum DynApbId {
+            $(
+                $(
+                    $( #[$( $cfg )+] )?
+                    $Type,
+                )+
+            )+
+        }
+
+        $(
+            $(
+                $( #[$( $cfg )+] )?
+                impl ApbId for $Type {
+                    const DYN: DynApbId = DynApbId::$Type;
+                }
+            )+
+        )+
+
+        paste! {
+            $(
+                bitflags::bitflags! {
+                    #[
+                        doc =
+                            "APB bridge `" $Reg "` register mask\n"
+                            "\n"
+                            "This is a [`bitflags`] struct with a binary representation "
+                            "exactly matching the `APB" $Reg "MASK` register."
+                    ]
+                    struct [<Apb $Reg Mask>]: u32 {
+                        $(
+                            $( #[$( $cfg )+] )?
+                            const [<$Type:upper>] = 1 << $BIT;
+                        )+
+                    }
+                }
+
+            )+
+
+            impl From<DynApbId> for ApbMask {
+                #[inline]
+                fn from(id: DynApbId) -> Self {
+                    use DynApbId::*;
+                    match id {
+                        $(
+                            $(
+                                $( #[$( $cfg )+] )?
+                                $Type => ApbMask::$Reg([<Apb $Reg Mask>]::[<$Type:upper>]),
+                            )+
+                        )+
+                    }
+                }
+            }
+        }
+    };
+}
+
+#[hal_cfg("clock-d11")]
+#[hal_macro_helper]
+define_apb_types!(
+    A {
+        Pac0 = 0,
+        Pm = 1,
+        SysCtrl = 2,
+        Gclk = 3,
+        Wdt = 4,
+        Rtc = 5,
+        Eic = 6,
+    }
+    B {
+        Pac1 = 0,
+        Dsu = 1,
+        NvmCtrl = 2,
+        Port = 3,
+        Dmac = 4,
+        Usb = 5,
+        Hmatrix = 6,
+    }
+    C {
+        Pac2 = 0,
+        EvSys = 1,
+        Sercom0 = 2,
+        Sercom1 = 3,
+        #[hal_cfg("sercom2")]
+        Sercom2 = 4,
+        Tcc0 = 5,
+        Tc1 = 6,
+        Tc2 = 7,
+        Adc = 8,
+        Ac = 9,
+        Dac = 10,
+        Ptc = 11,
+    }
+);
+
+#[hal_cfg("clock-d21")]
+#[hal_macro_helper]
+define_apb_types!(
+    A {
+        Pac0 = 0,
+        Pm = 1,
+        SysCtrl = 2,
+        Gclk = 3,
+        Wdt = 4,
+        Rtc = 5,
+        Eic = 6,
+    }
+    B {
+        Pac1 = 0,
+        Dsu = 1,
+        NvmCtrl = 2,
+        Port = 3,
+        Dmac = 4,
+        #[hal_cfg("usb")]
+        Usb = 5,
+        Hmatrix = 6,
+    }
+    C {
+        Pac2 = 0,
+        EvSys = 1,
+        Sercom0 = 2,
+        Sercom1 = 3,
+        Sercom2 = 4,
+        Sercom3 = 5,
+        #[hal_cfg("sercom4")]
+        Sercom4 = 6,
+        #[hal_cfg("sercom5")]
+        Sercom5 = 7,
+        Tcc0 = 8,
+        Tcc1 = 9,
+        Tcc2 = 10,
+        Tc3 = 11,
+        Tc4 = 12,
+        Tc5 = 13,
+        #[hal_cfg("tc6")]
+        Tc6 = 14,
+        #[hal_cfg("tc7")]
+        Tc7 = 15,
+        Adc = 16,
+        Ac = 17,
+        Dac = 18,
+        Ptc = 19,
+        #[hal_cfg("i2s")]
+        I2S = 20,
+    }
+);
+
+//==============================================================================
+// ApbId
+//==============================================================================
+
+/// Type-level enum identifying one of the possible APB clocks
+///
+/// The types implementing this trait are type-level variants of `ApbId`, and
+/// they identify one of the many possible APB clocks, which can vary by chip.
+/// Each type corresponds to a specific bit in one of the three `APBXMASK`
+/// registers.
+///
+/// `ApbId` is the type-level equivalent of [`DynApbId`]. See the documentation
+/// on [type-level programming] and specifically [type-level enums] for more
+/// details.
+///
+/// [type-level programming]: crate::typelevel
+/// [type-level enums]: crate::typelevel#type-level-enums
+pub trait ApbId: Sealed {
+    /// Corresponding variant of [`DynApbId`]
+    const DYN: DynApbId;
+}
+
+//==============================================================================
+// ApbToken
+//==============================================================================
+
+/// Singleton token that can be exchanged for an [`ApbClk`]
+///
+/// As explained in the [`clock` module documentation](super), instances of
+/// various `Token` types can be exchanged for actual clock types. They
+/// represent clocks that are disabled.
+///
+/// The type parameter `A` is an [`ApbId`] indicating which APB clock is
+/// represented by this token. To enable the corresponding APB clock, use the
+/// [`Apb::enable`] method.
+pub struct ApbToken<A: ApbId> {
+    id: PhantomData<A>,
+}
+
+impl<A: ApbId> ApbToken<A> {
+    /// Create a new instance of [`ApbToken`]
+    ///
+    /// # Safety
+    ///
+    /// Each `ApbToken` is a singleton. There must never be two simulatenous
+    /// instances with the same [`ApbId`]. See the notes on `Token` types and
+    /// memory safety in the root of the `clock` module for more details.
+    #[inline]
+    unsafe fn new() -> Self {
+        ApbToken { id: PhantomData }
+    }
+}
+
+//==============================================================================
+// ApbClk
+//==============================================================================
+
+/// An enabled APB clock
+///
+/// An [`ApbClk`] represents an enabled APB clock. The type parameter `A` is an
+/// [`ApbId`], which corresponds to a particular bit in the `APBXMASK`
+/// registers. An `ApbClk` can be disabled with the [`Apb::disable`] method.
+pub struct ApbClk<A: ApbId> {
+    token: ApbToken<A>,
+}
+
+impl<A: ApbId> ApbClk<A> {
+    #[inline]
+    fn new(token: ApbToken<A>) -> Self {
+        ApbClk { token }
+    }
+
+    #[inline]
+    fn free(self) -> ApbToken<A> {
+        self.token
+    }
+}
+
+//==============================================================================
+// ApbTokens
+//==============================================================================
+
+/// Set of [`ApbToken`]s for APB clocks that are disabled at power-on reset
+#[hal_macro_helper]
+pub struct ApbTokens {
+    pub pac2: ApbToken<Pac2>,
+    pub ev_sys: ApbToken<EvSys>,
+    pub sercom0: ApbToken<Sercom0>,
+    pub sercom1: ApbToken<Sercom1>,
+    #[hal_cfg("sercom2")]
+    pub sercom2: ApbToken<Sercom2>,
+    #[hal_cfg("sercom3")]
+    pub sercom3: ApbToken<Sercom3>,
+    #[hal_cfg("sercom4")]
+    pub sercom4: ApbToken<Sercom4>,
+    #[hal_cfg("sercom5")]
+    pub sercom5: ApbToken<Sercom5>,
+    pub tcc0: ApbToken<Tcc0>,
+    #[hal_cfg("tcc1")]
+    pub tcc1: ApbToken<Tcc1>,
+    #[hal_cfg("tcc2")]
+    pub tcc2: ApbToken<Tcc2>,
+    #[hal_cfg("tc1")]
+    pub tc1: ApbToken<Tc1>,
+    #[hal_cfg("tc2")]
+    pub tc2: ApbToken<Tc2>,
+    #[hal_cfg("tc3")]
+    pub tc3: ApbToken<Tc3>,
+    #[hal_cfg("tc4")]
+    pub tc4: ApbToken<Tc4>,
+    #[hal_cfg("tc5")]
+    pub tc5: ApbToken<Tc5>,
+    #[hal_cfg("tc6")]
+    pub tc6: ApbToken<Tc6>,
+    #[hal_cfg("tc7")]
+    pub tc7: ApbToken<Tc7>,
+    pub ac: ApbToken<Ac>,
+    pub dac: ApbToken<Dac>,
+    pub ptc: ApbToken<Ptc>,
+    #[hal_cfg("i2s")]
+    pub i2s: ApbToken<I2S>,
+}
+
+impl ApbTokens {
+    /// Create the set of [`ApbToken`]s
+    ///
+    /// # Safety
+    ///
+    /// All invariants required by `ApbToken::new` must be upheld here as well.
+    #[inline]
+    #[hal_macro_helper]
+    pub(super) unsafe fn new() -> Self {
+        Self {
+            pac2: ApbToken::new(),
+            ev_sys: ApbToken::new(),
+            sercom0: ApbToken::new(),
+            sercom1: ApbToken::new(),
+            #[hal_cfg("sercom2")]
+            sercom2: ApbToken::new(),
+            #[hal_cfg("sercom3")]
+            sercom3: ApbToken::new(),
+            #[hal_cfg("sercom4")]
+            sercom4: ApbToken::new(),
+            #[hal_cfg("sercom5")]
+            sercom5: ApbToken::new(),
+            tcc0: ApbToken::new(),
+            #[hal_cfg("tcc1")]
+            tcc1: ApbToken::new(),
+            #[hal_cfg("tcc2")]
+            tcc2: ApbToken::new(),
+            #[hal_cfg("tc1")]
+            tc1: ApbToken::new(),
+            #[hal_cfg("tc2")]
+            tc2: ApbToken::new(),
+            #[hal_cfg("tc3")]
+            tc3: ApbToken::new(),
+            #[hal_cfg("tc4")]
+            tc4: ApbToken::new(),
+            #[hal_cfg("tc5")]
+            tc5: ApbToken::new(),
+            #[hal_cfg("tc6")]
+            tc6: ApbToken::new(),
+            #[hal_cfg("tc7")]
+            tc7: ApbToken::new(),
+            ac: ApbToken::new(),
+            dac: ApbToken::new(),
+            ptc: ApbToken::new(),
+            #[hal_cfg("i2s")]
+            i2s: ApbToken::new(),
+        }
+    }
+}
+
+//==============================================================================
+// ApbClks
+//==============================================================================
+
+/// Set of [`ApbClk`]s for APB clocks that are enabled at power-on reset
+#[hal_macro_helper]
+pub struct ApbClks {
+    pub pac0: ApbClk<Pac0>,
+    pub pm: ApbClk<Pm>,
+    pub sys_ctrl: ApbClk<SysCtrl>,
+    pub gclk: ApbClk<Gclk>,
+    pub wdt: ApbClk<Wdt>,
+    pub rtc: ApbClk<Rtc>,
+    pub eic: ApbClk<Eic>,
+    pub pac1: ApbClk<Pac1>,
+    pub dsu: ApbClk<Dsu>,
+    pub nvm_ctrl: ApbClk<NvmCtrl>,
+    pub port: ApbClk<Port>,
+    pub dmac: ApbClk<Dmac>,
+    #[hal_cfg("usb")]
+    pub usb: ApbClk<Usb>,
+    pub hmatrix: ApbClk<Hmatrix>,
+    pub adc: ApbClk<Adc>,
+}
+
+impl ApbClks {
+    /// Create the set of [`ApbClk`]s
+    ///
+    /// # Safety
+    ///
+    /// All invariants required by `ApbToken::new` must be upheld here as well.
+    #[inline]
+    #[hal_macro_helper]
+    pub(super) unsafe fn new() -> Self {
+        ApbClks {
+            pac0: ApbClk::new(ApbToken::new()),
+            pm: ApbClk::new(ApbToken::new()),
+            sys_ctrl: ApbClk::new(ApbToken::new()),
+            gclk: ApbClk::new(ApbToken::new()),
+            wdt: ApbClk::new(ApbToken::new()),
+            rtc: ApbClk::new(ApbToken::new()),
+            eic: ApbClk::new(ApbToken::new()),
+            pac1: ApbClk::new(ApbToken::new()),
+            dsu: ApbClk::new(ApbToken::new()),
+            nvm_ctrl: ApbClk::new(ApbToken::new()),
+            port: ApbClk::new(ApbToken::new()),
+            dmac: ApbClk::new(ApbToken::new()),
+            #[hal_cfg("usb")]
+            usb: ApbClk::new(ApbToken::new()),
+            hmatrix: ApbClk::new(ApbToken::new()),
+            adc: ApbClk::new(ApbToken::new()),
+        }
+    }
+}
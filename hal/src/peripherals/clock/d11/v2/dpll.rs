@@ -0,0 +1,689 @@
+//! # Digital Phase-Locked Loop
+//!
+//! ## Overview
+//!
+//! The `dpll` module provides access to the fractional digital phase-locked
+//! loop (FDPLL96M) within the `SYSCTRL` peripheral.
+//!
+//! A DPLL is used to multiply clock frequencies. It takes a lower-frequency
+//! input clock and produces a higher-frequency output clock. It works by taking
+//! the output clock, dividing it down to the same frequency as the input clock,
+//! comparing phase between the two signals, and locking that phase difference
+//! to zero. Consequently, the clock divider within the feedback loop sets the
+//! frequency multiplication factor.
+//!
+//! The DPLL operates over a large range of frequencies, but its operating
+//! region is not infinite. Specifically, it can only accept input frequencies
+//! between 32 kHz and 2 MHz, and it can only output frequencies in the range
+//! of 48 MHz to 96 MHz.
+//!
+//! Creating and configuring a [`Dpll`] proceeds according to the principles
+//! outlined in the [`clock` module documentation]. It is best shown with an
+//! example.
+//!
+//! ## Example
+//!
+//! Suppose we would like to run the main clock at 48 MHz, using the DPLL to
+//! multiply the output of an external 32 kHz crystal.
+//!
+//! ```text
+//! XOSC32K (32.768 kHz)
+//! └── DPLL (47.972 MHz)
+//!     └── GCLK0 (47.972 MHz)
+//!         └── Main clock (47.972 MHz)
+//! ```
+//!
+//! Because the main clock is limited to 48 MHz, the DPLL cannot drive
+//! [`Gclk0`] directly at its maximum output frequency. Instead, we multiply the
+//! 32.768 kHz input by 1464, which yields a 47.972 MHz output.
+//!
+//! ```no_run
+//! use atsamd_hal::{
+//!     clock::v2::{clock_system_at_reset, dpll::Dpll, xosc32k::Xosc32k},
+//!     gpio::Pins,
+//!     pac::Peripherals,
+//! };
+//! let mut pac = Peripherals::take().unwrap();
+//! let pins = Pins::new(pac.port);
+//! let (buses, clocks, tokens) = clock_system_at_reset(
+//!     pac.sysctrl,
+//!     pac.gclk,
+//!     pac.pm,
+//!     &mut pac.nvmctrl,
+//! );
+//! let xosc32k = Xosc32k::from_crystal(tokens.xosc32k, pins.pa00, pins.pa01)
+//!     .on_demand(false)
+//!     .enable();
+//! while !xosc32k.is_ready() {}
+//! let (dpll, xosc32k) = Dpll::from_xosc32k(tokens.dpll, xosc32k);
+//! let dpll = dpll.loop_div(1464, 0).enable();
+//! while !dpll.is_ready() {}
+//! let (gclk0, osc8m, dpll) = clocks.gclk0.swap_sources(clocks.osc8m, dpll);
+//! ```
+//!
+//! [`clock` module documentation]: super
+//! [`Gclk0`]: super::gclk::Gclk0
+//! [`Pclk`]: super::pclk::Pclk
+
+use fugit::RateExtU32;
+use typenum::U0;
+
+use crate::pac::sysctrl::{self, dpllctrlb::Refclkselect, dpllstatus};
+
+use crate::time::Hertz;
+use crate::typelevel::{Decrement, Increment, Sealed};
+
+use super::gclk::GclkId;
+use super::pclk::Pclk;
+use super::xosc::XoscId;
+use super::xosc32k::Xosc32kId;
+use super::{Enabled, Source};
+
+//==============================================================================
+// DpllToken
+//==============================================================================
+
+/// Singleton token that can be exchanged for a [`Dpll`]
+///
+/// As explained in the [`clock` module documentation](super), instances of
+/// various `Token` types can be exchanged for actual clock types. They
+/// typically represent clocks that are disabled at power-on reset.
+///
+/// The [`Dpll`] is disabled at power-on reset. To use it, you must first
+/// exchange the token for an actual clock with [`Dpll::from_pclk`],
+/// [`Dpll::from_xosc`] or [`Dpll::from_xosc32k`].
+pub struct DpllToken(());
+
+impl DpllToken {
+    /// Create a new instance of [`DpllToken`]
+    ///
+    /// # Safety
+    ///
+    /// The `DpllToken` is a singleton. There must never be two simulatenous
+    /// instances of it. See the notes on `Token` types and memory safety in the
+    /// root of the `clock` module for more details.
+    #[inline]
+    pub(super) unsafe fn new() -> Self {
+        Self(())
+    }
+
+    /// Access the `SYSCTRL` register block
+    #[inline]
+    fn sysctrl(&self) -> &sysctrl::RegisterBlock {
+        // Safety: The `DpllToken` only has access to a mutually exclusive set
+        // of registers for the DPLL, and we use a shared reference to the
+        // register block. See the notes on `Token` types and memory safety in
+        // the root of the `clock` module for more details.
+        unsafe { &*crate::pac::Sysctrl::PTR }
+    }
+
+    #[inline]
+    fn status(&self) -> dpllstatus::R {
+        self.sysctrl().dpllstatus().read()
+    }
+
+    #[inline]
+    fn configure(&mut self, id: DynDpllSourceId, settings: Settings, prediv: u16) {
+        // Convert the actual predivider to the `div` register field value
+        let div = match id {
+            DynDpllSourceId::Xosc => prediv / 2 - 1,
+            _ => 0,
+        };
+        self.sysctrl().dpllctrlb().write(|w| {
+            // Safety: The value is masked to the correct bit width by the PAC.
+            // An invalid value could produce an invalid clock frequency, but
+            // that does not break memory safety.
+            unsafe { w.div().bits(div) };
+            w.refclk().variant(id.into());
+            w.lbypass().bit(settings.lock_bypass);
+            w.wuf().bit(settings.wake_up_fast)
+        });
+        // Safety: The values are masked to the correct bit width by the PAC.
+        // Invalid values here could produce invalid clock frequencies, but that
+        // does not break memory safety.
+        self.sysctrl().dpllratio().write(|w| unsafe {
+            w.ldr().bits(settings.mult - 1);
+            w.ldrfrac().bits(settings.frac)
+        });
+        self.sysctrl().dpllctrla().write(|w| {
+            w.ondemand().bit(settings.on_demand);
+            w.runstdby().bit(settings.run_standby)
+        });
+    }
+
+    #[inline]
+    fn enable(&mut self) {
+        self.sysctrl()
+            .dpllctrla()
+            .modify(|_, w| w.enable().set_bit());
+        while self.status().enable().bit_is_clear() {}
+    }
+
+    #[inline]
+    fn disable(&mut self) {
+        self.sysctrl()
+            .dpllctrla()
+            .modify(|_, w| w.enable().clear_bit());
+        while self.status().enable().bit_is_set() {}
+    }
+
+    #[inline]
+    fn is_locked(&self) -> bool {
+        self.status().lock().bit()
+    }
+
+    #[inline]
+    fn is_ready(&self) -> bool {
+        self.status().clkrdy().bit()
+    }
+}
+
+//==============================================================================
+// DpllId
+//==============================================================================
+
+/// [`Id` type](super#id-types) representing the identity of the DPLL clock
+///
+/// `DpllId` is also the [`PclkId`](super::pclk::PclkId) of the DPLL reference
+/// clock input.
+pub enum DpllId {}
+
+impl Sealed for DpllId {}
+
+//==============================================================================
+// DynDpllSourceId
+//==============================================================================
+
+/// Value-level enum of possible clock sources for a [`Dpll`]
+///
+/// The variants of this enum identify one of three possible clock sources for
+/// the [`Dpll`].
+///
+/// `DynDpllSourceId` is the value-level equivalent of [`DpllSourceId`].
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum DynDpllSourceId {
+    /// The DPLL is driven by a [`Pclk`]
+    Pclk,
+    /// The DPLL is driven by the [`Xosc`](super::xosc::Xosc)
+    Xosc,
+    /// The DPLL is driven by the [`Xosc32k`](super::xosc32k::Xosc32k)
+    Xosc32k,
+}
+
+impl From<DynDpllSourceId> for Refclkselect {
+    fn from(source: DynDpllSourceId) -> Self {
+        match source {
+            DynDpllSourceId::Pclk => Refclkselect::Gclk,
+            DynDpllSourceId::Xosc => Refclkselect::Ref1,
+            DynDpllSourceId::Xosc32k => Refclkselect::Ref0,
+        }
+    }
+}
+
+//==============================================================================
+// DpllSourceId
+//==============================================================================
+
+/// Type-level enum of possible clock [`Source`]s for a [`Dpll`]
+///
+/// The types implementing this trait are type-level variants of
+/// `DpllSourceId`, and they identify one of three possible clock [`Source`]s
+/// for the [`Dpll`]. All implementers of this trait are
+/// [`Id` types](super#id-types), which are described in more detail in the
+/// [`clock` module documentation](super).
+///
+/// `DpllSourceId` is the type-level equivalent of [`DynDpllSourceId`]. See
+/// the documentation on [type-level programming] and specifically
+/// [type-level enums] for more details.
+///
+/// [type-level programming]: crate::typelevel
+/// [type-level enums]: crate::typelevel#type-level-enums
+pub trait DpllSourceId {
+    /// Corresponding variant of [`DynDpllSourceId`]
+    const DYN: DynDpllSourceId;
+
+    /// Reference-specific settings type
+    #[doc(hidden)]
+    type Reference: settings::Reference;
+}
+
+impl<G: GclkId> DpllSourceId for G {
+    const DYN: DynDpllSourceId = DynDpllSourceId::Pclk;
+    type Reference = settings::Pclk<G>;
+}
+impl DpllSourceId for XoscId {
+    const DYN: DynDpllSourceId = DynDpllSourceId::Xosc;
+    type Reference = settings::Xosc;
+}
+impl DpllSourceId for Xosc32kId {
+    const DYN: DynDpllSourceId = DynDpllSourceId::Xosc32k;
+    type Reference = settings::Xosc32k;
+}
+
+//==============================================================================
+// Settings
+//==============================================================================
+
+// Struct containing all possible settings for a [`Dpll`]
+#[derive(Copy, Clone)]
+struct Settings {
+    mult: u16,
+    frac: u8,
+    lock_bypass: bool,
+    wake_up_fast: bool,
+    on_demand: bool,
+    run_standby: bool,
+}
+
+/// Store and retrieve [`Dpll`] settings for different reference clocks
+mod settings {
+    use super::super::pclk;
+    use super::RateExtU32;
+    use super::{DpllId, GclkId, Hertz};
+
+    /// [`Dpll`] settings when referenced to a [`Pclk`]
+    ///
+    /// [`Dpll`]: super::Dpll
+    /// [`Pclk`]: pclk::Pclk
+    pub struct Pclk<G: GclkId> {
+        pub pclk: pclk::Pclk<DpllId, G>,
+    }
+
+    /// [`Dpll`] settings when referenced to the [`Xosc`]
+    ///
+    /// [`Dpll`]: super::Dpll
+    /// [`Xosc`]: super::super::xosc::Xosc
+    pub struct Xosc {
+        pub freq: Hertz,
+        pub prediv: u16,
+    }
+
+    /// [`Dpll`] settings when referenced to the [`Xosc32k`]
+    ///
+    /// [`Dpll`]: super::Dpll
+    /// [`Xosc32k`]: super::super::xosc32k::Xosc32k
+    pub struct Xosc32k;
+
+    /// Generic interface for the frequency and predivider of a reference clock
+    pub trait Reference {
+        fn freq(&self) -> Hertz;
+        fn prediv(&self) -> u16;
+    }
+
+    impl<G: GclkId> Reference for Pclk<G> {
+        #[inline]
+        fn freq(&self) -> Hertz {
+            self.pclk.freq()
+        }
+        #[inline]
+        fn prediv(&self) -> u16 {
+            1
+        }
+    }
+
+    impl Reference for Xosc {
+        #[inline]
+        fn freq(&self) -> Hertz {
+            self.freq
+        }
+        #[inline]
+        fn prediv(&self) -> u16 {
+            self.prediv
+        }
+    }
+
+    impl Reference for Xosc32k {
+        #[inline]
+        fn freq(&self) -> Hertz {
+            32_768.Hz()
+        }
+        #[inline]
+        fn prediv(&self) -> u16 {
+            1
+        }
+    }
+}
+
+//==============================================================================
+// Dpll
+//==============================================================================
+
+/// The digital phase-locked loop
+///
+/// On its own, an instance of `Dpll` does not represent an enabled DPLL.
+/// Instead, it must first be wrapped with [`Enabled`], which implements
+/// compile-time safety of the clock tree.
+///
+/// The type parameter `I` is a [`DpllSourceId`] that identifies the reference
+/// clock.
+///
+/// Because the terminal call to [`enable`] consumes the `Dpll` and returns an
+/// [`EnabledDpll`], the remaining API uses the builder pattern, where each
+/// method takes and returns `self` by value, allowing them to be easily
+/// chained.
+///
+/// See the [module-level documentation](self) for an example of creating,
+/// configuring and using a `Dpll`.
+///
+/// [`enable`]: Dpll::enable
+pub struct Dpll<I: DpllSourceId> {
+    token: DpllToken,
+    reference: I::Reference,
+    settings: Settings,
+}
+
+impl<I: DpllSourceId> Dpll<I> {
+    fn new(token: DpllToken, reference: I::Reference) -> Self {
+        let settings = Settings {
+            mult: 1,
+            frac: 0,
+            lock_bypass: false,
+            wake_up_fast: false,
+            on_demand: true,
+            run_standby: false,
+        };
+        Self {
+            token,
+            reference,
+            settings,
+        }
+    }
+}
+
+impl<G: GclkId> Dpll<G> {
+    /// Create a [`Dpll`] from a [`Pclk`]
+    ///
+    /// Creating a [`Dpll`] does not modify any of the hardware registers. It
+    /// only creates a struct to track the DPLL configuration.
+    ///
+    /// The configuration data is stored until the user calls [`enable`]. At
+    /// that point, all of the registers are written according to the
+    /// initialization procedures specified in the datasheet, and an
+    /// [`EnabledDpll`] is returned. The `Dpll` is not active or useful until
+    /// that point.
+    ///
+    /// [`enable`]: Dpll::enable
+    #[inline]
+    pub fn from_pclk(token: DpllToken, pclk: Pclk<DpllId, G>) -> Self {
+        let reference = settings::Pclk { pclk };
+        Dpll::new(token, reference)
+    }
+
+    /// Consume the [`Dpll`], release the [`DpllToken`], and return the
+    /// [`Pclk`]
+    #[inline]
+    pub fn free_pclk(self) -> (DpllToken, Pclk<DpllId, G>) {
+        (self.token, self.reference.pclk)
+    }
+}
+
+impl Dpll<XoscId> {
+    /// Create a [`Dpll`] from the [`Xosc`]
+    ///
+    /// Note that, when the [`Xosc`] is used as the `Dpll` [`Source`], there is
+    /// an additional, programmable predivider, which defaults to 2. See
+    /// [`Dpll::prediv`].
+    ///
+    /// Creating a [`Dpll`] does not modify any of the hardware registers. It
+    /// only creates a struct to track the DPLL configuration and
+    /// [`Increment`]s the [`Source`] [`Enabled`] counter.
+    ///
+    /// [`Xosc`]: super::xosc::Xosc
+    #[inline]
+    pub fn from_xosc<S>(token: DpllToken, source: S) -> (Self, S::Inc)
+    where
+        S: Source<Id = XoscId> + Increment,
+    {
+        let reference = settings::Xosc {
+            freq: source.freq(),
+            prediv: 2,
+        };
+        let dpll = Dpll::new(token, reference);
+        (dpll, source.inc())
+    }
+
+    /// Consume the [`Dpll`], release the [`DpllToken`], and [`Decrement`] the
+    /// [`EnabledXosc`] consumer count
+    ///
+    /// [`EnabledXosc`]: super::xosc::EnabledXosc
+    #[inline]
+    pub fn free_xosc<S>(self, source: S) -> (DpllToken, S::Dec)
+    where
+        S: Source<Id = XoscId> + Decrement,
+    {
+        (self.token, source.dec())
+    }
+
+    /// Set the [`Xosc`] predivider
+    ///
+    /// The predivider must be an even integer in the range `[2, 4096]`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the predivider is out of range or odd.
+    ///
+    /// [`Xosc`]: super::xosc::Xosc
+    #[inline]
+    pub fn prediv(mut self, prediv: u16) -> Self {
+        if prediv % 2 != 0 || prediv < 2 || prediv > 4096 {
+            panic!("DPLL prediv must be an even integer in the range [2, 4096]")
+        }
+        self.reference.prediv = prediv;
+        self
+    }
+}
+
+impl Dpll<Xosc32kId> {
+    /// Create a [`Dpll`] from the [`Xosc32k`]
+    ///
+    /// Creating a [`Dpll`] does not modify any of the hardware registers. It
+    /// only creates a struct to track the DPLL configuration and
+    /// [`Increment`]s the [`Source`] [`Enabled`] counter.
+    ///
+    /// [`Xosc32k`]: super::xosc32k::Xosc32k
+    #[inline]
+    pub fn from_xosc32k<S>(token: DpllToken, source: S) -> (Self, S::Inc)
+    where
+        S: Source<Id = Xosc32kId> + Increment,
+    {
+        let dpll = Dpll::new(token, settings::Xosc32k);
+        (dpll, source.inc())
+    }
+
+    /// Consume the [`Dpll`], release the [`DpllToken`], and [`Decrement`] the
+    /// [`EnabledXosc32k`] consumer count
+    ///
+    /// [`EnabledXosc32k`]: super::xosc32k::EnabledXosc32k
+    #[inline]
+    pub fn free_xosc32k<S>(self, source: S) -> (DpllToken, S::Dec)
+    where
+        S: Source<Id = Xosc32kId> + Decrement,
+    {
+        (self.token, source.dec())
+    }
+}
+
+impl<I: DpllSourceId> Dpll<I> {
+    /// Set the [`Dpll`] loop divider, which is also the frequency
+    /// multiplication factor
+    ///
+    /// The inputs to this function are the natural integer and fractional
+    /// parts of the division factor, i.e. the division factor is:
+    ///
+    /// ```text
+    /// int + frac / 16
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// The integer part must be in the range `[1, 4096]`, and the fractional
+    /// part must be less than 16.
+    #[inline]
+    pub fn loop_div(mut self, int: u16, frac: u8) -> Self {
+        if int < 1 || int > 0x1000 {
+            panic!("Invalid integer part of the DPLL loop divider")
+        }
+        if frac > 15 {
+            panic!("Invalid fractional part of the DPLL loop divider")
+        }
+        self.settings.mult = int;
+        self.settings.frac = frac;
+        self
+    }
+
+    /// Bypass the [`Dpll`] lock
+    ///
+    /// If `true`, the output clock is always available, even before the loop
+    /// has locked.
+    #[inline]
+    pub fn lock_bypass(mut self, bypass: bool) -> Self {
+        self.settings.lock_bypass = bypass;
+        self
+    }
+
+    /// Output the [`Dpll`] clock immediately, without waiting for various
+    /// conditions
+    ///
+    /// See the datasheet for complete details.
+    #[inline]
+    pub fn wake_up_fast(mut self, wuf: bool) -> Self {
+        self.settings.wake_up_fast = wuf;
+        self
+    }
+
+    /// Set the [`Dpll`] on-demand behavior
+    ///
+    /// When enabled, the DPLL will only run when requested by a consumer.
+    #[inline]
+    pub fn on_demand(mut self, on_demand: bool) -> Self {
+        self.settings.on_demand = on_demand;
+        self
+    }
+
+    /// Set the [`Dpll`] run-standby behavior
+    ///
+    /// When enabled, the DPLL will run in Standby sleep mode if requested by
+    /// a consumer.
+    #[inline]
+    pub fn run_standby(mut self, run_standby: bool) -> Self {
+        self.settings.run_standby = run_standby;
+        self
+    }
+
+    #[inline]
+    fn input_freq(&self) -> Hertz {
+        use settings::Reference;
+        self.reference.freq() / self.reference.prediv() as u32
+    }
+
+    #[inline]
+    fn output_freq(&self) -> Hertz {
+        let input = self.input_freq();
+        input * self.settings.mult as u32 + input * self.settings.frac as u32 / 16
+    }
+
+    /// Return the output frequency of the [`Dpll`]
+    #[inline]
+    pub fn freq(&self) -> Hertz {
+        self.output_freq()
+    }
+
+    /// Enable the [`Dpll`], so that it can be used as a clock [`Source`]
+    ///
+    /// As mentioned when creating a new `Dpll`, no hardware registers are
+    /// actually modified until this call. Rather, the desired configuration is
+    /// stored internally, and the `Dpll` is initialized and configured here
+    /// according to the datasheet.
+    ///
+    /// The returned value is an [`EnabledDpll`] that can be used as a clock
+    /// [`Source`] for other clocks.
+    ///
+    /// # Panics
+    ///
+    /// This function will also check that the input and output clock
+    /// frequencies fall within the valid ranges specified in the datasheet.
+    /// Specifically, the input frequency must be between 32 kHz and 2 MHz,
+    /// while the output frequency must be between 48 MHz and 96 MHz. If either
+    /// frequency is invalid, this call will panic.
+    #[inline]
+    pub fn enable(self) -> EnabledDpll<I> {
+        let input_freq = self.input_freq().to_Hz();
+        let output_freq = self.output_freq().to_Hz();
+        if input_freq < 32_000 || input_freq > 2_000_000 {
+            panic!("Invalid DPLL input frequency");
+        }
+        if output_freq < 48_000_000 || output_freq > 96_000_000 {
+            panic!("Invalid DPLL output frequency");
+        }
+        self.enable_unchecked()
+    }
+
+    /// Enable the [`Dpll`] without validating the input & output frequencies
+    ///
+    /// This is equivalent to calling [`Dpll::enable`] but without the checks on
+    /// input and output frequencies. Using frequencies outside the ranges
+    /// specified in the datasheet may not work and could cause clocking
+    /// problems.
+    #[inline]
+    pub fn enable_unchecked(mut self) -> EnabledDpll<I> {
+        use settings::Reference;
+        let prediv = self.reference.prediv();
+        self.token.configure(I::DYN, self.settings, prediv);
+        self.token.enable();
+        Enabled::new(self)
+    }
+}
+
+//==============================================================================
+// EnabledDpll
+//==============================================================================
+
+/// An [`Enabled`] [`Dpll`]
+///
+/// As described in the [`clock` module documentation](super), the [`Enabled`]
+/// wrapper implements compile-time clock tree safety by tracking the number of
+/// consumer clocks and restricting access to the underlying [`Dpll`] to
+/// prevent modification while in use.
+///
+/// As with [`Enabled`], the default value for `N` is `U0`; if left unspecified,
+/// the counter is assumed to be zero.
+pub type EnabledDpll<I, N = U0> = Enabled<Dpll<I>, N>;
+
+impl<I: DpllSourceId> EnabledDpll<I> {
+    /// Disable the [`Dpll`]
+    ///
+    /// This method is only implemented for `N = U0`, which means the clock can
+    /// only be disabled when no other clocks consume this [`Dpll`].
+    #[inline]
+    pub fn disable(mut self) -> Dpll<I> {
+        self.0.token.disable();
+        self.0
+    }
+}
+
+impl<I: DpllSourceId, N> EnabledDpll<I, N> {
+    /// Test whether the [`Dpll`] is locked
+    #[inline]
+    pub fn is_locked(&self) -> bool {
+        self.0.token.is_locked()
+    }
+
+    /// Test whether the [`Dpll`] is ready
+    #[inline]
+    pub fn is_ready(&self) -> bool {
+        self.0.token.is_ready()
+    }
+}
+
+//==============================================================================
+// Source
+//==============================================================================
+
+impl<I: DpllSourceId, N> Source for EnabledDpll<I, N> {
+    type Id = DpllId;
+
+    #[inline]
+    fn freq(&self) -> Hertz {
+        self.0.freq()
+    }
+}
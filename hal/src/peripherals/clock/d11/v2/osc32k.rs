@@ -0,0 +1,332 @@
+//! # Internal, 32 kHz high-accuracy oscillator
+//!
+//! ## Overview
+//!
+//! The `osc32k` module provides access to the internal, 32 kHz high-accuracy
+//! oscillator (OSC32K) within the `SYSCTRL` peripheral.
+//!
+//! The OSC32K is disabled at power-on reset. When enabled, its output is
+//! calibrated with the factory value stored in the NVM software calibration
+//! area. Only the 32 kHz output can be used as a source for the [`Gclk`]s, so
+//! [`Osc32k`] enables the oscillator and its 32 kHz output together.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use atsamd_hal::{
+//!     clock::v2::{
+//!         clock_system_at_reset,
+//!         osc32k::{Osc32k, StartUpDelay},
+//!     },
+//!     pac::Peripherals,
+//! };
+//! let mut pac = Peripherals::take().unwrap();
+//! let (buses, clocks, tokens) = clock_system_at_reset(
+//!     pac.sysctrl,
+//!     pac.gclk,
+//!     pac.pm,
+//!     &mut pac.nvmctrl,
+//! );
+//! let osc32k = Osc32k::new(tokens.osc32k)
+//!     .start_up_delay(StartUpDelay::Delay122us)
+//!     .run_standby(true)
+//!     .enable();
+//! while !osc32k.is_ready() {}
+//! ```
+//!
+//! [`Gclk`]: super::gclk::Gclk
+
+use fugit::RateExtU32;
+use typenum::U0;
+
+use crate::pac::sysctrl::{self, Osc32k as Osc32kReg};
+
+use crate::time::Hertz;
+use crate::typelevel::Sealed;
+
+use super::{Enabled, Source};
+
+//==============================================================================
+// Osc32kToken
+//==============================================================================
+
+/// Singleton token that can be exchanged for an [`Osc32k`]
+///
+/// As explained in the [`clock` module documentation](super), instances of
+/// various `Token` types can be exchanged for actual clock types. They
+/// typically represent clocks that are disabled at power-on reset.
+///
+/// The [`Osc32k`] is disabled at power-on reset. To use it, you must first
+/// exchange the token for an actual clock with [`Osc32k::new`].
+pub struct Osc32kToken(());
+
+impl Osc32kToken {
+    /// Create a new instance of [`Osc32kToken`]
+    ///
+    /// # Safety
+    ///
+    /// The `Osc32kToken` is a singleton. There must never be two simulatenous
+    /// instances of it. See the notes on `Token` types and memory safety in the
+    /// root of the `clock` module for more details.
+    #[inline]
+    pub(super) unsafe fn new() -> Self {
+        Self(())
+    }
+
+    /// Return a reference to the OSC32K register
+    #[inline]
+    fn osc32k(&self) -> &Osc32kReg {
+        // Safety: The `Osc32kToken` has exclusive access to the `OSC32K`
+        // register, and we use a shared reference to the register block. See
+        // the notes on `Token` types and memory safety in the root of the
+        // `clock` module for more details.
+        unsafe { (*crate::pac::Sysctrl::PTR).osc32k() }
+    }
+
+    /// Read the PCLKSR register
+    #[inline]
+    fn pclksr(&self) -> sysctrl::pclksr::R {
+        // Safety: We are only reading from the `PCLKSR` register, so there is
+        // no risk of memory corruption.
+        unsafe { (*crate::pac::Sysctrl::PTR).pclksr().read() }
+    }
+
+    /// Check whether the OSC32K is stable and ready
+    #[inline]
+    fn is_ready(&self) -> bool {
+        self.pclksr().osc32krdy().bit_is_set()
+    }
+
+    /// Write the complete OSC32K register, leaving the oscillator disabled
+    #[inline]
+    fn configure(&mut self, settings: Settings) {
+        let calib = crate::calibration::osc32k_cal();
+        // Safety: The `STARTUP` value comes from the `StartUpDelay`, so it is
+        // guaranteed to be valid, and the `CALIB` value comes from the NVM
+        // software calibration area.
+        self.osc32k().write(|w| unsafe {
+            w.calib().bits(calib);
+            w.startup().bits(settings.start_up as u8);
+            w.ondemand().bit(settings.on_demand);
+            w.runstdby().bit(settings.run_standby);
+            w.en32k().set_bit()
+        });
+    }
+
+    /// Enable the OSC32K
+    #[inline]
+    fn enable(&mut self) {
+        self.osc32k().modify(|_, w| w.enable().set_bit());
+    }
+
+    /// Disable the OSC32K
+    #[inline]
+    fn disable(&mut self) {
+        self.osc32k().modify(|_, w| w.enable().clear_bit());
+    }
+
+    /// Freeze the OSC32K configuration until power-on reset
+    #[inline]
+    fn write_lock(&mut self) {
+        self.osc32k().modify(|_, w| w.wrtlock().set_bit());
+    }
+}
+
+//==============================================================================
+// Settings
+//==============================================================================
+
+// Collection of OSC32K register fields
+//
+// All of these fields are set in a single write to OSC32K during the call to
+// [`Osc32k::enable`].
+#[derive(Clone, Copy)]
+struct Settings {
+    start_up: StartUpDelay,
+    on_demand: bool,
+    run_standby: bool,
+}
+
+//==============================================================================
+// StartUpDelay
+//==============================================================================
+
+/// Start up delay before the [`Osc32k`] output is unmasked
+///
+/// After a hard reset or waking from sleep, the [`Osc32k`] output will remain
+/// masked for the start up period, to ensure an unstable clock is not
+/// propagated into the digital logic.
+#[repr(u8)]
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum StartUpDelay {
+    #[default]
+    Delay92us,
+    Delay122us,
+    Delay183us,
+    Delay305us,
+    Delay549us,
+    Delay1ms,
+    Delay2ms,
+    Delay4ms,
+}
+
+//==============================================================================
+// Osc32kId
+//==============================================================================
+
+/// Type representing the identity of the [`Osc32k`] clock
+///
+/// See the documentation on [`Id` types](super#id-types) for more details.
+pub enum Osc32kId {}
+
+impl Sealed for Osc32kId {}
+
+//==============================================================================
+// Osc32k
+//==============================================================================
+
+/// The internal, 32 kHz high-accuracy oscillator
+///
+/// On its own, an instance of `Osc32k` does not represent an enabled OSC32K.
+/// Instead, it must first be wrapped with [`Enabled`], which implements
+/// compile-time safety of the clock tree.
+///
+/// Because the terminal call to [`enable`] consumes the `Osc32k` and returns
+/// an [`EnabledOsc32k`], the remaining API uses the builder pattern, where
+/// each method takes and returns `self` by value, allowing them to be easily
+/// chained.
+///
+/// See the [module-level documentation](self) for an example of creating,
+/// configuring and using an `Osc32k`.
+///
+/// [`enable`]: Osc32k::enable
+pub struct Osc32k {
+    token: Osc32kToken,
+    settings: Settings,
+}
+
+/// An [`Enabled`] [`Osc32k`]
+///
+/// As described in the [`clock` module documentation](super), the [`Enabled`]
+/// wrapper implements compile-time clock tree safety by tracking the number of
+/// consumer clocks and restricting access to the underlying [`Osc32k`] to
+/// prevent modification while in use.
+///
+/// As with [`Enabled`], the default value for `N` is `U0`; if left unspecified,
+/// the counter is assumed to be zero.
+pub type EnabledOsc32k<N = U0> = Enabled<Osc32k, N>;
+
+impl Osc32k {
+    /// Create a new [`Osc32k`] from its [`Osc32kToken`]
+    ///
+    /// Creating an [`Osc32k`] does not modify any of the hardware registers.
+    /// It only creates a struct to track the configuration. The configuration
+    /// data is stored until the user calls [`enable`]. At that point, the
+    /// `OSC32K` register is written, and an [`EnabledOsc32k`] is returned.
+    ///
+    /// [`enable`]: Osc32k::enable
+    #[inline]
+    pub fn new(token: Osc32kToken) -> Self {
+        let settings = Settings {
+            start_up: StartUpDelay::Delay92us,
+            on_demand: true,
+            run_standby: false,
+        };
+        Self { token, settings }
+    }
+
+    /// Consume the [`Osc32k`] and release the [`Osc32kToken`]
+    #[inline]
+    pub fn free(self) -> Osc32kToken {
+        self.token
+    }
+
+    /// Set the start up delay before the [`Osc32k`] clock is unmasked
+    #[inline]
+    pub fn start_up_delay(mut self, delay: StartUpDelay) -> Self {
+        self.settings.start_up = delay;
+        self
+    }
+
+    /// Control the [`Osc32k`] on-demand behavior
+    ///
+    /// When the on-demand is enabled, the [`Osc32k`] clock will only run in
+    /// Idle or Standby sleep modes if it is requested by a peripheral.
+    /// Otherwise, its behavior is dependent on the run-standby setting.
+    #[inline]
+    pub fn on_demand(mut self, on_demand: bool) -> Self {
+        self.settings.on_demand = on_demand;
+        self
+    }
+
+    /// Control the [`Osc32k`] behavior in Standby sleep mode
+    ///
+    /// When `RUNSTDBY` is disabled, the [`Osc32k`] clock will never run in
+    /// Standby sleep mode unless `ONDEMAND` is enabled and the clock is
+    /// requested by a peripheral. When `RUNSTDBY` is enabled, the clock will
+    /// run in Standby sleep mode, but it can still be disabled if `ONDEMAND`
+    /// is enabled and the clock is not requested.
+    #[inline]
+    pub fn run_standby(mut self, run_standby: bool) -> Self {
+        self.settings.run_standby = run_standby;
+        self
+    }
+
+    /// Enable the [`Osc32k`], so that it can be used as a clock [`Source`]
+    ///
+    /// As mentioned when creating a new `Osc32k`, no hardware registers are
+    /// actually modified until this call. The returned value is an
+    /// [`EnabledOsc32k`] that can be used as a clock [`Source`] for other
+    /// clocks.
+    #[inline]
+    pub fn enable(mut self) -> EnabledOsc32k {
+        self.token.configure(self.settings);
+        self.token.enable();
+        Enabled::new(self)
+    }
+}
+
+impl EnabledOsc32k {
+    /// Disable the [`Osc32k`]
+    ///
+    /// This method is only implemented for `N = U0`, which means the clock can
+    /// only be disabled when no other clocks consume this [`Osc32k`].
+    #[inline]
+    pub fn disable(mut self) -> Osc32k {
+        self.0.token.disable();
+        self.0
+    }
+}
+
+impl<N> EnabledOsc32k<N> {
+    /// Check whether the OSC32K is stable and ready to be used as a clock
+    /// [`Source`]
+    #[inline]
+    pub fn is_ready(&self) -> bool {
+        self.0.token.is_ready()
+    }
+
+    /// Freeze the OSC32K configuration until power-on reset
+    ///
+    /// This function sets the write-lock bit, which freezes the OSC32K
+    /// configuration at the hardware level until power-on reset. At the API
+    /// level, it also consumes and drops the [`Osc32k`], which prevents any
+    /// further modifications.
+    #[inline]
+    pub fn write_lock(mut self) {
+        self.0.token.write_lock();
+    }
+}
+
+//==============================================================================
+// Source
+//==============================================================================
+
+impl<N> Source for EnabledOsc32k<N> {
+    type Id = Osc32kId;
+
+    #[inline]
+    fn freq(&self) -> Hertz {
+        32_768.Hz()
+    }
+}
@@ -0,0 +1,306 @@
+//! # Internal, 8 MHz oscillator
+//!
+//! ## Overview
+//!
+//! The `osc8m` module provides access to the internal, 8 MHz RC oscillator
+//! (OSC8M) within the `SYSCTRL` peripheral.
+//!
+//! The OSC8M is enabled at power-on reset, with a [`Prescaler`] of 8, and it
+//! drives [`Gclk0`], and thus the main clock, at 1 MHz. As a result, the
+//! [`Osc8m`] is returned by [`clock_system_at_reset`] as an [`EnabledOsc8m`]
+//! with one consumer.
+//!
+//! The OSC8M factory calibration is loaded automatically by the hardware at
+//! reset, so it is never modified here.
+//!
+//! ## Example
+//!
+//! To run the main clock at 8 MHz, [`Gclk0`] must first be moved to another
+//! [`Source`], so that the [`Osc8m`] can be reconfigured. Here we use the
+//! always-enabled [`OscUlp32k`] temporarily.
+//!
+//! ```no_run
+//! use atsamd_hal::{
+//!     clock::v2::{clock_system_at_reset, osc8m::Prescaler},
+//!     pac::Peripherals,
+//! };
+//! let mut pac = Peripherals::take().unwrap();
+//! let (buses, clocks, tokens) = clock_system_at_reset(
+//!     pac.sysctrl,
+//!     pac.gclk,
+//!     pac.pm,
+//!     &mut pac.nvmctrl,
+//! );
+//! let (gclk0, osc8m, osculp32k) =
+//!     clocks.gclk0.swap_sources(clocks.osc8m, clocks.osculp32k);
+//! let osc8m = osc8m.disable().prescaler(Prescaler::Div1).enable();
+//! let (gclk0, osc8m, osculp32k) = gclk0.swap_sources(osculp32k, osc8m);
+//! ```
+//!
+//! [`Gclk0`]: super::gclk::Gclk0
+//! [`OscUlp32k`]: super::osculp32k::OscUlp32k
+//! [`clock_system_at_reset`]: super::clock_system_at_reset
+
+use fugit::RateExtU32;
+use typenum::U0;
+
+use crate::pac::sysctrl::{self, osc8m::Prescselect, Osc8m as Osc8mReg};
+
+use crate::time::Hertz;
+use crate::typelevel::Sealed;
+
+use super::{Enabled, Source};
+
+//==============================================================================
+// Osc8mToken
+//==============================================================================
+
+/// Singleton token for the [`Osc8m`]
+///
+/// Unlike most other `Token` types, the `Osc8mToken` is never handed to the
+/// user, because the [`Osc8m`] is already enabled at power-on reset. It is
+/// stored within the [`Osc8m`] to provide exclusive access to the `OSC8M`
+/// register.
+pub struct Osc8mToken(());
+
+impl Osc8mToken {
+    /// Create a new instance of [`Osc8mToken`]
+    ///
+    /// # Safety
+    ///
+    /// The `Osc8mToken` is a singleton. There must never be two simulatenous
+    /// instances of it. See the notes on `Token` types and memory safety in the
+    /// root of the `clock` module for more details.
+    #[inline]
+    unsafe fn new() -> Self {
+        Self(())
+    }
+
+    /// Return a reference to the OSC8M register
+    #[inline]
+    fn osc8m(&self) -> &Osc8mReg {
+        // Safety: The `Osc8mToken` has exclusive access to the `OSC8M`
+        // register, and we use a shared reference to the register block. See
+        // the notes on `Token` types and memory safety in the root of the
+        // `clock` module for more details.
+        unsafe { (*crate::pac::Sysctrl::PTR).osc8m() }
+    }
+
+    /// Read the PCLKSR register
+    #[inline]
+    fn pclksr(&self) -> sysctrl::pclksr::R {
+        // Safety: We are only reading from the `PCLKSR` register, so there is
+        // no risk of memory corruption.
+        unsafe { (*crate::pac::Sysctrl::PTR).pclksr().read() }
+    }
+
+    /// Check whether the OSC8M is stable and ready
+    #[inline]
+    fn is_ready(&self) -> bool {
+        self.pclksr().osc8mrdy().bit_is_set()
+    }
+
+    /// Configure and enable the OSC8M
+    ///
+    /// The `CALIB` and `FRANGE` fields are preserved.
+    #[inline]
+    fn enable(&mut self, settings: Settings) {
+        self.osc8m().modify(|_, w| {
+            w.presc().variant(settings.prescaler.into());
+            w.ondemand().bit(settings.on_demand);
+            w.runstdby().bit(settings.run_standby);
+            w.enable().set_bit()
+        });
+    }
+
+    /// Disable the OSC8M
+    #[inline]
+    fn disable(&mut self) {
+        self.osc8m().modify(|_, w| w.enable().clear_bit());
+    }
+}
+
+//==============================================================================
+// Settings
+//==============================================================================
+
+// Collection of OSC8M register fields
+#[derive(Clone, Copy)]
+struct Settings {
+    prescaler: Prescaler,
+    on_demand: bool,
+    run_standby: bool,
+}
+
+//==============================================================================
+// Prescaler
+//==============================================================================
+
+/// Division factor applied to the 8 MHz output of the [`Osc8m`]
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum Prescaler {
+    Div1,
+    Div2,
+    Div4,
+    #[default]
+    Div8,
+}
+
+impl Prescaler {
+    /// Return the division factor as an integer
+    #[inline]
+    pub const fn div(self) -> u32 {
+        match self {
+            Prescaler::Div1 => 1,
+            Prescaler::Div2 => 2,
+            Prescaler::Div4 => 4,
+            Prescaler::Div8 => 8,
+        }
+    }
+}
+
+impl From<Prescaler> for Prescselect {
+    #[inline]
+    fn from(prescaler: Prescaler) -> Self {
+        match prescaler {
+            Prescaler::Div1 => Prescselect::_0,
+            Prescaler::Div2 => Prescselect::_1,
+            Prescaler::Div4 => Prescselect::_2,
+            Prescaler::Div8 => Prescselect::_3,
+        }
+    }
+}
+
+//==============================================================================
+// Osc8mId
+//==============================================================================
+
+/// Type representing the identity of the [`Osc8m`] clock
+///
+/// See the documentation on [`Id` types](super#id-types) for more details.
+pub enum Osc8mId {}
+
+impl Sealed for Osc8mId {}
+
+//==============================================================================
+// Osc8m
+//==============================================================================
+
+/// The internal, 8 MHz RC oscillator
+///
+/// On its own, an instance of `Osc8m` does not represent an enabled OSC8M.
+/// Instead, it must first be wrapped with [`Enabled`], which implements
+/// compile-time safety of the clock tree.
+///
+/// The `Osc8m` can only be reconfigured while it is disabled. Because it
+/// drives [`Gclk0`](super::gclk::Gclk0) at power-on reset, that generator must
+/// first be moved to a different [`Source`]. See the
+/// [module-level documentation](self) for an example.
+pub struct Osc8m {
+    token: Osc8mToken,
+    settings: Settings,
+}
+
+/// An [`Enabled`] [`Osc8m`]
+///
+/// As described in the [`clock` module documentation](super), the [`Enabled`]
+/// wrapper implements compile-time clock tree safety by tracking the number of
+/// consumer clocks and restricting access to the underlying [`Osc8m`] to
+/// prevent modification while in use.
+///
+/// As with [`Enabled`], the default value for `N` is `U0`; if left unspecified,
+/// the counter is assumed to be zero.
+pub type EnabledOsc8m<N = U0> = Enabled<Osc8m, N>;
+
+impl Osc8m {
+    /// Create the [`Osc8m`] in its power-on reset state
+    ///
+    /// # Safety
+    ///
+    /// This function must only be called once, when creating the clock tree
+    /// in [`clock_system_at_reset`](super::clock_system_at_reset).
+    #[inline]
+    pub(super) unsafe fn new() -> Self {
+        let settings = Settings {
+            prescaler: Prescaler::Div8,
+            on_demand: true,
+            run_standby: false,
+        };
+        Self {
+            token: Osc8mToken::new(),
+            settings,
+        }
+    }
+
+    /// Set the [`Prescaler`] applied to the 8 MHz output
+    #[inline]
+    pub fn prescaler(mut self, prescaler: Prescaler) -> Self {
+        self.settings.prescaler = prescaler;
+        self
+    }
+
+    /// Control the [`Osc8m`] on-demand behavior
+    ///
+    /// When the on-demand is enabled, the [`Osc8m`] clock will only run in
+    /// Idle or Standby sleep modes if it is requested by a peripheral.
+    /// Otherwise, its behavior is dependent on the run-standby setting.
+    #[inline]
+    pub fn on_demand(mut self, on_demand: bool) -> Self {
+        self.settings.on_demand = on_demand;
+        self
+    }
+
+    /// Control the [`Osc8m`] behavior in Standby sleep mode
+    ///
+    /// When `RUNSTDBY` is disabled, the [`Osc8m`] clock will never run in
+    /// Standby sleep mode unless `ONDEMAND` is enabled and the clock is
+    /// requested by a peripheral. When `RUNSTDBY` is enabled, the clock will
+    /// run in Standby sleep mode, but it can still be disabled if `ONDEMAND`
+    /// is enabled and the clock is not requested.
+    #[inline]
+    pub fn run_standby(mut self, run_standby: bool) -> Self {
+        self.settings.run_standby = run_standby;
+        self
+    }
+
+    /// Enable the [`Osc8m`], so that it can be used as a clock [`Source`]
+    #[inline]
+    pub fn enable(mut self) -> EnabledOsc8m {
+        self.token.enable(self.settings);
+        Enabled::new(self)
+    }
+}
+
+impl EnabledOsc8m {
+    /// Disable the [`Osc8m`]
+    ///
+    /// This method is only implemented for `N = U0`, which means the clock can
+    /// only be disabled when no other clocks consume this [`Osc8m`].
+    #[inline]
+    pub fn disable(mut self) -> Osc8m {
+        self.0.token.disable();
+        self.0
+    }
+}
+
+impl<N> EnabledOsc8m<N> {
+    /// Check whether the OSC8M is stable and ready to be used as a clock
+    /// [`Source`]
+    #[inline]
+    pub fn is_ready(&self) -> bool {
+        self.0.token.is_ready()
+    }
+}
+
+//==============================================================================
+// Source
+//==============================================================================
+
+impl<N> Source for EnabledOsc8m<N> {
+    type Id = Osc8mId;
+
+    #[inline]
+    fn freq(&self) -> Hertz {
+        (8_000_000 / self.0.settings.prescaler.div()).Hz()
+    }
+}
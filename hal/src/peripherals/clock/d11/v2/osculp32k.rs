@@ -0,0 +1,164 @@
+//! # Internal, ultra-low power, 32 kHz oscillator
+//!
+//! ## Overview
+//!
+//! The `osculp32k` module provides access to the 32 kHz ultra-low power
+//! internal oscillator (OSCULP32K) within the `SYSCTRL` peripheral.
+//!
+//! The `OSCULP32K` clock is unlike most other clocks, because it is always
+//! enabled and cannot be disabled. On the SAMD11 and SAMD21, it also has a
+//! single, always-enabled 32 kHz output, so it is represented in the type
+//! system by a single [`OscUlp32k`] clock, which is provided by
+//! [`clock_system_at_reset`] as an [`EnabledOscUlp32k`].
+//!
+//! The OSCULP32K is used internally by the [`Wdt`] and to count the start up
+//! delays of the other oscillators, so it cannot be disabled.
+//!
+//! The `OSCULP32K` register has a write lock bit that will freeze its
+//! configuration until the next power-on reset. We implement this by simply
+//! dropping the [`OscUlp32k`] clock, which prevents any further access to the
+//! register.
+//!
+//! ## Example
+//!
+//! The [`OscUlp32k`] is mostly used as a low-frequency source for a [`Gclk`].
+//!
+//! ```no_run
+//! use atsamd_hal::{
+//!     clock::v2::{clock_system_at_reset, gclk::Gclk},
+//!     pac::Peripherals,
+//! };
+//! let mut pac = Peripherals::take().unwrap();
+//! let (buses, clocks, tokens) = clock_system_at_reset(
+//!     pac.sysctrl,
+//!     pac.gclk,
+//!     pac.pm,
+//!     &mut pac.nvmctrl,
+//! );
+//! let (gclk2, osculp32k) = Gclk::from_source(tokens.gclks.gclk2, clocks.osculp32k);
+//! let gclk2 = gclk2.enable();
+//! ```
+//!
+//! [`Wdt`]: crate::watchdog::Watchdog
+//! [`Gclk`]: super::gclk::Gclk
+//! [`clock_system_at_reset`]: super::clock_system_at_reset
+
+use fugit::RateExtU32;
+use typenum::U0;
+
+use crate::pac::sysctrl::Osculp32k;
+
+use crate::time::Hertz;
+use crate::typelevel::Sealed;
+
+use super::{Enabled, Source};
+
+//==============================================================================
+// OscUlp32kToken
+//==============================================================================
+
+/// Singleton token for the [`OscUlp32k`] clock
+//
+// There should never be more than one instance of `OscUlp32kToken`, because it
+// relies on that fact for memory safety.
+//
+// Users never see `OscUlp32kToken`, because the OSCULP32K oscillator is always
+// enabled. Internally, however, it is used as a register interface. The token
+// is zero-sized, so it can be carried by clock types without introducing any
+// memory bloat.
+struct OscUlp32kToken(());
+
+impl OscUlp32kToken {
+    #[inline]
+    fn osculp32k(&self) -> &Osculp32k {
+        // Safety: The `OscUlp32kToken` has exclusive access to the
+        // `OSCULP32K` register. See the notes on `Token` types and memory
+        // safety in the root of the `clock` module for more details.
+        unsafe { (*crate::pac::Sysctrl::PTR).osculp32k() }
+    }
+
+    /// Enable the write lock
+    #[inline]
+    fn write_lock(&mut self) {
+        self.osculp32k().modify(|_, w| w.wrtlock().set_bit());
+    }
+}
+
+//==============================================================================
+// OscUlp32kId
+//==============================================================================
+
+/// Type representing the identity of the [`OscUlp32k`] clock
+///
+/// See the discussion on [`Id` types](super#id-types) for more information.
+pub enum OscUlp32kId {}
+
+impl Sealed for OscUlp32kId {}
+
+//==============================================================================
+// OscUlp32k
+//==============================================================================
+
+/// The internal, ultra-low power, 32 kHz oscillator
+///
+/// The OSCULP32K is always enabled. See the [module-level documentation](self)
+/// for details and examples.
+pub struct OscUlp32k {
+    token: OscUlp32kToken,
+}
+
+/// The [`Enabled`] [`OscUlp32k`] clock
+///
+/// As described in the [`clock` module documentation](super), the [`Enabled`]
+/// wrapper implements compile-time clock tree safety by tracking the number of
+/// clocks consuming the [`OscUlp32k`] clock and restricts access to the
+/// underlying type to prevent misuse.
+///
+/// **NOTE:** The `OscUlp32k` clock can never be disabled, so we do not provide
+/// a `disable` method.
+///
+/// As with [`Enabled`], the default value for `N` is `U0`; if left unspecified,
+/// the counter is assumed to be zero.
+pub type EnabledOscUlp32k<N = U0> = Enabled<OscUlp32k, N>;
+
+impl OscUlp32k {
+    /// Create the ultra-low power oscillator
+    ///
+    /// # Safety
+    ///
+    /// Because an `OscUlp32k` contains an `OscUlp32kToken`, there must never
+    /// be more than one instance of this struct at any given time. See the
+    /// notes on `Token` types and memory safety in the root of the `clock`
+    /// module for more details.
+    #[inline]
+    pub(super) unsafe fn new() -> EnabledOscUlp32k {
+        let token = OscUlp32kToken(());
+        Enabled::new(Self { token })
+    }
+}
+
+impl<N> EnabledOscUlp32k<N> {
+    /// Freeze the OSCULP32K configuration until power-on reset
+    ///
+    /// This function sets the write-lock bit, which freezes the OSCULP32K
+    /// configuration at the hardware level until power-on reset. At the API
+    /// level, it also consumes and drops the [`OscUlp32k`] clock, which
+    /// prevents any further modifications.
+    #[inline]
+    pub fn write_lock(mut self) {
+        self.0.token.write_lock();
+    }
+}
+
+//==============================================================================
+// Source
+//==============================================================================
+
+impl<N> Source for EnabledOscUlp32k<N> {
+    type Id = OscUlp32kId;
+
+    #[inline]
+    fn freq(&self) -> Hertz {
+        32_768.Hz()
+    }
+}
@@ -0,0 +1,196 @@
+//! This module is intentionally private. Its contents are publicly exported
+//! from the `v2` module, which is where the corresponding documentation will
+//! appear.
+
+use typenum::U1;
+
+use crate::pac::{Gclk, Nvmctrl, Pm, Sysctrl};
+
+use super::*;
+
+/// Collection of low-level PAC structs
+///
+/// This struct serves to guard access to the low-level PAC structs. It places
+/// them behind an `unsafe` barrier.
+///
+/// Normally, users trade the low-level PAC structs for the higher-level
+/// `clock::v2` API. However, in some cases, the `clock::v2` API may not be
+/// sufficient. In these cases, users can access the registers directly by
+/// calling [`Pac::steal`] to recover the PAC structs.
+pub struct Pac {
+    sysctrl: Sysctrl,
+    gclk: Gclk,
+    pm: Pm,
+}
+
+impl Pac {
+    /// Escape hatch allowing to access low-level PAC structs
+    ///
+    /// Consume the [`Pac`] and return the low-level PAC structs. This is
+    /// useful when the `clock::v2` API does not provide a necessary feature, or
+    /// when dealing with the legacy `clock::v1` API. For example, many of the
+    /// `clock::v1` functions require access to the [`Pm`] peripheral.
+    ///
+    /// # Safety
+    ///
+    /// Directly configuring clocks through the PAC API can invalidate the
+    /// type-level guarantees of the `clock` module API.
+    pub unsafe fn steal(self) -> (Sysctrl, Gclk, Pm) {
+        (self.sysctrl, self.gclk, self.pm)
+    }
+}
+
+/// Bus clock objects
+///
+/// This type is constructed using the [`clock_system_at_reset`] function, which
+/// consumes the PAC-level clocking structs and returns the HAL-level clocking
+/// structs in their reset state.
+///
+/// This type contains the [bus clocks](super#bus-clocks), which are a necessary
+/// to implement memory safety for the [`AhbClk`]s and [`ApbClk`]s.
+///
+/// [`AhbClk`]: super::ahb::AhbClk
+/// [`ApbClk`]: super::apb::ApbClk
+pub struct Buses {
+    pub ahb: ahb::Ahb,
+    pub apb: apb::Apb,
+}
+
+/// Enabled clocks at power-on reset
+///
+/// This type is constructed using the [`clock_system_at_reset`] function, which
+/// consumes the PAC-level clocking structs and returns the HAL-level clocking
+/// structs in their reset state.
+///
+/// This type represents the clocks as they are configured at power-on reset.
+/// The main clock, [`Gclk0`](gclk::Gclk0), runs at 1 MHz using the
+/// [`Osc8m`](osc8m::Osc8m) with a prescaler of 8. The ultra-low power
+/// [`OscUlp32k`](osculp32k::OscUlp32k) is also enabled and running, as it can
+/// never be disabled.
+///
+/// As described in the [top-level](super::super) documentation for the `clock`
+/// module, only [`Enabled`] clocks can be used as a [`Source`] for downstream
+/// clocks. This struct contains all of the `Enabled` clocks at reset.
+///
+/// This struct also contains the [`Pac`] wrapper struct, which provides
+/// `unsafe` access to the low-level PAC structs.
+pub struct Clocks {
+    /// Wrapper providing `unsafe` access to low-level PAC structs
+    pub pac: Pac,
+    /// Enabled AHB clocks
+    pub ahbs: ahb::AhbClks,
+    /// Enabled APB clocks
+    pub apbs: apb::ApbClks,
+    /// Main system clock, driven at 1 MHz by the OSC8M
+    pub gclk0: Enabled<gclk::Gclk0<osc8m::Osc8mId>, U1>,
+    /// OSC8M with a prescaler of 8
+    pub osc8m: Enabled<osc8m::Osc8m, U1>,
+    /// Always-enabled OSCULP32K oscillator
+    pub osculp32k: Enabled<osculp32k::OscUlp32k>,
+}
+
+/// Type-level tokens for unused clocks at power-on reset
+///
+/// This type is constructed using the [`clock_system_at_reset`] function, which
+/// consumes the PAC-level clocking structs and returns the HAL-level clocking
+/// structs in their reset state.
+///
+/// As described in the [top-level](super::super) documentation for the `clock`
+/// module, token types are used to guanrantee the uniqueness of each clock. To
+/// configure or enable a clock, you must provide the corresponding token.
+pub struct Tokens {
+    /// Tokens to create [`apb::ApbClk`]s
+    pub apbs: apb::ApbTokens,
+    /// Token to create [`dfll::Dfll`]
+    pub dfll: dfll::DfllToken,
+    /// Token to create [`dpll::Dpll`]
+    pub dpll: dpll::DpllToken,
+    /// Tokens to create [`gclk::Gclk`]
+    pub gclks: gclk::GclkTokens,
+    /// Tokens to create [`pclk::Pclk`]s
+    pub pclks: pclk::PclkTokens,
+    /// Token to create [`osc32k::Osc32k`]
+    pub osc32k: osc32k::Osc32kToken,
+    /// Token to create [`xosc::Xosc`]
+    pub xosc: xosc::XoscToken,
+    /// Token to create [`xosc32k::Xosc32k`]
+    pub xosc32k: xosc32k::Xosc32kToken,
+}
+
+/// Consume the PAC clocking structs and return a HAL-level
+/// representation of the clocks at power-on reset
+///
+/// This function consumes the [`Sysctrl`], [`Gclk`] and [`Pm`] PAC structs and
+/// returns the [`Buses`], [`Clocks`] and [`Tokens`].
+///
+/// Bootloaders commonly leave the main clock running from the DFLL, itself
+/// locked onto a crystal or the USB start of frame. To make sure the returned
+/// types match the hardware, this function re-enables the OSC8M with its reset
+/// prescaler and resets the `GCLK` peripheral, which returns
+/// [`Gclk0`](gclk::Gclk0) to the OSC8M and disables all other generators and
+/// peripheral channels. It then disables the DFLL, the DPLL and the XOSC,
+/// XOSC32K and OSC32K oscillators, whose tokens are returned. An XOSC32K
+/// locked with `WRTLOCK` cannot be disabled until the next reset.
+///
+/// It also sets the NVM to a single wait state, which supports a main clock
+/// of up to 48 MHz with VDD of at least 2.7 V. Lower supply voltages need
+/// more wait states at high frequencies.
+///
+/// See the [module-level documentation](super) for more details.
+#[inline]
+pub fn clock_system_at_reset(
+    sysctrl: Sysctrl,
+    gclk: Gclk,
+    pm: Pm,
+    nvmctrl: &mut Nvmctrl,
+) -> (Buses, Clocks, Tokens) {
+    sysctrl.osc8m().modify(|_, w| {
+        w.presc()._3();
+        w.ondemand().set_bit();
+        w.runstdby().clear_bit();
+        w.enable().set_bit()
+    });
+    while sysctrl.pclksr().read().osc8mrdy().bit_is_clear() {}
+    gclk.ctrl().write(|w| w.swrst().set_bit());
+    while gclk.status().read().syncbusy().bit_is_set() {}
+    // Nothing uses the other sources anymore. Clearing `ONDEMAND` along with
+    // `ENABLE` avoids the DFLL errata, where writing its registers hangs the
+    // bus while `ONDEMAND` is set.
+    sysctrl.dfllctrl().write(|w| w.enable().clear_bit());
+    while sysctrl.pclksr().read().dfllrdy().bit_is_clear() {}
+    sysctrl.dpllctrla().write(|w| w.ondemand().set_bit());
+    while sysctrl.dpllstatus().read().enable().bit_is_set() {}
+    sysctrl.xosc().modify(|_, w| w.enable().clear_bit());
+    sysctrl.xosc32k().modify(|_, w| w.enable().clear_bit());
+    sysctrl.osc32k().modify(|_, w| w.enable().clear_bit());
+    // Safety: No bus, clock or token is instantiated more than once
+    unsafe {
+        let buses = Buses {
+            ahb: ahb::Ahb::new(),
+            apb: apb::Apb::new(),
+        };
+        let pac = Pac { sysctrl, gclk, pm };
+        let osc8m = Enabled::<_>::new(osc8m::Osc8m::new());
+        let (gclk0, osc8m) = gclk::Gclk0::from_source(gclk::GclkToken::new(), osc8m);
+        let gclk0 = Enabled::new(gclk0);
+        let clocks = Clocks {
+            pac,
+            ahbs: ahb::AhbClks::new(),
+            apbs: apb::ApbClks::new(),
+            gclk0,
+            osc8m,
+            osculp32k: osculp32k::OscUlp32k::new(),
+        };
+        let tokens = Tokens {
+            apbs: apb::ApbTokens::new(),
+            dfll: dfll::DfllToken::new(),
+            dpll: dpll::DpllToken::new(),
+            gclks: gclk::GclkTokens::new(nvmctrl),
+            pclks: pclk::PclkTokens::new(),
+            osc32k: osc32k::Osc32kToken::new(),
+            xosc: xosc::XoscToken::new(),
+            xosc32k: xosc32k::Xosc32kToken::new(),
+        };
+        (buses, clocks, tokens)
+    }
+}
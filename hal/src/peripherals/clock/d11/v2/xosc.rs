@@ -0,0 +1,609 @@
+//! # External multipurpose crystal oscillator controller
+//!
+//! ## Overview
+//!
+//! The `xosc` module provides access to the external crystal oscillator
+//! controller (XOSC) within the `SYSCTRL` peripheral.
+//!
+//! The XOSC peripheral can operate in two [`Mode`]s. It can accept an external
+//! clock or can interface with an crystal oscillator. An external clock can be
+//! in the 0-32 MHz range, while a crystal must be in the 0.4-32 MHz range.
+//!
+//! When used with an external clock, only one GPIO [`Pin`] is required, but
+//! when used with a crystal oscillator, two GPIO `Pin`s are required. The
+//! [`XIn`] `Pin` is used in both `Mode`s, while the [`XOut`] `Pin` is only
+//! used in [`CrystalMode`].
+//!
+//! When operating in [`CrystalMode`], the oscillator gain is chosen
+//! automatically from the crystal frequency. The XOSC peripheral can also
+//! control the crystal amplitude automatically, to reduce power consumption.
+//!
+//! Creating and configuring an [`Xosc`] proceeds according to the principles
+//! outlined in the [`clock` module documentation]. It is best shown with an
+//! example.
+//!
+//! ## Example
+//!
+//! Let's start by using [`clock_system_at_reset`] to access the HAL clocking
+//! structs. We'll also need access to the GPIO [`Pins`].
+//!
+//! ```no_run
+//! use atsamd_hal::{
+//!     clock::v2::{
+//!         clock_system_at_reset,
+//!         xosc::{StartUpDelay, Xosc},
+//!     },
+//!     fugit::RateExtU32,
+//!     gpio::Pins,
+//!     pac::Peripherals,
+//! };
+//! let mut pac = Peripherals::take().unwrap();
+//! let pins = Pins::new(pac.port);
+//! let (buses, clocks, tokens) = clock_system_at_reset(
+//!     pac.sysctrl,
+//!     pac.gclk,
+//!     pac.pm,
+//!     &mut pac.nvmctrl,
+//! );
+//! ```
+//!
+//! Next, we can create and configure the [`Xosc`] in one long chain of methods,
+//! using the provided builder API. The final call to [`Xosc::enable`] yields an
+//! [`EnabledXosc`] that can act as a clock [`Source`] for other clocks in the
+//! tree.
+//!
+//! ```no_run
+//! # use atsamd_hal::{
+//! #     clock::v2::{
+//! #         clock_system_at_reset,
+//! #         xosc::{StartUpDelay, Xosc},
+//! #     },
+//! #     fugit::RateExtU32,
+//! #     gpio::Pins,
+//! #     pac::Peripherals,
+//! # };
+//! # let mut pac = Peripherals::take().unwrap();
+//! # let pins = Pins::new(pac.port);
+//! # let (buses, clocks, tokens) = clock_system_at_reset(
+//! #     pac.sysctrl,
+//! #     pac.gclk,
+//! #     pac.pm,
+//! #     &mut pac.nvmctrl,
+//! # );
+//! let xosc = Xosc::from_crystal(tokens.xosc, pins.pa14, pins.pa15, 8.MHz())
+//!     .amplitude_gain_control(true)
+//!     .start_up_delay(StartUpDelay::Delay488us)
+//!     .on_demand(false)
+//!     .run_standby(true)
+//!     .enable();
+//! while !xosc.is_ready() {}
+//! ```
+//!
+//! [`Pins`]: crate::gpio::Pins
+//! [`clock` module documentation]: super
+//! [`clock_system_at_reset`]: super::clock_system_at_reset
+
+use atsamd_hal_macros::hal_cfg;
+
+use typenum::U0;
+
+use crate::pac::sysctrl::xosc::Gainselect;
+use crate::pac::sysctrl::{self, Xosc as XoscReg};
+
+use crate::gpio::{FloatingDisabled, Pin};
+use crate::time::Hertz;
+use crate::typelevel::Sealed;
+
+use super::{Enabled, Source};
+
+#[hal_cfg("clock-d11")]
+use crate::gpio::{PA08, PA09};
+#[hal_cfg("clock-d21")]
+use crate::gpio::{PA14, PA15};
+
+//==============================================================================
+// XoscToken
+//==============================================================================
+
+/// Singleton token that can be exchanged for an [`Xosc`]
+///
+/// As explained in the [`clock` module documentation](super), instances of
+/// various `Token` types can be exchanged for actual clock types. They
+/// typically represent clocks that are disabled at power-on reset.
+///
+/// The [`Xosc`] is disabled at power-on reset. To use it, you must first
+/// exchange the token for an actual clock with [`Xosc::from_clock`] or
+/// [`Xosc::from_crystal`].
+pub struct XoscToken(());
+
+impl XoscToken {
+    /// Create a new instance of [`XoscToken`]
+    ///
+    /// # Safety
+    ///
+    /// The `XoscToken` is a singleton. There must never be two simulatenous
+    /// instances of it. See the notes on `Token` types and memory safety in the
+    /// root of the `clock` module for more details.
+    #[inline]
+    pub(super) unsafe fn new() -> Self {
+        Self(())
+    }
+
+    /// Return a reference to the XOSC register
+    #[inline]
+    fn xosc(&self) -> &XoscReg {
+        // Safety: The `XoscToken` has exclusive access to the `XOSC` register,
+        // and we use a shared reference to the register block. See the notes
+        // on `Token` types and memory safety in the root of the `clock` module
+        // for more details.
+        unsafe { (*crate::pac::Sysctrl::PTR).xosc() }
+    }
+
+    /// Read the PCLKSR register
+    #[inline]
+    fn pclksr(&self) -> sysctrl::pclksr::R {
+        // Safety: We are only reading from the `PCLKSR` register, so there is
+        // no risk of memory corruption.
+        unsafe { (*crate::pac::Sysctrl::PTR).pclksr().read() }
+    }
+
+    /// Check whether the XOSC is stable and ready
+    #[inline]
+    fn is_ready(&self) -> bool {
+        self.pclksr().xoscrdy().bit_is_set()
+    }
+
+    /// Write the complete XOSC register, leaving the oscillator disabled
+    #[inline]
+    fn configure(&mut self, settings: Settings) {
+        let xtalen = settings.mode == DynMode::CrystalMode;
+        // Safety: The `STARTUP` value comes from the `StartUpDelay`, so it is
+        // guaranteed to be valid.
+        self.xosc().write(|w| unsafe {
+            w.startup().bits(settings.start_up as u8);
+            w.gain().variant(settings.gain);
+            w.ondemand().bit(settings.on_demand);
+            w.runstdby().bit(settings.run_standby);
+            w.xtalen().bit(xtalen)
+        });
+    }
+
+    /// Enable automatic amplitude gain control
+    ///
+    /// The datasheet specifies that it must only be enabled once the crystal
+    /// oscillator is stable.
+    #[inline]
+    fn enable_amplitude_gain_control(&mut self) {
+        self.xosc().modify(|_, w| w.ampgc().set_bit());
+    }
+
+    /// Enable the XOSC
+    #[inline]
+    fn enable(&mut self) {
+        self.xosc().modify(|_, w| w.enable().set_bit());
+    }
+
+    /// Disable the XOSC
+    #[inline]
+    fn disable(&mut self) {
+        self.xosc().modify(|_, w| {
+            w.ampgc().clear_bit();
+            w.enable().clear_bit()
+        });
+    }
+}
+
+//==============================================================================
+// Settings
+//==============================================================================
+
+// Collection of XOSC register fields
+//
+// All of these fields are set in a single write to XOSC during the call to
+// [`Xosc::enable`]. Automatic amplitude gain control is only enabled once the
+// oscillator is stable.
+#[derive(Clone, Copy)]
+struct Settings {
+    start_up: StartUpDelay,
+    gain: Gainselect,
+    amplitude_gain_control: bool,
+    on_demand: bool,
+    run_standby: bool,
+    mode: DynMode,
+}
+
+//==============================================================================
+// XoscId
+//==============================================================================
+
+/// Type representing the identity of the XOSC
+///
+/// See the documentation on [`Id` types](super#id-types) for more details.
+pub enum XoscId {}
+
+impl Sealed for XoscId {}
+
+//==============================================================================
+// XIn & XOut
+//==============================================================================
+
+/// Type alias for the [`Xosc`] input [`Pin`]
+#[hal_cfg("clock-d11")]
+pub type XIn = Pin<PA08, FloatingDisabled>;
+
+/// Type alias for the [`Xosc`] output [`Pin`]
+#[hal_cfg("clock-d11")]
+pub type XOut = Pin<PA09, FloatingDisabled>;
+
+/// Type alias for the [`Xosc`] input [`Pin`]
+#[hal_cfg("clock-d21")]
+pub type XIn = Pin<PA14, FloatingDisabled>;
+
+/// Type alias for the [`Xosc`] output [`Pin`]
+#[hal_cfg("clock-d21")]
+pub type XOut = Pin<PA15, FloatingDisabled>;
+
+//==============================================================================
+// StartUpDelay
+//==============================================================================
+
+/// Start up delay before the [`Xosc`] output is unmasked
+///
+/// After a hard reset or waking from sleep, the [`Xosc`] output will remained
+/// masked for the start up period, to ensure an unstable clock is not
+/// propagated into the digital logic.
+///
+/// The start up delay is counted using the [`OscUlp32k`] clock, and the delay
+/// is equal to 2^N clock cycles, where N is selectable in the range `0..16`.
+///
+/// [`OscUlp32k`]: super::osculp32k::OscUlp32k
+#[repr(u8)]
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum StartUpDelay {
+    #[default]
+    Delay31us,
+    Delay62us,
+    Delay122us,
+    Delay244us,
+    Delay488us,
+    Delay977us,
+    Delay2ms,
+    Delay4ms,
+    Delay8ms,
+    Delay16ms,
+    Delay31ms,
+    Delay63ms,
+    Delay125ms,
+    Delay250ms,
+    Delay500ms,
+    Delay1s,
+}
+
+//==============================================================================
+// DynMode
+//==============================================================================
+
+/// Value-level enum identifying one of two possible [`Xosc`] operating modes
+///
+/// An [`Xosc`] can be sourced from either an external clock or crystal
+/// oscillator. The variants of this enum identify one of these two possible
+/// operating modes.
+///
+/// `DynMode` is the value-level equivalent of [`Mode`].
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum DynMode {
+    #[default]
+    ClockMode,
+    CrystalMode,
+}
+
+//==============================================================================
+// Mode
+//==============================================================================
+
+/// Type-level `enum` for the [`Xosc`] operating mode
+///
+/// An [`Xosc`] can be sourced from either an external clock or a cyrstal
+/// oscillator. This type-level `enum` provides two type-level variants,
+/// [`ClockMode`] and [`CrystalMode`], representing these operating modes.
+///
+/// `Mode` is the type-level equivalent of [`DynMode`]. See the documentation on
+/// [type-level programming] and specifically [type-level enums] for more
+/// details.
+///
+/// [type-level programming]: crate::typelevel
+/// [type-level enums]: crate::typelevel#type-level-enums
+pub trait Mode: Sealed {
+    /// Corresponding variant of [`DynMode`]
+    const DYN: DynMode;
+    #[doc(hidden)]
+    type Pins;
+}
+
+//==============================================================================
+// ClockMode
+//==============================================================================
+
+/// Type-level variant of the [`Xosc`] operating [`Mode`]
+///
+/// Represents the [`Xosc`] configured to use an externally provided clock.
+///
+/// See the documentation on [type-level programming] and specifically
+/// [type-level enums] for more details.
+///
+/// [type-level programming]: crate::typelevel
+/// [type-level enums]: crate::typelevel#type-level-enums
+pub enum ClockMode {}
+
+impl Sealed for ClockMode {}
+
+impl Mode for ClockMode {
+    const DYN: DynMode = DynMode::ClockMode;
+    type Pins = XIn;
+}
+
+//==============================================================================
+// CrystalMode
+//==============================================================================
+
+/// Type-level variant of the [`Xosc`] operating [`Mode`]
+///
+/// Represents the [`Xosc`] configured to use an external crystal oscillator.
+///
+/// See the documentation on [type-level programming] and specifically
+/// [type-level enums] for more details.
+///
+/// [type-level programming]: crate::typelevel
+/// [type-level enums]: crate::typelevel#type-level-enums
+pub enum CrystalMode {}
+
+impl Sealed for CrystalMode {}
+
+impl Mode for CrystalMode {
+    const DYN: DynMode = DynMode::CrystalMode;
+    type Pins = (XIn, XOut);
+}
+
+//==============================================================================
+// Xosc
+//==============================================================================
+
+/// The external multipurpose crystal oscillator controller
+///
+/// An `Xosc` interfaces with either an external clock or external crystal
+/// oscillator and delivers the resulting clock to the rest of the clock system.
+///
+/// The type parameter `M` represents the operating [`Mode`], either
+/// [`ClockMode`] or [`CrystalMode`].
+///
+/// On its own, an instance of `Xosc` does not represent an enabled XOSC.
+/// Instead, it must first be wrapped with [`Enabled`], which implements
+/// compile-time safety of the clock tree.
+///
+/// Because the terminal call to [`enable`] consumes the `Xosc` and returns an
+/// [`EnabledXosc`], the remaining API uses the builder pattern, where each
+/// method takes and returns `self` by value, allowing them to be easily
+/// chained.
+///
+/// See the [module-level documentation](self) for an example of creating,
+/// configuring and using an `Xosc`.
+///
+/// [`enable`]: Xosc::enable
+pub struct Xosc<M: Mode> {
+    token: XoscToken,
+    pins: M::Pins,
+    freq: Hertz,
+    settings: Settings,
+}
+
+/// An [`Enabled`] [`Xosc`]
+///
+/// As described in the [`clock` module documentation](super), the [`Enabled`]
+/// wrapper implements compile-time clock tree safety by tracking the number of
+/// consumer clocks and restricting access to the underlying [`Xosc`] to prevent
+/// modification while in use.
+///
+/// As with [`Enabled`], the default value for `N` is `U0`; if left unspecified,
+/// the counter is assumed to be zero.
+pub type EnabledXosc<M, N = U0> = Enabled<Xosc<M>, N>;
+
+impl Xosc<ClockMode> {
+    /// Create an [`Xosc`] from an external clock, taking ownership of the
+    /// [`XIn`] [`Pin`]
+    ///
+    /// Creating a [`Xosc`] does not modify any of the hardware registers. It
+    /// only creates a struct to track the configuration. The configuration data
+    /// is stored until the user calls [`enable`]. At that point, all of the
+    /// registers are written according to the initialization procedures
+    /// specified in the datasheet, and an [`EnabledXosc`] is returned. The
+    /// `Xosc` is not active or useful until that point.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the frequency is above 32 MHz.
+    ///
+    /// [`enable`]: Xosc::enable
+    #[inline]
+    pub fn from_clock(token: XoscToken, xin: impl Into<XIn>, freq: impl Into<Hertz>) -> Self {
+        let pins = xin.into();
+        Xosc::new(token, pins, freq.into())
+    }
+
+    /// Consume the [`Xosc`] and release the [`XoscToken`] and [`XIn`] [`Pin`]
+    #[inline]
+    pub fn free(self) -> (XoscToken, XIn) {
+        (self.token, self.pins)
+    }
+}
+
+impl Xosc<CrystalMode> {
+    /// Create an [`Xosc`] from an external crystal oscillator, taking ownership
+    /// of the [`XIn`] and [`XOut`] [`Pin`]s.
+    ///
+    /// Creating a [`Xosc`] does not modify any of the hardware registers. It
+    /// only creates a struct to track the configuration. The configuration data
+    /// is stored until the user calls [`enable`]. At that point, all of the
+    /// registers are written according to the initialization procedures
+    /// specified in the datasheet, and an [`EnabledXosc`] is returned. The
+    /// `Xosc` is not active or useful until that point.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the crystal frequency is outside the 0.4-32 MHz range.
+    ///
+    /// [`enable`]: Xosc::enable
+    #[inline]
+    pub fn from_crystal(
+        token: XoscToken,
+        xin: impl Into<XIn>,
+        xout: impl Into<XOut>,
+        freq: impl Into<Hertz>,
+    ) -> Self {
+        let pins = (xin.into(), xout.into());
+        Xosc::new(token, pins, freq.into())
+    }
+
+    /// Consume the [`Xosc`] and release the [`XoscToken`], [`XIn`] and [`XOut`]
+    /// [`Pin`]s
+    #[inline]
+    pub fn free(self) -> (XoscToken, XIn, XOut) {
+        let (xin, xout) = self.pins;
+        (self.token, xin, xout)
+    }
+
+    /// Toggle automatic amplitude gain control
+    ///
+    /// If enabled, the hardware will automatically adjust the oscillator
+    /// amplitude. In most cases, this will lower power consumption. Because it
+    /// can only be enabled once the oscillator is stable, [`Xosc::enable`]
+    /// will block until the crystal is ready when this option is set.
+    #[inline]
+    pub fn amplitude_gain_control(mut self, enable: bool) -> Self {
+        self.settings.amplitude_gain_control = enable;
+        self
+    }
+}
+
+impl<M: Mode> Xosc<M> {
+    #[inline]
+    fn new(token: XoscToken, pins: M::Pins, freq: Hertz) -> Self {
+        let gain = match (M::DYN, freq.to_Hz()) {
+            (DynMode::ClockMode, 0..=2_000_000) => Gainselect::_0,
+            (DynMode::CrystalMode, 400_000..=2_000_000) => Gainselect::_0,
+            (_, 2_000_001..=4_000_000) => Gainselect::_1,
+            (_, 4_000_001..=8_000_000) => Gainselect::_2,
+            (_, 8_000_001..=16_000_000) => Gainselect::_3,
+            (_, 16_000_001..=32_000_000) => Gainselect::_4,
+            (DynMode::ClockMode, _) => panic!("The XOSC input frequency must be 0-32 MHz"),
+            (DynMode::CrystalMode, _) => panic!("The XOSC crystal frequency must be 0.4-32 MHz"),
+        };
+        let settings = Settings {
+            start_up: StartUpDelay::Delay31us,
+            gain,
+            amplitude_gain_control: false,
+            on_demand: true,
+            run_standby: false,
+            mode: M::DYN,
+        };
+        Self {
+            token,
+            pins,
+            freq,
+            settings,
+        }
+    }
+
+    /// Return the clock or crystal frequency
+    #[inline]
+    pub fn freq(&self) -> Hertz {
+        self.freq
+    }
+
+    /// Set the start up delay before the [`Xosc`] is unmasked
+    ///
+    /// During the start up period, the [`Xosc`] is masked to prevent clock
+    /// instability from propagating to the digital logic.
+    #[inline]
+    pub fn start_up_delay(mut self, delay: StartUpDelay) -> Self {
+        self.settings.start_up = delay;
+        self
+    }
+
+    /// Control the [`Xosc`] on-demand behavior
+    ///
+    /// When the on-demand is enabled, the [`Xosc`] will only run in Idle or
+    /// Standby sleep modes if it is requested by a peripheral. Otherwise, its
+    /// behavior is dependent on the run-standby setting.
+    #[inline]
+    pub fn on_demand(mut self, on_demand: bool) -> Self {
+        self.settings.on_demand = on_demand;
+        self
+    }
+
+    /// Control the [`Xosc`] behavior in Standby sleep mode
+    ///
+    /// When `RUNSTDBY` is disabled, the [`Xosc`] will never run in Standby
+    /// sleep mode unless `ONDEMAND` is enabled and the `Xosc` is requested by a
+    /// peripheral. When `RUNSTDBY` is enabled, the `Xosc` will run in Standby
+    /// sleep mode, but it can still be disabled if `ONDEMAND` is enabled and
+    /// the `Xosc` is not requested.
+    #[inline]
+    pub fn run_standby(mut self, run_standby: bool) -> Self {
+        self.settings.run_standby = run_standby;
+        self
+    }
+
+    /// Enable the [`Xosc`], so that it can be used as a clock [`Source`]
+    ///
+    /// As mentioned when creating a new `Xosc`, no hardware registers are
+    /// actually modified until this call. Rather, the desired configuration is
+    /// stored internally, and the `Xosc` is initialized and configured here
+    /// according to the datasheet.
+    ///
+    /// The returned value is an [`EnabledXosc`] that can be used as a clock
+    /// [`Source`] for other clocks.
+    #[inline]
+    pub fn enable(mut self) -> EnabledXosc<M> {
+        self.token.configure(self.settings);
+        self.token.enable();
+        if self.settings.amplitude_gain_control {
+            while !self.token.is_ready() {}
+            self.token.enable_amplitude_gain_control();
+        }
+        Enabled::new(self)
+    }
+}
+
+impl<M: Mode> EnabledXosc<M> {
+    /// Disable the [`Xosc`]
+    ///
+    /// This method is only implemented for `N = U0`, which means the clock can
+    /// only be disabled when no other clocks consume this [`Xosc`].
+    #[inline]
+    pub fn disable(mut self) -> Xosc<M> {
+        self.0.token.disable();
+        self.0
+    }
+}
+
+impl<M: Mode, N> EnabledXosc<M, N> {
+    /// Check whether the [`Xosc`] is stable and ready to be used as a clock
+    /// [`Source`]
+    #[inline]
+    pub fn is_ready(&self) -> bool {
+        self.0.token.is_ready()
+    }
+}
+
+//==============================================================================
+// Source
+//==============================================================================
+
+impl<M: Mode, N> Source for EnabledXosc<M, N> {
+    type Id = XoscId;
+
+    #[inline]
+    fn freq(&self) -> Hertz {
+        self.0.freq()
+    }
+}
@@ -0,0 +1,540 @@
+//! # External, 32 kHz crystal oscillator controller
+//!
+//! ## Overview
+//!
+//! The `xosc32k` module provides access to the 32 kHz external crystal
+//! oscillator controller (XOSC32K) within the `SYSCTRL` peripheral.
+//!
+//! The peripheral can operate in two [`Mode`]s. It can accept an external
+//! clock, or it can interface with an crystal oscillator. In both cases, the
+//! clock must be 32,768 Hz.
+//!
+//! When used with an external clock, only one GPIO [`Pin`] is required, but
+//! when used with a crystal oscillator, two GPIO `Pin`s are required. The
+//! [`XIn32`] `Pin` is used in both `Mode`s, while the [`XOut32`] `Pin` is only
+//! used in [`CrystalMode`].
+//!
+//! On the SAMD11 and SAMD21, only the 32 kHz output of the XOSC32K can be used
+//! as a source for the [`Gclk`]s, so [`Xosc32k`] enables the oscillator and its
+//! 32 kHz output together. Like the [`OscUlp32k`], the `XOSC32K` register also
+//! has a write lock bit that will freeze its configuration until the next
+//! power-on reset.
+//!
+//! ## Example
+//!
+//! A typical use of the XOSC32K is as the reference for the closed-loop
+//! [`Dfll`] or the [`Dpll`].
+//!
+//! ```no_run
+//! use atsamd_hal::{
+//!     clock::v2::{
+//!         clock_system_at_reset,
+//!         xosc32k::{StartUpDelay, Xosc32k},
+//!     },
+//!     gpio::Pins,
+//!     pac::Peripherals,
+//! };
+//! let mut pac = Peripherals::take().unwrap();
+//! let pins = Pins::new(pac.port);
+//! let (buses, clocks, tokens) = clock_system_at_reset(
+//!     pac.sysctrl,
+//!     pac.gclk,
+//!     pac.pm,
+//!     &mut pac.nvmctrl,
+//! );
+//! let xosc32k = Xosc32k::from_crystal(tokens.xosc32k, pins.pa00, pins.pa01)
+//!     .automatic_amplitude_control(true)
+//!     .start_up_delay(StartUpDelay::Delay1s)
+//!     .on_demand(false)
+//!     .run_standby(true)
+//!     .enable();
+//! while !xosc32k.is_ready() {}
+//! ```
+//!
+//! [`Gclk`]: super::gclk::Gclk
+//! [`OscUlp32k`]: super::osculp32k::OscUlp32k
+//! [`Dfll`]: super::dfll::Dfll
+//! [`Dpll`]: super::dpll::Dpll
+
+use atsamd_hal_macros::hal_cfg;
+
+use fugit::RateExtU32;
+use typenum::U0;
+
+use crate::pac::sysctrl::{self, Xosc32k as Xosc32kReg};
+
+use crate::gpio::{FloatingDisabled, Pin};
+use crate::time::Hertz;
+use crate::typelevel::Sealed;
+
+use super::{Enabled, Source};
+
+#[hal_cfg("clock-d21")]
+use crate::gpio::{PA00, PA01};
+#[hal_cfg("clock-d11")]
+use crate::gpio::{PA08, PA09};
+
+//==============================================================================
+// Xosc32kToken
+//==============================================================================
+
+/// Singleton token that can be exchanged for an [`Xosc32k`]
+///
+/// As explained in the [`clock` module documentation](super), instances of
+/// various `Token` types can be exchanged for actual clock types. They
+/// typically represent clocks that are disabled at power-on reset.
+///
+/// The [`Xosc32k`] is disabled at power-on reset. To use it, you must first
+/// exchange the token for an actual clock with [`Xosc32k::from_clock`] or
+/// [`Xosc32k::from_crystal`].
+pub struct Xosc32kToken(());
+
+impl Xosc32kToken {
+    /// Create a new instance of [`Xosc32kToken`]
+    ///
+    /// # Safety
+    ///
+    /// The `Xosc32kToken` is a singleton. There must never be two simulatenous
+    /// instances of it. See the notes on `Token` types and memory safety in the
+    /// root of the `clock` module for more details.
+    #[inline]
+    pub(super) unsafe fn new() -> Self {
+        Self(())
+    }
+
+    /// Return a reference to the XOSC32K register
+    #[inline]
+    fn xosc32k(&self) -> &Xosc32kReg {
+        // Safety: The `Xosc32kToken` has exclusive access to the `XOSC32K`
+        // register, and we use a shared reference to the register block. See
+        // the notes on `Token` types and memory safety in the root of the
+        // `clock` module for more details.
+        unsafe { (*crate::pac::Sysctrl::PTR).xosc32k() }
+    }
+
+    /// Read the PCLKSR register
+    #[inline]
+    fn pclksr(&self) -> sysctrl::pclksr::R {
+        // Safety: We are only reading from the `PCLKSR` register, so there is
+        // no risk of memory corruption.
+        unsafe { (*crate::pac::Sysctrl::PTR).pclksr().read() }
+    }
+
+    /// Check whether the XOSC32K is stable and ready
+    #[inline]
+    fn is_ready(&self) -> bool {
+        self.pclksr().xosc32krdy().bit_is_set()
+    }
+
+    /// Write the complete XOSC32K register, leaving the oscillator disabled
+    #[inline]
+    fn configure(&mut self, settings: Settings) {
+        let xtalen = settings.mode == DynMode::CrystalMode;
+        // Safety: The `STARTUP` value comes from the `StartUpDelay`, so it is
+        // guaranteed to be valid.
+        self.xosc32k().write(|w| unsafe {
+            w.startup().bits(settings.start_up as u8);
+            w.aampen().bit(settings.automatic_amplitude_control);
+            w.ondemand().bit(settings.on_demand);
+            w.runstdby().bit(settings.run_standby);
+            w.en32k().set_bit();
+            w.xtalen().bit(xtalen)
+        });
+    }
+
+    /// Enable the XOSC32K
+    #[inline]
+    fn enable(&mut self) {
+        self.xosc32k().modify(|_, w| w.enable().set_bit());
+    }
+
+    /// Disable the XOSC32K
+    #[inline]
+    fn disable(&mut self) {
+        self.xosc32k().modify(|_, w| w.enable().clear_bit());
+    }
+
+    /// Freeze the XOSC32K configuration until power-on reset
+    #[inline]
+    fn write_lock(&mut self) {
+        self.xosc32k().modify(|_, w| w.wrtlock().set_bit());
+    }
+}
+
+//==============================================================================
+// Settings
+//==============================================================================
+
+// Collection of XOSC32K register fields
+//
+// All of these fields are set in a single write to XOSC32K during the call to
+// [`Xosc32k::enable`].
+#[derive(Clone, Copy)]
+struct Settings {
+    start_up: StartUpDelay,
+    automatic_amplitude_control: bool,
+    on_demand: bool,
+    run_standby: bool,
+    mode: DynMode,
+}
+
+//==============================================================================
+// XIn32 & XOut32
+//==============================================================================
+
+/// Type alias for the XOSC32K input [`Pin`]
+#[hal_cfg("clock-d11")]
+pub type XIn32 = Pin<PA08, FloatingDisabled>;
+
+/// Type alias for the XOSC32K output [`Pin`]
+#[hal_cfg("clock-d11")]
+pub type XOut32 = Pin<PA09, FloatingDisabled>;
+
+/// Type alias for the XOSC32K input [`Pin`]
+#[hal_cfg("clock-d21")]
+pub type XIn32 = Pin<PA00, FloatingDisabled>;
+
+/// Type alias for the XOSC32K output [`Pin`]
+#[hal_cfg("clock-d21")]
+pub type XOut32 = Pin<PA01, FloatingDisabled>;
+
+//==============================================================================
+// StartUpDelay
+//==============================================================================
+
+/// Start up delay before the [`Xosc32k`] output is unmasked
+///
+/// After a hard reset or waking from sleep, the [`Xosc32k`] output will
+/// remained masked for the start up period, to ensure an unstable clock is not
+/// propagated into the digital logic.
+///
+/// The start up delay is counted using the [`OscUlp32k`] clock. The crystal
+/// datasheet should be consulted to choose an appropriate value.
+///
+/// [`OscUlp32k`]: super::osculp32k::OscUlp32k
+#[repr(u8)]
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum StartUpDelay {
+    #[default]
+    Delay122us,
+    Delay1ms,
+    Delay63ms,
+    Delay125ms,
+    Delay500ms,
+    Delay1s,
+    Delay2s,
+    Delay4s,
+}
+
+//==============================================================================
+// DynMode
+//==============================================================================
+
+/// Value-level enum identifying one of two possible [`Xosc32k`] operating
+/// modes
+///
+/// The XOSC32K clock can be sourced from either an external clock or a crystal
+/// oscillator. The variants of this enum identify one of these two possible
+/// operating modes.
+///
+/// `DynMode` is the value-level equivalent of [`Mode`].
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum DynMode {
+    #[default]
+    ClockMode,
+    CrystalMode,
+}
+
+//==============================================================================
+// Mode
+//==============================================================================
+
+/// Type-level `enum` for the [`Xosc32k`] operating mode
+///
+/// The XOSC32K clock can be sourced from either an external clock or a crystal
+/// oscillator. This type-level `enum` provides two type-level variants,
+/// [`ClockMode`] and [`CrystalMode`], representing these operating modes.
+///
+/// `Mode` is the type-level equivalent of [`DynMode`]. See the documentation on
+/// [type-level programming] and specifically [type-level enums] for more
+/// details.
+///
+/// [type-level programming]: crate::typelevel
+/// [type-level enums]: crate::typelevel#type-level-enums
+pub trait Mode: Sealed {
+    /// Corresponding variant of [`DynMode`]
+    const DYN: DynMode;
+    #[doc(hidden)]
+    type Pins;
+}
+
+//==============================================================================
+// ClockMode
+//==============================================================================
+
+/// Type-level variant of the [`Xosc32k`] operating [`Mode`]
+///
+/// Represents the [`Xosc32k`] configured to use an externally provided clock.
+///
+/// See the documentation on [type-level programming] and specifically
+/// [type-level enums] for more details.
+///
+/// [type-level programming]: crate::typelevel
+/// [type-level enums]: crate::typelevel#type-level-enums
+pub enum ClockMode {}
+impl Sealed for ClockMode {}
+impl Mode for ClockMode {
+    const DYN: DynMode = DynMode::ClockMode;
+    type Pins = XIn32;
+}
+
+//==============================================================================
+// CrystalMode
+//==============================================================================
+
+/// Type-level variant of the [`Xosc32k`] operating [`Mode`]
+///
+/// Represents the [`Xosc32k`] configured to use an external, 32 kHz crystal
+/// oscillator.
+///
+/// See the documentation on [type-level programming] and specifically
+/// [type-level enums] for more details.
+///
+/// [type-level programming]: crate::typelevel
+/// [type-level enums]: crate::typelevel#type-level-enums
+pub enum CrystalMode {}
+impl Sealed for CrystalMode {}
+impl Mode for CrystalMode {
+    const DYN: DynMode = DynMode::CrystalMode;
+    type Pins = (XIn32, XOut32);
+}
+
+//==============================================================================
+// Xosc32kId
+//==============================================================================
+
+/// Type representing the identity of the [`Xosc32k`] clock
+///
+/// See the documentation on [`Id` types](super#id-types) for more details.
+pub enum Xosc32kId {}
+
+impl Sealed for Xosc32kId {}
+
+//==============================================================================
+// Xosc32k
+//==============================================================================
+
+/// The external, 32 kHz crystal oscillator controller
+///
+/// The type parameter `M` represents the operating [`Mode`], either
+/// [`ClockMode`] or [`CrystalMode`].
+///
+/// On its own, an instance of `Xosc32k` does not represent an enabled
+/// XOSC32K. Instead, it must first be wrapped with [`Enabled`], which
+/// implements compile-time safety of the clock tree.
+///
+/// Because the terminal call to [`enable`] consumes the `Xosc32k` and returns
+/// an [`EnabledXosc32k`], the remaining API uses the builder pattern, where
+/// each method takes and returns `self` by value, allowing them to be easily
+/// chained.
+///
+/// See the [module-level documentation](self) for an example of creating,
+/// configuring and using an `Xosc32k`.
+///
+/// [`enable`]: Xosc32k::enable
+pub struct Xosc32k<M: Mode> {
+    token: Xosc32kToken,
+    pins: M::Pins,
+    settings: Settings,
+}
+
+/// An [`Enabled`] [`Xosc32k`]
+///
+/// As described in the [`clock` module documentation](super), the [`Enabled`]
+/// wrapper implements compile-time clock tree safety by tracking the number of
+/// consumer clocks and restricting access to the underlying [`Xosc32k`] to
+/// prevent modification while in use.
+///
+/// As with [`Enabled`], the default value for `N` is `U0`; if left unspecified,
+/// the counter is assumed to be zero.
+pub type EnabledXosc32k<M, N = U0> = Enabled<Xosc32k<M>, N>;
+
+impl Xosc32k<ClockMode> {
+    /// Create the [`Xosc32k`] from an external clock, taking ownership of the
+    /// [`XIn32`] [`Pin`]
+    ///
+    /// Creating an [`Xosc32k`] does not modify any of the hardware registers.
+    /// It only creates a struct to track the configuration. The configuration
+    /// data is stored until the user calls [`enable`]. At that point, all of
+    /// the registers are written according to the initialization procedures
+    /// specified in the datasheet, and an [`EnabledXosc32k`] is returned. The
+    /// `Xosc32k` is not active or useful until that point.
+    ///
+    /// [`enable`]: Xosc32k::enable
+    #[inline]
+    pub fn from_clock(token: Xosc32kToken, xin32: impl Into<XIn32>) -> Self {
+        let pins = xin32.into();
+        Self::new(token, pins)
+    }
+
+    /// Consume the [`Xosc32k`] and release the [`Xosc32kToken`] and
+    /// [`XIn32`] [`Pin`]
+    #[inline]
+    pub fn free(self) -> (Xosc32kToken, XIn32) {
+        (self.token, self.pins)
+    }
+}
+
+impl Xosc32k<CrystalMode> {
+    /// Create the [`Xosc32k`] from an external crystal oscillator, taking
+    /// ownership of the [`XIn32`] and [`XOut32`] [`Pin`]s.
+    ///
+    /// Creating an [`Xosc32k`] does not modify any of the hardware registers.
+    /// It only creates a struct to track the configuration. The configuration
+    /// data is stored until the user calls [`enable`]. At that point, all of
+    /// the registers are written according to the initialization procedures
+    /// specified in the datasheet, and an [`EnabledXosc32k`] is returned. The
+    /// `Xosc32k` is not active or useful until that point.
+    ///
+    /// [`enable`]: Xosc32k::enable
+    #[inline]
+    pub fn from_crystal(
+        token: Xosc32kToken,
+        xin32: impl Into<XIn32>,
+        xout32: impl Into<XOut32>,
+    ) -> Self {
+        let pins = (xin32.into(), xout32.into());
+        Self::new(token, pins)
+    }
+
+    /// Consume the [`Xosc32k`] and release the [`Xosc32kToken`], [`XIn32`]
+    /// and [`XOut32`] [`Pin`]s
+    #[inline]
+    pub fn free(self) -> (Xosc32kToken, XIn32, XOut32) {
+        let (xin32, xout32) = self.pins;
+        (self.token, xin32, xout32)
+    }
+
+    /// Toggle automatic amplitude control
+    ///
+    /// If enabled, the hardware will automatically adjust the crystal
+    /// amplitude, which reduces power consumption.
+    #[inline]
+    pub fn automatic_amplitude_control(mut self, enable: bool) -> Self {
+        self.settings.automatic_amplitude_control = enable;
+        self
+    }
+}
+
+impl<M: Mode> Xosc32k<M> {
+    #[inline]
+    fn new(token: Xosc32kToken, pins: M::Pins) -> Self {
+        let settings = Settings {
+            start_up: StartUpDelay::Delay122us,
+            automatic_amplitude_control: false,
+            on_demand: true,
+            run_standby: false,
+            mode: M::DYN,
+        };
+        Self {
+            token,
+            pins,
+            settings,
+        }
+    }
+
+    /// Set the start up delay before the [`Xosc32k`] clock is unmasked
+    ///
+    /// During the start up period, the [`Xosc32k`] is masked to prevent clock
+    /// instability from propagating to the digital logic.
+    #[inline]
+    pub fn start_up_delay(mut self, delay: StartUpDelay) -> Self {
+        self.settings.start_up = delay;
+        self
+    }
+
+    /// Control the [`Xosc32k`] on-demand behavior
+    ///
+    /// When the on-demand is enabled, the [`Xosc32k`] clock will only run in
+    /// Idle or Standby sleep modes if it is requested by a peripheral.
+    /// Otherwise, its behavior is dependent on the run-standby setting.
+    #[inline]
+    pub fn on_demand(mut self, on_demand: bool) -> Self {
+        self.settings.on_demand = on_demand;
+        self
+    }
+
+    /// Control the [`Xosc32k`] behavior in Standby sleep mode
+    ///
+    /// When `RUNSTDBY` is disabled, the [`Xosc32k`] clock will never run in
+    /// Standby sleep mode unless `ONDEMAND` is enabled and the clock is
+    /// requested by a peripheral. When `RUNSTDBY` is enabled, the clock will
+    /// run in Standby sleep mode, but it can still be disabled if `ONDEMAND`
+    /// is enabled and the clock is not requested.
+    #[inline]
+    pub fn run_standby(mut self, run_standby: bool) -> Self {
+        self.settings.run_standby = run_standby;
+        self
+    }
+
+    /// Enable the [`Xosc32k`], so that it can be used as a clock [`Source`]
+    ///
+    /// As mentioned when creating a new `Xosc32k`, no hardware registers are
+    /// actually modified until this call. Rather, the desired configuration is
+    /// stored internally, and the `Xosc32k` is initialized and configured here
+    /// according to the datasheet.
+    ///
+    /// The returned value is an [`EnabledXosc32k`] that can be used as a clock
+    /// [`Source`] for other clocks.
+    #[inline]
+    pub fn enable(mut self) -> EnabledXosc32k<M> {
+        self.token.configure(self.settings);
+        self.token.enable();
+        Enabled::new(self)
+    }
+}
+
+impl<M: Mode> EnabledXosc32k<M> {
+    /// Disable the [`Xosc32k`]
+    ///
+    /// This method is only implemented for `N = U0`, which means the clock can
+    /// only be disabled when no other clocks consume this [`Xosc32k`].
+    #[inline]
+    pub fn disable(mut self) -> Xosc32k<M> {
+        self.0.token.disable();
+        self.0
+    }
+}
+
+impl<M: Mode, N> EnabledXosc32k<M, N> {
+    /// Check whether the XOSC32K is stable and ready to be used as a clock
+    /// [`Source`]
+    #[inline]
+    pub fn is_ready(&self) -> bool {
+        self.0.token.is_ready()
+    }
+
+    /// Freeze the XOSC32K configuration until power-on reset
+    ///
+    /// This function sets the write-lock bit, which freezes the XOSC32K
+    /// configuration at the hardware level until power-on reset. At the API
+    /// level, it also consumes and drops the [`Xosc32k`], which prevents any
+    /// further modifications.
+    #[inline]
+    pub fn write_lock(mut self) {
+        self.0.token.write_lock();
+    }
+}
+
+//==============================================================================
+// Source
+//==============================================================================
+
+impl<M: Mode, N> Source for EnabledXosc32k<M, N> {
+    type Id = Xosc32kId;
+
+    #[inline]
+    fn freq(&self) -> Hertz {
+        32_768.Hz()
+    }
+}
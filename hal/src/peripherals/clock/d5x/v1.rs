@@ -11,7 +11,8 @@ use atsamd_hal_macros::hal_macro_helper;
 
 use fugit::RateExtU32;
 
-use crate::clock::v2::pclk::{ids::*, Pclk, PclkSourceId};
+use crate::clock::v2::dynamic::DynPclk;
+use crate::clock::v2::pclk::{ids::*, Pclk, PclkId, PclkSourceId};
 use crate::pac::gclk::genctrl::Srcselect::*;
 use crate::pac::gclk::pchctrl::Genselect::*;
 use crate::pac::{self, Gclk, Mclk, Nvmctrl, Osc32kctrl, Oscctrl};
@@ -400,6 +401,22 @@ impl<I: PclkSourceId> core::convert::From<Pclk<$PclkId, I>> for $Type {
     }
 }
 
+/// Conversion from the run-time [`DynPclk`] of the
+/// [`DynClockTree`](crate::clock::v2::dynamic::DynClockTree). The conversion
+/// fails and returns the [`DynPclk`] if it belongs to a different peripheral.
+$(#[$attr])*
+impl core::convert::TryFrom<DynPclk> for $Type {
+    type Error = DynPclk;
+
+    fn try_from(pclk: DynPclk) -> Result<Self, DynPclk> {
+        if pclk.id() == <$PclkId as PclkId>::DYN {
+            Ok($Type { freq: pclk.freq() })
+        } else {
+            Err(pclk)
+        }
+    }
+}
+
 
 )+
 
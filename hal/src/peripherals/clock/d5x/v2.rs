@@ -14,8 +14,9 @@
 //! compile-time. This is exceedingly reasonable for most clocking
 //! configurations, because most users set up their clocks once and never change
 //! them again. However, if you need to dynamically change the clocking
-//! configuration at run-time, you may find this API less ergonomic. A future,
-//! fully dynamic API has been discussed, but nothing has been developed so far.
+//! configuration at run-time, you may find this API less ergonomic. In that
+//! case, the [`dynamic`] module provides a [`DynClockTree`] that tracks the
+//! clock tree state at run-time instead.
 //! </p>
 //!
 //! The sections that follow provide an explanation of key concepts in the
@@ -803,6 +804,8 @@
 //! [`DpllToken`]: dpll::DpllToken
 //! [`EnabledDpll0`]: dpll::EnabledDpll0
 //!
+//! [`DynClockTree`]: dynamic::DynClockTree
+//!
 //! [`Gclk0`]: gclk::Gclk0
 //! [`GclkId`]: gclk::GclkId
 //! [`EnabledGclk0`]: gclk::EnabledGclk0
@@ -864,6 +867,7 @@ pub mod ahb;
 pub mod apb;
 pub mod dfll;
 pub mod dpll;
+pub mod dynamic;
 pub mod gclk;
 pub mod osculp32k;
 pub mod pclk;
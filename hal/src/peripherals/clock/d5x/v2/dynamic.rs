@@ -0,0 +1,1116 @@
+//! # Run-time clock tree
+//!
+//! ## Overview
+//!
+//! The rest of the `clock::v2` module tracks the state of the clock tree at
+//! compile-time. That is ideal for applications that configure their clocks
+//! once at start up, but it becomes unwieldy when the clocking configuration
+//! must change while the application is running. For example, a
+//! power-sensitive application might want to run the CPU at 120 MHz while it
+//! is busy and drop down to 4 MHz while it is idle.
+//!
+//! The [`DynClockTree`] fills this gap. It takes ownership of the typed
+//! clocks and tokens that drive the generic clock controller and DPLL0, and
+//! it tracks their state at run-time instead. Every transition is validated
+//! before any register is modified. Rather than relying on type-level
+//! counters, the tree counts the consumers of each clock and checks the
+//! frequency limits of every affected [`Gclk`] and [`Pclk`]. Invalid
+//! transitions return an [`Error`] and leave the hardware untouched.
+//!
+//! The tree supports three clock sources, the [`Dfll`] in open-loop mode,
+//! [`Dpll0`] and the always-enabled OSCULP32K. Generic clock generators may use
+//! any of these as their source, with the exception of [`Gclk0`], which drives
+//! the CPU and can only use the DFLL or DPLL0. DPLL0 is always referenced to
+//! one of the other generators.
+//!
+//! ## Notifications
+//!
+//! Peripherals derive their timing from their [`Pclk`] frequency. When a
+//! transition changes the frequency of a [`Gclk`], peripherals driven by that
+//! generator must recompute their dividers, e.g. the SERCOM `BAUD` register or
+//! the TC prescaler. Peripherals that support this implement the [`Reclock`]
+//! trait.
+//!
+//! The [`DynClockTree`] can notify up to [`MAX_LISTENERS`] peripherals of
+//! frequency changes. Listeners are registered for a given [`DynPclkId`] with
+//! [`DynClockTree::register`], and they are notified through the
+//! [`ClockListener`] trait. Because the peripherals are typically shared with
+//! interrupt handlers, [`ClockListener`] is implemented for any [`Reclock`]
+//! type stored within a [`critical_section::Mutex`].
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use core::cell::RefCell;
+//!
+//! use atsamd_hal::{
+//!     clock::v2::{
+//!         clock_system_at_reset,
+//!         dynamic::{CpuClock, DynClockTree},
+//!         gclk::{DynGclkId, DynGclkSourceId},
+//!         pclk::DynPclkId,
+//!     },
+//!     clock::Tc2Tc3Clock,
+//!     pac::{Peripherals, Tc2},
+//!     timer::TimerCounter,
+//! };
+//! use critical_section::Mutex;
+//!
+//! static TIMER: Mutex<RefCell<Option<TimerCounter<Tc2>>>> = Mutex::new(RefCell::new(None));
+//!
+//! let mut pac = Peripherals::take().unwrap();
+//! let (buses, clocks, tokens) = clock_system_at_reset(
+//!     pac.oscctrl,
+//!     pac.osc32kctrl,
+//!     pac.gclk,
+//!     pac.mclk,
+//!     &mut pac.nvmctrl,
+//! );
+//! let mut tree = DynClockTree::new(
+//!     clocks.gclk0,
+//!     clocks.dfll,
+//!     tokens.dpll0,
+//!     tokens.gclks,
+//!     tokens.pclks,
+//! );
+//! // Run the CPU from GCLK0 and use it for TC2 as well
+//! let pclk = tree.enable_pclk(DynPclkId::Tc2Tc3, DynGclkId::Gclk0).unwrap();
+//! let clock = Tc2Tc3Clock::try_from(pclk).ok().unwrap();
+//! let (_, _, _, mut mclk) = unsafe { clocks.pac.steal() };
+//! let timer = TimerCounter::tc2_(&clock, pac.tc2, &mut mclk);
+//! critical_section::with(|cs| TIMER.borrow_ref_mut(cs).replace(timer));
+//! // Recompute the TC2 prescaler whenever GCLK0 changes
+//! tree.register(DynPclkId::Tc2Tc3, &TIMER).unwrap();
+//! // Use GCLK2 at 12 MHz from the DFLL for SERCOM0
+//! tree.set_gclk(DynGclkId::Gclk2, DynGclkSourceId::Dfll, 4).unwrap();
+//! let sercom0_freq = tree.enable_pclk(DynPclkId::Sercom0, DynGclkId::Gclk2).unwrap();
+//! // Speed up the CPU to 120 MHz, using GCLK5 as the DPLL0 reference
+//! tree.set_cpu_clock(CpuClock::Dpll120MHz {
+//!     reference: DynGclkId::Gclk5,
+//! })
+//! .unwrap();
+//! // Drop down to 4 MHz, which disables DPLL0 and GCLK5 again
+//! tree.set_cpu_clock(CpuClock::Dfll4MHz).unwrap();
+//! ```
+//!
+//! [`Gclk`]: super::gclk::Gclk
+//! [`Gclk0`]: super::gclk::Gclk0
+//! [`Pclk`]: super::pclk::Pclk
+//! [`Dfll`]: super::dfll::Dfll
+//! [`Dpll0`]: super::dpll::Dpll0
+
+use atsamd_hal_macros::hal_macro_helper;
+
+use core::cell::RefCell;
+
+use critical_section::Mutex;
+use fugit::RateExtU32;
+use heapless::Vec;
+use typenum::U1;
+
+use crate::pac;
+use crate::pac::gclk::genctrl::Divselselect;
+use crate::pac::oscctrl::dpll::dpllctrlb::Refclkselect;
+use crate::time::Hertz;
+
+use super::dfll::{DfllId, EnabledDfll};
+use super::dpll::{Dpll0Id, DpllToken};
+use super::gclk::{DynGclkId, DynGclkSourceId, EnabledGclk0, GclkTokens};
+use super::pclk::{DynPclkId, PclkTokens};
+
+//==============================================================================
+// Constants
+//==============================================================================
+
+/// Maximum number of [`ClockListener`]s registered with a [`DynClockTree`]
+pub const MAX_LISTENERS: usize = 8;
+
+const NUM_GCLKS: usize = 12;
+const NUM_PCLKS: usize = 48;
+
+const DFLL_FREQ: u32 = 48_000_000;
+const OSCULP32K_FREQ: u32 = 32_768;
+
+const GCLK0_MAX_FREQ: u32 = 120_000_000;
+const GCLK_MAX_FREQ: u32 = 200_000_000;
+const DPLL_MIN_INPUT_FREQ: u32 = 32_000;
+const DPLL_MIN_OUTPUT_FREQ: u32 = 96_000_000;
+const DPLL_MAX_OUTPUT_FREQ: u32 = 200_000_000;
+
+/// Return the maximum [`Pclk`](super::pclk::Pclk) frequency for the given
+/// peripheral, as specified in the datasheet
+#[hal_macro_helper]
+fn pclk_max_freq(id: DynPclkId) -> u32 {
+    use DynPclkId::*;
+    match id {
+        Dfll => 33_000,
+        Dpll0 | Dpll1 => 3_200_000,
+        Tc0Tc1 | Tc2Tc3 | Tcc0Tcc1 | Tcc2Tcc3 | Pdec => 200_000_000,
+        #[hal_cfg(all("tc4", "tc5"))]
+        Tc4Tc5 => 200_000_000,
+        #[hal_cfg(all("tc6", "tc7"))]
+        Tc6Tc7 => 200_000_000,
+        #[hal_cfg("tcc4")]
+        Tcc4 => 200_000_000,
+        Usb => 60_000_000,
+        _ => 100_000_000,
+    }
+}
+
+//==============================================================================
+// Error
+//==============================================================================
+
+/// Errors returned by [`DynClockTree`] transitions
+///
+/// The hardware is never modified when a transition returns an error.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The clock cannot be disabled or reconfigured, because it still has the
+    /// given number of consumers
+    InUse { consumers: u8 },
+    /// The requested source clock is not enabled
+    SourceDisabled,
+    /// The requested source is not managed by the [`DynClockTree`]
+    UnsupportedSource(DynGclkSourceId),
+    /// The [`Gclk`](super::gclk::Gclk) cannot be used as the DPLL0 reference
+    InvalidReference(DynGclkId),
+    /// The division or multiplication factor is out of range
+    InvalidFactor,
+    /// The [`Gclk`](super::gclk::Gclk) output frequency would be out of range
+    GclkFreq { id: DynGclkId, freq: Hertz },
+    /// The [`Pclk`](super::pclk::Pclk) frequency would be out of range
+    PclkFreq { id: DynPclkId, freq: Hertz },
+    /// The DPLL0 output frequency would be out of range
+    DpllFreq(Hertz),
+    /// The [`Pclk`](super::pclk::Pclk) is reserved for use by the tree itself
+    ReservedPclk(DynPclkId),
+    /// The [`Pclk`](super::pclk::Pclk) is already enabled
+    PclkEnabled(DynPclkId),
+    /// There is no room left to register another [`ClockListener`]
+    TooManyListeners,
+}
+
+//==============================================================================
+// Reclock
+//==============================================================================
+
+/// Peripherals that can adapt to a new [`Pclk`](super::pclk::Pclk) frequency
+///
+/// Implementers store the frequency of their peripheral channel clock and use
+/// it to compute internal dividers. A call to [`Reclock::reclock`] replaces the
+/// stored frequency and recomputes any dividers that are currently in use, so
+/// that the peripheral keeps its externally visible timing.
+pub trait Reclock {
+    /// Adapt the peripheral to a new clock frequency
+    fn reclock(&mut self, freq: Hertz);
+}
+
+impl<T: Reclock> Reclock for Option<T> {
+    #[inline]
+    fn reclock(&mut self, freq: Hertz) {
+        if let Some(t) = self {
+            t.reclock(freq);
+        }
+    }
+}
+
+//==============================================================================
+// ClockListener
+//==============================================================================
+
+/// Receiver of frequency change notifications from a [`DynClockTree`]
+///
+/// Listeners are registered with [`DynClockTree::register`] and are called
+/// after the frequency of their [`Pclk`](super::pclk::Pclk) has changed.
+pub trait ClockListener {
+    /// Notify the listener of a new [`Pclk`](super::pclk::Pclk) frequency
+    fn clock_changed(&self, freq: Hertz);
+}
+
+impl<T: Reclock> ClockListener for Mutex<RefCell<T>> {
+    #[inline]
+    fn clock_changed(&self, freq: Hertz) {
+        critical_section::with(|cs| self.borrow_ref_mut(cs).reclock(freq));
+    }
+}
+
+//==============================================================================
+// CpuClock
+//==============================================================================
+
+/// Preset configurations for the CPU clock, [`Gclk0`](super::gclk::Gclk0)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CpuClock {
+    /// Run the CPU at 120 MHz from DPLL0
+    ///
+    /// The `reference` generator is configured to output 1 MHz from the DFLL,
+    /// and it feeds DPLL0.
+    Dpll120MHz { reference: DynGclkId },
+    /// Run the CPU at 48 MHz directly from the DFLL
+    Dfll48MHz,
+    /// Run the CPU at 4 MHz from the DFLL, divided by 12
+    Dfll4MHz,
+}
+
+//==============================================================================
+// DynPclk
+//==============================================================================
+
+/// An enabled [`Pclk`](super::pclk::Pclk) managed by a [`DynClockTree`]
+///
+/// This is the run-time equivalent of a [`Pclk`](super::pclk::Pclk). It is
+/// returned by [`DynClockTree::enable_pclk`] and records the frequency of the
+/// peripheral channel at the time it was enabled. It can be converted into the
+/// corresponding `clock::v1` type or into [`Hertz`] when creating a
+/// peripheral.
+pub struct DynPclk {
+    id: DynPclkId,
+    freq: Hertz,
+}
+
+impl DynPclk {
+    /// Return the [`DynPclkId`] of this peripheral channel
+    #[inline]
+    pub fn id(&self) -> DynPclkId {
+        self.id
+    }
+
+    /// Return the frequency of this peripheral channel
+    #[inline]
+    pub fn freq(&self) -> Hertz {
+        self.freq
+    }
+}
+
+impl From<DynPclk> for Hertz {
+    #[inline]
+    fn from(pclk: DynPclk) -> Self {
+        pclk.freq
+    }
+}
+
+//==============================================================================
+// State
+//==============================================================================
+
+#[derive(Clone, Copy)]
+struct GclkState {
+    source: Option<DynGclkSourceId>,
+    div: u16,
+}
+
+impl GclkState {
+    const DISABLED: Self = Self {
+        source: None,
+        div: 1,
+    };
+}
+
+#[derive(Clone, Copy)]
+struct DpllState {
+    reference: DynGclkId,
+    mult: u16,
+    frac: u8,
+}
+
+/// Run-time state of the clock tree
+///
+/// This tracks the configuration of every clock managed by the
+/// [`DynClockTree`] and validates transitions against it. It never touches the
+/// hardware.
+#[derive(Clone, Copy)]
+struct TreeState {
+    gclks: [GclkState; NUM_GCLKS],
+    pclks: [Option<(DynPclkId, DynGclkId)>; NUM_PCLKS],
+    dfll: bool,
+    dpll0: Option<DpllState>,
+}
+
+impl TreeState {
+    /// State at power-on reset, with [`Gclk0`](super::gclk::Gclk0) driven by
+    /// the DFLL at the given frequency
+    fn at_reset(gclk0_freq: Hertz) -> Self {
+        let div = (DFLL_FREQ / gclk0_freq.to_Hz().max(1)).max(1) as u16;
+        let mut gclks = [GclkState::DISABLED; NUM_GCLKS];
+        gclks[0] = GclkState {
+            source: Some(DynGclkSourceId::Dfll),
+            div,
+        };
+        Self {
+            gclks,
+            pclks: [None; NUM_PCLKS],
+            dfll: true,
+            dpll0: None,
+        }
+    }
+
+    fn source_freq(&self, source: DynGclkSourceId) -> Option<Hertz> {
+        match source {
+            DynGclkSourceId::Dfll => self.dfll.then(|| DFLL_FREQ.Hz()),
+            DynGclkSourceId::Dpll0 => self.dpll0.and_then(|d| self.dpll_freq(d)),
+            DynGclkSourceId::OscUlp32k => Some(OSCULP32K_FREQ.Hz()),
+            _ => None,
+        }
+    }
+
+    fn gclk_freq(&self, id: DynGclkId) -> Option<Hertz> {
+        let state = self.gclks[id as usize];
+        self.source_freq(state.source?)
+            .map(|freq| freq / state.div as u32)
+    }
+
+    fn pclk_source(&self, id: DynPclkId) -> Option<DynGclkId> {
+        self.pclks[id as usize].map(|(_, gclk)| gclk)
+    }
+
+    fn dpll_freq(&self, dpll: DpllState) -> Option<Hertz> {
+        let input = self.gclk_freq(dpll.reference)?.to_Hz();
+        Some((input * dpll.mult as u32 + input * dpll.frac as u32 / 32).Hz())
+    }
+
+    fn source_consumers(&self, source: DynGclkSourceId) -> u8 {
+        self.gclks
+            .iter()
+            .filter(|g| g.source == Some(source))
+            .count() as u8
+    }
+
+    fn gclk_consumers(&self, id: DynGclkId) -> u8 {
+        let pclks = self
+            .pclks
+            .iter()
+            .flatten()
+            .filter(|(_, gclk)| *gclk == id)
+            .count() as u8;
+        match id {
+            DynGclkId::Gclk0 => pclks + 1,
+            _ => pclks,
+        }
+    }
+
+    /// Validate a new DPLL0 configuration and return its output frequency
+    fn check_dpll0(
+        &self,
+        reference: DynGclkId,
+        int: u16,
+        frac: u8,
+    ) -> Result<(DpllState, Hertz), Error> {
+        if int == 0 || int > 8192 || frac >= 32 {
+            return Err(Error::InvalidFactor);
+        }
+        if reference == DynGclkId::Gclk0
+            || self.gclks[reference as usize].source == Some(DynGclkSourceId::Dpll0)
+        {
+            return Err(Error::InvalidReference(reference));
+        }
+        let consumers = self.source_consumers(DynGclkSourceId::Dpll0);
+        if consumers > 0 {
+            return Err(Error::InUse { consumers });
+        }
+        let input = self.gclk_freq(reference).ok_or(Error::SourceDisabled)?;
+        if input.to_Hz() < DPLL_MIN_INPUT_FREQ || input.to_Hz() > pclk_max_freq(DynPclkId::Dpll0) {
+            return Err(Error::PclkFreq {
+                id: DynPclkId::Dpll0,
+                freq: input,
+            });
+        }
+        let state = DpllState {
+            reference,
+            mult: int,
+            frac,
+        };
+        let output = self.dpll_freq(state).ok_or(Error::SourceDisabled)?;
+        if output.to_Hz() < DPLL_MIN_OUTPUT_FREQ || output.to_Hz() > DPLL_MAX_OUTPUT_FREQ {
+            return Err(Error::DpllFreq(output));
+        }
+        Ok((state, output))
+    }
+
+    /// Validate a new generator configuration and return its output frequency
+    fn check_gclk(&self, id: DynGclkId, source: DynGclkSourceId, div: u16) -> Result<Hertz, Error> {
+        let max_div = match id {
+            DynGclkId::Gclk1 => u16::MAX,
+            _ => u8::MAX as u16,
+        };
+        if div == 0 || div > max_div {
+            return Err(Error::InvalidFactor);
+        }
+        match source {
+            DynGclkSourceId::Dfll | DynGclkSourceId::Dpll0 | DynGclkSourceId::OscUlp32k => (),
+            _ => return Err(Error::UnsupportedSource(source)),
+        }
+        if id == DynGclkId::Gclk0 && source == DynGclkSourceId::OscUlp32k {
+            return Err(Error::UnsupportedSource(source));
+        }
+        if self.pclk_source(DynPclkId::Dpll0) == Some(id) {
+            return Err(Error::InUse {
+                consumers: self.gclk_consumers(id),
+            });
+        }
+        let freq = self.source_freq(source).ok_or(Error::SourceDisabled)? / div as u32;
+        let max = match id {
+            DynGclkId::Gclk0 => GCLK0_MAX_FREQ,
+            _ => GCLK_MAX_FREQ,
+        };
+        if freq.to_Hz() > max {
+            return Err(Error::GclkFreq { id, freq });
+        }
+        for (pclk, gclk) in self.pclks.iter().flatten() {
+            if *gclk == id && freq.to_Hz() > pclk_max_freq(*pclk) {
+                return Err(Error::PclkFreq { id: *pclk, freq });
+            }
+        }
+        Ok(freq)
+    }
+
+    /// Validate a new peripheral channel and return its frequency
+    fn check_pclk(&self, id: DynPclkId, gclk: DynGclkId) -> Result<Hertz, Error> {
+        if matches!(id, DynPclkId::Dfll | DynPclkId::Dpll0 | DynPclkId::Dpll1) {
+            return Err(Error::ReservedPclk(id));
+        }
+        if self.pclks[id as usize].is_some() {
+            return Err(Error::PclkEnabled(id));
+        }
+        let freq = self.gclk_freq(gclk).ok_or(Error::SourceDisabled)?;
+        if freq.to_Hz() > pclk_max_freq(id) {
+            return Err(Error::PclkFreq { id, freq });
+        }
+        Ok(freq)
+    }
+}
+
+/// Typed clocks and tokens owned by the [`DynClockTree`]
+///
+/// These are kept only as proof of exclusive access to the corresponding
+/// registers. Their type-level state no longer reflects the hardware once the
+/// tree starts making transitions.
+#[allow(dead_code)]
+struct Owned {
+    gclk0: EnabledGclk0<DfllId, U1>,
+    dfll: EnabledDfll<super::dfll::OpenLoop, U1>,
+    dpll0: DpllToken<Dpll0Id>,
+    gclks: GclkTokens,
+    pclks: PclkTokens,
+}
+
+//==============================================================================
+// DynClockTree
+//==============================================================================
+
+/// Clock tree with run-time state tracking and validated transitions
+///
+/// See the [module-level documentation](self) for more details.
+pub struct DynClockTree<'a> {
+    _owned: Owned,
+    state: TreeState,
+    listeners: Vec<(DynPclkId, &'a dyn ClockListener), MAX_LISTENERS>,
+}
+
+impl<'a> DynClockTree<'a> {
+    /// Take ownership of the clocks and tokens in their power-on reset state
+    ///
+    /// At power-on reset, [`Gclk0`](super::gclk::Gclk0) is driven by the DFLL
+    /// in open-loop mode, and all other generators, DPLL0 and all peripheral
+    /// channels are disabled.
+    #[inline]
+    pub fn new(
+        gclk0: EnabledGclk0<DfllId, U1>,
+        dfll: EnabledDfll<super::dfll::OpenLoop, U1>,
+        dpll0: DpllToken<Dpll0Id>,
+        gclks: GclkTokens,
+        pclks: PclkTokens,
+    ) -> Self {
+        let state = TreeState::at_reset(gclk0.freq());
+        Self {
+            _owned: Owned {
+                gclk0,
+                dfll,
+                dpll0,
+                gclks,
+                pclks,
+            },
+            state,
+            listeners: Vec::new(),
+        }
+    }
+
+    //--------------------------------------------------------------------------
+    // Queries
+    //--------------------------------------------------------------------------
+
+    /// Return the frequency of a clock source, if it is enabled
+    #[inline]
+    pub fn source_freq(&self, source: DynGclkSourceId) -> Option<Hertz> {
+        self.state.source_freq(source)
+    }
+
+    /// Return the output frequency of a [`Gclk`](super::gclk::Gclk), if it is
+    /// enabled
+    #[inline]
+    pub fn gclk_freq(&self, id: DynGclkId) -> Option<Hertz> {
+        self.state.gclk_freq(id)
+    }
+
+    /// Return the frequency of a [`Pclk`](super::pclk::Pclk), if it is enabled
+    #[inline]
+    pub fn pclk_freq(&self, id: DynPclkId) -> Option<Hertz> {
+        self.gclk_freq(self.state.pclk_source(id)?)
+    }
+
+    /// Return the CPU frequency
+    #[inline]
+    pub fn cpu_freq(&self) -> Hertz {
+        // Gclk0 can never be disabled, so this never fails
+        self.gclk_freq(DynGclkId::Gclk0).unwrap_or(0.Hz())
+    }
+
+    /// Return the number of generators using the given clock source
+    #[inline]
+    pub fn source_consumers(&self, source: DynGclkSourceId) -> u8 {
+        self.state.source_consumers(source)
+    }
+
+    /// Return the number of consumers of a [`Gclk`](super::gclk::Gclk)
+    ///
+    /// For [`Gclk0`](super::gclk::Gclk0), the CPU counts as a consumer.
+    #[inline]
+    pub fn gclk_consumers(&self, id: DynGclkId) -> u8 {
+        self.state.gclk_consumers(id)
+    }
+
+    //--------------------------------------------------------------------------
+    // Sources
+    //--------------------------------------------------------------------------
+
+    /// Enable the DFLL in open-loop mode
+    #[inline]
+    pub fn enable_dfll(&mut self) -> Hertz {
+        if !self.state.dfll {
+            let oscctrl = self.oscctrl();
+            oscctrl.dfllctrla().modify(|_, w| w.enable().set_bit());
+            while oscctrl.dfllsync().read().enable().bit_is_set() {}
+            self.state.dfll = true;
+        }
+        DFLL_FREQ.Hz()
+    }
+
+    /// Disable the DFLL
+    ///
+    /// Returns [`Error::InUse`] if any generator still uses the DFLL.
+    #[inline]
+    pub fn disable_dfll(&mut self) -> Result<(), Error> {
+        let consumers = self.source_consumers(DynGclkSourceId::Dfll);
+        if consumers > 0 {
+            return Err(Error::InUse { consumers });
+        }
+        if self.state.dfll {
+            let oscctrl = self.oscctrl();
+            oscctrl.dfllctrla().modify(|_, w| w.enable().clear_bit());
+            while oscctrl.dfllsync().read().enable().bit_is_set() {}
+            self.state.dfll = false;
+        }
+        Ok(())
+    }
+
+    /// Enable DPLL0 with the given reference [`Gclk`](super::gclk::Gclk) and
+    /// loop division factor
+    ///
+    /// The output frequency is `ref * (int + frac / 32)`, where `int` must be
+    /// in the range `1..=8192` and `frac` must be less than 32. The reference
+    /// frequency must be between 32 kHz and 3.2 MHz, and the output frequency
+    /// must be between 96 MHz and 200 MHz.
+    ///
+    /// If DPLL0 is already enabled, it is reconfigured, provided that it has no
+    /// consumers. This function blocks until DPLL0 has locked.
+    pub fn enable_dpll0(
+        &mut self,
+        reference: DynGclkId,
+        int: u16,
+        frac: u8,
+    ) -> Result<Hertz, Error> {
+        let (state, output) = self.state.check_dpll0(reference, int, frac)?;
+
+        if self.state.dpll0.is_some() {
+            self.write_dpll0_enable(false);
+        }
+        self.write_pchctrl(DynPclkId::Dpll0, Some(reference));
+        self.state.pclks[DynPclkId::Dpll0 as usize] = Some((DynPclkId::Dpll0, reference));
+        let dpll = self.dpll0_regs();
+        // Only change the reference, keeping the filter and lock settings
+        dpll.dpllctrlb()
+            .modify(|_, w| w.refclk().variant(Refclkselect::Gclk));
+        // Safety: The values were checked against the field widths above
+        dpll.dpllratio().write(|w| unsafe {
+            w.ldr().bits(int - 1);
+            w.ldrfrac().bits(frac)
+        });
+        while dpll.dpllsyncbusy().read().dpllratio().bit_is_set() {}
+        dpll.dpllctrla().modify(|_, w| {
+            w.ondemand().clear_bit();
+            w.runstdby().clear_bit()
+        });
+        self.write_dpll0_enable(true);
+        let dpll = self.dpll0_regs();
+        loop {
+            let status = dpll.dpllstatus().read();
+            if status.lock().bit_is_set() && status.clkrdy().bit_is_set() {
+                break;
+            }
+        }
+        self.state.dpll0 = Some(state);
+        Ok(output)
+    }
+
+    /// Disable DPLL0 and its reference peripheral channel
+    ///
+    /// Returns [`Error::InUse`] if any generator still uses DPLL0.
+    #[inline]
+    pub fn disable_dpll0(&mut self) -> Result<(), Error> {
+        let consumers = self.source_consumers(DynGclkSourceId::Dpll0);
+        if consumers > 0 {
+            return Err(Error::InUse { consumers });
+        }
+        if self.state.dpll0.take().is_some() {
+            self.write_dpll0_enable(false);
+            self.write_pchctrl(DynPclkId::Dpll0, None);
+            self.state.pclks[DynPclkId::Dpll0 as usize] = None;
+        }
+        Ok(())
+    }
+
+    //--------------------------------------------------------------------------
+    // Generators
+    //--------------------------------------------------------------------------
+
+    /// Configure and enable a [`Gclk`](super::gclk::Gclk)
+    ///
+    /// The generator outputs the frequency of `source` divided by `div`.
+    /// [`Gclk1`](super::gclk::Gclk1) accepts division factors up to 65535,
+    /// while all other generators accept factors up to 255.
+    ///
+    /// The generator may already be enabled and in use. In that case, the new
+    /// frequency is validated against the limits of every [`Pclk`] it feeds,
+    /// and all registered [`ClockListener`]s for those [`Pclk`]s are notified
+    /// once the transition is complete. A generator used as the DPLL0
+    /// reference cannot be reconfigured while DPLL0 is enabled.
+    ///
+    /// [`Pclk`]: super::pclk::Pclk
+    pub fn set_gclk(
+        &mut self,
+        id: DynGclkId,
+        source: DynGclkSourceId,
+        div: u16,
+    ) -> Result<Hertz, Error> {
+        let freq = self.state.check_gclk(id, source, div)?;
+
+        let old = self.gclk_freq(id);
+        self.write_genctrl(id, source, div);
+        self.state.gclks[id as usize] = GclkState {
+            source: Some(source),
+            div,
+        };
+        if old != Some(freq) {
+            self.notify(id, freq);
+        }
+        Ok(freq)
+    }
+
+    /// Disable a [`Gclk`](super::gclk::Gclk)
+    ///
+    /// Returns [`Error::InUse`] if the generator still has consumers.
+    /// [`Gclk0`](super::gclk::Gclk0) always has at least one consumer, the
+    /// CPU, so it can never be disabled.
+    #[inline]
+    pub fn disable_gclk(&mut self, id: DynGclkId) -> Result<(), Error> {
+        let consumers = self.gclk_consumers(id);
+        if consumers > 0 {
+            return Err(Error::InUse { consumers });
+        }
+        if self.state.gclks[id as usize].source.is_some() {
+            let gclk = self.gclk();
+            gclk.genctrl(id as usize)
+                .modify(|_, w| w.genen().clear_bit());
+            while gclk.syncbusy().read().genctrl().bits() & (1 << id as u16) != 0 {}
+            self.state.gclks[id as usize] = GclkState::DISABLED;
+        }
+        Ok(())
+    }
+
+    //--------------------------------------------------------------------------
+    // Peripheral channels
+    //--------------------------------------------------------------------------
+
+    /// Enable a [`Pclk`](super::pclk::Pclk) driven by the given
+    /// [`Gclk`](super::gclk::Gclk)
+    ///
+    /// Returns a [`DynPclk`], which should be used to create the corresponding
+    /// peripheral. The DFLL and DPLL channels are reserved for the tree
+    /// itself.
+    #[inline]
+    pub fn enable_pclk(&mut self, id: DynPclkId, gclk: DynGclkId) -> Result<DynPclk, Error> {
+        let freq = self.state.check_pclk(id, gclk)?;
+        self.write_pchctrl(id, Some(gclk));
+        self.state.pclks[id as usize] = Some((id, gclk));
+        Ok(DynPclk { id, freq })
+    }
+
+    /// Disable a [`Pclk`](super::pclk::Pclk)
+    ///
+    /// Consumes the [`DynPclk`] returned by
+    /// [`enable_pclk`](Self::enable_pclk), so the peripheral channel can only
+    /// be disabled once the peripheral using it has released it.
+    #[inline]
+    pub fn disable_pclk(&mut self, pclk: DynPclk) {
+        let id = pclk.id;
+        self.state.pclks[id as usize] = None;
+        self.write_pchctrl(id, None);
+    }
+
+    //--------------------------------------------------------------------------
+    // Listeners
+    //--------------------------------------------------------------------------
+
+    /// Register a [`ClockListener`] for the given [`Pclk`](super::pclk::Pclk)
+    ///
+    /// The listener is notified whenever a transition changes the frequency of
+    /// the [`Gclk`](super::gclk::Gclk) driving the
+    /// [`Pclk`](super::pclk::Pclk).
+    #[inline]
+    pub fn register(
+        &mut self,
+        id: DynPclkId,
+        listener: &'a dyn ClockListener,
+    ) -> Result<(), Error> {
+        self.listeners
+            .push((id, listener))
+            .map_err(|_| Error::TooManyListeners)
+    }
+
+    /// Remove all [`ClockListener`]s registered for the given
+    /// [`Pclk`](super::pclk::Pclk)
+    #[inline]
+    pub fn unregister(&mut self, id: DynPclkId) {
+        self.listeners.retain(|(pclk, _)| *pclk != id);
+    }
+
+    #[inline]
+    fn notify(&self, id: DynGclkId, freq: Hertz) {
+        for (pclk, listener) in self.listeners.iter() {
+            if self.state.pclk_source(*pclk) == Some(id) {
+                listener.clock_changed(freq);
+            }
+        }
+    }
+
+    //--------------------------------------------------------------------------
+    // CPU clock
+    //--------------------------------------------------------------------------
+
+    /// Switch the CPU clock to one of the [`CpuClock`] presets
+    ///
+    /// The DFLL is enabled if necessary. When switching away from DPLL0, it is
+    /// disabled if it has no other consumers, along with its reference
+    /// generator, if that generator has no other consumers either. Returns the
+    /// new CPU frequency.
+    pub fn set_cpu_clock(&mut self, clock: CpuClock) -> Result<Hertz, Error> {
+        self.enable_dfll();
+        match clock {
+            CpuClock::Dpll120MHz { reference } => {
+                if reference == DynGclkId::Gclk0 {
+                    return Err(Error::InvalidReference(reference));
+                }
+                let target = 120_000_000.Hz();
+                let ready = self.state.dpll0.and_then(|d| self.state.dpll_freq(d)) == Some(target);
+                if !ready {
+                    let state = self.state.gclks[reference as usize];
+                    let consumers = self.gclk_consumers(reference);
+                    let configured = state.source == Some(DynGclkSourceId::Dfll) && state.div == 48;
+                    if consumers > 0 && !configured {
+                        return Err(Error::InUse { consumers });
+                    }
+                    if self.state.gclks[0].source == Some(DynGclkSourceId::Dpll0) {
+                        // Move the CPU off of DPLL0, so it can be reconfigured
+                        self.set_gclk(DynGclkId::Gclk0, DynGclkSourceId::Dfll, 1)?;
+                    }
+                    if !configured {
+                        self.set_gclk(reference, DynGclkSourceId::Dfll, 48)?;
+                    }
+                    self.enable_dpll0(reference, 120, 0)?;
+                }
+                self.set_gclk(DynGclkId::Gclk0, DynGclkSourceId::Dpll0, 1)?;
+            }
+            CpuClock::Dfll48MHz => {
+                self.set_gclk(DynGclkId::Gclk0, DynGclkSourceId::Dfll, 1)?;
+                self.release_dpll0();
+            }
+            CpuClock::Dfll4MHz => {
+                self.set_gclk(DynGclkId::Gclk0, DynGclkSourceId::Dfll, 12)?;
+                self.release_dpll0();
+            }
+        }
+        Ok(self.cpu_freq())
+    }
+
+    /// Disable DPLL0 and its reference generator, if they are unused
+    #[inline]
+    fn release_dpll0(&mut self) {
+        if let Some(dpll) = self.state.dpll0 {
+            if self.disable_dpll0().is_ok() {
+                // Ignore the error, because the generator may have other users
+                let _ = self.disable_gclk(dpll.reference);
+            }
+        }
+    }
+
+    //--------------------------------------------------------------------------
+    // Registers
+    //--------------------------------------------------------------------------
+
+    #[inline]
+    fn gclk(&self) -> &pac::gclk::RegisterBlock {
+        // Safety: The `DynClockTree` owns all of the `GclkToken`s and
+        // `PclkToken`s, so it has exclusive access to the `GENCTRL` and
+        // `PCHCTRL` registers. See the notes on `Token` types and memory
+        // safety in the root of the `clock` module for more details.
+        unsafe { &*pac::Gclk::PTR }
+    }
+
+    #[inline]
+    fn oscctrl(&self) -> &pac::oscctrl::RegisterBlock {
+        // Safety: The `DynClockTree` owns the `EnabledDfll` and the
+        // `DpllToken` for DPLL0, so it has exclusive access to the
+        // corresponding registers. It never touches any other register in the
+        // block.
+        unsafe { &*pac::Oscctrl::PTR }
+    }
+
+    #[inline]
+    fn dpll0_regs(&self) -> &pac::oscctrl::Dpll {
+        self.oscctrl().dpll(0)
+    }
+
+    #[inline]
+    fn write_dpll0_enable(&mut self, enable: bool) {
+        let dpll = self.dpll0_regs();
+        dpll.dpllctrla().modify(|_, w| w.enable().bit(enable));
+        while dpll.dpllsyncbusy().read().enable().bit_is_set() {}
+    }
+
+    #[inline]
+    fn write_genctrl(&mut self, id: DynGclkId, source: DynGclkSourceId, div: u16) {
+        let gclk = self.gclk();
+        // Safety: The division factor was checked against the valid range for
+        // this generator
+        gclk.genctrl(id as usize).write(|w| unsafe {
+            w.src().variant(source.into());
+            w.divsel().variant(Divselselect::Div1);
+            w.div().bits(div);
+            w.genen().set_bit()
+        });
+        while gclk.syncbusy().read().genctrl().bits() & (1 << id as u16) != 0 {}
+    }
+
+    #[inline]
+    fn write_pchctrl(&mut self, id: DynPclkId, gclk: Option<DynGclkId>) {
+        let pchctrl = self.gclk().pchctrl(id as usize);
+        match gclk {
+            Some(gclk) => pchctrl.write(|w| {
+                w.gen().variant(gclk.into());
+                w.chen().set_bit()
+            }),
+            None => pchctrl.modify(|_, w| w.chen().clear_bit()),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set_gclk(state: &mut TreeState, id: DynGclkId, source: DynGclkSourceId, div: u16) {
+        state.gclks[id as usize] = GclkState {
+            source: Some(source),
+            div,
+        };
+    }
+
+    /// DPLL0 at 120 MHz, referenced to GCLK5 at 1 MHz
+    fn dpll_at_120mhz() -> TreeState {
+        let mut state = TreeState::at_reset(48.MHz());
+        set_gclk(&mut state, DynGclkId::Gclk5, DynGclkSourceId::Dfll, 48);
+        let (dpll, _) = state.check_dpll0(DynGclkId::Gclk5, 120, 0).unwrap();
+        state.dpll0 = Some(dpll);
+        state.pclks[DynPclkId::Dpll0 as usize] = Some((DynPclkId::Dpll0, DynGclkId::Gclk5));
+        state
+    }
+
+    #[test]
+    fn consumers_at_reset() {
+        let state = TreeState::at_reset(48.MHz());
+        assert_eq!(state.gclk_consumers(DynGclkId::Gclk0), 1);
+        assert_eq!(state.gclk_consumers(DynGclkId::Gclk1), 0);
+        assert_eq!(state.source_consumers(DynGclkSourceId::Dfll), 1);
+        assert_eq!(state.source_consumers(DynGclkSourceId::Dpll0), 0);
+        assert_eq!(state.gclk_freq(DynGclkId::Gclk0), Some(48.MHz()));
+        assert_eq!(state.gclk_freq(DynGclkId::Gclk1), None);
+    }
+
+    #[test]
+    fn consumers_count_pclks() {
+        let mut state = TreeState::at_reset(48.MHz());
+        state.pclks[DynPclkId::Sercom0 as usize] = Some((DynPclkId::Sercom0, DynGclkId::Gclk0));
+        state.pclks[DynPclkId::Sercom1 as usize] = Some((DynPclkId::Sercom1, DynGclkId::Gclk2));
+        assert_eq!(state.gclk_consumers(DynGclkId::Gclk0), 2);
+        assert_eq!(state.gclk_consumers(DynGclkId::Gclk2), 1);
+    }
+
+    #[test]
+    fn gclk_division_factors() {
+        let state = TreeState::at_reset(48.MHz());
+        let check = |id, div| state.check_gclk(id, DynGclkSourceId::Dfll, div);
+        assert_eq!(check(DynGclkId::Gclk2, 0), Err(Error::InvalidFactor));
+        assert_eq!(check(DynGclkId::Gclk2, 256), Err(Error::InvalidFactor));
+        assert_eq!(check(DynGclkId::Gclk2, 255), Ok((48_000_000 / 255).Hz()));
+        assert_eq!(check(DynGclkId::Gclk1, 48_000), Ok(1.kHz()));
+    }
+
+    #[test]
+    fn gclk_sources() {
+        let state = TreeState::at_reset(48.MHz());
+        assert_eq!(
+            state.check_gclk(DynGclkId::Gclk0, DynGclkSourceId::OscUlp32k, 1),
+            Err(Error::UnsupportedSource(DynGclkSourceId::OscUlp32k))
+        );
+        assert_eq!(
+            state.check_gclk(DynGclkId::Gclk2, DynGclkSourceId::Xosc0, 1),
+            Err(Error::UnsupportedSource(DynGclkSourceId::Xosc0))
+        );
+        assert_eq!(
+            state.check_gclk(DynGclkId::Gclk2, DynGclkSourceId::Dpll0, 1),
+            Err(Error::SourceDisabled)
+        );
+    }
+
+    #[test]
+    fn gclk_frequency_limits() {
+        let state = dpll_at_120mhz();
+        assert_eq!(
+            state.check_gclk(DynGclkId::Gclk0, DynGclkSourceId::Dpll0, 1),
+            Ok(120.MHz())
+        );
+        assert_eq!(
+            state.check_gclk(DynGclkId::Gclk2, DynGclkSourceId::Dpll0, 1),
+            Ok(120.MHz())
+        );
+        // The DPLL0 reference cannot change while DPLL0 is enabled
+        assert_eq!(
+            state.check_gclk(DynGclkId::Gclk5, DynGclkSourceId::Dfll, 24),
+            Err(Error::InUse { consumers: 1 })
+        );
+    }
+
+    #[test]
+    fn gclk_checks_pclk_limits() {
+        let mut state = dpll_at_120mhz();
+        set_gclk(&mut state, DynGclkId::Gclk2, DynGclkSourceId::Dfll, 1);
+        state.pclks[DynPclkId::Sercom0 as usize] = Some((DynPclkId::Sercom0, DynGclkId::Gclk2));
+        assert_eq!(
+            state.check_gclk(DynGclkId::Gclk2, DynGclkSourceId::Dpll0, 1),
+            Err(Error::PclkFreq {
+                id: DynPclkId::Sercom0,
+                freq: 120.MHz()
+            })
+        );
+        assert_eq!(
+            state.check_gclk(DynGclkId::Gclk2, DynGclkSourceId::Dpll0, 2),
+            Ok(60.MHz())
+        );
+    }
+
+    #[test]
+    fn dpll_factors_and_reference() {
+        let mut state = TreeState::at_reset(48.MHz());
+        set_gclk(&mut state, DynGclkId::Gclk5, DynGclkSourceId::Dfll, 48);
+        assert_eq!(
+            state.check_dpll0(DynGclkId::Gclk5, 0, 0).err(),
+            Some(Error::InvalidFactor)
+        );
+        assert_eq!(
+            state.check_dpll0(DynGclkId::Gclk5, 120, 32).err(),
+            Some(Error::InvalidFactor)
+        );
+        assert_eq!(
+            state.check_dpll0(DynGclkId::Gclk0, 120, 0).err(),
+            Some(Error::InvalidReference(DynGclkId::Gclk0))
+        );
+        assert_eq!(
+            state.check_dpll0(DynGclkId::Gclk6, 120, 0).err(),
+            Some(Error::SourceDisabled)
+        );
+        let (_, freq) = state.check_dpll0(DynGclkId::Gclk5, 120, 16).unwrap();
+        assert_eq!(freq.to_Hz(), 120_500_000);
+    }
+
+    #[test]
+    fn dpll_frequency_limits() {
+        let mut state = TreeState::at_reset(48.MHz());
+        set_gclk(&mut state, DynGclkId::Gclk5, DynGclkSourceId::Dfll, 4);
+        assert_eq!(
+            state.check_dpll0(DynGclkId::Gclk5, 10, 0).err(),
+            Some(Error::PclkFreq {
+                id: DynPclkId::Dpll0,
+                freq: 12.MHz()
+            })
+        );
+        set_gclk(&mut state, DynGclkId::Gclk5, DynGclkSourceId::Dfll, 48);
+        assert_eq!(
+            state.check_dpll0(DynGclkId::Gclk5, 50, 0).err(),
+            Some(Error::DpllFreq(50.MHz()))
+        );
+        assert_eq!(
+            state.check_dpll0(DynGclkId::Gclk5, 201, 0).err(),
+            Some(Error::DpllFreq(201.MHz()))
+        );
+    }
+
+    #[test]
+    fn dpll_in_use() {
+        let mut state = dpll_at_120mhz();
+        set_gclk(&mut state, DynGclkId::Gclk0, DynGclkSourceId::Dpll0, 1);
+        assert_eq!(
+            state.check_dpll0(DynGclkId::Gclk5, 100, 0).err(),
+            Some(Error::InUse { consumers: 1 })
+        );
+    }
+
+    #[test]
+    fn pclk_checks() {
+        let mut state = dpll_at_120mhz();
+        set_gclk(&mut state, DynGclkId::Gclk2, DynGclkSourceId::Dpll0, 1);
+        assert_eq!(
+            state.check_pclk(DynPclkId::Dpll0, DynGclkId::Gclk2),
+            Err(Error::ReservedPclk(DynPclkId::Dpll0))
+        );
+        assert_eq!(
+            state.check_pclk(DynPclkId::Usb, DynGclkId::Gclk2),
+            Err(Error::PclkFreq {
+                id: DynPclkId::Usb,
+                freq: 120.MHz()
+            })
+        );
+        assert_eq!(
+            state.check_pclk(DynPclkId::Tc0Tc1, DynGclkId::Gclk2),
+            Ok(120.MHz())
+        );
+        assert_eq!(
+            state.check_pclk(DynPclkId::Sercom0, DynGclkId::Gclk3),
+            Err(Error::SourceDisabled)
+        );
+        state.pclks[DynPclkId::Sercom0 as usize] = Some((DynPclkId::Sercom0, DynGclkId::Gclk0));
+        assert_eq!(
+            state.check_pclk(DynPclkId::Sercom0, DynGclkId::Gclk0),
+            Err(Error::PclkEnabled(DynPclkId::Sercom0))
+        );
+    }
+}
@@ -514,6 +514,7 @@ impl<G: GclkId> GclkToken<G> {
 /// generators.
 ///
 /// `DynGclkId` is the value-level equivalent of [`GclkId`].
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub enum DynGclkId {
     Gclk0,
     Gclk1,
@@ -835,7 +836,7 @@ impl<I: GclkIo<GclkId = Gclk0Id>> Gclk0Io for I {}
 /// a given [`Gclk`].
 ///
 /// `DynGclkSourceId` is the value-level equivalent of [`GclkSourceId`].
-#[derive(Copy, Clone, PartialEq, Eq)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub enum DynGclkSourceId {
     Dfll,
     Dpll0,
@@ -197,6 +197,25 @@
 //! xosc.enable_failure_detection(clocks.dfll, SafeClockDiv::Div2);
 //! ```
 //!
+//! ## Failure notification
+//!
+//! With the `async` feature enabled, each XOSC can also notify the application
+//! when its clock fails. The corresponding `OSCCTRL_XOSCn` interrupt must be
+//! bound to the `InterruptHandler` for that [`XoscId`]. The
+//! `EnabledXosc::wait_for_clock_failure` future then resolves as soon as the
+//! failure detector triggers, at which point the hardware has already switched
+//! to the safe clock.
+//!
+//! ```ignore
+//! atsamd_hal::bind_interrupts!(struct Irqs {
+//!     OSCCTRL_XOSC0 => atsamd_hal::clock::v2::xosc::InterruptHandler<Xosc0Id>;
+//! });
+//!
+//! xosc.enable_failure_detection(clocks.dfll, SafeClockDiv::Div2);
+//! xosc.wait_for_clock_failure(Irqs).await;
+//! // Running from the safe clock; degrade gracefully
+//! ```
+//!
 //! [`Pins`]: crate::gpio::Pins
 //! [`clock` module documentation]: super
 //! [`clock_system_at_reset`]: super::clock_system_at_reset
@@ -322,6 +341,58 @@ impl<X: XoscId> XoscToken<X> {
         self.xoscctrl().modify(|_, w| w.cfden().clear_bit());
     }
 
+    /// Check whether the XOSC failure interrupt flag is set
+    #[cfg(feature = "async")]
+    #[inline]
+    fn failure_flag(&self) -> bool {
+        let mask = 1 << (X::NUM + 2);
+        // Safety: We are only reading from the `INTFLAG` register, so there is
+        // no risk of memory corruption.
+        unsafe { (*crate::pac::Oscctrl::PTR).intflag().read().bits() & mask != 0 }
+    }
+
+    /// Clear the XOSC failure interrupt flag
+    #[cfg(feature = "async")]
+    #[inline]
+    fn clear_failure_flag(&mut self) {
+        let mask = 1 << (X::NUM + 2);
+        // Safety: Writing a one to `INTFLAG` only clears the corresponding
+        // bit, so we only touch the flag belonging to this `XoscId`.
+        unsafe {
+            (*crate::pac::Oscctrl::PTR)
+                .intflag()
+                .write(|w| w.bits(mask))
+        };
+    }
+
+    /// Enable the XOSC failure interrupt
+    #[cfg(feature = "async")]
+    #[inline]
+    fn enable_failure_interrupt(&mut self) {
+        let mask = 1 << (X::NUM + 2);
+        // Safety: Writing a one to `INTENSET` only affects the corresponding
+        // bit, so we only touch the interrupt belonging to this `XoscId`.
+        unsafe {
+            (*crate::pac::Oscctrl::PTR)
+                .intenset()
+                .write(|w| w.bits(mask))
+        };
+    }
+
+    /// Disable the XOSC failure interrupt
+    #[cfg(feature = "async")]
+    #[inline]
+    fn disable_failure_interrupt(&mut self) {
+        let mask = 1 << (X::NUM + 2);
+        // Safety: Writing a one to `INTENCLR` only affects the corresponding
+        // bit, so we only touch the interrupt belonging to this `XoscId`.
+        unsafe {
+            (*crate::pac::Oscctrl::PTR)
+                .intenclr()
+                .write(|w| w.bits(mask))
+        };
+    }
+
     /// Set most of the fields in the XOSCCTRL register
     #[inline]
     fn set_xoscctrl(&mut self, settings: Settings) {
@@ -395,6 +466,9 @@ pub trait XoscId: Sealed {
     type XIn: PinId;
     /// Corresponding XOUT [`PinId`]
     type XOut: PinId;
+    /// Corresponding clock failure interrupt source
+    #[cfg(feature = "async")]
+    type Interrupt: crate::async_hal::interrupts::InterruptSource;
 }
 
 /// Type-level variant of [`XoscId`] representing the identity of XOSC0
@@ -412,6 +486,8 @@ impl XoscId for Xosc0Id {
     const NUM: usize = 0;
     type XIn = PA14;
     type XOut = PA15;
+    #[cfg(feature = "async")]
+    type Interrupt = crate::async_hal::interrupts::OSCCTRL_XOSC0;
 }
 
 /// Type-level variant of [`XoscId`] representing the identity of XOSC1
@@ -429,6 +505,8 @@ impl XoscId for Xosc1Id {
     const NUM: usize = 1;
     type XIn = PB22;
     type XOut = PB23;
+    #[cfg(feature = "async")]
+    type Interrupt = crate::async_hal::interrupts::OSCCTRL_XOSC1;
 }
 
 //==============================================================================
@@ -1006,6 +1084,97 @@ where
     }
 }
 
+//==============================================================================
+// Async failure notification
+//==============================================================================
+
+#[cfg(feature = "async")]
+mod async_api {
+    use core::future::poll_fn;
+    use core::task::Poll;
+
+    use embassy_sync::waitqueue::AtomicWaker;
+
+    use super::*;
+    use crate::async_hal::interrupts::{Binding, Handler, InterruptSource};
+
+    #[allow(clippy::declare_interior_mutable_const)]
+    const NEW_WAKER: AtomicWaker = AtomicWaker::new();
+    static WAKERS: [AtomicWaker; 2] = [NEW_WAKER; 2];
+
+    /// Interrupt handler for [`Xosc`] clock failure detection
+    ///
+    /// Bind it to the `OSCCTRL_XOSCn` interrupt corresponding to the
+    /// [`XoscId`] `X`.
+    pub struct InterruptHandler<X: XoscId> {
+        _private: (),
+        _id: PhantomData<X>,
+    }
+
+    impl<X: XoscId> Sealed for InterruptHandler<X> {}
+
+    impl<X: XoscId> Handler<X::Interrupt> for InterruptHandler<X> {
+        unsafe fn on_interrupt() {
+            let oscctrl = unsafe { &*crate::pac::Oscctrl::PTR };
+            let mask = 1 << (X::NUM + 2);
+            let pending = oscctrl.intflag().read().bits() & oscctrl.intenset().read().bits();
+            if pending & mask != 0 {
+                // Disable the interrupt but don't clear the flag; it will be
+                // cleared when the future is next polled.
+                oscctrl.intenclr().write(|w| unsafe { w.bits(mask) });
+                WAKERS[X::NUM].wake();
+            }
+        }
+    }
+
+    impl<X, M, N> EnabledXosc<X, M, N>
+    where
+        X: XoscId,
+        M: Mode,
+    {
+        /// Wait until the [`Xosc`] triggers clock failure detection
+        ///
+        /// The returned future resolves immediately if the `Xosc` has already
+        /// failed. Otherwise, it resolves once the failure detector triggers,
+        /// by which time the hardware has switched to the safe clock.
+        ///
+        /// Failure detection must first be enabled with
+        /// [`EnabledXosc::enable_failure_detection`], or the future will never
+        /// resolve.
+        pub async fn wait_for_clock_failure<I>(&mut self, _irq: I)
+        where
+            I: Binding<X::Interrupt, InterruptHandler<X>>,
+        {
+            X::Interrupt::unpend();
+            unsafe { X::Interrupt::enable() };
+
+            poll_fn(|cx| {
+                let token = &mut self.0.token;
+                if token.has_failed() || token.failure_flag() {
+                    token.clear_failure_flag();
+                    token.disable_failure_interrupt();
+                    return Poll::Ready(());
+                }
+
+                WAKERS[X::NUM].register(cx.waker());
+                token.enable_failure_interrupt();
+
+                if token.has_failed() || token.failure_flag() {
+                    token.clear_failure_flag();
+                    token.disable_failure_interrupt();
+                    return Poll::Ready(());
+                }
+
+                Poll::Pending
+            })
+            .await;
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+pub use async_api::InterruptHandler;
+
 //==============================================================================
 // Source
 //==============================================================================
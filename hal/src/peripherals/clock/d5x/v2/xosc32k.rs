@@ -328,6 +328,20 @@
 //! }
 //! ```
 //!
+//! With the `async` feature enabled, the application can also wait for a
+//! failure instead of polling for it. Bind the `OSC32KCTRL` interrupt to the
+//! `InterruptHandler` of this module and await
+//! `Xosc32kCfd::wait_for_clock_failure`.
+//!
+//! ```ignore
+//! atsamd_hal::bind_interrupts!(struct Irqs {
+//!     OSC32KCTRL => atsamd_hal::clock::v2::xosc32k::InterruptHandler;
+//! });
+//!
+//! cfd.wait_for_clock_failure(Irqs).await;
+//! // Running from the safe clock; degrade gracefully
+//! ```
+//!
 //! [`clock` module documentation]: super
 //! [`Pins`]: crate::gpio::Pins
 //! [`clock_system_at_reset`]: super::clock_system_at_reset
@@ -560,6 +574,60 @@ impl Xosc32kCfdToken {
     fn switch_back(&mut self) {
         self.cfdctrl().modify(|_, w| w.swback().set_bit());
     }
+
+    /// Check whether the XOSC32K failure interrupt flag is set
+    #[cfg(feature = "async")]
+    #[inline]
+    fn failure_flag(&self) -> bool {
+        // Safety: We are only reading from the `INTFLAG` register, so there is
+        // no risk of memory corruption.
+        unsafe {
+            (*crate::pac::Osc32kctrl::PTR)
+                .intflag()
+                .read()
+                .xosc32kfail()
+                .bit()
+        }
+    }
+
+    /// Clear the XOSC32K failure interrupt flag
+    #[cfg(feature = "async")]
+    #[inline]
+    fn clear_failure_flag(&mut self) {
+        // Safety: Writing a one to `INTFLAG` only clears the corresponding
+        // bit, so we only touch the failure flag.
+        unsafe {
+            (*crate::pac::Osc32kctrl::PTR)
+                .intflag()
+                .write(|w| w.xosc32kfail().set_bit())
+        };
+    }
+
+    /// Enable the XOSC32K failure interrupt
+    #[cfg(feature = "async")]
+    #[inline]
+    fn enable_failure_interrupt(&mut self) {
+        // Safety: Writing a one to `INTENSET` only affects the corresponding
+        // bit, so we only touch the failure interrupt.
+        unsafe {
+            (*crate::pac::Osc32kctrl::PTR)
+                .intenset()
+                .write(|w| w.xosc32kfail().set_bit())
+        };
+    }
+
+    /// Disable the XOSC32K failure interrupt
+    #[cfg(feature = "async")]
+    #[inline]
+    fn disable_failure_interrupt(&mut self) {
+        // Safety: Writing a one to `INTENCLR` only affects the corresponding
+        // bit, so we only touch the failure interrupt.
+        unsafe {
+            (*crate::pac::Osc32kctrl::PTR)
+                .intenclr()
+                .write(|w| w.xosc32kfail().set_bit())
+        };
+    }
 }
 
 //==============================================================================
@@ -1071,6 +1139,85 @@ impl Xosc32kCfd {
     }
 }
 
+//==============================================================================
+// Async failure notification
+//==============================================================================
+
+#[cfg(feature = "async")]
+mod async_api {
+    use core::future::poll_fn;
+    use core::task::Poll;
+
+    use embassy_sync::waitqueue::AtomicWaker;
+
+    use super::*;
+    use crate::async_hal::interrupts::{Binding, Handler, Interrupt, OSC32KCTRL};
+
+    static WAKER: AtomicWaker = AtomicWaker::new();
+
+    /// Interrupt handler for [`Xosc32kCfd`] clock failure detection
+    ///
+    /// Bind it to the `OSC32KCTRL` interrupt.
+    pub struct InterruptHandler {
+        _private: (),
+    }
+
+    impl Sealed for InterruptHandler {}
+
+    impl Handler<OSC32KCTRL> for InterruptHandler {
+        unsafe fn on_interrupt() {
+            let osc32kctrl = unsafe { &*crate::pac::Osc32kctrl::PTR };
+            let flag = osc32kctrl.intflag().read().xosc32kfail().bit();
+            let enabled = osc32kctrl.intenset().read().xosc32kfail().bit();
+            if flag && enabled {
+                // Disable the interrupt but don't clear the flag; it will be
+                // cleared when the future is next polled.
+                osc32kctrl.intenclr().write(|w| w.xosc32kfail().set_bit());
+                WAKER.wake();
+            }
+        }
+    }
+
+    impl Xosc32kCfd {
+        /// Wait until the XOSC32K triggers clock failure detection
+        ///
+        /// The returned future resolves immediately if the XOSC32K has already
+        /// failed. Otherwise, it resolves once the failure detector triggers,
+        /// by which time the hardware has switched to the safe clock.
+        pub async fn wait_for_clock_failure<I>(&mut self, _irq: I)
+        where
+            I: Binding<OSC32KCTRL, InterruptHandler>,
+        {
+            OSC32KCTRL::unpend();
+            unsafe { OSC32KCTRL::enable() };
+
+            poll_fn(|cx| {
+                let token = &mut self.token;
+                if token.has_failed() || token.failure_flag() {
+                    token.clear_failure_flag();
+                    token.disable_failure_interrupt();
+                    return Poll::Ready(());
+                }
+
+                WAKER.register(cx.waker());
+                token.enable_failure_interrupt();
+
+                if token.has_failed() || token.failure_flag() {
+                    token.clear_failure_flag();
+                    token.disable_failure_interrupt();
+                    return Poll::Ready(());
+                }
+
+                Poll::Pending
+            })
+            .await;
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+pub use async_api::InterruptHandler;
+
 //==============================================================================
 // Ids
 //==============================================================================
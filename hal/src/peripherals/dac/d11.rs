@@ -0,0 +1,215 @@
+//! # Digital-to-Analog Converter
+//!
+//! The SAMD11 and SAMD21 feature a single 10-bit DAC, whose output is
+//! available on `PA02` (`VOUT`). The output can also be routed internally to
+//! the Analog Comparators and the ADC.
+//!
+//! ```no_run
+//! use atsamd_hal::dac::{Dac, Reference};
+//!
+//! let gclk0 = clocks.gclk0();
+//! let dac_clock = clocks.dac(&gclk0).unwrap();
+//! let mut dac = Dac::new(&mut peripherals.pm, peripherals.dac, dac_clock, pins.pa02, Reference::Avcc);
+//!
+//! // Output half of the reference voltage
+//! dac.write(512);
+//! ```
+//!
+//! ## DMA
+//!
+//! With the `dma` feature enabled, [`Dac::send_with_dma`] writes a buffer of
+//! samples to the DAC data buffer register. A new sample is requested each
+//! time the data buffer is copied to the data register, which happens on every
+//! start conversion event. The sample rate is therefore set by the event
+//! generator, typically a TC overflow routed through the
+//! [`evsys`](crate::evsys) to the [`DacStart`](crate::evsys::users::DacStart)
+//! user.
+
+use crate::clock::DacClock;
+use crate::gpio::{AlternateB, AnyPin, Pin, PA02};
+use crate::pac::{self, dac, Pm};
+
+#[cfg(feature = "dma")]
+use crate::dmac::{
+    self, AnyChannel, Buffer, BufferPair, Busy, Channel, Ready, Transfer, TriggerAction,
+    TriggerSource,
+};
+
+/// Voltage reference
+pub use dac::ctrlb::Refselselect as Reference;
+
+/// Largest value which can be written to the DAC
+pub const MAX_VALUE: u16 = (1 << 10) - 1;
+
+/// Digital-to-Analog Converter
+pub struct Dac {
+    dac: pac::Dac,
+    clock: DacClock,
+    pin: Pin<PA02, AlternateB>,
+}
+
+impl Dac {
+    /// Create a new [`Dac`] and enable its output on `PA02`.
+    ///
+    /// The DAC is reset, then enabled with the selected voltage
+    /// [`Reference`]. Its output is initially set to 0.
+    pub fn new(
+        pm: &mut Pm,
+        dac: pac::Dac,
+        clock: DacClock,
+        pin: impl AnyPin<Id = PA02>,
+        reference: Reference,
+    ) -> Self {
+        pm.apbcmask().modify(|_, w| w.dac_().set_bit());
+
+        dac.ctrla().write(|w| w.swrst().set_bit());
+        while dac.ctrla().read().swrst().bit_is_set() {}
+        while dac.status().read().syncbusy().bit_is_set() {}
+
+        dac.ctrlb().write(|w| {
+            w.refsel().variant(reference);
+            w.eoen().set_bit()
+        });
+
+        let mut dac = Self {
+            dac,
+            clock,
+            pin: pin.into().into_alternate(),
+        };
+        dac.enable();
+        dac
+    }
+
+    /// Write a new value to the DAC.
+    ///
+    /// Values are right-adjusted. Only the lower 10 bits are used.
+    #[inline]
+    pub fn write(&mut self, value: u16) {
+        self.dac
+            .data()
+            .write(|w| unsafe { w.data().bits(value & MAX_VALUE) });
+        self.sync();
+    }
+
+    /// Change the voltage reference.
+    pub fn set_reference(&mut self, reference: Reference) {
+        self.disable();
+        self.dac
+            .ctrlb()
+            .modify(|_, w| w.refsel().variant(reference));
+        self.enable();
+    }
+
+    /// Route the DAC output to the Analog Comparators and the ADC.
+    pub fn internal_output(&mut self, enabled: bool) {
+        self.disable();
+        self.dac.ctrlb().modify(|_, w| w.ioen().bit(enabled));
+        self.enable();
+    }
+
+    /// Keep the DAC running in standby sleep mode.
+    pub fn run_in_standby(&mut self, enabled: bool) {
+        self.disable();
+        self.dac.ctrla().modify(|_, w| w.runstdby().bit(enabled));
+        self.enable();
+    }
+
+    /// Disable the voltage pump.
+    ///
+    /// The voltage pump should be disabled when VDDANA is above 2.5V, in
+    /// order to reduce power consumption.
+    pub fn disable_voltage_pump(&mut self, disabled: bool) {
+        self.disable();
+        self.dac.ctrlb().modify(|_, w| w.vpd().bit(disabled));
+        self.enable();
+    }
+
+    /// Copy the data buffer to the data register on every start conversion
+    /// event.
+    pub fn start_event_input(&mut self, enabled: bool) {
+        self.disable();
+        self.dac.evctrl().modify(|_, w| w.startei().bit(enabled));
+        self.enable();
+    }
+
+    /// Emit an event every time the data buffer becomes empty.
+    pub fn empty_event_output(&mut self, enabled: bool) {
+        self.disable();
+        self.dac.evctrl().modify(|_, w| w.emptyeo().bit(enabled));
+        self.enable();
+    }
+
+    /// Disable the DAC and return the underlying resources.
+    pub fn free(mut self) -> (pac::Dac, DacClock, Pin<PA02, AlternateB>) {
+        self.disable();
+        (self.dac, self.clock, self.pin)
+    }
+
+    #[inline]
+    fn enable(&mut self) {
+        self.dac.ctrla().modify(|_, w| w.enable().set_bit());
+        self.sync();
+    }
+
+    #[inline]
+    fn disable(&mut self) {
+        self.dac.ctrla().modify(|_, w| w.enable().clear_bit());
+        self.sync();
+    }
+
+    #[inline]
+    fn sync(&self) {
+        while self.dac.status().read().syncbusy().bit_is_set() {}
+    }
+}
+
+#[cfg(feature = "dma")]
+impl Dac {
+    /// Transform the [`Dac`] into a DMA [`Transfer`] and start sending the
+    /// provided buffer to the DAC data buffer.
+    ///
+    /// Start conversion events are enabled, as they are what moves each sample
+    /// from the data buffer to the output. If `circular` is `true`, the buffer
+    /// is sent repeatedly until the transfer is stopped.
+    pub fn send_with_dma<Ch, B>(
+        mut self,
+        buf: B,
+        mut channel: Ch,
+        circular: bool,
+    ) -> Transfer<Channel<Ch::Id, Busy>, BufferPair<B, Self>>
+    where
+        Ch: AnyChannel<Status = Ready>,
+        B: Buffer<Beat = u16> + 'static,
+    {
+        channel
+            .as_mut()
+            .enable_interrupts(dmac::InterruptFlags::new().with_tcmpl(true));
+
+        self.start_event_input(true);
+
+        // SAFETY: This is safe because of the `'static` bound check for `B`,
+        // and the fact that the buffer length of a `Dac` is always 1.
+        let xfer = unsafe { Transfer::new_unchecked(channel, buf, self, circular) };
+        xfer.begin(TriggerSource::DacEmpty, TriggerAction::Beat)
+    }
+}
+
+#[cfg(feature = "dma")]
+unsafe impl Buffer for Dac {
+    type Beat = u16;
+
+    #[inline]
+    fn dma_ptr(&mut self) -> *mut Self::Beat {
+        self.dac.databuf().as_ptr()
+    }
+
+    #[inline]
+    fn incrementing(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn buffer_len(&self) -> usize {
+        1
+    }
+}
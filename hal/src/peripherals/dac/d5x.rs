@@ -0,0 +1,423 @@
+//! # Digital-to-Analog Converter
+//!
+//! The SAMx5x feature two 12-bit DACs, whose outputs are available on `PA02`
+//! (`VOUT0`) and `PA05` (`VOUT1`). Both DACs share the same voltage
+//! reference, but are otherwise configured independently.
+//!
+//! [`Dac::split`] returns a [`Channel`] for each DAC. A channel is enabled
+//! with a [`ChannelConfig`] and its output pin, which returns an
+//! [`EnabledChannel`].
+//!
+//! **Note**: The DAC configuration registers can only be written while the
+//! whole peripheral is disabled. Enabling or disabling a channel therefore
+//! briefly interrupts the output of the other channel.
+//!
+//! ```no_run
+//! use atsamd_hal::dac::{ChannelConfig, Dac, Reference};
+//!
+//! let (pclk_dac, gclk0) = clock::pclk::Pclk::enable(tokens.pclks.dac, clocks.gclk0);
+//! let mut dac = Dac::new(&mut peripherals.mclk, peripherals.dac, pclk_dac.into(), Reference::Vddana);
+//! let channels = dac.split();
+//! let mut dac0 = channels.0.enable(pins.pa02, ChannelConfig::default());
+//!
+//! // Output half of the reference voltage
+//! dac0.write(2048);
+//! ```
+//!
+//! ## DMA
+//!
+//! With the `dma` feature enabled, [`EnabledChannel::send_with_dma`] writes a
+//! buffer of samples to the channel's data buffer register. A new sample is
+//! requested each time the data buffer is copied to the data register, which
+//! happens on every start conversion event. The sample rate is therefore set
+//! by the event generator, typically a TC overflow routed through the
+//! [`evsys`](crate::evsys) to the [`DacStart0`](crate::evsys::users::DacStart0)
+//! or [`DacStart1`](crate::evsys::users::DacStart1) user.
+
+use core::marker::PhantomData;
+
+use crate::clock::DacClock;
+use crate::gpio::{AlternateB, AnyPin, Pin, PinId, PA02, PA05};
+use crate::pac::{self, dac, Mclk};
+use crate::typelevel::Sealed;
+
+#[cfg(feature = "dma")]
+use crate::dmac::{
+    self, AnyChannel, Buffer, BufferPair, Busy, Ready, Transfer, TriggerAction, TriggerSource,
+};
+
+/// Voltage reference
+pub use dac::ctrlb::Refselselect as Reference;
+/// Current control, which must match the GCLK_DAC frequency
+pub use dac::dacctrl::Cctrlselect as CurrentControl;
+/// Interpolation oversampling ratio
+pub use dac::dacctrl::Osrselect as Oversampling;
+/// Output refresh period
+pub use dac::dacctrl::Refreshselect as Refresh;
+
+/// Largest value which can be written to a DAC
+pub const MAX_VALUE: u16 = (1 << 12) - 1;
+
+#[inline]
+fn dac() -> &'static dac::RegisterBlock {
+    // SAFETY: The global CTRLA and CTRLB registers are only modified through
+    // `&mut Dac` or by a `Channel` while both channels are reconfigured, and
+    // each `Channel` otherwise only touches its own registers.
+    unsafe { &*pac::Dac::ptr() }
+}
+
+//==============================================================================
+// Channel IDs
+//==============================================================================
+
+/// Trait representing a DAC channel ID
+pub trait ChId: Sealed {
+    /// Index of the channel
+    const ID: usize;
+    /// Output pin of the channel
+    type Pin: PinId;
+    /// DMA trigger requesting a new sample for the channel
+    #[cfg(feature = "dma")]
+    const DMA_TRIGGER: TriggerSource;
+}
+
+/// ID of DAC0
+pub enum Dac0 {}
+
+impl Sealed for Dac0 {}
+
+impl ChId for Dac0 {
+    const ID: usize = 0;
+    type Pin = PA02;
+    #[cfg(feature = "dma")]
+    const DMA_TRIGGER: TriggerSource = TriggerSource::DacEmpty0;
+}
+
+/// ID of DAC1
+pub enum Dac1 {}
+
+impl Sealed for Dac1 {}
+
+impl ChId for Dac1 {
+    const ID: usize = 1;
+    type Pin = PA05;
+    #[cfg(feature = "dma")]
+    const DMA_TRIGGER: TriggerSource = TriggerSource::DacEmpty1;
+}
+
+//==============================================================================
+// Dac
+//==============================================================================
+
+/// Digital-to-Analog Converter controller
+///
+/// Use [`split`](Self::split) to obtain the individual DAC [`Channels`].
+pub struct Dac {
+    dac: pac::Dac,
+    clock: DacClock,
+}
+
+/// Struct holding a handle to each DAC channel
+pub struct Channels(pub Channel<Dac0>, pub Channel<Dac1>);
+
+impl Dac {
+    /// Create a new [`Dac`] with the selected voltage [`Reference`].
+    pub fn new(mclk: &mut Mclk, dac: pac::Dac, clock: DacClock, reference: Reference) -> Self {
+        mclk.apbdmask().modify(|_, w| w.dac_().set_bit());
+
+        dac.ctrla().write(|w| w.swrst().set_bit());
+        while dac.syncbusy().read().swrst().bit_is_set() {}
+
+        dac.ctrlb().write(|w| w.refsel().variant(reference));
+
+        Self { dac, clock }
+    }
+
+    /// Split the [`Dac`] into individual [`Channels`].
+    #[inline]
+    pub fn split(&mut self) -> Channels {
+        Channels(Channel::new(), Channel::new())
+    }
+
+    /// Change the voltage reference.
+    ///
+    /// The output of any enabled channel is briefly interrupted.
+    pub fn set_reference(&mut self, reference: Reference) {
+        reconfigure(|| {
+            self.dac
+                .ctrlb()
+                .modify(|_, w| w.refsel().variant(reference));
+        });
+    }
+
+    /// Reset the DAC and return the underlying resources.
+    ///
+    /// **Note**: The [`Channels`] struct is consumed by this method. This
+    /// means that any [`EnabledChannel`] must be
+    /// [`disable`](EnabledChannel::disable)d, and the resulting [`Channel`]
+    /// moved back into the [`Channels`] struct.
+    pub fn free(self, _channels: Channels) -> (pac::Dac, DacClock) {
+        self.dac.ctrla().write(|w| w.swrst().set_bit());
+        while self.dac.syncbusy().read().swrst().bit_is_set() {}
+        (self.dac, self.clock)
+    }
+}
+
+//==============================================================================
+// Channel configuration
+//==============================================================================
+
+/// Configuration of a DAC channel
+#[derive(Clone, Copy)]
+pub struct ChannelConfig {
+    /// Current control. Must be selected according to the GCLK_DAC
+    /// frequency.
+    pub current: CurrentControl,
+    /// Refresh period of the output, required when the output is not updated
+    /// regularly.
+    pub refresh: Refresh,
+    /// Interpolation oversampling ratio.
+    pub oversampling: Oversampling,
+    /// Enable dithering. The 4 LSBs of each written value are then used as
+    /// dither bits, and the value must be left-shifted by 4 bits.
+    pub dither: bool,
+    /// Left-adjust written values.
+    pub left_adjust: bool,
+    /// Keep the channel running in standby sleep mode.
+    pub run_in_standby: bool,
+    /// Use an external filter on the output. This disables the internal
+    /// filter.
+    pub external_filter: bool,
+}
+
+impl Default for ChannelConfig {
+    fn default() -> Self {
+        Self {
+            current: CurrentControl::Cc12m,
+            refresh: Refresh::Refresh0,
+            oversampling: Oversampling::Osr1,
+            dither: false,
+            left_adjust: false,
+            run_in_standby: false,
+            external_filter: false,
+        }
+    }
+}
+
+//==============================================================================
+// Channel
+//==============================================================================
+
+/// Disabled DAC channel
+pub struct Channel<Id: ChId> {
+    _id: PhantomData<Id>,
+}
+
+impl<Id: ChId> Channel<Id> {
+    #[inline]
+    fn new() -> Self {
+        Self { _id: PhantomData }
+    }
+
+    /// Configure and enable the channel, and its output on the `pin`.
+    pub fn enable(
+        self,
+        pin: impl AnyPin<Id = Id::Pin>,
+        config: ChannelConfig,
+    ) -> EnabledChannel<Id> {
+        reconfigure(|| {
+            dac().dacctrl(Id::ID).write(|w| {
+                w.leftadj().bit(config.left_adjust);
+                w.enable().set_bit();
+                w.cctrl().variant(config.current);
+                w.fext().bit(config.external_filter);
+                w.runstdby().bit(config.run_in_standby);
+                w.dither().bit(config.dither);
+                w.refresh().variant(config.refresh);
+                w.osr().variant(config.oversampling)
+            });
+        });
+
+        // Dither bits fill the 4 LSBs of a left-shifted value
+        let mask = if config.dither {
+            u16::MAX
+        } else if config.left_adjust {
+            MAX_VALUE << 4
+        } else {
+            MAX_VALUE
+        };
+        let channel = EnabledChannel {
+            chan: self,
+            pin: pin.into().into_alternate(),
+            mask,
+        };
+        while !channel.is_ready() {}
+        channel
+    }
+}
+
+/// Disable the DAC, run `f`, then enable the DAC again if any channel is
+/// enabled.
+fn reconfigure(f: impl FnOnce()) {
+    let dac = dac();
+    dac.ctrla().modify(|_, w| w.enable().clear_bit());
+    while dac.syncbusy().read().enable().bit_is_set() {}
+
+    f();
+
+    let any_enabled = dac.dacctrl_iter().any(|r| r.read().enable().bit_is_set());
+    if any_enabled {
+        dac.ctrla().modify(|_, w| w.enable().set_bit());
+        while dac.syncbusy().read().enable().bit_is_set() {}
+    }
+}
+
+/// Enabled DAC channel
+pub struct EnabledChannel<Id: ChId> {
+    chan: Channel<Id>,
+    pin: Pin<Id::Pin, AlternateB>,
+    /// Bits of a written value used by the DAC
+    mask: u16,
+}
+
+impl<Id: ChId> EnabledChannel<Id> {
+    /// Write a new value to the DAC.
+    ///
+    /// Values are right-adjusted unless [`ChannelConfig::left_adjust`] was
+    /// set. When dithering is enabled, the value must be left-shifted by 4
+    /// bits and the 4 LSBs hold the dither bits. Only the 12 bits of the
+    /// value, and the dither bits, are used.
+    #[inline]
+    pub fn write(&mut self, value: u16) {
+        dac()
+            .data(Id::ID)
+            .write(|w| unsafe { w.data().bits(value & self.mask) });
+        while self.data_syncing() {}
+    }
+
+    /// Return `true` once the DAC is ready to perform conversions.
+    #[inline]
+    pub fn is_ready(&self) -> bool {
+        let status = dac().status().read();
+        match Id::ID {
+            0 => status.ready0().bit_is_set(),
+            _ => status.ready1().bit_is_set(),
+        }
+    }
+
+    /// Return `true` once the last conversion has completed.
+    #[inline]
+    pub fn conversion_complete(&self) -> bool {
+        let status = dac().status().read();
+        match Id::ID {
+            0 => status.eoc0().bit_is_set(),
+            _ => status.eoc1().bit_is_set(),
+        }
+    }
+
+    /// Copy the data buffer to the data register on every start conversion
+    /// event. If `invert` is `true`, the start event input is inverted.
+    pub fn start_event_input(&mut self, enabled: bool, invert: bool) {
+        reconfigure(|| {
+            dac().evctrl().modify(|_, w| match Id::ID {
+                0 => {
+                    w.startei0().bit(enabled);
+                    w.invei0().bit(invert)
+                }
+                _ => {
+                    w.startei1().bit(enabled);
+                    w.invei1().bit(invert)
+                }
+            });
+        });
+    }
+
+    /// Emit an event every time the data buffer becomes empty.
+    pub fn empty_event_output(&mut self, enabled: bool) {
+        reconfigure(|| {
+            dac().evctrl().modify(|_, w| match Id::ID {
+                0 => w.emptyeo0().bit(enabled),
+                _ => w.emptyeo1().bit(enabled),
+            });
+        });
+    }
+
+    /// Emit an event every time an interpolated result is ready.
+    pub fn result_ready_event_output(&mut self, enabled: bool) {
+        reconfigure(|| {
+            dac().evctrl().modify(|_, w| match Id::ID {
+                0 => w.resrdyeo0().bit(enabled),
+                _ => w.resrdyeo1().bit(enabled),
+            });
+        });
+    }
+
+    /// Disable the channel, and return the [`Channel`] and its output pin.
+    pub fn disable(self) -> (Channel<Id>, Pin<Id::Pin, AlternateB>) {
+        reconfigure(|| {
+            dac().dacctrl(Id::ID).write(|w| w.enable().clear_bit());
+        });
+        (self.chan, self.pin)
+    }
+
+    #[inline]
+    fn data_syncing(&self) -> bool {
+        let syncbusy = dac().syncbusy().read();
+        match Id::ID {
+            0 => syncbusy.data0().bit_is_set(),
+            _ => syncbusy.data1().bit_is_set(),
+        }
+    }
+}
+
+#[cfg(feature = "dma")]
+impl<Id: ChId> EnabledChannel<Id> {
+    /// Transform the [`EnabledChannel`] into a DMA [`Transfer`] and start
+    /// sending the provided buffer to the channel's data buffer.
+    ///
+    /// Start conversion events are enabled, as they are what moves each sample
+    /// from the data buffer to the output. If `circular` is `true`, the buffer
+    /// is sent repeatedly until the transfer is stopped.
+    pub fn send_with_dma<Ch, B>(
+        mut self,
+        buf: B,
+        mut channel: Ch,
+        circular: bool,
+    ) -> Transfer<dmac::Channel<Ch::Id, Busy>, BufferPair<B, Self>>
+    where
+        Ch: AnyChannel<Status = Ready>,
+        B: Buffer<Beat = u16> + 'static,
+    {
+        channel
+            .as_mut()
+            .enable_interrupts(dmac::InterruptFlags::new().with_tcmpl(true));
+
+        self.start_event_input(true, false);
+
+        // SAFETY: This is safe because of the `'static` bound check for `B`,
+        // and the fact that the buffer length of an `EnabledChannel` is always
+        // 1.
+        let xfer = unsafe { Transfer::new_unchecked(channel, buf, self, circular) };
+        xfer.begin(Id::DMA_TRIGGER, TriggerAction::Burst)
+    }
+}
+
+#[cfg(feature = "dma")]
+unsafe impl<Id: ChId> Buffer for EnabledChannel<Id> {
+    type Beat = u16;
+
+    #[inline]
+    fn dma_ptr(&mut self) -> *mut Self::Beat {
+        dac().databuf(Id::ID).as_ptr()
+    }
+
+    #[inline]
+    fn incrementing(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn buffer_len(&self) -> usize {
+        1
+    }
+}
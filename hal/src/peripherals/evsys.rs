@@ -0,0 +1,740 @@
+//! # Event System
+//!
+//! The Event System (EVSYS) allows peripherals to communicate with each other
+//! without any CPU involvement. A peripheral which emits events is called an
+//! event *generator*, and a peripheral which consumes events is called an event
+//! *user*. Generators and users are connected through event *channels*.
+//!
+//! Each chip has a number of event channels:
+//!
+//! * SAMD11: 6 channels
+//! * SAMD21: 12 channels
+//! * SAMx5x: 32 channels, only the first 12 of which support the synchronous
+//!   and resynchronized paths
+//!
+//! ## Generators and users
+//!
+//! Event generators are represented by the unit structs found in the
+//! [`generators`] module. A generator can drive any number of channels
+//! simultaneously, so generator types can be freely created and copied.
+//!
+//! Event users are represented by [`User`] tokens. A user can only ever listen
+//! to a single channel at once, so each [`User`] is a singleton, obtained from
+//! the [`Users`] struct returned by [`EvSys::split`]. The type parameter of a
+//! [`User`] is one of the marker types found in the [`users`] module.
+//!
+//! ## Event paths
+//!
+//! Each [`EventChannel`] is configured to use one of three paths, which are
+//! tracked at the type level:
+//!
+//! * [`Asynchronous`]: the event is routed directly from the generator to the
+//!   users. No clock is required, and edge detection is not available.
+//! * [`Synchronous`]: the event is synchronized to the channel's generic clock.
+//!   The generator and the users must run on the same clock domain.
+//! * [`Resynchronized`]: the event is resynchronized to the channel's generic
+//!   clock. This should be used when the generator and the users run on
+//!   different clock domains.
+//!
+//! The synchronous and resynchronized paths require the channel's generic
+//! clock to be configured, and support edge detection as well as
+//! software-triggered events (see [`EventChannel::trigger`]).
+//!
+//! ## Connecting users
+//!
+//! Connecting a [`User`] to an [`EventChannel`] returns a [`Link`] and
+//! increments the channel's user count, which is tracked at the type level in
+//! the same way as the `clock::v2` API tracks the consumers of a clock. A
+//! channel may only be released with [`EventChannel::free`] once all of its
+//! [`Link`]s have been [`disconnect`](EventChannel::disconnect)ed, which
+//! returns the [`User`] token.
+//!
+//! **Note**: Connecting a user to a channel only routes the events. The
+//! generator and user peripherals must also have their own event output and
+//! event input enabled, for instance through [`ExtInt::enable_event`].
+//!
+//! ## Example
+//!
+//! Start an ADC conversion on every rising edge of EXTINT\[3\] on a SAMD21:
+//!
+//! ```no_run
+//! use atsamd_hal::evsys::{generators, EdgeSelection, EvSys};
+//!
+//! let mut evsys = EvSys::new(&mut peripherals.pm, peripherals.evsys);
+//! let (channels, users) = evsys.split();
+//!
+//! // The resynchronized path requires the channel's generic clock
+//! let clock = clocks.evsys0(&gclk0).unwrap();
+//! let channel = channels
+//!     .0
+//!     .resynchronized(generators::EicExtint3, EdgeSelection::RisingEdge, clock);
+//!
+//! let (channel, link) = channel.connect(users.adc_start);
+//!
+//! // Tell the EIC to emit events on EXTINT[3]
+//! extint.enable_event();
+//! ```
+//!
+//! [`ExtInt::enable_event`]: crate::eic::ExtInt::enable_event
+
+#![allow(unused_braces)]
+// This is necessary until modular_bitfield fixes all their identity_op warnings
+#![allow(clippy::identity_op)]
+
+use core::marker::PhantomData;
+
+use atsamd_hal_macros::{hal_cfg, hal_module};
+use modular_bitfield::prelude::*;
+use seq_macro::seq;
+use typenum::U0;
+
+use crate::pac;
+use crate::typelevel::{Decrement, Increment, PrivateDecrement, PrivateIncrement, Sealed};
+
+#[hal_cfg(any("evsys-d11", "evsys-d21"))]
+pub use pac::evsys::channel::{Edgselselect as EdgeSelection, Pathselect as PathSelection};
+
+#[hal_cfg("evsys-d5x")]
+pub use pac::evsys::channels::channel::{
+    Edgselselect as EdgeSelection, Pathselect as PathSelection,
+};
+
+//==============================================================================
+// Generator and user tables
+//==============================================================================
+
+/// Declare event generator marker types
+macro_rules! generators {
+    (
+        $(
+            $(#[$($attr:tt)*])*
+            $Gen:ident = $id:expr
+        ),+ $(,)?
+    ) => {
+        $(
+            $(#[$($attr)*])*
+            #[derive(Clone, Copy, Debug, Default)]
+            pub struct $Gen;
+
+            $(#[$($attr)*])*
+            impl $crate::typelevel::Sealed for $Gen {}
+
+            $(#[$($attr)*])*
+            impl $crate::evsys::GeneratorId for $Gen {
+                const ID: u8 = $id;
+            }
+        )+
+    };
+}
+
+/// Declare event user marker types, as well as the [`Users`] struct
+/// containing a token for each of them
+macro_rules! users {
+    (
+        $(
+            $(#[$($attr:tt)*])*
+            $User:ident = $id:expr
+        ),+ $(,)?
+    ) => {
+        $crate::paste::paste! {
+            $(
+                $(#[$($attr)*])*
+                pub enum $User {}
+
+                $(#[$($attr)*])*
+                impl $crate::typelevel::Sealed for $User {}
+
+                $(#[$($attr)*])*
+                impl $crate::evsys::UserId for $User {
+                    const ID: u8 = $id;
+                }
+            )+
+
+            /// Struct containing a [`User`](crate::evsys::User) token for each
+            /// event user available on this chip
+            #[atsamd_hal_macros::hal_macro_helper]
+            pub struct Users {
+                $(
+                    $(#[$($attr)*])*
+                    pub [<$User:snake>]: $crate::evsys::User<$User>,
+                )+
+            }
+
+            impl Users {
+                #[inline]
+                #[allow(unused_doc_comments)]
+                #[atsamd_hal_macros::hal_macro_helper]
+                pub(in crate::evsys) fn new() -> Self {
+                    Self {
+                        $(
+                            $(#[$($attr)*])*
+                            [<$User:snake>]: $crate::evsys::User::new(),
+                        )+
+                    }
+                }
+            }
+        }
+    };
+}
+
+#[hal_module(
+    any("evsys-d11", "evsys-d21") => "evsys/d11.rs",
+    "evsys-d5x" => "evsys/d5x.rs",
+)]
+mod impls {}
+
+pub use impls::{generators, users};
+pub use users::Users;
+
+//==============================================================================
+// Channel IDs
+//==============================================================================
+
+/// Trait representing an event channel ID
+pub trait ChId: Sealed {
+    const ID: usize;
+}
+
+/// Trait representing an event channel ID which is connected to a generic
+/// clock, and can therefore use the [`Synchronous`] and [`Resynchronized`]
+/// paths
+pub trait ClockedChId: ChId {
+    /// Typed token proving that the channel's generic clock has been
+    /// configured
+    type Clock;
+}
+
+#[hal_cfg("evsys-d11")]
+macro_rules! with_num_channels {
+    ($some_macro:ident) => {
+        $some_macro! {6, 6}
+    };
+}
+
+#[hal_cfg("evsys-d21")]
+macro_rules! with_num_channels {
+    ($some_macro:ident) => {
+        $some_macro! {12, 12}
+    };
+}
+
+#[hal_cfg("evsys-d5x")]
+macro_rules! with_num_channels {
+    ($some_macro:ident) => {
+        $some_macro! {32, 12}
+    };
+}
+
+macro_rules! get {
+    ($channels:literal, $clocked:literal) => {
+        $channels
+    };
+}
+
+macro_rules! get_clocked {
+    ($channels:literal, $clocked:literal) => {
+        $clocked
+    };
+}
+
+/// The number of event channels on this chip.
+pub const NUM_CHANNELS: usize = with_num_channels!(get);
+
+/// The number of event channels which support the [`Synchronous`] and
+/// [`Resynchronized`] paths on this chip.
+pub const NUM_CLOCKED_CHANNELS: usize = with_num_channels!(get_clocked);
+
+macro_rules! define_channels_struct {
+    ($num_channels:literal, $num_clocked:literal) => {
+        seq!(N in 0..$num_channels {
+            #(
+                /// Type alias for a channel number
+                pub enum Ch~N {}
+
+                impl Sealed for Ch~N {}
+
+                impl ChId for Ch~N {
+                    const ID: usize = N;
+                }
+            )*
+
+            /// Struct generating individual handles to each event channel
+            pub struct Channels(
+                #(
+                    pub Channel<Ch~N>,
+                )*
+            );
+
+            impl Channels {
+                #[inline]
+                fn new() -> Self {
+                    Channels(
+                        #(
+                            Channel::new(),
+                        )*
+                    )
+                }
+            }
+        });
+
+        seq!(N in 0..$num_clocked {
+            $crate::paste::paste! {
+                #(
+                    impl ClockedChId for Ch~N {
+                        type Clock = crate::clock::[<Evsys N Clock>];
+                    }
+                )*
+            }
+        });
+    };
+}
+
+with_num_channels!(define_channels_struct);
+
+//==============================================================================
+// Generators and users
+//==============================================================================
+
+/// Trait implemented by all event generators
+///
+/// Event generators are found in the [`generators`] module.
+pub trait GeneratorId: Sealed + Copy {
+    /// Value of the `CHANNEL.EVGEN` field selecting this generator
+    const ID: u8;
+}
+
+/// Trait implemented by all event users
+///
+/// Event users are found in the [`users`] module.
+pub trait UserId: Sealed {
+    /// Index of the `USER` register for this user
+    const ID: u8;
+}
+
+/// Singleton token representing an event user
+///
+/// [`User`]s are obtained from the [`Users`] struct, and can be connected to
+/// an [`EventChannel`] with [`EventChannel::connect`].
+pub struct User<U: UserId> {
+    _user: PhantomData<U>,
+}
+
+impl<U: UserId> User<U> {
+    #[inline]
+    pub(crate) fn new() -> Self {
+        Self { _user: PhantomData }
+    }
+}
+
+/// Proof that a [`User`] is connected to the event channel `Id`
+///
+/// A [`Link`] must be given back to
+/// [`EventChannel::disconnect`] in order to recover the [`User`] token.
+pub struct Link<Id: ChId, U: UserId> {
+    user: User<U>,
+    _id: PhantomData<Id>,
+}
+
+//==============================================================================
+// Paths
+//==============================================================================
+
+/// Type-level enum of the event paths
+pub trait EventPath: Sealed {
+    /// Value of the `CHANNEL.PATH` field
+    const PATH: PathSelection;
+}
+
+/// Type-level variant of [`EventPath`] for the synchronous path
+pub enum Synchronous {}
+
+impl Sealed for Synchronous {}
+
+impl EventPath for Synchronous {
+    const PATH: PathSelection = PathSelection::Synchronous;
+}
+
+/// Type-level variant of [`EventPath`] for the resynchronized path
+pub enum Resynchronized {}
+
+impl Sealed for Resynchronized {}
+
+impl EventPath for Resynchronized {
+    const PATH: PathSelection = PathSelection::Resynchronized;
+}
+
+/// Type-level variant of [`EventPath`] for the asynchronous path
+pub enum Asynchronous {}
+
+impl Sealed for Asynchronous {}
+
+impl EventPath for Asynchronous {
+    const PATH: PathSelection = PathSelection::Asynchronous;
+}
+
+/// Marker trait for the paths going through the channel's edge detector and
+/// generic clock
+pub trait ClockedPath: EventPath {}
+
+impl ClockedPath for Synchronous {}
+impl ClockedPath for Resynchronized {}
+
+//==============================================================================
+// Interrupts
+//==============================================================================
+
+/// Interrupt sources available to an event channel
+#[bitfield]
+#[repr(u8)]
+#[derive(Clone, Copy)]
+pub struct InterruptFlags {
+    /// Overrun: a new event was received before the previous one was
+    /// handled by all users
+    pub ovr: bool,
+    /// Event detected
+    pub evd: bool,
+    #[skip]
+    _reserved: B6,
+}
+
+impl Default for InterruptFlags {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+//==============================================================================
+// EvSys
+//==============================================================================
+
+/// Event System peripheral
+///
+/// Use [`split`](Self::split) to obtain the individual event [`Channels`] and
+/// the [`Users`] tokens.
+pub struct EvSys {
+    evsys: pac::Evsys,
+}
+
+impl EvSys {
+    /// Create a new [`EvSys`] and reset the peripheral.
+    #[hal_cfg(any("evsys-d11", "evsys-d21"))]
+    #[inline]
+    pub fn new(pm: &mut pac::Pm, evsys: pac::Evsys) -> Self {
+        pm.apbcmask().modify(|_, w| w.evsys_().set_bit());
+
+        let mut evsys = Self { evsys };
+        evsys.swreset();
+        evsys
+    }
+
+    /// Create a new [`EvSys`] and reset the peripheral.
+    #[hal_cfg("evsys-d5x")]
+    #[inline]
+    pub fn new(mclk: &mut pac::Mclk, evsys: pac::Evsys) -> Self {
+        mclk.apbbmask().modify(|_, w| w.evsys_().set_bit());
+
+        let mut evsys = Self { evsys };
+        evsys.swreset();
+        evsys
+    }
+
+    /// Split the [`EvSys`] into individual [`Channels`], and the [`Users`]
+    /// tokens.
+    #[inline]
+    pub fn split(&mut self) -> (Channels, Users) {
+        (Channels::new(), Users::new())
+    }
+
+    /// Reset the EVSYS and return the register block.
+    ///
+    /// **Note**: The [`Channels`] and [`Users`] structs are consumed by this
+    /// method. This means that any [`Channel`] or [`User`] obtained by
+    /// [`split`](EvSys::split) must be moved back into their respective
+    /// structs before being able to pass them into [`free`](EvSys::free).
+    #[inline]
+    pub fn free(mut self, _channels: Channels, _users: Users) -> pac::Evsys {
+        self.swreset();
+        self.evsys
+    }
+
+    /// Issue a software reset to the EVSYS
+    #[hal_cfg(any("evsys-d11", "evsys-d21"))]
+    #[inline]
+    fn swreset(&mut self) {
+        // CTRL is write-only on these chips. The reset completes within a
+        // single APB clock cycle.
+        self.evsys.ctrl().write(|w| w.swrst().set_bit());
+    }
+
+    /// Issue a software reset to the EVSYS and wait for reset to complete
+    #[hal_cfg("evsys-d5x")]
+    #[inline]
+    fn swreset(&mut self) {
+        self.evsys.ctrla().write(|w| w.swrst().set_bit());
+        while self.evsys.ctrla().read().swrst().bit_is_set() {
+            core::hint::spin_loop();
+        }
+    }
+}
+
+//==============================================================================
+// Channel
+//==============================================================================
+
+/// Unconfigured event channel
+///
+/// Use one of [`asynchronous`](Self::asynchronous),
+/// [`synchronous`](Self::synchronous) or
+/// [`resynchronized`](Self::resynchronized) to select an event generator and
+/// turn the channel into an [`EventChannel`].
+pub struct Channel<Id: ChId> {
+    _id: PhantomData<Id>,
+}
+
+impl<Id: ChId> Channel<Id> {
+    #[inline]
+    fn new() -> Self {
+        Self { _id: PhantomData }
+    }
+
+    /// Route events from the generator `G` through the asynchronous path.
+    #[inline]
+    pub fn asynchronous<G: GeneratorId>(self, gen: G) -> EventChannel<Id, G, Asynchronous> {
+        EventChannel::new(self, gen, EdgeSelection::NoEvtOutput)
+    }
+}
+
+impl<Id: ClockedChId> Channel<Id> {
+    /// Route events from the generator `G` through the synchronous path.
+    ///
+    /// The generator and all users must be clocked from the same generic
+    /// clock as the channel.
+    #[inline]
+    pub fn synchronous<G: GeneratorId>(
+        self,
+        gen: G,
+        edge: EdgeSelection,
+        _clock: Id::Clock,
+    ) -> EventChannel<Id, G, Synchronous> {
+        EventChannel::new(self, gen, edge)
+    }
+
+    /// Route events from the generator `G` through the resynchronized path.
+    #[inline]
+    pub fn resynchronized<G: GeneratorId>(
+        self,
+        gen: G,
+        edge: EdgeSelection,
+        _clock: Id::Clock,
+    ) -> EventChannel<Id, G, Resynchronized> {
+        EventChannel::new(self, gen, edge)
+    }
+}
+
+//==============================================================================
+// EventChannel
+//==============================================================================
+
+/// Configured event channel, routing events from the generator `G` through the
+/// [`EventPath`] `P`
+///
+/// `N` is the number of [`User`]s currently connected to the channel.
+pub struct EventChannel<Id: ChId, G: GeneratorId, P: EventPath, N = U0> {
+    chan: Channel<Id>,
+    gen: G,
+    edge: EdgeSelection,
+    count: N,
+    _path: PhantomData<P>,
+}
+
+impl<Id, G, P> EventChannel<Id, G, P>
+where
+    Id: ChId,
+    G: GeneratorId,
+    P: EventPath,
+{
+    #[inline]
+    fn new(mut chan: Channel<Id>, gen: G, edge: EdgeSelection) -> Self {
+        chan.configure(G::ID, P::PATH, edge);
+        Self {
+            chan,
+            gen,
+            edge,
+            count: U0::default(),
+            _path: PhantomData,
+        }
+    }
+
+    /// Disable the channel and return the [`Channel`] and the generator.
+    ///
+    /// This method can only be called once all [`User`]s have been
+    /// [`disconnect`](Self::disconnect)ed.
+    #[inline]
+    pub fn free(mut self) -> (Channel<Id>, G) {
+        self.chan.reset();
+        (self.chan, self.gen)
+    }
+}
+
+impl<Id, G, P, N> EventChannel<Id, G, P, N>
+where
+    Id: ChId,
+    G: GeneratorId,
+    P: EventPath,
+{
+    /// Connect a [`User`] to this channel.
+    ///
+    /// The user will start receiving the channel's events. A [`Link`] is
+    /// returned, which must be given back to [`disconnect`](Self::disconnect)
+    /// in order to release the channel.
+    #[inline]
+    #[allow(clippy::type_complexity)]
+    pub fn connect<U: UserId>(
+        mut self,
+        user: User<U>,
+    ) -> (EventChannel<Id, G, P, N::Inc>, Link<Id, U>)
+    where
+        N: Increment,
+    {
+        self.chan.connect_user(U::ID);
+        let link = Link {
+            user,
+            _id: PhantomData,
+        };
+        (self.inc(), link)
+    }
+
+    /// Disconnect a [`User`] from this channel.
+    ///
+    /// The user will stop receiving the channel's events, and its [`User`]
+    /// token is returned.
+    #[inline]
+    #[allow(clippy::type_complexity)]
+    pub fn disconnect<U: UserId>(
+        mut self,
+        link: Link<Id, U>,
+    ) -> (EventChannel<Id, G, P, N::Dec>, User<U>)
+    where
+        N: Decrement,
+    {
+        self.chan.disconnect_user(U::ID);
+        (self.dec(), link.user)
+    }
+
+    /// Return the generator currently selected for this channel.
+    #[inline]
+    pub fn generator(&self) -> G {
+        self.gen
+    }
+
+    /// Return `true` if an event is being propagated through the channel.
+    #[inline]
+    pub fn is_busy(&self) -> bool {
+        self.chan.is_busy()
+    }
+
+    /// Return `true` if all the connected users are ready to handle incoming
+    /// events.
+    #[inline]
+    pub fn users_ready(&self) -> bool {
+        self.chan.users_ready()
+    }
+
+    /// Selectively enable interrupts
+    #[inline]
+    pub fn enable_interrupts(&mut self, flags: InterruptFlags) {
+        self.chan.enable_interrupts(flags);
+    }
+
+    /// Selectively disable interrupts
+    #[inline]
+    pub fn disable_interrupts(&mut self, flags: InterruptFlags) {
+        self.chan.disable_interrupts(flags);
+    }
+
+    /// Check the specified `flags`, clear then return any that were set
+    #[inline]
+    pub fn check_and_clear_interrupts(&mut self, flags: InterruptFlags) -> InterruptFlags {
+        self.chan.check_and_clear_interrupts(flags)
+    }
+
+    /// Keep the channel running in standby sleep mode.
+    #[hal_cfg("evsys-d5x")]
+    #[inline]
+    pub fn run_in_standby(&mut self, run_in_standby: bool) {
+        self.chan.set_run_in_standby(run_in_standby);
+    }
+
+    /// Only request the channel's generic clock when an event is being
+    /// propagated.
+    #[hal_cfg("evsys-d5x")]
+    #[inline]
+    pub fn on_demand(&mut self, on_demand: bool) {
+        self.chan.set_on_demand(on_demand);
+    }
+}
+
+impl<Id, G, P, N> EventChannel<Id, G, P, N>
+where
+    Id: ClockedChId,
+    G: GeneratorId,
+    P: ClockedPath,
+{
+    /// Issue a software event on this channel.
+    ///
+    /// The event is propagated to all connected users, as if it had been
+    /// emitted by the generator.
+    #[inline]
+    pub fn trigger(&mut self) {
+        self.chan.software_event(G::ID, P::PATH, self.edge);
+    }
+
+    /// Change the edge detection setting of the channel.
+    #[inline]
+    pub fn set_edge(&mut self, edge: EdgeSelection) {
+        self.edge = edge;
+        self.chan.configure(G::ID, P::PATH, edge);
+    }
+}
+
+impl<Id, G, P, N> PrivateIncrement for EventChannel<Id, G, P, N>
+where
+    Id: ChId,
+    G: GeneratorId,
+    P: EventPath,
+    N: Increment,
+{
+    type Inc = EventChannel<Id, G, P, N::Inc>;
+
+    #[inline]
+    fn inc(self) -> Self::Inc {
+        EventChannel {
+            chan: self.chan,
+            gen: self.gen,
+            edge: self.edge,
+            count: self.count.inc(),
+            _path: PhantomData,
+        }
+    }
+}
+
+impl<Id, G, P, N> PrivateDecrement for EventChannel<Id, G, P, N>
+where
+    Id: ChId,
+    G: GeneratorId,
+    P: EventPath,
+    N: Decrement,
+{
+    type Dec = EventChannel<Id, G, P, N::Dec>;
+
+    #[inline]
+    fn dec(self) -> Self::Dec {
+        EventChannel {
+            chan: self.chan,
+            gen: self.gen,
+            edge: self.edge,
+            count: self.count.dec(),
+            _path: PhantomData,
+        }
+    }
+}
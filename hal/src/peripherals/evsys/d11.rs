@@ -0,0 +1,468 @@
+//! EVSYS register access and event generator/user tables for SAMD11/SAMD21
+
+use super::{ChId, Channel, EdgeSelection, InterruptFlags, PathSelection};
+use crate::pac;
+
+#[inline]
+fn evsys() -> &'static pac::evsys::RegisterBlock {
+    // SAFETY: Each `Channel` only ever touches the register fields belonging
+    // to its own channel. The CHANNEL and USER registers are written in a
+    // single access which selects the target channel/user, so no
+    // read-modify-write sequence can be interleaved with another channel's.
+    unsafe { &*pac::Evsys::ptr() }
+}
+
+/// Bit position of a channel in the lower half of the CHSTATUS and INTFLAG
+/// registers (USRRDY, OVR)
+#[inline]
+const fn low_bit(id: usize) -> u32 {
+    if id < 8 {
+        1 << id
+    } else {
+        1 << (id + 8)
+    }
+}
+
+/// Bit position of a channel in the upper half of the CHSTATUS and INTFLAG
+/// registers (CHBUSY, EVD)
+#[inline]
+const fn high_bit(id: usize) -> u32 {
+    if id < 8 {
+        1 << (id + 8)
+    } else {
+        1 << (id + 16)
+    }
+}
+
+impl<Id: ChId> Channel<Id> {
+    #[inline]
+    pub(super) fn configure(&mut self, evgen: u8, path: PathSelection, edge: EdgeSelection) {
+        self.write_channel(evgen, path, edge, false);
+    }
+
+    #[inline]
+    pub(super) fn software_event(&mut self, evgen: u8, path: PathSelection, edge: EdgeSelection) {
+        // The SWEVT bit must be written along with the rest of the channel
+        // configuration, otherwise the channel would be reset.
+        self.write_channel(evgen, path, edge, true);
+    }
+
+    #[inline]
+    pub(super) fn reset(&mut self) {
+        // Selecting no generator disables the channel
+        self.write_channel(
+            0,
+            PathSelection::Synchronous,
+            EdgeSelection::NoEvtOutput,
+            false,
+        );
+    }
+
+    #[inline]
+    fn write_channel(&mut self, evgen: u8, path: PathSelection, edge: EdgeSelection, swevt: bool) {
+        evsys().channel().write(|w| unsafe {
+            w.channel().bits(Id::ID as u8);
+            w.evgen().bits(evgen);
+            w.path().variant(path);
+            w.edgsel().variant(edge);
+            w.swevt().bit(swevt)
+        });
+    }
+
+    #[inline]
+    pub(super) fn connect_user(&mut self, user: u8) {
+        // A value of 0 in USER.CHANNEL disconnects the user; channel n is
+        // selected by writing n + 1.
+        evsys().user().write(|w| unsafe {
+            w.user().bits(user);
+            w.channel().bits(Id::ID as u8 + 1)
+        });
+    }
+
+    #[inline]
+    pub(super) fn disconnect_user(&mut self, user: u8) {
+        evsys().user().write(|w| unsafe {
+            w.user().bits(user);
+            w.channel().bits(0)
+        });
+    }
+
+    #[inline]
+    pub(super) fn is_busy(&self) -> bool {
+        evsys().chstatus().read().bits() & high_bit(Id::ID) != 0
+    }
+
+    #[inline]
+    pub(super) fn users_ready(&self) -> bool {
+        evsys().chstatus().read().bits() & low_bit(Id::ID) != 0
+    }
+
+    #[inline]
+    fn flags_to_bits(flags: InterruptFlags) -> u32 {
+        let mut bits = 0;
+        if flags.ovr() {
+            bits |= low_bit(Id::ID);
+        }
+        if flags.evd() {
+            bits |= high_bit(Id::ID);
+        }
+        bits
+    }
+
+    #[inline]
+    pub(super) fn enable_interrupts(&mut self, flags: InterruptFlags) {
+        let bits = Self::flags_to_bits(flags);
+        // SAFETY: Only the bits belonging to this channel are written
+        evsys().intenset().write(|w| unsafe { w.bits(bits) });
+    }
+
+    #[inline]
+    pub(super) fn disable_interrupts(&mut self, flags: InterruptFlags) {
+        let bits = Self::flags_to_bits(flags);
+        // SAFETY: Only the bits belonging to this channel are written
+        evsys().intenclr().write(|w| unsafe { w.bits(bits) });
+    }
+
+    #[inline]
+    pub(super) fn check_and_clear_interrupts(&mut self, flags: InterruptFlags) -> InterruptFlags {
+        let set = evsys().intflag().read().bits() & Self::flags_to_bits(flags);
+        // SAFETY: Writing a 1 clears the flag, and only the bits belonging to
+        // this channel are written
+        evsys().intflag().write(|w| unsafe { w.bits(set) });
+
+        InterruptFlags::new()
+            .with_ovr(set & low_bit(Id::ID) != 0)
+            .with_evd(set & high_bit(Id::ID) != 0)
+    }
+}
+
+/// Event generators
+///
+/// Each generator is a unit struct which can be passed to the methods of
+/// [`Channel`](super::Channel) to route its events through a channel.
+pub mod generators {
+    use atsamd_hal_macros::hal_cfg;
+
+    generators! {
+        /// No generator. Only software events are propagated.
+        SoftwareOnly = 0x00,
+        /// RTC compare 0 (mode 0 and 1) or alarm 0 (mode 2)
+        RtcCmp0 = 0x01,
+        /// RTC compare 1
+        RtcCmp1 = 0x02,
+        /// RTC overflow
+        RtcOvf = 0x03,
+        /// RTC period 0
+        RtcPer0 = 0x04,
+        /// RTC period 1
+        RtcPer1 = 0x05,
+        /// RTC period 2
+        RtcPer2 = 0x06,
+        /// RTC period 3
+        RtcPer3 = 0x07,
+        /// RTC period 4
+        RtcPer4 = 0x08,
+        /// RTC period 5
+        RtcPer5 = 0x09,
+        /// RTC period 6
+        RtcPer6 = 0x0A,
+        /// RTC period 7
+        RtcPer7 = 0x0B,
+        /// EIC external interrupt 0
+        EicExtint0 = 0x0C,
+        /// EIC external interrupt 1
+        EicExtint1 = 0x0D,
+        /// EIC external interrupt 2
+        EicExtint2 = 0x0E,
+        /// EIC external interrupt 3
+        EicExtint3 = 0x0F,
+        /// EIC external interrupt 4
+        EicExtint4 = 0x10,
+        /// EIC external interrupt 5
+        EicExtint5 = 0x11,
+        /// EIC external interrupt 6
+        EicExtint6 = 0x12,
+        /// EIC external interrupt 7
+        EicExtint7 = 0x13,
+    }
+
+    #[hal_cfg("evsys-d11")]
+    generators! {
+        /// DMAC channel 0
+        DmacCh0 = 0x14,
+        /// DMAC channel 1
+        DmacCh1 = 0x15,
+        /// DMAC channel 2
+        DmacCh2 = 0x16,
+        /// DMAC channel 3
+        DmacCh3 = 0x17,
+        /// TCC0 overflow
+        Tcc0Ovf = 0x18,
+        /// TCC0 trig
+        Tcc0Trg = 0x19,
+        /// TCC0 counter
+        Tcc0Cnt = 0x1A,
+        /// TCC0 match/capture 0
+        Tcc0Mc0 = 0x1B,
+        /// TCC0 match/capture 1
+        Tcc0Mc1 = 0x1C,
+        /// TCC0 match/capture 2
+        Tcc0Mc2 = 0x1D,
+        /// TCC0 match/capture 3
+        Tcc0Mc3 = 0x1E,
+        /// TC1 overflow/underflow
+        Tc1Ovf = 0x1F,
+        /// TC1 match/capture 0
+        Tc1Mc0 = 0x20,
+        /// TC1 match/capture 1
+        Tc1Mc1 = 0x21,
+        /// TC2 overflow/underflow
+        Tc2Ovf = 0x22,
+        /// TC2 match/capture 0
+        Tc2Mc0 = 0x23,
+        /// TC2 match/capture 1
+        Tc2Mc1 = 0x24,
+        /// ADC result ready
+        AdcResrdy = 0x25,
+        /// ADC window monitor
+        AdcWinmon = 0x26,
+        /// AC comparator 0
+        AcComp0 = 0x27,
+        /// AC comparator 1
+        AcComp1 = 0x28,
+        /// AC window 0
+        AcWin0 = 0x29,
+        /// DAC data buffer empty
+        DacEmpty = 0x2A,
+        /// PTC end of conversion
+        PtcEoc = 0x2B,
+        /// PTC window comparator
+        PtcWcomp = 0x2C,
+    }
+
+    #[hal_cfg("evsys-d21")]
+    generators! {
+        /// EIC external interrupt 8
+        EicExtint8 = 0x14,
+        /// EIC external interrupt 9
+        EicExtint9 = 0x15,
+        /// EIC external interrupt 10
+        EicExtint10 = 0x16,
+        /// EIC external interrupt 11
+        EicExtint11 = 0x17,
+        /// EIC external interrupt 12
+        EicExtint12 = 0x18,
+        /// EIC external interrupt 13
+        EicExtint13 = 0x19,
+        /// EIC external interrupt 14
+        EicExtint14 = 0x1A,
+        /// EIC external interrupt 15
+        EicExtint15 = 0x1B,
+        /// DMAC channel 0
+        DmacCh0 = 0x1C,
+        /// DMAC channel 1
+        DmacCh1 = 0x1D,
+        /// DMAC channel 2
+        DmacCh2 = 0x1E,
+        /// DMAC channel 3
+        DmacCh3 = 0x1F,
+        /// TCC0 overflow
+        Tcc0Ovf = 0x20,
+        /// TCC0 trig
+        Tcc0Trg = 0x21,
+        /// TCC0 counter
+        Tcc0Cnt = 0x22,
+        /// TCC0 match/capture 0
+        Tcc0Mc0 = 0x23,
+        /// TCC0 match/capture 1
+        Tcc0Mc1 = 0x24,
+        /// TCC0 match/capture 2
+        Tcc0Mc2 = 0x25,
+        /// TCC0 match/capture 3
+        Tcc0Mc3 = 0x26,
+        /// TCC1 overflow
+        Tcc1Ovf = 0x27,
+        /// TCC1 trig
+        Tcc1Trg = 0x28,
+        /// TCC1 counter
+        Tcc1Cnt = 0x29,
+        /// TCC1 match/capture 0
+        Tcc1Mc0 = 0x2A,
+        /// TCC1 match/capture 1
+        Tcc1Mc1 = 0x2B,
+        /// TCC2 overflow
+        Tcc2Ovf = 0x2C,
+        /// TCC2 trig
+        Tcc2Trg = 0x2D,
+        /// TCC2 counter
+        Tcc2Cnt = 0x2E,
+        /// TCC2 match/capture 0
+        Tcc2Mc0 = 0x2F,
+        /// TCC2 match/capture 1
+        Tcc2Mc1 = 0x30,
+        /// TC3 overflow/underflow
+        Tc3Ovf = 0x31,
+        /// TC3 match/capture 0
+        Tc3Mc0 = 0x32,
+        /// TC3 match/capture 1
+        Tc3Mc1 = 0x33,
+        /// TC4 overflow/underflow
+        Tc4Ovf = 0x34,
+        /// TC4 match/capture 0
+        Tc4Mc0 = 0x35,
+        /// TC4 match/capture 1
+        Tc4Mc1 = 0x36,
+        /// TC5 overflow/underflow
+        Tc5Ovf = 0x37,
+        /// TC5 match/capture 0
+        Tc5Mc0 = 0x38,
+        /// TC5 match/capture 1
+        Tc5Mc1 = 0x39,
+        /// TC6 overflow/underflow
+        #[hal_cfg("tc6")]
+        Tc6Ovf = 0x3A,
+        /// TC6 match/capture 0
+        #[hal_cfg("tc6")]
+        Tc6Mc0 = 0x3B,
+        /// TC6 match/capture 1
+        #[hal_cfg("tc6")]
+        Tc6Mc1 = 0x3C,
+        /// TC7 overflow/underflow
+        #[hal_cfg("tc7")]
+        Tc7Ovf = 0x3D,
+        /// TC7 match/capture 0
+        #[hal_cfg("tc7")]
+        Tc7Mc0 = 0x3E,
+        /// TC7 match/capture 1
+        #[hal_cfg("tc7")]
+        Tc7Mc1 = 0x3F,
+        /// ADC result ready
+        AdcResrdy = 0x40,
+        /// ADC window monitor
+        AdcWinmon = 0x41,
+        /// AC comparator 0
+        AcComp0 = 0x42,
+        /// AC comparator 1
+        AcComp1 = 0x43,
+        /// AC window 0
+        AcWin0 = 0x44,
+        /// DAC data buffer empty
+        DacEmpty = 0x45,
+        /// PTC end of conversion
+        PtcEoc = 0x46,
+        /// PTC window comparator
+        PtcWcomp = 0x47,
+    }
+}
+
+/// Event users
+///
+/// Each user is represented by a [`User`](super::User) token, which can be
+/// obtained from the [`Users`] struct.
+pub mod users {
+    use atsamd_hal_macros::hal_cfg;
+
+    #[hal_cfg("evsys-d11")]
+    users! {
+        /// DMAC channel 0
+        DmacCh0 = 0x00,
+        /// DMAC channel 1
+        DmacCh1 = 0x01,
+        /// DMAC channel 2
+        DmacCh2 = 0x02,
+        /// DMAC channel 3
+        DmacCh3 = 0x03,
+        /// TCC0 event 0
+        Tcc0Ev0 = 0x04,
+        /// TCC0 event 1
+        Tcc0Ev1 = 0x05,
+        /// TCC0 match/capture 0
+        Tcc0Mc0 = 0x06,
+        /// TCC0 match/capture 1
+        Tcc0Mc1 = 0x07,
+        /// TCC0 match/capture 2
+        Tcc0Mc2 = 0x08,
+        /// TCC0 match/capture 3
+        Tcc0Mc3 = 0x09,
+        /// TC1 event
+        Tc1 = 0x0A,
+        /// TC2 event
+        Tc2 = 0x0B,
+        /// ADC start conversion
+        AdcStart = 0x0C,
+        /// ADC flush
+        AdcSync = 0x0D,
+        /// AC start comparator 0
+        AcComp0 = 0x0E,
+        /// AC start comparator 1
+        AcComp1 = 0x0F,
+        /// DAC start conversion
+        DacStart = 0x10,
+        /// PTC start conversion
+        PtcStconv = 0x11,
+    }
+
+    #[hal_cfg("evsys-d21")]
+    users! {
+        /// DMAC channel 0
+        DmacCh0 = 0x00,
+        /// DMAC channel 1
+        DmacCh1 = 0x01,
+        /// DMAC channel 2
+        DmacCh2 = 0x02,
+        /// DMAC channel 3
+        DmacCh3 = 0x03,
+        /// TCC0 event 0
+        Tcc0Ev0 = 0x04,
+        /// TCC0 event 1
+        Tcc0Ev1 = 0x05,
+        /// TCC0 match/capture 0
+        Tcc0Mc0 = 0x06,
+        /// TCC0 match/capture 1
+        Tcc0Mc1 = 0x07,
+        /// TCC0 match/capture 2
+        Tcc0Mc2 = 0x08,
+        /// TCC0 match/capture 3
+        Tcc0Mc3 = 0x09,
+        /// TCC1 event 0
+        Tcc1Ev0 = 0x0A,
+        /// TCC1 event 1
+        Tcc1Ev1 = 0x0B,
+        /// TCC1 match/capture 0
+        Tcc1Mc0 = 0x0C,
+        /// TCC1 match/capture 1
+        Tcc1Mc1 = 0x0D,
+        /// TCC2 event 0
+        Tcc2Ev0 = 0x0E,
+        /// TCC2 event 1
+        Tcc2Ev1 = 0x0F,
+        /// TCC2 match/capture 0
+        Tcc2Mc0 = 0x10,
+        /// TCC2 match/capture 1
+        Tcc2Mc1 = 0x11,
+        /// TC3 event
+        Tc3 = 0x12,
+        /// TC4 event
+        Tc4 = 0x13,
+        /// TC5 event
+        Tc5 = 0x14,
+        /// TC6 event
+        #[hal_cfg("tc6")]
+        Tc6 = 0x15,
+        /// TC7 event
+        #[hal_cfg("tc7")]
+        Tc7 = 0x16,
+        /// ADC start conversion
+        AdcStart = 0x17,
+        /// ADC flush
+        AdcSync = 0x18,
+        /// AC start comparator 0
+        AcComp0 = 0x19,
+        /// AC start comparator 1
+        AcComp1 = 0x1A,
+        /// DAC start conversion
+        DacStart = 0x1B,
+        /// PTC start conversion
+        PtcStconv = 0x1C,
+    }
+}
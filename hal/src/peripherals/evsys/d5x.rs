@@ -0,0 +1,543 @@
+//! EVSYS register access and event generator/user tables for SAMx5x
+
+use super::{ChId, Channel, EdgeSelection, InterruptFlags, PathSelection};
+use crate::pac;
+
+#[inline]
+fn evsys() -> &'static pac::evsys::RegisterBlock {
+    // SAFETY: Each `Channel` only ever touches its own CHANNELS registers, its
+    // own bit of SWEVT, and the USER registers of the users it owns.
+    unsafe { &*pac::Evsys::ptr() }
+}
+
+impl<Id: ChId> Channel<Id> {
+    #[inline]
+    fn regs(&self) -> &'static pac::evsys::Channels {
+        evsys().channels(Id::ID)
+    }
+
+    #[inline]
+    pub(super) fn configure(&mut self, evgen: u8, path: PathSelection, edge: EdgeSelection) {
+        // RUNSTDBY and ONDEMAND are left untouched
+        self.regs().channel().modify(|_, w| unsafe {
+            w.evgen().bits(evgen);
+            w.path().variant(path);
+            w.edgsel().variant(edge)
+        });
+    }
+
+    #[inline]
+    pub(super) fn software_event(
+        &mut self,
+        _evgen: u8,
+        _path: PathSelection,
+        _edge: EdgeSelection,
+    ) {
+        // SAFETY: Only the bit belonging to this channel is written
+        evsys().swevt().write(|w| unsafe { w.bits(1 << Id::ID) });
+    }
+
+    #[inline]
+    pub(super) fn reset(&mut self) {
+        // Selecting no generator disables the channel
+        self.regs().channel().reset();
+    }
+
+    #[inline]
+    pub(super) fn connect_user(&mut self, user: u8) {
+        // A value of 0 in USER.CHANNEL disconnects the user; channel n is
+        // selected by writing n + 1.
+        evsys()
+            .user(user as usize)
+            .write(|w| unsafe { w.channel().bits(Id::ID as u8 + 1) });
+    }
+
+    #[inline]
+    pub(super) fn disconnect_user(&mut self, user: u8) {
+        evsys()
+            .user(user as usize)
+            .write(|w| unsafe { w.channel().bits(0) });
+    }
+
+    #[inline]
+    pub(super) fn is_busy(&self) -> bool {
+        self.regs().chstatus().read().busych().bit_is_set()
+    }
+
+    #[inline]
+    pub(super) fn users_ready(&self) -> bool {
+        self.regs().chstatus().read().rdyusr().bit_is_set()
+    }
+
+    #[inline]
+    pub(super) fn enable_interrupts(&mut self, flags: InterruptFlags) {
+        self.regs()
+            .chintenset()
+            .write(|w| unsafe { w.bits(flags.into()) });
+    }
+
+    #[inline]
+    pub(super) fn disable_interrupts(&mut self, flags: InterruptFlags) {
+        self.regs()
+            .chintenclr()
+            .write(|w| unsafe { w.bits(flags.into()) });
+    }
+
+    #[inline]
+    pub(super) fn check_and_clear_interrupts(&mut self, flags: InterruptFlags) -> InterruptFlags {
+        let cleared = self.regs().chintflag().read().bits() & u8::from(flags);
+        // SAFETY: Writing a 1 clears the flag
+        self.regs()
+            .chintflag()
+            .write(|w| unsafe { w.bits(cleared) });
+        InterruptFlags::from(cleared)
+    }
+
+    #[inline]
+    pub(super) fn set_run_in_standby(&mut self, run_in_standby: bool) {
+        self.regs()
+            .channel()
+            .modify(|_, w| w.runstdby().bit(run_in_standby));
+    }
+
+    #[inline]
+    pub(super) fn set_on_demand(&mut self, on_demand: bool) {
+        self.regs()
+            .channel()
+            .modify(|_, w| w.ondemand().bit(on_demand));
+    }
+}
+
+/// Event generators
+///
+/// Each generator is a unit struct which can be passed to the methods of
+/// [`Channel`](super::Channel) to route its events through a channel.
+pub mod generators {
+    use atsamd_hal_macros::hal_cfg;
+
+    generators! {
+        /// No generator. Only software events are propagated.
+        SoftwareOnly = 0x00,
+        /// XOSC0 clock failure
+        XoscFail0 = 0x01,
+        /// XOSC1 clock failure
+        XoscFail1 = 0x02,
+        /// XOSC32K clock failure
+        Xosc32kFail = 0x03,
+        /// RTC period 0
+        RtcPer0 = 0x04,
+        /// RTC period 1
+        RtcPer1 = 0x05,
+        /// RTC period 2
+        RtcPer2 = 0x06,
+        /// RTC period 3
+        RtcPer3 = 0x07,
+        /// RTC period 4
+        RtcPer4 = 0x08,
+        /// RTC period 5
+        RtcPer5 = 0x09,
+        /// RTC period 6
+        RtcPer6 = 0x0A,
+        /// RTC period 7
+        RtcPer7 = 0x0B,
+        /// RTC compare 0
+        RtcCmp0 = 0x0C,
+        /// RTC compare 1
+        RtcCmp1 = 0x0D,
+        /// RTC compare 2
+        RtcCmp2 = 0x0E,
+        /// RTC compare 3
+        RtcCmp3 = 0x0F,
+        /// RTC tamper detection
+        RtcTamper = 0x10,
+        /// RTC overflow
+        RtcOvf = 0x11,
+        /// EIC external interrupt 0
+        EicExtint0 = 0x12,
+        /// EIC external interrupt 1
+        EicExtint1 = 0x13,
+        /// EIC external interrupt 2
+        EicExtint2 = 0x14,
+        /// EIC external interrupt 3
+        EicExtint3 = 0x15,
+        /// EIC external interrupt 4
+        EicExtint4 = 0x16,
+        /// EIC external interrupt 5
+        EicExtint5 = 0x17,
+        /// EIC external interrupt 6
+        EicExtint6 = 0x18,
+        /// EIC external interrupt 7
+        EicExtint7 = 0x19,
+        /// EIC external interrupt 8
+        EicExtint8 = 0x1A,
+        /// EIC external interrupt 9
+        EicExtint9 = 0x1B,
+        /// EIC external interrupt 10
+        EicExtint10 = 0x1C,
+        /// EIC external interrupt 11
+        EicExtint11 = 0x1D,
+        /// EIC external interrupt 12
+        EicExtint12 = 0x1E,
+        /// EIC external interrupt 13
+        EicExtint13 = 0x1F,
+        /// EIC external interrupt 14
+        EicExtint14 = 0x20,
+        /// EIC external interrupt 15
+        EicExtint15 = 0x21,
+        /// DMAC channel 0
+        DmacCh0 = 0x22,
+        /// DMAC channel 1
+        DmacCh1 = 0x23,
+        /// DMAC channel 2
+        DmacCh2 = 0x24,
+        /// DMAC channel 3
+        DmacCh3 = 0x25,
+        /// PAC access error
+        PacAccerr = 0x26,
+        /// TCC0 overflow
+        Tcc0Ovf = 0x29,
+        /// TCC0 trig
+        Tcc0Trg = 0x2A,
+        /// TCC0 counter
+        Tcc0Cnt = 0x2B,
+        /// TCC0 match/capture 0
+        Tcc0Mc0 = 0x2C,
+        /// TCC0 match/capture 1
+        Tcc0Mc1 = 0x2D,
+        /// TCC0 match/capture 2
+        Tcc0Mc2 = 0x2E,
+        /// TCC0 match/capture 3
+        Tcc0Mc3 = 0x2F,
+        /// TCC0 match/capture 4
+        Tcc0Mc4 = 0x30,
+        /// TCC0 match/capture 5
+        Tcc0Mc5 = 0x31,
+        /// TCC1 overflow
+        Tcc1Ovf = 0x32,
+        /// TCC1 trig
+        Tcc1Trg = 0x33,
+        /// TCC1 counter
+        Tcc1Cnt = 0x34,
+        /// TCC1 match/capture 0
+        Tcc1Mc0 = 0x35,
+        /// TCC1 match/capture 1
+        Tcc1Mc1 = 0x36,
+        /// TCC1 match/capture 2
+        Tcc1Mc2 = 0x37,
+        /// TCC1 match/capture 3
+        Tcc1Mc3 = 0x38,
+        /// TCC2 overflow
+        Tcc2Ovf = 0x39,
+        /// TCC2 trig
+        Tcc2Trg = 0x3A,
+        /// TCC2 counter
+        Tcc2Cnt = 0x3B,
+        /// TCC2 match/capture 0
+        Tcc2Mc0 = 0x3C,
+        /// TCC2 match/capture 1
+        Tcc2Mc1 = 0x3D,
+        /// TCC2 match/capture 2
+        Tcc2Mc2 = 0x3E,
+        /// TCC3 overflow
+        #[hal_cfg("tcc3")]
+        Tcc3Ovf = 0x3F,
+        /// TCC3 trig
+        #[hal_cfg("tcc3")]
+        Tcc3Trg = 0x40,
+        /// TCC3 counter
+        #[hal_cfg("tcc3")]
+        Tcc3Cnt = 0x41,
+        /// TCC3 match/capture 0
+        #[hal_cfg("tcc3")]
+        Tcc3Mc0 = 0x42,
+        /// TCC3 match/capture 1
+        #[hal_cfg("tcc3")]
+        Tcc3Mc1 = 0x43,
+        /// TCC4 overflow
+        #[hal_cfg("tcc4")]
+        Tcc4Ovf = 0x44,
+        /// TCC4 trig
+        #[hal_cfg("tcc4")]
+        Tcc4Trg = 0x45,
+        /// TCC4 counter
+        #[hal_cfg("tcc4")]
+        Tcc4Cnt = 0x46,
+        /// TCC4 match/capture 0
+        #[hal_cfg("tcc4")]
+        Tcc4Mc0 = 0x47,
+        /// TCC4 match/capture 1
+        #[hal_cfg("tcc4")]
+        Tcc4Mc1 = 0x48,
+        /// TC0 overflow/underflow
+        Tc0Ovf = 0x49,
+        /// TC0 match/capture 0
+        Tc0Mc0 = 0x4A,
+        /// TC0 match/capture 1
+        Tc0Mc1 = 0x4B,
+        /// TC1 overflow/underflow
+        Tc1Ovf = 0x4C,
+        /// TC1 match/capture 0
+        Tc1Mc0 = 0x4D,
+        /// TC1 match/capture 1
+        Tc1Mc1 = 0x4E,
+        /// TC2 overflow/underflow
+        Tc2Ovf = 0x4F,
+        /// TC2 match/capture 0
+        Tc2Mc0 = 0x50,
+        /// TC2 match/capture 1
+        Tc2Mc1 = 0x51,
+        /// TC3 overflow/underflow
+        Tc3Ovf = 0x52,
+        /// TC3 match/capture 0
+        Tc3Mc0 = 0x53,
+        /// TC3 match/capture 1
+        Tc3Mc1 = 0x54,
+        /// TC4 overflow/underflow
+        #[hal_cfg("tc4")]
+        Tc4Ovf = 0x55,
+        /// TC4 match/capture 0
+        #[hal_cfg("tc4")]
+        Tc4Mc0 = 0x56,
+        /// TC4 match/capture 1
+        #[hal_cfg("tc4")]
+        Tc4Mc1 = 0x57,
+        /// TC5 overflow/underflow
+        #[hal_cfg("tc5")]
+        Tc5Ovf = 0x58,
+        /// TC5 match/capture 0
+        #[hal_cfg("tc5")]
+        Tc5Mc0 = 0x59,
+        /// TC5 match/capture 1
+        #[hal_cfg("tc5")]
+        Tc5Mc1 = 0x5A,
+        /// TC6 overflow/underflow
+        #[hal_cfg("tc6")]
+        Tc6Ovf = 0x5B,
+        /// TC6 match/capture 0
+        #[hal_cfg("tc6")]
+        Tc6Mc0 = 0x5C,
+        /// TC6 match/capture 1
+        #[hal_cfg("tc6")]
+        Tc6Mc1 = 0x5D,
+        /// TC7 overflow/underflow
+        #[hal_cfg("tc7")]
+        Tc7Ovf = 0x5E,
+        /// TC7 match/capture 0
+        #[hal_cfg("tc7")]
+        Tc7Mc0 = 0x5F,
+        /// TC7 match/capture 1
+        #[hal_cfg("tc7")]
+        Tc7Mc1 = 0x60,
+        /// PDEC overflow
+        #[hal_cfg("pdec")]
+        PdecOvf = 0x61,
+        /// PDEC error
+        #[hal_cfg("pdec")]
+        PdecErr = 0x62,
+        /// PDEC direction change
+        #[hal_cfg("pdec")]
+        PdecDir = 0x63,
+        /// PDEC velocity
+        #[hal_cfg("pdec")]
+        PdecVlc = 0x64,
+        /// PDEC match/capture 0
+        #[hal_cfg("pdec")]
+        PdecMc0 = 0x65,
+        /// PDEC match/capture 1
+        #[hal_cfg("pdec")]
+        PdecMc1 = 0x66,
+        /// ADC0 result ready
+        Adc0Resrdy = 0x67,
+        /// ADC0 window monitor
+        Adc0Winmon = 0x68,
+        /// ADC1 result ready
+        Adc1Resrdy = 0x69,
+        /// ADC1 window monitor
+        Adc1Winmon = 0x6A,
+        /// AC comparator 0
+        AcComp0 = 0x6B,
+        /// AC comparator 1
+        AcComp1 = 0x6C,
+        /// AC window 0
+        AcWin0 = 0x6D,
+        /// DAC0 data buffer empty
+        DacEmpty0 = 0x6E,
+        /// DAC1 data buffer empty
+        DacEmpty1 = 0x6F,
+        /// DAC0 result ready
+        DacResrdy0 = 0x70,
+        /// DAC1 result ready
+        DacResrdy1 = 0x71,
+        /// GMAC timestamp comparison
+        #[hal_cfg("gmac")]
+        GmacTsuCmp = 0x72,
+        /// TRNG data ready
+        TrngReady = 0x73,
+        /// CCL LUT output 0
+        CclLutout0 = 0x74,
+        /// CCL LUT output 1
+        CclLutout1 = 0x75,
+        /// CCL LUT output 2
+        CclLutout2 = 0x76,
+        /// CCL LUT output 3
+        CclLutout3 = 0x77,
+    }
+}
+
+/// Event users
+///
+/// Each user is represented by a [`User`](super::User) token, which can be
+/// obtained from the [`Users`] struct.
+pub mod users {
+    use atsamd_hal_macros::hal_cfg;
+
+    users! {
+        /// RTC tamper event
+        RtcTamper = 0x00,
+        /// PORT event 0
+        PortEv0 = 0x01,
+        /// PORT event 1
+        PortEv1 = 0x02,
+        /// PORT event 2
+        PortEv2 = 0x03,
+        /// PORT event 3
+        PortEv3 = 0x04,
+        /// DMAC channel 0
+        DmacCh0 = 0x05,
+        /// DMAC channel 1
+        DmacCh1 = 0x06,
+        /// DMAC channel 2
+        DmacCh2 = 0x07,
+        /// DMAC channel 3
+        DmacCh3 = 0x08,
+        /// DMAC channel 4
+        DmacCh4 = 0x09,
+        /// DMAC channel 5
+        DmacCh5 = 0x0A,
+        /// DMAC channel 6
+        DmacCh6 = 0x0B,
+        /// DMAC channel 7
+        DmacCh7 = 0x0C,
+        /// CM4 trace start
+        Cm4TraceStart = 0x0D,
+        /// CM4 trace stop
+        Cm4TraceStop = 0x0E,
+        /// CM4 trace trigger
+        Cm4TraceTrig = 0x0F,
+        /// TCC0 event 0
+        Tcc0Ev0 = 0x10,
+        /// TCC0 event 1
+        Tcc0Ev1 = 0x11,
+        /// TCC0 match/capture 0
+        Tcc0Mc0 = 0x12,
+        /// TCC0 match/capture 1
+        Tcc0Mc1 = 0x13,
+        /// TCC0 match/capture 2
+        Tcc0Mc2 = 0x14,
+        /// TCC0 match/capture 3
+        Tcc0Mc3 = 0x15,
+        /// TCC0 match/capture 4
+        Tcc0Mc4 = 0x16,
+        /// TCC0 match/capture 5
+        Tcc0Mc5 = 0x17,
+        /// TCC1 event 0
+        Tcc1Ev0 = 0x18,
+        /// TCC1 event 1
+        Tcc1Ev1 = 0x19,
+        /// TCC1 match/capture 0
+        Tcc1Mc0 = 0x1A,
+        /// TCC1 match/capture 1
+        Tcc1Mc1 = 0x1B,
+        /// TCC1 match/capture 2
+        Tcc1Mc2 = 0x1C,
+        /// TCC1 match/capture 3
+        Tcc1Mc3 = 0x1D,
+        /// TCC2 event 0
+        Tcc2Ev0 = 0x1E,
+        /// TCC2 event 1
+        Tcc2Ev1 = 0x1F,
+        /// TCC2 match/capture 0
+        Tcc2Mc0 = 0x20,
+        /// TCC2 match/capture 1
+        Tcc2Mc1 = 0x21,
+        /// TCC2 match/capture 2
+        Tcc2Mc2 = 0x22,
+        /// TCC3 event 0
+        #[hal_cfg("tcc3")]
+        Tcc3Ev0 = 0x23,
+        /// TCC3 event 1
+        #[hal_cfg("tcc3")]
+        Tcc3Ev1 = 0x24,
+        /// TCC3 match/capture 0
+        #[hal_cfg("tcc3")]
+        Tcc3Mc0 = 0x25,
+        /// TCC3 match/capture 1
+        #[hal_cfg("tcc3")]
+        Tcc3Mc1 = 0x26,
+        /// TCC4 event 0
+        #[hal_cfg("tcc4")]
+        Tcc4Ev0 = 0x27,
+        /// TCC4 event 1
+        #[hal_cfg("tcc4")]
+        Tcc4Ev1 = 0x28,
+        /// TCC4 match/capture 0
+        #[hal_cfg("tcc4")]
+        Tcc4Mc0 = 0x29,
+        /// TCC4 match/capture 1
+        #[hal_cfg("tcc4")]
+        Tcc4Mc1 = 0x2A,
+        /// TC0 event
+        Tc0 = 0x2B,
+        /// TC1 event
+        Tc1 = 0x2C,
+        /// TC2 event
+        Tc2 = 0x2D,
+        /// TC3 event
+        Tc3 = 0x2E,
+        /// TC4 event
+        #[hal_cfg("tc4")]
+        Tc4 = 0x2F,
+        /// TC5 event
+        #[hal_cfg("tc5")]
+        Tc5 = 0x30,
+        /// TC6 event
+        #[hal_cfg("tc6")]
+        Tc6 = 0x31,
+        /// TC7 event
+        #[hal_cfg("tc7")]
+        Tc7 = 0x32,
+        /// PDEC event 0
+        #[hal_cfg("pdec")]
+        PdecEvu0 = 0x33,
+        /// PDEC event 1
+        #[hal_cfg("pdec")]
+        PdecEvu1 = 0x34,
+        /// PDEC event 2
+        #[hal_cfg("pdec")]
+        PdecEvu2 = 0x35,
+        /// ADC0 start conversion
+        Adc0Start = 0x36,
+        /// ADC0 flush
+        Adc0Sync = 0x37,
+        /// ADC1 start conversion
+        Adc1Start = 0x38,
+        /// ADC1 flush
+        Adc1Sync = 0x39,
+        /// AC start comparator 0
+        AcSoc0 = 0x3A,
+        /// AC start comparator 1
+        AcSoc1 = 0x3B,
+        /// DAC0 start conversion
+        DacStart0 = 0x3C,
+        /// DAC1 start conversion
+        DacStart1 = 0x3D,
+        /// CCL LUT input 0
+        CclLutin0 = 0x3E,
+        /// CCL LUT input 1
+        CclLutin1 = 0x3F,
+        /// CCL LUT input 2
+        CclLutin2 = 0x40,
+        /// CCL LUT input 3
+        CclLutin3 = 0x41,
+    }
+}
@@ -0,0 +1,86 @@
+//! `smoltcp` device
+
+use smoltcp::phy::{self, Device, DeviceCapabilities, Medium};
+use smoltcp::time::Instant;
+
+use crate::pac;
+
+use super::ring::{RxRing, TxRing};
+use super::{start_transmission, Gmac, InterfacePins, MTU};
+
+/// Token of a received frame
+pub struct RxToken<'a> {
+    rx: &'a mut RxRing<'static>,
+}
+
+impl phy::RxToken for RxToken<'_> {
+    fn consume<R, F>(self, f: F) -> R
+    where
+        F: FnOnce(&mut [u8]) -> R,
+    {
+        // The token is only created when a frame is waiting, and the DMA
+        // cannot take it back
+        self.rx.receive(f).unwrap()
+    }
+}
+
+/// Token of a frame to transmit
+pub struct TxToken<'a> {
+    tx: &'a mut TxRing<'static>,
+    gmac: &'a pac::Gmac,
+}
+
+impl phy::TxToken for TxToken<'_> {
+    fn consume<R, F>(self, len: usize, f: F) -> R
+    where
+        F: FnOnce(&mut [u8]) -> R,
+    {
+        // The token is only created when a descriptor is free, and the DMA
+        // cannot take it
+        let result = self.tx.transmit(len, f).unwrap();
+        start_transmission(self.gmac);
+        result
+    }
+}
+
+impl<P: InterfacePins> Device for Gmac<P> {
+    type RxToken<'a>
+        = RxToken<'a>
+    where
+        Self: 'a;
+    type TxToken<'a>
+        = TxToken<'a>
+    where
+        Self: 'a;
+
+    fn receive(&mut self, _timestamp: Instant) -> Option<(Self::RxToken<'_>, Self::TxToken<'_>)> {
+        if self.rx.poll() && self.tx.ready() {
+            let rx = RxToken { rx: &mut self.rx };
+            let tx = TxToken {
+                tx: &mut self.tx,
+                gmac: &self.gmac,
+            };
+            Some((rx, tx))
+        } else {
+            None
+        }
+    }
+
+    fn transmit(&mut self, _timestamp: Instant) -> Option<Self::TxToken<'_>> {
+        if self.tx.ready() {
+            Some(TxToken {
+                tx: &mut self.tx,
+                gmac: &self.gmac,
+            })
+        } else {
+            None
+        }
+    }
+
+    fn capabilities(&self) -> DeviceCapabilities {
+        let mut capabilities = DeviceCapabilities::default();
+        capabilities.medium = Medium::Ethernet;
+        capabilities.max_transmission_unit = MTU;
+        capabilities
+    }
+}
@@ -0,0 +1,721 @@
+//! # Ethernet MAC
+//!
+//! The GMAC of SAME53 and SAME54 chips transfers Ethernet frames between an
+//! external PHY, connected through RMII or MII, and rings of DMA descriptors
+//! in SRAM. Each descriptor of the rings points to a buffer large enough for
+//! a whole frame.
+//!
+//! ## Creating a [`Gmac`]
+//!
+//! The pins of the PHY interface are grouped in a [`RmiiPins`] or a
+//! [`MiiPins`] struct, including the `MDC` and `MDIO` pins of the management
+//! interface. The descriptor rings live in user-provided [`RxStorage`] and
+//! [`TxStorage`], which must be `'static` since the GMAC DMA keeps accessing
+//! them.
+//!
+//! ```no_run
+//! use atsamd_hal::gmac::{Gmac, RmiiPins, RxStorage, TxStorage};
+//!
+//! static mut RX_STORAGE: RxStorage<8> = RxStorage::new();
+//! static mut TX_STORAGE: TxStorage<4> = TxStorage::new();
+//!
+//! let rmii_pins = RmiiPins {
+//!     refck: pins.pa14.into_alternate(),
+//!     txen: pins.pa17.into_alternate(),
+//!     tx0: pins.pa18.into_alternate(),
+//!     tx1: pins.pa19.into_alternate(),
+//!     crsdv: pins.pc20.into_alternate(),
+//!     rx0: pins.pa13.into_alternate(),
+//!     rx1: pins.pa12.into_alternate(),
+//!     rxer: pins.pa15.into_alternate(),
+//!     mdc: pins.pc11.into_alternate(),
+//!     mdio: pins.pc12.into_alternate(),
+//! };
+//!
+//! let mut gmac = Gmac::new(
+//!     &mut peripherals.mclk,
+//!     peripherals.gmac,
+//!     120.MHz(),
+//!     rmii_pins,
+//!     unsafe { &mut *core::ptr::addr_of_mut!(RX_STORAGE) },
+//!     unsafe { &mut *core::ptr::addr_of_mut!(TX_STORAGE) },
+//!     [0x02, 0x00, 0x00, 0x12, 0x34, 0x56],
+//! );
+//! ```
+//!
+//! ## PHY management
+//!
+//! The registers of the PHY are accessed through [`Gmac::phy_read`] and
+//! [`Gmac::phy_write`], with the standard registers listed in the [`phy`]
+//! module. The GMAC does not follow the link state by itself: the speed and
+//! duplex mode negotiated by the PHY must be applied with
+//! [`Gmac::set_link`], for instance from a periodic task.
+//!
+//! ```no_run
+//! const PHY_ADDRESS: u8 = 0;
+//!
+//! if let Some(link) = gmac.phy_link(PHY_ADDRESS) {
+//!     gmac.set_link(link);
+//! }
+//! ```
+//!
+//! ## Address filtering
+//!
+//! Frames are received when their destination is the MAC address given to
+//! [`Gmac::new`], the broadcast address, one of the three additional
+//! addresses set with [`Gmac::set_address_filter`], or a multicast address
+//! added to the hash filter with [`Gmac::add_multicast`]. The filtering can
+//! be turned off with [`Gmac::set_promiscuous`].
+//!
+//! ## Frames
+//!
+//! Received frames are handed to a closure by [`Gmac::receive`], and frames
+//! to send are filled in place by a closure of [`Gmac::transmit`]. Both
+//! return `None` when the corresponding ring is empty or full. Frames carry
+//! neither the preamble nor the FCS, which the GMAC handles.
+//!
+//! ## `smoltcp` <span class="stab portability" title="Available on crate feature `smoltcp` only"><code>smoltcp</code></span>
+//!
+//! With the `smoltcp` feature, [`Gmac`] implements the
+//! `smoltcp::phy::Device` trait, and can be used directly as the device of a
+//! `smoltcp::iface::Interface`.
+
+use core::ops::Deref;
+
+use atsamd_hal_macros::hal_cfg;
+use bitflags::bitflags;
+
+use crate::gpio::{AlternateL, Pin};
+use crate::gpio::{PA12, PA13, PA14, PA15, PA16, PA17, PA18, PA19, PA20, PA21, PB14, PB15};
+use crate::pac::gmac::ncfgr::Clkselect;
+use crate::pac::gmac::RegisterBlock;
+use crate::pac::{self, Mclk};
+use crate::time::Hertz;
+
+#[hal_cfg("pc20")]
+use crate::gpio::{PC11, PC12, PC14, PC16, PC17, PC18, PC19, PC20, PC21};
+
+pub mod phy;
+mod ring;
+
+#[cfg(feature = "smoltcp")]
+mod device;
+
+pub use phy::{Duplex, Link, Speed};
+pub use ring::{RxStorage, TxStorage, BUFFER_SIZE, MTU};
+
+#[cfg(feature = "smoltcp")]
+pub use device::{RxToken, TxToken};
+
+use ring::{RxRing, TxRing};
+
+/// Maximum frequency of `MDC`
+const MDC_MAX_FREQ: u32 = 2_500_000;
+
+/// Number of 64-byte blocks in a receive buffer, for `DCFGR.DRBS`
+const RX_BUFFER_BLOCKS: u8 = (BUFFER_SIZE / 64) as u8;
+
+/// `MAN` bits: clause 22 frame
+const MAN_CLTTO: u32 = 1 << 30;
+/// `MAN` bits: write operation
+const MAN_OP_WRITE: u32 = 0b01 << 28;
+/// `MAN` bits: read operation
+const MAN_OP_READ: u32 = 0b10 << 28;
+/// `MAN` bits: mandatory write ten
+const MAN_WTN: u32 = 0b10 << 16;
+
+/// Number of address filters, including the MAC address
+pub const NUM_ADDRESS_FILTERS: usize = 4;
+
+//==============================================================================
+// Pins
+//==============================================================================
+
+/// Pin which can be used as the `REFCK` of RMII, or the `TXCK` of MII
+pub trait RefCkPin {}
+
+/// Pin which can be used as `TXEN`
+pub trait TxEnPin {}
+
+/// Pin which can be used as `TX0`
+pub trait Tx0Pin {}
+
+/// Pin which can be used as `TX1`
+pub trait Tx1Pin {}
+
+/// Pin which can be used as `TX2`, with MII
+pub trait Tx2Pin {}
+
+/// Pin which can be used as `TX3`, with MII
+pub trait Tx3Pin {}
+
+/// Pin which can be used as the `CRS_DV` of RMII, or the `RXDV` of MII
+pub trait RxDvPin {}
+
+/// Pin which can be used as `RX0`
+pub trait Rx0Pin {}
+
+/// Pin which can be used as `RX1`
+pub trait Rx1Pin {}
+
+/// Pin which can be used as `RX2`, with MII
+pub trait Rx2Pin {}
+
+/// Pin which can be used as `RX3`, with MII
+pub trait Rx3Pin {}
+
+/// Pin which can be used as `RXER`
+pub trait RxErPin {}
+
+/// Pin which can be used as `RXCK`, with MII
+pub trait RxCkPin {}
+
+/// Pin which can be used as `CRS`, with MII
+pub trait CrsPin {}
+
+/// Pin which can be used as `COL`, with MII
+pub trait ColPin {}
+
+/// Pin which can be used as the `MDC` of the management interface
+pub trait MdcPin {}
+
+/// Pin which can be used as the `MDIO` of the management interface
+pub trait MdioPin {}
+
+macro_rules! gmac_pins {
+    ($( $Id:ident: $Trait:ident ),+ $(,)?) => {
+        $(
+            impl $Trait for Pin<$Id, AlternateL> {}
+        )+
+    };
+}
+
+gmac_pins!(
+    PA12: Rx1Pin,
+    PA13: Rx0Pin,
+    PA14: RefCkPin,
+    PA15: RxErPin,
+    PA16: CrsPin,
+    PA17: TxEnPin,
+    PA18: Tx0Pin,
+    PA19: Tx1Pin,
+    PA20: MdcPin,
+    PA21: MdioPin,
+    PB14: MdcPin,
+    PB15: MdioPin,
+);
+
+#[hal_cfg("pc20")]
+gmac_pins!(
+    PC11: MdcPin,
+    PC12: MdioPin,
+    PC14: RxCkPin,
+    PC16: Tx2Pin,
+    PC17: Tx3Pin,
+    PC18: Rx2Pin,
+    PC19: Rx3Pin,
+    PC20: RxDvPin,
+    PC21: ColPin,
+);
+
+/// Pins of a PHY connected through RMII
+pub struct RmiiPins<REFCK, TXEN, TX0, TX1, CRSDV, RX0, RX1, RXER, MDC, MDIO> {
+    pub refck: REFCK,
+    pub txen: TXEN,
+    pub tx0: TX0,
+    pub tx1: TX1,
+    pub crsdv: CRSDV,
+    pub rx0: RX0,
+    pub rx1: RX1,
+    pub rxer: RXER,
+    pub mdc: MDC,
+    pub mdio: MDIO,
+}
+
+/// Pins of a PHY connected through MII
+pub struct MiiPins<
+    TXCK,
+    TXEN,
+    TX0,
+    TX1,
+    TX2,
+    TX3,
+    RXCK,
+    RXDV,
+    RX0,
+    RX1,
+    RX2,
+    RX3,
+    RXER,
+    CRS,
+    COL,
+    MDC,
+    MDIO,
+> {
+    pub txck: TXCK,
+    pub txen: TXEN,
+    pub tx0: TX0,
+    pub tx1: TX1,
+    pub tx2: TX2,
+    pub tx3: TX3,
+    pub rxck: RXCK,
+    pub rxdv: RXDV,
+    pub rx0: RX0,
+    pub rx1: RX1,
+    pub rx2: RX2,
+    pub rx3: RX3,
+    pub rxer: RXER,
+    pub crs: CRS,
+    pub col: COL,
+    pub mdc: MDC,
+    pub mdio: MDIO,
+}
+
+/// Set of pins of the PHY interface
+///
+/// Implemented for the [`RmiiPins`] and [`MiiPins`] whose fields are all
+/// valid GMAC pins.
+pub trait InterfacePins {
+    /// Whether the PHY is connected through MII rather than RMII
+    const MII: bool;
+}
+
+impl<REFCK, TXEN, TX0, TX1, CRSDV, RX0, RX1, RXER, MDC, MDIO> InterfacePins
+    for RmiiPins<REFCK, TXEN, TX0, TX1, CRSDV, RX0, RX1, RXER, MDC, MDIO>
+where
+    REFCK: RefCkPin,
+    TXEN: TxEnPin,
+    TX0: Tx0Pin,
+    TX1: Tx1Pin,
+    CRSDV: RxDvPin,
+    RX0: Rx0Pin,
+    RX1: Rx1Pin,
+    RXER: RxErPin,
+    MDC: MdcPin,
+    MDIO: MdioPin,
+{
+    const MII: bool = false;
+}
+
+impl<TXCK, TXEN, TX0, TX1, TX2, TX3, RXCK, RXDV, RX0, RX1, RX2, RX3, RXER, CRS, COL, MDC, MDIO>
+    InterfacePins
+    for MiiPins<
+        TXCK,
+        TXEN,
+        TX0,
+        TX1,
+        TX2,
+        TX3,
+        RXCK,
+        RXDV,
+        RX0,
+        RX1,
+        RX2,
+        RX3,
+        RXER,
+        CRS,
+        COL,
+        MDC,
+        MDIO,
+    >
+where
+    TXCK: RefCkPin,
+    TXEN: TxEnPin,
+    TX0: Tx0Pin,
+    TX1: Tx1Pin,
+    TX2: Tx2Pin,
+    TX3: Tx3Pin,
+    RXCK: RxCkPin,
+    RXDV: RxDvPin,
+    RX0: Rx0Pin,
+    RX1: Rx1Pin,
+    RX2: Rx2Pin,
+    RX3: Rx3Pin,
+    RXER: RxErPin,
+    CRS: CrsPin,
+    COL: ColPin,
+    MDC: MdcPin,
+    MDIO: MdioPin,
+{
+    const MII: bool = true;
+}
+
+//==============================================================================
+// Interrupts
+//==============================================================================
+
+bitflags! {
+    /// GMAC interrupt flags
+    ///
+    /// The bits match the `ISR`, `IER` and `IDR` registers.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct Interrupts: u32 {
+        /// Management frame sent
+        const MFS = 1 << 0;
+        /// Frame received
+        const RCOMP = 1 << 1;
+        /// Receive ring full
+        const RXUBR = 1 << 2;
+        /// Transmit ring empty
+        const TXUBR = 1 << 3;
+        /// Transmit underrun
+        const TUR = 1 << 4;
+        /// Retry limit exceeded
+        const RLEX = 1 << 5;
+        /// Transmit frame corruption
+        const TFC = 1 << 6;
+        /// Frame transmitted
+        const TCOMP = 1 << 7;
+        /// Receive overrun
+        const ROVR = 1 << 10;
+        /// AHB error
+        const HRESP = 1 << 11;
+    }
+}
+
+//==============================================================================
+// Gmac
+//==============================================================================
+
+/// Compute the 6-bit index of `address` in the hash filter
+///
+/// Each bit of the index is the XOR of every sixth bit of the address.
+pub fn hash_index(address: &[u8; 6]) -> u8 {
+    let mut index = 0;
+    for bit in 0..48 {
+        let value = (address[bit / 8] >> (bit % 8)) & 1;
+        index ^= value << (bit % 6);
+    }
+    index
+}
+
+/// Ethernet MAC
+pub struct Gmac<P> {
+    gmac: pac::Gmac,
+    pins: P,
+    rx: RxRing<'static>,
+    tx: TxRing<'static>,
+}
+
+impl<P: InterfacePins> Gmac<P> {
+    /// Create a new [`Gmac`], and start receiving and transmitting frames
+    ///
+    /// `mck` is the frequency of the GMAC bus clock, which is the CPU clock.
+    /// It sets the frequency of `MDC`. The station address of the GMAC is
+    /// `mac_address`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if one of the rings is empty.
+    pub fn new<const RX: usize, const TX: usize>(
+        mclk: &mut Mclk,
+        gmac: pac::Gmac,
+        mck: Hertz,
+        pins: P,
+        rx_storage: &'static mut RxStorage<RX>,
+        tx_storage: &'static mut TxStorage<TX>,
+        mac_address: [u8; 6],
+    ) -> Self {
+        mclk.ahbmask().modify(|_, w| w.gmac_().set_bit());
+        mclk.apbcmask().modify(|_, w| w.gmac_().set_bit());
+
+        let mut gmac = Self {
+            gmac,
+            pins,
+            rx: RxRing::new(rx_storage),
+            tx: TxRing::new(tx_storage),
+        };
+        gmac.configure(mck, mac_address);
+        gmac
+    }
+
+    fn configure(&mut self, mck: Hertz, mac_address: [u8; 6]) {
+        let regs = self.regs();
+
+        regs.ncr().reset();
+        regs.ncr().write(|w| w.clrstat().set_bit());
+        regs.idr().write(|w| unsafe { w.bits(u32::MAX) });
+        let _ = regs.isr().read();
+        regs.rsr().write(|w| unsafe { w.bits(u32::MAX) });
+        regs.tsr().write(|w| unsafe { w.bits(u32::MAX) });
+
+        regs.ur().write(|w| w.mii().bit(P::MII));
+
+        let divider = mck.to_Hz().div_ceil(MDC_MAX_FREQ);
+        let clk = match divider {
+            0..=8 => Clkselect::Mck8,
+            9..=16 => Clkselect::Mck16,
+            17..=32 => Clkselect::Mck32,
+            33..=48 => Clkselect::Mck48,
+            49..=64 => Clkselect::Mck64,
+            _ => Clkselect::Mck96,
+        };
+        regs.ncfgr().write(|w| {
+            w.spd().set_bit();
+            w.fd().set_bit();
+            w.mtihen().set_bit();
+            w.rfcs().set_bit();
+            w.clk().variant(clk)
+        });
+
+        regs.dcfgr().write(|w| {
+            w.fbldo().incr4();
+            w.rxbms().full();
+            w.txpbms().set_bit();
+            unsafe { w.drbs().bits(RX_BUFFER_BLOCKS) }
+        });
+
+        regs.rbqb().write(|w| unsafe { w.bits(self.rx.base()) });
+        regs.tbqb().write(|w| unsafe { w.bits(self.tx.base()) });
+
+        self.clear_multicast();
+        self.set_address_filter(0, Some(mac_address));
+        for filter in 1..NUM_ADDRESS_FILTERS {
+            self.set_address_filter(filter, None);
+        }
+
+        self.regs()
+            .ncr()
+            .write(|w| w.mpe().set_bit().rxen().set_bit().txen().set_bit());
+    }
+
+    #[inline]
+    fn regs(&self) -> &RegisterBlock {
+        self.gmac.deref()
+    }
+
+    /// Stop the GMAC, and return the peripheral and the pins
+    ///
+    /// The rings are released too, since the DMA no longer accesses them.
+    pub fn free(self) -> (pac::Gmac, P) {
+        self.regs().ncr().reset();
+        self.regs().idr().write(|w| unsafe { w.bits(u32::MAX) });
+        (self.gmac, self.pins)
+    }
+
+    //==========================================================================
+    // Frames
+    //==========================================================================
+
+    /// Return whether a received frame is waiting
+    #[inline]
+    pub fn frame_available(&mut self) -> bool {
+        self.rx.poll()
+    }
+
+    /// Pass the next received frame to `f`, and return its result
+    ///
+    /// Returns `None` if no frame is waiting.
+    #[inline]
+    pub fn receive<R>(&mut self, f: impl FnOnce(&mut [u8]) -> R) -> Option<R> {
+        self.rx.receive(f)
+    }
+
+    /// Return whether a frame can be queued for transmission
+    #[inline]
+    pub fn can_transmit(&self) -> bool {
+        self.tx.ready()
+    }
+
+    /// Let `f` fill a frame of `length` bytes, then send it
+    ///
+    /// Returns `None` if the transmit ring is full.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `length` is larger than the [`MTU`].
+    #[inline]
+    pub fn transmit<R>(&mut self, length: usize, f: impl FnOnce(&mut [u8]) -> R) -> Option<R> {
+        let result = self.tx.transmit(length, f)?;
+        start_transmission(&self.gmac);
+        Some(result)
+    }
+
+    /// Number of received frames dropped because they did not fit in a
+    /// buffer
+    #[inline]
+    pub fn dropped_frames(&self) -> u32 {
+        self.rx.dropped()
+    }
+
+    /// Number of frames whose transmission failed
+    #[inline]
+    pub fn transmit_errors(&self) -> u32 {
+        self.tx.errors()
+    }
+
+    //==========================================================================
+    // Interrupts
+    //==========================================================================
+
+    /// Enable the given interrupts
+    #[inline]
+    pub fn enable_interrupts(&mut self, interrupts: Interrupts) {
+        self.regs()
+            .ier()
+            .write(|w| unsafe { w.bits(interrupts.bits()) });
+    }
+
+    /// Disable the given interrupts
+    #[inline]
+    pub fn disable_interrupts(&mut self, interrupts: Interrupts) {
+        self.regs()
+            .idr()
+            .write(|w| unsafe { w.bits(interrupts.bits()) });
+    }
+
+    /// Read and clear the pending interrupts
+    #[inline]
+    pub fn read_interrupts(&mut self) -> Interrupts {
+        Interrupts::from_bits_truncate(self.regs().isr().read().bits())
+    }
+
+    //==========================================================================
+    // Address filtering
+    //==========================================================================
+
+    /// Set or clear one of the [`NUM_ADDRESS_FILTERS`] address filters
+    ///
+    /// Filter 0 holds the MAC address of the GMAC.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `filter` is out of range.
+    pub fn set_address_filter(&mut self, filter: usize, address: Option<[u8; 6]>) {
+        assert!(filter < NUM_ADDRESS_FILTERS, "Invalid address filter");
+        let sa = self.regs().sa(filter);
+        // Writing the bottom register disables the filter, and writing the
+        // top register enables it again
+        match address {
+            Some(address) => {
+                let bottom = u32::from_le_bytes([address[0], address[1], address[2], address[3]]);
+                let top = u16::from_le_bytes([address[4], address[5]]);
+                sa.sab().write(|w| unsafe { w.addr().bits(bottom) });
+                sa.sat().write(|w| unsafe { w.addr().bits(top) });
+            }
+            None => sa.sab().write(|w| unsafe { w.addr().bits(0) }),
+        }
+    }
+
+    /// Receive the frames sent to the multicast `address`
+    ///
+    /// The hash filter may let through frames sent to other addresses
+    /// sharing the same [`hash_index`].
+    pub fn add_multicast(&mut self, address: &[u8; 6]) {
+        let index = hash_index(address);
+        if index < 32 {
+            self.regs()
+                .hrb()
+                .modify(|r, w| unsafe { w.addr().bits(r.addr().bits() | 1 << index) });
+        } else {
+            self.regs()
+                .hrt()
+                .modify(|r, w| unsafe { w.addr().bits(r.addr().bits() | 1 << (index - 32)) });
+        }
+    }
+
+    /// Receive the frames sent to any multicast address
+    pub fn accept_all_multicast(&mut self) {
+        self.regs()
+            .hrb()
+            .write(|w| unsafe { w.addr().bits(u32::MAX) });
+        self.regs()
+            .hrt()
+            .write(|w| unsafe { w.addr().bits(u32::MAX) });
+    }
+
+    /// Clear the multicast hash filter
+    pub fn clear_multicast(&mut self) {
+        self.regs().hrb().write(|w| unsafe { w.addr().bits(0) });
+        self.regs().hrt().write(|w| unsafe { w.addr().bits(0) });
+    }
+
+    /// Receive all the frames, whatever their destination
+    #[inline]
+    pub fn set_promiscuous(&mut self, promiscuous: bool) {
+        self.regs().ncfgr().modify(|_, w| w.caf().bit(promiscuous));
+    }
+
+    /// Receive the frames sent to the broadcast address, which is the
+    /// default
+    #[inline]
+    pub fn set_broadcast(&mut self, broadcast: bool) {
+        self.regs().ncfgr().modify(|_, w| w.nbc().bit(!broadcast));
+    }
+
+    //==========================================================================
+    // PHY management
+    //==========================================================================
+
+    /// Read the register `reg` of the PHY at address `phy`
+    pub fn phy_read(&mut self, phy: u8, reg: u8) -> u16 {
+        self.phy_operation(MAN_OP_READ, phy, reg, 0);
+        self.regs().man().read().data().bits()
+    }
+
+    /// Write `value` to the register `reg` of the PHY at address `phy`
+    pub fn phy_write(&mut self, phy: u8, reg: u8, value: u16) {
+        self.phy_operation(MAN_OP_WRITE, phy, reg, value);
+    }
+
+    fn phy_operation(&mut self, op: u32, phy: u8, reg: u8, value: u16) {
+        let regs = self.regs();
+        while regs.nsr().read().idle().bit_is_clear() {}
+        let bits = MAN_CLTTO
+            | op
+            | (phy as u32 & 0x1F) << 23
+            | (reg as u32 & 0x1F) << 18
+            | MAN_WTN
+            | value as u32;
+        regs.man().write(|w| unsafe { w.bits(bits) });
+        while regs.nsr().read().idle().bit_is_clear() {}
+    }
+
+    /// Read the 32-bit identifier of the PHY at address `phy`
+    ///
+    /// A missing PHY reads as `0xFFFF_FFFF`.
+    pub fn phy_id(&mut self, phy: u8) -> u32 {
+        let high = self.phy_read(phy, phy::PHYIDR1) as u32;
+        let low = self.phy_read(phy, phy::PHYIDR2) as u32;
+        high << 16 | low
+    }
+
+    /// Enable and restart the auto-negotiation of the PHY at address `phy`
+    pub fn restart_autonegotiation(&mut self, phy: u8) {
+        let bmcr = self.phy_read(phy, phy::BMCR);
+        self.phy_write(
+            phy,
+            phy::BMCR,
+            bmcr | phy::BMCR_AN_ENABLE | phy::BMCR_AN_RESTART,
+        );
+    }
+
+    /// Read the link state of the PHY at address `phy`
+    ///
+    /// Returns `None` while the link is down or being negotiated.
+    pub fn phy_link(&mut self, phy: u8) -> Option<Link> {
+        // The link status is latched low, the second read returns the
+        // current state
+        let _ = self.phy_read(phy, phy::BMSR);
+        let bmsr = self.phy_read(phy, phy::BMSR);
+        let bmcr = self.phy_read(phy, phy::BMCR);
+        let anar = self.phy_read(phy, phy::ANAR);
+        let anlpar = self.phy_read(phy, phy::ANLPAR);
+        phy::decode_link(bmcr, bmsr, anar, anlpar)
+    }
+
+    /// Set the speed and duplex mode of the MAC to match the link of the PHY
+    #[inline]
+    pub fn set_link(&mut self, link: Link) {
+        self.regs().ncfgr().modify(|_, w| {
+            w.spd().bit(link.speed == Speed::Mbps100);
+            w.fd().bit(link.duplex == Duplex::Full)
+        });
+    }
+}
+
+/// Start the transmission of the frames queued in the transmit ring
+#[inline]
+fn start_transmission(gmac: &pac::Gmac) {
+    gmac.ncr().modify(|_, w| w.tstart().set_bit());
+}
@@ -0,0 +1,188 @@
+//! Ethernet PHY management
+//!
+//! Standard IEEE 802.3 clause 22 registers, common to all the PHYs, and the
+//! decoding of the link state they report.
+
+/// Basic Mode Control Register
+pub const BMCR: u8 = 0;
+/// Basic Mode Status Register
+pub const BMSR: u8 = 1;
+/// PHY Identifier Register 1
+pub const PHYIDR1: u8 = 2;
+/// PHY Identifier Register 2
+pub const PHYIDR2: u8 = 3;
+/// Auto-Negotiation Advertisement Register
+pub const ANAR: u8 = 4;
+/// Auto-Negotiation Link Partner Ability Register
+pub const ANLPAR: u8 = 5;
+
+/// `BMCR`: software reset
+pub const BMCR_RESET: u16 = 1 << 15;
+/// `BMCR`: 100 Mbit/s speed, when auto-negotiation is disabled
+pub const BMCR_SPEED_100: u16 = 1 << 13;
+/// `BMCR`: enable auto-negotiation
+pub const BMCR_AN_ENABLE: u16 = 1 << 12;
+/// `BMCR`: restart auto-negotiation
+pub const BMCR_AN_RESTART: u16 = 1 << 9;
+/// `BMCR`: full duplex, when auto-negotiation is disabled
+pub const BMCR_FULL_DUPLEX: u16 = 1 << 8;
+
+/// `BMSR`: auto-negotiation complete
+pub const BMSR_AN_COMPLETE: u16 = 1 << 5;
+/// `BMSR`: link up, latched low until read
+pub const BMSR_LINK_UP: u16 = 1 << 2;
+
+/// `ANAR`/`ANLPAR`: 10BASE-T, half duplex
+pub const AN_10_HALF: u16 = 1 << 5;
+/// `ANAR`/`ANLPAR`: 10BASE-T, full duplex
+pub const AN_10_FULL: u16 = 1 << 6;
+/// `ANAR`/`ANLPAR`: 100BASE-TX, half duplex
+pub const AN_100_HALF: u16 = 1 << 7;
+/// `ANAR`/`ANLPAR`: 100BASE-TX, full duplex
+pub const AN_100_FULL: u16 = 1 << 8;
+
+/// Speed of the link
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Speed {
+    /// 10 Mbit/s
+    Mbps10,
+    /// 100 Mbit/s
+    Mbps100,
+}
+
+/// Duplex mode of the link
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Duplex {
+    /// Half duplex
+    Half,
+    /// Full duplex
+    Full,
+}
+
+/// State of an established link
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Link {
+    pub speed: Speed,
+    pub duplex: Duplex,
+}
+
+/// Decode the link state from the `BMCR`, `BMSR`, `ANAR` and `ANLPAR`
+/// registers of a PHY
+///
+/// Returns `None` while the link is down, or while the auto-negotiation is
+/// running. The negotiated mode is the best one advertised by both ends.
+pub fn decode_link(bmcr: u16, bmsr: u16, anar: u16, anlpar: u16) -> Option<Link> {
+    if bmsr & BMSR_LINK_UP == 0 {
+        return None;
+    }
+
+    if bmcr & BMCR_AN_ENABLE == 0 {
+        let speed = if bmcr & BMCR_SPEED_100 != 0 {
+            Speed::Mbps100
+        } else {
+            Speed::Mbps10
+        };
+        let duplex = if bmcr & BMCR_FULL_DUPLEX != 0 {
+            Duplex::Full
+        } else {
+            Duplex::Half
+        };
+        return Some(Link { speed, duplex });
+    }
+
+    if bmsr & BMSR_AN_COMPLETE == 0 {
+        return None;
+    }
+
+    let common = anar & anlpar;
+    let (speed, duplex) = if common & AN_100_FULL != 0 {
+        (Speed::Mbps100, Duplex::Full)
+    } else if common & AN_100_HALF != 0 {
+        (Speed::Mbps100, Duplex::Half)
+    } else if common & AN_10_FULL != 0 {
+        (Speed::Mbps10, Duplex::Full)
+    } else {
+        (Speed::Mbps10, Duplex::Half)
+    };
+    Some(Link { speed, duplex })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL_MODES: u16 = AN_10_HALF | AN_10_FULL | AN_100_HALF | AN_100_FULL;
+    const UP: u16 = BMSR_LINK_UP | BMSR_AN_COMPLETE;
+
+    #[test]
+    fn link_down() {
+        assert_eq!(
+            decode_link(BMCR_AN_ENABLE, BMSR_AN_COMPLETE, ALL_MODES, ALL_MODES),
+            None
+        );
+        assert_eq!(
+            decode_link(BMCR_AN_ENABLE, BMSR_LINK_UP, ALL_MODES, ALL_MODES),
+            None
+        );
+    }
+
+    #[test]
+    fn negotiated_link() {
+        let link = |anlpar| decode_link(BMCR_AN_ENABLE, UP, ALL_MODES, anlpar).unwrap();
+        assert_eq!(
+            link(ALL_MODES),
+            Link {
+                speed: Speed::Mbps100,
+                duplex: Duplex::Full
+            }
+        );
+        assert_eq!(
+            link(AN_100_HALF | AN_10_FULL),
+            Link {
+                speed: Speed::Mbps100,
+                duplex: Duplex::Half
+            }
+        );
+        assert_eq!(
+            link(AN_10_FULL | AN_10_HALF),
+            Link {
+                speed: Speed::Mbps10,
+                duplex: Duplex::Full
+            }
+        );
+        assert_eq!(
+            link(0),
+            Link {
+                speed: Speed::Mbps10,
+                duplex: Duplex::Half
+            }
+        );
+    }
+
+    #[test]
+    fn negotiation_is_limited_by_advertisement() {
+        let link = decode_link(BMCR_AN_ENABLE, UP, AN_10_FULL | AN_10_HALF, ALL_MODES);
+        assert_eq!(
+            link,
+            Some(Link {
+                speed: Speed::Mbps10,
+                duplex: Duplex::Full
+            })
+        );
+    }
+
+    #[test]
+    fn forced_link() {
+        let link = decode_link(BMCR_SPEED_100, BMSR_LINK_UP, 0, 0);
+        assert_eq!(
+            link,
+            Some(Link {
+                speed: Speed::Mbps100,
+                duplex: Duplex::Half
+            })
+        );
+    }
+}
@@ -0,0 +1,491 @@
+//! DMA descriptor rings
+//!
+//! The GMAC DMA walks circular lists of buffer descriptors, handing each
+//! descriptor back to the software once its buffer has been filled or sent.
+//! The rings below only touch the descriptors and buffers in memory, never
+//! the GMAC registers, so that their bookkeeping can be tested on the host by
+//! playing the part of the DMA.
+
+use core::sync::atomic::{fence, Ordering};
+
+use vcell::VolatileCell;
+
+/// Size of a frame buffer, in bytes
+///
+/// Each buffer holds a whole Ethernet frame, without its FCS.
+pub const BUFFER_SIZE: usize = 1536;
+
+/// Maximum length of a transmitted frame, without its FCS
+pub const MTU: usize = 1514;
+
+/// Receive descriptor, first word: the software owns the buffer
+const RX_OWNERSHIP: u32 = 1 << 0;
+/// Receive descriptor, first word: last descriptor of the ring
+const RX_WRAP: u32 = 1 << 1;
+/// Receive descriptor, first word: buffer address
+const RX_ADDRESS_MASK: u32 = !0b11;
+/// Receive descriptor, second word: frame length
+const RX_LENGTH_MASK: u32 = 0x1FFF;
+/// Receive descriptor, second word: start of frame
+const RX_SOF: u32 = 1 << 14;
+/// Receive descriptor, second word: end of frame
+const RX_EOF: u32 = 1 << 15;
+
+/// Transmit descriptor, second word: frame length
+const TX_LENGTH_MASK: u32 = 0x3FFF;
+/// Transmit descriptor, second word: last buffer of the frame
+const TX_LAST: u32 = 1 << 15;
+/// Transmit descriptor, second word: late collision
+const TX_LATE_COLLISION: u32 = 1 << 26;
+/// Transmit descriptor, second word: frame corrupted by an AHB error
+const TX_AHB_ERROR: u32 = 1 << 27;
+/// Transmit descriptor, second word: retry limit exceeded
+const TX_RETRY_LIMIT: u32 = 1 << 29;
+/// Transmit descriptor, second word: last descriptor of the ring
+const TX_WRAP: u32 = 1 << 30;
+/// Transmit descriptor, second word: the software owns the buffer
+const TX_USED: u32 = 1 << 31;
+/// Transmit errors reported by the DMA
+const TX_ERRORS: u32 = TX_LATE_COLLISION | TX_AHB_ERROR | TX_RETRY_LIMIT;
+
+//==============================================================================
+// Descriptors
+//==============================================================================
+
+/// Buffer descriptor shared with the GMAC DMA
+#[repr(C, align(8))]
+struct Descriptor {
+    address: VolatileCell<u32>,
+    status: VolatileCell<u32>,
+}
+
+impl Descriptor {
+    const fn new() -> Self {
+        Self {
+            address: VolatileCell::new(0),
+            status: VolatileCell::new(0),
+        }
+    }
+}
+
+/// Frame buffer
+#[repr(C, align(4))]
+struct Buffer([u8; BUFFER_SIZE]);
+
+impl Buffer {
+    const fn new() -> Self {
+        Self([0; BUFFER_SIZE])
+    }
+
+    /// Address of the buffer, as seen by the DMA
+    #[inline]
+    fn address(&self) -> u32 {
+        self.0.as_ptr() as u32
+    }
+}
+
+/// Memory of a receive ring of `N` frames
+///
+/// The descriptors and buffers are accessed by the GMAC DMA, and must live in
+/// SRAM for as long as the GMAC runs, which is why the
+/// [`Gmac`](super::Gmac) takes a `&'static mut` reference to them.
+///
+/// ```no_run
+/// use atsamd_hal::gmac::RxStorage;
+///
+/// static mut RX_STORAGE: RxStorage<8> = RxStorage::new();
+/// ```
+#[repr(C)]
+pub struct RxStorage<const N: usize> {
+    descriptors: [Descriptor; N],
+    buffers: [Buffer; N],
+}
+
+impl<const N: usize> RxStorage<N> {
+    /// Create the memory of a receive ring
+    pub const fn new() -> Self {
+        Self {
+            descriptors: [const { Descriptor::new() }; N],
+            buffers: [const { Buffer::new() }; N],
+        }
+    }
+}
+
+impl<const N: usize> Default for RxStorage<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Memory of a transmit ring of `N` frames
+///
+/// See [`RxStorage`].
+#[repr(C)]
+pub struct TxStorage<const N: usize> {
+    descriptors: [Descriptor; N],
+    buffers: [Buffer; N],
+}
+
+impl<const N: usize> TxStorage<N> {
+    /// Create the memory of a transmit ring
+    pub const fn new() -> Self {
+        Self {
+            descriptors: [const { Descriptor::new() }; N],
+            buffers: [const { Buffer::new() }; N],
+        }
+    }
+}
+
+impl<const N: usize> Default for TxStorage<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+//==============================================================================
+// RxRing
+//==============================================================================
+
+/// Ring of receive descriptors
+pub(super) struct RxRing<'a> {
+    descriptors: &'a [Descriptor],
+    buffers: &'a mut [Buffer],
+    next: usize,
+    dropped: u32,
+}
+
+impl<'a> RxRing<'a> {
+    /// Create a ring whose descriptors are all owned by the DMA
+    ///
+    /// # Panics
+    ///
+    /// Panics if the ring is empty.
+    pub(super) fn new<const N: usize>(storage: &'a mut RxStorage<N>) -> Self {
+        assert!(N > 0, "The receive ring needs at least one descriptor");
+        let mut ring = Self {
+            descriptors: &storage.descriptors,
+            buffers: &mut storage.buffers,
+            next: 0,
+            dropped: 0,
+        };
+        ring.reset();
+        ring
+    }
+
+    /// Give all the descriptors back to the DMA, and restart from the first
+    /// one
+    pub(super) fn reset(&mut self) {
+        let last = self.descriptors.len() - 1;
+        for (i, (descriptor, buffer)) in
+            self.descriptors.iter().zip(self.buffers.iter()).enumerate()
+        {
+            let wrap = if i == last { RX_WRAP } else { 0 };
+            descriptor.status.set(0);
+            descriptor
+                .address
+                .set((buffer.address() & RX_ADDRESS_MASK) | wrap);
+        }
+        self.next = 0;
+        fence(Ordering::SeqCst);
+    }
+
+    /// Address of the first descriptor, for `RBQB`
+    #[inline]
+    pub(super) fn base(&self) -> u32 {
+        self.descriptors.as_ptr() as u32
+    }
+
+    /// Number of frames dropped because they did not fit in a buffer
+    #[inline]
+    pub(super) fn dropped(&self) -> u32 {
+        self.dropped
+    }
+
+    /// Return whether a complete frame is waiting
+    ///
+    /// Buffers holding a partial frame are given back to the DMA on the way.
+    /// Once a frame is waiting, it stays available until it is
+    /// [`receive`](Self::receive)d.
+    pub(super) fn poll(&mut self) -> bool {
+        loop {
+            let descriptor = &self.descriptors[self.next];
+            if descriptor.address.get() & RX_OWNERSHIP == 0 {
+                return false;
+            }
+            fence(Ordering::SeqCst);
+            let status = descriptor.status.get();
+            if status & (RX_SOF | RX_EOF) == RX_SOF | RX_EOF {
+                return true;
+            }
+            if status & RX_SOF != 0 {
+                self.dropped = self.dropped.wrapping_add(1);
+            }
+            self.release();
+        }
+    }
+
+    /// Pass the next received frame to `f`, then give its buffer back to the
+    /// DMA
+    ///
+    /// Returns `None` if no frame is waiting.
+    pub(super) fn receive<R>(&mut self, f: impl FnOnce(&mut [u8]) -> R) -> Option<R> {
+        if !self.poll() {
+            return None;
+        }
+        let length = (self.descriptors[self.next].status.get() & RX_LENGTH_MASK) as usize;
+        let length = length.min(BUFFER_SIZE);
+        let result = f(&mut self.buffers[self.next].0[..length]);
+        self.release();
+        Some(result)
+    }
+
+    /// Give the next descriptor back to the DMA
+    fn release(&mut self) {
+        let descriptor = &self.descriptors[self.next];
+        fence(Ordering::SeqCst);
+        descriptor
+            .address
+            .set(descriptor.address.get() & !RX_OWNERSHIP);
+        self.next = (self.next + 1) % self.descriptors.len();
+    }
+}
+
+//==============================================================================
+// TxRing
+//==============================================================================
+
+/// Ring of transmit descriptors
+pub(super) struct TxRing<'a> {
+    descriptors: &'a [Descriptor],
+    buffers: &'a mut [Buffer],
+    next: usize,
+    errors: u32,
+}
+
+impl<'a> TxRing<'a> {
+    /// Create a ring whose descriptors are all owned by the software
+    ///
+    /// # Panics
+    ///
+    /// Panics if the ring is empty.
+    pub(super) fn new<const N: usize>(storage: &'a mut TxStorage<N>) -> Self {
+        assert!(N > 0, "The transmit ring needs at least one descriptor");
+        let mut ring = Self {
+            descriptors: &storage.descriptors,
+            buffers: &mut storage.buffers,
+            next: 0,
+            errors: 0,
+        };
+        ring.reset();
+        ring
+    }
+
+    /// Take all the descriptors back from the DMA, and restart from the first
+    /// one
+    pub(super) fn reset(&mut self) {
+        let last = self.descriptors.len() - 1;
+        for (i, (descriptor, buffer)) in
+            self.descriptors.iter().zip(self.buffers.iter()).enumerate()
+        {
+            let wrap = if i == last { TX_WRAP } else { 0 };
+            descriptor.address.set(buffer.address());
+            descriptor.status.set(TX_USED | wrap);
+        }
+        self.next = 0;
+        fence(Ordering::SeqCst);
+    }
+
+    /// Address of the first descriptor, for `TBQB`
+    #[inline]
+    pub(super) fn base(&self) -> u32 {
+        self.descriptors.as_ptr() as u32
+    }
+
+    /// Number of frames whose transmission failed
+    #[inline]
+    pub(super) fn errors(&self) -> u32 {
+        self.errors
+    }
+
+    /// Return whether a descriptor is free for the next frame
+    #[inline]
+    pub(super) fn ready(&self) -> bool {
+        self.descriptors[self.next].status.get() & TX_USED != 0
+    }
+
+    /// Let `f` fill a frame of `length` bytes, then hand it to the DMA
+    ///
+    /// Returns `None` if all the descriptors are still owned by the DMA. The
+    /// transmission must then be started with `NCR.TSTART`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `length` is larger than the [`MTU`].
+    pub(super) fn transmit<R>(
+        &mut self,
+        length: usize,
+        f: impl FnOnce(&mut [u8]) -> R,
+    ) -> Option<R> {
+        assert!(length <= MTU, "Frame larger than the MTU");
+        if !self.ready() {
+            return None;
+        }
+        fence(Ordering::SeqCst);
+        let descriptor = &self.descriptors[self.next];
+        let status = descriptor.status.get();
+        if status & TX_ERRORS != 0 {
+            self.errors = self.errors.wrapping_add(1);
+        }
+
+        let result = f(&mut self.buffers[self.next].0[..length]);
+
+        fence(Ordering::SeqCst);
+        descriptor
+            .status
+            .set((status & TX_WRAP) | TX_LAST | (length as u32 & TX_LENGTH_MASK));
+        fence(Ordering::SeqCst);
+        self.next = (self.next + 1) % self.descriptors.len();
+        Some(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Act as the DMA receiving `frame` into descriptor `index`
+    fn dma_receive(ring: &mut RxRing<'_>, index: usize, frame: &[u8], status: u32) {
+        assert_eq!(ring.descriptors[index].address.get() & RX_OWNERSHIP, 0);
+        ring.buffers[index].0[..frame.len()].copy_from_slice(frame);
+        ring.descriptors[index]
+            .status
+            .set(status | frame.len() as u32);
+        let address = ring.descriptors[index].address.get();
+        ring.descriptors[index].address.set(address | RX_OWNERSHIP);
+    }
+
+    /// Act as the DMA sending the frame of descriptor `index`
+    fn dma_transmit(ring: &mut TxRing<'_>, index: usize, error: u32) -> usize {
+        let status = ring.descriptors[index].status.get();
+        assert_eq!(status & TX_USED, 0);
+        assert_ne!(status & TX_LAST, 0);
+        ring.descriptors[index].status.set(status | TX_USED | error);
+        (status & TX_LENGTH_MASK) as usize
+    }
+
+    #[test]
+    fn rx_ring_is_owned_by_dma() {
+        let mut storage = RxStorage::<4>::new();
+        let mut ring = RxRing::new(&mut storage);
+        for (i, descriptor) in ring.descriptors.iter().enumerate() {
+            let address = descriptor.address.get();
+            assert_eq!(address & RX_OWNERSHIP, 0);
+            assert_eq!(address & RX_WRAP != 0, i == 3);
+        }
+        assert!(!ring.poll());
+        assert_eq!(ring.receive(|_| ()), None);
+    }
+
+    #[test]
+    fn rx_ring_receives_in_order_and_wraps() {
+        let mut storage = RxStorage::<3>::new();
+        let mut ring = RxRing::new(&mut storage);
+        for round in 0..3u8 {
+            for i in 0..3 {
+                let frame = [round, i as u8, 0xAA];
+                dma_receive(&mut ring, i, &frame, RX_SOF | RX_EOF);
+            }
+            for i in 0..3 {
+                assert!(ring.poll());
+                let received = ring.receive(|frame| frame == [round, i as u8, 0xAA]);
+                assert_eq!(received, Some(true));
+                // The descriptor went back to the DMA, with its wrap bit
+                let address = ring.descriptors[i].address.get();
+                assert_eq!(address & RX_OWNERSHIP, 0);
+                assert_eq!(address & RX_WRAP != 0, i == 2);
+            }
+            assert!(!ring.poll());
+        }
+    }
+
+    #[test]
+    fn rx_ring_drops_partial_frames() {
+        let mut storage = RxStorage::<4>::new();
+        let mut ring = RxRing::new(&mut storage);
+        dma_receive(&mut ring, 0, &[1; 16], RX_SOF);
+        dma_receive(&mut ring, 1, &[2; 16], 0);
+        dma_receive(&mut ring, 2, &[3; 16], RX_EOF);
+        dma_receive(&mut ring, 3, &[4; 8], RX_SOF | RX_EOF);
+
+        assert_eq!(ring.receive(|frame| frame == [4; 8]), Some(true));
+        assert_eq!(ring.dropped(), 1);
+        for descriptor in ring.descriptors.iter() {
+            assert_eq!(descriptor.address.get() & RX_OWNERSHIP, 0);
+        }
+    }
+
+    #[test]
+    fn rx_frame_stays_available_until_received() {
+        let mut storage = RxStorage::<2>::new();
+        let mut ring = RxRing::new(&mut storage);
+        dma_receive(&mut ring, 0, &[5; 60], RX_SOF | RX_EOF);
+        assert!(ring.poll());
+        assert!(ring.poll());
+        assert_eq!(ring.receive(|frame| frame.len()), Some(60));
+        assert!(!ring.poll());
+    }
+
+    #[test]
+    fn tx_ring_is_owned_by_software() {
+        let mut storage = TxStorage::<4>::new();
+        let ring = TxRing::new(&mut storage);
+        for (i, descriptor) in ring.descriptors.iter().enumerate() {
+            let status = descriptor.status.get();
+            assert_ne!(status & TX_USED, 0);
+            assert_eq!(status & TX_WRAP != 0, i == 3);
+        }
+        assert!(ring.ready());
+    }
+
+    #[test]
+    fn tx_ring_fills_until_dma_catches_up() {
+        let mut storage = TxStorage::<2>::new();
+        let mut ring = TxRing::new(&mut storage);
+        assert_eq!(ring.transmit(60, |frame| frame.fill(1)), Some(()));
+        assert_eq!(ring.transmit(100, |frame| frame.fill(2)), Some(()));
+        assert!(!ring.ready());
+        assert_eq!(ring.transmit(60, |_| ()), None);
+
+        assert_eq!(dma_transmit(&mut ring, 0, 0), 60);
+        assert!(ring.buffers[0].0[..60].iter().all(|&b| b == 1));
+        assert!(ring.ready());
+        assert_eq!(ring.transmit(42, |frame| frame.len()), Some(42));
+
+        assert_eq!(dma_transmit(&mut ring, 1, 0), 100);
+        assert!(ring.buffers[1].0[..100].iter().all(|&b| b == 2));
+        // The wrap bit survives the round trips
+        let status = ring.descriptors[1].status.get();
+        assert_ne!(status & TX_WRAP, 0);
+        assert_eq!(dma_transmit(&mut ring, 0, 0), 42);
+        assert_eq!(ring.descriptors[0].status.get() & TX_WRAP, 0);
+    }
+
+    #[test]
+    fn tx_ring_counts_errors() {
+        let mut storage = TxStorage::<1>::new();
+        let mut ring = TxRing::new(&mut storage);
+        ring.transmit(60, |_| ()).unwrap();
+        dma_transmit(&mut ring, 0, TX_RETRY_LIMIT);
+        assert_eq!(ring.errors(), 0);
+        ring.transmit(60, |_| ()).unwrap();
+        assert_eq!(ring.errors(), 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn tx_ring_rejects_oversized_frames() {
+        let mut storage = TxStorage::<1>::new();
+        let mut ring = TxRing::new(&mut storage);
+        ring.transmit(MTU + 1, |_| ());
+    }
+}
@@ -0,0 +1,900 @@
+//! # Inter-IC Sound
+//!
+//! The Inter-IC Sound controller (I2S) provides a bidirectional, synchronous,
+//! digital audio link with external audio devices, such as codecs, DACs and
+//! microphones.
+//!
+//! The I2S has two clock units, each of which generates or receives a serial
+//! clock (`SCK`) and a frame sync (`FS`), and optionally outputs a master
+//! clock (`MCK`). The data is shifted in and out by two serializers, each
+//! using one of the clock units:
+//!
+//! * On SAMD21, both serializers can either transmit or receive, on the `SD0`
+//!   and `SD1` pins.
+//! * On SAMx5x, one serializer transmits on the `SDO` pin, and always uses
+//!   clock unit 0. The other one receives on the `SDI` pin.
+//!
+//! ## Creating an [`I2s`]
+//!
+//! An [`I2s`] drives a single [`ClockUnit`], whose pins are grouped in a
+//! [`ClockPins`] struct. Use [`NoneT`] for an unused `FS` or `MCK` pin. The
+//! clock unit is configured with a [`Config`]: in [`Mode::Master`], `SCK` and
+//! `FS` are derived from the I2S generic clock to obtain the requested
+//! [`Config::sample_rate`]. In [`Mode::Slave`], they are received from the
+//! pins.
+//!
+//! A transmitter and a receiver are then added with [`I2s::with_tx`] and
+//! [`I2s::with_rx`], each configured with a [`SerializerConfig`], before the
+//! peripheral is started with [`I2s::enable`].
+//!
+//! ```no_run
+//! use atsamd_hal::i2s::{ClockPins, Config, I2s, SerializerConfig};
+//! use atsamd_hal::typelevel::NoneT;
+//!
+//! let i2s_clock = clocks.i2s0(&gclk).unwrap();
+//! let clock_pins = ClockPins {
+//!     sck: pins.pa10.into_alternate(),
+//!     fs: pins.pa11.into_alternate(),
+//!     mck: NoneT,
+//! };
+//!
+//! // On SAMx5x, pass `&mut peripherals.mclk` instead
+//! let mut i2s = I2s::new(&mut peripherals.pm, peripherals.i2s, i2s_clock, clock_pins, Config::default())
+//!     .with_tx(pins.pa07.into_alternate(), SerializerConfig::default());
+//! i2s.enable();
+//!
+//! // Left and right samples of a stereo frame
+//! nb::block!(i2s.write(0x1234_0000)).unwrap();
+//! nb::block!(i2s.write(0x5678_0000)).unwrap();
+//! ```
+//!
+//! ## PDM microphones
+//!
+//! A receiver can also be added in `PDM2` mode with [`I2s::with_pdm`], to
+//! capture the bitstream of one or two PDM microphones sharing the data pin.
+//! The left microphone is sampled on the rising edge of `SCK`, and the right
+//! microphone on the falling edge. Each received word holds the interleaved
+//! bits of both microphones, which must be decimated in software. The clock
+//! unit should be in [`Mode::Master`], and no `FS` pin is needed.
+//!
+//! ## DMA streaming <span class="stab portability" title="Available on crate features `dma` and `async` only"><code>dma</code> and <code>async</code></span>
+//!
+//! With the `dma` and `async` features enabled, [`I2s::read_stream`] and
+//! [`I2s::write_stream`] continuously move audio between a buffer and the
+//! serializer through a [`dmac`](crate::dmac) channel. The buffer is split
+//! into two halves, which the DMAC alternately fills or empties in a circular
+//! transfer. Each time a half is complete, it is handed to a callback while
+//! the DMAC moves on to the other half, until the callback returns
+//! [`ControlFlow::Break`](core::ops::ControlFlow::Break).
+//!
+//! ```no_run
+//! use core::ops::ControlFlow;
+//!
+//! let mut buffer = [0u32; 512];
+//! i2s.read_stream(&mut dma_channel, &mut buffer, |samples| {
+//!     process(samples);
+//!     ControlFlow::<()>::Continue(())
+//! })
+//! .await?;
+//! ```
+//!
+//! The DMA channel must be configured for `async` operation, and the `DMAC`
+//! interrupt bound to the [`dmac::InterruptHandler`](crate::dmac::InterruptHandler).
+//! The callback must return before the other half completes, otherwise the
+//! DMAC starts overwriting the half being processed.
+
+use atsamd_hal_macros::hal_module;
+
+use crate::clock::{I2S0Clock, I2S1Clock};
+use crate::pac;
+use crate::time::Hertz;
+use crate::typelevel::{NoneT, Sealed};
+
+#[cfg(feature = "dma")]
+use crate::dmac;
+#[cfg(all(feature = "dma", feature = "async"))]
+use crate::dmac::{AnyChannel, Beat, Buffer, DmacDescriptor, PingPong, ReadyFuture};
+#[cfg(all(feature = "dma", feature = "async"))]
+use core::ops::ControlFlow;
+
+/// Delay between the frame sync edge and the first bit of the frame
+pub use pac::i2s::clkctrl::Bitdelayselect as BitDelay;
+/// Width of the frame sync pulse
+pub use pac::i2s::clkctrl::Fswidthselect as FrameSyncWidth;
+/// Number of bits of a slot
+pub use pac::i2s::clkctrl::Slotsizeselect as SlotSize;
+
+/// Implement the clock pin traits for a list of pins
+macro_rules! clock_pins {
+    ($Mode:ident: $($(#[$cfg:meta])* $Id:ident: $Trait:ident<$Unit:ident>),+ $(,)?) => {
+        $(
+            $(#[$cfg])*
+            impl $crate::i2s::$Trait<$crate::i2s::$Unit> for Pin<$Id, $Mode> {}
+
+            clock_pins!(@optional $Trait, $(#[$cfg])* $Id, $Mode, $Unit);
+        )+
+    };
+    (@optional SckPin, $(#[$cfg:meta])* $Id:ident, $Mode:ident, $Unit:ident) => {};
+    (@optional FsPin, $(#[$cfg:meta])* $Id:ident, $Mode:ident, $Unit:ident) => {
+        $(#[$cfg])*
+        impl $crate::i2s::OptionalFsPin<$crate::i2s::$Unit> for Pin<$Id, $Mode> {
+            const ENABLED: bool = true;
+        }
+    };
+    (@optional MckPin, $(#[$cfg:meta])* $Id:ident, $Mode:ident, $Unit:ident) => {
+        $(#[$cfg])*
+        impl $crate::i2s::OptionalMckPin<$crate::i2s::$Unit> for Pin<$Id, $Mode> {
+            const ENABLED: bool = true;
+        }
+    };
+}
+
+#[hal_module(
+    "i2s-d21" => "i2s/d21.rs",
+    "i2s-d5x" => "i2s/d5x.rs",
+)]
+mod impls {}
+
+use impls::*;
+
+/// `CTRLA` bits, which have the same position in `SYNCBUSY`
+const CTRLA_SWRST: u8 = 1 << 0;
+const CTRLA_ENABLE: u8 = 1 << 1;
+const CTRLA_CKEN: u8 = 1 << 2;
+const CTRLA_SEREN: u8 = 1 << 4;
+/// `SYNCBUSY` data bit of serializer 0
+const SYNC_DATA: u16 = 1 << 8;
+
+/// `INTFLAG` offsets, shifted by the serializer flag mask
+const INTFLAG_RXRDY: u16 = 0;
+const INTFLAG_RXOR: u16 = 4;
+const INTFLAG_TXRDY: u16 = 8;
+const INTFLAG_TXUR: u16 = 12;
+
+/// Serializer modes
+const SERMODE_RX: u32 = 0;
+const SERMODE_TX: u32 = 1;
+const SERMODE_PDM2: u32 = 2;
+
+//==============================================================================
+// Clock units
+//==============================================================================
+
+/// Trait representing an I2S clock unit
+pub trait ClockUnit: Sealed {
+    /// Index of the clock unit
+    const NUM: usize;
+    /// Generic clock of the clock unit
+    type Clock;
+
+    /// Frequency of the generic clock
+    fn freq(clock: &Self::Clock) -> Hertz;
+}
+
+/// Clock unit 0
+pub enum ClockUnit0 {}
+
+impl Sealed for ClockUnit0 {}
+
+impl ClockUnit for ClockUnit0 {
+    const NUM: usize = 0;
+    type Clock = I2S0Clock;
+
+    #[inline]
+    fn freq(clock: &Self::Clock) -> Hertz {
+        clock.freq()
+    }
+}
+
+/// Clock unit 1
+pub enum ClockUnit1 {}
+
+impl Sealed for ClockUnit1 {}
+
+impl ClockUnit for ClockUnit1 {
+    const NUM: usize = 1;
+    type Clock = I2S1Clock;
+
+    #[inline]
+    fn freq(clock: &Self::Clock) -> Hertz {
+        clock.freq()
+    }
+}
+
+//==============================================================================
+// Pins
+//==============================================================================
+
+/// Pin which can be used as the serial clock of the clock unit `U`
+pub trait SckPin<U: ClockUnit>: Sealed {}
+
+/// Pin which can be used as the frame sync of the clock unit `U`
+pub trait FsPin<U: ClockUnit>: Sealed {}
+
+/// Pin which can be used as the master clock of the clock unit `U`
+pub trait MckPin<U: ClockUnit>: Sealed {}
+
+/// Optional frame sync pin
+///
+/// Implemented for all the [`FsPin`]s, and for [`NoneT`].
+pub trait OptionalFsPin<U: ClockUnit>: Sealed {
+    /// Whether the frame sync is connected to a pin
+    const ENABLED: bool;
+}
+
+impl<U: ClockUnit> OptionalFsPin<U> for NoneT {
+    const ENABLED: bool = false;
+}
+
+/// Optional master clock pin
+///
+/// Implemented for all the [`MckPin`]s, and for [`NoneT`].
+pub trait OptionalMckPin<U: ClockUnit>: Sealed {
+    /// Whether the master clock is connected to a pin
+    const ENABLED: bool;
+}
+
+impl<U: ClockUnit> OptionalMckPin<U> for NoneT {
+    const ENABLED: bool = false;
+}
+
+/// Pin which can be used as the data output of a serializer clocked by the
+/// clock unit `U`
+pub trait TxPin<U: ClockUnit>: OptionalTxPin<U> {}
+
+/// Pin which can be used as the data input of a serializer clocked by the
+/// clock unit `U`
+pub trait RxPin<U: ClockUnit>: OptionalRxPin<U> {}
+
+/// Optional transmitter data pin
+///
+/// Implemented for all the [`TxPin`]s, and for [`NoneT`].
+pub trait OptionalTxPin<U: ClockUnit>: Sealed {
+    /// Whether the transmitter is used
+    const ENABLED: bool;
+    /// Index of the serializer
+    const SERIALIZER: usize;
+}
+
+impl<U: ClockUnit> OptionalTxPin<U> for NoneT {
+    const ENABLED: bool = false;
+    const SERIALIZER: usize = 0;
+}
+
+/// Optional receiver data pin
+///
+/// Implemented for all the [`RxPin`]s, and for [`NoneT`].
+pub trait OptionalRxPin<U: ClockUnit>: Sealed {
+    /// Whether the receiver is used
+    const ENABLED: bool;
+    /// Index of the serializer
+    const SERIALIZER: usize;
+}
+
+impl<U: ClockUnit> OptionalRxPin<U> for NoneT {
+    const ENABLED: bool = false;
+    const SERIALIZER: usize = 0;
+}
+
+/// Pins of a clock unit
+pub struct ClockPins<SCK, FS = NoneT, MCK = NoneT> {
+    /// Serial clock
+    pub sck: SCK,
+    /// Frame sync, or [`NoneT`]
+    pub fs: FS,
+    /// Master clock output, or [`NoneT`]
+    pub mck: MCK,
+}
+
+impl<SCK, FS, MCK> Sealed for ClockPins<SCK, FS, MCK> {}
+
+/// Set of pins of the clock unit `U`
+///
+/// Implemented for the [`ClockPins`] whose pins all belong to `U`.
+pub trait ClockPinSet<U: ClockUnit>: Sealed {
+    /// Whether the frame sync is connected to a pin
+    const FS: bool;
+    /// Whether the master clock is connected to a pin
+    const MCK: bool;
+}
+
+impl<U, SCK, FS, MCK> ClockPinSet<U> for ClockPins<SCK, FS, MCK>
+where
+    U: ClockUnit,
+    SCK: SckPin<U>,
+    FS: OptionalFsPin<U>,
+    MCK: OptionalMckPin<U>,
+{
+    const FS: bool = FS::ENABLED;
+    const MCK: bool = MCK::ENABLED;
+}
+
+//==============================================================================
+// Config
+//==============================================================================
+
+/// Clock unit mode
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    /// `SCK` and `FS` are generated from the generic clock
+    Master,
+    /// `SCK` and `FS` are received from the pins
+    Slave,
+}
+
+/// Clock unit configuration
+#[derive(Clone, Copy)]
+pub struct Config {
+    /// Clock unit mode.
+    pub mode: Mode,
+    /// Frame rate in [`Mode::Master`]. `SCK` is obtained by dividing the
+    /// generic clock, which must be a multiple of
+    /// `sample_rate * slot bits * num_slots`.
+    pub sample_rate: Hertz,
+    /// Size of a slot.
+    pub slot_size: SlotSize,
+    /// Number of slots per frame (`1..=8`).
+    pub num_slots: u8,
+    /// Width of the frame sync pulse.
+    pub frame_sync_width: FrameSyncWidth,
+    /// Delay of the first data bit after the frame sync edge.
+    pub bit_delay: BitDelay,
+    /// Invert the frame sync.
+    pub invert_frame_sync: bool,
+    /// Divider of the generic clock producing the `MCK` output, in
+    /// [`Mode::Master`] with an `MCK` pin.
+    pub master_clock_divider: u8,
+}
+
+impl Default for Config {
+    /// Stereo I2S master at 48 kHz, with 32-bit slots
+    fn default() -> Self {
+        Self {
+            mode: Mode::Master,
+            sample_rate: Hertz::Hz(48_000),
+            slot_size: SlotSize::_32,
+            num_slots: 2,
+            frame_sync_width: FrameSyncWidth::Half,
+            bit_delay: BitDelay::I2s,
+            invert_frame_sync: false,
+            master_clock_divider: 1,
+        }
+    }
+}
+
+impl Config {
+    /// Number of `SCK` cycles in a frame
+    #[inline]
+    fn frame_bits(&self) -> u32 {
+        8 * (self.slot_size as u32 + 1) * self.num_slots.clamp(1, 8) as u32
+    }
+}
+
+/// Size of the data words
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum DataSize {
+    Bits32 = 0,
+    Bits24 = 1,
+    Bits20 = 2,
+    Bits18 = 3,
+    Bits16 = 4,
+    /// Two 16-bit words packed in each 32-bit data word
+    Bits16Compact = 5,
+    Bits8 = 6,
+    /// Two 8-bit words packed in each 16-bit data word
+    Bits8Compact = 7,
+}
+
+/// Alignment of the data
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Adjust {
+    Right = 0,
+    Left = 1,
+}
+
+/// Value of the bits of a slot or data word which are not part of the data
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Extend {
+    Zero = 0,
+    One = 1,
+    /// Extend with the most significant bit, ie sign extension
+    Msb = 2,
+    /// Extend with the least significant bit
+    Lsb = 3,
+}
+
+/// Level of the data output during disabled slots
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum LineDefault {
+    Zero = 0,
+    One = 1,
+    HighImpedance = 3,
+}
+
+/// Serializer configuration
+#[derive(Clone, Copy)]
+pub struct SerializerConfig {
+    /// Size of the data words.
+    pub data_size: DataSize,
+    /// Alignment of the data word within the slot.
+    pub slot_adjust: Adjust,
+    /// Alignment of the data word within the `DATA` register.
+    pub word_adjust: Adjust,
+    /// Value of the unused bits of the slots and data words.
+    pub extend: Extend,
+    /// Shift the least significant bit first.
+    pub lsb_first: bool,
+    /// Transmit the left channel data in the right slot too, or receive
+    /// only the left channel.
+    pub mono: bool,
+    /// Mask of the disabled slots.
+    pub disabled_slots: u8,
+    /// Output level during disabled slots, for a transmitter.
+    pub line_default: LineDefault,
+    /// Repeat the last data word on underrun instead of sending zeros, for a
+    /// transmitter.
+    pub repeat_on_underrun: bool,
+}
+
+impl Default for SerializerConfig {
+    /// 32-bit I2S data words
+    fn default() -> Self {
+        Self {
+            data_size: DataSize::Bits32,
+            slot_adjust: Adjust::Left,
+            word_adjust: Adjust::Right,
+            extend: Extend::Zero,
+            lsb_first: false,
+            mono: false,
+            disabled_slots: 0,
+            line_default: LineDefault::Zero,
+            repeat_on_underrun: false,
+        }
+    }
+}
+
+impl SerializerConfig {
+    /// Value of the serializer control register, except for the serializer
+    /// mode and clock unit selection
+    #[inline]
+    fn bits(&self) -> u32 {
+        (self.line_default as u32) << 2
+            | (self.repeat_on_underrun as u32) << 4
+            | (self.slot_adjust as u32) << 7
+            | (self.data_size as u32) << 8
+            | (self.word_adjust as u32) << 12
+            | (self.extend as u32) << 13
+            | (self.lsb_first as u32) << 15
+            | (self.disabled_slots as u32) << 16
+            | (self.mono as u32) << 24
+    }
+}
+
+//==============================================================================
+// Error
+//==============================================================================
+
+/// Errors returned by the I2S
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// A received data word was lost, because the previous one had not been
+    /// read yet
+    Overrun,
+    /// The transmitter ran out of data
+    Underrun,
+    #[cfg(feature = "dma")]
+    Dma(dmac::Error),
+}
+
+//==============================================================================
+// I2s
+//==============================================================================
+
+/// Inter-IC Sound controller, using the clock unit `U` with the pins `P`, and
+/// optionally a transmitter with the data pin `T` and a receiver with the
+/// data pin `R`
+pub struct I2s<U: ClockUnit, P: ClockPinSet<U>, T = NoneT, R = NoneT> {
+    i2s: pac::I2s,
+    clock: U::Clock,
+    pins: P,
+    tx: T,
+    rx: R,
+}
+
+impl<U: ClockUnit, P: ClockPinSet<U>> I2s<U, P> {
+    /// Reset the I2S and configure the clock unit
+    fn create(i2s: pac::I2s, clock: U::Clock, pins: P, config: Config) -> Self {
+        i2s.ctrla().write(|w| w.swrst().set_bit());
+        while i2s.syncbusy().read().bits() & CTRLA_SWRST as u16 != 0 {
+            core::hint::spin_loop();
+        }
+
+        let master = config.mode == Mode::Master;
+        assert!(master || P::FS, "a slave clock unit needs a FS pin");
+        let div = if master {
+            let sck = config.sample_rate.to_Hz() * config.frame_bits();
+            let div = (U::freq(&clock).to_Hz() + sck / 2) / sck.max(1);
+            div.clamp(1, MAX_CLOCK_DIVIDER as u32) as u8
+        } else {
+            1
+        };
+        let mck_div = config.master_clock_divider.clamp(1, MAX_CLOCK_DIVIDER);
+
+        i2s.clkctrl(U::NUM).write(|w| unsafe {
+            w.slotsize().variant(config.slot_size);
+            w.nbslots().bits(config.num_slots.clamp(1, 8) - 1);
+            w.fswidth().variant(config.frame_sync_width);
+            w.bitdelay().variant(config.bit_delay);
+            w.fsinv().bit(config.invert_frame_sync);
+            if master {
+                w.mcksel().gclk();
+                w.scksel().mckdiv();
+                w.fssel().sckdiv();
+                w.mckdiv().bits(div - 1);
+                w.mcken().bit(P::MCK);
+                w.mckoutdiv().bits(mck_div - 1)
+            } else {
+                w.scksel().sckpin();
+                w.fssel().fspin()
+            }
+        });
+
+        Self {
+            i2s,
+            clock,
+            pins,
+            tx: NoneT,
+            rx: NoneT,
+        }
+    }
+}
+
+impl<U, P, R> I2s<U, P, NoneT, R>
+where
+    U: ClockUnit,
+    P: ClockPinSet<U>,
+    R: OptionalRxPin<U>,
+{
+    /// Add a transmitter, using the data pin `pin`.
+    ///
+    /// [`enable`](I2s::enable) must be called again to start the
+    /// transmitter.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the receiver already uses the same serializer.
+    pub fn with_tx<T: TxPin<U>>(self, pin: T, config: SerializerConfig) -> I2s<U, P, T, R> {
+        assert!(!R::ENABLED || R::SERIALIZER != T::SERIALIZER);
+        self.write_serializer(T::SERIALIZER, SERMODE_TX, config);
+        I2s {
+            i2s: self.i2s,
+            clock: self.clock,
+            pins: self.pins,
+            tx: pin,
+            rx: self.rx,
+        }
+    }
+}
+
+impl<U, P, T> I2s<U, P, T, NoneT>
+where
+    U: ClockUnit,
+    P: ClockPinSet<U>,
+    T: OptionalTxPin<U>,
+{
+    /// Add a receiver, using the data pin `pin`.
+    ///
+    /// [`enable`](I2s::enable) must be called again to start the receiver.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the transmitter already uses the same serializer.
+    pub fn with_rx<R: RxPin<U>>(self, pin: R, config: SerializerConfig) -> I2s<U, P, T, R> {
+        self.add_receiver(pin, SERMODE_RX, config)
+    }
+
+    /// Add a receiver in `PDM2` mode, to capture one or two PDM microphones
+    /// on the data pin `pin`.
+    ///
+    /// [`enable`](I2s::enable) must be called again to start the receiver.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the transmitter already uses the same serializer.
+    pub fn with_pdm<R: RxPin<U>>(self, pin: R, config: SerializerConfig) -> I2s<U, P, T, R> {
+        self.add_receiver(pin, SERMODE_PDM2, config)
+    }
+
+    fn add_receiver<R: RxPin<U>>(
+        self,
+        pin: R,
+        mode: u32,
+        config: SerializerConfig,
+    ) -> I2s<U, P, T, R> {
+        assert!(!T::ENABLED || T::SERIALIZER != R::SERIALIZER);
+        self.write_serializer(R::SERIALIZER, mode, config);
+        I2s {
+            i2s: self.i2s,
+            clock: self.clock,
+            pins: self.pins,
+            tx: self.tx,
+            rx: pin,
+        }
+    }
+}
+
+impl<U, P, T, R> I2s<U, P, T, R>
+where
+    U: ClockUnit,
+    P: ClockPinSet<U>,
+    T: OptionalTxPin<U>,
+    R: OptionalRxPin<U>,
+{
+    /// Write the control register of a serializer, which must be disabled
+    #[inline]
+    fn write_serializer(&self, serializer: usize, mode: u32, config: SerializerConfig) {
+        let bits = config.bits() | mode | (U::NUM as u32) << 5;
+        self.serializer_ctrl(serializer, bits);
+    }
+
+    #[inline]
+    fn sync(&self, bits: u16) {
+        while self.i2s.syncbusy().read().bits() & bits != 0 {
+            core::hint::spin_loop();
+        }
+    }
+
+    /// Enable the clock unit and the serializers.
+    pub fn enable(&mut self) {
+        let mut bits = CTRLA_ENABLE | CTRLA_CKEN << U::NUM;
+        if T::ENABLED {
+            bits |= CTRLA_SEREN << T::SERIALIZER;
+        }
+        if R::ENABLED {
+            bits |= CTRLA_SEREN << R::SERIALIZER;
+        }
+        self.i2s.ctrla().write(|w| unsafe { w.bits(bits) });
+        self.sync(bits as u16);
+    }
+
+    /// Disable the clock unit and the serializers.
+    pub fn disable(&mut self) {
+        self.i2s.ctrla().write(|w| unsafe { w.bits(0) });
+        self.sync(CTRLA_ENABLE as u16);
+    }
+
+    /// Frame rate produced in [`Mode::Master`], which may differ from the
+    /// requested [`Config::sample_rate`] due to the integer clock divider.
+    pub fn sample_rate(&self) -> Hertz {
+        let clkctrl = self.i2s.clkctrl(U::NUM).read();
+        let slot_bits = 8 * (clkctrl.slotsize().bits() as u32 + 1);
+        let frame_bits = slot_bits * (clkctrl.nbslots().bits() as u32 + 1);
+        let div = clkctrl.mckdiv().bits() as u32 + 1;
+        Hertz::from_raw(U::freq(&self.clock).to_Hz() / div / frame_bits)
+    }
+
+    /// Disable and reset the I2S, and return the underlying resources.
+    pub fn free(self) -> (pac::I2s, U::Clock, P, T, R) {
+        self.i2s.ctrla().write(|w| w.swrst().set_bit());
+        self.sync(CTRLA_SWRST as u16);
+        (self.i2s, self.clock, self.pins, self.tx, self.rx)
+    }
+}
+
+impl<U, P, T, R> I2s<U, P, T, R>
+where
+    U: ClockUnit,
+    P: ClockPinSet<U>,
+    T: TxPin<U>,
+    R: OptionalRxPin<U>,
+{
+    /// Write the next data word to the transmitter.
+    ///
+    /// In [`DataSize::Bits16Compact`] and [`DataSize::Bits8Compact`], each
+    /// word holds the data of two slots.
+    ///
+    /// Returns [`Error::Underrun`] once if the transmitter ran out of data
+    /// since the last write, in which case `word` is not written.
+    pub fn write(&mut self, word: u32) -> nb::Result<(), Error> {
+        let mask = flag_mask(T::SERIALIZER);
+        let flags = self.i2s.intflag().read().bits();
+        if flags & mask << INTFLAG_TXUR != 0 {
+            self.i2s
+                .intflag()
+                .write(|w| unsafe { w.bits(mask << INTFLAG_TXUR) });
+            return Err(nb::Error::Other(Error::Underrun));
+        }
+        if flags & mask << INTFLAG_TXRDY == 0 {
+            return Err(nb::Error::WouldBlock);
+        }
+
+        // SAFETY: Only the data register of our own transmitter is written
+        unsafe { self.data_ptr(T::SERIALIZER).write_volatile(word) };
+        self.sync(SYNC_DATA << T::SERIALIZER);
+        Ok(())
+    }
+}
+
+impl<U, P, T, R> I2s<U, P, T, R>
+where
+    U: ClockUnit,
+    P: ClockPinSet<U>,
+    T: OptionalTxPin<U>,
+    R: RxPin<U>,
+{
+    /// Read the next data word from the receiver.
+    ///
+    /// Returns [`Error::Overrun`] once if a data word was lost since the last
+    /// read.
+    pub fn read(&mut self) -> nb::Result<u32, Error> {
+        let mask = flag_mask(R::SERIALIZER);
+        let flags = self.i2s.intflag().read().bits();
+        if flags & mask << INTFLAG_RXOR != 0 {
+            self.i2s
+                .intflag()
+                .write(|w| unsafe { w.bits(mask << INTFLAG_RXOR) });
+            return Err(nb::Error::Other(Error::Overrun));
+        }
+        if flags & mask << INTFLAG_RXRDY == 0 {
+            return Err(nb::Error::WouldBlock);
+        }
+
+        self.sync(SYNC_DATA << R::SERIALIZER);
+        // SAFETY: Only the data register of our own receiver is read
+        Ok(unsafe { self.data_ptr(R::SERIALIZER).read_volatile() })
+    }
+}
+
+//==============================================================================
+// DMA
+//==============================================================================
+
+/// Data register of a serializer, for use as a DMA source or destination
+#[cfg(all(feature = "dma", feature = "async"))]
+struct DataReg<W>(*mut W);
+
+#[cfg(all(feature = "dma", feature = "async"))]
+unsafe impl<W: Beat> Buffer for DataReg<W> {
+    type Beat = W;
+
+    #[inline]
+    fn dma_ptr(&mut self) -> *mut Self::Beat {
+        self.0
+    }
+
+    #[inline]
+    fn incrementing(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn buffer_len(&self) -> usize {
+        1
+    }
+}
+
+#[cfg(all(feature = "dma", feature = "async"))]
+impl<U, P, T, R> I2s<U, P, T, R>
+where
+    U: ClockUnit,
+    P: ClockPinSet<U>,
+    T: TxPin<U>,
+    R: OptionalRxPin<U>,
+{
+    /// Continuously transmit audio from `buffer`, using a DMA channel.
+    ///
+    /// `buffer` is split into two halves, which are first filled by calling
+    /// `fill`. The DMAC then transmits them alternately, and calls `fill`
+    /// with each half as soon as it has been transmitted, until `fill`
+    /// returns [`ControlFlow::Break`]. The beat size `W` must match the
+    /// [`DataSize`] of the transmitter.
+    ///
+    /// Returns [`dmac::Error::LengthMismatch`] if the length of `buffer` is
+    /// not a non-zero even number.
+    pub async fn write_stream<Ch, W, F, Ret>(
+        &mut self,
+        channel: &mut Ch,
+        buffer: &mut [W],
+        mut fill: F,
+    ) -> Result<Ret, Error>
+    where
+        Ch: AnyChannel<Status = ReadyFuture>,
+        W: Beat,
+        F: FnMut(&mut [W]) -> ControlFlow<Ret>,
+    {
+        let data = DataReg(self.data_ptr(T::SERIALIZER) as *mut W);
+        let mut storage = [DmacDescriptor::default(); 2];
+        let mut chain = PingPong::transmit(&mut storage, buffer, data)?;
+        let (first, second) = chain.halves_mut();
+        for half in [first, second] {
+            if let ControlFlow::Break(result) = fill(half) {
+                return Ok(result);
+            }
+        }
+
+        // SAFETY: `xfer` is never forgotten, and stops the transfer when it goes
+        // out of scope, before `chain`. As with `transfer_future`, the future
+        // returned by this method must not be forgotten either.
+        let mut xfer = unsafe {
+            channel.as_mut().transfer_linked(
+                &mut chain,
+                tx_trigger(T::SERIALIZER),
+                TRIGGER_ACTION,
+            )?
+        };
+        loop {
+            // The chain is circular, so it never runs out of blocks
+            xfer.next_block().await?;
+            if let Some(half) = xfer.completed_half() {
+                if let ControlFlow::Break(result) = fill(half) {
+                    return Ok(result);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(all(feature = "dma", feature = "async"))]
+impl<U, P, T, R> I2s<U, P, T, R>
+where
+    U: ClockUnit,
+    P: ClockPinSet<U>,
+    T: OptionalTxPin<U>,
+    R: RxPin<U>,
+{
+    /// Continuously receive audio into `buffer`, using a DMA channel.
+    ///
+    /// `buffer` is split into two halves, which the DMAC fills alternately.
+    /// `process` is called with each half as soon as it is full, until it
+    /// returns [`ControlFlow::Break`]. The beat size `W` must match the
+    /// [`DataSize`] of the receiver.
+    ///
+    /// Returns [`dmac::Error::LengthMismatch`] if the length of `buffer` is
+    /// not a non-zero even number.
+    pub async fn read_stream<Ch, W, F, Ret>(
+        &mut self,
+        channel: &mut Ch,
+        buffer: &mut [W],
+        mut process: F,
+    ) -> Result<Ret, Error>
+    where
+        Ch: AnyChannel<Status = ReadyFuture>,
+        W: Beat,
+        F: FnMut(&[W]) -> ControlFlow<Ret>,
+    {
+        let data = DataReg(self.data_ptr(R::SERIALIZER) as *mut W);
+        let mut storage = [DmacDescriptor::default(); 2];
+        let mut chain = PingPong::receive(&mut storage, data, buffer)?;
+
+        // Discard any stale data and overrun
+        let mask = flag_mask(R::SERIALIZER);
+        self.i2s
+            .intflag()
+            .write(|w| unsafe { w.bits(mask << INTFLAG_RXOR) });
+
+        // SAFETY: `xfer` is never forgotten, and stops the transfer when it goes
+        // out of scope, before `chain`. As with `transfer_future`, the future
+        // returned by this method must not be forgotten either.
+        let mut xfer = unsafe {
+            channel.as_mut().transfer_linked(
+                &mut chain,
+                rx_trigger(R::SERIALIZER),
+                TRIGGER_ACTION,
+            )?
+        };
+        loop {
+            // The chain is circular, so it never runs out of blocks
+            xfer.next_block().await?;
+            if let Some(half) = xfer.completed_half() {
+                if let ControlFlow::Break(result) = process(half) {
+                    return Ok(result);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,170 @@
+//! I2S for SAMD21 chips
+
+use atsamd_hal_macros::hal_cfg;
+
+use crate::gpio::{AlternateG, Pin};
+use crate::pac::{self, Pm};
+
+use super::{ClockPinSet, ClockUnit, Config, I2s};
+
+#[cfg(all(feature = "dma", feature = "async"))]
+use crate::dmac::{TriggerAction, TriggerSource};
+
+#[hal_cfg("pa07")]
+use crate::gpio::PA07;
+#[hal_cfg("pa08")]
+use crate::gpio::PA08;
+#[hal_cfg("pa09")]
+use crate::gpio::PA09;
+#[hal_cfg("pa10")]
+use crate::gpio::PA10;
+#[hal_cfg("pa11")]
+use crate::gpio::PA11;
+#[hal_cfg("pa19")]
+use crate::gpio::PA19;
+#[hal_cfg("pa20")]
+use crate::gpio::PA20;
+#[hal_cfg("pa21")]
+use crate::gpio::PA21;
+#[hal_cfg("pb10")]
+use crate::gpio::PB10;
+#[hal_cfg("pb11")]
+use crate::gpio::PB11;
+#[hal_cfg("pb12")]
+use crate::gpio::PB12;
+#[hal_cfg("pb16")]
+use crate::gpio::PB16;
+#[hal_cfg("pb17")]
+use crate::gpio::PB17;
+
+/// Maximum value of the `SCK` and `MCK` output dividers
+pub(super) const MAX_CLOCK_DIVIDER: u8 = 32;
+
+/// DMA trigger action of the serializers
+#[cfg(all(feature = "dma", feature = "async"))]
+pub(super) const TRIGGER_ACTION: TriggerAction = TriggerAction::Beat;
+
+//==============================================================================
+// Pins
+//==============================================================================
+
+clock_pins!(AlternateG:
+    #[hal_cfg("pa09")]
+    PA09: MckPin<ClockUnit0>,
+    #[hal_cfg("pa10")]
+    PA10: SckPin<ClockUnit0>,
+    #[hal_cfg("pa11")]
+    PA11: FsPin<ClockUnit0>,
+    #[hal_cfg("pa20")]
+    PA20: SckPin<ClockUnit0>,
+    #[hal_cfg("pa21")]
+    PA21: FsPin<ClockUnit0>,
+    #[hal_cfg("pb10")]
+    PB10: MckPin<ClockUnit1>,
+    #[hal_cfg("pb11")]
+    PB11: SckPin<ClockUnit1>,
+    #[hal_cfg("pb12")]
+    PB12: FsPin<ClockUnit1>,
+    #[hal_cfg("pb17")]
+    PB17: MckPin<ClockUnit0>,
+);
+
+/// Implement the data pin traits for the pins of a serializer, which can
+/// transmit or receive with either clock unit
+macro_rules! data_pins {
+    ($($(#[$cfg:meta])* $Id:ident: $Serializer:literal),+ $(,)?) => {
+        $(
+            $(#[$cfg])*
+            impl<U: ClockUnit> super::TxPin<U> for Pin<$Id, AlternateG> {}
+
+            $(#[$cfg])*
+            impl<U: ClockUnit> super::OptionalTxPin<U> for Pin<$Id, AlternateG> {
+                const ENABLED: bool = true;
+                const SERIALIZER: usize = $Serializer;
+            }
+
+            $(#[$cfg])*
+            impl<U: ClockUnit> super::RxPin<U> for Pin<$Id, AlternateG> {}
+
+            $(#[$cfg])*
+            impl<U: ClockUnit> super::OptionalRxPin<U> for Pin<$Id, AlternateG> {
+                const ENABLED: bool = true;
+                const SERIALIZER: usize = $Serializer;
+            }
+        )+
+    };
+}
+
+data_pins!(
+    #[hal_cfg("pa07")]
+    PA07: 0,
+    #[hal_cfg("pa08")]
+    PA08: 1,
+    #[hal_cfg("pa19")]
+    PA19: 0,
+    #[hal_cfg("pb16")]
+    PB16: 1,
+);
+
+//==============================================================================
+// I2s
+//==============================================================================
+
+impl<U: ClockUnit, P: ClockPinSet<U>> I2s<U, P> {
+    /// Create a new [`I2s`] using the clock unit `U`, and configure it.
+    ///
+    /// The transmitter and receiver are then added with
+    /// [`with_tx`](I2s::with_tx), [`with_rx`](I2s::with_rx) or
+    /// [`with_pdm`](I2s::with_pdm).
+    ///
+    /// # Panics
+    ///
+    /// Panics if a [`Mode::Slave`](super::Mode::Slave) clock unit has no FS
+    /// pin.
+    pub fn new(pm: &mut Pm, i2s: pac::I2s, clock: U::Clock, pins: P, config: Config) -> Self {
+        pm.apbcmask().modify(|_, w| w.i2s_().set_bit());
+        Self::create(i2s, clock, pins, config)
+    }
+}
+
+impl<U: ClockUnit, P: ClockPinSet<U>, T, R> I2s<U, P, T, R> {
+    /// Write the control register of a serializer
+    #[inline]
+    pub(super) fn serializer_ctrl(&self, serializer: usize, bits: u32) {
+        self.i2s
+            .serctrl(serializer)
+            .write(|w| unsafe { w.bits(bits) });
+    }
+
+    /// Pointer to the data register of a serializer
+    #[inline]
+    pub(super) fn data_ptr(&self, serializer: usize) -> *mut u32 {
+        self.i2s.data(serializer).as_ptr()
+    }
+}
+
+/// Mask of the `INTFLAG` bits of a serializer
+#[inline]
+pub(super) fn flag_mask(serializer: usize) -> u16 {
+    1 << serializer
+}
+
+/// DMA trigger of a transmitting serializer
+#[cfg(all(feature = "dma", feature = "async"))]
+#[inline]
+pub(super) fn tx_trigger(serializer: usize) -> TriggerSource {
+    match serializer {
+        0 => TriggerSource::I2sTx0,
+        _ => TriggerSource::I2sTx1,
+    }
+}
+
+/// DMA trigger of a receiving serializer
+#[cfg(all(feature = "dma", feature = "async"))]
+#[inline]
+pub(super) fn rx_trigger(serializer: usize) -> TriggerSource {
+    match serializer {
+        0 => TriggerSource::I2sRx0,
+        _ => TriggerSource::I2sRx1,
+    }
+}
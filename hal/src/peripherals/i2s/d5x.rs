@@ -0,0 +1,179 @@
+//! I2S for SAMx5x chips
+
+use atsamd_hal_macros::hal_cfg;
+
+use crate::gpio::{AlternateJ, Pin};
+use crate::pac::{self, Mclk};
+
+use super::{ClockPinSet, ClockUnit, ClockUnit0, Config, I2s};
+
+#[cfg(all(feature = "dma", feature = "async"))]
+use crate::dmac::{TriggerAction, TriggerSource};
+
+#[hal_cfg("pa08")]
+use crate::gpio::PA08;
+#[hal_cfg("pa09")]
+use crate::gpio::PA09;
+#[hal_cfg("pa10")]
+use crate::gpio::PA10;
+#[hal_cfg("pa11")]
+use crate::gpio::PA11;
+#[hal_cfg("pa20")]
+use crate::gpio::PA20;
+#[hal_cfg("pa21")]
+use crate::gpio::PA21;
+#[hal_cfg("pa22")]
+use crate::gpio::PA22;
+#[hal_cfg("pa23")]
+use crate::gpio::PA23;
+#[hal_cfg("pb10")]
+use crate::gpio::PB10;
+#[hal_cfg("pb12")]
+use crate::gpio::PB12;
+#[hal_cfg("pb16")]
+use crate::gpio::PB16;
+#[hal_cfg("pb17")]
+use crate::gpio::PB17;
+
+/// Maximum value of the `SCK` and `MCK` output dividers
+pub(super) const MAX_CLOCK_DIVIDER: u8 = 64;
+
+/// DMA trigger action of the serializers
+#[cfg(all(feature = "dma", feature = "async"))]
+pub(super) const TRIGGER_ACTION: TriggerAction = TriggerAction::Burst;
+
+/// `TXCTRL` has no serializer mode and clock unit selection
+const TXCTRL_MASK: u32 = !(0b11 | 1 << 5);
+
+//==============================================================================
+// Pins
+//==============================================================================
+
+clock_pins!(AlternateJ:
+    #[hal_cfg("pa08")]
+    PA08: MckPin<ClockUnit0>,
+    #[hal_cfg("pa09")]
+    PA09: FsPin<ClockUnit0>,
+    #[hal_cfg("pa10")]
+    PA10: SckPin<ClockUnit0>,
+    #[hal_cfg("pa20")]
+    PA20: FsPin<ClockUnit0>,
+    #[hal_cfg("pa23")]
+    PA23: FsPin<ClockUnit1>,
+    #[hal_cfg("pb12")]
+    PB12: SckPin<ClockUnit1>,
+    #[hal_cfg("pb16")]
+    PB16: SckPin<ClockUnit0>,
+    #[hal_cfg("pb17")]
+    PB17: MckPin<ClockUnit0>,
+);
+
+/// Implement the data pin traits. The transmitter is serializer 0 and always
+/// uses clock unit 0, the receiver is serializer 1 and can use either unit.
+macro_rules! data_pins {
+    ($($(#[$cfg:meta])* $Id:ident: $Trait:ident),+ $(,)?) => {
+        $(
+            data_pins!(@impl $Trait, $(#[$cfg])* $Id);
+        )+
+    };
+    (@impl TxPin, $(#[$cfg:meta])* $Id:ident) => {
+        $(#[$cfg])*
+        impl super::TxPin<ClockUnit0> for Pin<$Id, AlternateJ> {}
+
+        $(#[$cfg])*
+        impl super::OptionalTxPin<ClockUnit0> for Pin<$Id, AlternateJ> {
+            const ENABLED: bool = true;
+            const SERIALIZER: usize = 0;
+        }
+    };
+    (@impl RxPin, $(#[$cfg:meta])* $Id:ident) => {
+        $(#[$cfg])*
+        impl<U: ClockUnit> super::RxPin<U> for Pin<$Id, AlternateJ> {}
+
+        $(#[$cfg])*
+        impl<U: ClockUnit> super::OptionalRxPin<U> for Pin<$Id, AlternateJ> {
+            const ENABLED: bool = true;
+            const SERIALIZER: usize = 1;
+        }
+    };
+}
+
+data_pins!(
+    #[hal_cfg("pa11")]
+    PA11: TxPin,
+    #[hal_cfg("pa21")]
+    PA21: TxPin,
+    #[hal_cfg("pa22")]
+    PA22: RxPin,
+    #[hal_cfg("pb10")]
+    PB10: RxPin,
+);
+
+//==============================================================================
+// I2s
+//==============================================================================
+
+impl<U: ClockUnit, P: ClockPinSet<U>> I2s<U, P> {
+    /// Create a new [`I2s`] using the clock unit `U`, and configure it.
+    ///
+    /// The transmitter and receiver are then added with
+    /// [`with_tx`](I2s::with_tx), [`with_rx`](I2s::with_rx) or
+    /// [`with_pdm`](I2s::with_pdm). The transmitter is only available with
+    /// [`ClockUnit0`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if a [`Mode::Slave`](super::Mode::Slave) clock unit has no FS
+    /// pin.
+    pub fn new(mclk: &mut Mclk, i2s: pac::I2s, clock: U::Clock, pins: P, config: Config) -> Self {
+        mclk.apbdmask().modify(|_, w| w.i2s_().set_bit());
+        Self::create(i2s, clock, pins, config)
+    }
+}
+
+impl<U: ClockUnit, P: ClockPinSet<U>, T, R> I2s<U, P, T, R> {
+    /// Write the control register of a serializer
+    #[inline]
+    pub(super) fn serializer_ctrl(&self, serializer: usize, bits: u32) {
+        if serializer == 0 {
+            self.i2s
+                .txctrl()
+                .write(|w| unsafe { w.bits(bits & TXCTRL_MASK) });
+        } else {
+            self.i2s.rxctrl().write(|w| unsafe { w.bits(bits) });
+        }
+    }
+
+    /// Pointer to the data register of a serializer
+    #[inline]
+    pub(super) fn data_ptr(&self, serializer: usize) -> *mut u32 {
+        if serializer == 0 {
+            self.i2s.txdata().as_ptr()
+        } else {
+            self.i2s.rxdata().as_ptr()
+        }
+    }
+}
+
+/// Mask of the `INTFLAG` bits of a serializer
+///
+/// The flags are reported per DMA channel, and both are used in single
+/// channel mode.
+#[inline]
+pub(super) fn flag_mask(_serializer: usize) -> u16 {
+    0b11
+}
+
+/// DMA trigger of the transmitter
+#[cfg(all(feature = "dma", feature = "async"))]
+#[inline]
+pub(super) fn tx_trigger(_serializer: usize) -> TriggerSource {
+    TriggerSource::I2sTx0
+}
+
+/// DMA trigger of the receiver
+#[cfg(all(feature = "dma", feature = "async"))]
+#[inline]
+pub(super) fn rx_trigger(_serializer: usize) -> TriggerSource {
+    TriggerSource::I2sRx0
+}
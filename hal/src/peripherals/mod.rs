@@ -6,6 +6,15 @@ use atsamd_hal_macros::{hal_cfg, hal_module};
 )]
 pub mod adc {}
 
+#[hal_module("ac")]
+pub mod ac {}
+
+#[hal_module(
+    any("dac-d11", "dac-d21") => "dac/d11.rs",
+    "dac-d5x" => "dac/d5x.rs",
+)]
+pub mod dac {}
+
 #[hal_module(
     any("nvmctrl-d11", "nvmctrl-d21") => "calibration/d11.rs",
     "nvmctrl-d5x" => "calibration/d5x.rs",
@@ -21,11 +30,11 @@ pub mod timer {}
 #[cfg(feature = "device")]
 pub mod eic;
 
+#[hal_module("evsys")]
+pub mod evsys {}
+
 #[cfg(feature = "usb")]
-#[hal_module(
-    any("usb-d11", "usb-d21") => "usb/d11/mod.rs",
-    "usb-d5x" => "usb/d5x/mod.rs",
-)]
+#[hal_module("usb")]
 pub mod usb {}
 
 #[hal_module(
@@ -35,7 +44,7 @@ pub mod usb {}
 pub mod pwm {}
 
 #[hal_module(
-    any("clock-d11", "clock-d21") => "clock/d11.rs",
+    any("clock-d11", "clock-d21") => "clock/d11/mod.rs",
     "clock-d5x" => "clock/d5x/mod.rs",
 )]
 pub mod clock {}
@@ -52,6 +61,21 @@ pub mod pukcc {}
 #[hal_module("qspi")]
 pub mod qspi {}
 
+#[hal_module("pdec")]
+pub mod pdec {}
+
+#[hal_module("i2s")]
+pub mod i2s {}
+
+#[hal_module("sdhc0")]
+pub mod sdhc {}
+
+#[hal_module("gmac")]
+pub mod gmac {}
+
+#[hal_module("pcc")]
+pub mod pcc {}
+
 #[hal_module("trng")]
 pub mod trng {}
 
@@ -68,6 +92,9 @@ pub mod can {}
 #[hal_module("wdt")]
 pub mod watchdog {}
 
+#[hal_module(any("pm-d11", "pm-d21", "pm-d5x"))]
+pub mod power {}
+
 #[hal_module(any("pm-d11", "pm-d21", "rstc-d5x"))]
 mod reset_cause {}
 
@@ -360,15 +360,7 @@ impl Nvm {
     /// Read the calibration area for temperatures
     #[inline]
     pub fn temperatures_calibration_area(&self) -> TemperaturesCalibrationArea {
-        let mut buffer = 0_u128;
-        let base_addr: *const u8 = 0x0080_0100 as *const u8;
-
-        for i in 0..11 {
-            buffer |= unsafe { core::ptr::read_volatile(base_addr.offset(i as isize)) as u128 }
-                << (i * 8);
-        }
-
-        TemperaturesCalibrationArea(buffer)
+        read_temperatures_calibration_area()
     }
 
     /// Enable security bit
@@ -916,6 +908,22 @@ bitfield! {
     pub usb_trim, _: 44, 42;
 }
 
+/// Read the calibration area for temperatures, without requiring ownership
+/// of the NVM controller. The area lives in the read-only NVM software
+/// calibration row, so reading it never conflicts with an [`Nvm`].
+#[inline]
+pub(crate) fn read_temperatures_calibration_area() -> TemperaturesCalibrationArea {
+    let mut buffer = 0_u128;
+    let base_addr: *const u8 = 0x0080_0100 as *const u8;
+
+    for i in 0..11 {
+        buffer |=
+            unsafe { core::ptr::read_volatile(base_addr.offset(i as isize)) as u128 } << (i * 8);
+    }
+
+    TemperaturesCalibrationArea(buffer)
+}
+
 bitfield! {
     #[derive(Copy, Clone, Default)]
     /// POD-style struct representing NVM calibration area for
@@ -936,7 +944,7 @@ bitfield! {
     /// Access the `vph` field. Setter is not provided.
     pub vph, _: 63, 52;
     /// Access the `vcl` field. Setter is not provided.
-    pub vcl, _: 75, 63;
+    pub vcl, _: 75, 64;
     /// Access the `vch` field. Setter is not provided.
     pub vch, _: 87, 76;
 }
@@ -0,0 +1,476 @@
+//! # Parallel Capture Controller
+//!
+//! The Parallel Capture Controller (PCC) samples an 8, 10, 12 or 14-bit
+//! parallel data bus on the rising edges of its `CLK` pin, such as the output
+//! of a CMOS camera sensor. Data is only sampled while the two data enable
+//! pins, `DEN1` and `DEN2`, are high, which matches the `VSYNC` and `HSYNC`
+//! outputs of OV7670-style cameras. With [`Config::always_sample`], the data
+//! enable pins are ignored.
+//!
+//! Consecutive samples can be packed by two or four into each word read from
+//! the PCC, to reduce the number of DMA beats. With
+//! [`Config::clear_if_disabled`], a partially packed word is discarded when a
+//! data enable pin goes low, so that each line or frame starts on a word
+//! boundary.
+//!
+//! ## Creating a [`Pcc`]
+//!
+//! Each PCC signal is available on a single pin, and the data pins are
+//! grouped in a [`Data8`], [`Data10`], [`Data12`] or [`Data14`] struct,
+//! which sets the width of the data bus.
+//!
+//! ```no_run
+//! use atsamd_hal::pcc::{Config, Data8, Packing, Pcc, Pins};
+//!
+//! let pcc_pins = Pins {
+//!     den1: pins.pa12.into(),
+//!     den2: pins.pa13.into(),
+//!     clk: pins.pa14.into(),
+//!     data: Data8 {
+//!         d0: pins.pa16.into(),
+//!         d1: pins.pa17.into(),
+//!         d2: pins.pa18.into(),
+//!         d3: pins.pa19.into(),
+//!         d4: pins.pa20.into(),
+//!         d5: pins.pa21.into(),
+//!         d6: pins.pa22.into(),
+//!         d7: pins.pa23.into(),
+//!     },
+//! };
+//! let config = Config {
+//!     packing: Packing::Word,
+//!     ..Default::default()
+//! };
+//! let mut pcc = Pcc::new(&mut peripherals.mclk, peripherals.pcc, pcc_pins, config);
+//! pcc.enable();
+//! let four_pixels = nb::block!(pcc.read())?;
+//! ```
+//!
+//! ## Frame capture <span class="stab portability" title="Available on crate features `dma` and `async` only"><code>dma</code> and <code>async</code></span>
+//!
+//! With the `dma` and `async` features enabled, a DMA channel is attached
+//! with [`Pcc::with_dma_channel`], and [`Pcc::capture_frame`] fills a buffer
+//! with the samples. The beat size of the buffer must match the [`Packing`]
+//! and the width of the data bus.
+//!
+//! ```no_run
+//! let mut pcc = pcc.with_dma_channel(dma_channel);
+//! let mut frame = [0u32; 320 * 240 * 2 / 4];
+//! pcc.capture_frame(&mut frame).await?;
+//! ```
+//!
+//! The capture starts with the first sample following the call, and the PCC
+//! does not detect the start of a frame by itself: to capture whole frames,
+//! start it while `VSYNC` is low, for instance from an
+//! [`eic`](crate::eic) interrupt on its falling edge. The DMA channel must be
+//! configured for `async` operation, and the `DMAC` interrupt bound to the
+//! [`dmac::InterruptHandler`](crate::dmac::InterruptHandler).
+
+use atsamd_hal_macros::hal_cfg;
+
+use crate::gpio::{AlternateK, Pin};
+use crate::gpio::{PA12, PA13, PA14, PA16, PA17, PA18, PA19, PA20, PA21, PA22, PA23};
+use crate::pac::pcc::mr::{Dsizeselect, Isizeselect};
+use crate::pac::{self, Mclk};
+use crate::typelevel::{NoneT, Sealed};
+
+#[hal_cfg("pb14")]
+use crate::gpio::{PB14, PB15};
+#[hal_cfg("pc12")]
+use crate::gpio::{PC12, PC13, PC14, PC15};
+
+#[cfg(feature = "dma")]
+use crate::dmac;
+#[cfg(all(feature = "dma", feature = "async"))]
+use crate::dmac::{AnyChannel, Beat, Buffer, ReadyFuture, TriggerAction, TriggerSource};
+
+//==============================================================================
+// Pins
+//==============================================================================
+
+/// Width of the data bus
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DataWidth {
+    Bits8,
+    Bits10,
+    Bits12,
+    Bits14,
+}
+
+/// Data pins `DATA0` to `DATA7`
+pub struct Data8 {
+    pub d0: Pin<PA16, AlternateK>,
+    pub d1: Pin<PA17, AlternateK>,
+    pub d2: Pin<PA18, AlternateK>,
+    pub d3: Pin<PA19, AlternateK>,
+    pub d4: Pin<PA20, AlternateK>,
+    pub d5: Pin<PA21, AlternateK>,
+    pub d6: Pin<PA22, AlternateK>,
+    pub d7: Pin<PA23, AlternateK>,
+}
+
+/// Data pins `DATA0` to `DATA9`
+#[hal_cfg("pb14")]
+pub struct Data10 {
+    pub low: Data8,
+    pub d8: Pin<PB14, AlternateK>,
+    pub d9: Pin<PB15, AlternateK>,
+}
+
+/// Data pins `DATA0` to `DATA11`
+#[hal_cfg("pc12")]
+pub struct Data12 {
+    pub low: Data10,
+    pub d10: Pin<PC12, AlternateK>,
+    pub d11: Pin<PC13, AlternateK>,
+}
+
+/// Data pins `DATA0` to `DATA13`
+#[hal_cfg("pc12")]
+pub struct Data14 {
+    pub low: Data12,
+    pub d12: Pin<PC14, AlternateK>,
+    pub d13: Pin<PC15, AlternateK>,
+}
+
+/// Set of data pins
+///
+/// Implemented for [`Data8`], [`Data10`], [`Data12`] and [`Data14`].
+pub trait DataBus: Sealed {
+    /// Width of the data bus
+    const WIDTH: DataWidth;
+}
+
+macro_rules! data_bus {
+    ($( $( #[$cfg:meta] )* $Data:ident: $Width:ident ),+ $(,)?) => {
+        $(
+            $( #[$cfg] )*
+            impl Sealed for $Data {}
+
+            $( #[$cfg] )*
+            impl DataBus for $Data {
+                const WIDTH: DataWidth = DataWidth::$Width;
+            }
+        )+
+    };
+}
+
+data_bus!(
+    Data8: Bits8,
+    #[hal_cfg("pb14")]
+    Data10: Bits10,
+    #[hal_cfg("pc12")]
+    Data12: Bits12,
+    #[hal_cfg("pc12")]
+    Data14: Bits14,
+);
+
+/// Pins of a [`Pcc`]
+pub struct Pins<B: DataBus> {
+    pub den1: Pin<PA12, AlternateK>,
+    pub den2: Pin<PA13, AlternateK>,
+    pub clk: Pin<PA14, AlternateK>,
+    pub data: B,
+}
+
+//==============================================================================
+// Config
+//==============================================================================
+
+/// Packing of the samples into the words read from the PCC
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Packing {
+    /// One sample per byte with an 8-bit bus, or per half-word with a wider
+    /// bus
+    #[default]
+    None,
+    /// Two samples per half-word, with an 8-bit bus only
+    HalfWord,
+    /// Four samples per word with an 8-bit bus, or two with a wider bus
+    Word,
+}
+
+/// Selection of the samples to keep
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Sampling {
+    /// Keep all the samples
+    #[default]
+    All,
+    /// Keep one sample out of two, starting with the first one
+    Even,
+    /// Keep one sample out of two, starting with the second one
+    Odd,
+}
+
+/// Data enable pins whose falling edge discards a partially packed word
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ClearIfDisabled {
+    #[default]
+    Never,
+    Den1,
+    Den2,
+    Both,
+}
+
+/// Configuration of a [`Pcc`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Config {
+    /// Packing of the samples
+    pub packing: Packing,
+    /// Selection of the samples to keep
+    pub sampling: Sampling,
+    /// Sample the data bus regardless of the data enable pins
+    pub always_sample: bool,
+    /// Discard a partially packed word when a data enable pin goes low
+    pub clear_if_disabled: ClearIfDisabled,
+}
+
+//==============================================================================
+// Error
+//==============================================================================
+
+/// Errors returned by the PCC
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// Samples were lost, because the previous word had not been read yet
+    Overrun,
+    #[cfg(feature = "dma")]
+    Dma(dmac::Error),
+}
+
+//==============================================================================
+// Pcc
+//==============================================================================
+
+/// Parallel Capture Controller, with the data bus `B` and optionally the DMA
+/// channel `D`
+pub struct Pcc<B: DataBus, D = NoneT> {
+    pcc: pac::Pcc,
+    pins: Pins<B>,
+    config: Config,
+    _dma_channel: D,
+}
+
+impl<B: DataBus> Pcc<B> {
+    /// Create a new [`Pcc`], and configure it
+    ///
+    /// The PCC is then started with [`Pcc::enable`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`Packing::HalfWord`] is used with a bus wider than 8 bits.
+    pub fn new(mclk: &mut Mclk, pcc: pac::Pcc, pins: Pins<B>, config: Config) -> Self {
+        let wide = B::WIDTH != DataWidth::Bits8;
+        assert!(
+            !(wide && config.packing == Packing::HalfWord),
+            "Half-word packing needs an 8-bit bus"
+        );
+
+        mclk.apbdmask().modify(|_, w| w.pcc_().set_bit());
+
+        let input_size = match B::WIDTH {
+            DataWidth::Bits8 => Isizeselect::_8bits,
+            DataWidth::Bits10 => Isizeselect::_10bits,
+            DataWidth::Bits12 => Isizeselect::_12bits,
+            DataWidth::Bits14 => Isizeselect::_14bits,
+        };
+        let dsize = match (config.packing, wide) {
+            (Packing::None, _) => Dsizeselect::_1data,
+            (Packing::HalfWord, _) | (Packing::Word, true) => Dsizeselect::_2data,
+            (Packing::Word, false) => Dsizeselect::_4data,
+        };
+        let cid = match config.clear_if_disabled {
+            ClearIfDisabled::Never => 0,
+            ClearIfDisabled::Den1 => 1,
+            ClearIfDisabled::Den2 => 2,
+            ClearIfDisabled::Both => 3,
+        };
+
+        pcc.mr().write(|w| {
+            w.isize().variant(input_size);
+            w.dsize().variant(dsize);
+            w.alwys().bit(config.always_sample);
+            w.halfs().bit(config.sampling != Sampling::All);
+            w.frsts().bit(config.sampling == Sampling::Odd);
+            unsafe { w.cid().bits(cid) }
+        });
+
+        Self {
+            pcc,
+            pins,
+            config,
+            _dma_channel: NoneT,
+        }
+    }
+
+    /// Attach a DMA channel, to capture frames with
+    /// [`capture_frame`](Pcc::capture_frame)
+    #[cfg(all(feature = "dma", feature = "async"))]
+    pub fn with_dma_channel<D>(self, dma_channel: D) -> Pcc<B, D>
+    where
+        D: AnyChannel<Status = ReadyFuture>,
+    {
+        Pcc {
+            pcc: self.pcc,
+            pins: self.pins,
+            config: self.config,
+            _dma_channel: dma_channel,
+        }
+    }
+
+    /// Disable the PCC, and return the peripheral and the pins
+    pub fn free(mut self) -> (pac::Pcc, Pins<B>) {
+        self.disable();
+        (self.pcc, self.pins)
+    }
+}
+
+impl<B: DataBus, D> Pcc<B, D> {
+    /// Return the configuration of the PCC
+    #[inline]
+    pub fn config(&self) -> Config {
+        self.config
+    }
+
+    /// Start sampling the data bus
+    #[inline]
+    pub fn enable(&mut self) {
+        self.pcc.mr().modify(|_, w| w.pcen().set_bit());
+    }
+
+    /// Stop sampling the data bus
+    #[inline]
+    pub fn disable(&mut self) {
+        self.pcc.mr().modify(|_, w| w.pcen().clear_bit());
+    }
+
+    /// Size in bytes of the words read from the PCC
+    #[inline]
+    pub fn word_size(&self) -> usize {
+        match (self.config.packing, B::WIDTH) {
+            (Packing::None, DataWidth::Bits8) => 1,
+            (Packing::None, _) | (Packing::HalfWord, _) => 2,
+            (Packing::Word, _) => 4,
+        }
+    }
+
+    /// Read the next word of samples
+    ///
+    /// Returns [`Error::Overrun`] if words were lost since the last read. The
+    /// next word can then be read.
+    #[inline]
+    pub fn read(&mut self) -> nb::Result<u32, Error> {
+        // Reading the status clears the overrun flag
+        let status = self.pcc.isr().read();
+        if status.ovre().bit_is_set() {
+            return Err(nb::Error::Other(Error::Overrun));
+        }
+        if status.drdy().bit_is_set() {
+            Ok(self.pcc.rhr().read().bits())
+        } else {
+            Err(nb::Error::WouldBlock)
+        }
+    }
+}
+
+//==============================================================================
+// DMA
+//==============================================================================
+
+/// Reception holding register, for use as a DMA source
+#[cfg(all(feature = "dma", feature = "async"))]
+struct RhrPtr<W>(*mut W);
+
+#[cfg(all(feature = "dma", feature = "async"))]
+unsafe impl<W: Beat> Buffer for RhrPtr<W> {
+    type Beat = W;
+
+    #[inline]
+    fn dma_ptr(&mut self) -> *mut Self::Beat {
+        self.0
+    }
+
+    #[inline]
+    fn incrementing(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn buffer_len(&self) -> usize {
+        1
+    }
+}
+
+#[cfg(all(feature = "dma", feature = "async"))]
+impl<B, D> Pcc<B, D>
+where
+    B: DataBus,
+    D: AnyChannel<Status = ReadyFuture>,
+{
+    /// Detach the DMA channel
+    pub fn take_dma_channel(self) -> (Pcc<B>, D) {
+        let pcc = Pcc {
+            pcc: self.pcc,
+            pins: self.pins,
+            config: self.config,
+            _dma_channel: NoneT,
+        };
+        (pcc, self._dma_channel)
+    }
+
+    /// Fill `buffer` with the next samples, using the DMA channel
+    ///
+    /// The PCC is enabled for the duration of the capture, and disabled once
+    /// `buffer` is full. Returns [`Error::Overrun`] if the DMAC could not
+    /// keep up with the data bus, in which case samples are missing from
+    /// `buffer`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the size of `W` does not match the
+    /// [`word_size`](Pcc::word_size).
+    pub async fn capture_frame<W: Beat>(&mut self, buffer: &mut [W]) -> Result<(), Error> {
+        assert_eq!(
+            core::mem::size_of::<W>(),
+            self.word_size(),
+            "The beat size must match the packing of the samples"
+        );
+        if buffer.is_empty() {
+            return Ok(());
+        }
+
+        // Discard any stale word and overrun
+        self.disable();
+        let _ = self.pcc.rhr().read();
+        let _ = self.pcc.isr().read();
+
+        // The DRDY request stays asserted until the holding register is read,
+        // so the first word can't be missed even though the PCC is enabled
+        // before the channel.
+        self.enable();
+        let rhr = self.pcc.rhr().as_ptr() as *mut W;
+        let result = self
+            ._dma_channel
+            .as_mut()
+            .transfer_future(
+                RhrPtr(rhr),
+                buffer,
+                TriggerSource::PccRx,
+                TriggerAction::Burst,
+            )
+            .await;
+        self.disable();
+
+        result?;
+        if self.pcc.isr().read().ovre().bit_is_set() {
+            return Err(Error::Overrun);
+        }
+        Ok(())
+    }
+}
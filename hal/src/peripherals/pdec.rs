@@ -0,0 +1,753 @@
+//! # Position Decoder
+//!
+//! The Position Decoder (PDEC) decodes the signals of rotary encoders and
+//! Hall effect sensors in hardware, on the three `QDI` inputs:
+//!
+//! * In [`Mode::Qdec`], `QDI0` and `QDI1` are the phases A and B of a
+//!   quadrature encoder, and `QDI2` is its optional index. The counter is
+//!   split into an angular position counter and a revolution counter, whose
+//!   respective sizes are selected with [`Config::angular`].
+//! * In [`Mode::Hall`], the three inputs are connected to the Hall sensors of
+//!   a brushless motor, and invalid Hall codes are detected.
+//! * In [`Mode::Counter`], the PDEC operates as a 16-bit counter, counting
+//!   the events routed through the [`evsys`](crate::evsys).
+//!
+//! ## Pins
+//!
+//! The `QDI` pins must be configured in [`AlternateG`] mode, and are passed
+//! to [`Pdec::new`] as a tuple of three optional pins. Use [`NoneT`] for an
+//! unused input. The inputs can also be driven by events instead of pins.
+//!
+//! ```no_run
+//! use atsamd_hal::gpio::{AlternateG, Pin};
+//! use atsamd_hal::pdec::{Config, Pdec};
+//! use atsamd_hal::typelevel::NoneT;
+//!
+//! let qdi0: Pin<_, AlternateG> = pins.pb18.into();
+//! let qdi1: Pin<_, AlternateG> = pins.pb19.into();
+//!
+//! // Quadrature encoder with 4 * 256 positions per revolution
+//! let mut config = Config::default();
+//! config.angular = 1;
+//! config.period_enable = true;
+//! config.period = 4 * 256 - 1;
+//!
+//! let mut pdec = Pdec::new(
+//!     &mut peripherals.mclk,
+//!     peripherals.pdec,
+//!     pdec_clock,
+//!     (qdi0, qdi1, NoneT),
+//!     config,
+//! );
+//!
+//! let position = pdec.position();
+//! let revolution = pdec.revolution();
+//! ```
+//!
+//! ## Errors
+//!
+//! The PDEC detects invalid transitions of the quadrature signals, missing or
+//! misplaced index pulses, and invalid Hall codes. Each error sets a bit of
+//! the [`Status`] register and the [`Flags::ERR`] interrupt flag, and can
+//! emit an event. The errors are cleared with [`Pdec::clear_errors`].
+//!
+//! ## `async` operation <span class="stab portability" title="Available on crate feature `async` only"><code>async</code></span>
+//!
+//! Bind the `PDEC` interrupt sources to the [`InterruptHandler`] (refer to
+//! the module-level [`async_hal`](crate::async_hal) documentation for more
+//! information), then turn the [`Pdec`] into an async-enabled [`Pdec`] by
+//! calling [`Pdec::into_future`]. It provides async methods to wait for a
+//! compare match, an overflow, or any set of [`Flags`].
+
+use core::marker::PhantomData;
+
+use atsamd_hal_macros::hal_cfg;
+use bitflags::bitflags;
+
+use crate::clock::PdecClock;
+use crate::gpio::{AlternateG, Pin};
+use crate::pac;
+use crate::typelevel::{NoneT, Sealed};
+
+#[hal_cfg("pa24")]
+use crate::gpio::PA24;
+#[hal_cfg("pa25")]
+use crate::gpio::PA25;
+#[hal_cfg("pb18")]
+use crate::gpio::PB18;
+#[hal_cfg("pb19")]
+use crate::gpio::PB19;
+#[hal_cfg("pb20")]
+use crate::gpio::PB20;
+#[hal_cfg("pb22")]
+use crate::gpio::PB22;
+#[hal_cfg("pb23")]
+use crate::gpio::PB23;
+
+/// Decoder configuration, selecting the decoded edges and whether the index
+/// input is used
+pub use pac::pdec::ctrla::Confselect as Configuration;
+/// Operation mode
+pub use pac::pdec::ctrla::Modeselect as Mode;
+/// Action taken on an incoming event
+pub use pac::pdec::evctrl::Evactselect as EventAction;
+/// Prescaler of the PDEC clock
+pub use pac::pdec::presc::Prescselect as Prescaler;
+
+/// `SYNCBUSY` bits
+const SYNC_CTRLB: u32 = 1 << 2;
+const SYNC_STATUS: u32 = 1 << 3;
+const SYNC_PRESC: u32 = 1 << 4;
+const SYNC_FILTER: u32 = 1 << 5;
+const SYNC_COUNT: u32 = 1 << 6;
+const SYNC_CC0: u32 = 1 << 7;
+
+//==============================================================================
+// Pins
+//==============================================================================
+
+/// Trait representing a PDEC input ID
+pub trait QdiId: Sealed {
+    const NUM: usize;
+}
+
+/// ID of the `QDI0` input
+pub enum Qdi0 {}
+
+impl Sealed for Qdi0 {}
+
+impl QdiId for Qdi0 {
+    const NUM: usize = 0;
+}
+
+/// ID of the `QDI1` input
+pub enum Qdi1 {}
+
+impl Sealed for Qdi1 {}
+
+impl QdiId for Qdi1 {
+    const NUM: usize = 1;
+}
+
+/// ID of the `QDI2` input
+pub enum Qdi2 {}
+
+impl Sealed for Qdi2 {}
+
+impl QdiId for Qdi2 {
+    const NUM: usize = 2;
+}
+
+/// Pin which can be used as the `Q` input of the PDEC
+pub trait QdiPin<Q: QdiId>: Sealed {}
+
+/// Optional PDEC input pin
+///
+/// Implemented for all the [`QdiPin`]s, and for [`NoneT`] when the input is
+/// not connected to a pin.
+pub trait OptionalQdiPin<Q: QdiId>: Sealed {
+    /// Whether the input is connected to a pin
+    const ENABLED: bool;
+}
+
+impl<Q: QdiId> OptionalQdiPin<Q> for NoneT {
+    const ENABLED: bool = false;
+}
+
+macro_rules! qdi_pins {
+    ($($(#[$cfg:meta])* $Id:ident: $Qdi:ident),+ $(,)?) => {
+        $(
+            $(#[$cfg])*
+            impl QdiPin<$Qdi> for Pin<$Id, AlternateG> {}
+
+            $(#[$cfg])*
+            impl OptionalQdiPin<$Qdi> for Pin<$Id, AlternateG> {
+                const ENABLED: bool = true;
+            }
+        )+
+    };
+}
+
+qdi_pins!(
+    #[hal_cfg("pa24")]
+    PA24: Qdi0,
+    #[hal_cfg("pa25")]
+    PA25: Qdi1,
+    #[hal_cfg("pb18")]
+    PB18: Qdi0,
+    #[hal_cfg("pb19")]
+    PB19: Qdi1,
+    #[hal_cfg("pb20")]
+    PB20: Qdi2,
+    #[hal_cfg("pb22")]
+    PB22: Qdi2,
+    #[hal_cfg("pb23")]
+    PB23: Qdi0,
+);
+
+/// Set of the three PDEC input pins
+///
+/// This trait is implemented for tuples `(P0, P1, P2)` of
+/// [`OptionalQdiPin`]s, in the order `QDI0`, `QDI1`, `QDI2`.
+pub trait PdecPins: Sealed {
+    /// Whether each input is connected to a pin
+    const ENABLED: [bool; 3];
+}
+
+impl<P0, P1, P2> Sealed for (P0, P1, P2)
+where
+    P0: OptionalQdiPin<Qdi0>,
+    P1: OptionalQdiPin<Qdi1>,
+    P2: OptionalQdiPin<Qdi2>,
+{
+}
+
+impl<P0, P1, P2> PdecPins for (P0, P1, P2)
+where
+    P0: OptionalQdiPin<Qdi0>,
+    P1: OptionalQdiPin<Qdi1>,
+    P2: OptionalQdiPin<Qdi2>,
+{
+    const ENABLED: [bool; 3] = [P0::ENABLED, P1::ENABLED, P2::ENABLED];
+}
+
+//==============================================================================
+// Flags and status
+//==============================================================================
+
+bitflags! {
+    /// Interrupt flags of the PDEC
+    ///
+    /// The same flags select the events emitted with
+    /// [`Config::event_outputs`].
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct Flags: u8 {
+        /// Overflow or underflow of the counter
+        const OVF = 1 << 0;
+        /// Error, see [`Status`]
+        const ERR = 1 << 1;
+        /// Change of direction
+        const DIR = 1 << 2;
+        /// Velocity
+        const VLC = 1 << 3;
+        /// Compare match on channel 0
+        const MC0 = 1 << 4;
+        /// Compare match on channel 1
+        const MC1 = 1 << 5;
+    }
+}
+
+bitflags! {
+    /// Status of the PDEC
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct Status: u16 {
+        /// Quadrature error: invalid transition of the phases
+        const QERR = 1 << 0;
+        /// Index error: the index pulse was detected at an unexpected
+        /// position
+        const IDXERR = 1 << 1;
+        /// Missing pulse error: more than [`Config::max_missing_pulses`]
+        /// consecutive index pulses are missing
+        const MPERR = 1 << 2;
+        /// Window error: two Hall code changes occurred within the window
+        /// delay
+        const WINERR = 1 << 4;
+        /// Hall error: invalid Hall code
+        const HERR = 1 << 5;
+        /// The counter is stopped
+        const STOP = 1 << 6;
+        /// The counter is counting down
+        const DIR = 1 << 7;
+    }
+}
+
+impl Status {
+    /// All the error bits
+    pub const ERRORS: Self = Self::QERR
+        .union(Self::IDXERR)
+        .union(Self::MPERR)
+        .union(Self::WINERR)
+        .union(Self::HERR);
+}
+
+/// Counting direction
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Down,
+}
+
+//==============================================================================
+// Config
+//==============================================================================
+
+/// PDEC configuration
+///
+/// These settings are enable-protected, and can only be changed by recreating
+/// the [`Pdec`].
+#[derive(Clone, Copy)]
+pub struct Config {
+    /// Operation mode.
+    pub mode: Mode,
+    /// Decoder configuration, in [`Mode::Qdec`].
+    pub configuration: Configuration,
+    /// Swap the phases A and B, in [`Mode::Qdec`].
+    pub swap: bool,
+    /// Invert the input pins, indexed by `QDI` number.
+    pub invert: [bool; 3],
+    /// Size of the angular counter in [`Mode::Qdec`], as `9 + angular` bits
+    /// (`0..=7`). The remaining upper bits of the counter hold the
+    /// revolution counter.
+    pub angular: u8,
+    /// Wrap the counter at [`period`](Self::period).
+    pub period_enable: bool,
+    /// Period of the counter, written to the compare channel 0.
+    ///
+    /// In [`Mode::Qdec`], the angular bits hold the number of angular
+    /// positions per revolution minus one, and the upper bits the number of
+    /// revolutions minus one.
+    pub period: u16,
+    /// Maximum number of consecutive missing index pulses before a
+    /// [`Status::MPERR`] error (`0..=15`). `0` disables the detection.
+    pub max_missing_pulses: u8,
+    /// Stop the counter automatically on an error.
+    pub auto_lock: bool,
+    /// Prescaler of the PDEC clock.
+    pub prescaler: Prescaler,
+    /// Number of prescaled clock cycles an input must be stable to be
+    /// accepted. `0` disables the filter.
+    pub filter: u8,
+    /// Action taken on the incoming events.
+    pub event_action: EventAction,
+    /// Enable the event inputs, indexed by `QDI` number. An input
+    /// connected to an event must not be connected to a pin.
+    pub event_inputs: [bool; 3],
+    /// Invert the event inputs, indexed by `QDI` number.
+    pub invert_event_inputs: [bool; 3],
+    /// Emit an event on each of the selected conditions.
+    pub event_outputs: Flags,
+    /// Keep the PDEC running in standby sleep mode.
+    pub run_in_standby: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            mode: Mode::Qdec,
+            configuration: Configuration::X4,
+            swap: false,
+            invert: [false; 3],
+            angular: 7,
+            period_enable: false,
+            period: u16::MAX,
+            max_missing_pulses: 0,
+            auto_lock: false,
+            prescaler: Prescaler::Div1,
+            filter: 0,
+            event_action: EventAction::Off,
+            event_inputs: [false; 3],
+            invert_event_inputs: [false; 3],
+            event_outputs: Flags::empty(),
+            run_in_standby: false,
+        }
+    }
+}
+
+#[inline]
+fn mask(bits: [bool; 3]) -> u8 {
+    bits.iter()
+        .enumerate()
+        .fold(0, |acc, (i, &set)| acc | ((set as u8) << i))
+}
+
+//==============================================================================
+// Pdec
+//==============================================================================
+
+/// Marker type for a [`Pdec`] capable of doing async operations.
+#[cfg(feature = "async")]
+pub enum PdecFuture {}
+
+/// Position Decoder peripheral
+pub struct Pdec<P: PdecPins, I = NoneT> {
+    pdec: pac::Pdec,
+    pins: P,
+    angular_bits: u8,
+    _irqs: PhantomData<I>,
+}
+
+impl<P: PdecPins> Pdec<P> {
+    /// Create a new [`Pdec`], reset and configure the peripheral, and start
+    /// the counter.
+    pub fn new(
+        mclk: &mut pac::Mclk,
+        pdec: pac::Pdec,
+        _clock: PdecClock,
+        pins: P,
+        config: Config,
+    ) -> Self {
+        mclk.apbcmask().modify(|_, w| w.pdec_().set_bit());
+
+        let mut pdec = Self {
+            pdec,
+            pins,
+            angular_bits: 9 + config.angular.min(7),
+            _irqs: PhantomData,
+        };
+        pdec.swreset();
+
+        let pinen = P::ENABLED;
+        pdec.pdec.ctrla().write(|w| {
+            w.mode().variant(config.mode);
+            w.conf().variant(config.configuration);
+            w.swap().bit(config.swap);
+            w.peren().bit(config.period_enable);
+            w.alock().bit(config.auto_lock);
+            w.runstdby().bit(config.run_in_standby);
+            w.pinen0().bit(pinen[0]);
+            w.pinen1().bit(pinen[1]);
+            w.pinen2().bit(pinen[2]);
+            w.pinven0().bit(config.invert[0]);
+            w.pinven1().bit(config.invert[1]);
+            w.pinven2().bit(config.invert[2]);
+            unsafe {
+                w.angular().bits(config.angular.min(7));
+                w.maxcmp().bits(config.max_missing_pulses.min(15))
+            }
+        });
+        let evctrl = config.event_action as u16
+            | (mask(config.invert_event_inputs) as u16) << 2
+            | (mask(config.event_inputs) as u16) << 5
+            | (config.event_outputs.bits() as u16) << 8;
+        pdec.pdec.evctrl().write(|w| unsafe { w.bits(evctrl) });
+        pdec.pdec
+            .presc()
+            .write(|w| w.presc().variant(config.prescaler));
+        pdec.sync(SYNC_PRESC);
+        pdec.pdec
+            .filter()
+            .write(|w| unsafe { w.filter().bits(config.filter) });
+        pdec.sync(SYNC_FILTER);
+        pdec.pdec
+            .cc(0)
+            .write(|w| unsafe { w.cc().bits(config.period) });
+        pdec.sync(SYNC_CC0);
+
+        pdec.pdec.ctrla().modify(|_, w| w.enable().set_bit());
+        while pdec.pdec.syncbusy().read().enable().bit_is_set() {
+            core::hint::spin_loop();
+        }
+        pdec.start();
+        pdec
+    }
+}
+
+impl<P: PdecPins, I> Pdec<P, I> {
+    #[inline]
+    fn sync(&self, bits: u32) {
+        while self.pdec.syncbusy().read().bits() & bits != 0 {
+            core::hint::spin_loop();
+        }
+    }
+
+    #[inline]
+    fn command(&mut self, f: impl FnOnce(&mut pac::pdec::ctrlbset::W)) {
+        self.pdec.ctrlbset().write(|w| {
+            f(w);
+            w
+        });
+        self.sync(SYNC_CTRLB);
+    }
+
+    /// Start the counter.
+    #[inline]
+    pub fn start(&mut self) {
+        self.command(|w| {
+            w.cmd().start();
+        });
+    }
+
+    /// Stop the counter.
+    #[inline]
+    pub fn stop(&mut self) {
+        self.command(|w| {
+            w.cmd().stop();
+        });
+    }
+
+    /// Clear the counter and restart it.
+    #[inline]
+    pub fn retrigger(&mut self) {
+        self.command(|w| {
+            w.cmd().retrigger();
+        });
+    }
+
+    /// Copy the buffered prescaler, filter and compare values to their
+    /// registers immediately.
+    #[inline]
+    pub fn force_update(&mut self) {
+        self.command(|w| {
+            w.cmd().update();
+        });
+    }
+
+    /// Read the raw value of the counter.
+    #[inline]
+    pub fn count(&mut self) -> u16 {
+        self.command(|w| {
+            w.cmd().readsync();
+        });
+        self.sync(SYNC_COUNT);
+        self.pdec.count().read().count().bits()
+    }
+
+    /// Write the raw value of the counter.
+    #[inline]
+    pub fn set_count(&mut self, count: u16) {
+        self.pdec
+            .count()
+            .write(|w| unsafe { w.count().bits(count) });
+        self.sync(SYNC_COUNT);
+    }
+
+    /// Read the angular position, in [`Mode::Qdec`].
+    #[inline]
+    pub fn position(&mut self) -> u16 {
+        let count = self.count() as u32;
+        (count & ((1 << self.angular_bits) - 1)) as u16
+    }
+
+    /// Read the revolution counter, in [`Mode::Qdec`].
+    ///
+    /// Returns `0` when the angular counter uses all 16 bits.
+    #[inline]
+    pub fn revolution(&mut self) -> u16 {
+        let count = self.count() as u32;
+        (count >> self.angular_bits) as u16
+    }
+
+    /// Return the current counting [`Direction`].
+    #[inline]
+    pub fn direction(&self) -> Direction {
+        if self.pdec.status().read().dir().bit_is_set() {
+            Direction::Down
+        } else {
+            Direction::Up
+        }
+    }
+
+    /// Return the value of a compare channel.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` is not `0` or `1`.
+    #[inline]
+    pub fn compare(&self, channel: usize) -> u16 {
+        assert!(channel < 2);
+        self.pdec.cc(channel).read().cc().bits()
+    }
+
+    /// Set the value of a compare channel at the next update condition.
+    ///
+    /// The compare channel 0 holds the period of the counter.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` is not `0` or `1`.
+    #[inline]
+    pub fn set_compare(&mut self, channel: usize, value: u16) {
+        assert!(channel < 2);
+        self.pdec
+            .ccbuf(channel)
+            .write(|w| unsafe { w.ccbuf().bits(value) });
+    }
+
+    /// Set the prescaler at the next update condition.
+    #[inline]
+    pub fn set_prescaler(&mut self, prescaler: Prescaler) {
+        self.pdec
+            .prescbuf()
+            .write(|w| unsafe { w.bits(prescaler as u8) });
+    }
+
+    /// Set the input filter at the next update condition.
+    #[inline]
+    pub fn set_filter(&mut self, filter: u8) {
+        self.pdec
+            .filterbuf()
+            .write(|w| unsafe { w.filterbuf().bits(filter) });
+    }
+
+    /// Read the [`Status`].
+    #[inline]
+    pub fn status(&self) -> Status {
+        Status::from_bits_truncate(self.pdec.status().read().bits())
+    }
+
+    /// Clear errors.
+    #[inline]
+    pub fn clear_errors(&mut self, errors: Status) {
+        let bits = (errors & Status::ERRORS).bits();
+        self.pdec.status().write(|w| unsafe { w.bits(bits) });
+        self.sync(SYNC_STATUS);
+    }
+
+    /// Read the interrupt flags.
+    #[inline]
+    pub fn read_flags(&self) -> Flags {
+        Flags::from_bits_truncate(self.pdec.intflag().read().bits())
+    }
+
+    /// Clear interrupt flags.
+    #[inline]
+    pub fn clear_flags(&mut self, flags: Flags) {
+        self.pdec
+            .intflag()
+            .write(|w| unsafe { w.bits(flags.bits()) });
+    }
+
+    /// Enable interrupts for the specified flags.
+    #[inline]
+    pub fn enable_interrupts(&mut self, flags: Flags) {
+        self.pdec
+            .intenset()
+            .write(|w| unsafe { w.bits(flags.bits()) });
+    }
+
+    /// Disable interrupts for the specified flags.
+    #[inline]
+    pub fn disable_interrupts(&mut self, flags: Flags) {
+        self.pdec
+            .intenclr()
+            .write(|w| unsafe { w.bits(flags.bits()) });
+    }
+
+    /// Reset the PDEC, and return the register block and the pins.
+    #[inline]
+    pub fn free(mut self) -> (pac::Pdec, P) {
+        self.swreset();
+        (self.pdec, self.pins)
+    }
+
+    #[inline]
+    fn swreset(&mut self) {
+        self.pdec.ctrla().write(|w| w.swrst().set_bit());
+        while self.pdec.syncbusy().read().swrst().bit_is_set() {
+            core::hint::spin_loop();
+        }
+    }
+}
+
+//==============================================================================
+// async
+//==============================================================================
+
+#[cfg(feature = "async")]
+mod async_api {
+    use core::future::poll_fn;
+    use core::task::Poll;
+
+    use embassy_sync::waitqueue::AtomicWaker;
+
+    use super::*;
+    use crate::async_hal::interrupts::{Binding, Handler, InterruptSource, PDEC as PdecInterrupt};
+
+    static WAKER: AtomicWaker = AtomicWaker::new();
+
+    /// Interrupt handler for the PDEC peripheral.
+    pub struct InterruptHandler {
+        _private: (),
+    }
+
+    impl Sealed for InterruptHandler {}
+
+    impl Handler<PdecInterrupt> for InterruptHandler {
+        unsafe fn on_interrupt() {
+            let pdec = &*pac::Pdec::ptr();
+            let pending = pdec.intflag().read().bits() & pdec.intenset().read().bits();
+            if pending != 0 {
+                // Disable the interrupts but don't clear; will be cleared
+                // when the future is next polled.
+                pdec.intenclr().write(|w| w.bits(pending));
+                WAKER.wake();
+            }
+        }
+    }
+
+    impl<P: PdecPins> Pdec<P> {
+        /// Turn a [`Pdec`] into an async-enabled [`Pdec`]. The correct
+        /// interrupt sources are needed.
+        pub fn into_future<I>(self, _irq: I) -> Pdec<P, PdecFuture>
+        where
+            I: Binding<PdecInterrupt, InterruptHandler>,
+        {
+            PdecInterrupt::unpend();
+            unsafe { PdecInterrupt::enable() };
+
+            Pdec {
+                pdec: self.pdec,
+                pins: self.pins,
+                angular_bits: self.angular_bits,
+                _irqs: PhantomData,
+            }
+        }
+    }
+
+    impl<P: PdecPins> Pdec<P, PdecFuture> {
+        /// Wait until any of the `flags` is set, then clear and return the
+        /// flags that were set.
+        pub async fn wait_flags(&mut self, flags: Flags) -> Flags {
+            poll_fn(|cx| {
+                let set = self.read_flags() & flags;
+                if !set.is_empty() {
+                    self.disable_interrupts(flags);
+                    self.clear_flags(set);
+                    return Poll::Ready(set);
+                }
+
+                WAKER.register(cx.waker());
+                self.enable_interrupts(flags);
+
+                let set = self.read_flags() & flags;
+                if !set.is_empty() {
+                    self.disable_interrupts(flags);
+                    self.clear_flags(set);
+                    return Poll::Ready(set);
+                }
+
+                Poll::Pending
+            })
+            .await
+        }
+
+        /// Wait for a compare match on a channel.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `channel` is not `0` or `1`.
+        pub async fn wait_compare(&mut self, channel: usize) {
+            assert!(channel < 2);
+            let flag = Flags::from_bits_truncate(Flags::MC0.bits() << channel);
+            self.clear_flags(flag);
+            self.wait_flags(flag).await;
+        }
+
+        /// Wait for an overflow or underflow of the counter, ie. a full
+        /// revolution in [`Mode::Qdec`] with the period enabled.
+        pub async fn wait_overflow(&mut self) {
+            self.clear_flags(Flags::OVF);
+            self.wait_flags(Flags::OVF).await;
+        }
+
+        /// Wait for an error, and return the error [`Status`].
+        pub async fn wait_error(&mut self) -> Status {
+            self.clear_flags(Flags::ERR);
+            self.wait_flags(Flags::ERR).await;
+            self.status() & Status::ERRORS
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+pub use async_api::InterruptHandler;
@@ -0,0 +1,87 @@
+//! # Power management
+//!
+//! This module provides access to the sleep modes of the power manager (`PM`)
+//! and to the supply controller features that matter for low-power
+//! applications: brown-out detection on `VDD` (`BOD33`) and the main voltage
+//! regulator.
+//!
+//! The available sleep modes differ between chip families:
+//!
+//! * SAMD11/SAMD21: three `Idle` levels, which gate progressively more of the
+//!   CPU, AHB and APB clocks, and `Standby`, which stops all clocks that are
+//!   not explicitly requested to run in standby.
+//! * SAMx5x: `Idle`, `Standby`, `Hibernate`, `Backup` and `Off`. The last three
+//!   modes power down the core domain, so only the backup domain peripherals
+//!   can wake the chip. Waking from `Backup` or `Off` resets the chip.
+//!
+//! ## Example
+//!
+//! Create a [`Power`] struct, select the sleep mode, then enter it with
+//! [`Power::sleep`]. The slice passed to `sleep` lists the peripherals that are
+//! expected to wake the chip. Before executing `WFI`, each of them is checked
+//! against the selected sleep mode, which catches the common mistake of
+//! entering standby with a SERCOM that was not configured to
+//! [`run_in_standby`](crate::sercom::uart::Config::run_in_standby).
+//!
+//! ```no_run
+//! use atsamd_hal::power::{Power, SleepMode};
+//!
+//! // On SAMD11/SAMD21, use `Power::new(&mut peripherals.pm)` instead
+//! let mut power = Power::new(peripherals.pm, peripherals.supc);
+//! power.set_sleep_mode(SleepMode::Standby);
+//! loop {
+//!     // `uart` must have been configured with `run_in_standby(true)`
+//!     power.sleep(&[&uart]).unwrap();
+//!     // Handle the wake-up event
+//! }
+//! ```
+
+use atsamd_hal_macros::hal_module;
+
+#[hal_module(
+    any("pm-d11", "pm-d21") => "power/d11.rs",
+    "pm-d5x" => "power/d5x.rs",
+)]
+mod impls {}
+
+pub use impls::*;
+
+//==============================================================================
+// RunStandby
+//==============================================================================
+
+/// Peripherals that can be configured to keep running in standby sleep
+///
+/// [`Power::sleep`] uses this trait to verify that every peripheral expected
+/// to wake the chip will still be clocked in the selected sleep mode.
+pub trait RunStandby {
+    /// Return `true` if the peripheral keeps running in standby sleep
+    fn runs_in_standby(&self) -> bool;
+}
+
+//==============================================================================
+// Error
+//==============================================================================
+
+/// Errors returned by [`Power::sleep`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// The wake-up source at the given index does not run in standby
+    NotRunStandby(usize),
+    /// The selected sleep mode powers down the core domain, so none of the
+    /// wake-up sources would be able to wake the chip
+    PoweredDown,
+}
+
+/// Check that every wake-up source is able to run in the selected sleep mode
+#[inline]
+fn check_wakeup_sources(standby: bool, sources: &[&dyn RunStandby]) -> Result<(), Error> {
+    if !standby {
+        return Ok(());
+    }
+    match sources.iter().position(|s| !s.runs_in_standby()) {
+        Some(index) => Err(Error::NotRunStandby(index)),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,262 @@
+//! Power management for SAMD11/SAMD21 chips
+
+use atsamd_hal_macros::hal_cfg;
+use cortex_m::asm;
+use cortex_m::peripheral::SCB;
+
+use crate::pac::pm::sleep::Idleselect;
+use crate::pac::sysctrl::bod33::Actionselect;
+use crate::pac::{Pm, Sysctrl};
+
+use super::{check_wakeup_sources, Error, RunStandby};
+
+/// `SLEEPDEEP` bit of the Cortex-M System Control Register
+const SCR_SLEEPDEEP: u32 = 1 << 2;
+
+//==============================================================================
+// SleepMode
+//==============================================================================
+
+/// Sleep mode entered by [`Power::sleep`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SleepMode {
+    /// The CPU clock domain is stopped
+    #[default]
+    Idle0,
+    /// The CPU and AHB clock domains are stopped
+    Idle1,
+    /// The CPU, AHB and APB clock domains are stopped
+    Idle2,
+    /// All clocks are stopped, except those requested to run in standby
+    Standby,
+}
+
+//==============================================================================
+// Bod33
+//==============================================================================
+
+/// Action taken when the `BOD33` detects a brown-out
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Bod33Action {
+    /// Only set the `BOD33DET` status bit
+    None,
+    /// Reset the chip
+    #[default]
+    Reset,
+    /// Trigger the `SYSCTRL` interrupt
+    Interrupt,
+}
+
+impl From<Bod33Action> for Actionselect {
+    #[inline]
+    fn from(action: Bod33Action) -> Self {
+        match action {
+            Bod33Action::None => Self::None,
+            Bod33Action::Reset => Self::Reset,
+            Bod33Action::Interrupt => Self::Interrupt,
+        }
+    }
+}
+
+/// Configuration of the `VDD` brown-out detector
+///
+/// In sampling mode, the `BOD33` only measures `VDD` periodically, which
+/// greatly reduces its current consumption in the sleep modes. The sampling
+/// period is set by the prescaler of the 1 kHz output of the OSCULP32K.
+#[derive(Debug, Clone, Copy)]
+pub struct Bod33Config {
+    level: u8,
+    hysteresis: bool,
+    action: Bod33Action,
+    sampling: Option<u8>,
+    run_standby: bool,
+}
+
+impl Bod33Config {
+    /// Create a new configuration with the given threshold `LEVEL`, in the
+    /// range `0..64`
+    ///
+    /// Refer to the electrical characteristics of the datasheet for the
+    /// threshold voltage corresponding to each `LEVEL`. The detector resets
+    /// the chip by default and runs continuously.
+    #[inline]
+    pub fn new(level: u8) -> Self {
+        assert!(level < 64, "BOD33 level must be in the range 0..64");
+        Self {
+            level,
+            hysteresis: false,
+            action: Bod33Action::Reset,
+            sampling: None,
+            run_standby: false,
+        }
+    }
+
+    /// Enable the hysteresis
+    #[inline]
+    pub fn hysteresis(mut self, hysteresis: bool) -> Self {
+        self.hysteresis = hysteresis;
+        self
+    }
+
+    /// Set the action taken on a brown-out
+    #[inline]
+    pub fn action(mut self, action: Bod33Action) -> Self {
+        self.action = action;
+        self
+    }
+
+    /// Sample `VDD` instead of monitoring it continuously
+    ///
+    /// `prescaler` divides the 1 kHz clock, and must be a power of two in the
+    /// range `2..=65536`.
+    #[inline]
+    pub fn sampling(mut self, prescaler: u32) -> Self {
+        assert!(
+            prescaler.is_power_of_two() && (2..=65536).contains(&prescaler),
+            "Invalid BOD33 sampling prescaler"
+        );
+        self.sampling = Some(prescaler.trailing_zeros() as u8 - 1);
+        self
+    }
+
+    /// Keep the `BOD33` running in standby sleep mode
+    #[inline]
+    pub fn run_standby(mut self, run_standby: bool) -> Self {
+        self.run_standby = run_standby;
+        self
+    }
+}
+
+//==============================================================================
+// Power
+//==============================================================================
+
+/// Power manager
+///
+/// On the SAMD11 and SAMD21, the `PM` peripheral also gates the bus clocks of
+/// every other peripheral, so [`Power`] does not take ownership of it. It only
+/// accesses the `SLEEP` register of the `PM`, along with the `BOD33` and
+/// `VREG` registers of the `SYSCTRL`, which are not used by the `clock`
+/// module.
+///
+/// See the [module-level documentation](super) for an example.
+pub struct Power {
+    mode: SleepMode,
+}
+
+impl Power {
+    /// Create a new [`Power`]
+    ///
+    /// The sleep mode is reset to [`SleepMode::Idle0`].
+    #[inline]
+    pub fn new(_pm: &mut Pm) -> Self {
+        let mut power = Self {
+            mode: SleepMode::Idle0,
+        };
+        power.set_sleep_mode(SleepMode::Idle0);
+        power
+    }
+
+    #[inline]
+    fn pm(&self) -> &crate::pac::pm::RegisterBlock {
+        // Safety: `Power` only accesses the `SLEEP` register, which is not
+        // touched by any other part of the HAL.
+        unsafe { &*Pm::PTR }
+    }
+
+    #[inline]
+    fn sysctrl(&self) -> &crate::pac::sysctrl::RegisterBlock {
+        // Safety: `Power` only accesses the `BOD33` and `VREG` registers,
+        // which are not touched by the `clock` module.
+        unsafe { &*Sysctrl::PTR }
+    }
+
+    /// Return the currently selected sleep mode
+    #[inline]
+    pub fn sleep_mode(&self) -> SleepMode {
+        self.mode
+    }
+
+    /// Select the sleep mode entered by [`Power::sleep`]
+    #[inline]
+    pub fn set_sleep_mode(&mut self, mode: SleepMode) {
+        let idle = match mode {
+            SleepMode::Idle0 | SleepMode::Standby => Idleselect::Cpu,
+            SleepMode::Idle1 => Idleselect::Ahb,
+            SleepMode::Idle2 => Idleselect::Apb,
+        };
+        self.pm().sleep().write(|w| w.idle().variant(idle));
+        // Safety: The `SLEEPDEEP` bit only selects between the idle and
+        // standby sleep modes, which is owned by `Power`.
+        unsafe {
+            (*SCB::PTR).scr.modify(|scr| match mode {
+                SleepMode::Standby => scr | SCR_SLEEPDEEP,
+                _ => scr & !SCR_SLEEPDEEP,
+            });
+        }
+        self.mode = mode;
+    }
+
+    /// Keep the main voltage regulator in normal mode during standby
+    ///
+    /// By default, the regulator switches to its low-power mode in standby,
+    /// which limits the current available to peripherals running in standby.
+    #[hal_cfg("sysctrl-d21")]
+    #[inline]
+    pub fn set_regulator_run_standby(&mut self, run_standby: bool) {
+        self.sysctrl()
+            .vreg()
+            .modify(|_, w| w.runstdby().bit(run_standby));
+    }
+
+    /// Enable the `VDD` brown-out detector
+    ///
+    /// The `BOD33` is disabled while it is reconfigured. This function blocks
+    /// until the detector is ready.
+    #[inline]
+    pub fn enable_bod33(&mut self, config: Bod33Config) {
+        self.disable_bod33();
+        let bod33 = self.sysctrl().bod33();
+        // Safety: The `LEVEL` range is checked by the `Bod33Config` builder
+        bod33.modify(|_, w| unsafe {
+            w.level().bits(config.level);
+            w.hyst().bit(config.hysteresis);
+            w.action().variant(config.action.into());
+            w.mode().bit(config.sampling.is_some());
+            w.psel().bits(config.sampling.unwrap_or(0));
+            w.runstdby().bit(config.run_standby)
+        });
+        if config.sampling.is_some() {
+            bod33.modify(|_, w| w.cen().set_bit());
+        }
+        bod33.modify(|_, w| w.enable().set_bit());
+        while self.sysctrl().pclksr().read().bod33rdy().bit_is_clear() {}
+    }
+
+    /// Disable the `VDD` brown-out detector
+    #[inline]
+    pub fn disable_bod33(&mut self) {
+        self.sysctrl().bod33().modify(|_, w| {
+            w.enable().clear_bit();
+            w.cen().clear_bit()
+        });
+    }
+
+    /// Check whether `VDD` is currently below the `BOD33` threshold
+    #[inline]
+    pub fn bod33_detected(&self) -> bool {
+        self.sysctrl().pclksr().read().bod33det().bit_is_set()
+    }
+
+    /// Enter the selected sleep mode
+    ///
+    /// `wakeup_sources` lists the peripherals expected to wake the chip. In
+    /// [`SleepMode::Standby`], each of them must run in standby.
+    #[inline]
+    pub fn sleep(&mut self, wakeup_sources: &[&dyn RunStandby]) -> Result<(), Error> {
+        check_wakeup_sources(self.mode == SleepMode::Standby, wakeup_sources)?;
+        asm::dsb();
+        asm::wfi();
+        Ok(())
+    }
+}
@@ -0,0 +1,419 @@
+//! Power management for SAMx5x chips
+
+use cortex_m::asm;
+
+use crate::pac::pm::sleepcfg::Sleepmodeselect;
+use crate::pac::pm::stdbycfg::Fastwkupselect;
+use crate::pac::supc::bod33::{Actionselect, Pselselect};
+use crate::pac::{Pm, Supc};
+
+use super::{check_wakeup_sources, Error, RunStandby};
+
+//==============================================================================
+// SleepMode
+//==============================================================================
+
+/// Sleep mode entered by [`Power::sleep`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SleepMode {
+    /// The CPU is stopped, all peripherals keep running
+    #[default]
+    Idle,
+    /// All clocks are stopped, except those requested to run in standby
+    Standby,
+    /// The core domain is powered down, only the backup domain keeps running
+    Hibernate,
+    /// Only the backup domain is powered, waking up resets the chip
+    Backup,
+    /// Everything is powered down, only a reset wakes up the chip
+    Off,
+}
+
+impl From<SleepMode> for Sleepmodeselect {
+    #[inline]
+    fn from(mode: SleepMode) -> Self {
+        match mode {
+            SleepMode::Idle => Self::Idle,
+            SleepMode::Standby => Self::Standby,
+            SleepMode::Hibernate => Self::Hibernate,
+            SleepMode::Backup => Self::Backup,
+            SleepMode::Off => Self::Off,
+        }
+    }
+}
+
+//==============================================================================
+// RamRetention
+//==============================================================================
+
+/// SRAM retention policy in the low-power sleep modes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RamRetention {
+    /// The whole RAM is retained
+    #[default]
+    Retain,
+    /// Only the first 32 KB of the system RAM, or the first 4 KB of the backup
+    /// RAM, are retained
+    Partial,
+    /// The RAM is not retained
+    Off,
+}
+
+impl RamRetention {
+    #[inline]
+    fn bits(self) -> u8 {
+        match self {
+            Self::Retain => 0,
+            Self::Partial => 1,
+            Self::Off => 2,
+        }
+    }
+}
+
+//==============================================================================
+// FastWakeup
+//==============================================================================
+
+/// Blocks kept powered in standby to shorten the wake-up time
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FastWakeup {
+    /// Regular wake-up time
+    #[default]
+    Disabled,
+    /// Keep the NVM powered
+    Nvm,
+    /// Keep the main voltage regulator in its active mode
+    MainRegulator,
+    /// Keep both the NVM and the main voltage regulator active
+    Both,
+}
+
+impl From<FastWakeup> for Fastwkupselect {
+    #[inline]
+    fn from(wakeup: FastWakeup) -> Self {
+        match wakeup {
+            FastWakeup::Disabled => Self::No,
+            FastWakeup::Nvm => Self::Nvm,
+            FastWakeup::MainRegulator => Self::Mainvreg,
+            FastWakeup::Both => Self::Both,
+        }
+    }
+}
+
+//==============================================================================
+// Regulator
+//==============================================================================
+
+/// Main voltage regulator used to supply the core domain
+///
+/// The buck converter is more efficient, but requires an external inductor on
+/// the `VSW` pin. Refer to the schematic checklist of the datasheet.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Regulator {
+    /// Linear regulator, selected at reset
+    #[default]
+    Ldo,
+    /// Switching (buck) regulator
+    Buck,
+}
+
+//==============================================================================
+// Bod33
+//==============================================================================
+
+/// Action taken when the `BOD33` detects a brown-out
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Bod33Action {
+    /// Only set the `BOD33DET` status bit
+    None,
+    /// Reset the chip
+    #[default]
+    Reset,
+    /// Trigger the `SUPC` interrupt
+    Interrupt,
+    /// Enter backup sleep mode
+    Backup,
+}
+
+impl From<Bod33Action> for Actionselect {
+    #[inline]
+    fn from(action: Bod33Action) -> Self {
+        match action {
+            Bod33Action::None => Self::None,
+            Bod33Action::Reset => Self::Reset,
+            Bod33Action::Interrupt => Self::Int,
+            Bod33Action::Backup => Self::Bkup,
+        }
+    }
+}
+
+/// Configuration of the `VDD` brown-out detector
+///
+/// In sampling mode, the `BOD33` only measures `VDD` periodically, which
+/// greatly reduces its current consumption in the sleep modes. The sampling
+/// period is set by the prescaler of the 1 kHz output of the OSCULP32K.
+#[derive(Debug, Clone, Copy)]
+pub struct Bod33Config {
+    level: u8,
+    hysteresis: u8,
+    action: Bod33Action,
+    sampling: Option<Pselselect>,
+    run_standby: bool,
+    run_hibernate: bool,
+    run_backup: bool,
+}
+
+impl Bod33Config {
+    /// Create a new configuration with the given threshold `LEVEL`
+    ///
+    /// Refer to the electrical characteristics of the datasheet for the
+    /// threshold voltage corresponding to each `LEVEL`. The detector resets
+    /// the chip by default and runs continuously in active and idle modes.
+    #[inline]
+    pub fn new(level: u8) -> Self {
+        Self {
+            level,
+            hysteresis: 0,
+            action: Bod33Action::Reset,
+            sampling: None,
+            run_standby: false,
+            run_hibernate: false,
+            run_backup: false,
+        }
+    }
+
+    /// Set the hysteresis level, in the range `0..16`
+    #[inline]
+    pub fn hysteresis(mut self, hysteresis: u8) -> Self {
+        assert!(
+            hysteresis < 16,
+            "BOD33 hysteresis must be in the range 0..16"
+        );
+        self.hysteresis = hysteresis;
+        self
+    }
+
+    /// Set the action taken on a brown-out
+    #[inline]
+    pub fn action(mut self, action: Bod33Action) -> Self {
+        self.action = action;
+        self
+    }
+
+    /// Sample `VDD` in standby instead of monitoring it continuously
+    ///
+    /// `prescaler` divides the 1 kHz clock, and must be 1 or a power of two in
+    /// the range `4..=256`.
+    #[inline]
+    pub fn sampling(mut self, prescaler: u16) -> Self {
+        let psel = match prescaler {
+            1 => Pselselect::Nodiv,
+            4 => Pselselect::Div4,
+            8 => Pselselect::Div8,
+            16 => Pselselect::Div16,
+            32 => Pselselect::Div32,
+            64 => Pselselect::Div64,
+            128 => Pselselect::Div128,
+            256 => Pselselect::Div256,
+            _ => panic!("Invalid BOD33 sampling prescaler"),
+        };
+        self.sampling = Some(psel);
+        self
+    }
+
+    /// Keep the `BOD33` running in standby sleep mode
+    #[inline]
+    pub fn run_standby(mut self, run_standby: bool) -> Self {
+        self.run_standby = run_standby;
+        self
+    }
+
+    /// Keep the `BOD33` running in hibernate sleep mode
+    #[inline]
+    pub fn run_hibernate(mut self, run_hibernate: bool) -> Self {
+        self.run_hibernate = run_hibernate;
+        self
+    }
+
+    /// Keep the `BOD33` running in backup sleep mode
+    #[inline]
+    pub fn run_backup(mut self, run_backup: bool) -> Self {
+        self.run_backup = run_backup;
+        self
+    }
+}
+
+//==============================================================================
+// Power
+//==============================================================================
+
+/// Power manager and supply controller
+///
+/// See the [module-level documentation](super) for an example.
+pub struct Power {
+    pm: Pm,
+    supc: Supc,
+    mode: SleepMode,
+}
+
+impl Power {
+    /// Create a new [`Power`] from the `PM` and `SUPC` peripherals
+    ///
+    /// The sleep mode is reset to [`SleepMode::Idle`].
+    #[inline]
+    pub fn new(pm: Pm, supc: Supc) -> Self {
+        let mut power = Self {
+            pm,
+            supc,
+            mode: SleepMode::Idle,
+        };
+        power.set_sleep_mode(SleepMode::Idle);
+        power
+    }
+
+    /// Return the currently selected sleep mode
+    #[inline]
+    pub fn sleep_mode(&self) -> SleepMode {
+        self.mode
+    }
+
+    /// Select the sleep mode entered by [`Power::sleep`]
+    #[inline]
+    pub fn set_sleep_mode(&mut self, mode: SleepMode) {
+        self.pm
+            .sleepcfg()
+            .write(|w| w.sleepmode().variant(mode.into()));
+        // The datasheet requires reading back the register before executing
+        // `WFI`, to make sure the new mode has been taken into account
+        while self.pm.sleepcfg().read().sleepmode().bits() != Sleepmodeselect::from(mode) as u8 {}
+        self.mode = mode;
+    }
+
+    /// Set the RAM retention policy in standby sleep mode
+    #[inline]
+    pub fn set_standby_ram(&mut self, ram: RamRetention) {
+        // Safety: `RamRetention` only produces the valid values 0..=2
+        self.pm
+            .stdbycfg()
+            .modify(|_, w| unsafe { w.ramcfg().bits(ram.bits()) });
+    }
+
+    /// Select the blocks kept active in standby to speed up the wake-up
+    #[inline]
+    pub fn set_fast_wakeup(&mut self, wakeup: FastWakeup) {
+        self.pm
+            .stdbycfg()
+            .modify(|_, w| w.fastwkup().variant(wakeup.into()));
+    }
+
+    /// Set the retention policy of the main RAM and backup RAM in hibernate
+    /// sleep mode
+    #[inline]
+    pub fn set_hibernate_ram(&mut self, ram: RamRetention, backup_ram: RamRetention) {
+        // Safety: `RamRetention` only produces the valid values 0..=2
+        self.pm.hibcfg().write(|w| unsafe {
+            w.ramcfg().bits(ram.bits());
+            w.bramcfg().bits(backup_ram.bits())
+        });
+    }
+
+    /// Set the retention policy of the backup RAM in backup sleep mode
+    #[inline]
+    pub fn set_backup_ram(&mut self, backup_ram: RamRetention) {
+        // Safety: `RamRetention` only produces the valid values 0..=2
+        self.pm
+            .bkupcfg()
+            .write(|w| unsafe { w.bramcfg().bits(backup_ram.bits()) });
+    }
+
+    /// Select the main voltage regulator
+    ///
+    /// Blocks until the new regulator is ready.
+    #[inline]
+    pub fn set_regulator(&mut self, regulator: Regulator) {
+        self.supc
+            .vreg()
+            .modify(|_, w| w.sel().bit(regulator == Regulator::Buck));
+        while self.supc.status().read().vregrdy().bit_is_clear() {}
+    }
+
+    /// Return the currently selected main voltage regulator
+    #[inline]
+    pub fn regulator(&self) -> Regulator {
+        if self.supc.vreg().read().sel().bit_is_set() {
+            Regulator::Buck
+        } else {
+            Regulator::Ldo
+        }
+    }
+
+    /// Enable the `VDD` brown-out detector
+    ///
+    /// The `BOD33` is disabled while it is reconfigured. This function blocks
+    /// until the detector is ready.
+    #[inline]
+    pub fn enable_bod33(&mut self, config: Bod33Config) {
+        self.disable_bod33();
+        // Safety: The hysteresis is checked by the `Bod33Config` builder, and
+        // any 8-bit `LEVEL` is valid
+        self.supc.bod33().modify(|_, w| unsafe {
+            w.level().bits(config.level);
+            w.hyst().bits(config.hysteresis);
+            w.action().variant(config.action.into());
+            w.stdbycfg().bit(config.sampling.is_some());
+            w.psel()
+                .variant(config.sampling.unwrap_or(Pselselect::Nodiv));
+            w.runstdby().bit(config.run_standby);
+            w.runhib().bit(config.run_hibernate);
+            w.runbkup().bit(config.run_backup)
+        });
+        self.supc.bod33().modify(|_, w| w.enable().set_bit());
+        while self.supc.status().read().bod33rdy().bit_is_clear() {}
+    }
+
+    /// Disable the `VDD` brown-out detector
+    #[inline]
+    pub fn disable_bod33(&mut self) {
+        self.supc.bod33().modify(|_, w| w.enable().clear_bit());
+    }
+
+    /// Check whether `VDD` is currently below the `BOD33` threshold
+    #[inline]
+    pub fn bod33_detected(&self) -> bool {
+        self.supc.status().read().bod33det().bit_is_set()
+    }
+
+    /// Enter the selected sleep mode
+    ///
+    /// `wakeup_sources` lists the peripherals expected to wake the chip. They
+    /// are checked against the selected [`SleepMode`] before executing `WFI`:
+    ///
+    /// * In [`SleepMode::Standby`], each of them must run in standby.
+    /// * In [`SleepMode::Hibernate`] and deeper modes, the core domain is
+    ///   powered down, so the list must be empty. Wake-up then relies on the
+    ///   backup domain, such as the RTC.
+    ///
+    /// Waking from [`SleepMode::Backup`] or [`SleepMode::Off`] resets the chip,
+    /// so this function only returns in the other modes.
+    #[inline]
+    pub fn sleep(&mut self, wakeup_sources: &[&dyn RunStandby]) -> Result<(), Error> {
+        match self.mode {
+            SleepMode::Idle => (),
+            SleepMode::Standby => check_wakeup_sources(true, wakeup_sources)?,
+            SleepMode::Hibernate | SleepMode::Backup | SleepMode::Off => {
+                if !wakeup_sources.is_empty() {
+                    return Err(Error::PoweredDown);
+                }
+            }
+        }
+        asm::dsb();
+        asm::wfi();
+        Ok(())
+    }
+
+    /// Release the `PM` and `SUPC` peripherals
+    #[inline]
+    pub fn free(self) -> (Pm, Supc) {
+        (self.pm, self.supc)
+    }
+}
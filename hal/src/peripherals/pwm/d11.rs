@@ -1,28 +1,128 @@
+#![allow(non_snake_case)]
+
 use atsamd_hal_macros::hal_cfg;
 
 use crate::clock;
+use crate::gpio::*;
+use crate::gpio::{AlternateE, AnyPin, Pin};
 use crate::pac::Pm;
 use crate::time::Hertz;
 use crate::timer_params::TimerParams;
 
+pub mod tcc;
+
 // Timer/Counter (TCx)
 
+/// Declare the pinout type of a TC, which takes ownership of one of its
+/// output pins and configures it in the right peripheral function.
+///
+/// The pinouts are constructed with functions named after the pins, for
+/// instance `TC3Pinout::Pa15(pin)`, so that only the valid pins for each TC
+/// are accepted. The TC uses `CC0` as the period, so only its `WO[1]` pins can
+/// output the PWM waveform.
+macro_rules! impl_tc_pinout {
+    (
+        $Type:ident: [ $(
+            $( #[$attr:meta] )?
+            ($func: ident, $Id: ident)
+        ),+ ]
+    ) => {
+        pub struct $Type<I: PinId> {
+            _pin: Pin<I, AlternateE>,
+        }
+
+        $(
+            $( #[$attr] )?
+            impl $Type<$Id> {
+                #[inline]
+                pub fn $func(pin: impl AnyPin<Id = $Id>) -> Self {
+                    let _pin = pin.into().into_alternate();
+                    Self { _pin }
+                }
+            }
+        )+
+    };
+}
+
+#[hal_cfg("tc1")]
+impl_tc_pinout!(TC1Pinout: [
+    #[hal_cfg("pa05")]
+    (Pa5, PA05),
+    #[hal_cfg("pa15")]
+    (Pa15, PA15)
+]);
+
+#[hal_cfg("tc2")]
+impl_tc_pinout!(TC2Pinout: [
+    #[hal_cfg("pa11")]
+    (Pa11, PA11)
+]);
+
+#[hal_cfg("tc3")]
+impl_tc_pinout!(TC3Pinout: [
+    #[hal_cfg("pa15")]
+    (Pa15, PA15),
+    #[hal_cfg("pa19")]
+    (Pa19, PA19)
+]);
+
+#[hal_cfg("tc4")]
+impl_tc_pinout!(TC4Pinout: [
+    #[hal_cfg("pa23")]
+    (Pa23, PA23),
+    #[hal_cfg("pb09")]
+    (Pb9, PB09),
+    #[hal_cfg("pb13")]
+    (Pb13, PB13)
+]);
+
+#[hal_cfg("tc5")]
+impl_tc_pinout!(TC5Pinout: [
+    #[hal_cfg("pa25")]
+    (Pa25, PA25),
+    #[hal_cfg("pb11")]
+    (Pb11, PB11),
+    #[hal_cfg("pb15")]
+    (Pb15, PB15)
+]);
+
+#[hal_cfg("tc6")]
+impl_tc_pinout!(TC6Pinout: [
+    #[hal_cfg("pb03")]
+    (Pb3, PB03),
+    #[hal_cfg("pb17")]
+    (Pb17, PB17)
+]);
+
+#[hal_cfg("tc7")]
+impl_tc_pinout!(TC7Pinout: [
+    #[hal_cfg("pa21")]
+    (Pa21, PA21),
+    #[hal_cfg("pb01")]
+    (Pb1, PB01),
+    #[hal_cfg("pb23")]
+    (Pb23, PB23)
+]);
+
 macro_rules! pwm {
-    ($($TYPE:ident: ($TC:ident, $clock:ident, $apmask:ident, $apbits:ident, $wrapper:ident)),+) => {
+    ($($TYPE:ident: ($TC:ident, $pinout:ident, $clock:ident, $apmask:ident, $apbits:ident, $wrapper:ident)),+) => {
         $(
 
-pub struct $TYPE {
+pub struct $TYPE<I: PinId> {
     /// The frequency of the attached clock, not the period of the pwm.
     /// Used to calculate the period of the pwm.
     clock_freq: Hertz,
     tc: crate::pac::$TC,
+    #[allow(dead_code)]
+    pinout: $pinout<I>,
 }
 
-impl $TYPE {
+impl<I: PinId> $TYPE<I> {
     pub fn new(
         clock: &clock::$clock,
         freq: Hertz,
         tc: crate::pac::$TC,
+        pinout: $pinout<I>,
         pm: &mut Pm,
     ) -> Self {
         let count = tc.count16();
@@ -54,6 +154,7 @@ impl $TYPE {
         Self {
             clock_freq: clock.freq(),
             tc,
+            pinout,
         }
     }
 
@@ -89,11 +190,11 @@ impl $TYPE {
     }
 }
 
-impl $crate::ehal::pwm::ErrorType for$TYPE {
+impl<I: PinId> $crate::ehal::pwm::ErrorType for $TYPE<I> {
     type Error = ::core::convert::Infallible;
 }
 
-impl $crate::ehal::pwm::SetDutyCycle for $TYPE {
+impl<I: PinId> $crate::ehal::pwm::SetDutyCycle for $TYPE<I> {
     fn max_duty_cycle(&self) -> u16 {
         let count = self.tc.count16();
         let top = count.cc(0).read().cc().bits();
@@ -107,7 +208,7 @@ impl $crate::ehal::pwm::SetDutyCycle for $TYPE {
     }
 }
 
-impl $crate::ehal_02::PwmPin for $TYPE {
+impl<I: PinId> $crate::ehal_02::PwmPin for $TYPE<I> {
     type Duty = u16;
 
     fn disable(&mut self) {
@@ -142,22 +243,26 @@ impl $crate::ehal_02::PwmPin for $TYPE {
 )+}}
 
 #[hal_cfg("tc1")]
-pwm! { Pwm1: (Tc1, Tc1Tc2Clock, apbcmask, tc1_, Pwm1Wrapper) }
+pwm! { Pwm1: (Tc1, TC1Pinout, Tc1Tc2Clock, apbcmask, tc1_, Pwm1Wrapper) }
 #[hal_cfg("tc2")]
-pwm! { Pwm2: (Tc2, Tc1Tc2Clock, apbcmask, tc2_, Pwm2Wrapper) }
+pwm! { Pwm2: (Tc2, TC2Pinout, Tc1Tc2Clock, apbcmask, tc2_, Pwm2Wrapper) }
 #[hal_cfg("tc3")]
-pwm! { Pwm3: (Tc3, Tcc2Tc3Clock, apbcmask, tc3_, Pwm3Wrapper) }
+pwm! { Pwm3: (Tc3, TC3Pinout, Tcc2Tc3Clock, apbcmask, tc3_, Pwm3Wrapper) }
 #[hal_cfg("tc4")]
-pwm! { Pwm4: (Tc4, Tc4Tc5Clock, apbcmask, tc4_, Pwm4Wrapper) }
+pwm! { Pwm4: (Tc4, TC4Pinout, Tc4Tc5Clock, apbcmask, tc4_, Pwm4Wrapper) }
 #[hal_cfg("tc5")]
-pwm! { Pwm5: (Tc5, Tc4Tc5Clock, apbcmask, tc5_, Pwm5Wrapper) }
+pwm! { Pwm5: (Tc5, TC5Pinout, Tc4Tc5Clock, apbcmask, tc5_, Pwm5Wrapper) }
 
 #[hal_cfg("tc6")]
-pwm! { Pwm6: (Tc6, Tc6Tc7Clock, apbcmask, tc6_, Pwm6Wrapper) }
+pwm! { Pwm6: (Tc6, TC6Pinout, Tc6Tc7Clock, apbcmask, tc6_, Pwm6Wrapper) }
 #[hal_cfg("tc7")]
-pwm! { Pwm7: (Tc7, Tc6Tc7Clock, apbcmask, tc7_, Pwm7Wrapper) }
+pwm! { Pwm7: (Tc7, TC7Pinout, Tc6Tc7Clock, apbcmask, tc7_, Pwm7Wrapper) }
 
 // Timer/Counter for Control Applications (TCCx)
+//
+// The `TccXPwm` types only provide normal PWM. See the `tcc` module for
+// per-channel `SetDutyCycle` handles, dual-slope PWM, dead-time insertion,
+// pattern generation and fault inputs.
 
 #[derive(Copy, Clone)]
 pub enum Channel {
@@ -167,22 +272,185 @@ pub enum Channel {
     _3,
 }
 
+/// Declare the pinout type of a TCC, which takes ownership of one of its
+/// output pins and configures it in the right peripheral function.
+macro_rules! impl_tcc_pinout {
+    (
+        $Type:ident: [ $(
+            $( #[$attr:meta] )?
+            ($func: ident, $Id: ident, $Mode:ident)
+        ),+ ]
+    ) => {
+        pub struct $Type<I: PinId, M: PinMode> {
+            _pin: Pin<I, M>,
+        }
+
+        $(
+            $( #[$attr] )?
+            impl $Type<$Id, $Mode> {
+                #[inline]
+                pub fn $func(pin: impl AnyPin<Id = $Id>) -> Self {
+                    let _pin = pin.into().into_alternate();
+                    Self { _pin }
+                }
+            }
+        )+
+    };
+}
+
+#[hal_cfg("tcc0-d11")]
+impl_tcc_pinout!(TCC0Pinout: [
+    #[hal_cfg("pa04")]
+    (Pa4, PA04, AlternateF),
+    #[hal_cfg("pa05")]
+    (Pa5, PA05, AlternateF),
+    #[hal_cfg("pa08")]
+    (Pa8, PA08, AlternateF),
+    #[hal_cfg("pa09")]
+    (Pa9, PA09, AlternateF),
+    #[hal_cfg("pa10")]
+    (Pa10, PA10, AlternateF),
+    #[hal_cfg("pa11")]
+    (Pa11, PA11, AlternateF),
+    #[hal_cfg("pa14")]
+    (Pa14, PA14, AlternateF),
+    #[hal_cfg("pa15")]
+    (Pa15, PA15, AlternateF),
+    #[hal_cfg("pa16")]
+    (Pa16, PA16, AlternateF),
+    #[hal_cfg("pa17")]
+    (Pa17, PA17, AlternateF),
+    #[hal_cfg("pa22")]
+    (Pa22, PA22, AlternateF),
+    #[hal_cfg("pa23")]
+    (Pa23, PA23, AlternateF)
+]);
+
+#[hal_cfg("tcc0-d21")]
+impl_tcc_pinout!(TCC0Pinout: [
+    #[hal_cfg("pa04")]
+    (Pa4, PA04, AlternateE),
+    #[hal_cfg("pa05")]
+    (Pa5, PA05, AlternateE),
+    #[hal_cfg("pa08")]
+    (Pa8, PA08, AlternateE),
+    #[hal_cfg("pa09")]
+    (Pa9, PA09, AlternateE),
+    #[hal_cfg("pa10")]
+    (Pa10, PA10, AlternateF),
+    #[hal_cfg("pa11")]
+    (Pa11, PA11, AlternateF),
+    #[hal_cfg("pa12")]
+    (Pa12, PA12, AlternateF),
+    #[hal_cfg("pa13")]
+    (Pa13, PA13, AlternateF),
+    #[hal_cfg("pa14")]
+    (Pa14, PA14, AlternateF),
+    #[hal_cfg("pa15")]
+    (Pa15, PA15, AlternateF),
+    #[hal_cfg("pa16")]
+    (Pa16, PA16, AlternateF),
+    #[hal_cfg("pa17")]
+    (Pa17, PA17, AlternateF),
+    #[hal_cfg("pa18")]
+    (Pa18, PA18, AlternateF),
+    #[hal_cfg("pa19")]
+    (Pa19, PA19, AlternateF),
+    #[hal_cfg("pa20")]
+    (Pa20, PA20, AlternateF),
+    #[hal_cfg("pa21")]
+    (Pa21, PA21, AlternateF),
+    #[hal_cfg("pa22")]
+    (Pa22, PA22, AlternateF),
+    #[hal_cfg("pa23")]
+    (Pa23, PA23, AlternateF),
+    #[hal_cfg("pb10")]
+    (Pb10, PB10, AlternateF),
+    #[hal_cfg("pb11")]
+    (Pb11, PB11, AlternateF),
+    #[hal_cfg("pb12")]
+    (Pb12, PB12, AlternateF),
+    #[hal_cfg("pb13")]
+    (Pb13, PB13, AlternateF),
+    #[hal_cfg("pb16")]
+    (Pb16, PB16, AlternateF),
+    #[hal_cfg("pb17")]
+    (Pb17, PB17, AlternateF),
+    #[hal_cfg("pb30")]
+    (Pb30, PB30, AlternateE),
+    #[hal_cfg("pb31")]
+    (Pb31, PB31, AlternateE)
+]);
+
+#[hal_cfg("tcc1")]
+impl_tcc_pinout!(TCC1Pinout: [
+    #[hal_cfg("pa06")]
+    (Pa6, PA06, AlternateE),
+    #[hal_cfg("pa07")]
+    (Pa7, PA07, AlternateE),
+    #[hal_cfg("pa08")]
+    (Pa8, PA08, AlternateF),
+    #[hal_cfg("pa09")]
+    (Pa9, PA09, AlternateF),
+    #[hal_cfg("pa10")]
+    (Pa10, PA10, AlternateE),
+    #[hal_cfg("pa11")]
+    (Pa11, PA11, AlternateE),
+    #[hal_cfg("pa24")]
+    (Pa24, PA24, AlternateF),
+    #[hal_cfg("pa25")]
+    (Pa25, PA25, AlternateF),
+    #[hal_cfg("pa30")]
+    (Pa30, PA30, AlternateE),
+    #[hal_cfg("pa31")]
+    (Pa31, PA31, AlternateE),
+    #[hal_cfg("pb30")]
+    (Pb30, PB30, AlternateF),
+    #[hal_cfg("pb31")]
+    (Pb31, PB31, AlternateF)
+]);
+
+#[hal_cfg("tcc2")]
+impl_tcc_pinout!(TCC2Pinout: [
+    #[hal_cfg("pa00")]
+    (Pa0, PA00, AlternateE),
+    #[hal_cfg("pa01")]
+    (Pa1, PA01, AlternateE),
+    #[hal_cfg("pa12")]
+    (Pa12, PA12, AlternateE),
+    #[hal_cfg("pa13")]
+    (Pa13, PA13, AlternateE),
+    #[hal_cfg("pa16")]
+    (Pa16, PA16, AlternateE),
+    #[hal_cfg("pa17")]
+    (Pa17, PA17, AlternateE)
+]);
+
 macro_rules! pwm_tcc {
-    ($($TYPE:ident: ($TCC:ident, $clock:ident, $apmask:ident, $apbits:ident, $wrapper:ident)),+) => {
+    ($($TYPE:ident: ($TCC:ident, $pinout:ident, $clock:ident, $apmask:ident, $apbits:ident, $wrapper:ident)),+) => {
         $(
 
-pub struct $TYPE {
+/// PWM on all the channels of a TCC
+///
+/// The pinout only takes ownership of a single output pin, while
+/// [`Pwm::set_duty`](crate::ehal_02::Pwm::set_duty) drives every channel of
+/// the TCC. The pins of the other channels must be configured by hand, or
+/// owned by a [`tcc::Config`] instead.
+pub struct $TYPE<I: PinId, M: PinMode> {
     /// The frequency of the attached clock, not the period of the pwm.
     /// Used to calculate the period of the pwm.
     clock_freq: Hertz,
     tcc: crate::pac::$TCC,
+    #[allow(dead_code)]
+    pinout: $pinout<I, M>,
 }
 
-impl $TYPE {
+impl<I: PinId, M: PinMode> $TYPE<I, M> {
     pub fn new<F: Into<Hertz>> (
         clock: &clock::$clock,
         freq: F,
         tcc: crate::pac::$TCC,
+        pinout: $pinout<I, M>,
         pm: &mut Pm,
     ) -> Self {
         let freq = freq.into();
@@ -219,11 +487,12 @@ impl $TYPE {
         Self {
             clock_freq: clock.freq(),
             tcc,
+            pinout,
         }
     }
 }
 
-impl $crate::ehal_02::Pwm for $TYPE {
+impl<I: PinId, M: PinMode> $crate::ehal_02::Pwm for $TYPE<I, M> {
     type Channel = Channel;
     type Time = Hertz;
     type Duty = u32;
@@ -291,10 +560,10 @@ impl $crate::ehal_02::Pwm for $TYPE {
 )+}}
 
 #[hal_cfg("tcc0-d11")]
-pwm_tcc! { Pwm0: (Tcc0, Tcc0Clock, apbcmask, tcc0_, Pwm0Wrapper) }
+pwm_tcc! { Tcc0Pwm: (Tcc0, TCC0Pinout, Tcc0Clock, apbcmask, tcc0_, TccPwm0Wrapper) }
 #[hal_cfg("tcc0-d21")]
-pwm_tcc! { Pwm0: (Tcc0, Tcc0Tcc1Clock, apbcmask, tcc0_, Pwm0Wrapper) }
-#[hal_cfg("tcc1")]
-pwm_tcc! { Pwm1: (Tcc1, Tcc0Tcc1Clock, apbcmask, tcc1_, Pwm1Wrapper) }
+pwm_tcc! { Tcc0Pwm: (Tcc0, TCC0Pinout, Tcc0Tcc1Clock, apbcmask, tcc0_, TccPwm0Wrapper) }
 #[hal_cfg("tcc1")]
-pwm_tcc! { Pwm2: (Tcc2, Tcc2Tc3Clock, apbcmask, tcc2_, Pwm2Wrapper) }
+pwm_tcc! { Tcc1Pwm: (Tcc1, TCC1Pinout, Tcc0Tcc1Clock, apbcmask, tcc1_, TccPwm1Wrapper) }
+#[hal_cfg("tcc2")]
+pwm_tcc! { Tcc2Pwm: (Tcc2, TCC2Pinout, Tcc2Tc3Clock, apbcmask, tcc2_, TccPwm2Wrapper) }
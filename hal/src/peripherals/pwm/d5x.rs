@@ -9,6 +9,8 @@ use crate::pac::Mclk;
 use crate::time::Hertz;
 use crate::timer_params::TimerParams;
 
+pub mod tcc;
+
 // Timer/Counter (TCx)
 
 /// This is a major syntax hack.
@@ -292,6 +294,9 @@ pwm! { Pwm6: (Tc6, TC6Pinout, Tc6Tc7Clock, apbdmask, tc6_, Pwm6Wrapper) }
 pwm! { Pwm7: (Tc7, TC7Pinout, Tc6Tc7Clock, apbdmask, tc7_, Pwm7Wrapper) }
 
 // Timer/Counter for Control Applications (TCCx)
+//
+// The `TccXPwm` types only provide normal PWM. See the `tcc` module for
+// dual-slope PWM, dead-time insertion, pattern generation and fault inputs.
 
 #[derive(Copy, Clone)]
 pub enum Channel {
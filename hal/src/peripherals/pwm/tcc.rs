@@ -0,0 +1,1103 @@
+//! # Timer/Counter for Control Applications
+//!
+//! The [`Tcc`] driver exposes the waveform features of the TCCs that go beyond
+//! the simple PWM provided by the `TccXPwm` types, mostly for motor control
+//! and power conversion:
+//!
+//! * Normal and dual-slope PWM, selected with [`Waveform`]. Dual-slope PWM
+//!   produces center-aligned pulses, at half the frequency of a normal PWM
+//!   for the same period.
+//! * Complementary outputs with dead-time insertion (DTI). When DTI is enabled
+//!   on a pair of outputs `WO[x]` and `WO[x + 4]`, the low side `WO[x]` and the
+//!   high side `WO[x + 4]` are driven from the same compare channel as
+//!   complementary signals, separated by a configurable dead time.
+//! * The output matrix (OTMX), which routes the compare channels to the
+//!   waveform outputs.
+//! * Pattern generation (PATT), which overrides the waveform outputs with
+//!   fixed levels. This is typically used for the commutation of BLDC motors.
+//! * Recoverable and non-recoverable fault inputs, routed from the
+//!   [`evsys`](crate::evsys).
+//! * Double-buffered updates of the period, compare values and pattern, which
+//!   take effect at the next update condition (counter overflow or underflow)
+//!   to avoid glitches.
+//!
+//! Dead-time insertion, the output matrix and pattern generation are only
+//! available on the TCCs implementing [`WaveformExtension`], ie. TCC0 and
+//! TCC1 on SAMx5x chips, and TCC0 on SAMD11 and SAMD21 chips.
+//!
+//! ## Configuration
+//!
+//! The TCC is configured through a [`Config`], while it is disabled, because
+//! most of the waveform extension and fault registers are enable-protected.
+//! [`Config::enable`] then returns a [`Tcc`], which only allows the
+//! run-time updates of the period, compare values and pattern, and handles
+//! the fault status. The output pins are owned by the [`Config`] and [`Tcc`],
+//! and must belong to the same TCC instance (see [`TccPins`]).
+//!
+//! ## Faults
+//!
+//! Fault events are routed to the TCC through the [`evsys`](crate::evsys):
+//!
+//! * The recoverable faults A and B use the match/capture 0 and 1 event users
+//!   (eg. `users::Tcc0Mc0` and `users::Tcc0Mc1`). They are configured with
+//!   [`Config::recoverable_fault`], and only affect the compare channel outputs
+//!   for the duration selected in [`RecoverableFault`].
+//! * The non-recoverable faults 0 and 1 use the event 0 and 1 users (eg.
+//!   `users::Tcc0Ev0` and `users::Tcc0Ev1`). They are configured with
+//!   [`Config::non_recoverable_fault`], and drive the outputs to the levels
+//!   selected with [`Config::fault_output`] until the fault is cleared with
+//!   [`Tcc::clear_faults`].
+//!
+//! ## Example
+//!
+//! The driver takes the `Mclk` on SAMx5x chips, and the `Pm` on SAMD11 and
+//! SAMD21 chips, to enable the APB clock of the TCC. On a SAMx5x, drive one
+//! phase of a half-bridge with complementary, center-aligned PWM at
+//! 20 kHz, with 500 ns of dead time on both edges:
+//!
+//! ```no_run
+//! use atsamd_hal::pwm::tcc::{Config, Waveform};
+//! use atsamd_hal::pwm::TCC0Pinout;
+//!
+//! // WO[0] is the low side, WO[4] the high side
+//! let pins = (TCC0Pinout::Pa8(pins.pa08), TCC0Pinout::Pb10(pins.pb10));
+//! let mut tcc = Config::new(&tcc0_clock, peripherals.tcc0, pins, &mut mclk)
+//!     .waveform(Waveform::DualSlopeBottom)
+//!     .frequency(20.kHz())
+//!     // 60 cycles of a 120 MHz clock
+//!     .dead_time(60, 60)
+//!     .dead_time_insertion(0, true)
+//!     .enable();
+//!
+//! // Set the duty cycle of the phase, applied at the next update condition
+//! let half = tcc.period() / 2;
+//! tcc.set_compare(0, half);
+//! ```
+
+use core::ops::Deref;
+
+use atsamd_hal_macros::hal_cfg;
+use bitflags::bitflags;
+
+use crate::clock;
+use crate::ehal::pwm::{ErrorType, SetDutyCycle};
+use crate::gpio::{PinId, PinMode};
+use crate::pac::tcc0::RegisterBlock;
+use crate::time::Hertz;
+use crate::timer_params::TimerParams;
+
+#[hal_cfg("tcc0-d5x")]
+use crate::pac::Mclk as ApbClkCtrl;
+#[hal_cfg(any("tcc0-d11", "tcc0-d21"))]
+use crate::pac::Pm as ApbClkCtrl;
+
+#[hal_cfg("tcc0")]
+use super::TCC0Pinout;
+#[hal_cfg("tcc1")]
+use super::TCC1Pinout;
+#[hal_cfg("tcc2")]
+use super::TCC2Pinout;
+#[hal_cfg("tcc3")]
+use super::TCC3Pinout;
+#[hal_cfg("tcc4")]
+use super::TCC4Pinout;
+
+//==============================================================================
+// TccInstance
+//==============================================================================
+
+/// A TCC peripheral instance
+///
+/// The TCC instances differ in their number of compare channels and waveform
+/// outputs, and in the width of their counter.
+///
+/// **⚠️ Warning** This trait should not be implemented outside of this crate!
+pub trait TccInstance: Deref<Target = RegisterBlock> {
+    /// Number of compare/capture channels
+    const CC_NUM: usize;
+
+    /// Number of waveform outputs
+    const WO_NUM: usize;
+
+    /// Maximum value of the counter
+    const MAX_COUNT: u32;
+
+    /// Generic clock of this TCC
+    type Clock;
+
+    /// Return the frequency of the generic clock
+    fn clock_freq(clock: &Self::Clock) -> Hertz;
+
+    /// Enable the APB clock of this TCC
+    fn enable_apb_clock(apb: &mut ApbClkCtrl);
+}
+
+/// TCC instances providing the waveform extension: output matrix, dead-time
+/// insertion, output swap and pattern generation
+pub trait WaveformExtension: TccInstance {}
+
+macro_rules! tcc_instance {
+    (
+        $(
+            $( #[$attr:meta] )?
+            $Tcc:ident: ($cc:expr, $wo:expr, $max:expr, $clock:ident, $apmask:ident, $apbits:ident)
+        ),+
+    ) => {
+        $(
+            $( #[$attr] )?
+            impl TccInstance for crate::pac::$Tcc {
+                const CC_NUM: usize = $cc;
+                const WO_NUM: usize = $wo;
+                const MAX_COUNT: u32 = $max;
+
+                type Clock = clock::$clock;
+
+                #[inline]
+                fn clock_freq(clock: &Self::Clock) -> Hertz {
+                    clock.freq()
+                }
+
+                #[inline]
+                fn enable_apb_clock(apb: &mut ApbClkCtrl) {
+                    apb.$apmask().modify(|_, w| w.$apbits().set_bit());
+                }
+            }
+        )+
+    };
+}
+
+#[hal_cfg("tcc0-d11")]
+tcc_instance!(Tcc0: (4, 8, 0xFF_FFFF, Tcc0Clock, apbcmask, tcc0_));
+
+#[hal_cfg("tcc0-d21")]
+tcc_instance!(
+    Tcc0: (4, 8, 0xFF_FFFF, Tcc0Tcc1Clock, apbcmask, tcc0_),
+    #[hal_cfg("tcc1")]
+    Tcc1: (2, 4, 0xFF_FFFF, Tcc0Tcc1Clock, apbcmask, tcc1_),
+    #[hal_cfg("tcc2")]
+    Tcc2: (2, 2, 0xFFFF, Tcc2Tc3Clock, apbcmask, tcc2_)
+);
+
+#[hal_cfg("tcc0-d5x")]
+tcc_instance!(
+    Tcc0: (6, 8, 0xFF_FFFF, Tcc0Tcc1Clock, apbbmask, tcc0_),
+    #[hal_cfg("tcc1")]
+    Tcc1: (4, 8, 0xFF_FFFF, Tcc0Tcc1Clock, apbbmask, tcc1_),
+    #[hal_cfg("tcc2")]
+    Tcc2: (3, 3, 0xFFFF, Tcc2Tcc3Clock, apbcmask, tcc2_),
+    #[hal_cfg("tcc3")]
+    Tcc3: (2, 2, 0xFFFF, Tcc2Tcc3Clock, apbcmask, tcc3_),
+    #[hal_cfg("tcc4")]
+    Tcc4: (2, 2, 0xFFFF, Tcc4Clock, apbdmask, tcc4_)
+);
+
+#[hal_cfg("tcc0")]
+impl WaveformExtension for crate::pac::Tcc0 {}
+
+#[hal_cfg("tcc1-d5x")]
+impl WaveformExtension for crate::pac::Tcc1 {}
+
+//==============================================================================
+// TccPins
+//==============================================================================
+
+/// Set of output pins of a TCC
+///
+/// This trait is implemented for the pinout type of each TCC (eg.
+/// [`TCC0Pinout`]), for tuples of up to 8 pinouts of the same TCC, and for
+/// `()` when the TCC drives no pin.
+pub trait TccPins<T: TccInstance> {}
+
+impl<T: TccInstance> TccPins<T> for () {}
+
+macro_rules! tcc_pins {
+    (
+        $(
+            $( #[$attr:meta] )?
+            $Tcc:ident: $Pinout:ident
+        ),+
+    ) => {
+        $(
+            $( #[$attr] )?
+            impl<I: PinId, M: PinMode> TccPins<crate::pac::$Tcc> for $Pinout<I, M> {}
+        )+
+    };
+}
+
+tcc_pins!(
+    #[hal_cfg("tcc0")]
+    Tcc0: TCC0Pinout,
+    #[hal_cfg("tcc1")]
+    Tcc1: TCC1Pinout,
+    #[hal_cfg("tcc2")]
+    Tcc2: TCC2Pinout,
+    #[hal_cfg("tcc3")]
+    Tcc3: TCC3Pinout,
+    #[hal_cfg("tcc4")]
+    Tcc4: TCC4Pinout
+);
+
+macro_rules! tcc_pins_tuple {
+    ($($P:ident),+) => {
+        impl<T: TccInstance, $($P: TccPins<T>),+> TccPins<T> for ($($P,)+) {}
+    };
+}
+
+tcc_pins_tuple!(P0);
+tcc_pins_tuple!(P0, P1);
+tcc_pins_tuple!(P0, P1, P2);
+tcc_pins_tuple!(P0, P1, P2, P3);
+tcc_pins_tuple!(P0, P1, P2, P3, P4);
+tcc_pins_tuple!(P0, P1, P2, P3, P4, P5);
+tcc_pins_tuple!(P0, P1, P2, P3, P4, P5, P6);
+tcc_pins_tuple!(P0, P1, P2, P3, P4, P5, P6, P7);
+
+//==============================================================================
+// Configuration enums
+//==============================================================================
+
+/// Waveform generation operation
+///
+/// In the dual-slope modes, the counter counts up to the period and back down
+/// to zero, which produces center-aligned pulses. The variants differ in the
+/// update condition of the double-buffered registers, and in the events
+/// generated at the top and bottom of the count.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Waveform {
+    /// Single-slope PWM, counting up
+    Normal,
+    /// Dual-slope PWM, updated at the bottom, with the `ERR` flag set at the
+    /// top when a compare value is outside of the period (critical mode)
+    DualSlopeCritical,
+    /// Dual-slope PWM, updated and interrupting at the bottom
+    DualSlopeBottom,
+    /// Dual-slope PWM, updated at the bottom, interrupting at both the top and
+    /// the bottom
+    DualSlopeBoth,
+    /// Dual-slope PWM, updated at the bottom, interrupting at the top
+    DualSlopeTop,
+}
+
+impl Waveform {
+    /// Return `true` for the dual-slope operations
+    #[inline]
+    pub fn is_dual_slope(self) -> bool {
+        self != Waveform::Normal
+    }
+}
+
+/// Routing of the compare channels to the waveform outputs
+///
+/// See the `OTMX` table of the datasheet for the full mapping of each option.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum OutputMatrix {
+    /// `WO[x]` is driven by `CC[x % CC_NUM]`
+    Default = 0,
+    /// `WO[x]` is driven by `CC[x % 4]`
+    Modulo4 = 1,
+    /// All outputs are driven by `CC0`
+    Cc0 = 2,
+    /// `WO[0]` is driven by `CC0`, the other outputs by `CC1`
+    Cc0Cc1 = 3,
+}
+
+/// Source of a recoverable fault
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum FaultSource {
+    /// The fault is active when the event is high
+    Enable = 1,
+    /// The fault is active when the event is low
+    Invert = 2,
+    /// The fault is active when the event of the alternate fault (B for A,
+    /// A for B) is high
+    AltFault = 3,
+}
+
+/// Fault blanking start point
+///
+/// The fault input is ignored for the blanking period after the selected
+/// waveform edge.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum Blanking {
+    /// Blanking applied from the start of the ramp
+    Start = 0,
+    /// Blanking applied from the rising edge of the output waveform
+    Rise = 1,
+    /// Blanking applied from the falling edge of the output waveform
+    Fall = 2,
+    /// Blanking applied from each toggle of the output waveform
+    Both = 3,
+}
+
+/// Action taken on the output of a recoverable fault
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum FaultHalt {
+    /// The fault only restarts the counter, if enabled
+    Disable = 0,
+    /// The output is halted as long as the fault is present
+    Hardware = 1,
+    /// The output is halted until the fault is cleared by software
+    Software = 2,
+    /// The fault is handled as a non-recoverable fault
+    NonRecoverable = 3,
+}
+
+/// Recoverable fault input
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RecoverableInput {
+    /// Fault A, from the match/capture 0 event input
+    A,
+    /// Fault B, from the match/capture 1 event input
+    B,
+}
+
+/// Non-recoverable fault input
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum NonRecoverableInput {
+    /// Fault 0, from the event 0 input
+    Ev0,
+    /// Fault 1, from the event 1 input
+    Ev1,
+}
+
+/// Configuration of a recoverable fault input
+///
+/// Recoverable faults only affect the outputs of their compare channel
+/// (`CC0` for fault A, `CC1` for fault B), or of all channels through the
+/// output matrix.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RecoverableFault {
+    /// Polarity of the fault event
+    pub source: FaultSource,
+    /// Keep the output halted until the end of the TCC cycle, instead of
+    /// releasing it as soon as the fault disappears
+    pub keep: bool,
+    /// Only consider the fault while the output is active
+    pub qualify: bool,
+    /// Start point of the blanking period
+    pub blanking: Blanking,
+    /// Length of the blanking period, in prescaled clock cycles
+    pub blanking_time: u8,
+    /// Restart the counter on fault
+    pub restart: bool,
+    /// Action taken on the output
+    pub halt: FaultHalt,
+    /// Number of consecutive samples for the fault to be detected (`0..=15`)
+    pub filter: u8,
+}
+
+impl Default for RecoverableFault {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            source: FaultSource::Enable,
+            keep: false,
+            qualify: false,
+            blanking: Blanking::Start,
+            blanking_time: 0,
+            restart: false,
+            halt: FaultHalt::Hardware,
+            filter: 0,
+        }
+    }
+}
+
+impl RecoverableFault {
+    /// Value of the `FCTRLx` register for this configuration
+    #[inline]
+    fn bits(&self) -> u32 {
+        self.source as u32
+            | (self.keep as u32) << 3
+            | (self.qualify as u32) << 4
+            | (self.blanking as u32) << 5
+            | (self.restart as u32) << 7
+            | (self.halt as u32) << 8
+            | (self.blanking_time as u32) << 16
+            | (self.filter as u32 & 0xF) << 24
+    }
+}
+
+//==============================================================================
+// Flags and status
+//==============================================================================
+
+bitflags! {
+    /// Interrupt flags of a [`Tcc`]
+    ///
+    /// The bits match the `INTFLAG` register.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct Flags: u32 {
+        /// Counter overflow or underflow
+        const OVF = 1 << 0;
+        /// Retrigger
+        const TRG = 1 << 1;
+        /// Counter event
+        const CNT = 1 << 2;
+        /// Compare fault in critical dual-slope mode, or capture overflow
+        const ERR = 1 << 3;
+        /// Non-recoverable update fault (SAMx5x only)
+        const UFS = 1 << 10;
+        /// Non-recoverable debug fault
+        const DFS = 1 << 11;
+        /// Recoverable fault A
+        const FAULTA = 1 << 12;
+        /// Recoverable fault B
+        const FAULTB = 1 << 13;
+        /// Non-recoverable fault 0
+        const FAULT0 = 1 << 14;
+        /// Non-recoverable fault 1
+        const FAULT1 = 1 << 15;
+        /// Match or capture on channel 0
+        const MC0 = 1 << 16;
+        /// Match or capture on channel 1
+        const MC1 = 1 << 17;
+        /// Match or capture on channel 2
+        const MC2 = 1 << 18;
+        /// Match or capture on channel 3
+        const MC3 = 1 << 19;
+        /// Match or capture on channel 4
+        const MC4 = 1 << 20;
+        /// Match or capture on channel 5
+        const MC5 = 1 << 21;
+    }
+}
+
+bitflags! {
+    /// Fault status of a [`Tcc`]
+    ///
+    /// The bits match the fault bits of the `STATUS` register.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct Status: u32 {
+        /// Recoverable fault A input is active
+        const FAULTA_IN = 1 << 8;
+        /// Recoverable fault B input is active
+        const FAULTB_IN = 1 << 9;
+        /// Non-recoverable fault 0 input is active
+        const FAULT0_IN = 1 << 10;
+        /// Non-recoverable fault 1 input is active
+        const FAULT1_IN = 1 << 11;
+        /// Recoverable fault A occurred
+        const FAULTA = 1 << 12;
+        /// Recoverable fault B occurred
+        const FAULTB = 1 << 13;
+        /// Non-recoverable fault 0 occurred
+        const FAULT0 = 1 << 14;
+        /// Non-recoverable fault 1 occurred
+        const FAULT1 = 1 << 15;
+    }
+}
+
+//==============================================================================
+// Register helpers
+//==============================================================================
+
+/// `SYNCBUSY` bits
+const SYNC_CTRLB: u32 = 1 << 2;
+const SYNC_STATUS: u32 = 1 << 3;
+const SYNC_PATT: u32 = 1 << 5;
+const SYNC_WAVE: u32 = 1 << 6;
+const SYNC_PER: u32 = 1 << 7;
+const SYNC_CC0: u32 = 1 << 8;
+
+#[inline]
+fn sync(tcc: &RegisterBlock, bits: u32) {
+    while tcc.syncbusy().read().bits() & bits != 0 {}
+}
+
+/// Write the period buffer register
+#[hal_cfg("tcc0-d5x")]
+#[inline]
+fn write_period_buffer(tcc: &RegisterBlock, period: u32) {
+    tcc.perbuf().write(|w| unsafe { w.perbuf().bits(period) });
+}
+
+/// Write the period buffer register
+#[hal_cfg(any("tcc0-d11", "tcc0-d21"))]
+#[inline]
+fn write_period_buffer(tcc: &RegisterBlock, period: u32) {
+    tcc.perb().write(|w| unsafe { w.perb().bits(period) });
+    sync(tcc, 1 << 18);
+}
+
+/// Write the compare buffer register of a channel
+#[hal_cfg("tcc0-d5x")]
+#[inline]
+fn write_compare_buffer(tcc: &RegisterBlock, channel: usize, value: u32) {
+    tcc.ccbuf(channel)
+        .write(|w| unsafe { w.ccbuf().bits(value) });
+}
+
+/// Write the compare buffer register of a channel
+#[hal_cfg(any("tcc0-d11", "tcc0-d21"))]
+#[inline]
+fn write_compare_buffer(tcc: &RegisterBlock, channel: usize, value: u32) {
+    tcc.ccb(channel).write(|w| unsafe { w.ccb().bits(value) });
+    sync(tcc, 1 << (19 + channel));
+}
+
+/// Write the pattern buffer register
+#[hal_cfg("tcc0-d5x")]
+#[inline]
+fn write_pattern_buffer(tcc: &RegisterBlock, bits: u16) {
+    tcc.pattbuf().write(|w| unsafe { w.bits(bits) });
+}
+
+/// Write the pattern buffer register
+#[hal_cfg(any("tcc0-d11", "tcc0-d21"))]
+#[inline]
+fn write_pattern_buffer(tcc: &RegisterBlock, bits: u16) {
+    tcc.pattb().write(|w| unsafe { w.bits(bits) });
+    sync(tcc, 1 << 16);
+}
+
+#[inline]
+fn set_enabled(tcc: &RegisterBlock, enabled: bool) {
+    tcc.ctrla().modify(|_, w| w.enable().bit(enabled));
+    while tcc.syncbusy().read().enable().bit_is_set() {}
+}
+
+/// Set or clear a single bit of a register with an unsafe `bits` writer
+macro_rules! modify_bit {
+    ($reg:expr, $bit:expr, $set:expr) => {
+        $reg.modify(|r, w| unsafe {
+            let mask = 1 << $bit;
+            w.bits(if $set {
+                r.bits() | mask
+            } else {
+                r.bits() & !mask
+            })
+        })
+    };
+}
+
+//==============================================================================
+// Config
+//==============================================================================
+
+/// Configuration of a disabled TCC
+///
+/// The setters write the TCC registers directly. See the [module
+/// documentation](self) for an overview of the features.
+pub struct Config<T: TccInstance, P: TccPins<T>> {
+    tcc: T,
+    pins: P,
+    clock_freq: Hertz,
+    divider: u16,
+    waveform: Waveform,
+}
+
+impl<T: TccInstance, P: TccPins<T>> Config<T, P> {
+    /// Reset the TCC and create a new [`Config`]
+    ///
+    /// The TCC starts in [`Waveform::Normal`] mode, with the maximum period
+    /// and no prescaler.
+    #[inline]
+    pub fn new(clock: &T::Clock, tcc: T, pins: P, apb: &mut ApbClkCtrl) -> Self {
+        T::enable_apb_clock(apb);
+        set_enabled(&tcc, false);
+        tcc.ctrla().write(|w| w.swrst().set_bit());
+        while tcc.syncbusy().read().swrst().bit_is_set() {}
+        let mut config = Self {
+            tcc,
+            pins,
+            clock_freq: T::clock_freq(clock),
+            divider: 1,
+            waveform: Waveform::Normal,
+        };
+        config.write_waveform();
+        config
+    }
+
+    #[inline]
+    fn write_waveform(&mut self) {
+        self.tcc.wave().modify(|_, w| {
+            match self.waveform {
+                Waveform::Normal => w.wavegen().npwm(),
+                Waveform::DualSlopeCritical => w.wavegen().dscritical(),
+                Waveform::DualSlopeBottom => w.wavegen().dsbottom(),
+                Waveform::DualSlopeBoth => w.wavegen().dsboth(),
+                Waveform::DualSlopeTop => w.wavegen().dstop(),
+            };
+            w.ramp().ramp1()
+        });
+        sync(&self.tcc, SYNC_WAVE);
+    }
+
+    /// Select the [`Waveform`] operation
+    ///
+    /// The frequency set with [`frequency`](Self::frequency) depends on the
+    /// operation, so it must be set afterwards.
+    #[inline]
+    pub fn waveform(mut self, waveform: Waveform) -> Self {
+        self.waveform = waveform;
+        self.write_waveform();
+        self
+    }
+
+    /// Set the PWM frequency, by selecting the prescaler and period
+    #[inline]
+    pub fn frequency(mut self, freq: Hertz) -> Self {
+        let params = TimerParams::new(freq, self.clock_freq);
+        self.divider = params.divider;
+        self.tcc.ctrla().modify(|_, w| match params.divider {
+            1 => w.prescaler().div1(),
+            2 => w.prescaler().div2(),
+            4 => w.prescaler().div4(),
+            8 => w.prescaler().div8(),
+            16 => w.prescaler().div16(),
+            64 => w.prescaler().div64(),
+            256 => w.prescaler().div256(),
+            1024 => w.prescaler().div1024(),
+            _ => unreachable!(),
+        });
+        // A normal PWM cycle lasts PER + 1 cycles, a dual-slope one 2 * PER
+        let period = if self.waveform.is_dual_slope() {
+            params.cycles / 2
+        } else {
+            params.cycles.saturating_sub(1)
+        };
+        self.period(period)
+    }
+
+    /// Set the period (`TOP` value) of the counter directly
+    #[inline]
+    pub fn period(self, period: u32) -> Self {
+        self.tcc
+            .per()
+            .write(|w| unsafe { w.per().bits(period.min(T::MAX_COUNT)) });
+        sync(&self.tcc, SYNC_PER);
+        self
+    }
+
+    /// Set the compare value of a channel
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` is not a compare channel of this TCC.
+    #[inline]
+    pub fn compare(self, channel: usize, value: u32) -> Self {
+        assert!(channel < T::CC_NUM);
+        self.tcc
+            .cc(channel)
+            .write(|w| unsafe { w.cc().bits(value.min(T::MAX_COUNT)) });
+        sync(&self.tcc, SYNC_CC0 << channel);
+        self
+    }
+
+    /// Invert the polarity of a compare channel
+    ///
+    /// In normal PWM, the output of a non-inverted channel is set at the start
+    /// of the cycle and cleared on compare match.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` is not a compare channel of this TCC.
+    #[inline]
+    pub fn polarity(self, channel: usize, inverted: bool) -> Self {
+        assert!(channel < T::CC_NUM);
+        modify_bit!(self.tcc.wave(), 16 + channel, inverted);
+        sync(&self.tcc, SYNC_WAVE);
+        self
+    }
+
+    /// Invert a waveform output at the pin driver, after the dead-time
+    /// insertion and pattern generation
+    ///
+    /// # Panics
+    ///
+    /// Panics if `output` is not a waveform output of this TCC.
+    #[inline]
+    pub fn invert_output(self, output: usize, inverted: bool) -> Self {
+        assert!(output < T::WO_NUM);
+        modify_bit!(self.tcc.drvctrl(), 16 + output, inverted);
+        self
+    }
+
+    /// Configure a recoverable fault input
+    ///
+    /// This enables the match/capture event input of the corresponding
+    /// channel, which must be connected to an event channel.
+    #[inline]
+    pub fn recoverable_fault(self, input: RecoverableInput, fault: RecoverableFault) -> Self {
+        let bits = fault.bits();
+        match input {
+            RecoverableInput::A => {
+                self.tcc.fctrla().write(|w| unsafe { w.bits(bits) });
+                self.tcc.evctrl().modify(|_, w| w.mcei0().set_bit());
+            }
+            RecoverableInput::B => {
+                self.tcc.fctrlb().write(|w| unsafe { w.bits(bits) });
+                self.tcc.evctrl().modify(|_, w| w.mcei1().set_bit());
+            }
+        }
+        self
+    }
+
+    /// Configure a non-recoverable fault input
+    ///
+    /// This enables the event input, which must be connected to an event
+    /// channel. `filter` is the number of consecutive samples for the fault to
+    /// be detected (`0..=15`). The outputs are driven to the levels selected
+    /// with [`fault_output`](Self::fault_output) while the fault is active.
+    #[inline]
+    pub fn non_recoverable_fault(
+        self,
+        input: NonRecoverableInput,
+        inverted: bool,
+        filter: u8,
+    ) -> Self {
+        let filter = (filter as u32 & 0xF) << 24;
+        match input {
+            NonRecoverableInput::Ev0 => {
+                self.tcc.evctrl().modify(|_, w| {
+                    w.evact0().fault();
+                    w.tcinv0().bit(inverted);
+                    w.tcei0().set_bit()
+                });
+                self.tcc
+                    .drvctrl()
+                    .modify(|r, w| unsafe { w.bits(r.bits() & !(0xF << 24) | filter) });
+            }
+            NonRecoverableInput::Ev1 => {
+                self.tcc.evctrl().modify(|_, w| {
+                    w.evact1().fault();
+                    w.tcinv1().bit(inverted);
+                    w.tcei1().set_bit()
+                });
+                self.tcc
+                    .drvctrl()
+                    .modify(|r, w| unsafe { w.bits(r.bits() & !(0xF << 28) | filter << 4) });
+            }
+        }
+        self
+    }
+
+    /// Select the level of a waveform output during a non-recoverable fault
+    ///
+    /// `None` leaves the output driven by the waveform generator.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `output` is not a waveform output of this TCC.
+    #[inline]
+    pub fn fault_output(self, output: usize, level: Option<bool>) -> Self {
+        assert!(output < T::WO_NUM);
+        modify_bit!(self.tcc.drvctrl(), output, level.is_some());
+        modify_bit!(self.tcc.drvctrl(), 8 + output, level.unwrap_or(false));
+        self
+    }
+
+    /// Keep the TCC running in standby sleep mode
+    #[inline]
+    pub fn run_in_standby(self, set: bool) -> Self {
+        self.tcc.ctrla().modify(|_, w| w.runstdby().bit(set));
+        self
+    }
+
+    /// Enable the TCC and return a [`Tcc`]
+    #[inline]
+    pub fn enable(self) -> Tcc<T, P> {
+        set_enabled(&self.tcc, true);
+        Tcc { config: self }
+    }
+
+    /// Reset the TCC and return its parts
+    #[inline]
+    pub fn free(self) -> (T, P) {
+        set_enabled(&self.tcc, false);
+        self.tcc.ctrla().write(|w| w.swrst().set_bit());
+        while self.tcc.syncbusy().read().swrst().bit_is_set() {}
+        (self.tcc, self.pins)
+    }
+}
+
+impl<T: WaveformExtension, P: TccPins<T>> Config<T, P> {
+    /// Select the [`OutputMatrix`]
+    #[inline]
+    pub fn output_matrix(self, otmx: OutputMatrix) -> Self {
+        self.tcc
+            .wexctrl()
+            .modify(|_, w| unsafe { w.otmx().bits(otmx as u8) });
+        self
+    }
+
+    /// Set the dead times of the low side and high side outputs, in clock
+    /// cycles of the TCC
+    ///
+    /// The dead times are shared by all the output pairs that have dead-time
+    /// insertion enabled.
+    #[inline]
+    pub fn dead_time(self, low_side: u8, high_side: u8) -> Self {
+        self.tcc.wexctrl().modify(|_, w| unsafe {
+            w.dtls().bits(low_side);
+            w.dths().bits(high_side)
+        });
+        self
+    }
+
+    /// Enable or disable dead-time insertion on the output pair `WO[pair]`
+    /// (low side) and `WO[pair + 4]` (high side)
+    ///
+    /// When enabled, both outputs are driven by the compare channel of the low
+    /// side as complementary signals, separated by the [dead
+    /// times](Self::dead_time).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `pair` is not in `0..4`.
+    #[inline]
+    pub fn dead_time_insertion(self, pair: usize, enabled: bool) -> Self {
+        assert!(pair < 4);
+        modify_bit!(self.tcc.wexctrl(), 8 + pair, enabled);
+        self
+    }
+
+    /// Swap the low side and high side outputs of a pair with dead-time
+    /// insertion
+    ///
+    /// # Panics
+    ///
+    /// Panics if `pair` is not in `0..4`.
+    #[inline]
+    pub fn swap(self, pair: usize, swapped: bool) -> Self {
+        assert!(pair < 4);
+        modify_bit!(self.tcc.wave(), 24 + pair, swapped);
+        sync(&self.tcc, SYNC_WAVE);
+        self
+    }
+
+    /// Set the output pattern
+    ///
+    /// Each bit of `enable` overrides the corresponding waveform output with
+    /// the same bit of `value`.
+    #[inline]
+    pub fn pattern(self, enable: u8, value: u8) -> Self {
+        self.tcc
+            .patt()
+            .write(|w| unsafe { w.bits(enable as u16 | (value as u16) << 8) });
+        sync(&self.tcc, SYNC_PATT);
+        self
+    }
+}
+
+//==============================================================================
+// Tcc
+//==============================================================================
+
+/// An enabled TCC
+///
+/// Created by [`Config::enable`].
+pub struct Tcc<T: TccInstance, P: TccPins<T>> {
+    config: Config<T, P>,
+}
+
+impl<T: TccInstance, P: TccPins<T>> Tcc<T, P> {
+    #[inline]
+    fn tcc(&self) -> &RegisterBlock {
+        &self.config.tcc
+    }
+
+    /// Return the [`Waveform`] operation
+    #[inline]
+    pub fn waveform(&self) -> Waveform {
+        self.config.waveform
+    }
+
+    /// Return the current period (`TOP` value) of the counter
+    #[inline]
+    pub fn period(&self) -> u32 {
+        self.tcc().per().read().per().bits()
+    }
+
+    /// Return the current PWM frequency
+    #[inline]
+    pub fn frequency(&self) -> Hertz {
+        let period = self.period();
+        let cycles = if self.config.waveform.is_dual_slope() {
+            2 * period
+        } else {
+            period + 1
+        };
+        self.config.clock_freq / self.config.divider as u32 / cycles.max(1)
+    }
+
+    /// Set the period of the counter at the next update condition
+    ///
+    /// The prescaler is enable-protected, so the period can only be changed
+    /// within the range allowed by the prescaler selected in the [`Config`].
+    #[inline]
+    pub fn set_period(&mut self, period: u32) {
+        write_period_buffer(self.tcc(), period.min(T::MAX_COUNT));
+    }
+
+    /// Return the current compare value of a channel
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` is not a compare channel of this TCC.
+    #[inline]
+    pub fn compare(&self, channel: usize) -> u32 {
+        assert!(channel < T::CC_NUM);
+        self.tcc().cc(channel).read().cc().bits()
+    }
+
+    /// Set the compare value of a channel at the next update condition
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` is not a compare channel of this TCC.
+    #[inline]
+    pub fn set_compare(&mut self, channel: usize, value: u32) {
+        assert!(channel < T::CC_NUM);
+        write_compare_buffer(self.tcc(), channel, value.min(T::MAX_COUNT));
+    }
+
+    /// Group several double-buffered updates, so that they are applied at the
+    /// same update condition
+    ///
+    /// The buffered registers are not copied while `f` runs.
+    #[inline]
+    pub fn update<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+        self.tcc().ctrlbset().write(|w| w.lupd().set_bit());
+        sync(self.tcc(), SYNC_CTRLB);
+        let ret = f(self);
+        self.tcc().ctrlbclr().write(|w| w.lupd().set_bit());
+        sync(self.tcc(), SYNC_CTRLB);
+        ret
+    }
+
+    /// Apply the double-buffered updates immediately, without waiting for the
+    /// next update condition
+    #[inline]
+    pub fn force_update(&mut self) {
+        self.tcc().ctrlbset().write(|w| w.cmd().update());
+        sync(self.tcc(), SYNC_CTRLB);
+    }
+
+    /// Borrow a compare channel as a [`SetDutyCycle`] PWM channel
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channel` is not a compare channel of this TCC.
+    #[inline]
+    pub fn channel(&mut self, channel: usize) -> PwmChannel<'_, T, P> {
+        assert!(channel < T::CC_NUM);
+        PwmChannel { tcc: self, channel }
+    }
+
+    /// Read the fault [`Status`]
+    #[inline]
+    pub fn status(&self) -> Status {
+        Status::from_bits_truncate(self.tcc().status().read().bits())
+    }
+
+    /// Clear fault states
+    ///
+    /// Recoverable faults halted by software, and non-recoverable faults, keep
+    /// the outputs in their fault state until cleared. A fault can only be
+    /// cleared once its input is inactive.
+    #[inline]
+    pub fn clear_faults(&mut self, status: Status) {
+        let bits =
+            (status & (Status::FAULTA | Status::FAULTB | Status::FAULT0 | Status::FAULT1)).bits();
+        self.tcc().status().write(|w| unsafe { w.bits(bits) });
+        sync(self.tcc(), SYNC_STATUS);
+    }
+
+    /// Read the interrupt flags
+    #[inline]
+    pub fn read_flags(&self) -> Flags {
+        Flags::from_bits_truncate(self.tcc().intflag().read().bits())
+    }
+
+    /// Clear interrupt flags
+    #[inline]
+    pub fn clear_flags(&mut self, flags: Flags) {
+        self.tcc()
+            .intflag()
+            .write(|w| unsafe { w.bits(flags.bits()) });
+    }
+
+    /// Enable interrupts for the specified flags
+    #[inline]
+    pub fn enable_interrupts(&mut self, flags: Flags) {
+        self.tcc()
+            .intenset()
+            .write(|w| unsafe { w.bits(flags.bits()) });
+    }
+
+    /// Disable interrupts for the specified flags
+    #[inline]
+    pub fn disable_interrupts(&mut self, flags: Flags) {
+        self.tcc()
+            .intenclr()
+            .write(|w| unsafe { w.bits(flags.bits()) });
+    }
+
+    /// Disable the TCC and return its [`Config`]
+    #[inline]
+    pub fn disable(self) -> Config<T, P> {
+        set_enabled(self.tcc(), false);
+        self.config
+    }
+}
+
+impl<T: WaveformExtension, P: TccPins<T>> Tcc<T, P> {
+    /// Set the output pattern at the next update condition
+    ///
+    /// Each bit of `enable` overrides the corresponding waveform output with
+    /// the same bit of `value`. Updating the pattern at the update condition
+    /// keeps the commutation of a BLDC motor in step with the PWM cycle.
+    #[inline]
+    pub fn set_pattern(&mut self, enable: u8, value: u8) {
+        write_pattern_buffer(self.tcc(), enable as u16 | (value as u16) << 8);
+    }
+}
+
+//==============================================================================
+// PwmChannel
+//==============================================================================
+
+/// A compare channel of a [`Tcc`], borrowed with [`Tcc::channel`]
+///
+/// The duty cycle is double-buffered. When the period exceeds `u16::MAX`, the
+/// duty cycle is scaled to the period.
+pub struct PwmChannel<'a, T: TccInstance, P: TccPins<T>> {
+    tcc: &'a mut Tcc<T, P>,
+    channel: usize,
+}
+
+impl<T: TccInstance, P: TccPins<T>> ErrorType for PwmChannel<'_, T, P> {
+    type Error = ::core::convert::Infallible;
+}
+
+impl<T: TccInstance, P: TccPins<T>> SetDutyCycle for PwmChannel<'_, T, P> {
+    #[inline]
+    fn max_duty_cycle(&self) -> u16 {
+        self.tcc.period().min(u16::MAX as u32) as u16
+    }
+
+    #[inline]
+    fn set_duty_cycle(&mut self, duty: u16) -> Result<(), Self::Error> {
+        let period = self.tcc.period();
+        let value = if period > u16::MAX as u32 {
+            (duty as u64 * period as u64 / u16::MAX as u64) as u32
+        } else {
+            duty as u32
+        };
+        self.tcc.set_compare(self.channel, value);
+        Ok(())
+    }
+}
@@ -0,0 +1,681 @@
+//! # SD/MMC Host Controller
+//!
+//! The SDHC peripherals of SAMx5x chips drive SD cards natively, with a 1-bit
+//! or 4-bit data bus, and transfer the data blocks with their own ADMA2
+//! engine instead of the [`dmac`](crate::dmac).
+//!
+//! ## Creating an [`Sdhc`]
+//!
+//! The bus pins are grouped in a [`Pins`] struct, whose `dat1` to `dat3`
+//! fields are set to [`NoneT`] for a 1-bit bus. All the pins must belong to
+//! the same SDHC instance. The card detect and write protect switches of
+//! the card socket are optional, and read through any
+//! [`InputPin`](crate::ehal::digital::InputPin) with
+//! [`Sdhc::with_card_detect`] and [`Sdhc::with_write_protect`].
+//!
+//! [`Sdhc::init_card`] then runs the card identification state machine:
+//! reset, voltage check, power up, addressing and selection of the card,
+//! before switching it to the widest bus supported by the pins, and to the
+//! high-speed timing if the card supports it and the maximum frequency allows
+//! it. The SD clock is derived from the generic clock of the SDHC, which
+//! should run at a multiple of 50 MHz for the fastest timing.
+//!
+//! ```no_run
+//! use atsamd_hal::sdhc::{Pins, Sdhc, BLOCK_SIZE};
+//! use atsamd_hal::typelevel::NoneT;
+//!
+//! let sdhc_clock = clocks.sdhc0(&gclk0).unwrap();
+//! let bus_pins = Pins {
+//!     ck: pins.pb11.into_alternate(),
+//!     cmd: pins.pa08.into_alternate(),
+//!     dat0: pins.pa09.into_alternate(),
+//!     dat1: pins.pa10.into_alternate(),
+//!     dat2: pins.pa11.into_alternate(),
+//!     dat3: pins.pb10.into_alternate(),
+//! };
+//!
+//! let mut sdhc = Sdhc::new(&mut mclk, peripherals.sdhc0, sdhc_clock, bus_pins)
+//!     .with_card_detect(pins.pa12.into_pull_up_input());
+//! let card = sdhc.init_card(&mut delay, 50.MHz())?;
+//!
+//! let mut block = [0; BLOCK_SIZE];
+//! sdhc.read_blocks(0, &mut block)?;
+//! ```
+//!
+//! ## `embedded-sdmmc` <span class="stab portability" title="Available on crate feature `sdmmc` only"><code>sdmmc</code></span>
+//!
+//! With the `sdmmc` feature, an [`Sdhc`] with an initialized card can be
+//! turned into an [`SdhcBlockDevice`], which implements the
+//! `embedded_sdmmc::BlockDevice` trait to access the FAT filesystem of the
+//! card.
+
+use core::ops::Deref;
+
+use atsamd_hal_macros::hal_cfg;
+
+use crate::clock;
+use crate::ehal::delay::DelayNs;
+use crate::ehal::digital::InputPin;
+use crate::gpio::{AlternateI, Pin};
+use crate::pac::sdhc0::RegisterBlock;
+use crate::pac::Mclk;
+use crate::time::Hertz;
+use crate::typelevel::NoneT;
+
+#[cfg(feature = "sdmmc")]
+use core::cell::RefCell;
+#[cfg(feature = "sdmmc")]
+use embedded_sdmmc::{Block, BlockCount, BlockDevice, BlockIdx};
+
+mod protocol;
+
+pub use protocol::{Card, Error, BLOCK_SIZE};
+
+use protocol::{AdmaDescriptor, Registers, ADMA_TABLE_LEN, MAX_TRANSFER_BLOCKS, SRR_SWRSTALL};
+
+use crate::gpio::{PA08, PA09, PA10, PA11, PB10, PB11};
+#[hal_cfg("sdhc1")]
+use crate::gpio::{PA20, PA21, PB18, PB19, PB20, PB21};
+
+/// `CCR` bits
+const CCR_INTCLKEN: u16 = 1 << 0;
+const CCR_INTCLKS: u16 = 1 << 1;
+const CCR_SDCLKEN: u16 = 1 << 2;
+
+/// `HC1R` bits
+const HC1R_DW: u8 = 1 << 1;
+const HC1R_HSEN: u8 = 1 << 2;
+
+//==============================================================================
+// SdhcInstance
+//==============================================================================
+
+/// An SDHC peripheral instance
+///
+/// **⚠️ Warning** This trait should not be implemented outside of this crate!
+pub trait SdhcInstance: Deref<Target = RegisterBlock> {
+    /// Generic clock of this SDHC
+    type Clock;
+
+    /// Return the frequency of the generic clock
+    fn clock_freq(clock: &Self::Clock) -> Hertz;
+
+    /// Enable the AHB clock of this SDHC
+    fn enable_ahb_clock(mclk: &mut Mclk);
+}
+
+macro_rules! sdhc_instance {
+    ($( $( #[$attr:meta] )? $Sdhc:ident: ($clock:ident, $ahbbits:ident) ),+) => {
+        $(
+            $( #[$attr] )?
+            impl SdhcInstance for crate::pac::$Sdhc {
+                type Clock = clock::$clock;
+
+                #[inline]
+                fn clock_freq(clock: &Self::Clock) -> Hertz {
+                    clock.freq()
+                }
+
+                #[inline]
+                fn enable_ahb_clock(mclk: &mut Mclk) {
+                    mclk.ahbmask().modify(|_, w| w.$ahbbits().set_bit());
+                }
+            }
+        )+
+    };
+}
+
+sdhc_instance!(
+    Sdhc0: (Sdhc0Clock, sdhc0_),
+    #[hal_cfg("sdhc1")]
+    Sdhc1: (Sdhc1Clock, sdhc1_)
+);
+
+//==============================================================================
+// Pins
+//==============================================================================
+
+/// Pin which can be used as the SD clock of the SDHC `S`
+pub trait CkPin<S: SdhcInstance> {}
+
+/// Pin which can be used as the command line of the SDHC `S`
+pub trait CmdPin<S: SdhcInstance> {}
+
+/// Pin which can be used as the data line 0 of the SDHC `S`
+pub trait Dat0Pin<S: SdhcInstance> {}
+
+/// Pin which can be used as the data line 1 of the SDHC `S`
+pub trait Dat1Pin<S: SdhcInstance> {}
+
+/// Pin which can be used as the data line 2 of the SDHC `S`
+pub trait Dat2Pin<S: SdhcInstance> {}
+
+/// Pin which can be used as the data line 3 of the SDHC `S`
+pub trait Dat3Pin<S: SdhcInstance> {}
+
+macro_rules! sdhc_pins {
+    ($( $( #[$cfg:meta] )* $Id:ident: $Trait:ident<$Sdhc:ident> ),+ $(,)?) => {
+        $(
+            $( #[$cfg] )*
+            impl $Trait<crate::pac::$Sdhc> for Pin<$Id, AlternateI> {}
+        )+
+    };
+}
+
+sdhc_pins!(
+    PA08: CmdPin<Sdhc0>,
+    PA09: Dat0Pin<Sdhc0>,
+    PA10: Dat1Pin<Sdhc0>,
+    PA11: Dat2Pin<Sdhc0>,
+    PB10: Dat3Pin<Sdhc0>,
+    PB11: CkPin<Sdhc0>,
+);
+
+#[hal_cfg("sdhc1")]
+sdhc_pins!(
+    PA20: CkPin<Sdhc1>,
+    PA21: CmdPin<Sdhc1>,
+    PB18: Dat0Pin<Sdhc1>,
+    PB19: Dat1Pin<Sdhc1>,
+    PB20: Dat2Pin<Sdhc1>,
+    PB21: Dat3Pin<Sdhc1>,
+);
+
+/// Width of the data bus
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BusWidth {
+    /// `DAT0` only
+    One,
+    /// `DAT0` to `DAT3`
+    Four,
+}
+
+/// Bus pins of an SDHC
+///
+/// Use [`NoneT`] for `dat1`, `dat2` and `dat3` with a 1-bit bus.
+pub struct Pins<CK, CMD, DAT0, DAT1 = NoneT, DAT2 = NoneT, DAT3 = NoneT> {
+    pub ck: CK,
+    pub cmd: CMD,
+    pub dat0: DAT0,
+    pub dat1: DAT1,
+    pub dat2: DAT2,
+    pub dat3: DAT3,
+}
+
+/// Set of bus pins of the SDHC `S`
+///
+/// Implemented for the [`Pins`] of a 1-bit or a 4-bit bus, whose pins all
+/// belong to `S`.
+pub trait BusPins<S: SdhcInstance> {
+    /// Width of the data bus
+    const WIDTH: BusWidth;
+}
+
+impl<S, CK, CMD, DAT0> BusPins<S> for Pins<CK, CMD, DAT0>
+where
+    S: SdhcInstance,
+    CK: CkPin<S>,
+    CMD: CmdPin<S>,
+    DAT0: Dat0Pin<S>,
+{
+    const WIDTH: BusWidth = BusWidth::One;
+}
+
+/// Implement [`BusPins`] for a 4-bit bus
+///
+/// Each SDHC has a single pin for each of `DAT1` to `DAT3`, so they are named
+/// explicitly. This keeps the impl disjoint from the 1-bit one, which uses
+/// [`NoneT`] for these lines.
+macro_rules! four_bit_bus {
+    ($( $( #[$cfg:meta] )* $Sdhc:ident: ($Dat1:ident, $Dat2:ident, $Dat3:ident) ),+ $(,)?) => {
+        $(
+            $( #[$cfg] )*
+            impl<CK, CMD, DAT0> BusPins<crate::pac::$Sdhc>
+                for Pins<
+                    CK,
+                    CMD,
+                    DAT0,
+                    Pin<$Dat1, AlternateI>,
+                    Pin<$Dat2, AlternateI>,
+                    Pin<$Dat3, AlternateI>,
+                >
+            where
+                CK: CkPin<crate::pac::$Sdhc>,
+                CMD: CmdPin<crate::pac::$Sdhc>,
+                DAT0: Dat0Pin<crate::pac::$Sdhc>,
+            {
+                const WIDTH: BusWidth = BusWidth::Four;
+            }
+        )+
+    };
+}
+
+four_bit_bus!(
+    Sdhc0: (PA10, PA11, PB10),
+    #[hal_cfg("sdhc1")]
+    Sdhc1: (PB19, PB20, PB21),
+);
+
+/// Optional card detect or write protect switch
+///
+/// Implemented for all the [`InputPin`]s, and for [`NoneT`].
+pub trait OptionalSwitch {
+    /// Return whether the switch input is high, or `None` without a switch
+    fn is_high(&mut self) -> Option<bool>;
+}
+
+impl OptionalSwitch for NoneT {
+    #[inline]
+    fn is_high(&mut self) -> Option<bool> {
+        None
+    }
+}
+
+impl<P: InputPin> OptionalSwitch for P {
+    #[inline]
+    fn is_high(&mut self) -> Option<bool> {
+        InputPin::is_high(self).ok()
+    }
+}
+
+//==============================================================================
+// Registers
+//==============================================================================
+
+/// [`Registers`] implementation for an SDHC peripheral
+struct Regs<'a> {
+    sdhc: &'a RegisterBlock,
+    base_freq: u32,
+}
+
+impl Registers for Regs<'_> {
+    #[inline]
+    fn present_state(&mut self) -> u32 {
+        self.sdhc.psr().read().bits()
+    }
+
+    #[inline]
+    fn normal_status(&mut self) -> u16 {
+        self.sdhc.nistr().read().bits()
+    }
+
+    #[inline]
+    fn error_status(&mut self) -> u16 {
+        self.sdhc.eistr().read().bits()
+    }
+
+    #[inline]
+    fn clear_status(&mut self, normal: u16, error: u16) {
+        self.sdhc.nistr().write(|w| unsafe { w.bits(normal) });
+        self.sdhc.eistr().write(|w| unsafe { w.bits(error) });
+    }
+
+    fn reset(&mut self, lines: u8) {
+        self.sdhc.srr().write(|w| unsafe { w.bits(lines) });
+        while self.sdhc.srr().read().bits() & lines != 0 {
+            core::hint::spin_loop();
+        }
+    }
+
+    fn set_clock(&mut self, freq: u32) {
+        // Divided clock mode: SDCLK = base / (2 * div), or base if div == 0
+        let div = if freq >= self.base_freq {
+            0
+        } else {
+            self.base_freq.div_ceil(2 * freq).min(0x3FF) as u16
+        };
+
+        self.sdhc
+            .ccr()
+            .modify(|r, w| unsafe { w.bits(r.bits() & !CCR_SDCLKEN) });
+        self.sdhc
+            .ccr()
+            .write(|w| unsafe { w.bits(CCR_INTCLKEN | (div & 0xFF) << 8 | (div >> 8) << 6) });
+        while self.sdhc.ccr().read().bits() & CCR_INTCLKS == 0 {
+            core::hint::spin_loop();
+        }
+        self.sdhc
+            .ccr()
+            .modify(|r, w| unsafe { w.bits(r.bits() | CCR_SDCLKEN) });
+    }
+
+    fn set_bus(&mut self, width: BusWidth, high_speed: bool) {
+        self.sdhc.hc1r().modify(|r, w| {
+            let mut bits = r.bits() & !(HC1R_DW | HC1R_HSEN);
+            if width == BusWidth::Four {
+                bits |= HC1R_DW;
+            }
+            if high_speed {
+                bits |= HC1R_HSEN;
+            }
+            unsafe { w.bits(bits) }
+        });
+    }
+
+    fn set_data(&mut self, table: &[AdmaDescriptor], block_size: u16, block_count: u16) {
+        self.sdhc
+            .asar(0)
+            .write(|w| unsafe { w.bits(table.as_ptr() as u32) });
+        self.sdhc.bsr().write(|w| unsafe { w.bits(block_size) });
+        self.sdhc.bcr().write(|w| unsafe { w.bits(block_count) });
+    }
+
+    fn send(&mut self, argument: u32, mode: u16, command: u16) {
+        self.sdhc.arg1r().write(|w| unsafe { w.bits(argument) });
+        self.sdhc.tmr().write(|w| unsafe { w.bits(mode) });
+        // Writing CR sends the command
+        self.sdhc.cr().write(|w| unsafe { w.bits(command) });
+    }
+
+    #[inline]
+    fn response(&mut self) -> [u32; 4] {
+        core::array::from_fn(|n| self.sdhc.rr(n).read().bits())
+    }
+}
+
+//==============================================================================
+// Sdhc
+//==============================================================================
+
+/// Block buffer aligned for the ADMA
+#[repr(C, align(4))]
+struct AlignedBlock([u8; BLOCK_SIZE]);
+
+/// SD/MMC host controller `S`, with the bus pins `P`, and the optional card
+/// detect `CD` and write protect `WP` switches
+pub struct Sdhc<S: SdhcInstance, P: BusPins<S>, CD = NoneT, WP = NoneT> {
+    sdhc: S,
+    clock: S::Clock,
+    pins: P,
+    card_detect: CD,
+    write_protect: WP,
+    card: Option<Card>,
+    bounce: AlignedBlock,
+}
+
+impl<S: SdhcInstance, P: BusPins<S>> Sdhc<S, P> {
+    /// Create a new [`Sdhc`], and power the card.
+    pub fn new(mclk: &mut Mclk, sdhc: S, clock: S::Clock, pins: P) -> Self {
+        S::enable_ahb_clock(mclk);
+
+        let mut regs = Regs {
+            sdhc: &sdhc,
+            base_freq: S::clock_freq(&clock).to_Hz(),
+        };
+        regs.reset(SRR_SWRSTALL);
+
+        sdhc.pcr().write(|w| w.sdbpwr().on().sdbvsel()._3v3());
+        sdhc.tcr().write(|w| unsafe { w.dtcval().bits(0xE) });
+        // The card presence is tracked with the card detect switch, so the
+        // controller always sees a card
+        sdhc.hc1r().write(|w| {
+            w.dmasel()._32bit();
+            w.carddtl().yes();
+            w.carddsel().test()
+        });
+        sdhc.nister().write(|w| unsafe { w.bits(0x00FF) });
+        sdhc.eister().write(|w| unsafe { w.bits(0x03FF) });
+
+        Self {
+            sdhc,
+            clock,
+            pins,
+            card_detect: NoneT,
+            write_protect: NoneT,
+            card: None,
+            bounce: AlignedBlock([0; BLOCK_SIZE]),
+        }
+    }
+}
+
+impl<S: SdhcInstance, P: BusPins<S>, WP> Sdhc<S, P, NoneT, WP> {
+    /// Add a card detect switch, which is low when a card is inserted.
+    pub fn with_card_detect<CD: InputPin>(self, pin: CD) -> Sdhc<S, P, CD, WP> {
+        Sdhc {
+            sdhc: self.sdhc,
+            clock: self.clock,
+            pins: self.pins,
+            card_detect: pin,
+            write_protect: self.write_protect,
+            card: self.card,
+            bounce: self.bounce,
+        }
+    }
+}
+
+impl<S: SdhcInstance, P: BusPins<S>, CD> Sdhc<S, P, CD, NoneT> {
+    /// Add a write protect switch, which is high when the card is write
+    /// protected.
+    pub fn with_write_protect<WP: InputPin>(self, pin: WP) -> Sdhc<S, P, CD, WP> {
+        Sdhc {
+            sdhc: self.sdhc,
+            clock: self.clock,
+            pins: self.pins,
+            card_detect: self.card_detect,
+            write_protect: pin,
+            card: self.card,
+            bounce: self.bounce,
+        }
+    }
+}
+
+impl<S, P, CD, WP> Sdhc<S, P, CD, WP>
+where
+    S: SdhcInstance,
+    P: BusPins<S>,
+    CD: OptionalSwitch,
+    WP: OptionalSwitch,
+{
+    #[inline]
+    fn regs<'a>(sdhc: &'a S, clock: &S::Clock) -> Regs<'a> {
+        Regs {
+            sdhc,
+            base_freq: S::clock_freq(clock).to_Hz(),
+        }
+    }
+
+    /// Return whether a card is inserted.
+    ///
+    /// Without a card detect switch, a card is assumed to be present.
+    pub fn card_present(&mut self) -> bool {
+        self.card_detect.is_high() != Some(true)
+    }
+
+    /// Return whether the card is write protected.
+    ///
+    /// Without a write protect switch, the card is assumed to be writable.
+    pub fn write_protected(&mut self) -> bool {
+        self.write_protect.is_high() == Some(true)
+    }
+
+    /// Identify and initialize the card, using an SD clock of at most
+    /// `max_freq`.
+    ///
+    /// The card is switched to the bus width of the pins, and to the
+    /// high-speed timing if it supports it and `max_freq` is above 25 MHz.
+    pub fn init_card<D: DelayNs>(&mut self, delay: &mut D, max_freq: Hertz) -> Result<Card, Error> {
+        self.card = None;
+        if !self.card_present() {
+            return Err(Error::NoCard);
+        }
+        let mut regs = Self::regs(&self.sdhc, &self.clock);
+        let card = protocol::initialize(&mut regs, delay, P::WIDTH, max_freq.to_Hz())?;
+        self.card = Some(card);
+        Ok(card)
+    }
+
+    /// Return the initialized card, if any.
+    #[inline]
+    pub fn card(&self) -> Option<&Card> {
+        self.card.as_ref()
+    }
+
+    /// Check that the card is still present, and that `len` bytes from
+    /// `block` are within the card
+    fn check_transfer(&mut self, block: u32, len: usize) -> Result<Card, Error> {
+        if !self.card_present() {
+            self.card = None;
+            return Err(Error::NoCard);
+        }
+        let card = self.card.ok_or(Error::NotInitialized)?;
+        if len % BLOCK_SIZE != 0 {
+            return Err(Error::InvalidLength);
+        }
+        let end = block as u64 + (len / BLOCK_SIZE) as u64;
+        if end > card.num_blocks as u64 {
+            return Err(Error::OutOfRange);
+        }
+        Ok(card)
+    }
+
+    /// Read blocks starting at `block` into `buffer`, whose length must be a
+    /// multiple of [`BLOCK_SIZE`].
+    ///
+    /// Word-aligned buffers are transferred directly by the ADMA, with
+    /// multiple block reads. Other buffers are read block by block through
+    /// an internal buffer.
+    pub fn read_blocks(&mut self, block: u32, buffer: &mut [u8]) -> Result<(), Error> {
+        let card = self.check_transfer(block, buffer.len())?;
+        let mut regs = Self::regs(&self.sdhc, &self.clock);
+        let mut table = [AdmaDescriptor::default(); ADMA_TABLE_LEN];
+
+        if buffer.as_ptr() as usize % 4 == 0 {
+            let chunks = buffer.chunks_mut(MAX_TRANSFER_BLOCKS * BLOCK_SIZE);
+            for (start, chunk) in (block..).step_by(MAX_TRANSFER_BLOCKS).zip(chunks) {
+                let count = (chunk.len() / BLOCK_SIZE) as u16;
+                let table =
+                    protocol::adma_table(&mut table, chunk.as_mut_ptr() as u32, chunk.len());
+                protocol::read_blocks(&mut regs, &card, start, table, count)?;
+            }
+        } else {
+            let bounce = &mut self.bounce.0;
+            for (start, chunk) in (block..).zip(buffer.chunks_mut(BLOCK_SIZE)) {
+                let table =
+                    protocol::adma_table(&mut table, bounce.as_mut_ptr() as u32, BLOCK_SIZE);
+                protocol::read_blocks(&mut regs, &card, start, table, 1)?;
+                chunk.copy_from_slice(bounce);
+            }
+        }
+        Ok(())
+    }
+
+    /// Write `buffer` to the blocks starting at `block`, and wait for the
+    /// card to program them. The length of `buffer` must be a multiple of
+    /// [`BLOCK_SIZE`].
+    ///
+    /// Word-aligned buffers are transferred directly by the ADMA, with
+    /// multiple block writes. Other buffers are written block by block
+    /// through an internal buffer.
+    pub fn write_blocks(&mut self, block: u32, buffer: &[u8]) -> Result<(), Error> {
+        let card = self.check_transfer(block, buffer.len())?;
+        if self.write_protected() {
+            return Err(Error::WriteProtected);
+        }
+        let mut regs = Self::regs(&self.sdhc, &self.clock);
+        let mut table = [AdmaDescriptor::default(); ADMA_TABLE_LEN];
+
+        if buffer.as_ptr() as usize % 4 == 0 {
+            let chunks = buffer.chunks(MAX_TRANSFER_BLOCKS * BLOCK_SIZE);
+            for (start, chunk) in (block..).step_by(MAX_TRANSFER_BLOCKS).zip(chunks) {
+                let count = (chunk.len() / BLOCK_SIZE) as u16;
+                let table = protocol::adma_table(&mut table, chunk.as_ptr() as u32, chunk.len());
+                protocol::write_blocks(&mut regs, &card, start, table, count)?;
+            }
+        } else {
+            let bounce = &mut self.bounce.0;
+            for (start, chunk) in (block..).zip(buffer.chunks(BLOCK_SIZE)) {
+                bounce.copy_from_slice(chunk);
+                let table = protocol::adma_table(&mut table, bounce.as_ptr() as u32, BLOCK_SIZE);
+                protocol::write_blocks(&mut regs, &card, start, table, 1)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Power off the card, reset the SDHC, and return the underlying
+    /// resources.
+    pub fn free(self) -> (S, S::Clock, P, CD, WP) {
+        Self::regs(&self.sdhc, &self.clock).reset(SRR_SWRSTALL);
+        self.sdhc.pcr().write(|w| w.sdbpwr().off());
+        (
+            self.sdhc,
+            self.clock,
+            self.pins,
+            self.card_detect,
+            self.write_protect,
+        )
+    }
+}
+
+//==============================================================================
+// BlockDevice
+//==============================================================================
+
+/// [`BlockDevice`] backed by an [`Sdhc`] with an initialized card
+#[cfg(feature = "sdmmc")]
+pub struct SdhcBlockDevice<S: SdhcInstance, P: BusPins<S>, CD = NoneT, WP = NoneT> {
+    sdhc: RefCell<Sdhc<S, P, CD, WP>>,
+}
+
+#[cfg(feature = "sdmmc")]
+impl<S, P, CD, WP> SdhcBlockDevice<S, P, CD, WP>
+where
+    S: SdhcInstance,
+    P: BusPins<S>,
+    CD: OptionalSwitch,
+    WP: OptionalSwitch,
+{
+    /// Create a new [`SdhcBlockDevice`].
+    ///
+    /// The card should have been initialized with [`Sdhc::init_card`],
+    /// otherwise all the accesses return [`Error::NotInitialized`].
+    pub fn new(sdhc: Sdhc<S, P, CD, WP>) -> Self {
+        Self {
+            sdhc: RefCell::new(sdhc),
+        }
+    }
+
+    /// Return the underlying [`Sdhc`].
+    pub fn into_inner(self) -> Sdhc<S, P, CD, WP> {
+        self.sdhc.into_inner()
+    }
+}
+
+#[cfg(feature = "sdmmc")]
+impl<S, P, CD, WP> BlockDevice for SdhcBlockDevice<S, P, CD, WP>
+where
+    S: SdhcInstance,
+    P: BusPins<S>,
+    CD: OptionalSwitch,
+    WP: OptionalSwitch,
+{
+    type Error = Error;
+
+    fn read(
+        &self,
+        blocks: &mut [Block],
+        start_block_idx: BlockIdx,
+        _reason: &str,
+    ) -> Result<(), Self::Error> {
+        let mut sdhc = self.sdhc.borrow_mut();
+        for (idx, block) in (start_block_idx.0..).zip(blocks) {
+            sdhc.read_blocks(idx, &mut block.contents)?;
+        }
+        Ok(())
+    }
+
+    fn write(&self, blocks: &[Block], start_block_idx: BlockIdx) -> Result<(), Self::Error> {
+        let mut sdhc = self.sdhc.borrow_mut();
+        for (idx, block) in (start_block_idx.0..).zip(blocks) {
+            sdhc.write_blocks(idx, &block.contents)?;
+        }
+        Ok(())
+    }
+
+    fn num_blocks(&self) -> Result<BlockCount, Self::Error> {
+        let sdhc = self.sdhc.borrow();
+        let card = sdhc.card().ok_or(Error::NotInitialized)?;
+        Ok(BlockCount(card.num_blocks))
+    }
+}
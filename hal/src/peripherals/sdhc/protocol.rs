@@ -0,0 +1,958 @@
+//! SD card protocol
+//!
+//! Command/response handling, card initialization and block transfers of the
+//! [`Sdhc`](super::Sdhc) driver. The state machine only accesses the
+//! controller through the [`Registers`] trait, so that it can be tested on
+//! the host against a mock register set.
+
+use core::sync::atomic::{fence, Ordering};
+
+use crate::ehal::delay::DelayNs;
+
+use super::BusWidth;
+
+/// Size of a data block, in bytes
+pub const BLOCK_SIZE: usize = 512;
+
+/// `PSR` bits
+const PSR_CMDINHC: u32 = 1 << 0;
+const PSR_CMDINHD: u32 = 1 << 1;
+
+/// `NISTR` bits
+const NISTR_CMDC: u16 = 1 << 0;
+const NISTR_TRFC: u16 = 1 << 1;
+const NISTR_ERRINT: u16 = 1 << 15;
+
+/// `EISTR` bits
+const EISTR_CMDTEO: u16 = 1 << 0;
+const EISTR_CMDCRC: u16 = 1 << 1;
+const EISTR_CMDEND: u16 = 1 << 2;
+const EISTR_CMDIDX: u16 = 1 << 3;
+const EISTR_DATTEO: u16 = 1 << 4;
+const EISTR_DATCRC: u16 = 1 << 5;
+const EISTR_DATEND: u16 = 1 << 6;
+const EISTR_CURLIM: u16 = 1 << 7;
+const EISTR_ACMD: u16 = 1 << 8;
+/// Errors of the command line
+const EISTR_CMD_LINE: u16 = EISTR_CMDTEO | EISTR_CMDCRC | EISTR_CMDEND | EISTR_CMDIDX;
+
+/// `SRR` bits
+pub(super) const SRR_SWRSTALL: u8 = 1 << 0;
+const SRR_SWRSTCMD: u8 = 1 << 1;
+const SRR_SWRSTDAT: u8 = 1 << 2;
+
+/// `TMR` bits
+const TMR_DMAEN: u16 = 1 << 0;
+const TMR_BCEN: u16 = 1 << 1;
+const TMR_ACMD12: u16 = 1 << 2;
+const TMR_DTDSEL_READ: u16 = 1 << 4;
+const TMR_MSBSEL: u16 = 1 << 5;
+
+/// `CR` bits
+const CR_RESPTYP_136: u16 = 1;
+const CR_RESPTYP_48: u16 = 2;
+const CR_RESPTYP_48_BUSY: u16 = 3;
+const CR_CMDCCEN: u16 = 1 << 3;
+const CR_CMDICEN: u16 = 1 << 4;
+const CR_DPSEL: u16 = 1 << 5;
+
+/// Frequency of the SD clock during the card identification
+const INIT_FREQ: u32 = 400_000;
+/// Maximum frequency of the SD clock in default speed mode
+const DEFAULT_SPEED_FREQ: u32 = 25_000_000;
+/// Maximum frequency of the SD clock in high-speed mode
+const HIGH_SPEED_FREQ: u32 = 50_000_000;
+
+/// `CMD8` argument: 2.7-3.6V supply and check pattern
+const CMD8_ARG: u32 = 0x1AA;
+/// `ACMD41` argument: 2.7-3.6V operating voltage window
+const OCR_VOLTAGE_WINDOW: u32 = 0x00FF_8000;
+/// `ACMD41` argument: host supports high capacity cards
+const OCR_HCS: u32 = 1 << 30;
+/// `ACMD41` response: card capacity status
+const OCR_CCS: u32 = 1 << 30;
+/// `ACMD41` response: card power up status
+const OCR_POWER_UP: u32 = 1 << 31;
+/// Number of `ACMD41` attempts, 1 ms apart, before giving up
+const ACMD41_RETRIES: u32 = 1000;
+/// `ACMD6` argument: 4-bit bus
+const ACMD6_4BIT: u32 = 0b10;
+/// `CMD6` argument: switch to the high-speed function of group 1
+const CMD6_HIGH_SPEED: u32 = 0x80FF_FFF1;
+/// `CCC` bit of the switch command class
+const CCC_SWITCH: u32 = 1 << 10;
+
+/// Error bits of the R1 card status
+const R1_ERRORS: u32 = 0xFDF9_8008;
+/// R1 card status: ready for data
+const R1_READY_FOR_DATA: u32 = 1 << 8;
+/// R1 card status: transfer state
+const R1_STATE_TRAN: u32 = 4;
+
+//==============================================================================
+// Error
+//==============================================================================
+
+/// Errors returned by the SDHC driver
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// No card is inserted
+    NoCard,
+    /// The card is not initialized
+    NotInitialized,
+    /// The card is write protected
+    WriteProtected,
+    /// The card did not respond to a command
+    CommandTimeout,
+    /// CRC error in a command response
+    CommandCrc,
+    /// End bit error in a command response
+    CommandEndBit,
+    /// Wrong command index in a command response
+    CommandIndex,
+    /// The card did not send or accept the data in time
+    DataTimeout,
+    /// CRC error in a data block
+    DataCrc,
+    /// End bit error in a data block
+    DataEndBit,
+    /// The card drew too much current
+    CurrentLimit,
+    /// The automatic `CMD12` failed
+    AutoCommand,
+    /// The ADMA failed to access a descriptor or a buffer
+    Adma,
+    /// The card reported an error in its status
+    CardStatus(u32),
+    /// The card does not support the host voltage, or its CSD is invalid
+    UnsupportedCard,
+    /// The card did not power up in time
+    InitTimeout,
+    /// The buffer length is not a multiple of [`BLOCK_SIZE`]
+    InvalidLength,
+    /// The blocks are past the end of the card
+    OutOfRange,
+}
+
+impl Error {
+    /// Convert the content of `EISTR`
+    fn from_status(status: u16) -> Self {
+        if status & EISTR_CMDTEO != 0 {
+            Error::CommandTimeout
+        } else if status & EISTR_CMDCRC != 0 {
+            Error::CommandCrc
+        } else if status & EISTR_CMDEND != 0 {
+            Error::CommandEndBit
+        } else if status & EISTR_CMDIDX != 0 {
+            Error::CommandIndex
+        } else if status & EISTR_DATTEO != 0 {
+            Error::DataTimeout
+        } else if status & EISTR_DATCRC != 0 {
+            Error::DataCrc
+        } else if status & EISTR_DATEND != 0 {
+            Error::DataEndBit
+        } else if status & EISTR_CURLIM != 0 {
+            Error::CurrentLimit
+        } else if status & EISTR_ACMD != 0 {
+            Error::AutoCommand
+        } else {
+            Error::Adma
+        }
+    }
+}
+
+//==============================================================================
+// Registers
+//==============================================================================
+
+/// Access to the SDHC registers used by the protocol
+pub(super) trait Registers {
+    /// Read `PSR`
+    fn present_state(&mut self) -> u32;
+
+    /// Read `NISTR`
+    fn normal_status(&mut self) -> u16;
+
+    /// Read `EISTR`
+    fn error_status(&mut self) -> u16;
+
+    /// Clear flags of `NISTR` and `EISTR`
+    fn clear_status(&mut self, normal: u16, error: u16);
+
+    /// Reset the lines selected in `SRR`, and wait for the end of the reset
+    fn reset(&mut self, lines: u8);
+
+    /// Set the SD clock to the highest frequency not above `freq`
+    fn set_clock(&mut self, freq: u32);
+
+    /// Set the data bus width and the high-speed timing
+    fn set_bus(&mut self, width: BusWidth, high_speed: bool);
+
+    /// Set the ADMA2 descriptor table, block size and block count of the
+    /// next data transfer
+    fn set_data(&mut self, table: &[AdmaDescriptor], block_size: u16, block_count: u16);
+
+    /// Write `ARG1R`, `TMR` and `CR`, which sends the command
+    fn send(&mut self, argument: u32, mode: u16, command: u16);
+
+    /// Read `RR0` to `RR3`
+    fn response(&mut self) -> [u32; 4];
+}
+
+//==============================================================================
+// ADMA2
+//==============================================================================
+
+/// Number of descriptors of an ADMA2 descriptor table
+pub(super) const ADMA_TABLE_LEN: usize = 8;
+
+/// Length of the data described by a single descriptor
+const ADMA_MAX_LENGTH: usize = 64 * BLOCK_SIZE;
+
+/// Maximum number of blocks of a single data transfer
+pub(super) const MAX_TRANSFER_BLOCKS: usize = ADMA_TABLE_LEN * ADMA_MAX_LENGTH / BLOCK_SIZE;
+
+/// ADMA2 descriptor attributes
+const ADMA_VALID: u16 = 1 << 0;
+const ADMA_END: u16 = 1 << 1;
+const ADMA_ACT_TRAN: u16 = 0b10 << 4;
+
+/// 32-bit ADMA2 descriptor
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(C, align(4))]
+pub(super) struct AdmaDescriptor {
+    attributes: u16,
+    length: u16,
+    address: u32,
+}
+
+/// Fill `table` with the descriptors of `len` bytes at `address`, and return
+/// the used part of the table.
+///
+/// `len` must be at most [`MAX_TRANSFER_BLOCKS`] blocks.
+pub(super) fn adma_table(
+    table: &mut [AdmaDescriptor; ADMA_TABLE_LEN],
+    address: u32,
+    len: usize,
+) -> &[AdmaDescriptor] {
+    let count = len.div_ceil(ADMA_MAX_LENGTH).clamp(1, ADMA_TABLE_LEN);
+    for (i, descriptor) in table[..count].iter_mut().enumerate() {
+        let offset = i * ADMA_MAX_LENGTH;
+        let mut attributes = ADMA_VALID | ADMA_ACT_TRAN;
+        if i == count - 1 {
+            attributes |= ADMA_END;
+        }
+        *descriptor = AdmaDescriptor {
+            attributes,
+            length: (len - offset).min(ADMA_MAX_LENGTH) as u16,
+            address: address + offset as u32,
+        };
+    }
+    &table[..count]
+}
+
+//==============================================================================
+// Commands
+//==============================================================================
+
+/// Type of a command response
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Response {
+    None,
+    /// Card status
+    R1,
+    /// Card status, followed by busy signalling on `DAT0`
+    R1b,
+    /// CID or CSD
+    R2,
+    /// OCR
+    R3,
+    /// Published RCA
+    R6,
+    /// Card interface condition
+    R7,
+}
+
+impl Response {
+    /// `CR` bits of the response
+    fn bits(self) -> u16 {
+        match self {
+            Response::None => 0,
+            Response::R2 => CR_RESPTYP_136 | CR_CMDCCEN,
+            Response::R3 => CR_RESPTYP_48,
+            Response::R1 | Response::R6 | Response::R7 => CR_RESPTYP_48 | CR_CMDCCEN | CR_CMDICEN,
+            Response::R1b => CR_RESPTYP_48_BUSY | CR_CMDCCEN | CR_CMDICEN,
+        }
+    }
+}
+
+/// Direction of a data transfer
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Direction {
+    Read,
+    Write,
+}
+
+/// Data transfer of a command
+struct Transfer<'a> {
+    table: &'a [AdmaDescriptor],
+    block_size: u16,
+    block_count: u16,
+    direction: Direction,
+}
+
+/// Wait until `flag` is set in `NISTR`, and clear it.
+///
+/// On error, the lines in error are reset.
+fn wait_for<R: Registers>(regs: &mut R, flag: u16) -> Result<(), Error> {
+    loop {
+        let status = regs.normal_status();
+        if status & NISTR_ERRINT != 0 {
+            let errors = regs.error_status();
+            regs.clear_status(NISTR_ERRINT, errors);
+            let mut lines = 0;
+            if errors & EISTR_CMD_LINE != 0 {
+                lines |= SRR_SWRSTCMD;
+            }
+            if errors & !EISTR_CMD_LINE != 0 {
+                lines |= SRR_SWRSTDAT;
+            }
+            regs.reset(lines);
+            return Err(Error::from_status(errors));
+        }
+        if status & flag != 0 {
+            regs.clear_status(flag, 0);
+            return Ok(());
+        }
+    }
+}
+
+/// Send a command, with an optional data transfer, and return its response.
+///
+/// For commands with an R1 response, the card status is checked for errors.
+fn send_command<R: Registers>(
+    regs: &mut R,
+    index: u8,
+    argument: u32,
+    response: Response,
+    data: Option<Transfer<'_>>,
+) -> Result<[u32; 4], Error> {
+    let busy = data.is_some() || response == Response::R1b;
+    let inhibit = if busy {
+        PSR_CMDINHC | PSR_CMDINHD
+    } else {
+        PSR_CMDINHC
+    };
+    while regs.present_state() & inhibit != 0 {
+        core::hint::spin_loop();
+    }
+    regs.clear_status(!0, !0);
+
+    let mut mode = 0;
+    let mut command = response.bits() | (index as u16) << 8;
+    if let Some(data) = data {
+        regs.set_data(data.table, data.block_size, data.block_count);
+        mode = TMR_DMAEN | TMR_BCEN;
+        if data.block_count > 1 {
+            mode |= TMR_MSBSEL | TMR_ACMD12;
+        }
+        if data.direction == Direction::Read {
+            mode |= TMR_DTDSEL_READ;
+        }
+        command |= CR_DPSEL;
+        // Make sure the buffers are up to date before the ADMA accesses them
+        fence(Ordering::SeqCst);
+    }
+
+    regs.send(argument, mode, command);
+    wait_for(regs, NISTR_CMDC)?;
+    let words = regs.response();
+    if busy {
+        wait_for(regs, NISTR_TRFC)?;
+        fence(Ordering::SeqCst);
+    }
+
+    if matches!(response, Response::R1 | Response::R1b) && words[0] & R1_ERRORS != 0 {
+        return Err(Error::CardStatus(words[0]));
+    }
+    Ok(words)
+}
+
+/// Send `CMD55`, announcing an application specific command
+fn app_command<R: Registers>(regs: &mut R, rca: u16) -> Result<(), Error> {
+    send_command(regs, 55, (rca as u32) << 16, Response::R1, None)?;
+    Ok(())
+}
+
+//==============================================================================
+// Card
+//==============================================================================
+
+/// Initialized SD card
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Card {
+    /// Relative card address
+    pub rca: u16,
+    /// Whether the card is a high capacity (SDHC/SDXC) card, addressed by
+    /// blocks instead of bytes
+    pub high_capacity: bool,
+    /// Whether the card uses the high-speed timing
+    pub high_speed: bool,
+    /// Width of the data bus
+    pub bus_width: BusWidth,
+    /// Capacity of the card, in blocks of [`BLOCK_SIZE`] bytes
+    pub num_blocks: u32,
+    /// Card identification register, without the CRC
+    pub cid: [u32; 4],
+    /// Card specific data register, without the CRC
+    pub csd: [u32; 4],
+}
+
+impl Card {
+    /// Command argument addressing the block `block`
+    #[inline]
+    fn address(&self, block: u32) -> u32 {
+        if self.high_capacity {
+            block
+        } else {
+            block * BLOCK_SIZE as u32
+        }
+    }
+}
+
+/// Extract a field of a CID or CSD register, whose `lsb` is counted in the
+/// register including the CRC
+fn register_field(words: &[u32; 4], lsb: u32, width: u32) -> u32 {
+    // The response registers hold bits [127:8], without the CRC
+    let raw = (words[3] as u128) << 96
+        | (words[2] as u128) << 64
+        | (words[1] as u128) << 32
+        | words[0] as u128;
+    ((raw >> (lsb - 8)) & ((1 << width) - 1)) as u32
+}
+
+/// Compute the capacity of a card from its CSD
+fn csd_num_blocks(csd: &[u32; 4]) -> Option<u32> {
+    match register_field(csd, 126, 2) {
+        // CSD version 1.0, for standard capacity cards
+        0 => {
+            let c_size = register_field(csd, 62, 12) as u64;
+            let c_size_mult = register_field(csd, 47, 3);
+            let read_bl_len = register_field(csd, 80, 4);
+            let bytes = (c_size + 1) << (c_size_mult + 2 + read_bl_len);
+            u32::try_from(bytes / BLOCK_SIZE as u64).ok()
+        }
+        // CSD version 2.0, for high and extended capacity cards
+        1 => (register_field(csd, 48, 22) + 1).checked_mul(1024),
+        _ => None,
+    }
+}
+
+/// Identify and initialize the card, and switch it to the widest bus and
+/// fastest timing allowed by `width` and `max_freq`.
+pub(super) fn initialize<R: Registers, D: DelayNs>(
+    regs: &mut R,
+    delay: &mut D,
+    width: BusWidth,
+    max_freq: u32,
+) -> Result<Card, Error> {
+    regs.set_bus(BusWidth::One, false);
+    regs.set_clock(INIT_FREQ.min(max_freq));
+    // Give the card at least 74 clock cycles to power up
+    delay.delay_ms(1);
+
+    send_command(regs, 0, 0, Response::None, None)?;
+
+    // Version 1.x cards do not know CMD8
+    let v2 = match send_command(regs, 8, CMD8_ARG, Response::R7, None) {
+        Ok(words) if words[0] & 0xFFF == CMD8_ARG => true,
+        Ok(_) => return Err(Error::UnsupportedCard),
+        Err(Error::CommandTimeout) => false,
+        Err(e) => return Err(e),
+    };
+
+    let argument = if v2 {
+        OCR_VOLTAGE_WINDOW | OCR_HCS
+    } else {
+        OCR_VOLTAGE_WINDOW
+    };
+    let mut ocr = 0;
+    for _ in 0..ACMD41_RETRIES {
+        app_command(regs, 0)?;
+        ocr = send_command(regs, 41, argument, Response::R3, None)?[0];
+        if ocr & OCR_POWER_UP != 0 {
+            break;
+        }
+        delay.delay_ms(1);
+    }
+    if ocr & OCR_POWER_UP == 0 {
+        return Err(Error::InitTimeout);
+    }
+    if ocr & OCR_VOLTAGE_WINDOW == 0 {
+        return Err(Error::UnsupportedCard);
+    }
+    let high_capacity = ocr & OCR_CCS != 0;
+
+    let cid = send_command(regs, 2, 0, Response::R2, None)?;
+    let rca = (send_command(regs, 3, 0, Response::R6, None)?[0] >> 16) as u16;
+    let csd = send_command(regs, 9, (rca as u32) << 16, Response::R2, None)?;
+    let num_blocks = csd_num_blocks(&csd).ok_or(Error::UnsupportedCard)?;
+
+    send_command(regs, 7, (rca as u32) << 16, Response::R1b, None)?;
+    if !high_capacity {
+        send_command(regs, 16, BLOCK_SIZE as u32, Response::R1, None)?;
+    }
+
+    if width == BusWidth::Four {
+        app_command(regs, rca)?;
+        send_command(regs, 6, ACMD6_4BIT, Response::R1, None)?;
+    }
+    regs.set_bus(width, false);
+    regs.set_clock(DEFAULT_SPEED_FREQ.min(max_freq));
+
+    let supports_switch = register_field(&csd, 84, 12) & CCC_SWITCH != 0;
+    let high_speed = max_freq > DEFAULT_SPEED_FREQ && supports_switch && switch_high_speed(regs)?;
+    if high_speed {
+        regs.set_bus(width, true);
+        regs.set_clock(HIGH_SPEED_FREQ.min(max_freq));
+    }
+
+    Ok(Card {
+        rca,
+        high_capacity,
+        high_speed,
+        bus_width: width,
+        num_blocks,
+        cid,
+        csd,
+    })
+}
+
+/// Switch status returned by `CMD6`
+#[repr(C, align(4))]
+struct SwitchStatus([u8; 64]);
+
+/// Switch the card to the high-speed function, and return whether it
+/// succeeded
+fn switch_high_speed<R: Registers>(regs: &mut R) -> Result<bool, Error> {
+    let mut status = SwitchStatus([0; 64]);
+    let mut table = [AdmaDescriptor::default(); ADMA_TABLE_LEN];
+    let table = adma_table(&mut table, status.0.as_mut_ptr() as u32, status.0.len());
+    let data = Transfer {
+        table,
+        block_size: status.0.len() as u16,
+        block_count: 1,
+        direction: Direction::Read,
+    };
+    send_command(regs, 6, CMD6_HIGH_SPEED, Response::R1, Some(data))?;
+    // Function selected in group 1, in bits [379:376] of the big-endian status.
+    // SAFETY: The ADMA is done writing the status.
+    let selected = unsafe { core::ptr::read_volatile(&status.0[16]) } & 0x0F;
+    Ok(selected == 1)
+}
+
+/// Wait until the card is done programming and back in the transfer state
+fn wait_ready<R: Registers>(regs: &mut R, card: &Card) -> Result<(), Error> {
+    loop {
+        let status = send_command(regs, 13, (card.rca as u32) << 16, Response::R1, None)?[0];
+        if status & R1_READY_FOR_DATA != 0 && (status >> 9) & 0xF == R1_STATE_TRAN {
+            return Ok(());
+        }
+    }
+}
+
+/// Read `count` blocks starting at `block` through the ADMA2 descriptor table
+/// `table`
+pub(super) fn read_blocks<R: Registers>(
+    regs: &mut R,
+    card: &Card,
+    block: u32,
+    table: &[AdmaDescriptor],
+    count: u16,
+) -> Result<(), Error> {
+    let index = if count > 1 { 18 } else { 17 };
+    let data = Transfer {
+        table,
+        block_size: BLOCK_SIZE as u16,
+        block_count: count,
+        direction: Direction::Read,
+    };
+    send_command(regs, index, card.address(block), Response::R1, Some(data))?;
+    Ok(())
+}
+
+/// Write `count` blocks starting at `block` through the ADMA2 descriptor
+/// table `table`, and wait for the card to program them
+pub(super) fn write_blocks<R: Registers>(
+    regs: &mut R,
+    card: &Card,
+    block: u32,
+    table: &[AdmaDescriptor],
+    count: u16,
+) -> Result<(), Error> {
+    let index = if count > 1 { 25 } else { 24 };
+    let data = Transfer {
+        table,
+        block_size: BLOCK_SIZE as u16,
+        block_count: count,
+        direction: Direction::Write,
+    };
+    send_command(regs, index, card.address(block), Response::R1, Some(data))?;
+    wait_ready(regs, card)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// R1 status of an idle card in the transfer state
+    const STATUS_TRAN: u32 = R1_READY_FOR_DATA | R1_STATE_TRAN << 9;
+
+    struct NoDelay;
+
+    impl DelayNs for NoDelay {
+        fn delay_ns(&mut self, _ns: u32) {}
+    }
+
+    /// Command received by the mock card
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    struct Sent {
+        app: bool,
+        index: u8,
+        argument: u32,
+        mode: u16,
+        block_count: u16,
+    }
+
+    /// Mock register set, answering commands like an SD card
+    struct MockCard {
+        v2: bool,
+        high_capacity: bool,
+        busy_rounds: u32,
+        csd: [u32; 4],
+        fail_command: Option<u8>,
+        app: bool,
+        normal: u16,
+        error: u16,
+        words: [u32; 4],
+        block_count: u16,
+        resets: u8,
+        clock: u32,
+        bus: (BusWidth, bool),
+        sent: [Sent; 64],
+        num_sent: usize,
+        acmd41_count: u32,
+    }
+
+    impl MockCard {
+        fn new(v2: bool, high_capacity: bool, csd: [u32; 4]) -> Self {
+            Self {
+                v2,
+                high_capacity,
+                busy_rounds: 2,
+                csd,
+                fail_command: None,
+                app: false,
+                normal: 0,
+                error: 0,
+                words: [0; 4],
+                block_count: 0,
+                resets: 0,
+                clock: 0,
+                bus: (BusWidth::One, false),
+                sent: [Sent::default(); 64],
+                num_sent: 0,
+                acmd41_count: 0,
+            }
+        }
+
+        fn sent(&self) -> &[Sent] {
+            &self.sent[..self.num_sent]
+        }
+
+        fn find(&self, app: bool, index: u8) -> Option<Sent> {
+            self.sent()
+                .iter()
+                .copied()
+                .find(|sent| sent.app == app && sent.index == index)
+        }
+
+        fn fail(&mut self, error: u16) {
+            self.error = error;
+            self.normal = NISTR_ERRINT;
+        }
+    }
+
+    impl Registers for MockCard {
+        fn present_state(&mut self) -> u32 {
+            0
+        }
+
+        fn normal_status(&mut self) -> u16 {
+            self.normal
+        }
+
+        fn error_status(&mut self) -> u16 {
+            self.error
+        }
+
+        fn clear_status(&mut self, normal: u16, error: u16) {
+            self.normal &= !normal;
+            self.error &= !error;
+        }
+
+        fn reset(&mut self, lines: u8) {
+            self.resets |= lines;
+        }
+
+        fn set_clock(&mut self, freq: u32) {
+            self.clock = freq;
+        }
+
+        fn set_bus(&mut self, width: BusWidth, high_speed: bool) {
+            self.bus = (width, high_speed);
+        }
+
+        fn set_data(&mut self, _table: &[AdmaDescriptor], _block_size: u16, block_count: u16) {
+            self.block_count = block_count;
+        }
+
+        fn send(&mut self, argument: u32, mode: u16, command: u16) {
+            let index = (command >> 8) as u8;
+            let app = core::mem::take(&mut self.app);
+            if app && index == 41 {
+                self.acmd41_count += 1;
+            }
+            if let Some(sent) = self.sent.get_mut(self.num_sent) {
+                *sent = Sent {
+                    app,
+                    index,
+                    argument,
+                    mode,
+                    block_count: if command & CR_DPSEL != 0 {
+                        self.block_count
+                    } else {
+                        0
+                    },
+                };
+                self.num_sent += 1;
+            }
+
+            if self.fail_command == Some(index) {
+                return self.fail(EISTR_CMDCRC);
+            }
+            self.words = match (app, index) {
+                (_, 8) if !self.v2 => return self.fail(EISTR_CMDTEO),
+                (_, 0) => [0; 4],
+                (_, 8) => [argument; 4],
+                (_, 55) => {
+                    self.app = true;
+                    [STATUS_TRAN | 1 << 5; 4]
+                }
+                (true, 41) if self.busy_rounds > 0 => {
+                    self.busy_rounds -= 1;
+                    [OCR_VOLTAGE_WINDOW; 4]
+                }
+                (true, 41) => {
+                    let ccs = self.high_capacity && argument & OCR_HCS != 0;
+                    [OCR_POWER_UP | OCR_VOLTAGE_WINDOW | if ccs { OCR_CCS } else { 0 }; 4]
+                }
+                (_, 2) => [0x0102_0304, 0x0506_0708, 0x090A_0B0C, 0x000E_0F10],
+                (_, 3) => [0x1234 << 16; 4],
+                (_, 9) => self.csd,
+                _ => [STATUS_TRAN; 4],
+            };
+            self.normal = NISTR_CMDC | NISTR_TRFC;
+        }
+
+        fn response(&mut self) -> [u32; 4] {
+            self.words
+        }
+    }
+
+    /// Build the response registers of a CSD from its fields
+    fn csd(fields: &[(u32, u32, u32)]) -> [u32; 4] {
+        let mut raw = 0u128;
+        for &(lsb, width, value) in fields {
+            raw |= ((value as u128) & ((1 << width) - 1)) << (lsb - 8);
+        }
+        [
+            raw as u32,
+            (raw >> 32) as u32,
+            (raw >> 64) as u32,
+            (raw >> 96) as u32,
+        ]
+    }
+
+    /// CSD 2.0 of a 16 GiB card, supporting CMD6
+    fn csd_v2() -> [u32; 4] {
+        csd(&[(126, 2, 1), (84, 12, 0x5B5), (48, 22, 0x7FFF)])
+    }
+
+    /// CSD 1.0 of a 1 GiB card, with 1024-byte blocks, not supporting CMD6
+    fn csd_v1() -> [u32; 4] {
+        csd(&[
+            (126, 2, 0),
+            (84, 12, 0x1B5),
+            (80, 4, 10),
+            (62, 12, 4095),
+            (47, 3, 6),
+        ])
+    }
+
+    #[test]
+    fn initialize_high_capacity_card() {
+        let mut regs = MockCard::new(true, true, csd_v2());
+        let card = initialize(&mut regs, &mut NoDelay, BusWidth::Four, 50_000_000).unwrap();
+
+        assert!(card.high_capacity);
+        assert_eq!(card.rca, 0x1234);
+        assert_eq!(card.num_blocks, 0x8000 * 1024);
+        assert_eq!(card.bus_width, BusWidth::Four);
+        assert_eq!(card.csd, csd_v2());
+
+        let cmd8 = regs.find(false, 8).unwrap();
+        assert_eq!(cmd8.argument, CMD8_ARG);
+        let acmd41 = regs.find(true, 41).unwrap();
+        assert_eq!(acmd41.argument, OCR_VOLTAGE_WINDOW | OCR_HCS);
+        assert_eq!(regs.sent().iter().filter(|s| s.index == 41).count(), 3);
+        assert_eq!(regs.find(false, 7).unwrap().argument, 0x1234 << 16);
+        assert_eq!(regs.find(true, 6).unwrap().argument, ACMD6_4BIT);
+        assert_eq!(regs.find(false, 16), None);
+
+        // The mock does not fill the switch status, so the card stays in
+        // default speed
+        let cmd6 = regs.find(false, 6).unwrap();
+        assert_eq!(cmd6.argument, CMD6_HIGH_SPEED);
+        assert_eq!(cmd6.block_count, 1);
+        assert!(!card.high_speed);
+        assert_eq!(regs.bus, (BusWidth::Four, false));
+        assert_eq!(regs.clock, DEFAULT_SPEED_FREQ);
+    }
+
+    #[test]
+    fn initialize_standard_capacity_v1_card() {
+        let mut regs = MockCard::new(false, false, csd_v1());
+        let card = initialize(&mut regs, &mut NoDelay, BusWidth::One, 50_000_000).unwrap();
+
+        assert!(!card.high_capacity);
+        assert_eq!(card.num_blocks, 2 * 1024 * 1024);
+        // CMD8 timed out, and the command line was reset
+        assert_eq!(regs.resets, SRR_SWRSTCMD);
+        assert_eq!(regs.find(true, 41).unwrap().argument, OCR_VOLTAGE_WINDOW);
+        assert_eq!(regs.find(false, 16).unwrap().argument, BLOCK_SIZE as u32);
+        assert_eq!(regs.find(true, 6), None);
+        assert_eq!(regs.find(false, 6), None);
+        assert_eq!(regs.bus, (BusWidth::One, false));
+    }
+
+    #[test]
+    fn initialize_respects_max_freq() {
+        let mut regs = MockCard::new(true, true, csd_v2());
+        initialize(&mut regs, &mut NoDelay, BusWidth::Four, 10_000_000).unwrap();
+
+        assert_eq!(regs.find(false, 6), None);
+        assert_eq!(regs.clock, 10_000_000);
+    }
+
+    #[test]
+    fn initialize_times_out() {
+        let mut regs = MockCard::new(true, true, csd_v2());
+        regs.busy_rounds = u32::MAX;
+
+        let result = initialize(&mut regs, &mut NoDelay, BusWidth::Four, 50_000_000);
+        assert_eq!(result, Err(Error::InitTimeout));
+        assert_eq!(regs.acmd41_count, ACMD41_RETRIES);
+    }
+
+    #[test]
+    fn command_errors_reset_the_command_line() {
+        let mut regs = MockCard::new(true, true, csd_v2());
+        regs.fail_command = Some(2);
+
+        let result = initialize(&mut regs, &mut NoDelay, BusWidth::Four, 50_000_000);
+        assert_eq!(result, Err(Error::CommandCrc));
+        assert_eq!(regs.resets, SRR_SWRSTCMD);
+    }
+
+    #[test]
+    fn block_addressing() {
+        let mut high_capacity = MockCard::new(true, true, csd_v2());
+        let card =
+            initialize(&mut high_capacity, &mut NoDelay, BusWidth::Four, 50_000_000).unwrap();
+        high_capacity.num_sent = 0;
+
+        let mut table = [AdmaDescriptor::default(); ADMA_TABLE_LEN];
+        let one = adma_table(&mut table, 0x2000_0000, BLOCK_SIZE);
+        read_blocks(&mut high_capacity, &card, 100, one, 1).unwrap();
+        let sent = high_capacity.sent()[0];
+        assert_eq!((sent.index, sent.argument, sent.block_count), (17, 100, 1));
+        assert_eq!(sent.mode, TMR_DMAEN | TMR_BCEN | TMR_DTDSEL_READ);
+
+        let mut standard = MockCard::new(false, false, csd_v1());
+        let card = initialize(&mut standard, &mut NoDelay, BusWidth::One, 50_000_000).unwrap();
+        standard.num_sent = 0;
+
+        let mut table = [AdmaDescriptor::default(); ADMA_TABLE_LEN];
+        let four = adma_table(&mut table, 0x2000_0000, 4 * BLOCK_SIZE);
+        write_blocks(&mut standard, &card, 100, four, 4).unwrap();
+        let sent = standard.sent()[0];
+        assert_eq!(
+            (sent.index, sent.argument, sent.block_count),
+            (25, 100 * 512, 4)
+        );
+        assert_eq!(sent.mode, TMR_DMAEN | TMR_BCEN | TMR_MSBSEL | TMR_ACMD12);
+        // The card status is polled after the write
+        assert_eq!(standard.sent()[1].index, 13);
+    }
+
+    #[test]
+    fn data_command_errors() {
+        let mut regs = MockCard::new(true, true, csd_v2());
+        let card = initialize(&mut regs, &mut NoDelay, BusWidth::Four, 50_000_000).unwrap();
+
+        let mut table = [AdmaDescriptor::default(); ADMA_TABLE_LEN];
+        let table = adma_table(&mut table, 0x2000_0000, BLOCK_SIZE);
+        regs.fail_command = Some(17);
+        assert_eq!(
+            read_blocks(&mut regs, &card, 0, table, 1),
+            Err(Error::CommandCrc)
+        );
+        assert_eq!(regs.resets, SRR_SWRSTCMD);
+    }
+
+    #[test]
+    fn adma_descriptors() {
+        let mut table = [AdmaDescriptor::default(); ADMA_TABLE_LEN];
+        let used = adma_table(&mut table, 0x2000_0000, 150 * BLOCK_SIZE);
+
+        assert_eq!(used.len(), 3);
+        let tran = ADMA_VALID | ADMA_ACT_TRAN;
+        assert_eq!(
+            used[0],
+            AdmaDescriptor {
+                attributes: tran,
+                length: 0x8000,
+                address: 0x2000_0000
+            }
+        );
+        assert_eq!(
+            used[1],
+            AdmaDescriptor {
+                attributes: tran,
+                length: 0x8000,
+                address: 0x2000_8000
+            }
+        );
+        assert_eq!(
+            used[2],
+            AdmaDescriptor {
+                attributes: tran | ADMA_END,
+                length: 22 * BLOCK_SIZE as u16,
+                address: 0x2001_0000
+            }
+        );
+    }
+}
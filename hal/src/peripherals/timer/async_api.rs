@@ -1,7 +1,9 @@
 //! Async APIs for timers.
 //!
 //! Use [`TimerCounter::into_future`] to convert a regular [`TimerCounter`] into
-//! an asynchronous [`TimerFuture`].
+//! an asynchronous [`TimerFuture`]. Similarly, on chips that can pair their TCs
+//! in 32-bit mode, use [`TimerCounter32::into_future`] to convert a
+//! [`TimerCounter32`] into a [`TimerFuture32`].
 
 use crate::{
     async_hal::interrupts::{Binding, Handler, Interrupt},
@@ -18,10 +20,16 @@ use core::{
 };
 use embassy_sync::waitqueue::AtomicWaker;
 use fugit::NanosDurationU32;
+#[hal_cfg(any("tc3-d21", "tc3-d5x"))]
+use fugit::{MicrosDurationU32, NanosDurationU64};
 use portable_atomic::AtomicBool;
 
 use crate::peripherals::timer;
 
+#[hal_cfg("tc0")]
+#[allow(unused_imports)]
+use crate::pac::Tc0;
+
 #[hal_cfg("tc1")]
 #[allow(unused_imports)]
 use crate::pac::Tc1;
@@ -42,8 +50,15 @@ use crate::pac::Tc4;
 #[allow(unused_imports)]
 use crate::pac::Tc5;
 
+#[hal_cfg("tc6")]
+#[allow(unused_imports)]
+use crate::pac::Tc6;
+
 use timer::{Count16, TimerCounter};
 
+#[hal_cfg(any("tc3-d21", "tc3-d5x"))]
+use timer::{Count32, TimerCounter32};
+
 #[hal_cfg("tc1-d11")]
 type RegBlock = pac::tc1::RegisterBlock;
 
@@ -221,6 +236,189 @@ where
     }
 }
 
+/// Trait enabling the use of a pair of Timer/Counters in async mode.
+/// Specifically, this trait enables us to register the `TC*` interrupt of the
+/// master TC as a waker for 32-bit timer futures.
+///
+/// **⚠️ Warning** This trait should not be implemented outside of this crate!
+#[hal_cfg(any("tc3-d21", "tc3-d5x"))]
+pub trait AsyncCount32: Count32 + Sealed {
+    /// Index of this TC pair in the `STATE32` tracker
+    const STATE_ID: usize;
+
+    /// Get a reference to the master timer's register block
+    fn reg_block(peripherals: &pac::Peripherals) -> &RegBlock;
+
+    /// Interrupt type for this timer
+    type Interrupt: Interrupt;
+}
+
+/// Interrupt handler for async 32-bit timer operarions
+#[hal_cfg(any("tc3-d21", "tc3-d5x"))]
+pub struct InterruptHandler32<T: AsyncCount32> {
+    _private: (),
+    _tc: PhantomData<T>,
+}
+
+#[hal_cfg(any("tc3-d21", "tc3-d5x"))]
+impl<T: AsyncCount32> crate::typelevel::Sealed for InterruptHandler32<T> {}
+
+#[hal_cfg(any("tc3-d21", "tc3-d5x"))]
+impl<A: AsyncCount32> Handler<A::Interrupt> for InterruptHandler32<A> {
+    /// Callback function when the corresponding TC interrupt is fired
+    ///
+    /// # Safety
+    ///
+    /// This method may [`steal`](crate::pac::Peripherals::steal) the `TC`
+    /// peripheral instance to check the interrupt flags. The only
+    /// modifications it is allowed to apply to the peripheral is to clear
+    /// the interrupt flag (to prevent re-firing). This method should ONLY be
+    /// able to be called while a [`TimerFuture32`] holds an unique reference
+    /// to the underlying `TC` peripheral.
+    unsafe fn on_interrupt() {
+        let periph = unsafe { crate::pac::Peripherals::steal() };
+        let tc = A::reg_block(&periph);
+        let intflag = &tc.count32().intflag();
+
+        if intflag.read().ovf().bit_is_set() {
+            // Clear the flag
+            intflag.modify(|_, w| w.ovf().set_bit());
+            STATE32[A::STATE_ID].wake();
+        }
+    }
+}
+
+#[hal_cfg(any("tc3-d21", "tc3-d5x"))]
+macro_rules! impl_async_count32 {
+    ($TC: ident, $id: expr) => {
+        paste::paste! {
+            impl AsyncCount32 for $TC {
+                const STATE_ID: usize = $id;
+
+                type Interrupt = crate::async_hal::interrupts::[< $TC:upper >];
+
+                fn reg_block(peripherals: &pac::Peripherals) -> &RegBlock {
+                    &*peripherals.[< $TC:lower >]
+                }
+            }
+        }
+    };
+}
+
+// TC0 and TC6 are only used as the master of a 32-bit pair, so they are not
+// sealed by `impl_async_count16`
+#[hal_cfg("tc0-d5x")]
+impl Sealed for Tc0 {}
+
+#[hal_cfg("tc6")]
+impl Sealed for Tc6 {}
+
+#[hal_cfg("tc4-d21")]
+impl_async_count32!(Tc4, 0);
+
+#[hal_cfg(all("tc6-d21", "tc7-d21"))]
+impl_async_count32!(Tc6, 1);
+
+#[hal_cfg("tc0-d5x")]
+impl_async_count32!(Tc0, 0);
+
+#[hal_cfg("tc2-d5x")]
+impl_async_count32!(Tc2, 1);
+
+#[hal_cfg(all("tc4-d5x", "tc5-d5x"))]
+impl_async_count32!(Tc4, 2);
+
+#[hal_cfg(all("tc6-d5x", "tc7-d5x"))]
+impl_async_count32!(Tc6, 3);
+
+// Reserve space for the max number of timer pairs based on chip type
+#[hal_cfg("tc3-d21")]
+const NUM_TIMER_PAIRS: usize = 2;
+
+#[hal_cfg("tc3-d5x")]
+const NUM_TIMER_PAIRS: usize = 4;
+
+#[hal_cfg(any("tc3-d21", "tc3-d5x"))]
+impl<T> TimerCounter32<T>
+where
+    T: AsyncCount32,
+{
+    /// Transform a [`TimerCounter32`] into an [`TimerFuture32`]
+    #[inline]
+    pub fn into_future<I>(mut self, _irq: I) -> TimerFuture32<T>
+    where
+        I: Binding<T::Interrupt, InterruptHandler32<T>>,
+    {
+        T::Interrupt::unpend();
+        unsafe { T::Interrupt::enable() };
+        self.enable_interrupt();
+
+        TimerFuture32 { timer: self }
+    }
+}
+
+/// Wrapper around a [`TimerCounter32`] with an `async` interface
+#[hal_cfg(any("tc3-d21", "tc3-d5x"))]
+pub struct TimerFuture32<T>
+where
+    T: AsyncCount32,
+{
+    timer: TimerCounter32<T>,
+}
+
+#[hal_cfg(any("tc3-d21", "tc3-d5x"))]
+impl<T> TimerFuture32<T>
+where
+    T: AsyncCount32,
+{
+    /// Delay asynchronously
+    ///
+    /// The delay is given in microseconds, so that it can be longer than the
+    /// 4.29 s of a `u32` nanosecond duration.
+    #[inline]
+    pub async fn delay(&mut self, count: MicrosDurationU32) {
+        self.delay_timeout(NanosDurationU64::micros(count.ticks() as u64))
+            .await;
+    }
+
+    async fn delay_timeout(&mut self, timeout: NanosDurationU64) {
+        self.timer.start_timeout(timeout);
+        self.timer.enable_interrupt();
+
+        poll_fn(|cx| {
+            STATE32[T::STATE_ID].register(cx.waker());
+            if STATE32[T::STATE_ID].ready() {
+                return Poll::Ready(());
+            }
+
+            Poll::Pending
+        })
+        .await;
+    }
+}
+
+#[hal_cfg(any("tc3-d21", "tc3-d5x"))]
+impl<T> Drop for TimerFuture32<T>
+where
+    T: AsyncCount32,
+{
+    #[inline]
+    fn drop(&mut self) {
+        T::Interrupt::disable();
+    }
+}
+
+#[hal_cfg(any("tc3-d21", "tc3-d5x"))]
+impl<T> embedded_hal_async::delay::DelayNs for TimerFuture32<T>
+where
+    T: AsyncCount32,
+{
+    async fn delay_ns(&mut self, ns: u32) {
+        self.delay_timeout(NanosDurationU64::from_ticks(ns as u64))
+            .await;
+    }
+}
+
 // TODO instead of tracking the state manually, we could use ONESHOT
 // mode and check the STATUS.STOP bit
 struct State {
@@ -257,3 +455,6 @@ impl State {
 #[allow(clippy::declare_interior_mutable_const)]
 const STATE_NEW: State = State::new();
 static STATE: [State; NUM_TIMERS] = [STATE_NEW; NUM_TIMERS];
+
+#[hal_cfg(any("tc3-d21", "tc3-d5x"))]
+static STATE32: [State; NUM_TIMER_PAIRS] = [STATE_NEW; NUM_TIMER_PAIRS];
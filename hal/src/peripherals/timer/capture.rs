@@ -0,0 +1,1158 @@
+//! # Input capture
+//!
+//! In capture mode, a TC or TCC copies its counter value into one of its
+//! compare/capture (`CC`) registers every time it receives an input event,
+//! instead of comparing the counter against it. This allows measuring the
+//! timing of external signals precisely, without any CPU involvement between
+//! two measurements.
+//!
+//! The [`CaptureMode`] of a [`Capture`] is tracked at the type level:
+//!
+//! * [`Timestamp`]: the counter runs freely, and its value is captured into
+//!   `CC0` on every event.
+//! * [`PeriodPulseWidth`] and [`PulseWidthPeriod`]: the counter is captured and
+//!   restarted on every rising edge of the event, which measures the signal
+//!   period, and captured again on every falling edge, which measures the
+//!   pulse width. These two modes only differ in the `CC` registers holding
+//!   each value (`CC0` holds the period in [`PeriodPulseWidth`] mode, and the
+//!   pulse width in [`PulseWidthPeriod`] mode). This only matters when
+//!   capturing with DMA.
+//!
+//! The edges are inverted with [`Capture::set_inverted`].
+//!
+//! ## Routing the input signal
+//!
+//! The input events are routed through the [`evsys`](crate::evsys), typically
+//! from an EIC pin with [`ExtInt::enable_event`](crate::eic::ExtInt). The event
+//! channel must use the asynchronous path, or the synchronous or
+//! resynchronized path without edge detection, so that the timer sees both
+//! edges of the signal. The event user to connect depends on the timer and
+//! mode:
+//!
+//! * TC: the TC event user (eg. `users::Tc3`), in every mode.
+//! * TCC in [`Timestamp`] mode: the match/capture 0 user (eg.
+//!   `users::Tcc0Mc0`).
+//! * TCC in [`PeriodPulseWidth`] or [`PulseWidthPeriod`] mode: the event 1 user
+//!   (eg. `users::Tcc0Ev1`).
+//!
+//! ## Example
+//!
+//! Measure the frequency of a tachometer signal on a SAMx5x:
+//!
+//! ```no_run
+//! use atsamd_hal::timer::capture::{Capture, PeriodPulseWidth, Prescaler};
+//!
+//! let mut capture = Capture::tc3_(&tc3_clock, peripherals.tc3, &mut mclk, PeriodPulseWidth);
+//! capture.set_prescaler(Prescaler::Div16);
+//!
+//! // Route the EIC pin to the TC3 event user through the EVSYS
+//! let channel = channels.0.asynchronous(generators::EicExtint5);
+//! let (channel, link) = channel.connect(users.tc3);
+//! extint.enable_event();
+//!
+//! loop {
+//!     let pulse = nb::block!(capture.read()).unwrap();
+//!     let frequency = capture.tick_rate().to_Hz() / pulse.period;
+//! }
+//! ```
+//!
+//! ## Errors
+//!
+//! [`Capture::read`] returns [`Error::Overflow`] if a new value was captured
+//! before the previous one was read, and [`Error::CounterOverflow`] if the
+//! counter wrapped around while measuring a period, ie. the period of the
+//! signal is longer than the range of the counter at the current
+//! [`tick_rate`](Capture::tick_rate). In both cases, the measurement is
+//! discarded, and the next one is valid.
+//!
+//! ## DMA
+//!
+//! With the `dma` feature enabled, `Capture::receive_with_dma` copies every
+//! captured timestamp, or every captured period, into a buffer. In the pulse
+//! width modes, the pulse width keeps being captured, but is not read by the
+//! DMA.
+//!
+//! ## `async` operation
+//!
+//! With the `async` feature enabled, bind the timer interrupt to the capture
+//! `InterruptHandler`, and turn the [`Capture`] into a `CaptureFuture` with
+//! `Capture::into_future`. On SAMx5x, TCCs have one interrupt per channel, so
+//! they must be bound with
+//! [`bind_multiple_interrupts`](crate::bind_multiple_interrupts).
+//!
+//! ```no_run
+//! use atsamd_hal::timer::capture;
+//!
+//! atsamd_hal::bind_interrupts!(struct Irqs {
+//!     TC3 => capture::InterruptHandler<Tc3>;
+//! });
+//!
+//! let mut capture = capture.into_future(Irqs);
+//! let pulse = capture.capture().await.unwrap();
+//! ```
+
+use core::marker::PhantomData;
+
+use atsamd_hal_macros::hal_cfg;
+use bitflags::bitflags;
+
+use crate::clock;
+use crate::time::Hertz;
+use crate::typelevel::Sealed;
+
+#[hal_cfg("tc0-d5x")]
+use crate::pac::tc0::Count16 as Count16Reg;
+#[hal_cfg("tc1-d11")]
+use crate::pac::tc1::Count16 as Count16Reg;
+#[hal_cfg("tc3-d21")]
+use crate::pac::tc3::Count16 as Count16Reg;
+
+#[hal_cfg("clock-d5x")]
+use crate::pac::Mclk as ApbClkCtrl;
+#[hal_cfg(any("clock-d11", "clock-d21"))]
+use crate::pac::Pm as ApbClkCtrl;
+
+use crate::pac::tcc0::RegisterBlock as TccReg;
+
+#[cfg(feature = "dma")]
+use crate::dmac::{
+    self, AnyChannel, Buffer, BufferPair, Busy, Ready, Transfer, TriggerAction, TriggerSource,
+};
+
+//==============================================================================
+// Capture modes
+//==============================================================================
+
+/// Type-level enum of the capture modes
+///
+/// See the [module-level documentation](self) for details.
+pub trait CaptureMode: Sealed {
+    /// Capture operation to configure on the timer
+    const KIND: CaptureKind;
+
+    /// `CC` channel captured last, whose flag signals a complete measurement
+    const LAST: usize;
+
+    /// `CC` channel read by DMA transfers
+    const DMA_CHANNEL: usize;
+
+    /// Value returned by each measurement
+    type Output;
+
+    /// Build a measurement from the values of `CC0` and `CC1`
+    fn output(cc: [u32; 2]) -> Self::Output;
+}
+
+/// Value-level version of [`CaptureMode`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CaptureKind {
+    /// See [`Timestamp`]
+    Timestamp,
+    /// See [`PeriodPulseWidth`]
+    PeriodPulseWidth,
+    /// See [`PulseWidthPeriod`]
+    PulseWidthPeriod,
+}
+
+/// Capture the free-running counter into `CC0` on every event
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Timestamp;
+
+impl Sealed for Timestamp {}
+
+impl CaptureMode for Timestamp {
+    const KIND: CaptureKind = CaptureKind::Timestamp;
+    const LAST: usize = 0;
+    const DMA_CHANNEL: usize = 0;
+    type Output = u32;
+
+    #[inline]
+    fn output(cc: [u32; 2]) -> u32 {
+        cc[0]
+    }
+}
+
+/// Capture the period into `CC0`, and the pulse width into `CC1`
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PeriodPulseWidth;
+
+impl Sealed for PeriodPulseWidth {}
+
+impl CaptureMode for PeriodPulseWidth {
+    const KIND: CaptureKind = CaptureKind::PeriodPulseWidth;
+    const LAST: usize = 1;
+    const DMA_CHANNEL: usize = 0;
+    type Output = Pulse;
+
+    #[inline]
+    fn output(cc: [u32; 2]) -> Pulse {
+        Pulse {
+            period: cc[0],
+            width: cc[1],
+        }
+    }
+}
+
+/// Capture the pulse width into `CC0`, and the period into `CC1`
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PulseWidthPeriod;
+
+impl Sealed for PulseWidthPeriod {}
+
+impl CaptureMode for PulseWidthPeriod {
+    const KIND: CaptureKind = CaptureKind::PulseWidthPeriod;
+    const LAST: usize = 0;
+    const DMA_CHANNEL: usize = 1;
+    type Output = Pulse;
+
+    #[inline]
+    fn output(cc: [u32; 2]) -> Pulse {
+        Pulse {
+            period: cc[1],
+            width: cc[0],
+        }
+    }
+}
+
+/// Period and pulse width measurement, in timer ticks
+///
+/// Convert ticks to time with [`Capture::tick_rate`]. The width is measured
+/// on the last pulse, while the period is measured on the cycle preceding it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Pulse {
+    /// Time between the last two rising edges
+    pub period: u32,
+    /// Time between the last rising edge and the last falling edge
+    pub width: u32,
+}
+
+//==============================================================================
+// Prescaler
+//==============================================================================
+
+/// Division of the timer clock
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Prescaler {
+    #[default]
+    Div1,
+    Div2,
+    Div4,
+    Div8,
+    Div16,
+    Div64,
+    Div256,
+    Div1024,
+}
+
+impl Prescaler {
+    /// Return the division factor
+    #[inline]
+    pub fn divider(self) -> u32 {
+        match self {
+            Prescaler::Div1 => 1,
+            Prescaler::Div2 => 2,
+            Prescaler::Div4 => 4,
+            Prescaler::Div8 => 8,
+            Prescaler::Div16 => 16,
+            Prescaler::Div64 => 64,
+            Prescaler::Div256 => 256,
+            Prescaler::Div1024 => 1024,
+        }
+    }
+}
+
+/// Write a [`Prescaler`] to the `CTRLA.PRESCALER` field of a TC or TCC
+macro_rules! set_prescaler {
+    ($w:expr, $prescaler:expr) => {
+        match $prescaler {
+            Prescaler::Div1 => $w.prescaler().div1(),
+            Prescaler::Div2 => $w.prescaler().div2(),
+            Prescaler::Div4 => $w.prescaler().div4(),
+            Prescaler::Div8 => $w.prescaler().div8(),
+            Prescaler::Div16 => $w.prescaler().div16(),
+            Prescaler::Div64 => $w.prescaler().div64(),
+            Prescaler::Div256 => $w.prescaler().div256(),
+            Prescaler::Div1024 => $w.prescaler().div1024(),
+        }
+    };
+}
+
+//==============================================================================
+// Flags and errors
+//==============================================================================
+
+bitflags! {
+    /// Interrupt flags of a [`Capture`]
+    ///
+    /// The bits match the `INTFLAG` register of the TCs. They are translated
+    /// for TCCs.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct Flags: u8 {
+        /// Counter overflow
+        const OVF = 0x01;
+        /// Capture overflow
+        const ERR = 0x02;
+        /// Capture on channel 0
+        const MC0 = 0x10;
+        /// Capture on channel 1
+        const MC1 = 0x20;
+    }
+}
+
+impl Flags {
+    /// Capture flag of the channel `ch`
+    #[inline]
+    fn mc(ch: usize) -> Self {
+        Self::from_bits_retain(Self::MC0.bits() << ch)
+    }
+}
+
+/// Errors returned by [`Capture::read`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// A new value was captured before the previous one was read
+    Overflow,
+    /// The counter wrapped around while measuring a period
+    CounterOverflow,
+}
+
+//==============================================================================
+// CaptureTimer
+//==============================================================================
+
+/// Timers that support input capture
+///
+/// This trait is implemented for all TC and TCC instances. Its functions
+/// access the registers of the timer directly, and are only meant to be used
+/// by [`Capture`].
+///
+/// **⚠️ Warning** This trait should not be implemented outside of this crate!
+pub trait CaptureTimer {
+    /// Type of the `CC` registers
+    type Word: Copy + Into<u32>;
+
+    /// Index of this timer in the waker array
+    #[cfg(feature = "async")]
+    const WAKER_ID: usize;
+
+    /// Interrupt source of this timer
+    #[cfg(feature = "async")]
+    type Interrupt: crate::async_hal::interrupts::InterruptSource;
+
+    /// Reset the timer, configure it for the capture operation `kind` and
+    /// enable it
+    fn configure(kind: CaptureKind, prescaler: Prescaler, invert: bool);
+
+    /// Change the prescaler and the event inversion, briefly disabling the
+    /// timer
+    fn reconfigure(prescaler: Prescaler, invert: bool);
+
+    /// Reset the timer
+    fn reset();
+
+    /// Read the `CC` register of channel `ch`
+    fn read_cc(ch: usize) -> Self::Word;
+
+    /// Read the interrupt flags
+    fn read_flags() -> Flags;
+
+    /// Clear interrupt flags
+    fn clear_flags(flags: Flags);
+
+    /// Read the enabled interrupts
+    fn enabled_interrupts() -> Flags;
+
+    /// Enable interrupts
+    fn enable_interrupts(flags: Flags);
+
+    /// Disable interrupts
+    fn disable_interrupts(flags: Flags);
+
+    /// DMA trigger of channel `ch`
+    #[cfg(feature = "dma")]
+    fn dma_trigger(ch: usize) -> TriggerSource;
+
+    /// Pointer to the `CC` register of channel `ch`
+    #[cfg(feature = "dma")]
+    fn cc_ptr(ch: usize) -> *mut Self::Word;
+}
+
+//==============================================================================
+// TC implementation
+//==============================================================================
+
+#[hal_cfg(any("tc1-d11", "tc3-d21"))]
+#[inline]
+fn tc_sync(count: &Count16Reg) {
+    while count.status().read().syncbusy().bit_is_set() {}
+}
+
+#[hal_cfg(any("tc1-d11", "tc3-d21"))]
+#[inline]
+fn tc_set_enabled(count: &Count16Reg, enabled: bool) {
+    count.ctrla().modify(|_, w| w.enable().bit(enabled));
+    tc_sync(count);
+}
+
+#[hal_cfg("tc0-d5x")]
+#[inline]
+fn tc_set_enabled(count: &Count16Reg, enabled: bool) {
+    count.ctrla().modify(|_, w| w.enable().bit(enabled));
+    while count.syncbusy().read().enable().bit_is_set() {}
+}
+
+#[hal_cfg(any("tc1-d11", "tc3-d21"))]
+#[inline]
+fn tc_reset(count: &Count16Reg) {
+    tc_set_enabled(count, false);
+    count.ctrla().write(|w| w.swrst().set_bit());
+    tc_sync(count);
+    // the SVD erroneously marks swrst as write-only, so we
+    // need to manually read the bit here
+    while count.ctrla().read().bits() & 1 != 0 {}
+}
+
+#[hal_cfg("tc0-d5x")]
+#[inline]
+fn tc_reset(count: &Count16Reg) {
+    tc_set_enabled(count, false);
+    count.ctrla().write(|w| w.swrst().set_bit());
+    while count.syncbusy().read().swrst().bit_is_set() {}
+}
+
+#[hal_cfg(any("tc1-d11", "tc3-d21"))]
+#[inline]
+fn tc_configure(count: &Count16Reg, kind: CaptureKind, prescaler: Prescaler, invert: bool) {
+    tc_reset(count);
+    tc_set_events(count, kind, invert);
+    count.ctrlc().write(|w| {
+        w.cpten0().set_bit();
+        w.cpten1().bit(kind != CaptureKind::Timestamp)
+    });
+    tc_sync(count);
+    count.ctrla().write(|w| {
+        w.mode().count16();
+        set_prescaler!(w, prescaler)
+    });
+    tc_set_enabled(count, true);
+}
+
+#[hal_cfg("tc0-d5x")]
+#[inline]
+fn tc_configure(count: &Count16Reg, kind: CaptureKind, prescaler: Prescaler, invert: bool) {
+    tc_reset(count);
+    tc_set_events(count, kind, invert);
+    count.ctrla().write(|w| {
+        w.mode().count16();
+        set_prescaler!(w, prescaler);
+        w.capten0().set_bit();
+        w.capten1().bit(kind != CaptureKind::Timestamp)
+    });
+    tc_set_enabled(count, true);
+}
+
+#[inline]
+fn tc_set_events(count: &Count16Reg, kind: CaptureKind, invert: bool) {
+    count.evctrl().write(|w| {
+        match kind {
+            CaptureKind::Timestamp => w.evact().off(),
+            CaptureKind::PeriodPulseWidth => w.evact().ppw(),
+            CaptureKind::PulseWidthPeriod => w.evact().pwp(),
+        };
+        w.tcinv().bit(invert);
+        w.tcei().set_bit()
+    });
+}
+
+#[inline]
+fn tc_reconfigure(count: &Count16Reg, prescaler: Prescaler, invert: bool) {
+    tc_set_enabled(count, false);
+    count.evctrl().modify(|_, w| w.tcinv().bit(invert));
+    count.ctrla().modify(|_, w| set_prescaler!(w, prescaler));
+    tc_set_enabled(count, true);
+}
+
+macro_rules! impl_capture_tc {
+    ($($Tc:ident: ($id:expr, $clock:ident, $apbmask:ident),)+) => {
+        $(
+            paste::paste! {
+                impl CaptureTimer for crate::pac::$Tc {
+                    type Word = u16;
+
+                    #[cfg(feature = "async")]
+                    const WAKER_ID: usize = $id;
+
+                    #[cfg(feature = "async")]
+                    type Interrupt = crate::async_hal::interrupts::[<$Tc:upper>];
+
+                    #[inline]
+                    fn configure(kind: CaptureKind, prescaler: Prescaler, invert: bool) {
+                        tc_configure(tc_regs!($Tc), kind, prescaler, invert);
+                    }
+
+                    #[inline]
+                    fn reconfigure(prescaler: Prescaler, invert: bool) {
+                        tc_reconfigure(tc_regs!($Tc), prescaler, invert);
+                    }
+
+                    #[inline]
+                    fn reset() {
+                        tc_reset(tc_regs!($Tc));
+                    }
+
+                    #[inline]
+                    fn read_cc(ch: usize) -> u16 {
+                        tc_regs!($Tc).cc(ch).read().cc().bits()
+                    }
+
+                    #[inline]
+                    fn read_flags() -> Flags {
+                        Flags::from_bits_truncate(tc_regs!($Tc).intflag().read().bits())
+                    }
+
+                    #[inline]
+                    fn clear_flags(flags: Flags) {
+                        tc_regs!($Tc)
+                            .intflag()
+                            .write(|w| unsafe { w.bits(flags.bits()) });
+                    }
+
+                    #[inline]
+                    fn enabled_interrupts() -> Flags {
+                        Flags::from_bits_truncate(tc_regs!($Tc).intenset().read().bits())
+                    }
+
+                    #[inline]
+                    fn enable_interrupts(flags: Flags) {
+                        tc_regs!($Tc)
+                            .intenset()
+                            .write(|w| unsafe { w.bits(flags.bits()) });
+                    }
+
+                    #[inline]
+                    fn disable_interrupts(flags: Flags) {
+                        tc_regs!($Tc)
+                            .intenclr()
+                            .write(|w| unsafe { w.bits(flags.bits()) });
+                    }
+
+                    #[cfg(feature = "dma")]
+                    #[inline]
+                    fn dma_trigger(ch: usize) -> TriggerSource {
+                        match ch {
+                            0 => TriggerSource::[<$Tc Mc0>],
+                            _ => TriggerSource::[<$Tc Mc1>],
+                        }
+                    }
+
+                    #[cfg(feature = "dma")]
+                    #[inline]
+                    fn cc_ptr(ch: usize) -> *mut u16 {
+                        tc_regs!($Tc).cc(ch).as_ptr()
+                    }
+                }
+
+                impl<M: CaptureMode> Capture<crate::pac::$Tc, M> {
+                    #[doc = "Configure " $Tc:upper " in capture `mode`."]
+                    ///
+                    /// The timer counts at the frequency of `clock`, divided by
+                    /// the [`Prescaler`], which is initially
+                    /// [`Prescaler::Div1`].
+                    #[inline]
+                    pub fn [<$Tc:lower _>](
+                        clock: &clock::$clock,
+                        tc: crate::pac::$Tc,
+                        apb_clk_ctrl: &mut ApbClkCtrl,
+                        mode: M,
+                    ) -> Self {
+                        apb_clk_ctrl
+                            .$apbmask()
+                            .modify(|_, w| w.[<$Tc:lower _>]().set_bit());
+                        Self::new(clock.freq(), tc, mode)
+                    }
+                }
+            }
+        )+
+    };
+}
+
+/// Access the `COUNT16` registers of a TC
+macro_rules! tc_regs {
+    ($Tc:ident) => {
+        // Safety: The TC instance is owned by the `Capture`
+        unsafe { (*crate::pac::$Tc::PTR).count16() }
+    };
+}
+
+#[hal_cfg("tc1-d11")]
+impl_capture_tc! {
+    Tc1: (1, Tc1Tc2Clock, apbcmask),
+    Tc2: (2, Tc1Tc2Clock, apbcmask),
+}
+
+#[hal_cfg("tc3-d21")]
+impl_capture_tc! {
+    Tc3: (3, Tcc2Tc3Clock, apbcmask),
+    Tc4: (4, Tc4Tc5Clock, apbcmask),
+    Tc5: (5, Tc4Tc5Clock, apbcmask),
+}
+
+#[hal_cfg(all("tc6-d21", "tc7-d21"))]
+impl_capture_tc! {
+    Tc6: (6, Tc6Tc7Clock, apbcmask),
+    Tc7: (7, Tc6Tc7Clock, apbcmask),
+}
+
+#[hal_cfg("tc0-d5x")]
+impl_capture_tc! {
+    Tc0: (0, Tc0Tc1Clock, apbamask),
+    Tc1: (1, Tc0Tc1Clock, apbamask),
+    Tc2: (2, Tc2Tc3Clock, apbbmask),
+    Tc3: (3, Tc2Tc3Clock, apbbmask),
+}
+
+#[hal_cfg(all("tc4-d5x", "tc5-d5x"))]
+impl_capture_tc! {
+    Tc4: (4, Tc4Tc5Clock, apbcmask),
+    Tc5: (5, Tc4Tc5Clock, apbcmask),
+}
+
+#[hal_cfg(all("tc6-d5x", "tc7-d5x"))]
+impl_capture_tc! {
+    Tc6: (6, Tc6Tc7Clock, apbdmask),
+    Tc7: (7, Tc6Tc7Clock, apbdmask),
+}
+
+//==============================================================================
+// TCC implementation
+//==============================================================================
+
+/// `INTFLAG` bits of the TCC matching [`Flags`]
+const TCC_OVF: u32 = 1 << 0;
+const TCC_ERR: u32 = 1 << 3;
+const TCC_MC0: u32 = 1 << 16;
+const TCC_MC1: u32 = 1 << 17;
+
+/// Translate [`Flags`] to the TCC `INTFLAG` layout
+#[inline]
+fn tcc_bits(flags: Flags) -> u32 {
+    let mut bits = 0;
+    for (flag, bit) in [
+        (Flags::OVF, TCC_OVF),
+        (Flags::ERR, TCC_ERR),
+        (Flags::MC0, TCC_MC0),
+        (Flags::MC1, TCC_MC1),
+    ] {
+        if flags.contains(flag) {
+            bits |= bit;
+        }
+    }
+    bits
+}
+
+/// Translate the TCC `INTFLAG` layout to [`Flags`]
+#[inline]
+fn tcc_flags(bits: u32) -> Flags {
+    let mut flags = Flags::empty();
+    for (flag, bit) in [
+        (Flags::OVF, TCC_OVF),
+        (Flags::ERR, TCC_ERR),
+        (Flags::MC0, TCC_MC0),
+        (Flags::MC1, TCC_MC1),
+    ] {
+        if bits & bit != 0 {
+            flags |= flag;
+        }
+    }
+    flags
+}
+
+#[inline]
+fn tcc_set_enabled(tcc: &TccReg, enabled: bool) {
+    tcc.ctrla().modify(|_, w| w.enable().bit(enabled));
+    while tcc.syncbusy().read().enable().bit_is_set() {}
+}
+
+#[inline]
+fn tcc_reset(tcc: &TccReg) {
+    tcc_set_enabled(tcc, false);
+    tcc.ctrla().write(|w| w.swrst().set_bit());
+    while tcc.syncbusy().read().swrst().bit_is_set() {}
+}
+
+#[inline]
+fn tcc_configure(tcc: &TccReg, kind: CaptureKind, prescaler: Prescaler, invert: bool) {
+    tcc_reset(tcc);
+    tcc.evctrl().write(|w| {
+        match kind {
+            CaptureKind::Timestamp => w.mcei0().set_bit(),
+            CaptureKind::PeriodPulseWidth => {
+                w.evact1().ppw();
+                w.tcei1().set_bit()
+            }
+            CaptureKind::PulseWidthPeriod => {
+                w.evact1().pwp();
+                w.tcei1().set_bit()
+            }
+        };
+        w.tcinv1().bit(invert)
+    });
+    tcc.ctrla().write(|w| {
+        set_prescaler!(w, prescaler);
+        w.cpten0().set_bit();
+        w.cpten1().bit(kind != CaptureKind::Timestamp)
+    });
+    tcc_set_enabled(tcc, true);
+}
+
+#[inline]
+fn tcc_reconfigure(tcc: &TccReg, prescaler: Prescaler, invert: bool) {
+    tcc_set_enabled(tcc, false);
+    tcc.evctrl().modify(|_, w| w.tcinv1().bit(invert));
+    tcc.ctrla().modify(|_, w| set_prescaler!(w, prescaler));
+    tcc_set_enabled(tcc, true);
+}
+
+macro_rules! impl_capture_tcc {
+    ($($Tcc:ident: ($id:expr, $clock:ident, $apbmask:ident),)+) => {
+        $(
+            paste::paste! {
+                impl CaptureTimer for crate::pac::$Tcc {
+                    type Word = u32;
+
+                    #[cfg(feature = "async")]
+                    const WAKER_ID: usize = $id;
+
+                    #[cfg(feature = "async")]
+                    type Interrupt = crate::async_hal::interrupts::[<$Tcc:upper>];
+
+                    #[inline]
+                    fn configure(kind: CaptureKind, prescaler: Prescaler, invert: bool) {
+                        tcc_configure(tcc_regs!($Tcc), kind, prescaler, invert);
+                    }
+
+                    #[inline]
+                    fn reconfigure(prescaler: Prescaler, invert: bool) {
+                        tcc_reconfigure(tcc_regs!($Tcc), prescaler, invert);
+                    }
+
+                    #[inline]
+                    fn reset() {
+                        tcc_reset(tcc_regs!($Tcc));
+                    }
+
+                    #[inline]
+                    fn read_cc(ch: usize) -> u32 {
+                        tcc_regs!($Tcc).cc(ch).read().cc().bits()
+                    }
+
+                    #[inline]
+                    fn read_flags() -> Flags {
+                        tcc_flags(tcc_regs!($Tcc).intflag().read().bits())
+                    }
+
+                    #[inline]
+                    fn clear_flags(flags: Flags) {
+                        tcc_regs!($Tcc)
+                            .intflag()
+                            .write(|w| unsafe { w.bits(tcc_bits(flags)) });
+                    }
+
+                    #[inline]
+                    fn enabled_interrupts() -> Flags {
+                        tcc_flags(tcc_regs!($Tcc).intenset().read().bits())
+                    }
+
+                    #[inline]
+                    fn enable_interrupts(flags: Flags) {
+                        tcc_regs!($Tcc)
+                            .intenset()
+                            .write(|w| unsafe { w.bits(tcc_bits(flags)) });
+                    }
+
+                    #[inline]
+                    fn disable_interrupts(flags: Flags) {
+                        tcc_regs!($Tcc)
+                            .intenclr()
+                            .write(|w| unsafe { w.bits(tcc_bits(flags)) });
+                    }
+
+                    #[cfg(feature = "dma")]
+                    #[inline]
+                    fn dma_trigger(ch: usize) -> TriggerSource {
+                        match ch {
+                            0 => TriggerSource::[<$Tcc Mc0>],
+                            _ => TriggerSource::[<$Tcc Mc1>],
+                        }
+                    }
+
+                    #[cfg(feature = "dma")]
+                    #[inline]
+                    fn cc_ptr(ch: usize) -> *mut u32 {
+                        tcc_regs!($Tcc).cc(ch).as_ptr()
+                    }
+                }
+
+                impl<M: CaptureMode> Capture<crate::pac::$Tcc, M> {
+                    #[doc = "Configure " $Tcc:upper " in capture `mode`."]
+                    ///
+                    /// The timer counts at the frequency of `clock`, divided by
+                    /// the [`Prescaler`], which is initially
+                    /// [`Prescaler::Div1`].
+                    #[inline]
+                    pub fn [<$Tcc:lower _>](
+                        clock: &clock::$clock,
+                        tcc: crate::pac::$Tcc,
+                        apb_clk_ctrl: &mut ApbClkCtrl,
+                        mode: M,
+                    ) -> Self {
+                        apb_clk_ctrl
+                            .$apbmask()
+                            .modify(|_, w| w.[<$Tcc:lower _>]().set_bit());
+                        Self::new(clock.freq(), tcc, mode)
+                    }
+                }
+            }
+        )+
+    };
+}
+
+/// Access the registers of a TCC
+macro_rules! tcc_regs {
+    ($Tcc:ident) => {
+        // Safety: The TCC instance is owned by the `Capture`
+        unsafe { &*crate::pac::$Tcc::PTR }
+    };
+}
+
+#[hal_cfg("tcc0-d11")]
+impl_capture_tcc! {
+    Tcc0: (8, Tcc0Clock, apbcmask),
+}
+
+#[hal_cfg("tcc0-d21")]
+impl_capture_tcc! {
+    Tcc0: (8, Tcc0Tcc1Clock, apbcmask),
+    Tcc1: (9, Tcc0Tcc1Clock, apbcmask),
+    Tcc2: (10, Tcc2Tc3Clock, apbcmask),
+}
+
+#[hal_cfg("tcc0-d5x")]
+impl_capture_tcc! {
+    Tcc0: (8, Tcc0Tcc1Clock, apbbmask),
+    Tcc1: (9, Tcc0Tcc1Clock, apbbmask),
+    Tcc2: (10, Tcc2Tcc3Clock, apbcmask),
+}
+
+#[hal_cfg("tcc3-d5x")]
+impl_capture_tcc! {
+    Tcc3: (11, Tcc2Tcc3Clock, apbcmask),
+}
+
+#[hal_cfg("tcc4-d5x")]
+impl_capture_tcc! {
+    Tcc4: (12, Tcc4Clock, apbdmask),
+}
+
+//==============================================================================
+// Capture
+//==============================================================================
+
+/// A TC or TCC configured in input capture mode
+///
+/// See the [module-level documentation](self) for details.
+pub struct Capture<T: CaptureTimer, M: CaptureMode> {
+    timer: T,
+    freq: Hertz,
+    prescaler: Prescaler,
+    inverted: bool,
+    _mode: PhantomData<M>,
+}
+
+impl<T: CaptureTimer, M: CaptureMode> Capture<T, M> {
+    #[inline]
+    fn new(freq: Hertz, timer: T, _mode: M) -> Self {
+        T::configure(M::KIND, Prescaler::Div1, false);
+        Self {
+            timer,
+            freq,
+            prescaler: Prescaler::Div1,
+            inverted: false,
+            _mode: PhantomData,
+        }
+    }
+
+    /// Return the counting frequency of the timer
+    ///
+    /// Every measurement is expressed in periods of this frequency.
+    #[inline]
+    pub fn tick_rate(&self) -> Hertz {
+        self.freq / self.prescaler.divider()
+    }
+
+    /// Return the current [`Prescaler`]
+    #[inline]
+    pub fn prescaler(&self) -> Prescaler {
+        self.prescaler
+    }
+
+    /// Set the [`Prescaler`]
+    ///
+    /// A larger division factor extends the range of the measurements, at
+    /// the expense of their resolution. The timer is briefly disabled, so an
+    /// ongoing measurement may be lost.
+    #[inline]
+    pub fn set_prescaler(&mut self, prescaler: Prescaler) {
+        self.prescaler = prescaler;
+        T::reconfigure(self.prescaler, self.inverted);
+    }
+
+    /// Invert the input event, swapping the rising and falling edges
+    ///
+    /// The timer is briefly disabled, so an ongoing measurement may be lost.
+    #[inline]
+    pub fn set_inverted(&mut self, inverted: bool) {
+        self.inverted = inverted;
+        T::reconfigure(self.prescaler, self.inverted);
+    }
+
+    /// Read the next measurement
+    ///
+    /// Returns [`nb::Error::WouldBlock`] until a complete measurement has been
+    /// captured.
+    #[inline]
+    pub fn read(&mut self) -> nb::Result<M::Output, Error> {
+        let flags = T::read_flags();
+        if flags.contains(Flags::ERR) {
+            T::clear_flags(Flags::all());
+            return Err(nb::Error::Other(Error::Overflow));
+        }
+        if !flags.contains(Flags::mc(M::LAST)) {
+            return Err(nb::Error::WouldBlock);
+        }
+        if M::KIND != CaptureKind::Timestamp && flags.contains(Flags::OVF) {
+            T::clear_flags(Flags::all());
+            return Err(nb::Error::Other(Error::CounterOverflow));
+        }
+        // Reading the `CC` registers clears their capture flags
+        let cc = [T::read_cc(0).into(), T::read_cc(1).into()];
+        Ok(M::output(cc))
+    }
+
+    /// Read the interrupt flags
+    #[inline]
+    pub fn read_flags(&self) -> Flags {
+        T::read_flags()
+    }
+
+    /// Clear interrupt flags
+    #[inline]
+    pub fn clear_flags(&mut self, flags: Flags) {
+        T::clear_flags(flags);
+    }
+
+    /// Enable interrupts for the specified flags
+    #[inline]
+    pub fn enable_interrupts(&mut self, flags: Flags) {
+        T::enable_interrupts(flags);
+    }
+
+    /// Disable interrupts for the specified flags
+    #[inline]
+    pub fn disable_interrupts(&mut self, flags: Flags) {
+        T::disable_interrupts(flags);
+    }
+
+    /// Reset the timer and return it
+    #[inline]
+    pub fn free(self) -> T {
+        T::reset();
+        self.timer
+    }
+}
+
+//==============================================================================
+// DMA
+//==============================================================================
+
+#[cfg(feature = "dma")]
+impl<T, M> Capture<T, M>
+where
+    T: CaptureTimer,
+    T::Word: dmac::Beat,
+    M: CaptureMode,
+{
+    /// Transform the [`Capture`] into a DMA [`Transfer`] and start receiving
+    /// measurements into the provided buffer.
+    ///
+    /// In [`Timestamp`] mode, the buffer receives every timestamp. In the
+    /// pulse width modes, it receives every period. If `circular` is `true`,
+    /// the buffer is filled repeatedly until the transfer is stopped.
+    #[inline]
+    #[atsamd_hal_macros::hal_macro_helper]
+    pub fn receive_with_dma<Ch, B>(
+        self,
+        buf: B,
+        mut channel: Ch,
+        circular: bool,
+    ) -> Transfer<dmac::Channel<Ch::Id, Busy>, BufferPair<Self, B>>
+    where
+        Ch: AnyChannel<Status = Ready>,
+        B: Buffer<Beat = T::Word> + 'static,
+    {
+        channel
+            .as_mut()
+            .enable_interrupts(dmac::InterruptFlags::new().with_tcmpl(true));
+
+        #[hal_cfg("dmac-d5x")]
+        let trigger_action = TriggerAction::Burst;
+
+        #[hal_cfg(any("dmac-d11", "dmac-d21"))]
+        let trigger_action = TriggerAction::Beat;
+
+        // SAFETY: This is safe because of the `'static` bound check for `B`,
+        // and the fact that the buffer length of a `Capture` is always 1.
+        let xfer = unsafe { Transfer::new_unchecked(channel, self, buf, circular) };
+        xfer.begin(T::dma_trigger(M::DMA_CHANNEL), trigger_action)
+    }
+}
+
+#[cfg(feature = "dma")]
+unsafe impl<T, M> Buffer for Capture<T, M>
+where
+    T: CaptureTimer,
+    T::Word: dmac::Beat,
+    M: CaptureMode,
+{
+    type Beat = T::Word;
+
+    #[inline]
+    fn dma_ptr(&mut self) -> *mut Self::Beat {
+        T::cc_ptr(M::DMA_CHANNEL)
+    }
+
+    #[inline]
+    fn incrementing(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn buffer_len(&self) -> usize {
+        1
+    }
+}
+
+//==============================================================================
+// async
+//==============================================================================
+
+#[cfg(feature = "async")]
+mod async_api {
+    use core::future::poll_fn;
+    use core::task::Poll;
+
+    use embassy_sync::waitqueue::AtomicWaker;
+
+    use super::*;
+    use crate::async_hal::interrupts::{Binding, Handler, InterruptSource};
+
+    /// Number of TC and TCC instances on the largest chips
+    const NUM_WAKERS: usize = 13;
+
+    #[allow(clippy::declare_interior_mutable_const)]
+    const NEW_WAKER: AtomicWaker = AtomicWaker::new();
+    static WAKERS: [AtomicWaker; NUM_WAKERS] = [NEW_WAKER; NUM_WAKERS];
+
+    /// Interrupt handler for async capture operations
+    pub struct InterruptHandler<T: CaptureTimer> {
+        _private: (),
+        _timer: PhantomData<T>,
+    }
+
+    impl<T: CaptureTimer> Sealed for InterruptHandler<T> {}
+
+    impl<T: CaptureTimer> Handler<T::Interrupt> for InterruptHandler<T> {
+        unsafe fn on_interrupt() {
+            let pending = T::read_flags() & T::enabled_interrupts();
+            // Disable the interrupts, but don't clear the flags. The future
+            // reads the measurement when woken.
+            if !pending.is_empty() {
+                T::disable_interrupts(pending);
+                WAKERS[T::WAKER_ID].wake();
+            }
+        }
+    }
+
+    impl<T: CaptureTimer, M: CaptureMode> Capture<T, M> {
+        /// Turn a [`Capture`] into a [`CaptureFuture`]
+        #[inline]
+        pub fn into_future<I>(self, _irq: I) -> CaptureFuture<T, M>
+        where
+            I: Binding<T::Interrupt, InterruptHandler<T>>,
+        {
+            T::Interrupt::unpend();
+            unsafe { T::Interrupt::enable() };
+
+            CaptureFuture { capture: self }
+        }
+    }
+
+    /// `async` version of [`Capture`]
+    ///
+    /// Create this struct by calling [`Capture::into_future`].
+    pub struct CaptureFuture<T: CaptureTimer, M: CaptureMode> {
+        capture: Capture<T, M>,
+    }
+
+    impl<T: CaptureTimer, M: CaptureMode> CaptureFuture<T, M> {
+        /// Wait for the next measurement
+        ///
+        /// See [`Capture::read`] for more details.
+        pub async fn capture(&mut self) -> Result<M::Output, Error> {
+            poll_fn(|cx| {
+                match self.capture.read() {
+                    Ok(output) => return Poll::Ready(Ok(output)),
+                    Err(nb::Error::Other(error)) => return Poll::Ready(Err(error)),
+                    Err(nb::Error::WouldBlock) => (),
+                }
+
+                WAKERS[T::WAKER_ID].register(cx.waker());
+                T::enable_interrupts(Flags::mc(M::LAST) | Flags::ERR);
+
+                match self.capture.read() {
+                    Ok(output) => Poll::Ready(Ok(output)),
+                    Err(nb::Error::Other(error)) => Poll::Ready(Err(error)),
+                    Err(nb::Error::WouldBlock) => Poll::Pending,
+                }
+            })
+            .await
+        }
+
+        /// Disable the timer interrupt and return the underlying [`Capture`]
+        #[inline]
+        pub fn free(self) -> Capture<T, M> {
+            T::disable_interrupts(Flags::all());
+            T::Interrupt::disable();
+            self.capture
+        }
+    }
+
+    impl<T: CaptureTimer, M: CaptureMode> AsRef<Capture<T, M>> for CaptureFuture<T, M> {
+        #[inline]
+        fn as_ref(&self) -> &Capture<T, M> {
+            &self.capture
+        }
+    }
+
+    impl<T: CaptureTimer, M: CaptureMode> AsMut<Capture<T, M>> for CaptureFuture<T, M> {
+        #[inline]
+        fn as_mut(&mut self) -> &mut Capture<T, M> {
+            &mut self.capture
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+pub use async_api::*;
@@ -2,11 +2,12 @@
 use core::convert::Infallible;
 
 use atsamd_hal_macros::hal_cfg;
-use fugit::NanosDurationU32;
+use fugit::{NanosDurationU32, NanosDurationU64};
 
+use crate::ehal::delay::DelayNs;
 use crate::ehal_02::timer::{CountDown, Periodic};
-use crate::pac::tc0::Count16 as Count16Reg;
-use crate::pac::{Mclk, Tc2, Tc3};
+use crate::pac::tc0::{Count16 as Count16Reg, Count32 as Count32Reg};
+use crate::pac::{Mclk, Tc0, Tc1, Tc2, Tc3};
 #[hal_cfg(all("tc4", "tc5"))]
 use crate::pac::{Tc4, Tc5};
 #[hal_cfg(all("tc6", "tc7"))]
@@ -15,7 +16,10 @@ use crate::timer_params::TimerParams;
 use crate::timer_traits::InterruptDrivenTimer;
 
 use crate::clock;
-use crate::time::{Hertz, Nanoseconds};
+use crate::clock::v2::dynamic::Reclock;
+use crate::time::{Hertz, Microseconds, Nanoseconds};
+
+pub mod capture;
 
 #[cfg(feature = "async")]
 mod async_api;
@@ -23,17 +27,14 @@ mod async_api;
 #[cfg(feature = "async")]
 pub use async_api::*;
 
-// Note:
-// TC3 + TC4 can be paired to make a 32-bit counter
-// TC5 + TC6 can be paired to make a 32-bit counter
-
 /// A generic hardware timer counter.
 ///
-/// The counters are exposed in 16-bit mode only.
-/// The hardware allows configuring the 8-bit mode
-/// and pairing up some instances to run in 32-bit
-/// mode, but that functionality is not currently
-/// exposed by this hal implementation.
+/// The counters are exposed in 16-bit mode.
+/// The hardware allows configuring the 8-bit mode,
+/// but that functionality is not currently
+/// exposed by this hal implementation. Pairs of
+/// instances can be run in 32-bit mode with
+/// [`TimerCounter32`].
 /// TimerCounter implements both the `Periodic` and
 /// the `CountDown` embedded_hal timer traits.
 /// Before a hardware timer can be used, it must first
@@ -41,6 +42,7 @@ pub use async_api::*;
 pub struct TimerCounter<TC> {
     freq: Hertz,
     tc: TC,
+    timeout: Option<NanosDurationU32>,
 }
 
 /// This is a helper trait to make it easier to make most of the
@@ -89,7 +91,9 @@ where
     where
         T: Into<NanosDurationU32>,
     {
-        let params = TimerParams::new_ns(timeout.into(), self.freq);
+        let timeout = timeout.into();
+        self.timeout = Some(timeout);
+        let params = TimerParams::new_ns(timeout, self.freq);
         let divider = params.divider;
         let cycles = params.cycles;
         let count = self.tc.count_16();
@@ -153,6 +157,27 @@ where
     }
 }
 
+/// Recompute the prescaler and period of a running timer, so that it keeps
+/// its current timeout at the new clock frequency
+impl<TC> Reclock for TimerCounter<TC>
+where
+    TC: Count16,
+{
+    fn reclock(&mut self, freq: Hertz) {
+        self.freq = freq;
+        let count = self.tc.count_16();
+        let enabled = count.ctrla().read().enable().bit_is_set();
+        // Restarting the timer resets it, so preserve the enabled interrupts
+        let ovf = count.intenset().read().ovf().bit_is_set();
+        if let (true, Some(timeout)) = (enabled, self.timeout) {
+            <Self as InterruptDrivenTimer>::start(self, timeout);
+            if ovf {
+                self.enable_interrupt();
+            }
+        }
+    }
+}
+
 macro_rules! tc {
     ($($TYPE:ident: ($TC:ident, $mclk:ident, $clock:ident, $apmask:ident),)+) => {
         $(
@@ -185,6 +210,7 @@ impl TimerCounter<$TC>
         Self {
             freq: clock.freq(),
             tc,
+            timeout: None,
         }
     }
 }
@@ -208,3 +234,253 @@ tc! {
     TimerCounter6: (Tc6, tc6_, Tc6Tc7Clock, apbdmask),
     TimerCounter7: (Tc7, tc7_, Tc6Tc7Clock, apbdmask),
 }
+
+/// A 32-bit hardware timer counter, built from a pair of TC instances.
+///
+/// In 32-bit mode, an even-numbered TC (the master) is chained with the next
+/// odd-numbered TC (the slave): TC0 + TC1, TC2 + TC3, TC4 + TC5 and TC6 + TC7.
+/// Only the registers of the master are used, but the slave is owned by the
+/// [`TimerCounter32`] so that it can't be used for anything else while the
+/// pair is running.
+///
+/// The `CountDown` timeout is given in microseconds, so that it can reach
+/// about 71 minutes instead of the 4.29 s of a `u32` nanosecond duration.
+/// The prescaler is only used when the timeout doesn't fit in the 32-bit
+/// period, so the full resolution of the clock is kept whenever possible.
+/// TimerCounter32 implements the `Periodic` and `CountDown` embedded_hal 0.2
+/// timer traits, as well as the embedded_hal `DelayNs` trait.
+/// Before a hardware timer can be used, it must first
+/// have a clock configured.
+pub struct TimerCounter32<TC: Count32> {
+    freq: Hertz,
+    tc: TC,
+    slave: TC::Slave,
+    timeout: Option<NanosDurationU64>,
+}
+
+/// This is a helper trait to make it easier to make most of the
+/// TimerCounter32 impl generic.  It is implemented for the master
+/// TC of each pair, and doesn't make sense outside of this module.
+pub trait Count32 {
+    /// The odd-numbered TC paired with this one
+    type Slave;
+
+    fn count_32(&self) -> &Count32Reg;
+}
+
+impl<TC: Count32> TimerCounter32<TC> {
+    /// Disable the timer and return the paired TC instances
+    pub fn free(self) -> (TC, TC::Slave) {
+        let count = self.tc.count_32();
+        count.ctrla().modify(|_, w| w.enable().clear_bit());
+        while count.syncbusy().read().enable().bit_is_set() {}
+        (self.tc, self.slave)
+    }
+
+    fn start_timeout(&mut self, timeout: NanosDurationU64) {
+        self.timeout = Some(timeout);
+        let params = TimerParams::new_ns_32(timeout, self.freq);
+        let divider = params.divider;
+        let cycles = params.cycles;
+        let count = self.tc.count_32();
+
+        // Disable the timer while we reconfigure it
+        count.ctrla().modify(|_, w| w.enable().clear_bit());
+        while count.syncbusy().read().enable().bit_is_set() {}
+
+        // Now that we have a clock routed to the peripheral, we
+        // can ask it to perform a reset.
+        count.ctrla().write(|w| w.swrst().set_bit());
+        while count.syncbusy().read().swrst().bit_is_set() {}
+
+        // The counter mode must be selected before the 32-bit
+        // registers can be accessed
+        count.ctrla().write(|w| w.mode().count32());
+
+        count.ctrlbset().write(|w| {
+            // Count up when the direction bit is zero
+            w.dir().clear_bit();
+            // Periodic
+            w.oneshot().clear_bit()
+        });
+
+        // Set TOP value for mfrq mode
+        count.cc(0).write(|w| unsafe { w.cc().bits(cycles) });
+
+        // Enable Match Frequency Waveform generation
+        count.wave().modify(|_, w| w.wavegen().mfrq());
+
+        count.ctrla().modify(|_, w| {
+            match divider {
+                1 => w.prescaler().div1(),
+                2 => w.prescaler().div2(),
+                4 => w.prescaler().div4(),
+                8 => w.prescaler().div8(),
+                16 => w.prescaler().div16(),
+                64 => w.prescaler().div64(),
+                256 => w.prescaler().div256(),
+                1024 => w.prescaler().div1024(),
+                _ => unreachable!(),
+            };
+            w.enable().set_bit();
+            w.runstdby().set_bit()
+        });
+    }
+}
+
+impl<TC: Count32> Periodic for TimerCounter32<TC> {}
+impl<TC> CountDown for TimerCounter32<TC>
+where
+    TC: Count32,
+{
+    type Time = Microseconds;
+
+    fn start<T>(&mut self, timeout: T)
+    where
+        T: Into<Self::Time>,
+    {
+        self.start_timeout(NanosDurationU64::micros(timeout.into().ticks() as u64));
+    }
+
+    fn wait(&mut self) -> nb::Result<(), void::Void> {
+        nb::block! {
+            <Self as InterruptDrivenTimer>::wait(self)
+        }
+        .unwrap(); // wait() is Infallible
+        Ok(())
+    }
+}
+
+impl<TC> InterruptDrivenTimer for TimerCounter32<TC>
+where
+    TC: Count32,
+{
+    /// Enable the interrupt generation for this hardware timer.
+    /// This method only sets the clock configuration to trigger
+    /// the interrupt; it does not configure the interrupt controller
+    /// or define an interrupt handler.
+    fn enable_interrupt(&mut self) {
+        self.tc.count_32().intenset().write(|w| w.ovf().set_bit());
+    }
+
+    fn start<T>(&mut self, timeout: T)
+    where
+        T: Into<NanosDurationU32>,
+    {
+        self.start_timeout(timeout.into().into());
+    }
+
+    fn wait(&mut self) -> nb::Result<(), Infallible> {
+        let count = self.tc.count_32();
+        if count.intflag().read().ovf().bit_is_set() {
+            // Writing a 1 clears the flag
+            count.intflag().modify(|_, w| w.ovf().set_bit());
+            Ok(())
+        } else {
+            Err(nb::Error::WouldBlock)
+        }
+    }
+
+    /// Disables interrupt generation for this hardware timer.
+    /// This method only sets the clock configuration to prevent
+    /// triggering the interrupt; it does not configure the interrupt
+    /// controller.
+    fn disable_interrupt(&mut self) {
+        self.tc.count_32().intenclr().write(|w| w.ovf().set_bit());
+    }
+}
+
+impl<TC> DelayNs for TimerCounter32<TC>
+where
+    TC: Count32,
+{
+    fn delay_ns(&mut self, ns: u32) {
+        <Self as InterruptDrivenTimer>::start(self, NanosDurationU32::from_ticks(ns));
+        nb::block!(<Self as InterruptDrivenTimer>::wait(self)).unwrap();
+
+        let count = self.tc.count_32();
+        count.ctrla().modify(|_, w| w.enable().clear_bit());
+        while count.syncbusy().read().enable().bit_is_set() {}
+    }
+}
+
+/// Recompute the period of a running timer, so that it keeps its current
+/// timeout at the new clock frequency
+impl<TC> Reclock for TimerCounter32<TC>
+where
+    TC: Count32,
+{
+    fn reclock(&mut self, freq: Hertz) {
+        self.freq = freq;
+        let count = self.tc.count_32();
+        let enabled = count.ctrla().read().enable().bit_is_set();
+        // Restarting the timer resets it, so preserve the enabled interrupts
+        let ovf = count.intenset().read().ovf().bit_is_set();
+        if let (true, Some(timeout)) = (enabled, self.timeout) {
+            self.start_timeout(timeout);
+            if ovf {
+                self.enable_interrupt();
+            }
+        }
+    }
+}
+
+macro_rules! tc32 {
+    ($($TYPE:ident: ($TC:ident, $SLAVE:ident, $fn:ident, $mclk:ident, $slave_mclk:ident, $clock:ident, $apmask:ident),)+) => {
+        $(
+pub type $TYPE = TimerCounter32<$TC>;
+
+impl Count32 for $TC {
+    type Slave = $SLAVE;
+
+    fn count_32(&self) -> &Count32Reg {
+        self.count32()
+    }
+}
+
+impl TimerCounter32<$TC>
+{
+    /// Configure this pair of timer counter instances as a 32-bit timer.
+    /// The clock is obtained from the `GenericClockController` instance
+    /// and is shared by both instances of the pair. Its frequency sets
+    /// the resolution of the timeout values that can be passed to the
+    /// `start` method.
+    pub fn $fn(clock: &clock::$clock, tc: $TC, slave: $SLAVE, mclk: &mut Mclk) -> Self {
+        // The slave must be clocked for the pair to count in 32-bit mode
+        mclk.$apmask().modify(|_, w| {
+            w.$mclk().set_bit();
+            w.$slave_mclk().set_bit()
+        });
+        {
+            let count = tc.count32();
+
+            // Disable the timer while we reconfigure it
+            count.ctrla().modify(|_, w| w.enable().clear_bit());
+            while count.syncbusy().read().enable().bit_is_set()  {}
+        }
+        Self {
+            freq: clock.freq(),
+            tc,
+            slave,
+            timeout: None,
+        }
+    }
+}
+        )+
+    }
+}
+
+tc32! {
+    TimerCounter01: (Tc0, Tc1, tc0_tc1_, tc0_, tc1_, Tc0Tc1Clock, apbamask),
+    TimerCounter23: (Tc2, Tc3, tc2_tc3_, tc2_, tc3_, Tc2Tc3Clock, apbbmask),
+}
+
+#[hal_cfg(all("tc4", "tc5"))]
+tc32! {
+    TimerCounter45: (Tc4, Tc5, tc4_tc5_, tc4_, tc5_, Tc4Tc5Clock, apbcmask),
+}
+
+#[hal_cfg(all("tc6", "tc7"))]
+tc32! {
+    TimerCounter67: (Tc6, Tc7, tc6_tc7_, tc6_, tc7_, Tc6Tc7Clock, apbdmask),
+}
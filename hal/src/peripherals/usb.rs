@@ -0,0 +1,13 @@
+//! USB Device and Host support
+
+use atsamd_hal_macros::hal_module;
+
+#[hal_module(
+    any("usb-d11", "usb-d21") => "usb/d11/mod.rs",
+    "usb-d5x" => "usb/d5x/mod.rs",
+)]
+mod impls {}
+pub use impls::*;
+
+#[hal_module(any("usb-d21", "usb-d5x"))]
+mod host_common {}
@@ -234,6 +234,19 @@ impl Descriptors {
     pub fn bank(&mut self, idx: usize, bank: usize) -> &mut DeviceDescBank {
         &mut self.desc[idx].bank[bank]
     }
+
+    /// Returns a pointer to a bank descriptor, without borrowing the
+    /// descriptors of the other endpoints.
+    ///
+    /// # Safety
+    ///
+    /// `this` must point to a valid [`Descriptors`], and `idx` and `bank` must
+    /// be in range. No reference to the descriptors is created, so the
+    /// returned pointer may be used while other banks are borrowed.
+    #[cfg(feature = "async")]
+    pub unsafe fn bank_ptr(this: *mut Self, idx: usize, bank: usize) -> *mut DeviceDescBank {
+        core::ptr::addr_of_mut!((*this).desc[idx].bank[bank])
+    }
 }
 
 unsafe impl Send for DeviceDescBank {}
@@ -0,0 +1,693 @@
+//! # USB Host
+//!
+//! Besides the device mode driven by [`UsbBus`](super::UsbBus), the USB
+//! peripheral can act as the host of a single full-speed or low-speed
+//! device. Hubs are not supported.
+//!
+//! ## Creating a [`UsbHost`]
+//!
+//! The host is created from the USB pads, the 48 MHz USB clock, and an
+//! [`OutputPin`] switching the VBUS supply of the connector. Once VBUS is
+//! turned on with [`UsbHost::set_vbus`], [`UsbHost::poll`] reports the
+//! connection and disconnection of a device as [`HostEvent`]s.
+//!
+//! A newly connected device is reset, addressed and configured by
+//! [`UsbHost::enumerate`], which returns the [`Device`] with its device
+//! descriptor, and the raw configuration descriptor. [`Descriptors`] iterates
+//! over the interface and endpoint descriptors it holds, to find the
+//! endpoints of the class driver.
+//!
+//! ```no_run
+//! use atsamd_hal::usb::host::{Descriptor, Descriptors, HostEvent, UsbHost};
+//!
+//! let mut host = UsbHost::new(
+//!     &usb_clock,
+//!     &mut peripherals.pm,
+//!     pins.pa24,
+//!     pins.pa25,
+//!     peripherals.usb,
+//!     pins.pa14.into_push_pull_output(),
+//! );
+//! host.set_vbus(true).unwrap();
+//!
+//! loop {
+//!     if let Some(HostEvent::Connected(_)) = host.poll() {
+//!         let mut config = [0; 256];
+//!         let (device, config) = host.enumerate(&mut delay, &mut config)?;
+//!         for descriptor in Descriptors::new(config) {
+//!             if let Descriptor::Endpoint(endpoint) = descriptor {
+//!                 // ...
+//!             }
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! ## Class drivers
+//!
+//! Class drivers are written against the [`HostController`] trait, which
+//! performs control transfers on endpoint 0 and bulk or interrupt transfers
+//! on [`Pipe`]s allocated with [`HostController::alloc_pipe`]. Seven pipes
+//! are available in addition to the control pipe, with packets of up to 64
+//! bytes.
+//!
+//! For instance, a HID keyboard exposes an interface of class 3, sub class 1
+//! (boot interface) and protocol 1, with an interrupt IN endpoint polled with
+//! [`HostController::poll_in`] for 8-byte reports. A USB stick exposes a
+//! mass-storage interface of class 8, sub class 6 (SCSI) and protocol 0x50
+//! (bulk-only transport), with a bulk IN and a bulk OUT endpoint used with
+//! [`HostController::transfer_in`] and [`HostController::transfer_out`].
+//!
+//! All the transfers are blocking, and fail with [`HostError::Timeout`] if
+//! the device does not complete them within the timeout set by
+//! [`UsbHost::set_timeout`]. The pipes of a disconnected device must be
+//! released with [`HostController::free_pipe`].
+
+pub use crate::usb::host_common::class::*;
+
+use crate::calibration::{usb_transn_cal, usb_transp_cal, usb_trim_cal};
+use crate::clock;
+use crate::ehal::delay::DelayNs;
+use crate::ehal::digital::OutputPin;
+use crate::gpio::{AlternateG, AnyPin, Pin, PA24, PA25};
+use crate::pac::usb::host::{Binterval, Pcfg, Pintflag, Pstatusclr, Pstatusset};
+use crate::pac::usb::Host;
+use crate::pac::{Pm, Usb};
+use crate::usb::host_common::pipedesc::{PipeDescriptors, PIPE_BUFFER_SIZE};
+use cortex_m::singleton;
+
+/// Address assigned to the connected device
+const DEVICE_ADDRESS: u8 = 1;
+
+/// Default timeout of a transfer, in milliseconds
+const DEFAULT_TIMEOUT_MS: u32 = 5000;
+
+/// Valid values for the PTYPE field of the PCFGn registers
+#[derive(Clone, Copy)]
+enum PipeTypeBits {
+    Disabled = 0,
+    Control = 1,
+    Bulk = 3,
+    Interrupt = 4,
+}
+
+/// Valid values for the PTOKEN field of the PCFGn registers
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Token {
+    Setup = 0,
+    In = 1,
+    Out = 2,
+}
+
+/// Connection event of the bus
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum HostEvent {
+    /// A device was connected
+    Connected(Speed),
+    /// The device was disconnected
+    Disconnected,
+}
+
+fn host() -> &'static Host {
+    unsafe { (*Usb::ptr()).host() }
+}
+
+/// Registers of a single pipe
+#[derive(Clone, Copy)]
+struct PipeRegs(usize);
+
+impl PipeRegs {
+    #[inline]
+    fn pcfg(self) -> &'static Pcfg {
+        host().pcfg(self.0)
+    }
+
+    #[inline]
+    fn binterval(self) -> &'static Binterval {
+        host().binterval(self.0)
+    }
+
+    #[inline]
+    fn pstatusclr(self) -> &'static Pstatusclr {
+        host().pstatusclr(self.0)
+    }
+
+    #[inline]
+    fn pstatusset(self) -> &'static Pstatusset {
+        host().pstatusset(self.0)
+    }
+
+    #[inline]
+    fn pintflag(self) -> &'static Pintflag {
+        host().pintflag(self.0)
+    }
+}
+
+#[inline]
+fn pipe(index: usize) -> PipeRegs {
+    PipeRegs(index)
+}
+
+fn frame_number() -> u16 {
+    host().fnum().read().fnum().bits()
+}
+
+/// Counts the milliseconds elapsed since its creation with the frame number,
+/// which is incremented by each start of frame
+struct FrameTimer {
+    last: u16,
+    remaining: u32,
+}
+
+impl FrameTimer {
+    fn new(timeout_ms: u32) -> Self {
+        Self {
+            last: frame_number(),
+            remaining: timeout_ms,
+        }
+    }
+
+    fn expired(&mut self) -> bool {
+        let now = frame_number();
+        // The frame number is 11 bits wide
+        let elapsed = now.wrapping_sub(self.last) & 0x7FF;
+        self.last = now;
+        self.remaining = self.remaining.saturating_sub(elapsed.into());
+        self.remaining == 0
+    }
+}
+
+/// USB host controller
+pub struct UsbHost<V> {
+    _usb: Usb,
+    _dm_pad: Pin<PA24, AlternateG>,
+    _dp_pad: Pin<PA25, AlternateG>,
+    vbus: V,
+    desc: &'static mut PipeDescriptors,
+    /// Bit mask of the allocated pipes
+    allocated: u8,
+    speed: Option<Speed>,
+    timeout_ms: u32,
+}
+
+impl<V: OutputPin> UsbHost<V> {
+    /// Create the USB host, with VBUS turned off.
+    ///
+    /// The pipe descriptors are allocated statically, so a single
+    /// [`UsbHost`] can be created.
+    pub fn new(
+        _clock: &clock::UsbClock,
+        pm: &mut Pm,
+        dm_pad: impl AnyPin<Id = PA24>,
+        dp_pad: impl AnyPin<Id = PA25>,
+        usb: Usb,
+        mut vbus: V,
+    ) -> Self {
+        pm.apbbmask().modify(|_, w| w.usb_().set_bit());
+
+        let _ = vbus.set_low();
+
+        let desc = singleton!(: PipeDescriptors = PipeDescriptors::new()).unwrap();
+        desc.init();
+
+        let mut usb_host = Self {
+            _usb: usb,
+            _dm_pad: dm_pad.into().into_mode::<AlternateG>(),
+            _dp_pad: dp_pad.into().into_mode::<AlternateG>(),
+            vbus,
+            desc,
+            allocated: 0,
+            speed: None,
+            timeout_ms: DEFAULT_TIMEOUT_MS,
+        };
+        usb_host.enable();
+        usb_host
+    }
+
+    fn enable(&mut self) {
+        let usb = host();
+        usb.ctrla().modify(|_, w| w.swrst().set_bit());
+        while usb.syncbusy().read().swrst().bit_is_set() {}
+
+        let addr = self.desc.address();
+        usb.descadd().write(|w| unsafe { w.descadd().bits(addr) });
+        usb.padcal().modify(|_, w| unsafe {
+            w.transn().bits(usb_transn_cal());
+            w.transp().bits(usb_transp_cal());
+            w.trim().bits(usb_trim_cal())
+        });
+        usb.qosctrl().modify(|_, w| unsafe {
+            w.dqos().bits(0b11);
+            w.cqos().bits(0b11)
+        });
+        usb.ctrla().modify(|_, w| {
+            w.mode().host();
+            w.runstdby().set_bit()
+        });
+        usb.ctrlb().modify(|_, w| w.spdconf().normal());
+
+        usb.ctrla().modify(|_, w| w.enable().set_bit());
+        while usb.syncbusy().read().enable().bit_is_set() {}
+
+        // The control pipe is always configured
+        pipe(0)
+            .pcfg()
+            .write(|w| unsafe { w.ptype().bits(PipeTypeBits::Control as u8) });
+        pipe(0).pstatusset().write(|w| w.pfreeze().set_bit());
+    }
+
+    /// Turn the VBUS supply of the connector on or off. Devices are only
+    /// detected while VBUS is on.
+    pub fn set_vbus(&mut self, on: bool) -> Result<(), V::Error> {
+        if on {
+            self.vbus.set_high()?;
+            host().ctrlb().modify(|_, w| w.vbusok().set_bit());
+        } else {
+            host().ctrlb().modify(|_, w| w.vbusok().clear_bit());
+            self.vbus.set_low()?;
+            if self.speed.is_some() {
+                self.disconnect();
+            }
+        }
+        Ok(())
+    }
+
+    /// Set the timeout of the transfers, in milliseconds
+    pub fn set_timeout(&mut self, timeout_ms: u32) {
+        self.timeout_ms = timeout_ms;
+    }
+
+    /// Speed of the connected device, or `None` if no device is connected
+    pub fn speed(&self) -> Option<Speed> {
+        self.speed
+    }
+
+    /// Check for the connection or disconnection of a device
+    pub fn poll(&mut self) -> Option<HostEvent> {
+        let flags = host().intflag().read();
+        if flags.ddisc().bit_is_set() {
+            // A pending connection belongs to the device that just left
+            host()
+                .intflag()
+                .write(|w| w.ddisc().set_bit().dconn().set_bit());
+            self.disconnect();
+            Some(HostEvent::Disconnected)
+        } else if flags.dconn().bit_is_set() {
+            host().intflag().write(|w| w.dconn().set_bit());
+            let speed = bus_speed();
+            self.speed = Some(speed);
+            Some(HostEvent::Connected(speed))
+        } else {
+            None
+        }
+    }
+
+    fn disconnect(&mut self) {
+        self.speed = None;
+        host().ctrlb().modify(|_, w| w.sofe().clear_bit());
+        for index in 0..8 {
+            pipe(index).pstatusset().write(|w| w.pfreeze().set_bit());
+        }
+    }
+
+    /// Reset the connected device, and start sending the start of frames.
+    fn reset_bus(&mut self, delay: &mut impl DelayNs) -> Result<(), HostError> {
+        host().intflag().write(|w| w.rst().set_bit());
+        host().ctrlb().modify(|_, w| w.busreset().set_bit());
+        let mut reset = false;
+        for _ in 0..100 {
+            if host().intflag().read().rst().bit_is_set() {
+                reset = true;
+                break;
+            }
+            delay.delay_ms(1);
+        }
+        if !reset {
+            return Err(HostError::Timeout);
+        }
+        host().intflag().write(|w| w.rst().set_bit());
+        host().ctrlb().modify(|_, w| w.sofe().set_bit());
+
+        // Reset recovery time
+        delay.delay_ms(20);
+        if host().intflag().read().ddisc().bit_is_set() {
+            return Err(HostError::Disconnected);
+        }
+        self.speed = Some(bus_speed());
+        Ok(())
+    }
+
+    /// Enumerate the connected device: reset it, assign its address, read its
+    /// device and configuration descriptors, and select its first
+    /// configuration.
+    ///
+    /// The configuration descriptor, with all the interface and endpoint
+    /// descriptors it holds, is read into `config` and returned with the
+    /// [`Device`].
+    pub fn enumerate<'c>(
+        &mut self,
+        delay: &mut impl DelayNs,
+        config: &'c mut [u8],
+    ) -> Result<(Device, &'c [u8]), HostError> {
+        if self.speed.is_none() {
+            return Err(HostError::Disconnected);
+        }
+
+        // Let the power of the device settle
+        delay.delay_ms(100);
+        self.reset_bus(delay)?;
+
+        let mut device = Device {
+            address: 0,
+            speed: self.speed.ok_or(HostError::Disconnected)?,
+            descriptor: DeviceDescriptor {
+                max_packet_size0: 8,
+                ..Default::default()
+            },
+        };
+
+        // Read the maximum packet size of endpoint 0 before anything else
+        let mut buf = [0; DeviceDescriptor::LENGTH];
+        let len = self.get_descriptor(&device, descriptor_type::DEVICE, 0, &mut buf[..8])?;
+        if len < 8 || !matches!(buf[7], 8 | 16 | 32 | 64) {
+            return Err(HostError::InvalidDescriptor);
+        }
+        device.descriptor.max_packet_size0 = buf[7];
+
+        self.control_out(&device, &SetupPacket::set_address(DEVICE_ADDRESS), &[])?;
+        // SET_ADDRESS recovery time
+        delay.delay_ms(2);
+        device.address = DEVICE_ADDRESS;
+
+        let len = self.get_descriptor(&device, descriptor_type::DEVICE, 0, &mut buf)?;
+        device.descriptor = DeviceDescriptor::parse(&buf[..len])?;
+
+        if config.len() < ConfigurationDescriptor::LENGTH {
+            return Err(HostError::BufferOverflow);
+        }
+        let len = self.get_descriptor(
+            &device,
+            descriptor_type::CONFIGURATION,
+            0,
+            &mut config[..ConfigurationDescriptor::LENGTH],
+        )?;
+        let header = ConfigurationDescriptor::parse(&config[..len])?;
+        let total_length = header.total_length as usize;
+        if total_length > config.len() {
+            return Err(HostError::BufferOverflow);
+        }
+        let len = self.get_descriptor(
+            &device,
+            descriptor_type::CONFIGURATION,
+            0,
+            &mut config[..total_length],
+        )?;
+
+        self.control_out(
+            &device,
+            &SetupPacket::set_configuration(header.configuration_value),
+            &[],
+        )?;
+
+        Ok((device, &config[..len]))
+    }
+
+    /// Start a transaction of `len` bytes on a pipe
+    fn start(&mut self, index: usize, token: Token, len: u16) {
+        self.desc.bank(index).prepare(len, token != Token::In);
+
+        let pipe = pipe(index);
+        pipe.pcfg()
+            .modify(|_, w| unsafe { w.ptoken().bits(token as u8) });
+        pipe.pintflag().write(|w| {
+            w.trcpt0().set_bit();
+            w.trcpt1().set_bit();
+            w.trfail().set_bit();
+            w.perr().set_bit();
+            w.txstp().set_bit();
+            w.stall().set_bit()
+        });
+        match token {
+            Token::In => pipe.pstatusclr().write(|w| w.bk0rdy().set_bit()),
+            _ => pipe.pstatusset().write(|w| w.bk0rdy().set_bit()),
+        }
+        pipe.pstatusclr().write(|w| w.pfreeze().set_bit());
+    }
+
+    /// Check whether the transaction running on a pipe is over, and return
+    /// the number of bytes received if it is.
+    fn check(&mut self, index: usize, token: Token) -> Option<Result<u16, HostError>> {
+        let pipe = pipe(index);
+        let flags = pipe.pintflag().read();
+        let complete = match token {
+            Token::Setup => flags.txstp().bit_is_set(),
+            _ => flags.trcpt0().bit_is_set(),
+        };
+        let result = if flags.stall().bit_is_set() {
+            Err(HostError::Stall)
+        } else if flags.perr().bit_is_set() {
+            Err(HostError::PipeError)
+        } else if flags.trfail().bit_is_set() {
+            Err(HostError::TransferFailed)
+        } else if complete {
+            Ok(self.desc.bank(index).byte_count())
+        } else if host().intflag().read().ddisc().bit_is_set() {
+            Err(HostError::Disconnected)
+        } else {
+            return None;
+        };
+
+        pipe.pstatusset().write(|w| w.pfreeze().set_bit());
+        pipe.pintflag().write(|w| {
+            w.trcpt0().set_bit();
+            w.trfail().set_bit();
+            w.perr().set_bit();
+            w.txstp().set_bit();
+            w.stall().set_bit()
+        });
+        Some(result)
+    }
+
+    /// Run a transaction of `len` bytes on a pipe, and return the number of
+    /// bytes received
+    fn transaction(&mut self, index: usize, token: Token, len: u16) -> Result<u16, HostError> {
+        // Without a device, the frames stop and the timer would never expire
+        if self.speed.is_none() {
+            return Err(HostError::Disconnected);
+        }
+        self.start(index, token, len);
+        let mut timer = FrameTimer::new(self.timeout_ms);
+        loop {
+            if let Some(result) = self.check(index, token) {
+                return result;
+            }
+            if timer.expired() {
+                pipe(index).pstatusset().write(|w| w.pfreeze().set_bit());
+                return Err(HostError::Timeout);
+            }
+        }
+    }
+
+    /// Send the SETUP packet of a control transfer to `device`
+    fn setup_stage(&mut self, device: &Device, setup: &SetupPacket) -> Result<(), HostError> {
+        let bank = self.desc.bank(0);
+        bank.set_endpoint(device.address, 0);
+        bank.set_endpoint_size(device.descriptor.max_packet_size0.into());
+        self.desc.buffer(0)[..8].copy_from_slice(&setup.to_bytes());
+
+        pipe(0).pstatusclr().write(|w| w.dtgl().set_bit());
+        self.transaction(0, Token::Setup, 8)?;
+        // The data and status stages start with DATA1
+        pipe(0).pstatusset().write(|w| w.dtgl().set_bit());
+        Ok(())
+    }
+}
+
+fn bus_speed() -> Speed {
+    match host().status().read().speed().bits() {
+        1 => Speed::Low,
+        _ => Speed::Full,
+    }
+}
+
+impl<V: OutputPin> HostController for UsbHost<V> {
+    fn control_in(
+        &mut self,
+        device: &Device,
+        setup: &SetupPacket,
+        buf: &mut [u8],
+    ) -> Result<usize, HostError> {
+        self.setup_stage(device, setup)?;
+
+        let max_packet_size = device.descriptor.max_packet_size0 as usize;
+        let len = buf.len().min(setup.length as usize);
+        let mut received = 0;
+        while received < len {
+            let count = self.transaction(0, Token::In, max_packet_size as u16)? as usize;
+            if count > len - received {
+                return Err(HostError::BufferOverflow);
+            }
+            buf[received..received + count].copy_from_slice(&self.desc.buffer(0)[..count]);
+            received += count;
+            if count < max_packet_size {
+                break;
+            }
+        }
+
+        pipe(0).pstatusset().write(|w| w.dtgl().set_bit());
+        self.transaction(0, Token::Out, 0)?;
+        Ok(received)
+    }
+
+    fn control_out(
+        &mut self,
+        device: &Device,
+        setup: &SetupPacket,
+        data: &[u8],
+    ) -> Result<(), HostError> {
+        self.setup_stage(device, setup)?;
+
+        let max_packet_size = device.descriptor.max_packet_size0 as usize;
+        let len = data.len().min(setup.length as usize);
+        for chunk in data[..len].chunks(max_packet_size) {
+            self.desc.buffer(0)[..chunk.len()].copy_from_slice(chunk);
+            self.transaction(0, Token::Out, chunk.len() as u16)?;
+        }
+
+        pipe(0).pstatusset().write(|w| w.dtgl().set_bit());
+        self.transaction(0, Token::In, max_packet_size as u16)?;
+        Ok(())
+    }
+
+    fn alloc_pipe(
+        &mut self,
+        device: &Device,
+        endpoint: &EndpointDescriptor,
+    ) -> Result<Pipe, HostError> {
+        let ptype = match endpoint.transfer_type() {
+            TransferType::Bulk => PipeTypeBits::Bulk,
+            TransferType::Interrupt => PipeTypeBits::Interrupt,
+            _ => return Err(HostError::Unsupported),
+        };
+        if endpoint.max_packet_size == 0 {
+            return Err(HostError::InvalidDescriptor);
+        }
+        if endpoint.max_packet_size as usize > PIPE_BUFFER_SIZE {
+            return Err(HostError::Unsupported);
+        }
+        let token = match endpoint.direction() {
+            Direction::In => Token::In,
+            Direction::Out => Token::Out,
+        };
+
+        // Pipe 0 is the control pipe
+        let index = (1..8)
+            .find(|index| self.allocated & (1 << index) == 0)
+            .ok_or(HostError::NoPipe)?;
+        self.allocated |= 1 << index;
+
+        let bank = self.desc.bank(index);
+        bank.set_endpoint(device.address, endpoint.number());
+        bank.set_endpoint_size(endpoint.max_packet_size);
+
+        let pipe = pipe(index);
+        pipe.pcfg().write(|w| unsafe {
+            w.ptype().bits(ptype as u8);
+            w.ptoken().bits(token as u8)
+        });
+        pipe.binterval()
+            .write(|w| unsafe { w.bitinterval().bits(endpoint.interval) });
+        pipe.pstatusset().write(|w| w.pfreeze().set_bit());
+        pipe.pstatusclr().write(|w| w.dtgl().set_bit());
+
+        Ok(Pipe {
+            index: index as u8,
+            endpoint: *endpoint,
+            in_flight: false,
+        })
+    }
+
+    fn free_pipe(&mut self, pipe: Pipe) {
+        let index = pipe.index as usize;
+        let regs = self::pipe(index);
+        regs.pstatusset().write(|w| w.pfreeze().set_bit());
+        regs.pcfg()
+            .write(|w| unsafe { w.ptype().bits(PipeTypeBits::Disabled as u8) });
+        self.allocated &= !(1 << index);
+    }
+
+    fn transfer_in(&mut self, pipe: &mut Pipe, buf: &mut [u8]) -> Result<usize, HostError> {
+        if pipe.endpoint.direction() != Direction::In {
+            return Err(HostError::Unsupported);
+        }
+        let index = pipe.index as usize;
+        if pipe.in_flight {
+            self::pipe(index)
+                .pstatusset()
+                .write(|w| w.pfreeze().set_bit());
+            pipe.in_flight = false;
+        }
+
+        let max_packet_size = pipe.endpoint.max_packet_size as usize;
+        let mut received = 0;
+        while received < buf.len() {
+            let count = self.transaction(index, Token::In, max_packet_size as u16)? as usize;
+            if count > buf.len() - received {
+                return Err(HostError::BufferOverflow);
+            }
+            buf[received..received + count].copy_from_slice(&self.desc.buffer(index)[..count]);
+            received += count;
+            if count < max_packet_size {
+                break;
+            }
+        }
+        Ok(received)
+    }
+
+    fn transfer_out(&mut self, pipe: &mut Pipe, data: &[u8], zlp: bool) -> Result<(), HostError> {
+        if pipe.endpoint.direction() != Direction::Out {
+            return Err(HostError::Unsupported);
+        }
+        let index = pipe.index as usize;
+        let max_packet_size = pipe.endpoint.max_packet_size as usize;
+        for chunk in data.chunks(max_packet_size) {
+            self.desc.buffer(index)[..chunk.len()].copy_from_slice(chunk);
+            self.transaction(index, Token::Out, chunk.len() as u16)?;
+        }
+        if data.is_empty() || (zlp && data.len() % max_packet_size == 0) {
+            self.transaction(index, Token::Out, 0)?;
+        }
+        Ok(())
+    }
+
+    fn poll_in(&mut self, pipe: &mut Pipe, buf: &mut [u8]) -> nb::Result<usize, HostError> {
+        if pipe.endpoint.direction() != Direction::In {
+            return Err(nb::Error::Other(HostError::Unsupported));
+        }
+        let index = pipe.index as usize;
+        if !pipe.in_flight {
+            if self.speed.is_none() {
+                return Err(nb::Error::Other(HostError::Disconnected));
+            }
+            self.start(index, Token::In, pipe.endpoint.max_packet_size);
+            pipe.in_flight = true;
+            return Err(nb::Error::WouldBlock);
+        }
+
+        let count = self.check(index, Token::In).ok_or(nb::Error::WouldBlock)?;
+        pipe.in_flight = false;
+        let count = count? as usize;
+        if count > buf.len() {
+            return Err(nb::Error::Other(HostError::BufferOverflow));
+        }
+        buf[..count].copy_from_slice(&self.desc.buffer(index)[..count]);
+        Ok(count)
+    }
+
+    fn reset_data_toggle(&mut self, pipe: &mut Pipe) {
+        self::pipe(pipe.index as usize)
+            .pstatusclr()
+            .write(|w| w.dtgl().set_bit());
+    }
+}
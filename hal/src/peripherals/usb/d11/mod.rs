@@ -1,4 +1,4 @@
-//! USB Device support
+use atsamd_hal_macros::hal_module;
 
 use crate::gpio::{
     pin::{Pin, PA23, PA24, PA25},
@@ -13,6 +13,16 @@ pub use self::bus::UsbBus;
 mod devicedesc;
 use self::devicedesc::Descriptors;
 
+#[hal_module("usb-d21")]
+pub mod host {}
+
+#[cfg(feature = "async")]
+mod async_api;
+#[cfg(feature = "async")]
+pub use self::async_api::*;
+#[cfg(feature = "async")]
+pub use embassy_usb_driver;
+
 /// Emit SOF at 1Khz on this pin when configured as function G
 pub type SofPad = Pin<PA23, AlternateG>;
 
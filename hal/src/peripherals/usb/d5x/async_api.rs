@@ -0,0 +1,799 @@
+//! `async` USB device driver
+//!
+//! [`Driver`] implements the [`embassy_usb_driver::Driver`] trait, so that the
+//! USB peripheral can be used with the [`embassy-usb`] device stack. Endpoint
+//! transfers, the control pipe and bus events are driven by the USB
+//! interrupts, which must be bound to the [`InterruptHandler`]:
+//!
+//! ```no_run
+//! use atsamd_hal::usb::{Driver, InterruptHandler};
+//!
+//! atsamd_hal::bind_multiple_interrupts!(struct Irqs {
+//!     USB: [USB_OTHER, USB_SOF_HSOF, USB_TRCPT0, USB_TRCPT1] => InterruptHandler;
+//! });
+//!
+//! let driver = Driver::new(&usb_clock, &mut peripherals.mclk, pins.pa24, pins.pa25, peripherals.usb, Irqs);
+//! let mut builder = embassy_usb::Builder::new(driver, config, /* ... */);
+//! ```
+//!
+//! [`embassy-usb`]: https://crates.io/crates/embassy-usb
+
+use core::future::poll_fn;
+use core::task::Poll;
+
+use cortex_m::singleton;
+use embassy_sync::waitqueue::AtomicWaker;
+use embassy_usb_driver::{
+    self as usb_driver, Direction, EndpointAddress, EndpointAllocError, EndpointError,
+    EndpointInfo, EndpointType, Event, Unsupported,
+};
+
+use super::bus::{allocated_size, BufferAllocator, EndpointTypeBits};
+use super::devicedesc::DeviceDescBank;
+use super::Descriptors;
+use crate::async_hal::interrupts::{Binding, Handler, InterruptSource, USB};
+use crate::calibration::{usb_transn_cal, usb_transp_cal, usb_trim_cal};
+use crate::clock;
+use crate::gpio::{AlternateH, AnyPin, Pin, PA24, PA25};
+use crate::pac::usb::device::device_endpoint::DeviceEndpoint;
+use crate::pac::usb::Device;
+use crate::pac::{Mclk, Usb};
+
+/// Number of endpoints supported by the USB peripheral
+const NUM_ENDPOINTS: usize = 8;
+
+// EPINTFLAG bits
+const TRCPT0: u8 = 1 << 0;
+const TRCPT1: u8 = 1 << 1;
+const TRFAIL0: u8 = 1 << 2;
+const TRFAIL1: u8 = 1 << 3;
+const RXSTP: u8 = 1 << 4;
+const STALL0: u8 = 1 << 5;
+const STALL1: u8 = 1 << 6;
+
+/// Endpoint interrupt flags related to bank 0 (OUT and SETUP)
+const OUT_FLAGS: u8 = TRCPT0 | TRFAIL0 | RXSTP | STALL0;
+/// Endpoint interrupt flags related to bank 1 (IN)
+const IN_FLAGS: u8 = TRCPT1 | TRFAIL1 | STALL1;
+
+#[allow(clippy::declare_interior_mutable_const)]
+const NEW_WAKER: AtomicWaker = AtomicWaker::new();
+static BUS_WAKER: AtomicWaker = AtomicWaker::new();
+static OUT_WAKERS: [AtomicWaker; NUM_ENDPOINTS] = [NEW_WAKER; NUM_ENDPOINTS];
+static IN_WAKERS: [AtomicWaker; NUM_ENDPOINTS] = [NEW_WAKER; NUM_ENDPOINTS];
+
+#[inline]
+fn usb() -> &'static Device {
+    unsafe { (*Usb::ptr()).device() }
+}
+
+#[inline]
+fn endpoint(idx: usize) -> &'static DeviceEndpoint {
+    usb().device_endpoint(idx)
+}
+
+/// Bank of an endpoint descriptor used by a given direction
+#[inline]
+fn bank_index(direction: Direction) -> usize {
+    match direction {
+        Direction::Out => 0,
+        Direction::In => 1,
+    }
+}
+
+impl From<EndpointType> for EndpointTypeBits {
+    fn from(ep_type: EndpointType) -> EndpointTypeBits {
+        match ep_type {
+            EndpointType::Control => EndpointTypeBits::Control,
+            EndpointType::Isochronous => EndpointTypeBits::Isochronous,
+            EndpointType::Bulk => EndpointTypeBits::Bulk,
+            EndpointType::Interrupt => EndpointTypeBits::Interrupt,
+        }
+    }
+}
+
+/// Interrupt handler for the USB peripheral.
+pub struct InterruptHandler {
+    _private: (),
+}
+
+impl crate::typelevel::Sealed for InterruptHandler {}
+
+impl Handler<USB> for InterruptHandler {
+    unsafe fn on_interrupt() {
+        let usb = usb();
+
+        // Disable the bus interrupts but don't clear them; they will be
+        // cleared when the bus is next polled.
+        let pending = usb.intflag().read().bits() & usb.intenset().read().bits();
+        if pending != 0 {
+            usb.intenclr().write(|w| w.bits(pending));
+            BUS_WAKER.wake();
+        }
+
+        for idx in 0..NUM_ENDPOINTS {
+            let ep = endpoint(idx);
+            let pending = ep.epintflag().read().bits() & ep.epintenset().read().bits();
+            if pending == 0 {
+                continue;
+            }
+
+            // Same as above, the flags are cleared by the endpoint futures
+            ep.epintenclr().write(|w| w.bits(pending));
+            if pending & OUT_FLAGS != 0 {
+                OUT_WAKERS[idx].wake();
+            }
+            if pending & IN_FLAGS != 0 {
+                IN_WAKERS[idx].wake();
+            }
+        }
+    }
+}
+
+//==============================================================================
+// Descriptors
+//==============================================================================
+
+/// Shared handle to the endpoint descriptors
+///
+/// Each bank descriptor is only ever accessed by the single endpoint (or
+/// control pipe) that owns it.
+#[derive(Clone, Copy)]
+struct DescriptorsPtr(*mut Descriptors);
+
+// SAFETY: The descriptors live in a static, and each bank is only accessed by
+// its owner
+unsafe impl Send for DescriptorsPtr {}
+unsafe impl Sync for DescriptorsPtr {}
+
+impl DescriptorsPtr {
+    /// # Safety
+    ///
+    /// The caller must own the bank, and not hold another reference to it.
+    #[inline]
+    #[allow(clippy::mut_from_ref)]
+    unsafe fn bank(&self, idx: usize, bank: usize) -> &mut DeviceDescBank {
+        // SAFETY: `self.0` points to the static descriptors, the indices come
+        // from an allocated endpoint, and the caller owns the bank
+        unsafe { &mut *Descriptors::bank_ptr(self.0, idx, bank) }
+    }
+}
+
+//==============================================================================
+// Driver
+//==============================================================================
+
+/// `async` USB device driver, implementing [`embassy_usb_driver::Driver`]
+pub struct Driver {
+    desc: DescriptorsPtr,
+    buffers: BufferAllocator,
+    ep_types: [[EndpointTypeBits; 2]; NUM_ENDPOINTS],
+    dm_pad: Pin<PA24, AlternateH>,
+    dp_pad: Pin<PA25, AlternateH>,
+}
+
+impl Driver {
+    /// Create the driver. The USB interrupts must be bound to the
+    /// [`InterruptHandler`].
+    pub fn new<I>(
+        _clock: &clock::UsbClock,
+        mclk: &mut Mclk,
+        dm_pad: impl AnyPin<Id = PA24>,
+        dp_pad: impl AnyPin<Id = PA25>,
+        _usb: Usb,
+        _irq: I,
+    ) -> Self
+    where
+        I: Binding<USB, InterruptHandler>,
+    {
+        mclk.ahbmask().modify(|_, w| w.usb_().set_bit());
+        mclk.apbbmask().modify(|_, w| w.usb_().set_bit());
+
+        USB::unpend();
+        unsafe { USB::enable() };
+
+        let desc: *mut Descriptors = singleton!(: Descriptors = Descriptors::new()).unwrap();
+
+        Self {
+            desc: DescriptorsPtr(desc),
+            buffers: BufferAllocator::new(),
+            ep_types: [[EndpointTypeBits::Disabled; 2]; NUM_ENDPOINTS],
+            dm_pad: dm_pad.into().into_mode(),
+            dp_pad: dp_pad.into().into_mode(),
+        }
+    }
+
+    fn alloc_endpoint(
+        &mut self,
+        direction: Direction,
+        ep_type: EndpointType,
+        max_packet_size: u16,
+        interval_ms: u8,
+    ) -> Result<Endpoint, EndpointAllocError> {
+        let bank = bank_index(direction);
+        let allocated_size = allocated_size(max_packet_size).ok_or(EndpointAllocError)?;
+        // Endpoint 0 is reserved for the control pipe
+        let idx = (1..NUM_ENDPOINTS)
+            .find(|&idx| self.ep_types[idx][bank] == EndpointTypeBits::Disabled)
+            .ok_or(EndpointAllocError)?;
+        let buffer = self
+            .buffers
+            .allocate_buffer(allocated_size)
+            .map_err(|_| EndpointAllocError)?;
+
+        self.ep_types[idx][bank] = ep_type.into();
+        // SAFETY: The bank was not allocated before
+        let desc = unsafe { self.desc.bank(idx, bank) };
+        desc.set_address(buffer);
+        desc.set_endpoint_size(max_packet_size);
+        desc.set_multi_packet_size(0);
+        desc.set_byte_count(0);
+
+        Ok(Endpoint {
+            info: EndpointInfo {
+                addr: EndpointAddress::from_parts(idx, direction),
+                ep_type,
+                max_packet_size,
+                interval_ms,
+            },
+            desc: self.desc,
+        })
+    }
+}
+
+impl<'a> usb_driver::Driver<'a> for Driver {
+    type EndpointOut = EndpointOut;
+    type EndpointIn = EndpointIn;
+    type ControlPipe = ControlPipe;
+    type Bus = Bus;
+
+    fn alloc_endpoint_out(
+        &mut self,
+        ep_type: EndpointType,
+        max_packet_size: u16,
+        interval_ms: u8,
+    ) -> Result<EndpointOut, EndpointAllocError> {
+        self.alloc_endpoint(Direction::Out, ep_type, max_packet_size, interval_ms)
+            .map(EndpointOut)
+    }
+
+    fn alloc_endpoint_in(
+        &mut self,
+        ep_type: EndpointType,
+        max_packet_size: u16,
+        interval_ms: u8,
+    ) -> Result<EndpointIn, EndpointAllocError> {
+        self.alloc_endpoint(Direction::In, ep_type, max_packet_size, interval_ms)
+            .map(EndpointIn)
+    }
+
+    fn start(mut self, control_max_packet_size: u16) -> (Bus, ControlPipe) {
+        let allocated_size = allocated_size(control_max_packet_size)
+            .expect("unsupported control endpoint packet size");
+        for bank in 0..2 {
+            let buffer = self
+                .buffers
+                .allocate_buffer(allocated_size)
+                .expect("not enough USB memory for the control endpoint");
+            self.ep_types[0][bank] = EndpointTypeBits::Control;
+            // SAFETY: Endpoint 0 is reserved for the control pipe
+            let desc = unsafe { self.desc.bank(0, bank) };
+            desc.set_address(buffer);
+            desc.set_endpoint_size(control_max_packet_size);
+            desc.set_multi_packet_size(0);
+            desc.set_byte_count(0);
+        }
+
+        let bus = Bus {
+            desc_addr: self.desc.0 as u32,
+            ep_types: self.ep_types,
+            powered: false,
+            suspended: false,
+            _dm_pad: self.dm_pad,
+            _dp_pad: self.dp_pad,
+        };
+        let control = ControlPipe {
+            max_packet_size: control_max_packet_size,
+            desc: self.desc,
+        };
+        (bus, control)
+    }
+}
+
+//==============================================================================
+// Bus
+//==============================================================================
+
+/// USB bus, implementing [`embassy_usb_driver::Bus`]
+pub struct Bus {
+    desc_addr: u32,
+    ep_types: [[EndpointTypeBits; 2]; NUM_ENDPOINTS],
+    powered: bool,
+    suspended: bool,
+    _dm_pad: Pin<PA24, AlternateH>,
+    _dp_pad: Pin<PA25, AlternateH>,
+}
+
+impl Bus {
+    /// Configure the control endpoint, and disable all the other ones until
+    /// the host selects a configuration.
+    fn reset_endpoints(&mut self) {
+        for idx in 0..NUM_ENDPOINTS {
+            let eptype = if idx == 0 {
+                EndpointTypeBits::Control
+            } else {
+                EndpointTypeBits::Disabled
+            };
+            endpoint(idx).epcfg().write(|w| unsafe {
+                w.eptype0().bits(eptype as u8);
+                w.eptype1().bits(eptype as u8)
+            });
+            OUT_WAKERS[idx].wake();
+            IN_WAKERS[idx].wake();
+        }
+        endpoint(0).epstatusclr().write(|w| {
+            w.stallrq0().set_bit();
+            w.stallrq1().set_bit();
+            w.bk0rdy().set_bit();
+            w.bk1rdy().set_bit()
+        });
+    }
+}
+
+impl usb_driver::Bus for Bus {
+    async fn enable(&mut self) {
+        let usb = usb();
+        usb.ctrla().modify(|_, w| w.swrst().set_bit());
+        while usb.syncbusy().read().swrst().bit_is_set() {}
+
+        usb.descadd()
+            .write(|w| unsafe { w.descadd().bits(self.desc_addr) });
+        usb.padcal().modify(|_, w| unsafe {
+            w.transn().bits(usb_transn_cal());
+            w.transp().bits(usb_transp_cal());
+            w.trim().bits(usb_trim_cal())
+        });
+        usb.qosctrl().modify(|_, w| unsafe {
+            w.dqos().bits(0b11);
+            w.cqos().bits(0b11)
+        });
+        usb.ctrla().modify(|_, w| {
+            w.mode().device();
+            w.runstdby().set_bit()
+        });
+        // full speed
+        usb.ctrlb().modify(|_, w| w.spdconf().fs());
+
+        usb.ctrla().modify(|_, w| w.enable().set_bit());
+        while usb.syncbusy().read().enable().bit_is_set() {}
+
+        // Clear pending.
+        usb.intflag()
+            .write(|w| unsafe { w.bits(usb.intflag().read().bits()) });
+
+        self.reset_endpoints();
+        self.suspended = false;
+
+        usb.ctrlb().modify(|_, w| w.detach().clear_bit());
+    }
+
+    async fn disable(&mut self) {
+        let usb = usb();
+        usb.ctrlb().modify(|_, w| w.detach().set_bit());
+        usb.ctrla().modify(|_, w| w.enable().clear_bit());
+        while usb.syncbusy().read().enable().bit_is_set() {}
+    }
+
+    async fn poll(&mut self) -> Event {
+        poll_fn(|cx| {
+            BUS_WAKER.register(cx.waker());
+
+            // There is no VBUS detection, so assume the device is powered
+            if !self.powered {
+                self.powered = true;
+                return Poll::Ready(Event::PowerDetected);
+            }
+
+            let usb = usb();
+            let flags = usb.intflag().read();
+            if flags.eorst().bit_is_set() {
+                usb.intflag().write(|w| w.eorst().set_bit());
+                self.reset_endpoints();
+                self.suspended = false;
+                return Poll::Ready(Event::Reset);
+            }
+            if self.suspended && flags.wakeup().bit_is_set() {
+                usb.intflag()
+                    .write(|w| w.wakeup().set_bit().suspend().set_bit());
+                self.suspended = false;
+                return Poll::Ready(Event::Resume);
+            }
+            if !self.suspended && flags.suspend().bit_is_set() {
+                usb.intflag()
+                    .write(|w| w.suspend().set_bit().wakeup().set_bit());
+                self.suspended = true;
+                return Poll::Ready(Event::Suspend);
+            }
+
+            let suspended = self.suspended;
+            usb.intenset().write(|w| {
+                w.eorst().set_bit();
+                w.wakeup().bit(suspended);
+                w.suspend().bit(!suspended)
+            });
+            Poll::Pending
+        })
+        .await
+    }
+
+    fn endpoint_set_enabled(&mut self, ep_addr: EndpointAddress, enabled: bool) {
+        let idx = ep_addr.index();
+        let ep = endpoint(idx);
+        let eptype = if enabled {
+            self.ep_types[idx][bank_index(ep_addr.direction())]
+        } else {
+            EndpointTypeBits::Disabled
+        };
+
+        // The endpoint must be configured before its status can be written
+        match ep_addr.direction() {
+            Direction::Out => {
+                ep.epcfg()
+                    .modify(|_, w| unsafe { w.eptype0().bits(eptype as u8) });
+                ep.epstatusclr().write(|w| {
+                    w.bk0rdy().set_bit();
+                    w.dtglout().set_bit();
+                    w.stallrq0().set_bit()
+                });
+                OUT_WAKERS[idx].wake();
+            }
+            Direction::In => {
+                ep.epcfg()
+                    .modify(|_, w| unsafe { w.eptype1().bits(eptype as u8) });
+                ep.epstatusclr().write(|w| {
+                    w.bk1rdy().set_bit();
+                    w.dtglin().set_bit();
+                    w.stallrq1().set_bit()
+                });
+                IN_WAKERS[idx].wake();
+            }
+        }
+    }
+
+    fn endpoint_set_stalled(&mut self, ep_addr: EndpointAddress, stalled: bool) {
+        let ep = endpoint(ep_addr.index());
+        match (ep_addr.direction(), stalled) {
+            (Direction::Out, true) => ep.epstatusset().write(|w| w.stallrq0().set_bit()),
+            (Direction::In, true) => ep.epstatusset().write(|w| w.stallrq1().set_bit()),
+            // Clearing a halt feature also resets the data toggle
+            (Direction::Out, false) => ep
+                .epstatusclr()
+                .write(|w| w.stallrq0().set_bit().dtglout().set_bit()),
+            (Direction::In, false) => ep
+                .epstatusclr()
+                .write(|w| w.stallrq1().set_bit().dtglin().set_bit()),
+        }
+    }
+
+    fn endpoint_is_stalled(&mut self, ep_addr: EndpointAddress) -> bool {
+        let status = endpoint(ep_addr.index()).epstatus().read();
+        match ep_addr.direction() {
+            Direction::Out => status.stallrq0().bit_is_set(),
+            Direction::In => status.stallrq1().bit_is_set(),
+        }
+    }
+
+    async fn remote_wakeup(&mut self) -> Result<(), Unsupported> {
+        usb().ctrlb().modify(|_, w| w.uprsm().set_bit());
+        Ok(())
+    }
+}
+
+//==============================================================================
+// Endpoints
+//==============================================================================
+
+/// State shared by both endpoint directions
+struct Endpoint {
+    info: EndpointInfo,
+    desc: DescriptorsPtr,
+}
+
+impl Endpoint {
+    #[inline]
+    fn index(&self) -> usize {
+        self.info.addr.index()
+    }
+
+    #[inline]
+    fn desc_bank(&mut self) -> &mut DeviceDescBank {
+        let idx = self.index();
+        let bank = bank_index(self.info.addr.direction());
+        // SAFETY: The endpoint owns its bank, and the returned reference
+        // borrows the endpoint
+        unsafe { self.desc.bank(idx, bank) }
+    }
+
+    fn is_enabled(&self) -> bool {
+        let cfg = endpoint(self.index()).epcfg().read();
+        match self.info.addr.direction() {
+            Direction::Out => cfg.eptype0().bits() != 0,
+            Direction::In => cfg.eptype1().bits() != 0,
+        }
+    }
+
+    async fn wait_enabled(&mut self) {
+        let idx = self.index();
+        let waker = match self.info.addr.direction() {
+            Direction::Out => &OUT_WAKERS[idx],
+            Direction::In => &IN_WAKERS[idx],
+        };
+        poll_fn(|cx| {
+            waker.register(cx.waker());
+            if self.is_enabled() {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        })
+        .await
+    }
+}
+
+/// OUT endpoint, implementing [`embassy_usb_driver::EndpointOut`]
+pub struct EndpointOut(Endpoint);
+
+/// IN endpoint, implementing [`embassy_usb_driver::EndpointIn`]
+pub struct EndpointIn(Endpoint);
+
+impl usb_driver::Endpoint for EndpointOut {
+    fn info(&self) -> &EndpointInfo {
+        &self.0.info
+    }
+
+    async fn wait_enabled(&mut self) {
+        self.0.wait_enabled().await
+    }
+}
+
+impl usb_driver::Endpoint for EndpointIn {
+    fn info(&self) -> &EndpointInfo {
+        &self.0.info
+    }
+
+    async fn wait_enabled(&mut self) {
+        self.0.wait_enabled().await
+    }
+}
+
+impl usb_driver::EndpointOut for EndpointOut {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, EndpointError> {
+        let idx = self.0.index();
+        let ep = endpoint(idx);
+
+        poll_fn(|cx| {
+            OUT_WAKERS[idx].register(cx.waker());
+            if !self.0.is_enabled() {
+                return Poll::Ready(Err(EndpointError::Disabled));
+            }
+            if ep.epstatus().read().bk0rdy().bit_is_set() {
+                return Poll::Ready(Ok(()));
+            }
+            ep.epintenset().write(|w| w.trcpt0().set_bit());
+            Poll::Pending
+        })
+        .await?;
+
+        read_bank0(idx, self.0.desc_bank(), buf)
+    }
+}
+
+impl usb_driver::EndpointIn for EndpointIn {
+    async fn write(&mut self, buf: &[u8]) -> Result<(), EndpointError> {
+        if buf.len() > self.0.info.max_packet_size as usize {
+            return Err(EndpointError::BufferOverflow);
+        }
+
+        let idx = self.0.index();
+        let ep = endpoint(idx);
+
+        // Wait for the bank to be free
+        poll_fn(|cx| {
+            IN_WAKERS[idx].register(cx.waker());
+            if !self.0.is_enabled() {
+                return Poll::Ready(Err(EndpointError::Disabled));
+            }
+            if ep.epstatus().read().bk1rdy().bit_is_clear() {
+                return Poll::Ready(Ok(()));
+            }
+            ep.epintenset().write(|w| w.trcpt1().set_bit());
+            Poll::Pending
+        })
+        .await?;
+
+        write_bank1(idx, self.0.desc_bank(), buf);
+        Ok(())
+    }
+}
+
+/// Copy the packet held by bank 0 of endpoint `idx` into `buf`, and release
+/// the bank
+fn read_bank0(
+    idx: usize,
+    desc: &mut DeviceDescBank,
+    buf: &mut [u8],
+) -> Result<usize, EndpointError> {
+    let ep = endpoint(idx);
+    let size = desc.get_byte_count() as usize;
+    let result = if size > buf.len() {
+        Err(EndpointError::BufferOverflow)
+    } else {
+        unsafe {
+            desc.get_address()
+                .copy_to_nonoverlapping(buf.as_mut_ptr(), size);
+        }
+        Ok(size)
+    };
+
+    desc.set_byte_count(0);
+    desc.set_multi_packet_size(0);
+    ep.epintflag()
+        .write(|w| w.trcpt0().set_bit().trfail0().set_bit());
+    ep.epstatusclr().write(|w| w.bk0rdy().set_bit());
+    result
+}
+
+/// Copy `buf` into bank 1 of endpoint `idx`, and hand it over to the
+/// hardware
+fn write_bank1(idx: usize, desc: &mut DeviceDescBank, buf: &[u8]) {
+    let ep = endpoint(idx);
+    unsafe {
+        buf.as_ptr()
+            .copy_to_nonoverlapping(desc.get_address(), buf.len());
+    }
+    desc.set_multi_packet_size(0);
+    desc.set_byte_count(buf.len() as u16);
+
+    ep.epintflag()
+        .write(|w| w.trcpt1().set_bit().trfail1().set_bit());
+    ep.epstatusset().write(|w| w.bk1rdy().set_bit());
+}
+
+//==============================================================================
+// ControlPipe
+//==============================================================================
+
+/// Control pipe of endpoint 0, implementing
+/// [`embassy_usb_driver::ControlPipe`]
+pub struct ControlPipe {
+    max_packet_size: u16,
+    desc: DescriptorsPtr,
+}
+
+impl ControlPipe {
+    /// Wait until `done` returns true. Fails if a new SETUP packet aborts the
+    /// current control transfer.
+    async fn wait_for(
+        &mut self,
+        interrupts: u8,
+        done: impl Fn() -> bool,
+    ) -> Result<(), EndpointError> {
+        let ep = endpoint(0);
+        poll_fn(|cx| {
+            OUT_WAKERS[0].register(cx.waker());
+            IN_WAKERS[0].register(cx.waker());
+            if ep.epintflag().read().rxstp().bit_is_set() {
+                return Poll::Ready(Err(EndpointError::Disabled));
+            }
+            if done() {
+                return Poll::Ready(Ok(()));
+            }
+            ep.epintenset()
+                .write(|w| unsafe { w.bits(interrupts | RXSTP) });
+            Poll::Pending
+        })
+        .await
+    }
+
+    /// Send a packet, and wait for the host to acknowledge it
+    async fn write_packet(&mut self, data: &[u8]) -> Result<(), EndpointError> {
+        // SAFETY: Endpoint 0 is owned by the control pipe
+        write_bank1(0, unsafe { self.desc.bank(0, 1) }, data);
+        self.wait_for(TRCPT1, || {
+            endpoint(0).epintflag().read().trcpt1().bit_is_set()
+        })
+        .await?;
+        endpoint(0).epintflag().write(|w| w.trcpt1().set_bit());
+        Ok(())
+    }
+}
+
+impl usb_driver::ControlPipe for ControlPipe {
+    fn max_packet_size(&self) -> usize {
+        self.max_packet_size as usize
+    }
+
+    async fn setup(&mut self) -> [u8; 8] {
+        let ep = endpoint(0);
+        poll_fn(|cx| {
+            OUT_WAKERS[0].register(cx.waker());
+            if ep.epintflag().read().rxstp().bit_is_set() {
+                return Poll::Ready(());
+            }
+            ep.epintenset().write(|w| w.rxstp().set_bit());
+            Poll::Pending
+        })
+        .await;
+
+        let mut setup = [0; 8];
+        // SAFETY: Endpoint 0 is owned by the control pipe
+        let desc = unsafe { self.desc.bank(0, 0) };
+        unsafe {
+            desc.get_address()
+                .copy_to_nonoverlapping(setup.as_mut_ptr(), setup.len());
+        }
+        desc.set_byte_count(0);
+        desc.set_multi_packet_size(0);
+
+        // The RXSTP flag is only cleared once the packet has been copied, as
+        // the hardware could otherwise overwrite it - see SAMD5x datasheet
+        // "38.6.2.6 Management of SETUP Transactions". The previous control
+        // transfer, if any, is abandoned.
+        ep.epintflag()
+            .write(|w| unsafe { w.bits(RXSTP | TRCPT0 | TRFAIL0 | TRCPT1 | TRFAIL1) });
+        ep.epstatusclr().write(|w| {
+            w.bk0rdy().set_bit();
+            w.bk1rdy().set_bit();
+            w.stallrq0().set_bit();
+            w.stallrq1().set_bit()
+        });
+        setup
+    }
+
+    async fn data_out(
+        &mut self,
+        buf: &mut [u8],
+        _first: bool,
+        _last: bool,
+    ) -> Result<usize, EndpointError> {
+        self.wait_for(TRCPT0, || {
+            endpoint(0).epstatus().read().bk0rdy().bit_is_set()
+        })
+        .await?;
+        // SAFETY: Endpoint 0 is owned by the control pipe
+        read_bank0(0, unsafe { self.desc.bank(0, 0) }, buf)
+    }
+
+    async fn data_in(
+        &mut self,
+        data: &[u8],
+        _first: bool,
+        _last: bool,
+    ) -> Result<(), EndpointError> {
+        if data.len() > self.max_packet_size as usize {
+            return Err(EndpointError::BufferOverflow);
+        }
+        // The OUT status stage is received in bank 0, which was released
+        // when the SETUP packet was read.
+        self.write_packet(data).await
+    }
+
+    async fn accept(&mut self) {
+        // Status stage: send a zero-length packet
+        let _ = self.write_packet(&[]).await;
+    }
+
+    async fn reject(&mut self) {
+        endpoint(0)
+            .epstatusset()
+            .write(|w| w.stallrq0().set_bit().stallrq1().set_bit());
+    }
+
+    async fn accept_set_address(&mut self, addr: u8) {
+        // The new address only applies after the status stage
+        self.accept().await;
+        usb()
+            .dadd()
+            .write(|w| unsafe { w.dadd().bits(addr).adden().set_bit() });
+    }
+}
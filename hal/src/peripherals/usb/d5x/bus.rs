@@ -5,6 +5,7 @@
 // people doing that should be familiar with the USB standard. http://ww1.microchip.com/downloads/en/DeviceDoc/60001507E.pdf
 // http://ww1.microchip.com/downloads/en/AppNotes/Atmel-42261-SAM-D21-USB_Application-Note_AT06475.pdf
 
+use super::devicedesc::DeviceDescBank;
 use super::Descriptors;
 use crate::calibration::{usb_transn_cal, usb_transp_cal, usb_trim_cal};
 use crate::clock;
@@ -12,7 +13,6 @@ use crate::gpio::{AlternateH, AnyPin, Pin, PA24, PA25};
 use crate::pac;
 use crate::pac::usb::Device;
 use crate::pac::{Mclk, Usb};
-use crate::usb::devicedesc::DeviceDescBank;
 use core::cell::{Ref, RefCell, RefMut};
 use core::marker::PhantomData;
 use core::mem;
@@ -152,20 +152,20 @@ fn buffer() -> &'static mut [u8; BUFFER_SIZE] {
     singleton!(: [u8; BUFFER_SIZE] = [0; BUFFER_SIZE] ).unwrap()
 }
 
-struct BufferAllocator {
+pub(super) struct BufferAllocator {
     buffers: &'static mut [u8; BUFFER_SIZE],
     next_buf: u16,
 }
 
 impl BufferAllocator {
-    fn new() -> Self {
+    pub(super) fn new() -> Self {
         Self {
             next_buf: 0,
             buffers: buffer(),
         }
     }
 
-    fn allocate_buffer(&mut self, size: u16) -> UsbResult<*mut u8> {
+    pub(super) fn allocate_buffer(&mut self, size: u16) -> UsbResult<*mut u8> {
         debug_assert!(size & 1 == 0);
 
         let start_addr = &mut self.buffers[self.next_buf as usize] as *mut u8;
@@ -191,6 +191,25 @@ impl BufferAllocator {
     }
 }
 
+/// Size of the buffer to allocate for an endpoint of `max_packet_size`
+/// bytes, or `None` if the hardware doesn't support that packet size.
+pub(super) fn allocated_size(max_packet_size: u16) -> Option<u16> {
+    // The USB hardware encodes the maximum packet size in 3 bits, so
+    // reserve enough buffer that the hardware won't overwrite it even if
+    // the other side issues an overly-long transfer.
+    match max_packet_size {
+        1..=8 => Some(8),
+        9..=16 => Some(16),
+        17..=32 => Some(32),
+        33..=64 => Some(64),
+        65..=128 => Some(128),
+        129..=256 => Some(256),
+        257..=512 => Some(512),
+        513..=1023 => Some(1024),
+        _ => None,
+    }
+}
+
 struct Inner {
     desc: RefCell<Descriptors>,
     _dm_pad: Pin<PA24, AlternateH>,
@@ -707,20 +726,7 @@ impl Inner {
         max_packet_size: u16,
         interval: u8,
     ) -> UsbResult<EndpointAddress> {
-        // The USB hardware encodes the maximum packet size in 3 bits, so
-        // reserve enough buffer that the hardware won't overwrite it even if
-        // the other side issues an overly-long transfer.
-        let allocated_size = match max_packet_size {
-            1..=8 => 8,
-            9..=16 => 16,
-            17..=32 => 32,
-            33..=64 => 64,
-            65..=128 => 128,
-            129..=256 => 256,
-            257..=512 => 512,
-            513..=1023 => 1024,
-            _ => return Err(UsbError::Unsupported),
-        };
+        let allocated_size = allocated_size(max_packet_size).ok_or(UsbError::Unsupported)?;
 
         let buffer = self.buffers.borrow_mut().allocate_buffer(allocated_size)?;
 
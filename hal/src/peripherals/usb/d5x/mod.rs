@@ -1,5 +1,3 @@
-//! USB Device support
-
 use crate::gpio::{
     pin::{Pin, PA23, PA24, PA25},
     AlternateH,
@@ -13,6 +11,15 @@ pub use self::bus::UsbBus;
 mod devicedesc;
 use self::devicedesc::Descriptors;
 
+pub mod host;
+
+#[cfg(feature = "async")]
+mod async_api;
+#[cfg(feature = "async")]
+pub use self::async_api::*;
+#[cfg(feature = "async")]
+pub use embassy_usb_driver;
+
 /// Default SOF pad
 pub type SofPad = Pin<PA23, AlternateH>;
 
@@ -0,0 +1,660 @@
+//! Host controller interface used by class drivers, and standard USB
+//! requests and descriptors
+
+/// Errors of the USB host
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum HostError {
+    /// No device is connected, or it was disconnected during the transfer
+    Disconnected,
+    /// The device answered with a STALL handshake
+    Stall,
+    /// The device did not complete the transfer in time
+    Timeout,
+    /// The transfer failed, for instance after a CRC error
+    TransferFailed,
+    /// The pipe exceeded its maximum number of retries
+    PipeError,
+    /// The device sent more data than the buffer can hold
+    BufferOverflow,
+    /// All the pipes are in use
+    NoPipe,
+    /// The endpoint is not supported by the host controller
+    Unsupported,
+    /// The device returned a malformed descriptor
+    InvalidDescriptor,
+}
+
+/// Bus speed of the connected device
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Speed {
+    Low,
+    Full,
+}
+
+/// Direction of a transfer, from the point of view of the host
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Direction {
+    /// Host to device
+    Out,
+    /// Device to host
+    In,
+}
+
+/// Transfer type of an endpoint
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TransferType {
+    Control = 0,
+    Isochronous = 1,
+    Bulk = 2,
+    Interrupt = 3,
+}
+
+//==============================================================================
+// Requests
+//==============================================================================
+
+/// Standard request codes
+pub mod request {
+    pub const GET_STATUS: u8 = 0x00;
+    pub const CLEAR_FEATURE: u8 = 0x01;
+    pub const SET_FEATURE: u8 = 0x03;
+    pub const SET_ADDRESS: u8 = 0x05;
+    pub const GET_DESCRIPTOR: u8 = 0x06;
+    pub const GET_CONFIGURATION: u8 = 0x08;
+    pub const SET_CONFIGURATION: u8 = 0x09;
+    pub const SET_INTERFACE: u8 = 0x0B;
+}
+
+/// Standard descriptor types
+pub mod descriptor_type {
+    pub const DEVICE: u8 = 0x01;
+    pub const CONFIGURATION: u8 = 0x02;
+    pub const STRING: u8 = 0x03;
+    pub const INTERFACE: u8 = 0x04;
+    pub const ENDPOINT: u8 = 0x05;
+}
+
+/// `ENDPOINT_HALT` feature selector
+pub const FEATURE_ENDPOINT_HALT: u16 = 0;
+
+/// Recipient of a request
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Recipient {
+    Device = 0,
+    Interface = 1,
+    Endpoint = 2,
+    Other = 3,
+}
+
+/// Type of a request
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RequestType {
+    Standard = 0,
+    Class = 1,
+    Vendor = 2,
+}
+
+/// SETUP packet starting a control transfer
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SetupPacket {
+    pub request_type: u8,
+    pub request: u8,
+    pub value: u16,
+    pub index: u16,
+    pub length: u16,
+}
+
+impl SetupPacket {
+    /// Create a SETUP packet
+    pub const fn new(
+        direction: Direction,
+        request_type: RequestType,
+        recipient: Recipient,
+        request: u8,
+        value: u16,
+        index: u16,
+        length: u16,
+    ) -> Self {
+        let direction = match direction {
+            Direction::Out => 0,
+            Direction::In => 0x80,
+        };
+        Self {
+            request_type: direction | (request_type as u8) << 5 | recipient as u8,
+            request,
+            value,
+            index,
+            length,
+        }
+    }
+
+    /// `GET_DESCRIPTOR` request for `length` bytes of a descriptor
+    pub const fn get_descriptor(descriptor_type: u8, index: u8, length: u16) -> Self {
+        Self::new(
+            Direction::In,
+            RequestType::Standard,
+            Recipient::Device,
+            request::GET_DESCRIPTOR,
+            (descriptor_type as u16) << 8 | index as u16,
+            0,
+            length,
+        )
+    }
+
+    /// `SET_ADDRESS` request
+    pub const fn set_address(address: u8) -> Self {
+        Self::new(
+            Direction::Out,
+            RequestType::Standard,
+            Recipient::Device,
+            request::SET_ADDRESS,
+            address as u16,
+            0,
+            0,
+        )
+    }
+
+    /// `SET_CONFIGURATION` request
+    pub const fn set_configuration(configuration: u8) -> Self {
+        Self::new(
+            Direction::Out,
+            RequestType::Standard,
+            Recipient::Device,
+            request::SET_CONFIGURATION,
+            configuration as u16,
+            0,
+            0,
+        )
+    }
+
+    /// `SET_INTERFACE` request
+    pub const fn set_interface(interface: u8, alternate_setting: u8) -> Self {
+        Self::new(
+            Direction::Out,
+            RequestType::Standard,
+            Recipient::Interface,
+            request::SET_INTERFACE,
+            alternate_setting as u16,
+            interface as u16,
+            0,
+        )
+    }
+
+    /// `CLEAR_FEATURE(ENDPOINT_HALT)` request, clearing a stalled endpoint
+    pub const fn clear_halt(endpoint_address: u8) -> Self {
+        Self::new(
+            Direction::Out,
+            RequestType::Standard,
+            Recipient::Endpoint,
+            request::CLEAR_FEATURE,
+            FEATURE_ENDPOINT_HALT,
+            endpoint_address as u16,
+            0,
+        )
+    }
+
+    /// Direction of the data stage
+    pub const fn direction(&self) -> Direction {
+        if self.request_type & 0x80 != 0 {
+            Direction::In
+        } else {
+            Direction::Out
+        }
+    }
+
+    /// Serialize the packet as sent on the bus
+    pub fn to_bytes(&self) -> [u8; 8] {
+        let value = self.value.to_le_bytes();
+        let index = self.index.to_le_bytes();
+        let length = self.length.to_le_bytes();
+        [
+            self.request_type,
+            self.request,
+            value[0],
+            value[1],
+            index[0],
+            index[1],
+            length[0],
+            length[1],
+        ]
+    }
+}
+
+//==============================================================================
+// Descriptors
+//==============================================================================
+
+#[inline]
+fn le_u16(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+/// Device descriptor
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DeviceDescriptor {
+    pub usb_version: u16,
+    pub class: u8,
+    pub sub_class: u8,
+    pub protocol: u8,
+    pub max_packet_size0: u8,
+    pub vendor_id: u16,
+    pub product_id: u16,
+    pub device_version: u16,
+    pub manufacturer_index: u8,
+    pub product_index: u8,
+    pub serial_number_index: u8,
+    pub num_configurations: u8,
+}
+
+impl DeviceDescriptor {
+    /// Length of a device descriptor
+    pub const LENGTH: usize = 18;
+
+    /// Parse a device descriptor
+    pub fn parse(bytes: &[u8]) -> Result<Self, HostError> {
+        if bytes.len() < Self::LENGTH || bytes[1] != descriptor_type::DEVICE {
+            return Err(HostError::InvalidDescriptor);
+        }
+        Ok(Self {
+            usb_version: le_u16(bytes, 2),
+            class: bytes[4],
+            sub_class: bytes[5],
+            protocol: bytes[6],
+            max_packet_size0: bytes[7],
+            vendor_id: le_u16(bytes, 8),
+            product_id: le_u16(bytes, 10),
+            device_version: le_u16(bytes, 12),
+            manufacturer_index: bytes[14],
+            product_index: bytes[15],
+            serial_number_index: bytes[16],
+            num_configurations: bytes[17],
+        })
+    }
+}
+
+/// Header of a configuration descriptor
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ConfigurationDescriptor {
+    /// Length of the configuration descriptor, including the interface and
+    /// endpoint descriptors that follow it
+    pub total_length: u16,
+    pub num_interfaces: u8,
+    pub configuration_value: u8,
+    pub attributes: u8,
+    pub max_power: u8,
+}
+
+impl ConfigurationDescriptor {
+    /// Length of a configuration descriptor header
+    pub const LENGTH: usize = 9;
+
+    /// Parse a configuration descriptor header
+    pub fn parse(bytes: &[u8]) -> Result<Self, HostError> {
+        if bytes.len() < Self::LENGTH || bytes[1] != descriptor_type::CONFIGURATION {
+            return Err(HostError::InvalidDescriptor);
+        }
+        Ok(Self {
+            total_length: le_u16(bytes, 2),
+            num_interfaces: bytes[4],
+            configuration_value: bytes[5],
+            attributes: bytes[7],
+            max_power: bytes[8],
+        })
+    }
+}
+
+/// Interface descriptor
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct InterfaceDescriptor {
+    pub interface_number: u8,
+    pub alternate_setting: u8,
+    pub num_endpoints: u8,
+    pub class: u8,
+    pub sub_class: u8,
+    pub protocol: u8,
+}
+
+/// Endpoint descriptor
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct EndpointDescriptor {
+    /// Endpoint address, including the direction bit
+    pub address: u8,
+    pub attributes: u8,
+    pub max_packet_size: u16,
+    pub interval: u8,
+}
+
+impl EndpointDescriptor {
+    /// Endpoint number
+    pub fn number(&self) -> u8 {
+        self.address & 0x0F
+    }
+
+    /// Direction of the endpoint
+    pub fn direction(&self) -> Direction {
+        if self.address & 0x80 != 0 {
+            Direction::In
+        } else {
+            Direction::Out
+        }
+    }
+
+    /// Transfer type of the endpoint
+    pub fn transfer_type(&self) -> TransferType {
+        match self.attributes & 0x03 {
+            0 => TransferType::Control,
+            1 => TransferType::Isochronous,
+            2 => TransferType::Bulk,
+            _ => TransferType::Interrupt,
+        }
+    }
+}
+
+/// Descriptor found in a configuration descriptor
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Descriptor<'a> {
+    Configuration(ConfigurationDescriptor),
+    Interface(InterfaceDescriptor),
+    Endpoint(EndpointDescriptor),
+    /// Any other descriptor, such as class-specific ones, as raw bytes
+    Other(&'a [u8]),
+}
+
+/// Iterator over the descriptors held by a configuration descriptor
+///
+/// The iteration stops at the first malformed descriptor.
+#[derive(Clone)]
+pub struct Descriptors<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Descriptors<'a> {
+    /// Iterate over the descriptors held by `bytes`
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+}
+
+impl<'a> Iterator for Descriptors<'a> {
+    type Item = Descriptor<'a>;
+
+    fn next(&mut self) -> Option<Descriptor<'a>> {
+        let length = *self.bytes.first()? as usize;
+        if length < 2 || length > self.bytes.len() {
+            self.bytes = &[];
+            return None;
+        }
+        let (bytes, rest) = self.bytes.split_at(length);
+        self.bytes = rest;
+
+        let descriptor = match bytes[1] {
+            descriptor_type::CONFIGURATION => ConfigurationDescriptor::parse(bytes)
+                .map(Descriptor::Configuration)
+                .unwrap_or(Descriptor::Other(bytes)),
+            descriptor_type::INTERFACE if length >= 9 => {
+                Descriptor::Interface(InterfaceDescriptor {
+                    interface_number: bytes[2],
+                    alternate_setting: bytes[3],
+                    num_endpoints: bytes[4],
+                    class: bytes[5],
+                    sub_class: bytes[6],
+                    protocol: bytes[7],
+                })
+            }
+            descriptor_type::ENDPOINT if length >= 7 => Descriptor::Endpoint(EndpointDescriptor {
+                address: bytes[2],
+                attributes: bytes[3],
+                max_packet_size: le_u16(bytes, 4) & 0x7FF,
+                interval: bytes[6],
+            }),
+            _ => Descriptor::Other(bytes),
+        };
+        Some(descriptor)
+    }
+}
+
+//==============================================================================
+// HostController
+//==============================================================================
+
+/// Device enumerated by the host
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Device {
+    /// Address assigned to the device
+    pub address: u8,
+    pub speed: Speed,
+    pub descriptor: DeviceDescriptor,
+}
+
+/// Pipe to a device endpoint, allocated by
+/// [`HostController::alloc_pipe`]
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Pipe {
+    pub(crate) index: u8,
+    pub(crate) endpoint: EndpointDescriptor,
+    pub(crate) in_flight: bool,
+}
+
+impl Pipe {
+    /// Endpoint the pipe is connected to
+    pub fn endpoint(&self) -> &EndpointDescriptor {
+        &self.endpoint
+    }
+}
+
+/// Transfers offered by a USB host controller to class drivers
+pub trait HostController {
+    /// Perform a control transfer on endpoint 0 of `device`, whose data
+    /// stage (if any) goes from the device to `buf`. Returns the number of
+    /// bytes received.
+    fn control_in(
+        &mut self,
+        device: &Device,
+        setup: &SetupPacket,
+        buf: &mut [u8],
+    ) -> Result<usize, HostError>;
+
+    /// Perform a control transfer on endpoint 0 of `device`, whose data
+    /// stage (if any) sends `data` to the device.
+    fn control_out(
+        &mut self,
+        device: &Device,
+        setup: &SetupPacket,
+        data: &[u8],
+    ) -> Result<(), HostError>;
+
+    /// Allocate a pipe to a bulk or interrupt endpoint of `device`
+    fn alloc_pipe(
+        &mut self,
+        device: &Device,
+        endpoint: &EndpointDescriptor,
+    ) -> Result<Pipe, HostError>;
+
+    /// Release a pipe
+    fn free_pipe(&mut self, pipe: Pipe);
+
+    /// Receive a transfer from an IN pipe, until a short packet is received
+    /// or `buf` is full. Returns the number of bytes received.
+    fn transfer_in(&mut self, pipe: &mut Pipe, buf: &mut [u8]) -> Result<usize, HostError>;
+
+    /// Send `data` to an OUT pipe. A zero-length packet terminates the
+    /// transfer if its length is a multiple of the packet size and `zlp` is
+    /// set.
+    fn transfer_out(&mut self, pipe: &mut Pipe, data: &[u8], zlp: bool) -> Result<(), HostError>;
+
+    /// Poll an IN pipe for a single packet, without blocking. This is meant
+    /// for interrupt endpoints, which only answer when they have data.
+    fn poll_in(&mut self, pipe: &mut Pipe, buf: &mut [u8]) -> nb::Result<usize, HostError>;
+
+    /// Reset the data toggle of a pipe, after its endpoint was reset by the
+    /// device
+    fn reset_data_toggle(&mut self, pipe: &mut Pipe);
+
+    /// Read `buf.len()` bytes of a descriptor of `device`
+    fn get_descriptor(
+        &mut self,
+        device: &Device,
+        descriptor_type: u8,
+        index: u8,
+        buf: &mut [u8],
+    ) -> Result<usize, HostError> {
+        let length = buf.len().try_into().unwrap_or(u16::MAX);
+        let setup = SetupPacket::get_descriptor(descriptor_type, index, length);
+        self.control_in(device, &setup, buf)
+    }
+
+    /// Clear the halt condition of a stalled endpoint, and reset the data
+    /// toggle of its pipe
+    fn clear_halt(&mut self, device: &Device, pipe: &mut Pipe) -> Result<(), HostError> {
+        self.control_out(device, &SetupPacket::clear_halt(pipe.endpoint.address), &[])?;
+        self.reset_data_toggle(pipe);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Device descriptor of a full-speed device with a 64-byte control pipe
+    const DEVICE: [u8; 18] = [
+        18, 0x01, 0x00, 0x02, 0xFF, 0x00, 0x00, 64, 0x34, 0x12, 0x78, 0x56, 0x00, 0x01, 1, 2, 3, 1,
+    ];
+
+    /// Configuration with a vendor interface, a class-specific descriptor and
+    /// a bulk IN and an interrupt OUT endpoint
+    const CONFIGURATION: [u8; 37] = [
+        9, 0x02, 37, 0, 1, 1, 0, 0x80, 50, // configuration
+        9, 0x04, 0, 0, 2, 0xFF, 0x01, 0x02, 0, // interface
+        5, 0x24, 0x00, 0x10, 0x01, // class-specific
+        7, 0x05, 0x81, 0x02, 0x40, 0x00, 0, // bulk IN 1
+        7, 0x05, 0x02, 0x03, 0x08, 0xF8, 10, // interrupt OUT 2
+    ];
+
+    #[test]
+    fn parse_device_descriptor() {
+        let descriptor = DeviceDescriptor::parse(&DEVICE).unwrap();
+        assert_eq!(descriptor.usb_version, 0x0200);
+        assert_eq!(descriptor.class, 0xFF);
+        assert_eq!(descriptor.max_packet_size0, 64);
+        assert_eq!(descriptor.vendor_id, 0x1234);
+        assert_eq!(descriptor.product_id, 0x5678);
+        assert_eq!(descriptor.device_version, 0x0100);
+        assert_eq!(descriptor.manufacturer_index, 1);
+        assert_eq!(descriptor.product_index, 2);
+        assert_eq!(descriptor.serial_number_index, 3);
+        assert_eq!(descriptor.num_configurations, 1);
+    }
+
+    #[test]
+    fn reject_invalid_device_descriptor() {
+        assert_eq!(
+            DeviceDescriptor::parse(&DEVICE[..8]),
+            Err(HostError::InvalidDescriptor)
+        );
+        let mut wrong_type = DEVICE;
+        wrong_type[1] = descriptor_type::CONFIGURATION;
+        assert_eq!(
+            DeviceDescriptor::parse(&wrong_type),
+            Err(HostError::InvalidDescriptor)
+        );
+    }
+
+    #[test]
+    fn parse_configuration_descriptor() {
+        let descriptor = ConfigurationDescriptor::parse(&CONFIGURATION).unwrap();
+        assert_eq!(descriptor.total_length, 37);
+        assert_eq!(descriptor.num_interfaces, 1);
+        assert_eq!(descriptor.configuration_value, 1);
+        assert_eq!(descriptor.attributes, 0x80);
+        assert_eq!(descriptor.max_power, 50);
+        assert_eq!(
+            ConfigurationDescriptor::parse(&CONFIGURATION[..4]),
+            Err(HostError::InvalidDescriptor)
+        );
+    }
+
+    #[test]
+    fn iterate_configuration() {
+        let mut descriptors = Descriptors::new(&CONFIGURATION);
+        assert!(matches!(
+            descriptors.next(),
+            Some(Descriptor::Configuration(ConfigurationDescriptor {
+                total_length: 37,
+                ..
+            }))
+        ));
+        assert_eq!(
+            descriptors.next(),
+            Some(Descriptor::Interface(InterfaceDescriptor {
+                interface_number: 0,
+                alternate_setting: 0,
+                num_endpoints: 2,
+                class: 0xFF,
+                sub_class: 0x01,
+                protocol: 0x02,
+            }))
+        );
+        assert_eq!(
+            descriptors.next(),
+            Some(Descriptor::Other(&CONFIGURATION[18..23]))
+        );
+
+        let Some(Descriptor::Endpoint(bulk)) = descriptors.next() else {
+            panic!("expected an endpoint descriptor");
+        };
+        assert_eq!(bulk.number(), 1);
+        assert_eq!(bulk.direction(), Direction::In);
+        assert_eq!(bulk.transfer_type(), TransferType::Bulk);
+        assert_eq!(bulk.max_packet_size, 64);
+
+        let Some(Descriptor::Endpoint(interrupt)) = descriptors.next() else {
+            panic!("expected an endpoint descriptor");
+        };
+        assert_eq!(interrupt.number(), 2);
+        assert_eq!(interrupt.direction(), Direction::Out);
+        assert_eq!(interrupt.transfer_type(), TransferType::Interrupt);
+        // The bits above the packet size select additional transactions
+        assert_eq!(interrupt.max_packet_size, 8);
+        assert_eq!(interrupt.interval, 10);
+
+        assert_eq!(descriptors.next(), None);
+    }
+
+    #[test]
+    fn stop_on_malformed_length() {
+        // Zero length, and a length past the end of the buffer
+        for bytes in [&[0u8, 0x04, 0, 0][..], &[9, 0x04, 0, 0][..]] {
+            let mut descriptors = Descriptors::new(bytes);
+            assert_eq!(descriptors.next(), None);
+            assert_eq!(descriptors.next(), None);
+        }
+    }
+
+    #[test]
+    fn serialize_setup_packet() {
+        let setup = SetupPacket::get_descriptor(descriptor_type::DEVICE, 0, 18);
+        assert_eq!(setup.direction(), Direction::In);
+        assert_eq!(
+            setup.to_bytes(),
+            [0x80, 0x06, 0x00, 0x01, 0x00, 0x00, 18, 0]
+        );
+
+        let setup = SetupPacket::clear_halt(0x81);
+        assert_eq!(setup.direction(), Direction::Out);
+        assert_eq!(setup.to_bytes(), [0x02, 0x01, 0x00, 0x00, 0x81, 0x00, 0, 0]);
+    }
+}
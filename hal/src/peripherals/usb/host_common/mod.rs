@@ -0,0 +1,4 @@
+//! Parts of the USB host driver shared by the SAMD21 and SAMx5x chips
+
+pub(super) mod class;
+pub(super) mod pipedesc;
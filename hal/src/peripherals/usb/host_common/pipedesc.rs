@@ -0,0 +1,152 @@
+use bitfield::bitfield;
+use core::mem;
+use core::ptr::null_mut;
+
+bitfield! {
+    struct PckSize(u32);
+    impl Debug;
+    pub byte_count, set_byte_count: 13, 0;
+    pub multi_packet_size, set_multi_packet_size: 27, 14;
+    pub size, set_size: 30, 28;
+    pub auto_zlp, set_auto_zlp : 31;
+}
+
+bitfield! {
+    struct CtrlPipe(u16);
+    impl Debug;
+    pub pdaddr, set_pdaddr: 6, 0;
+    pub pepnum, set_pepnum: 11, 8;
+    pub permax, set_permax: 15, 12;
+}
+
+bitfield! {
+    struct StatusPipe(u16);
+    impl Debug;
+    pub dtgler, set_dtgler: 0;
+    pub dapider, set_dapider: 1;
+    pub pider, set_pider: 2;
+    pub touter, set_touter: 3;
+    pub crcer, set_crcer: 4;
+    pub ercnt, set_ercnt: 7, 5;
+}
+
+/// Number of retries of a transaction before the pipe reports an error
+const PIPE_MAX_ERRORS: u16 = 3;
+
+/// Size of the buffer of each pipe, enough for full-speed control, bulk and
+/// interrupt endpoints
+pub const PIPE_BUFFER_SIZE: usize = 64;
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct PipeDescBank {
+    /// pipe data buffer, must be 32-bit aligned
+    addr: *mut u8,
+    pcksize: PckSize,
+    _extreg: u16,
+    _status_bk: u8,
+    _reserved: u8,
+    ctrl_pipe: CtrlPipe,
+    status_pipe: StatusPipe,
+}
+
+impl PipeDescBank {
+    const fn new() -> Self {
+        Self {
+            addr: null_mut(),
+            pcksize: PckSize(0),
+            _extreg: 0,
+            _status_bk: 0,
+            _reserved: 0,
+            ctrl_pipe: CtrlPipe(0),
+            status_pipe: StatusPipe(0),
+        }
+    }
+
+    /// Point the pipe to an endpoint of a device
+    pub fn set_endpoint(&mut self, address: u8, endpoint: u8) {
+        self.ctrl_pipe.set_pdaddr(address.into());
+        self.ctrl_pipe.set_pepnum(endpoint.into());
+        self.ctrl_pipe.set_permax(PIPE_MAX_ERRORS);
+    }
+
+    /// Set the maximum packet size of the endpoint, rounded up to a size
+    /// supported by the hardware
+    pub fn set_endpoint_size(&mut self, size: u16) {
+        let size = match size {
+            0..=8 => 0u32,
+            9..=16 => 1,
+            17..=32 => 2,
+            _ => 3,
+        };
+        self.pcksize.set_size(size);
+    }
+
+    /// Prepare the next transaction. For OUT and SETUP tokens, `size` is the
+    /// number of bytes to send; for IN tokens, it is the number of bytes
+    /// expected.
+    pub fn prepare(&mut self, size: u16, out: bool) {
+        if out {
+            self.pcksize.set_byte_count(size.into());
+            self.pcksize.set_multi_packet_size(0);
+        } else {
+            self.pcksize.set_byte_count(0);
+            self.pcksize.set_multi_packet_size(size.into());
+        }
+        self.status_pipe = StatusPipe(0);
+    }
+
+    /// Number of bytes received by the last IN transaction
+    pub fn byte_count(&self) -> u16 {
+        self.pcksize.byte_count() as u16
+    }
+
+    pub fn set_address(&mut self, address: *mut u8) {
+        self.addr = address;
+    }
+}
+
+#[repr(C)]
+#[repr(align(4))]
+pub struct PipeBuffer(pub [u8; PIPE_BUFFER_SIZE]);
+
+/// Pipe descriptors and buffers used by the host
+pub struct PipeDescriptors {
+    /// Only bank 0 of each pipe is used, as the pipes are single-banked
+    desc: [[PipeDescBank; 2]; 8],
+    buffers: [PipeBuffer; 8],
+}
+
+impl PipeDescriptors {
+    pub const fn new() -> Self {
+        const BANK: PipeDescBank = PipeDescBank::new();
+        const PIPE: [PipeDescBank; 2] = [BANK; 2];
+        const BUFFER: PipeBuffer = PipeBuffer([0; PIPE_BUFFER_SIZE]);
+        Self {
+            desc: [PIPE; 8],
+            buffers: [BUFFER; 8],
+        }
+    }
+
+    /// Point each pipe to its buffer
+    pub fn init(&mut self) {
+        debug_assert_eq!(16, mem::size_of::<PipeDescBank>());
+        for (desc, buffer) in self.desc.iter_mut().zip(self.buffers.iter_mut()) {
+            desc[0].set_address(buffer.0.as_mut_ptr());
+        }
+    }
+
+    pub fn address(&self) -> u32 {
+        &self.desc as *const _ as u32
+    }
+
+    pub fn bank(&mut self, pipe: usize) -> &mut PipeDescBank {
+        &mut self.desc[pipe][0]
+    }
+
+    pub fn buffer(&mut self, pipe: usize) -> &mut [u8; PIPE_BUFFER_SIZE] {
+        &mut self.buffers[pipe].0
+    }
+}
+
+unsafe impl Send for PipeDescBank {}
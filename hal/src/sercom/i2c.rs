@@ -175,9 +175,13 @@
 //! let config = i2c.disable();
 //! ```
 //!
+//! # I2C client
+//!
+//! The [`client`] module provides an [`I2cClient`](client::I2cClient), which
+//! answers transactions initiated by another host on the bus.
+//!
 //! # Non-supported features
 //!
-//! * Slave mode is not supported at this time.
 //! * High-speed mode is not supported.
 //! * 4-wire mode is not supported.
 //! * 32-bit extension mode is not supported (SAMx5x). If you need to transfer
@@ -389,6 +393,8 @@ pub use config::*;
 
 mod impl_ehal;
 
+pub mod client;
+
 #[cfg(feature = "async")]
 mod async_api;
 
@@ -0,0 +1,929 @@
+//! Use the SERCOM peripheral as an I2C client
+//!
+//! In client mode, the SERCOM answers transactions initiated by another host on
+//! the bus. Creating an [`I2cClient`] follows the same steps as an [`I2c`]
+//! host: create a set of [`Pads`], build a client [`Config`] and [`enable`]
+//! it.
+//!
+//! ```no_run
+//! use atsamd_hal::sercom::i2c::{self, client};
+//!
+//! let pads = i2c::Pads::<Sercom0>::new(pins.pa08, pins.pa09);
+//! let mut i2c = client::Config::new(&mclk, sercom, pads, 0x42)
+//!     .run_in_standby(true)
+//!     .enable();
+//! ```
+//!
+//! # Addressing
+//!
+//! The client can answer a single 7-bit or 10-bit address, an address with a
+//! mask of ignored bits, two distinct addresses, or a range of addresses. See
+//! [`AddressMode`]. It can also answer the general call address.
+//!
+//! With 10-bit addressing, the hardware acknowledges the first address byte,
+//! which holds bits 9:8 of the address, without reporting it. When the client
+//! answers several addresses that only differ in these bits, the address of
+//! [`Event::AddressMatch`] is the lowest of them which ends with the received
+//! byte.
+//!
+//! # Events
+//!
+//! Transactions are handled one bus event at a time, by calling
+//! [`I2cClient::read_event`]. This function is non-blocking, so it can be
+//! called from the `SERCOM` interrupt handler after enabling the interrupts
+//! with [`I2cClient::enable_interrupts`]. Each [`Event`] must be answered
+//! before the host can proceed; the client stretches the clock in the
+//! meantime.
+//!
+//! * [`Event::AddressMatch`]: the host addressed the client. Answer with
+//!   [`ack`](I2cClient::ack) or [`nack`](I2cClient::nack).
+//! * [`Event::Received`]: the host wrote a byte. Answer with
+//!   [`ack`](I2cClient::ack) to accept more bytes, or
+//!   [`nack`](I2cClient::nack) to refuse them.
+//! * [`Event::Requested`]: the host reads a byte. Answer with
+//!   [`write_byte`](I2cClient::write_byte).
+//! * [`Event::Stop`]: the host ended the transaction.
+//! * [`Event::QuickCommand`]: the host addressed the client for writing, then
+//!   immediately ended the transaction, as in the SMBus quick command.
+//!
+//! ```no_run
+//! use atsamd_hal::sercom::i2c::client::Event;
+//!
+//! let mut registers = [0u8; 16];
+//! let mut index = 0;
+//! loop {
+//!     match nb::block!(i2c.read_event()) {
+//!         Ok(Event::AddressMatch { .. }) => i2c.ack(),
+//!         Ok(Event::Received(byte)) => {
+//!             registers[index % 16] = byte;
+//!             index += 1;
+//!             i2c.ack();
+//!         }
+//!         Ok(Event::Requested) => {
+//!             i2c.write_byte(registers[index % 16]);
+//!             index += 1;
+//!         }
+//!         Ok(Event::Stop) | Ok(Event::QuickCommand) => index = 0,
+//!         Err(_) => index = 0,
+//!     }
+//! }
+//! ```
+//!
+//! # `async` operation <span class="stab portability" title="Available on crate feature `async` only"><code>async</code></span>
+//!
+//! Bind the corresponding `SERCOM` interrupt source to the client
+//! `InterruptHandler`, then turn the [`I2cClient`] into an `I2cClientFuture`
+//! with `I2cClient::into_future`. Its `wait_event` method returns the next
+//! [`Event`], which is answered with the same methods as above.
+//!
+//! [`I2c`]: super::I2c
+//! [`Pads`]: super::Pads
+//! [`enable`]: Config::enable
+
+use super::{Error, PadSet};
+use crate::pac;
+use crate::sercom::{ApbClkCtrl, Sercom};
+use crate::typelevel::{Is, Sealed};
+use atsamd_hal_macros::hal_cfg;
+use bitflags::bitflags;
+
+#[hal_cfg(any("sercom0-d11", "sercom0-d21"))]
+type DataReg = u8;
+
+#[hal_cfg("sercom0-d5x")]
+type DataReg = u32;
+
+/// `CTRLB.CMD` value to continue the transaction after an (N)ACK
+const CMD_CONTINUE: u8 = 3;
+
+/// `CTRLB.CMD` value to wait for the next START condition
+const CMD_WAIT_START: u8 = 2;
+
+//=============================================================================
+// Flags
+//=============================================================================
+
+bitflags! {
+    /// Interrupt bitflags for I2C client transactions
+    ///
+    /// The binary format of the underlying bits exactly matches the `INTFLAG`
+    /// bits.
+    #[derive(Clone, Copy)]
+    pub struct Flags: u8 {
+        /// Stop received interrupt
+        const PREC = 0x01;
+        /// Address match interrupt
+        const AMATCH = 0x02;
+        /// Data ready interrupt
+        const DRDY = 0x04;
+        /// Error interrupt
+        const ERROR = 0x80;
+    }
+}
+
+//=============================================================================
+// AddressMode
+//=============================================================================
+
+/// Set of addresses answered by the [`I2cClient`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AddressMode {
+    /// Answer `address`, ignoring the address bits set in `mask`
+    Mask { address: u16, mask: u16 },
+    /// Answer either of two addresses
+    Two(u16, u16),
+    /// Answer every address in the inclusive range `low..=high`
+    Range { low: u16, high: u16 },
+}
+
+impl AddressMode {
+    /// Check whether `address` is answered
+    fn matches(&self, address: u16) -> bool {
+        match *self {
+            AddressMode::Mask {
+                address: addr,
+                mask,
+            } => (addr ^ address) & !mask & 0x3ff == 0,
+            AddressMode::Two(first, second) => address == first || address == second,
+            AddressMode::Range { low, high } => (low..=high).contains(&address),
+        }
+    }
+}
+
+/// Decode the address of an address match from the byte held in `DATA`
+///
+/// A 7-bit address is followed by the R/W bit. With 10-bit addressing, the
+/// first address byte `0b11110_A9_A8_0` is acknowledged by the hardware, and
+/// the match is raised by the second byte, which holds bits 7:0 of the
+/// address. Bits 9:8 are then those of the lowest address answered by `mode`
+/// that ends with this byte. A read repeats the first byte with the R/W bit
+/// set after a repeated START, so bits 9:8 are received again, and bits 7:0
+/// are those of the `previous` address.
+fn decode_address(
+    data: u8,
+    ten_bit: bool,
+    direction: Direction,
+    mode: AddressMode,
+    previous: u16,
+) -> u16 {
+    match (ten_bit, direction) {
+        (false, _) => (data >> 1) as u16,
+        (true, Direction::Write) => (0..4)
+            .map(|high| (high << 8) | data as u16)
+            .find(|&address| mode.matches(address))
+            .unwrap_or(data as u16),
+        (true, Direction::Read) => ((data as u16 & 0b110) << 7) | (previous & 0xff),
+    }
+}
+
+/// SCL clock stretching mode
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ClockStretch {
+    /// Stretch SCL before the acknowledge bit. The (N)ACK given when answering
+    /// an [`Event`] applies to the byte that triggered it.
+    #[default]
+    BeforeAck,
+    /// Stretch SCL after the acknowledge bit. The hardware acknowledges each
+    /// byte with the (N)ACK given when answering the previous [`Event`]. This
+    /// mode is required for high-speed transfers.
+    AfterAck,
+}
+
+/// Direction of the transaction, from the point of view of the host
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Direction {
+    /// The host writes to the client
+    Write,
+    /// The host reads from the client
+    Read,
+}
+
+//=============================================================================
+// Event
+//=============================================================================
+
+/// Bus event returned by [`I2cClient::read_event`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Event {
+    /// The host addressed the client
+    ///
+    /// `address` is the received 7-bit or 10-bit address, which is useful
+    /// when the client answers more than one address. Answer with
+    /// [`I2cClient::ack`] or [`I2cClient::nack`].
+    AddressMatch { address: u16, direction: Direction },
+    /// The host wrote a byte
+    ///
+    /// Answer with [`I2cClient::ack`] or [`I2cClient::nack`].
+    Received(u8),
+    /// The host reads a byte
+    ///
+    /// Answer with [`I2cClient::write_byte`].
+    Requested,
+    /// The host sent a STOP condition
+    Stop,
+    /// The host sent a STOP condition right after addressing the client for
+    /// writing, without any data
+    QuickCommand,
+}
+
+//=============================================================================
+// Config
+//=============================================================================
+
+/// A configurable, disabled I2C client
+///
+/// Upon creation, the [`Config`] takes ownership of the [`Sercom`] and resets
+/// it, returning it configured as an I2C client answering a single 7-bit
+/// address.
+///
+/// [`Config`] uses a builder-pattern API to configure the peripheral,
+/// culminating in a call to [`enable`](Config::enable), which consumes the
+/// [`Config`] and returns an enabled [`I2cClient`].
+pub struct Config<P: PadSet> {
+    sercom: P::Sercom,
+    pads: P,
+}
+
+impl<P: PadSet> Config<P> {
+    /// Create a new [`Config`] answering the 7-bit `address`
+    ///
+    /// This function will enable the corresponding APB clock and reset the
+    /// [`Sercom`] peripheral. Unlike in host mode, the client does not generate
+    /// the SCL clock, but the GCLK must still be configured by the user.
+    #[inline]
+    pub fn new(apb_clk_ctrl: &ApbClkCtrl, mut sercom: P::Sercom, pads: P, address: u8) -> Self {
+        sercom.enable_apb_clock(apb_clk_ctrl);
+        let mut config = Self { sercom, pads };
+        config.swrst();
+        config.i2cs().ctrla().modify(|_, w| w.mode().i2c_slave());
+        config.set_address_mode(AddressMode::Mask {
+            address: address as u16,
+            mask: 0,
+        });
+        config
+    }
+
+    #[inline]
+    fn i2cs(&self) -> &pac::sercom0::I2cs {
+        self.sercom.i2cs()
+    }
+
+    #[inline]
+    fn swrst(&mut self) {
+        self.i2cs().ctrla().write(|w| w.swrst().set_bit());
+        while self.i2cs().syncbusy().read().swrst().bit_is_set() {}
+    }
+
+    /// Obtain a reference to the PAC `SERCOM` struct
+    ///
+    /// # Safety
+    ///
+    /// Directly accessing the `SERCOM` could break the invariants of the
+    /// type-level tracking in this module, so it is unsafe.
+    #[inline]
+    pub unsafe fn sercom(&self) -> &P::Sercom {
+        &self.sercom
+    }
+
+    /// Consume the [`Config`], reset the peripheral, and return the [`Sercom`]
+    /// and [`Pads`](super::Pads)
+    #[inline]
+    pub fn free(mut self) -> (P::Sercom, P) {
+        self.swrst();
+        (self.sercom, self.pads)
+    }
+
+    /// Set the addresses answered by the client (builder pattern version)
+    #[inline]
+    pub fn address_mode(mut self, mode: AddressMode) -> Self {
+        self.set_address_mode(mode);
+        self
+    }
+
+    /// Set the addresses answered by the client (setter version)
+    ///
+    /// Addresses are 7-bit wide, unless 10-bit addressing is enabled with
+    /// [`Config::set_ten_bit_addressing`].
+    #[inline]
+    pub fn set_address_mode(&mut self, mode: AddressMode) {
+        let (amode, addr, mask) = match mode {
+            AddressMode::Mask { address, mask } => (0, address, mask),
+            AddressMode::Two(first, second) => (1, first, second),
+            AddressMode::Range { low, high } => (2, high, low),
+        };
+        // Safety: Both address fields are 10 bits wide, and `AMODE` values
+        // 0..=2 are all valid
+        self.i2cs().addr().modify(|_, w| unsafe {
+            w.addr().bits(addr & 0x3ff);
+            w.addrmask().bits(mask & 0x3ff)
+        });
+        self.i2cs()
+            .ctrlb()
+            .modify(|_, w| unsafe { w.amode().bits(amode) });
+    }
+
+    /// Enable 10-bit addressing (builder pattern version)
+    #[inline]
+    pub fn ten_bit_addressing(mut self, set: bool) -> Self {
+        self.set_ten_bit_addressing(set);
+        self
+    }
+
+    /// Enable 10-bit addressing (setter version)
+    #[inline]
+    pub fn set_ten_bit_addressing(&mut self, set: bool) {
+        self.i2cs().addr().modify(|_, w| w.tenbiten().bit(set));
+    }
+
+    /// Answer the general call address `0x00` (builder pattern version)
+    #[inline]
+    pub fn general_call(mut self, set: bool) -> Self {
+        self.set_general_call(set);
+        self
+    }
+
+    /// Answer the general call address `0x00` (setter version)
+    #[inline]
+    pub fn set_general_call(&mut self, set: bool) {
+        self.i2cs().addr().modify(|_, w| w.gencen().bit(set));
+    }
+
+    /// Set the SCL clock stretching mode (builder pattern version)
+    #[inline]
+    pub fn clock_stretch(mut self, mode: ClockStretch) -> Self {
+        self.set_clock_stretch(mode);
+        self
+    }
+
+    /// Set the SCL clock stretching mode (setter version)
+    #[inline]
+    pub fn set_clock_stretch(&mut self, mode: ClockStretch) {
+        self.i2cs()
+            .ctrla()
+            .modify(|_, w| w.sclsm().bit(mode == ClockStretch::AfterAck));
+    }
+
+    /// Get the SCL clock stretching mode
+    #[inline]
+    pub fn get_clock_stretch(&self) -> ClockStretch {
+        if self.i2cs().ctrla().read().sclsm().bit() {
+            ClockStretch::AfterAck
+        } else {
+            ClockStretch::BeforeAck
+        }
+    }
+
+    /// Set the SMBus SCL low time-out (builder pattern version)
+    ///
+    /// When set, the client releases SCL and waits for a new START condition
+    /// if SCL is held low for 25-35 ms.
+    #[inline]
+    pub fn low_timeout(mut self, set: bool) -> Self {
+        self.set_low_timeout(set);
+        self
+    }
+
+    /// Set the SMBus SCL low time-out (setter version)
+    ///
+    /// When set, the client releases SCL and waits for a new START condition
+    /// if SCL is held low for 25-35 ms.
+    #[inline]
+    pub fn set_low_timeout(&mut self, set: bool) {
+        self.i2cs().ctrla().modify(|_, w| w.lowtouten().bit(set));
+    }
+
+    /// Set the SMBus cumulative clock low extend time-out (builder pattern
+    /// version)
+    ///
+    /// When set, the client releases SCL if the cumulative time it stretched
+    /// the clock during a message exceeds 25 ms.
+    #[inline]
+    pub fn extend_timeout(mut self, set: bool) -> Self {
+        self.set_extend_timeout(set);
+        self
+    }
+
+    /// Set the SMBus cumulative clock low extend time-out (setter version)
+    ///
+    /// When set, the client releases SCL if the cumulative time it stretched
+    /// the clock during a message exceeds 25 ms.
+    #[inline]
+    pub fn set_extend_timeout(&mut self, set: bool) {
+        self.i2cs().ctrla().modify(|_, w| w.sexttoen().bit(set));
+    }
+
+    /// Run in standby mode (builder pattern version)
+    ///
+    /// When set, the I2C client will run in standby mode, and an address match
+    /// can wake the chip up.
+    #[inline]
+    pub fn run_in_standby(mut self, set: bool) -> Self {
+        self.set_run_in_standby(set);
+        self
+    }
+
+    /// Run in standby mode (setter version)
+    #[inline]
+    pub fn set_run_in_standby(&mut self, set: bool) {
+        self.i2cs().ctrla().modify(|_, w| w.runstdby().bit(set));
+    }
+
+    /// Get the current run in standby mode
+    #[inline]
+    pub fn get_run_in_standby(&self) -> bool {
+        self.i2cs().ctrla().read().runstdby().bit()
+    }
+
+    #[inline]
+    fn enable_peripheral(&mut self, enable: bool) {
+        self.i2cs().ctrla().modify(|_, w| w.enable().bit(enable));
+        while self.i2cs().syncbusy().read().enable().bit_is_set() {}
+    }
+
+    /// Enable the I2C client
+    #[inline]
+    pub fn enable(mut self) -> I2cClient<Self> {
+        self.enable_peripheral(true);
+        I2cClient {
+            config: self,
+            quick_command: false,
+            address: 0,
+        }
+    }
+}
+
+//=============================================================================
+// AnyConfig
+//=============================================================================
+
+/// Type class for all possible client [`Config`] types
+///
+/// This trait uses the [`AnyKind`] trait pattern to create a [type class] for
+/// client [`Config`] types. See the [`AnyKind`] documentation for more details
+/// on the pattern.
+///
+/// [`AnyKind`]: crate::typelevel#anykind-trait-pattern
+/// [type class]: crate::typelevel#type-classes
+pub trait AnyConfig: Is<Type = SpecificConfig<Self>> {
+    type Sercom: Sercom;
+    type Pads: PadSet<Sercom = Self::Sercom>;
+}
+
+/// Type alias to recover the specific client [`Config`] type from an
+/// implementation of [`AnyConfig`]
+pub type SpecificConfig<C> = Config<<C as AnyConfig>::Pads>;
+
+impl<P: PadSet> Sealed for Config<P> {}
+
+impl<P: PadSet> AnyConfig for Config<P> {
+    type Sercom = P::Sercom;
+    type Pads = P;
+}
+
+impl<P: PadSet> AsRef<Self> for Config<P> {
+    #[inline]
+    fn as_ref(&self) -> &Self {
+        self
+    }
+}
+
+impl<P: PadSet> AsMut<Self> for Config<P> {
+    #[inline]
+    fn as_mut(&mut self) -> &mut Self {
+        self
+    }
+}
+
+//=============================================================================
+// I2cClient
+//=============================================================================
+
+/// An enabled I2C client
+///
+/// See the [module-level documentation](self) for details.
+pub struct I2cClient<C: AnyConfig> {
+    config: C,
+    /// Set after a write address match, cleared when data is received
+    quick_command: bool,
+    /// Last matched address, which completes a 10-bit read address
+    address: u16,
+}
+
+impl<C: AnyConfig> I2cClient<C> {
+    #[inline]
+    fn i2cs(&self) -> &pac::sercom0::I2cs {
+        self.config.as_ref().i2cs()
+    }
+
+    /// Read the interrupt flags
+    #[inline]
+    pub fn read_flags(&self) -> Flags {
+        Flags::from_bits_truncate(self.i2cs().intflag().read().bits())
+    }
+
+    /// Clear interrupt flags
+    #[inline]
+    pub fn clear_flags(&mut self, flags: Flags) {
+        self.i2cs()
+            .intflag()
+            .write(|w| unsafe { w.bits(flags.bits()) });
+    }
+
+    /// Enable interrupts for the specified flags
+    #[inline]
+    pub fn enable_interrupts(&mut self, flags: Flags) {
+        self.i2cs()
+            .intenset()
+            .write(|w| unsafe { w.bits(flags.bits()) });
+    }
+
+    /// Disable interrupts for the specified flags
+    #[inline]
+    pub fn disable_interrupts(&mut self, flags: Flags) {
+        self.i2cs()
+            .intenclr()
+            .write(|w| unsafe { w.bits(flags.bits()) });
+    }
+
+    /// Read and clear the error bits of the `STATUS` register
+    #[inline]
+    fn take_error(&mut self) -> Error {
+        let status = self.i2cs().status().read();
+        let error = if status.buserr().bit() {
+            Error::BusError
+        } else if status.coll().bit() {
+            Error::ArbitrationLost
+        } else {
+            Error::Timeout
+        };
+        self.i2cs().status().write(|w| {
+            w.buserr().set_bit();
+            w.coll().set_bit();
+            w.lowtout().set_bit();
+            w.sexttout().set_bit()
+        });
+        error
+    }
+
+    #[inline]
+    #[allow(clippy::unnecessary_cast)]
+    fn read_data(&self) -> u8 {
+        // SAMx5x: u32 -> u8 conversion is fine, as the client never enables
+        // 32-bit data
+        self.i2cs().data().read().bits() as u8
+    }
+
+    /// Read back the [`AddressMode`] set in the `ADDR` and `CTRLB` registers
+    #[inline]
+    fn address_mode(&self) -> AddressMode {
+        let addr = self.i2cs().addr().read();
+        let (first, second) = (addr.addr().bits(), addr.addrmask().bits());
+        match self.i2cs().ctrlb().read().amode().bits() {
+            0 => AddressMode::Mask {
+                address: first,
+                mask: second,
+            },
+            1 => AddressMode::Two(first, second),
+            _ => AddressMode::Range {
+                low: second,
+                high: first,
+            },
+        }
+    }
+
+    #[inline]
+    fn command(&mut self, nack: bool, cmd: u8) {
+        // Safety: `CMD_CONTINUE` and `CMD_WAIT_START` are valid client
+        // commands
+        self.i2cs().ctrlb().modify(|_, w| unsafe {
+            w.ackact().bit(nack);
+            w.cmd().bits(cmd)
+        });
+    }
+
+    /// Read the next bus [`Event`]
+    ///
+    /// Returns [`nb::Error::WouldBlock`] when no event is pending. An error
+    /// aborts the current transaction; the client then waits for the next
+    /// START condition.
+    #[inline]
+    pub fn read_event(&mut self) -> nb::Result<Event, Error> {
+        let flags = self.read_flags();
+        if flags.contains(Flags::ERROR) {
+            self.clear_flags(Flags::ERROR);
+            self.quick_command = false;
+            return Err(nb::Error::Other(self.take_error()));
+        }
+        if flags.contains(Flags::PREC) {
+            self.clear_flags(Flags::PREC);
+            let quick_command = core::mem::take(&mut self.quick_command);
+            return Ok(if quick_command {
+                Event::QuickCommand
+            } else {
+                Event::Stop
+            });
+        }
+        let status = self.i2cs().status().read();
+        let direction = if status.dir().bit() {
+            Direction::Read
+        } else {
+            Direction::Write
+        };
+        if flags.contains(Flags::AMATCH) {
+            let address = decode_address(
+                self.read_data(),
+                self.i2cs().addr().read().tenbiten().bit(),
+                direction,
+                self.address_mode(),
+                self.address,
+            );
+            self.address = address;
+            self.quick_command = direction == Direction::Write;
+            return Ok(Event::AddressMatch { address, direction });
+        }
+        if flags.contains(Flags::DRDY) {
+            self.quick_command = false;
+            return match direction {
+                Direction::Write => Ok(Event::Received(self.read_data())),
+                Direction::Read if status.rxnack().bit() => {
+                    // The host does not want any more data; release the bus
+                    // until the STOP or repeated START
+                    self.command(false, CMD_WAIT_START);
+                    Err(nb::Error::WouldBlock)
+                }
+                Direction::Read => Ok(Event::Requested),
+            };
+        }
+        Err(nb::Error::WouldBlock)
+    }
+
+    /// Acknowledge the address or byte of the last [`Event`], and continue the
+    /// transaction
+    #[inline]
+    pub fn ack(&mut self) {
+        self.command(false, CMD_CONTINUE);
+    }
+
+    /// Refuse the address or byte of the last [`Event`], and wait for the next
+    /// START condition
+    #[inline]
+    pub fn nack(&mut self) {
+        self.command(true, CMD_WAIT_START);
+    }
+
+    /// Send a byte in answer to [`Event::Requested`]
+    #[inline]
+    pub fn write_byte(&mut self, byte: u8) {
+        // Safety: Any byte is a valid `DATA` value
+        self.i2cs()
+            .data()
+            .write(|w| unsafe { w.bits(byte as DataReg) });
+    }
+
+    /// Reconfigure the I2C client
+    ///
+    /// Calling this method will temporarily disable the SERCOM peripheral, as
+    /// some registers are enable-protected. This may interrupt any ongoing
+    /// transactions.
+    #[inline]
+    pub fn reconfigure<F>(&mut self, update: F)
+    where
+        F: FnOnce(&mut SpecificConfig<C>),
+    {
+        self.config.as_mut().enable_peripheral(false);
+        update(self.config.as_mut());
+        self.config.as_mut().enable_peripheral(true);
+    }
+
+    /// Disable the I2C client and return the underlying [`Config`]
+    #[inline]
+    pub fn disable(self) -> C {
+        let mut config = self.config;
+        config.as_mut().enable_peripheral(false);
+        config
+    }
+}
+
+impl<P: PadSet> AsRef<Config<P>> for I2cClient<Config<P>> {
+    #[inline]
+    fn as_ref(&self) -> &Config<P> {
+        self.config.as_ref()
+    }
+}
+
+impl<C: AnyConfig> crate::power::RunStandby for I2cClient<C> {
+    #[inline]
+    fn runs_in_standby(&self) -> bool {
+        self.config.as_ref().get_run_in_standby()
+    }
+}
+
+//=============================================================================
+// async
+//=============================================================================
+
+#[cfg(feature = "async")]
+mod async_api {
+    use core::marker::PhantomData;
+    use core::task::Poll;
+
+    use super::*;
+    use crate::async_hal::interrupts::{Binding, Handler, InterruptSource};
+
+    /// Interrupt handler for async I2C client operations
+    pub struct InterruptHandler<S: Sercom> {
+        _private: (),
+        _sercom: PhantomData<S>,
+    }
+
+    impl<S: Sercom> Sealed for InterruptHandler<S> {}
+
+    impl<S: Sercom> Handler<S::Interrupt> for InterruptHandler<S> {
+        #[inline]
+        unsafe fn on_interrupt() {
+            let mut peripherals = unsafe { crate::pac::Peripherals::steal() };
+            let i2cs = S::reg_block(&mut peripherals).i2cs();
+            let flags_pending = Flags::from_bits_truncate(i2cs.intflag().read().bits());
+
+            // Disable interrupts, but don't clear the flags. The future will
+            // take care of clearing flags and re-enabling interrupts when
+            // woken.
+            if Flags::all().intersects(flags_pending) {
+                i2cs.intenclr()
+                    .write(|w| unsafe { w.bits(flags_pending.bits()) });
+                S::rx_waker().wake();
+            }
+        }
+    }
+
+    impl<C, S> I2cClient<C>
+    where
+        C: AnyConfig<Sercom = S>,
+        S: Sercom,
+    {
+        /// Turn an [`I2cClient`] into an [`I2cClientFuture`]
+        #[inline]
+        pub fn into_future<I>(self, _interrupts: I) -> I2cClientFuture<C>
+        where
+            I: Binding<S::Interrupt, InterruptHandler<S>>,
+        {
+            S::Interrupt::unpend();
+            unsafe { S::Interrupt::enable() };
+
+            I2cClientFuture { client: self }
+        }
+    }
+
+    /// `async` version of [`I2cClient`]
+    ///
+    /// Create this struct by calling [`I2cClient::into_future`].
+    pub struct I2cClientFuture<C: AnyConfig> {
+        client: I2cClient<C>,
+    }
+
+    impl<C, S> I2cClientFuture<C>
+    where
+        C: AnyConfig<Sercom = S>,
+        S: Sercom,
+    {
+        /// Return the underlying [`I2cClient`]
+        #[inline]
+        pub fn free(self) -> I2cClient<C> {
+            self.client
+        }
+
+        async fn wait_flags(&mut self) {
+            core::future::poll_fn(|cx| {
+                if Flags::all().intersects(self.client.read_flags()) {
+                    return Poll::Ready(());
+                }
+
+                // By convention, I2C uses the sercom's RX waker.
+                S::rx_waker().register(cx.waker());
+                self.client.enable_interrupts(Flags::all());
+
+                if Flags::all().intersects(self.client.read_flags()) {
+                    Poll::Ready(())
+                } else {
+                    Poll::Pending
+                }
+            })
+            .await;
+        }
+
+        /// Wait for the next bus [`Event`]
+        ///
+        /// See [`I2cClient::read_event`] for more details.
+        pub async fn wait_event(&mut self) -> Result<Event, Error> {
+            loop {
+                match self.client.read_event() {
+                    Ok(event) => return Ok(event),
+                    Err(nb::Error::Other(error)) => return Err(error),
+                    Err(nb::Error::WouldBlock) => self.wait_flags().await,
+                }
+            }
+        }
+
+        /// Acknowledge the address or byte of the last [`Event`]
+        ///
+        /// See [`I2cClient::ack`].
+        #[inline]
+        pub fn ack(&mut self) {
+            self.client.ack();
+        }
+
+        /// Refuse the address or byte of the last [`Event`]
+        ///
+        /// See [`I2cClient::nack`].
+        #[inline]
+        pub fn nack(&mut self) {
+            self.client.nack();
+        }
+
+        /// Send a byte in answer to [`Event::Requested`]
+        #[inline]
+        pub fn write_byte(&mut self, byte: u8) {
+            self.client.write_byte(byte);
+        }
+    }
+
+    impl<C: AnyConfig> AsRef<I2cClient<C>> for I2cClientFuture<C> {
+        #[inline]
+        fn as_ref(&self) -> &I2cClient<C> {
+            &self.client
+        }
+    }
+
+    impl<C: AnyConfig> AsMut<I2cClient<C>> for I2cClientFuture<C> {
+        #[inline]
+        fn as_mut(&mut self) -> &mut I2cClient<C> {
+            &mut self.client
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+pub use async_api::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seven_bit_address() {
+        let mode = AddressMode::Two(0x42, 0x43);
+        assert_eq!(decode_address(0x86, false, Direction::Write, mode, 0), 0x43);
+        assert_eq!(decode_address(0x85, false, Direction::Read, mode, 0), 0x42);
+    }
+
+    #[test]
+    fn ten_bit_write_address() {
+        let mask = AddressMode::Mask {
+            address: 0x2a5,
+            mask: 0,
+        };
+        assert_eq!(decode_address(0xa5, true, Direction::Write, mask, 0), 0x2a5);
+
+        let two = AddressMode::Two(0x1a5, 0x310);
+        assert_eq!(decode_address(0xa5, true, Direction::Write, two, 0), 0x1a5);
+        assert_eq!(decode_address(0x10, true, Direction::Write, two, 0), 0x310);
+
+        let range = AddressMode::Range {
+            low: 0x1f0,
+            high: 0x20f,
+        };
+        assert_eq!(
+            decode_address(0x05, true, Direction::Write, range, 0),
+            0x205
+        );
+        assert_eq!(
+            decode_address(0xf5, true, Direction::Write, range, 0),
+            0x1f5
+        );
+    }
+
+    #[test]
+    fn ten_bit_read_address() {
+        let mode = AddressMode::Mask {
+            address: 0x000,
+            mask: 0x3ff,
+        };
+        // 0b11110_10_1: bits 9:8 of the address are 0b10
+        assert_eq!(
+            decode_address(0xf5, true, Direction::Read, mode, 0x2a5),
+            0x2a5
+        );
+    }
+
+    #[test]
+    fn mask_ignores_bits() {
+        let mode = AddressMode::Mask {
+            address: 0x40,
+            mask: 0x03,
+        };
+        assert!(mode.matches(0x43));
+        assert!(!mode.matches(0x44));
+    }
+}
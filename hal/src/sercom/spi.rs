@@ -451,8 +451,11 @@ use atsamd_hal_macros::{hal_cfg, hal_docs, hal_macro_helper, hal_module};
 use bitflags::bitflags;
 use num_traits::AsPrimitive;
 
+#[hal_cfg("clock-d5x")]
+use crate::clock::v2::dynamic::Reclock;
 use crate::ehal;
 pub use crate::ehal::spi::{Phase, Polarity, MODE_0, MODE_1, MODE_2, MODE_3};
+use crate::power::RunStandby;
 use crate::sercom::{pad::SomePad, ApbClkCtrl, Sercom};
 use crate::time::Hertz;
 use crate::typelevel::{Is, NoneT, Sealed};
@@ -793,6 +796,7 @@ impl Transmit for Duplex {}
 ///
 /// See the [module-level](super) documentation for more details on declaring
 /// and instantiating `Pads` types.
+#[hal_macro_helper]
 pub struct Config<P, M = Master, Z = DefaultSize>
 where
     P: ValidPads,
@@ -804,6 +808,10 @@ where
     mode: PhantomData<M>,
     size: PhantomData<Z>,
     freq: Hertz,
+    /// Baud rate requested with [`Config::set_baud`], from which the `BAUD`
+    /// register is recomputed when the GCLK frequency changes
+    #[hal_cfg("clock-d5x")]
+    baud: Option<Hertz>,
     nop_word: DataWidth,
 }
 
@@ -826,6 +834,8 @@ impl<P: ValidPads> Config<P> {
             mode: PhantomData,
             size: PhantomData,
             freq: freq.into(),
+            #[hal_cfg("clock-d5x")]
+            baud: None,
             nop_word: 0x00.as_(),
         }
     }
@@ -882,6 +892,7 @@ where
 {
     /// Change the [`OpMode`] or [`Size`]
     #[inline]
+    #[hal_macro_helper]
     fn change<M2, Z2>(self) -> Config<P, M2, Z2>
     where
         M2: OpMode,
@@ -893,6 +904,8 @@ where
             mode: PhantomData,
             size: PhantomData,
             freq: self.freq,
+            #[hal_cfg("clock-d5x")]
+            baud: self.baud,
             nop_word: self.nop_word,
         }
     }
@@ -1085,8 +1098,13 @@ where
     /// half the GCLK frequency. The minimum baud rate is the GCLK frequency /
     /// 512. Values outside this range will saturate at the extremes.
     #[inline]
+    #[hal_macro_helper]
     pub fn set_baud(&mut self, baud: Hertz) {
         self.regs.set_baud(self.freq, baud);
+        #[hal_cfg("clock-d5x")]
+        {
+            self.baud = Some(baud);
+        }
     }
 
     /// Set the baud rate using the builder API
@@ -1206,6 +1224,29 @@ where
     }
 }
 
+/// Keep the requested baud rate when the GCLK frequency changes
+///
+/// The `BAUD` register is recomputed from the baud rate last passed to
+/// [`Config::set_baud`], so repeated frequency changes do not accumulate
+/// rounding errors. The SERCOM must be disabled, so use [`Spi::reconfigure`]
+/// for an enabled [`Spi`], or rely on the [`Reclock`] implementation of
+/// [`Spi`] itself.
+#[hal_cfg("clock-d5x")]
+impl<P, M, Z> Reclock for Config<P, M, Z>
+where
+    P: ValidPads,
+    M: OpMode,
+    Z: Size,
+{
+    #[inline]
+    fn reclock(&mut self, freq: Hertz) {
+        self.freq = freq;
+        if let Some(baud) = self.baud {
+            self.regs.set_baud(freq, baud);
+        }
+    }
+}
+
 //=============================================================================
 // AnyConfig
 //=============================================================================
@@ -1513,6 +1554,32 @@ where
     }
 }
 
+/// Keep the requested baud rate when the GCLK frequency changes
+///
+/// The SERCOM is temporarily disabled, as with [`Spi::reconfigure`].
+#[hal_cfg("clock-d5x")]
+impl<C, A, RxDma, TxDma> Reclock for Spi<C, A, RxDma, TxDma>
+where
+    C: ValidConfig,
+    A: Capability,
+{
+    #[inline]
+    fn reclock(&mut self, freq: Hertz) {
+        self.reconfigure(|config| config.reclock(freq));
+    }
+}
+
+impl<C, A, RxDma, TxDma> RunStandby for Spi<C, A, RxDma, TxDma>
+where
+    C: ValidConfig,
+    A: Capability,
+{
+    #[inline]
+    fn runs_in_standby(&self) -> bool {
+        self.config.as_ref().get_run_in_standby()
+    }
+}
+
 impl<C, D> Spi<C, D>
 where
     C: ValidConfig,
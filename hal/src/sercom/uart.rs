@@ -552,12 +552,16 @@ mod async_api;
 pub use async_api::*;
 
 use crate::{
+    power::RunStandby,
     sercom::pad::SomePad,
     typelevel::{NoneT, Sealed},
 };
 use core::marker::PhantomData;
 use num_traits::AsPrimitive;
 
+#[hal_cfg("clock-d5x")]
+use crate::{clock::v2::dynamic::Reclock, time::Hertz};
+
 /// Size of the SERCOM's `DATA` register
 #[hal_cfg(any("sercom0-d11", "sercom0-d21"))]
 pub type DataReg = u16;
@@ -895,6 +899,32 @@ where
     }
 }
 
+/// Keep the requested baud rate when the GCLK frequency changes
+///
+/// The SERCOM is temporarily disabled, as with [`Uart::reconfigure`].
+#[hal_cfg("clock-d5x")]
+impl<C, D, R, T> Reclock for Uart<C, D, R, T>
+where
+    C: ValidConfig,
+    D: Capability,
+{
+    #[inline]
+    fn reclock(&mut self, freq: Hertz) {
+        self._reconfigure(|config| config.reclock(freq));
+    }
+}
+
+impl<C, D, R, T> RunStandby for Uart<C, D, R, T>
+where
+    C: ValidConfig,
+    D: Capability,
+{
+    #[inline]
+    fn runs_in_standby(&self) -> bool {
+        self.config.as_ref().get_run_in_standby()
+    }
+}
+
 impl<C, D, R, T> Uart<C, D, R, T>
 where
     C: ValidConfig,
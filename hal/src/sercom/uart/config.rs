@@ -1,6 +1,6 @@
 //! UART [`Config`] definition and implementation\
 
-use atsamd_hal_macros::hal_cfg;
+use atsamd_hal_macros::{hal_cfg, hal_macro_helper};
 
 use super::{
     BaudMode, BitOrder, Capability, CharSize, CharSizeEnum, DataReg, DynCharSize, EightBit,
@@ -13,6 +13,9 @@ use crate::{
     typelevel::{Is, NoneT, Sealed},
 };
 use core::marker::PhantomData;
+
+#[hal_cfg("clock-d5x")]
+use crate::clock::v2::dynamic::Reclock;
 use num_traits::{AsPrimitive, PrimInt};
 
 //=============================================================================
@@ -39,6 +42,7 @@ use num_traits::{AsPrimitive, PrimInt};
 ///
 /// [`enable`]: Config::enable
 /// [`Pads`]: super::Pads
+#[hal_macro_helper]
 pub struct Config<P, C = EightBit>
 where
     P: ValidPads,
@@ -48,6 +52,10 @@ where
     pads: P,
     chsize: PhantomData<C>,
     freq: Hertz,
+    /// Baud rate and mode requested with [`Config::set_baud`], from which the
+    /// `BAUD` register is recomputed when the GCLK frequency changes
+    #[hal_cfg("clock-d5x")]
+    baud: Option<(Hertz, BaudMode)>,
 }
 
 /// Clock type needed to create a new [`Config`]. [`Pm`](pac::Pm) for thumbv6m
@@ -84,6 +92,7 @@ impl<P: ValidPads> Config<P> {
 
     /// Create a new [`Config`] in the default configuration
     #[inline]
+    #[hal_macro_helper]
     fn default(sercom: P::Sercom, pads: P, freq: impl Into<Hertz>) -> Self {
         let mut registers = Registers::new(sercom);
         registers.swrst();
@@ -98,6 +107,8 @@ impl<P: ValidPads> Config<P> {
             pads,
             chsize: PhantomData,
             freq: freq.into(),
+            #[hal_cfg("clock-d5x")]
+            baud: None,
         }
     }
 }
@@ -109,6 +120,7 @@ where
 {
     /// Change the [`Config`] [`CharSize`]
     #[inline]
+    #[hal_macro_helper]
     fn change<C2>(self) -> Config<P, C2>
     where
         C2: CharSize,
@@ -118,6 +130,8 @@ where
             pads: self.pads,
             chsize: PhantomData,
             freq: self.freq,
+            #[hal_cfg("clock-d5x")]
+            baud: self.baud,
         }
     }
 
@@ -284,8 +298,13 @@ where
     ///
     /// Note that 3x oversampling is not supported.
     #[inline]
+    #[hal_macro_helper]
     pub fn set_baud(&mut self, baud: Hertz, mode: BaudMode) {
         self.registers.set_baud(self.freq, baud, mode);
+        #[hal_cfg("clock-d5x")]
+        {
+            self.baud = Some((baud, mode));
+        }
     }
 
     /// Get the contents of the `BAUD` register and the current baud mode. Note
@@ -414,6 +433,28 @@ where
     }
 }
 
+/// Keep the requested baud rate when the GCLK frequency changes
+///
+/// The `BAUD` register is recomputed from the baud rate last passed to
+/// [`Config::set_baud`], so repeated frequency changes do not accumulate
+/// rounding errors. The SERCOM must be disabled, so use
+/// [`Uart::reconfigure`] for an enabled [`Uart`], or rely on the [`Reclock`]
+/// implementation of [`Uart`] itself.
+#[hal_cfg("clock-d5x")]
+impl<P, C> Reclock for Config<P, C>
+where
+    P: ValidPads,
+    C: CharSize,
+{
+    #[inline]
+    fn reclock(&mut self, freq: Hertz) {
+        self.freq = freq;
+        if let Some((baud, mode)) = self.baud {
+            self.registers.set_baud(freq, baud, mode);
+        }
+    }
+}
+
 //=============================================================================
 // AnyConfig
 //=============================================================================
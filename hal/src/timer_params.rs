@@ -1,4 +1,6 @@
 //! helper struct to calculate divider & cycles settings for timers.
+use fugit::NanosDurationU64;
+
 use crate::time::{Hertz, Nanoseconds};
 
 /// Helper type for computing cycles and divider given frequency
@@ -17,9 +19,30 @@ impl TimerParams {
 
     /// calculates TimerParams from a given period based timeout.
     pub fn new_ns(timeout: Nanoseconds, src_freq: Hertz) -> Self {
-        let ticks: u32 =
-            (timeout.to_nanos() as u64 * src_freq.to_Hz() as u64 / 1_000_000_000_u64) as u32;
-        Self::new_from_ticks(ticks)
+        Self::new_from_ticks(Self::ticks_ns(timeout, src_freq))
+    }
+
+    /// calculates TimerParams for a 32-bit counter from a given period based
+    /// timeout.
+    ///
+    /// The prescaler is only used when the timeout doesn't fit in a 32-bit
+    /// counter at `src_freq`, so the full resolution of the clock is kept
+    /// whenever possible.
+    pub fn new_ns_32(timeout: NanosDurationU64, src_freq: Hertz) -> Self {
+        let ticks = timeout.ticks() as u128 * src_freq.to_Hz() as u128 / 1_000_000_000_u128;
+        let divider = [1, 2, 4, 8, 16, 64, 256, 1024]
+            .into_iter()
+            .find(|&divider| ticks / divider <= u32::MAX as u128)
+            .unwrap_or_else(|| panic!("ticks {} is out of range for a 32 bit counter", ticks));
+
+        TimerParams {
+            divider: divider as u16,
+            cycles: (ticks / divider) as u32,
+        }
+    }
+
+    fn ticks_ns(timeout: Nanoseconds, src_freq: Hertz) -> u32 {
+        (timeout.to_nanos() as u64 * src_freq.to_Hz() as u64 / 1_000_000_000_u64) as u32
     }
 
     fn new_from_ticks(ticks: u32) -> Self {
@@ -51,7 +74,7 @@ impl TimerParams {
 
 #[cfg(test)]
 mod tests {
-    use crate::fugit::{ExtU32, RateExtU32};
+    use crate::fugit::{ExtU32, NanosDurationU64, RateExtU32};
     use crate::timer_params::TimerParams;
 
     #[test]
@@ -73,4 +96,21 @@ mod tests {
         assert_eq!(tp_from_hz.divider, tp_from_us.divider);
         assert!((tp_from_hz.cycles as i32 - tp_from_us.cycles as i32).abs() <= 1);
     }
+
+    #[test]
+    fn timer_params_32_bit_no_divider() {
+        let tp = TimerParams::new_ns_32(NanosDurationU64::micros(4_000_000), 120.MHz());
+
+        assert_eq!(tp.divider, 1);
+        assert_eq!(tp.cycles, 480_000_000);
+    }
+
+    #[test]
+    fn timer_params_32_bit_long_timeout() {
+        // 60 s at 120 MHz is 7.2e9 cycles, which needs the prescaler
+        let tp = TimerParams::new_ns_32(NanosDurationU64::secs(60), 120.MHz());
+
+        assert_eq!(tp.divider, 2);
+        assert_eq!(tp.cycles, 3_600_000_000);
+    }
 }